@@ -0,0 +1,540 @@
+//! Expansion of the C extension's 16-bit encodings into their 32-bit
+//! equivalents, so they share the regular decoder and executor.
+
+use crate::decode::{
+    Cmjt, Cmmv, Cmpp, IllegalInstruction, Instruction, U10, U12, U2, U3, U4, U5, U7, U9,
+};
+use crate::instruction_ids::*;
+use crate::isa::{Extensions, Xlen};
+
+/// Whether the low parcel of an instruction starts a 16-bit instruction.
+#[inline(always)]
+pub const fn is_compressed(parcel: u16) -> bool {
+    parcel & 0b11 != 0b11
+}
+
+#[inline(always)]
+const fn bits(value: u16, hi: u32, lo: u32) -> u32 {
+    ((value as u32) >> lo) & ((1 << (hi - lo + 1)) - 1)
+}
+
+/// Registers x8-x15 encoded in three bits.
+#[inline(always)]
+const fn creg(value: u16, lo: u32) -> u32 {
+    bits(value, lo + 2, lo) + 8
+}
+
+#[inline(always)]
+const fn sign_extend(value: u32, bits: u32) -> u32 {
+    (((value << (32 - bits)) as i32) >> (32 - bits)) as u32
+}
+
+#[inline(always)]
+const fn r_type(id: U10, rs2: u32, rs1: u32, rd: u32, opcode: U7) -> u32 {
+    ((id.as_u32() >> 3) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | ((id.as_u32() & 0b111) << 12)
+        | (rd << 7)
+        | opcode.as_u32()
+}
+
+#[inline(always)]
+const fn i_type(imm: u32, rs1: u32, funct3: U3, rd: u32, opcode: U7) -> u32 {
+    ((imm & 0xfff) << 20) | (rs1 << 15) | (funct3.as_u32() << 12) | (rd << 7) | opcode.as_u32()
+}
+
+#[inline(always)]
+const fn shift_type(id: U9, shamt: u32, rs1: u32, rd: u32) -> u32 {
+    let prefix = id.as_u32() >> 3;
+    let funct3 = unsafe { U3::new_unchecked((id.as_u32() & 0b111) as u8) };
+    i_type((prefix << 6) | shamt, rs1, funct3, rd, OPCODE_OP_IMM)
+}
+
+/// Zbb unary ops, selected by the immediate of an SLLI.
+#[inline(always)]
+const fn unary(id: U12, reg: u32) -> u32 {
+    i_type(id.as_u32(), reg, UNARY_SLLI, reg, OPCODE_OP_IMM)
+}
+
+#[inline(always)]
+const fn s_type(imm: u32, rs2: u32, rs1: u32, funct3: U3, opcode: U7) -> u32 {
+    (((imm >> 5) & 0x7f) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (funct3.as_u32() << 12)
+        | ((imm & 0x1f) << 7)
+        | opcode.as_u32()
+}
+
+#[inline(always)]
+const fn b_type(imm: u32, rs1: u32, funct3: U3) -> u32 {
+    (((imm >> 12) & 1) << 31)
+        | (((imm >> 5) & 0x3f) << 25)
+        | (rs1 << 15)
+        | (funct3.as_u32() << 12)
+        | (((imm >> 1) & 0xf) << 8)
+        | (((imm >> 11) & 1) << 7)
+        | OPCODE_BRANCH.as_u32()
+}
+
+#[inline(always)]
+const fn j_type(imm: u32, rd: u32) -> u32 {
+    (((imm >> 20) & 1) << 31)
+        | (((imm >> 1) & 0x3ff) << 21)
+        | (((imm >> 11) & 1) << 20)
+        | (((imm >> 12) & 0xff) << 12)
+        | (rd << 7)
+        | OPCODE_JAL.as_u32()
+}
+
+/// CI-format 6-bit signed immediate.
+#[inline(always)]
+const fn ci_imm(value: u16) -> u32 {
+    sign_extend((bits(value, 12, 12) << 5) | bits(value, 6, 2), 6)
+}
+
+/// CI/CB-format 6-bit shift amount.
+#[inline(always)]
+const fn ci_shamt(value: u16) -> u32 {
+    (bits(value, 12, 12) << 5) | bits(value, 6, 2)
+}
+
+/// CJ-format jump offset.
+#[inline(always)]
+const fn cj_imm(value: u16) -> u32 {
+    sign_extend(
+        (bits(value, 12, 12) << 11)
+            | (bits(value, 11, 11) << 4)
+            | (bits(value, 10, 9) << 8)
+            | (bits(value, 8, 8) << 10)
+            | (bits(value, 7, 7) << 6)
+            | (bits(value, 6, 6) << 7)
+            | (bits(value, 5, 3) << 1)
+            | (bits(value, 2, 2) << 5),
+        12,
+    )
+}
+
+/// CB-format branch offset.
+#[inline(always)]
+const fn cb_imm(value: u16) -> u32 {
+    sign_extend(
+        (bits(value, 12, 12) << 8)
+            | (bits(value, 11, 10) << 3)
+            | (bits(value, 6, 5) << 6)
+            | (bits(value, 4, 3) << 1)
+            | (bits(value, 2, 2) << 5),
+        9,
+    )
+}
+
+/// CL/CS-format offset of word accesses.
+#[inline(always)]
+const fn cl_word(value: u16) -> u32 {
+    (bits(value, 12, 10) << 3) | (bits(value, 6, 6) << 2) | (bits(value, 5, 5) << 6)
+}
+
+/// CL/CS-format offset of doubleword accesses.
+#[inline(always)]
+const fn cl_double(value: u16) -> u32 {
+    (bits(value, 12, 10) << 3) | (bits(value, 6, 5) << 6)
+}
+
+/// CI-format offset of stack-relative word loads.
+#[inline(always)]
+const fn ci_lwsp(value: u16) -> u32 {
+    (bits(value, 12, 12) << 5) | (bits(value, 6, 4) << 2) | (bits(value, 3, 2) << 6)
+}
+
+/// CI-format offset of stack-relative doubleword loads.
+#[inline(always)]
+const fn ci_ldsp(value: u16) -> u32 {
+    (bits(value, 12, 12) << 5) | (bits(value, 6, 5) << 3) | (bits(value, 4, 2) << 6)
+}
+
+/// CSS-format offset of stack-relative word stores.
+#[inline(always)]
+const fn css_swsp(value: u16) -> u32 {
+    (bits(value, 12, 9) << 2) | (bits(value, 8, 7) << 6)
+}
+
+/// CSS-format offset of stack-relative doubleword stores.
+#[inline(always)]
+const fn css_sdsp(value: u16) -> u32 {
+    (bits(value, 12, 10) << 3) | (bits(value, 9, 7) << 6)
+}
+
+const SP: u32 = 2;
+const RA: u32 = 1;
+
+/// Expands a 16-bit instruction into the 32-bit instruction it stands for.
+///
+/// Reserved encodings, and those whose meaning is left to custom or future
+/// extensions, are illegal. Floating point encodings, and the Zcb forms of
+/// M, Zba and Zbb instructions, are expanded unconditionally and rejected by
+/// the regular decoder when the matching extension is disabled.
+pub fn expand(
+    encoded: u16,
+    xlen: Xlen,
+    extensions: &Extensions,
+) -> Result<u32, IllegalInstruction> {
+    let illegal = Err(IllegalInstruction(encoded as u32));
+    let rv64 = xlen == Xlen::Rv64;
+    let quadrant = U2::new_truncate(encoded as u8);
+    let funct3 = U3::new_truncate((encoded >> 13) as u8);
+    let rd = bits(encoded, 11, 7);
+    let rs2 = bits(encoded, 6, 2);
+    let rd_ = creg(encoded, 2);
+    let rs1_ = creg(encoded, 7);
+    let expanded = match (quadrant, funct3) {
+        // the all-zero parcel is defined to be illegal
+        (C_Q0, C_ADDI4SPN) => {
+            let imm = (bits(encoded, 12, 11) << 4)
+                | (bits(encoded, 10, 7) << 6)
+                | (bits(encoded, 6, 6) << 2)
+                | (bits(encoded, 5, 5) << 3);
+            if imm == 0 {
+                return illegal;
+            }
+            i_type(imm, SP, ADDI, rd_, OPCODE_OP_IMM)
+        }
+        (C_Q0, C_FLD) if extensions.zcd() => {
+            i_type(cl_double(encoded), rs1_, FLD, rd_, OPCODE_LOAD_FP)
+        }
+        (C_Q0, C_LW) => i_type(cl_word(encoded), rs1_, LW, rd_, OPCODE_LOAD),
+        (C_Q0, C_FLW_LD) if rv64 => i_type(cl_double(encoded), rs1_, LD, rd_, OPCODE_LOAD),
+        (C_Q0, C_FLW_LD) => i_type(cl_word(encoded), rs1_, FLW, rd_, OPCODE_LOAD_FP),
+        (C_Q0, C_FSD) if extensions.zcd() => {
+            s_type(cl_double(encoded), rd_, rs1_, FSD, OPCODE_STORE_FP)
+        }
+        (C_Q0, C_ZCB_MEM) if extensions.zcb => {
+            // bit 6 is offset bit 0 of byte accesses and picks the signed
+            // halfword load
+            let byte = (bits(encoded, 5, 5) << 1) | bits(encoded, 6, 6);
+            let half = bits(encoded, 5, 5) << 1;
+            let signed = bits(encoded, 6, 6) != 0;
+            match U3::new_truncate(bits(encoded, 12, 10) as u8) {
+                C_LBU => i_type(byte, rs1_, LBU, rd_, OPCODE_LOAD),
+                C_LHU_LH if signed => i_type(half, rs1_, LH, rd_, OPCODE_LOAD),
+                C_LHU_LH => i_type(half, rs1_, LHU, rd_, OPCODE_LOAD),
+                C_SB => s_type(byte, rd_, rs1_, SB, OPCODE_STORE),
+                C_SH if !signed => s_type(half, rd_, rs1_, SH, OPCODE_STORE),
+                _ => return illegal,
+            }
+        }
+        (C_Q0, C_SW) => s_type(cl_word(encoded), rd_, rs1_, SW, OPCODE_STORE),
+        (C_Q0, C_FSW_SD) if rv64 => s_type(cl_double(encoded), rd_, rs1_, SD, OPCODE_STORE),
+        (C_Q0, C_FSW_SD) => s_type(cl_word(encoded), rd_, rs1_, FSW, OPCODE_STORE_FP),
+        (C_Q1, C_ADDI) => i_type(ci_imm(encoded), rd, ADDI, rd, OPCODE_OP_IMM),
+        (C_Q1, C_JAL_ADDIW) if rv64 => {
+            if rd == 0 {
+                return illegal;
+            }
+            i_type(ci_imm(encoded), rd, ADDIW, rd, OPCODE_OP_IMM_32)
+        }
+        (C_Q1, C_JAL_ADDIW) => j_type(cj_imm(encoded), RA),
+        (C_Q1, C_LI) => i_type(ci_imm(encoded), 0, ADDI, rd, OPCODE_OP_IMM),
+        (C_Q1, C_LUI_ADDI16SP) if rd == SP => {
+            let imm = sign_extend(
+                (bits(encoded, 12, 12) << 9)
+                    | (bits(encoded, 6, 6) << 4)
+                    | (bits(encoded, 5, 5) << 6)
+                    | (bits(encoded, 4, 3) << 7)
+                    | (bits(encoded, 2, 2) << 5),
+                10,
+            );
+            if imm == 0 {
+                return illegal;
+            }
+            i_type(imm, SP, ADDI, SP, OPCODE_OP_IMM)
+        }
+        (C_Q1, C_LUI_ADDI16SP) => {
+            let imm = ci_imm(encoded);
+            if imm == 0 {
+                return illegal;
+            }
+            (imm << 12) | (rd << 7) | OPCODE_LUI.as_u32()
+        }
+        (C_Q1, C_MISC_ALU) => {
+            let shamt = ci_shamt(encoded);
+            match U2::new_truncate(bits(encoded, 11, 10) as u8) {
+                C_SRLI | C_SRAI if !rv64 && shamt >= 32 => return illegal,
+                C_SRLI => shift_type(SRLI, shamt, rs1_, rs1_),
+                C_SRAI => shift_type(SRAI, shamt, rs1_, rs1_),
+                C_ANDI => i_type(ci_imm(encoded), rs1_, ANDI, rs1_, OPCODE_OP_IMM),
+                _ => {
+                    let op = (bits(encoded, 12, 12) << 2) | bits(encoded, 6, 5);
+                    match U3::new_truncate(op as u8) {
+                        C_SUB => r_type(SUB, rd_, rs1_, rs1_, OPCODE_OP),
+                        C_XOR => r_type(XOR, rd_, rs1_, rs1_, OPCODE_OP),
+                        C_OR => r_type(OR, rd_, rs1_, rs1_, OPCODE_OP),
+                        C_AND => r_type(AND, rd_, rs1_, rs1_, OPCODE_OP),
+                        C_SUBW if rv64 => r_type(SUBW, rd_, rs1_, rs1_, OPCODE_OP_32),
+                        C_ADDW if rv64 => r_type(ADDW, rd_, rs1_, rs1_, OPCODE_OP_32),
+                        C_MUL if extensions.zcb => r_type(MUL, rd_, rs1_, rs1_, OPCODE_OP),
+                        C_UNARY if extensions.zcb => match U3::new_truncate(rs2 as u8 & 0b111) {
+                            C_ZEXT_B => i_type(0xff, rs1_, ANDI, rs1_, OPCODE_OP_IMM),
+                            C_SEXT_B => unary(SEXT_B, rs1_),
+                            C_ZEXT_H if rv64 => r_type(ZEXT_H, 0, rs1_, rs1_, OPCODE_OP_32),
+                            C_ZEXT_H => r_type(ZEXT_H, 0, rs1_, rs1_, OPCODE_OP),
+                            C_SEXT_H => unary(SEXT_H, rs1_),
+                            C_ZEXT_W if rv64 => r_type(ADD_UW, 0, rs1_, rs1_, OPCODE_OP_32),
+                            C_NOT => i_type(0xfff, rs1_, XORI, rs1_, OPCODE_OP_IMM),
+                            _ => return illegal,
+                        },
+                        _ => return illegal,
+                    }
+                }
+            }
+        }
+        (C_Q1, C_J) => j_type(cj_imm(encoded), 0),
+        (C_Q1, C_BEQZ) => b_type(cb_imm(encoded), rs1_, BEQ),
+        (C_Q1, C_BNEZ) => b_type(cb_imm(encoded), rs1_, BNE),
+        (C_Q2, C_SLLI) => {
+            let shamt = ci_shamt(encoded);
+            if !rv64 && shamt >= 32 {
+                return illegal;
+            }
+            shift_type(SLLI, shamt, rd, rd)
+        }
+        (C_Q2, C_FLDSP) if extensions.zcd() => {
+            i_type(ci_ldsp(encoded), SP, FLD, rd, OPCODE_LOAD_FP)
+        }
+        (C_Q2, C_LWSP) => {
+            if rd == 0 {
+                return illegal;
+            }
+            i_type(ci_lwsp(encoded), SP, LW, rd, OPCODE_LOAD)
+        }
+        (C_Q2, C_FLWSP_LDSP) if rv64 => {
+            if rd == 0 {
+                return illegal;
+            }
+            i_type(ci_ldsp(encoded), SP, LD, rd, OPCODE_LOAD)
+        }
+        (C_Q2, C_FLWSP_LDSP) => i_type(ci_lwsp(encoded), SP, FLW, rd, OPCODE_LOAD_FP),
+        (C_Q2, C_JR_MV_ADD) => match (bits(encoded, 12, 12), rd, rs2) {
+            // c.jr
+            (0, 0, 0) => return illegal,
+            (0, _, 0) => i_type(0, rd, JALR, 0, OPCODE_JALR),
+            // c.mv
+            (0, _, _) => r_type(ADD, rs2, 0, rd, OPCODE_OP),
+            // c.ebreak
+            (_, 0, 0) => (EBREAK.as_u32() << 20) | OPCODE_SYSTEM.as_u32(),
+            // c.jalr
+            (_, _, 0) => i_type(0, rd, JALR, RA, OPCODE_JALR),
+            // c.add
+            _ => r_type(ADD, rs2, rd, rd, OPCODE_OP),
+        },
+        (C_Q2, C_FSDSP) if extensions.zcd() => {
+            s_type(css_sdsp(encoded), rs2, SP, FSD, OPCODE_STORE_FP)
+        }
+        (C_Q2, C_SWSP) => s_type(css_swsp(encoded), rs2, SP, SW, OPCODE_STORE),
+        (C_Q2, C_FSWSP_SDSP) if rv64 => s_type(css_sdsp(encoded), rs2, SP, SD, OPCODE_STORE),
+        (C_Q2, C_FSWSP_SDSP) => s_type(css_swsp(encoded), rs2, SP, FSW, OPCODE_STORE_FP),
+        _ => return illegal,
+    };
+    Ok(expanded)
+}
+
+/// Whether a 16-bit instruction lies in the `c.fsdsp` slot, which Zcmp and
+/// Zcmt take over.
+#[inline(always)]
+pub const fn is_cm(parcel: u16) -> bool {
+    U2::new_truncate(parcel as u8).as_u8() == C_Q2.as_u8()
+        && U3::new_truncate((parcel >> 13) as u8).as_u8() == C_FSDSP.as_u8()
+}
+
+/// Decodes the Zcmp and Zcmt instructions of the `c.fsdsp` slot, which stand
+/// for several 32-bit instructions and so have no expansion. `None` for
+/// reserved encodings and those of a disabled extension.
+pub fn decode_cm(encoded: u16, extensions: &Extensions) -> Option<Instruction> {
+    let pp = Cmpp {
+        rlist: U4::new_truncate(bits(encoded, 7, 4) as u8),
+        spimm: U2::new_truncate(bits(encoded, 3, 2) as u8),
+    };
+    let mv = Cmmv {
+        r1s: U3::new_truncate(bits(encoded, 9, 7) as u8),
+        r2s: U3::new_truncate(bits(encoded, 4, 2) as u8),
+    };
+    let jt = Cmjt {
+        index: bits(encoded, 9, 2) as u8,
+    };
+    // rlist values below 4 would leave out ra
+    let zcmp_pp = extensions.zcmp && pp.rlist.as_u8() >= 4;
+    let instruction = match U5::new_truncate(bits(encoded, 12, 8) as u8) {
+        CM_PUSH if zcmp_pp => Instruction::CmPush(pp),
+        CM_POP if zcmp_pp => Instruction::CmPop(pp),
+        CM_POPRETZ if zcmp_pp => Instruction::CmPopretz(pp),
+        CM_POPRET if zcmp_pp => Instruction::CmPopret(pp),
+        _ => match (
+            U3::new_truncate(bits(encoded, 12, 10) as u8),
+            U2::new_truncate(bits(encoded, 6, 5) as u8),
+        ) {
+            (CM_MV, CM_MVSA01) if extensions.zcmp && mv.r1s != mv.r2s => Instruction::CmMvsa01(mv),
+            (CM_MV, CM_MVA01S) if extensions.zcmp => Instruction::CmMva01s(mv),
+            (CM_JT, _) if extensions.zcmt && jt.index < 32 => Instruction::CmJt(jt),
+            (CM_JT, _) if extensions.zcmt => Instruction::CmJalt(jt),
+            _ => return None,
+        },
+    };
+    Some(instruction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_rv32() {
+        for (compressed, expanded) in [
+            (0x1fe8, 0x3fc10513), // addi a0, sp, 1020
+            (0x5d7c, 0x07c52783), // lw a5, 124(a0)
+            (0xc0dc, 0x00f4a223), // sw a5, 4(s1)
+            (0x3de8, 0x0f85b507), // fld fa0, 248(a1)
+            (0xa780, 0x0087b427), // fsd fs0, 8(a5)
+            (0x1501, 0xfe050513), // addi a0, a0, -32
+            (0x42fd, 0x01f00293), // li t0, 31
+            (0x7605, 0xfffe1637), // lui a2, 1048545
+            (0x7101, 0xe0010113), // addi sp, sp, -512
+            (0x807d, 0x01f45413), // srli s0, s0, 31
+            (0x8585, 0x4015d593), // srai a1, a1, 1
+            (0x9afd, 0xfff6f693), // andi a3, a3, -1
+            (0x8c1d, 0x40f40433), // sub s0, s0, a5
+            (0x8c3d, 0x00f44433), // xor s0, s0, a5
+            (0x8c5d, 0x00f46433), // or s0, s0, a5
+            (0x8c7d, 0x00f47433), // and s0, s0, a5
+            (0xb001, 0x801ff06f), // j -2048
+            (0x2ffd, 0x7fe000ef), // jal 2046
+            (0xd101, 0xf00500e3), // beqz a0, -256
+            (0xecfd, 0x0e049f63), // bnez s1, 254
+            (0x00fe, 0x01f09093), // slli ra, ra, 31
+            (0x50fe, 0x0fc12083), // lw ra, 252(sp)
+            (0x34fe, 0x1f813487), // fld fs1, 504(sp)
+            (0x6592, 0x00412587), // flw fa1, 4(sp)
+            (0x8082, 0x00008067), // ret
+            (0x852e, 0x00b00533), // mv a0, a1
+            (0x9002, 0x00100073), // ebreak
+            (0x9282, 0x000280e7), // jalr t0
+            (0x952e, 0x00b50533), // add a0, a0, a1
+            (0xdf86, 0x0e112e23), // sw ra, 252(sp)
+            (0xbfa6, 0x1e913c27), // fsd fs1, 504(sp)
+            (0xe1a8, 0x04a5a027), // fsw fa0, 64(a1)
+            (0x61a8, 0x0405a507), // flw fa0, 64(a1)
+            (0xe22e, 0x00b12227), // fsw fa1, 4(sp)
+            (0x0001, 0x00000013), // nop
+        ] {
+            assert_eq!(
+                expand(compressed, Xlen::Rv32, &Extensions::ALL),
+                Ok(expanded),
+                "{compressed:#06x}"
+            );
+        }
+    }
+
+    #[test]
+    fn expand_rv64() {
+        for (compressed, expanded) in [
+            (0x7de8, 0x0f85b503), // ld a0, 248(a1)
+            (0xe788, 0x00a7b423), // sd a0, 8(a5)
+            (0x357d, 0xfff5051b), // addiw a0, a0, -1
+            (0x9c1d, 0x40f4043b), // subw s0, s0, a5
+            (0x9c3d, 0x00f4043b), // addw s0, s0, a5
+            (0x70fe, 0x1f813083), // ld ra, 504(sp)
+            (0xff86, 0x1e113c23), // sd ra, 504(sp)
+            (0x157e, 0x03f51513), // slli a0, a0, 63
+            (0x9581, 0x4205d593), // srai a1, a1, 32
+            (0x9185, 0x0215d593), // srli a1, a1, 33
+        ] {
+            assert_eq!(
+                expand(compressed, Xlen::Rv64, &Extensions::ALL),
+                Ok(expanded),
+                "{compressed:#06x}"
+            );
+        }
+    }
+
+    #[test]
+    fn expand_reserved() {
+        let c = Extensions {
+            zcb: false,
+            ..Extensions::ALL
+        };
+        for (compressed, xlen) in [
+            (0x0000, Xlen::Rv32), // all zeros
+            (0x0010, Xlen::Rv32), // c.addi4spn with a zero immediate
+            (0x4002, Xlen::Rv32), // c.lwsp x0
+            (0x8002, Xlen::Rv32), // c.jr x0
+            (0x6101, Xlen::Rv32), // c.addi16sp 0
+            (0x6601, Xlen::Rv32), // c.lui a2, 0
+            (0x1502, Xlen::Rv32), // c.slli a0, 32
+            (0x9581, Xlen::Rv32), // c.srai a1, 32
+            (0x9c1d, Xlen::Rv32), // c.subw
+            (0x2001, Xlen::Rv64), // c.addiw x0
+            (0x6002, Xlen::Rv64), // c.ldsp x0
+            (0x9c5d, Xlen::Rv64), // reserved arithmetic without Zcb
+            (0x8000, Xlen::Rv64), // reserved quadrant 0 slot without Zcb
+        ] {
+            assert_eq!(
+                expand(compressed, xlen, &c),
+                Err(IllegalInstruction(compressed as u32)),
+                "{compressed:#06x}"
+            );
+        }
+    }
+
+    #[test]
+    fn expand_zcb() {
+        for (compressed, xlen, expanded) in [
+            (0x81e8, Xlen::Rv32, 0x0035c503), // lbu a0, 3(a1)
+            (0x85a8, Xlen::Rv32, 0x0025d503), // lhu a0, 2(a1)
+            (0x85e8, Xlen::Rv32, 0x00259503), // lh a0, 2(a1)
+            (0x89c8, Xlen::Rv32, 0x00a580a3), // sb a0, 1(a1)
+            (0x8da8, Xlen::Rv32, 0x00a59123), // sh a0, 2(a1)
+            (0x9c61, Xlen::Rv32, 0x0ff47413), // andi s0, s0, 255
+            (0x9c65, Xlen::Rv32, 0x60441413), // sext.b s0, s0
+            (0x9c69, Xlen::Rv32, 0x08044433), // zext.h s0, s0
+            (0x9c6d, Xlen::Rv32, 0x60541413), // sext.h s0, s0
+            (0x9c75, Xlen::Rv32, 0xfff44413), // not s0, s0
+            (0x9c5d, Xlen::Rv32, 0x02f40433), // mul s0, s0, a5
+            (0x9c69, Xlen::Rv64, 0x0804443b), // zext.h s0, s0
+            (0x9c71, Xlen::Rv64, 0x0804043b), // zext.w s0, s0
+        ] {
+            assert_eq!(
+                expand(compressed, xlen, &Extensions::ALL),
+                Ok(expanded),
+                "{compressed:#06x}"
+            );
+        }
+        let zcmp = Extensions {
+            zcmp: true,
+            ..Extensions::ALL
+        };
+        for (compressed, xlen, extensions) in [
+            (0x8de8, Xlen::Rv32, Extensions::ALL), // c.sh with bit 6 set
+            (0x9188, Xlen::Rv32, Extensions::ALL), // reserved quadrant 0 op
+            (0x9c71, Xlen::Rv32, Extensions::ALL), // c.zext.w
+            (0x9c79, Xlen::Rv64, Extensions::ALL), // reserved unary op
+            (0x9c7d, Xlen::Rv64, Extensions::ALL), // reserved unary op
+            // Zcmp replaces the compressed double-precision accesses
+            (0x3de8, Xlen::Rv32, zcmp),
+            (0xa780, Xlen::Rv32, zcmp),
+            (0x34fe, Xlen::Rv32, zcmp),
+            (0xbfa6, Xlen::Rv32, zcmp),
+        ] {
+            assert_eq!(
+                expand(compressed, xlen, &extensions),
+                Err(IllegalInstruction(compressed as u32)),
+                "{compressed:#06x}"
+            );
+        }
+    }
+
+    #[test]
+    fn compressed_length() {
+        assert!(is_compressed(0x0001));
+        assert!(is_compressed(0x8082));
+        assert!(!is_compressed(0x0013));
+    }
+}
@@ -0,0 +1,166 @@
+//! The CSRs the emulator implements: who may access them, which bits writes
+//! change and what reads return.
+
+use crate::isa::{Extensions, Privilege, Xlen};
+use crate::registers::*;
+
+/// What a read of a CSR returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CsrRead {
+    /// The value stored in its own cell.
+    Value,
+    /// The value of another CSR, as the user-level counters show the
+    /// machine-level ones.
+    Alias(usize),
+    /// `frm` and `fflags` packed together.
+    Fcsr,
+    /// `vxrm` and `vxsat` packed together.
+    Vcsr,
+    /// Fresh entropy, consumed by the read.
+    Entropy,
+    /// The XLEN and single-letter extensions of the hart.
+    Misa,
+    /// A trap return address, whose bit 1 reads as zero when IALIGN is 32.
+    Epc,
+    /// The given fields of another CSR, as the supervisor CSRs show the
+    /// machine ones. Writes go to that CSR.
+    View(usize, u64),
+}
+
+/// How an implemented CSR behaves under the Zicsr instructions.
+#[derive(Debug, Clone, Copy)]
+pub struct CsrSpec {
+    /// Least privileged mode that may access it.
+    pub privilege: Privilege,
+    /// Bits that writes change, the others keep their value.
+    pub mask: u64,
+    /// Where reads take the value from.
+    pub read: CsrRead,
+    /// Whether it exists on a hart with this XLEN and these extensions.
+    pub exists: fn(Xlen, &Extensions) -> bool,
+}
+
+const ALL: u64 = u64::MAX;
+
+fn always(_: Xlen, _: &Extensions) -> bool {
+    true
+}
+
+fn rv32(xlen: Xlen, _: &Extensions) -> bool {
+    xlen == Xlen::Rv32
+}
+
+fn float(_: Xlen, extensions: &Extensions) -> bool {
+    extensions.f || extensions.zfinx
+}
+
+fn vector(_: Xlen, extensions: &Extensions) -> bool {
+    extensions.v
+}
+
+fn supervisor(_: Xlen, extensions: &Extensions) -> bool {
+    extensions.s
+}
+
+const fn spec(
+    privilege: Privilege,
+    mask: u64,
+    read: CsrRead,
+    exists: fn(Xlen, &Extensions) -> bool,
+) -> Option<CsrSpec> {
+    Some(CsrSpec {
+        privilege,
+        mask,
+        read,
+        exists,
+    })
+}
+
+impl CsrSpec {
+    /// Looks `reg` up, `None` if the emulator implements no such CSR.
+    pub fn of(reg: usize) -> Option<Self> {
+        use CsrRead::*;
+        use Privilege::*;
+        match reg {
+            FFLAGS => spec(User, 0x1f, Value, float),
+            FRM => spec(User, 0x7, Value, float),
+            FCSR => spec(User, 0xff, Fcsr, float),
+            VSTART => spec(User, ALL, Value, vector),
+            VXSAT => spec(User, 0x1, Value, vector),
+            VXRM => spec(User, 0x3, Value, vector),
+            VCSR => spec(User, 0x7, Vcsr, vector),
+            // mseccfg is not modelled, so only M-mode may access seed
+            SEED => spec(Machine, ALL, Entropy, |_, e| e.zkr),
+            JVT => spec(User, ALL, Value, |_, e| e.zcmt),
+            VL | VTYPE | VLENB => spec(User, 0, Value, vector),
+            TIME => spec(User, 0, Value, |_, e| e.zicntr),
+            TIMEH => spec(User, 0, Value, |x, e| rv32(x, e) && e.zicntr),
+            CYCLE..=INSTRET => spec(User, 0, Alias(reg - CYCLE + MCYCLE), |_, e| e.zicntr),
+            HPMCOUNTER3..=HPMCOUNTER31 => {
+                spec(User, 0, Alias(reg - CYCLE + MCYCLE), |_, e| e.zihpm)
+            }
+            CYCLEH..=INSTRETH => spec(User, 0, Alias(reg - CYCLEH + MCYCLEH), |x, e| {
+                rv32(x, e) && e.zicntr
+            }),
+            HPMCOUNTER3H..=HPMCOUNTER31H => spec(User, 0, Alias(reg - CYCLEH + MCYCLEH), |x, e| {
+                rv32(x, e) && e.zihpm
+            }),
+            SSTATUS => spec(
+                Supervisor,
+                SSTATUS_FIELDS,
+                View(MSTATUS, SSTATUS_FIELDS),
+                supervisor,
+            ),
+            SIE => spec(
+                Supervisor,
+                S_INTERRUPTS,
+                View(MIE, S_INTERRUPTS),
+                supervisor,
+            ),
+            SIP => spec(Supervisor, MIP_SSIP, View(MIP, S_INTERRUPTS), supervisor),
+            STVEC => spec(Supervisor, !0b10, Value, supervisor),
+            SSCRATCH | SCAUSE | STVAL => spec(Supervisor, ALL, Value, supervisor),
+            SEPC => spec(Supervisor, !0b1, Epc, supervisor),
+            SCOUNTEREN => spec(Supervisor, 0xffff_ffff, Value, supervisor),
+            // Writes of unsupported modes are ignored
+            SATP => spec(Supervisor, ALL, Value, supervisor),
+            // Legalized further against the modes misa has
+            MSTATUS => spec(
+                Machine,
+                MSTATUS_MIE
+                    | MSTATUS_MPIE
+                    | MSTATUS_MPP
+                    | SSTATUS_FIELDS
+                    | MSTATUS_MPRV
+                    | MSTATUS_TVM
+                    | MSTATUS_TW
+                    | MSTATUS_TSR,
+                Value,
+                always,
+            ),
+            MSTATUSH => spec(Machine, 0, Value, rv32),
+            MISA => spec(Machine, 0, Misa, always),
+            MIE | MIP => spec(Machine, INTERRUPTS, Value, always),
+            // Environment calls from M-mode stay in M-mode
+            MEDELEG => spec(Machine, 0xb3ff, Value, supervisor),
+            MIDELEG => spec(Machine, S_INTERRUPTS, Value, supervisor),
+            // Modes 2 and 3 are reserved
+            MTVEC => spec(Machine, !0b10, Value, always),
+            MSCRATCH | MCAUSE | MTVAL => spec(Machine, ALL, Value, always),
+            MEPC => spec(Machine, !0b1, Epc, always),
+            MCOUNTEREN => spec(Machine, 0xffff_ffff, Value, always),
+            MCOUNTINHIBIT => spec(Machine, 0xffff_fffd, Value, always),
+            MHPMEVENT3..=MHPMEVENT31 => spec(Machine, ALL, Value, always),
+            MCYCLE | MINSTRET | MHPMCOUNTER3..=MHPMCOUNTER31 => spec(Machine, ALL, Value, always),
+            MCYCLEH | MINSTRETH | MHPMCOUNTER3H..=MHPMCOUNTER31H => spec(Machine, ALL, Value, rv32),
+            MVENDORID | MARCHID | MIMPID | MHARTID => spec(Machine, 0, Value, always),
+            _ => None,
+        }
+    }
+
+    /// Whether writes to `reg` are illegal, as bits 11:10 of its address say.
+    #[inline(always)]
+    pub const fn read_only(reg: usize) -> bool {
+        reg >> 10 & 3 == 3
+    }
+}
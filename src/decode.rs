@@ -1,3 +1,8 @@
+use crate::compressed;
+use crate::instruction_ids::*;
+use crate::isa::{Extensions, Xlen};
+use crate::trap::Exception;
+
 const B12_MASK: u32 = bitmask(12);
 const B7_MASK: u32 = bitmask(7);
 const B6_MASK: u32 = bitmask(6);
@@ -33,8 +38,8 @@ pub struct I {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Shift {
-    pub prefix: U7,
-    pub shamt: U5,
+    pub prefix: U6,
+    pub shamt: U6,
     pub rs1: U5,
     pub funct3: U3,
     pub rd: U5,
@@ -50,6 +55,21 @@ pub struct Fence {
     pub rd: U5,
 }
 
+/// R-type layout of the A extension, with funct7 split into the operation
+/// and its memory ordering bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amo {
+    pub funct5: U5,
+    /// Acquire: later memory operations are not observed before this one.
+    pub aq: bool,
+    /// Release: earlier memory operations are observed before this one.
+    pub rl: bool,
+    pub rs2: U5,
+    pub rs1: U5,
+    pub funct3: U3,
+    pub rd: U5,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct S {
     pub imm: U12,
@@ -78,6 +98,35 @@ pub struct J {
     pub rd: U5,
 }
 
+/// OP-V arithmetic layout. `vs1` holds `rs1` or a 5-bit immediate depending
+/// on the operand category in `funct3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct V {
+    pub funct6: U6,
+    /// Unmasked, when clear the operation is masked by `v0`.
+    pub vm: bool,
+    pub vs2: U5,
+    pub vs1: U5,
+    pub funct3: U3,
+    pub vd: U5,
+}
+
+/// Vector load and store layout of LOAD-FP and STORE-FP. `vd` is the source
+/// register group of stores, `rs2` holds the stride register, the index
+/// register group or the unit-stride variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VMem {
+    /// Number of fields of a segment, minus one.
+    pub nf: U3,
+    pub mew: bool,
+    pub mop: U2,
+    pub vm: bool,
+    pub rs2: U5,
+    pub rs1: U5,
+    pub width: U3,
+    pub vd: U5,
+}
+
 impl R {
     #[inline(always)]
     pub const fn from_u32(value: u32) -> Self {
@@ -97,8 +146,35 @@ impl R {
 
     #[inline(always)]
     pub fn fid(&self) -> U12 {
-        let need_f3 = [0b0010000u16, 0b0010100, 0b1110000, 0b1010000, 0b1111000];
-        let need_r2 = [0b1100000u16, 0b1101000];
+        let need_f3 = [
+            0b0010000u16,
+            0b0010100,
+            0b1110000,
+            0b1010000,
+            0b1111000,
+            0b0010001,
+            0b0010101,
+            0b1110001,
+            0b1010001,
+            0b1111001,
+            0b0010010,
+            0b0010110,
+            0b1110010,
+            0b1010010,
+            0b1111010,
+            0b1011001,
+        ];
+        let need_r2 = [
+            0b1100000u16,
+            0b1101000,
+            0b0100000,
+            0b0100001,
+            0b1100001,
+            0b1101001,
+            0b0100010,
+            0b1100010,
+            0b1101010,
+        ];
         unsafe {
             U12::new_unchecked(if need_f3.contains(&self.funct7.as_u16()) {
                 self.funct7.as_u16() << 3 | self.funct3.as_u16()
@@ -174,8 +250,8 @@ impl Shift {
         }: I,
     ) -> Self {
         Self {
-            prefix: unsafe { U7::new_unchecked((imm.as_u16() >> 5) as u8) },
-            shamt: U5::new_truncate(imm.as_u16() as u8),
+            prefix: unsafe { U6::new_unchecked((imm.as_u16() >> 6) as u8) },
+            shamt: U6::new_truncate(imm.as_u16() as u8),
             rs1,
             funct3,
             rd,
@@ -188,8 +264,8 @@ impl Shift {
     }
 
     #[inline(always)]
-    pub const fn id(&self) -> U10 {
-        unsafe { U10::new_unchecked((self.prefix.as_u16() << 3) | self.funct3.as_u16()) }
+    pub const fn id(&self) -> U9 {
+        unsafe { U9::new_unchecked((self.prefix.as_u16() << 3) | self.funct3.as_u16()) }
     }
 }
 
@@ -214,7 +290,7 @@ impl Fence {
             fm: unsafe { U4::new_unchecked((value >> 28) as u8) },
             pred: U4::new_truncate((value >> 24) as u8),
             succ: U4::new_truncate((value >> 20) as u8),
-            rs1: U5::new_truncate((value >> 16) as u8),
+            rs1: U5::new_truncate((value >> 15) as u8),
             funct3: U3::new_truncate((value >> 12) as u8),
             rd: U5::new_truncate((value >> 7) as u8),
         }
@@ -228,6 +304,40 @@ impl From<u32> for Fence {
     }
 }
 
+impl Amo {
+    #[inline(always)]
+    pub const fn from_u32(value: u32) -> Self {
+        Self {
+            funct5: unsafe { U5::new_unchecked((value >> 27) as u8) },
+            aq: (value >> 26) & 1 != 0,
+            rl: (value >> 25) & 1 != 0,
+            rs2: U5::new_truncate((value >> 20) as u8),
+            rs1: U5::new_truncate((value >> 15) as u8),
+            funct3: U3::new_truncate((value >> 12) as u8),
+            rd: U5::new_truncate((value >> 7) as u8),
+        }
+    }
+
+    /// funct5 and funct3, ignoring the ordering bits.
+    #[inline(always)]
+    pub const fn id(&self) -> U10 {
+        unsafe { U10::new_unchecked((self.funct5.as_u16() << 5) | self.funct3.as_u16()) }
+    }
+
+    /// Size in bytes of the memory access.
+    #[inline(always)]
+    pub const fn width(&self) -> usize {
+        1 << self.funct3.as_u8()
+    }
+}
+
+impl From<u32> for Amo {
+    #[inline(always)]
+    fn from(value: u32) -> Self {
+        Self::from_u32(value)
+    }
+}
+
 impl S {
     #[inline(always)]
     pub const fn from_u32(value: u32) -> Self {
@@ -247,6 +357,12 @@ impl S {
     pub const fn id(&self) -> U3 {
         self.funct3
     }
+
+    /// Size in bytes of the stored value.
+    #[inline(always)]
+    pub const fn width(&self) -> usize {
+        1 << self.funct3.as_u8()
+    }
 }
 
 impl From<u32> for S {
@@ -338,6 +454,1871 @@ impl From<u32> for J {
     }
 }
 
+impl V {
+    #[inline(always)]
+    pub const fn from_u32(value: u32) -> Self {
+        Self {
+            funct6: unsafe { U6::new_unchecked((value >> 26) as u8) },
+            vm: (value >> 25) & 1 != 0,
+            vs2: U5::new_truncate((value >> 20) as u8),
+            vs1: U5::new_truncate((value >> 15) as u8),
+            funct3: U3::new_truncate((value >> 12) as u8),
+            vd: U5::new_truncate((value >> 7) as u8),
+        }
+    }
+}
+
+impl From<u32> for V {
+    #[inline(always)]
+    fn from(value: u32) -> Self {
+        Self::from_u32(value)
+    }
+}
+
+impl VMem {
+    #[inline(always)]
+    pub const fn from_u32(value: u32) -> Self {
+        Self {
+            nf: unsafe { U3::new_unchecked((value >> 29) as u8) },
+            mew: (value >> 28) & 1 != 0,
+            mop: U2::new_truncate((value >> 26) as u8),
+            vm: (value >> 25) & 1 != 0,
+            rs2: U5::new_truncate((value >> 20) as u8),
+            rs1: U5::new_truncate((value >> 15) as u8),
+            width: U3::new_truncate((value >> 12) as u8),
+            vd: U5::new_truncate((value >> 7) as u8),
+        }
+    }
+
+    /// Width in bits of the memory elements, or of the indices of indexed
+    /// accesses. `None` for the scalar float widths.
+    #[inline(always)]
+    pub const fn eew(&self) -> Option<u32> {
+        match self.width.as_u8() {
+            0b000 => Some(8),
+            0b101 => Some(16),
+            0b110 => Some(32),
+            0b111 => Some(64),
+            _ => None,
+        }
+    }
+}
+
+impl From<u32> for VMem {
+    #[inline(always)]
+    fn from(value: u32) -> Self {
+        Self::from_u32(value)
+    }
+}
+
+/// Zcmp push and pop layout. `rlist` selects `ra` and the first saved
+/// registers, `spimm` the extra stack space in units of 16 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cmpp {
+    pub rlist: U4,
+    pub spimm: U2,
+}
+
+/// Zcmp moves between `a0`/`a1` and two saved registers, `r1s` and `r2s`
+/// numbering `s0`-`s7` from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cmmv {
+    pub r1s: U3,
+    pub r2s: U3,
+}
+
+/// Zcmt table jump layout, indices from 32 link through `ra`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cmjt {
+    pub index: u8,
+}
+
+impl Cmpp {
+    /// Number of registers in the list, `ra` included. `s10` is never saved
+    /// without `s11`, so the last list holds 13.
+    #[inline(always)]
+    pub const fn count(&self) -> usize {
+        match self.rlist.as_u8() {
+            15 => 13,
+            rlist => rlist as usize - 3,
+        }
+    }
+
+    /// Bytes the stack pointer moves by, the register area rounded up to 16
+    /// bytes plus `spimm`.
+    #[inline(always)]
+    pub const fn stack_adj(&self, xlen: Xlen) -> usize {
+        let bytes = match xlen {
+            Xlen::Rv32 => 4,
+            Xlen::Rv64 => 8,
+        };
+        (self.count() * bytes).next_multiple_of(16) + self.spimm.as_u8() as usize * 16
+    }
+}
+
+impl Cmmv {
+    /// Register number of a saved register `s0`-`s7`.
+    #[inline(always)]
+    pub const fn sreg(r: U3) -> U5 {
+        match r.as_u8() {
+            r @ 0..=1 => U5::new_truncate(8 + r),
+            r => U5::new_truncate(16 + r),
+        }
+    }
+}
+
+macro_rules! instructions {
+    ($($(#[$attr:meta])* $variant:ident($format:ident) => $mnemonic:literal,)*) => {
+        /// A decoded instruction, one variant per mnemonic.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Instruction {
+            $($(#[$attr])* $variant($format),)*
+        }
+
+        impl Instruction {
+            /// The assembler mnemonic of the instruction.
+            #[inline(always)]
+            pub const fn mnemonic(&self) -> &'static str {
+                match self {
+                    $(Self::$variant(_) => $mnemonic,)*
+                }
+            }
+        }
+    };
+}
+
+instructions! {
+    // RV32I
+    Lui(U) => "lui",
+    Auipc(U) => "auipc",
+    Jal(J) => "jal",
+    Jalr(I) => "jalr",
+    Beq(B) => "beq",
+    Bne(B) => "bne",
+    Blt(B) => "blt",
+    Bge(B) => "bge",
+    Bltu(B) => "bltu",
+    Bgeu(B) => "bgeu",
+    Lb(I) => "lb",
+    Lh(I) => "lh",
+    Lw(I) => "lw",
+    Lbu(I) => "lbu",
+    Lhu(I) => "lhu",
+    Sb(S) => "sb",
+    Sh(S) => "sh",
+    Sw(S) => "sw",
+    Addi(I) => "addi",
+    Slti(I) => "slti",
+    Sltiu(I) => "sltiu",
+    Xori(I) => "xori",
+    Ori(I) => "ori",
+    Andi(I) => "andi",
+    Slli(Shift) => "slli",
+    Srli(Shift) => "srli",
+    Srai(Shift) => "srai",
+    Add(R) => "add",
+    Sub(R) => "sub",
+    Sll(R) => "sll",
+    Slt(R) => "slt",
+    Sltu(R) => "sltu",
+    Xor(R) => "xor",
+    Srl(R) => "srl",
+    Sra(R) => "sra",
+    Or(R) => "or",
+    And(R) => "and",
+    Fence(Fence) => "fence",
+    Ecall(I) => "ecall",
+    Ebreak(I) => "ebreak",
+    // RV64I
+    Lwu(I) => "lwu",
+    Ld(I) => "ld",
+    Sd(S) => "sd",
+    Addiw(I) => "addiw",
+    Slliw(Shift) => "slliw",
+    Srliw(Shift) => "srliw",
+    Sraiw(Shift) => "sraiw",
+    Addw(R) => "addw",
+    Subw(R) => "subw",
+    Sllw(R) => "sllw",
+    Srlw(R) => "srlw",
+    Sraw(R) => "sraw",
+    // A extension
+    LrW(Amo) => "lr.w",
+    ScW(Amo) => "sc.w",
+    AmoswapW(Amo) => "amoswap.w",
+    AmoaddW(Amo) => "amoadd.w",
+    AmoxorW(Amo) => "amoxor.w",
+    AmoandW(Amo) => "amoand.w",
+    AmoorW(Amo) => "amoor.w",
+    AmominW(Amo) => "amomin.w",
+    AmomaxW(Amo) => "amomax.w",
+    AmominuW(Amo) => "amominu.w",
+    AmomaxuW(Amo) => "amomaxu.w",
+    LrD(Amo) => "lr.d",
+    ScD(Amo) => "sc.d",
+    AmoswapD(Amo) => "amoswap.d",
+    AmoaddD(Amo) => "amoadd.d",
+    AmoxorD(Amo) => "amoxor.d",
+    AmoandD(Amo) => "amoand.d",
+    AmoorD(Amo) => "amoor.d",
+    AmominD(Amo) => "amomin.d",
+    AmomaxD(Amo) => "amomax.d",
+    AmominuD(Amo) => "amominu.d",
+    AmomaxuD(Amo) => "amomaxu.d",
+    // Zifencei extension
+    FenceI(I) => "fence.i",
+    // Zicsr extension
+    Csrrw(I) => "csrrw",
+    Csrrs(I) => "csrrs",
+    Csrrc(I) => "csrrc",
+    Csrrwi(I) => "csrrwi",
+    Csrrsi(I) => "csrrsi",
+    Csrrci(I) => "csrrci",
+    // Privileged architecture
+    Mret(I) => "mret",
+    Sret(I) => "sret",
+    Wfi(I) => "wfi",
+    SfenceVma(R) => "sfence.vma",
+    // M extension
+    Mul(R) => "mul",
+    Mulh(R) => "mulh",
+    Mulhsu(R) => "mulhsu",
+    Mulhu(R) => "mulhu",
+    Div(R) => "div",
+    Divu(R) => "divu",
+    Rem(R) => "rem",
+    Remu(R) => "remu",
+    Mulw(R) => "mulw",
+    Divw(R) => "divw",
+    Divuw(R) => "divuw",
+    Remw(R) => "remw",
+    Remuw(R) => "remuw",
+    // F extension
+    Flw(I) => "flw",
+    Fsw(S) => "fsw",
+    FmaddS(R4) => "fmadd.s",
+    FmsubS(R4) => "fmsub.s",
+    FnmsubS(R4) => "fnmsub.s",
+    FnmaddS(R4) => "fnmadd.s",
+    FaddS(R) => "fadd.s",
+    FsubS(R) => "fsub.s",
+    FmulS(R) => "fmul.s",
+    FdivS(R) => "fdiv.s",
+    FsqrtS(R) => "fsqrt.s",
+    FsgnjS(R) => "fsgnj.s",
+    FsgnjnS(R) => "fsgnjn.s",
+    FsgnjxS(R) => "fsgnjx.s",
+    FminS(R) => "fmin.s",
+    FmaxS(R) => "fmax.s",
+    FcvtWS(R) => "fcvt.w.s",
+    FcvtWuS(R) => "fcvt.wu.s",
+    FmvXW(R) => "fmv.x.w",
+    FeqS(R) => "feq.s",
+    FltS(R) => "flt.s",
+    FleS(R) => "fle.s",
+    FclassS(R) => "fclass.s",
+    FcvtSW(R) => "fcvt.s.w",
+    FcvtSWu(R) => "fcvt.s.wu",
+    FmvWX(R) => "fmv.w.x",
+    FcvtLS(R) => "fcvt.l.s",
+    FcvtLuS(R) => "fcvt.lu.s",
+    FcvtSL(R) => "fcvt.s.l",
+    FcvtSLu(R) => "fcvt.s.lu",
+    // D extension
+    Fld(I) => "fld",
+    Fsd(S) => "fsd",
+    FmaddD(R4) => "fmadd.d",
+    FmsubD(R4) => "fmsub.d",
+    FnmsubD(R4) => "fnmsub.d",
+    FnmaddD(R4) => "fnmadd.d",
+    FaddD(R) => "fadd.d",
+    FsubD(R) => "fsub.d",
+    FmulD(R) => "fmul.d",
+    FdivD(R) => "fdiv.d",
+    FsqrtD(R) => "fsqrt.d",
+    FsgnjD(R) => "fsgnj.d",
+    FsgnjnD(R) => "fsgnjn.d",
+    FsgnjxD(R) => "fsgnjx.d",
+    FminD(R) => "fmin.d",
+    FmaxD(R) => "fmax.d",
+    FcvtSD(R) => "fcvt.s.d",
+    FcvtDS(R) => "fcvt.d.s",
+    FeqD(R) => "feq.d",
+    FltD(R) => "flt.d",
+    FleD(R) => "fle.d",
+    FclassD(R) => "fclass.d",
+    FcvtWD(R) => "fcvt.w.d",
+    FcvtWuD(R) => "fcvt.wu.d",
+    FcvtDW(R) => "fcvt.d.w",
+    FcvtDWu(R) => "fcvt.d.wu",
+    FcvtLD(R) => "fcvt.l.d",
+    FcvtLuD(R) => "fcvt.lu.d",
+    FmvXD(R) => "fmv.x.d",
+    FcvtDL(R) => "fcvt.d.l",
+    FcvtDLu(R) => "fcvt.d.lu",
+    FmvDX(R) => "fmv.d.x",
+    // Zfh and Zfhmin extensions
+    Flh(I) => "flh",
+    Fsh(S) => "fsh",
+    FmaddH(R4) => "fmadd.h",
+    FmsubH(R4) => "fmsub.h",
+    FnmsubH(R4) => "fnmsub.h",
+    FnmaddH(R4) => "fnmadd.h",
+    FaddH(R) => "fadd.h",
+    FsubH(R) => "fsub.h",
+    FmulH(R) => "fmul.h",
+    FdivH(R) => "fdiv.h",
+    FsqrtH(R) => "fsqrt.h",
+    FsgnjH(R) => "fsgnj.h",
+    FsgnjnH(R) => "fsgnjn.h",
+    FsgnjxH(R) => "fsgnjx.h",
+    FminH(R) => "fmin.h",
+    FmaxH(R) => "fmax.h",
+    FcvtSH(R) => "fcvt.s.h",
+    FcvtHS(R) => "fcvt.h.s",
+    FcvtDH(R) => "fcvt.d.h",
+    FcvtHD(R) => "fcvt.h.d",
+    FeqH(R) => "feq.h",
+    FltH(R) => "flt.h",
+    FleH(R) => "fle.h",
+    FclassH(R) => "fclass.h",
+    FcvtWH(R) => "fcvt.w.h",
+    FcvtWuH(R) => "fcvt.wu.h",
+    FmvXH(R) => "fmv.x.h",
+    FcvtHW(R) => "fcvt.h.w",
+    FcvtHWu(R) => "fcvt.h.wu",
+    FmvHX(R) => "fmv.h.x",
+    FcvtLH(R) => "fcvt.l.h",
+    FcvtLuH(R) => "fcvt.lu.h",
+    FcvtHL(R) => "fcvt.h.l",
+    FcvtHLu(R) => "fcvt.h.lu",
+    // Zfa extension
+    FliS(R) => "fli.s",
+    FminmS(R) => "fminm.s",
+    FmaxmS(R) => "fmaxm.s",
+    FroundS(R) => "fround.s",
+    FroundnxS(R) => "froundnx.s",
+    FleqS(R) => "fleq.s",
+    FltqS(R) => "fltq.s",
+    FliD(R) => "fli.d",
+    FminmD(R) => "fminm.d",
+    FmaxmD(R) => "fmaxm.d",
+    FroundD(R) => "fround.d",
+    FroundnxD(R) => "froundnx.d",
+    FleqD(R) => "fleq.d",
+    FltqD(R) => "fltq.d",
+    FcvtmodWD(R) => "fcvtmod.w.d",
+    FmvhXD(R) => "fmvh.x.d",
+    FmvpDX(R) => "fmvp.d.x",
+    FliH(R) => "fli.h",
+    FminmH(R) => "fminm.h",
+    FmaxmH(R) => "fmaxm.h",
+    FroundH(R) => "fround.h",
+    FroundnxH(R) => "froundnx.h",
+    FleqH(R) => "fleq.h",
+    FltqH(R) => "fltq.h",
+    // Zcmp extension
+    CmPush(Cmpp) => "cm.push",
+    CmPop(Cmpp) => "cm.pop",
+    CmPopretz(Cmpp) => "cm.popretz",
+    CmPopret(Cmpp) => "cm.popret",
+    CmMvsa01(Cmmv) => "cm.mvsa01",
+    CmMva01s(Cmmv) => "cm.mva01s",
+    // Zcmt extension
+    CmJt(Cmjt) => "cm.jt",
+    CmJalt(Cmjt) => "cm.jalt",
+    // Zba extension
+    Sh1add(R) => "sh1add",
+    Sh2add(R) => "sh2add",
+    Sh3add(R) => "sh3add",
+    AddUw(R) => "add.uw",
+    Sh1addUw(R) => "sh1add.uw",
+    Sh2addUw(R) => "sh2add.uw",
+    Sh3addUw(R) => "sh3add.uw",
+    SlliUw(Shift) => "slli.uw",
+    // Zbb extension
+    Andn(R) => "andn",
+    Orn(R) => "orn",
+    Xnor(R) => "xnor",
+    Clz(I) => "clz",
+    Ctz(I) => "ctz",
+    Cpop(I) => "cpop",
+    Max(R) => "max",
+    Maxu(R) => "maxu",
+    Min(R) => "min",
+    Minu(R) => "minu",
+    SextB(I) => "sext.b",
+    SextH(I) => "sext.h",
+    ZextH(I) => "zext.h",
+    Rol(R) => "rol",
+    Ror(R) => "ror",
+    Rori(Shift) => "rori",
+    OrcB(I) => "orc.b",
+    Rev8(I) => "rev8",
+    Clzw(I) => "clzw",
+    Ctzw(I) => "ctzw",
+    Cpopw(I) => "cpopw",
+    Rolw(R) => "rolw",
+    Rorw(R) => "rorw",
+    Roriw(Shift) => "roriw",
+    // Zbc extension
+    Clmul(R) => "clmul",
+    Clmulh(R) => "clmulh",
+    Clmulr(R) => "clmulr",
+    // Zbs extension
+    Bclr(R) => "bclr",
+    Bclri(Shift) => "bclri",
+    Bext(R) => "bext",
+    Bexti(Shift) => "bexti",
+    Binv(R) => "binv",
+    Binvi(Shift) => "binvi",
+    Bset(R) => "bset",
+    Bseti(Shift) => "bseti",
+    // Zbkb extension
+    Pack(R) => "pack",
+    Packh(R) => "packh",
+    Packw(R) => "packw",
+    Brev8(I) => "brev8",
+    Zip(I) => "zip",
+    Unzip(I) => "unzip",
+    // Zbkx extension
+    Xperm4(R) => "xperm4",
+    Xperm8(R) => "xperm8",
+    // Zicond extension
+    CzeroEqz(R) => "czero.eqz",
+    CzeroNez(R) => "czero.nez",
+    // Zkne and Zknd extensions
+    Aes32esi(R) => "aes32esi",
+    Aes32esmi(R) => "aes32esmi",
+    Aes32dsi(R) => "aes32dsi",
+    Aes32dsmi(R) => "aes32dsmi",
+    Aes64es(R) => "aes64es",
+    Aes64esm(R) => "aes64esm",
+    Aes64ds(R) => "aes64ds",
+    Aes64dsm(R) => "aes64dsm",
+    Aes64im(I) => "aes64im",
+    Aes64ks1i(I) => "aes64ks1i",
+    Aes64ks2(R) => "aes64ks2",
+    // Zknh extension
+    Sha256sig0(I) => "sha256sig0",
+    Sha256sig1(I) => "sha256sig1",
+    Sha256sum0(I) => "sha256sum0",
+    Sha256sum1(I) => "sha256sum1",
+    Sha512sig0h(R) => "sha512sig0h",
+    Sha512sig0l(R) => "sha512sig0l",
+    Sha512sig1h(R) => "sha512sig1h",
+    Sha512sig1l(R) => "sha512sig1l",
+    Sha512sum0r(R) => "sha512sum0r",
+    Sha512sum1r(R) => "sha512sum1r",
+    Sha512sig0(I) => "sha512sig0",
+    Sha512sig1(I) => "sha512sig1",
+    Sha512sum0(I) => "sha512sum0",
+    Sha512sum1(I) => "sha512sum1",
+    // Zksed extension
+    Sm4ed(R) => "sm4ed",
+    Sm4ks(R) => "sm4ks",
+    // Zksh extension
+    Sm3p0(I) => "sm3p0",
+    Sm3p1(I) => "sm3p1",
+    // V extension
+    Vsetvli(I) => "vsetvli",
+    Vsetivli(I) => "vsetivli",
+    Vsetvl(R) => "vsetvl",
+    // Loads and stores, nf > 0 selects the segment forms
+    Vle(VMem) => "vle",
+    Vleff(VMem) => "vleff",
+    Vlm(VMem) => "vlm",
+    Vlse(VMem) => "vlse",
+    Vluxei(VMem) => "vluxei",
+    Vloxei(VMem) => "vloxei",
+    Vlr(VMem) => "vlr",
+    Vse(VMem) => "vse",
+    Vsm(VMem) => "vsm",
+    Vsse(VMem) => "vsse",
+    Vsuxei(VMem) => "vsuxei",
+    Vsoxei(VMem) => "vsoxei",
+    Vsr(VMem) => "vsr",
+    // Integer arithmetic
+    Vadd(V) => "vadd",
+    Vsub(V) => "vsub",
+    Vrsub(V) => "vrsub",
+    Vwaddu(V) => "vwaddu",
+    Vwadd(V) => "vwadd",
+    Vwsubu(V) => "vwsubu",
+    Vwsub(V) => "vwsub",
+    VwadduW(V) => "vwaddu.w",
+    VwaddW(V) => "vwadd.w",
+    VwsubuW(V) => "vwsubu.w",
+    VwsubW(V) => "vwsub.w",
+    VzextVf2(V) => "vzext.vf2",
+    VsextVf2(V) => "vsext.vf2",
+    VzextVf4(V) => "vzext.vf4",
+    VsextVf4(V) => "vsext.vf4",
+    VzextVf8(V) => "vzext.vf8",
+    VsextVf8(V) => "vsext.vf8",
+    Vadc(V) => "vadc",
+    Vmadc(V) => "vmadc",
+    Vsbc(V) => "vsbc",
+    Vmsbc(V) => "vmsbc",
+    Vand(V) => "vand",
+    Vor(V) => "vor",
+    Vxor(V) => "vxor",
+    Vsll(V) => "vsll",
+    Vsrl(V) => "vsrl",
+    Vsra(V) => "vsra",
+    Vnsrl(V) => "vnsrl",
+    Vnsra(V) => "vnsra",
+    Vmseq(V) => "vmseq",
+    Vmsne(V) => "vmsne",
+    Vmsltu(V) => "vmsltu",
+    Vmslt(V) => "vmslt",
+    Vmsleu(V) => "vmsleu",
+    Vmsle(V) => "vmsle",
+    Vmsgtu(V) => "vmsgtu",
+    Vmsgt(V) => "vmsgt",
+    Vminu(V) => "vminu",
+    Vmin(V) => "vmin",
+    Vmaxu(V) => "vmaxu",
+    Vmax(V) => "vmax",
+    Vmul(V) => "vmul",
+    Vmulh(V) => "vmulh",
+    Vmulhu(V) => "vmulhu",
+    Vmulhsu(V) => "vmulhsu",
+    Vdivu(V) => "vdivu",
+    Vdiv(V) => "vdiv",
+    Vremu(V) => "vremu",
+    Vrem(V) => "vrem",
+    Vwmul(V) => "vwmul",
+    Vwmulu(V) => "vwmulu",
+    Vwmulsu(V) => "vwmulsu",
+    Vmacc(V) => "vmacc",
+    Vnmsac(V) => "vnmsac",
+    Vmadd(V) => "vmadd",
+    Vnmsub(V) => "vnmsub",
+    Vwmaccu(V) => "vwmaccu",
+    Vwmacc(V) => "vwmacc",
+    Vwmaccsu(V) => "vwmaccsu",
+    Vwmaccus(V) => "vwmaccus",
+    Vmerge(V) => "vmerge",
+    VmvV(V) => "vmv.v",
+    // Fixed point arithmetic
+    Vsaddu(V) => "vsaddu",
+    Vsadd(V) => "vsadd",
+    Vssubu(V) => "vssubu",
+    Vssub(V) => "vssub",
+    Vaaddu(V) => "vaaddu",
+    Vaadd(V) => "vaadd",
+    Vasubu(V) => "vasubu",
+    Vasub(V) => "vasub",
+    Vsmul(V) => "vsmul",
+    Vssrl(V) => "vssrl",
+    Vssra(V) => "vssra",
+    Vnclipu(V) => "vnclipu",
+    Vnclip(V) => "vnclip",
+    // Floating point arithmetic
+    Vfadd(V) => "vfadd",
+    Vfsub(V) => "vfsub",
+    Vfrsub(V) => "vfrsub",
+    Vfwadd(V) => "vfwadd",
+    Vfwsub(V) => "vfwsub",
+    VfwaddW(V) => "vfwadd.w",
+    VfwsubW(V) => "vfwsub.w",
+    Vfmul(V) => "vfmul",
+    Vfdiv(V) => "vfdiv",
+    Vfrdiv(V) => "vfrdiv",
+    Vfwmul(V) => "vfwmul",
+    Vfmacc(V) => "vfmacc",
+    Vfnmacc(V) => "vfnmacc",
+    Vfmsac(V) => "vfmsac",
+    Vfnmsac(V) => "vfnmsac",
+    Vfmadd(V) => "vfmadd",
+    Vfnmadd(V) => "vfnmadd",
+    Vfmsub(V) => "vfmsub",
+    Vfnmsub(V) => "vfnmsub",
+    Vfwmacc(V) => "vfwmacc",
+    Vfwnmacc(V) => "vfwnmacc",
+    Vfwmsac(V) => "vfwmsac",
+    Vfwnmsac(V) => "vfwnmsac",
+    Vfsqrt(V) => "vfsqrt",
+    Vfrsqrt7(V) => "vfrsqrt7",
+    Vfrec7(V) => "vfrec7",
+    Vfmin(V) => "vfmin",
+    Vfmax(V) => "vfmax",
+    Vfsgnj(V) => "vfsgnj",
+    Vfsgnjn(V) => "vfsgnjn",
+    Vfsgnjx(V) => "vfsgnjx",
+    Vmfeq(V) => "vmfeq",
+    Vmfne(V) => "vmfne",
+    Vmflt(V) => "vmflt",
+    Vmfle(V) => "vmfle",
+    Vmfgt(V) => "vmfgt",
+    Vmfge(V) => "vmfge",
+    Vfclass(V) => "vfclass",
+    Vfmerge(V) => "vfmerge",
+    VfmvVF(V) => "vfmv.v.f",
+    VfcvtXuF(V) => "vfcvt.xu.f",
+    VfcvtXF(V) => "vfcvt.x.f",
+    VfcvtRtzXuF(V) => "vfcvt.rtz.xu.f",
+    VfcvtRtzXF(V) => "vfcvt.rtz.x.f",
+    VfcvtFXu(V) => "vfcvt.f.xu",
+    VfcvtFX(V) => "vfcvt.f.x",
+    VfwcvtXuF(V) => "vfwcvt.xu.f",
+    VfwcvtXF(V) => "vfwcvt.x.f",
+    VfwcvtRtzXuF(V) => "vfwcvt.rtz.xu.f",
+    VfwcvtRtzXF(V) => "vfwcvt.rtz.x.f",
+    VfwcvtFXu(V) => "vfwcvt.f.xu",
+    VfwcvtFX(V) => "vfwcvt.f.x",
+    VfwcvtFF(V) => "vfwcvt.f.f",
+    VfncvtXuF(V) => "vfncvt.xu.f",
+    VfncvtXF(V) => "vfncvt.x.f",
+    VfncvtRtzXuF(V) => "vfncvt.rtz.xu.f",
+    VfncvtRtzXF(V) => "vfncvt.rtz.x.f",
+    VfncvtFXu(V) => "vfncvt.f.xu",
+    VfncvtFX(V) => "vfncvt.f.x",
+    VfncvtFF(V) => "vfncvt.f.f",
+    VfncvtRodFF(V) => "vfncvt.rod.f.f",
+    // Reductions
+    Vredsum(V) => "vredsum",
+    Vredmaxu(V) => "vredmaxu",
+    Vredmax(V) => "vredmax",
+    Vredminu(V) => "vredminu",
+    Vredmin(V) => "vredmin",
+    Vredand(V) => "vredand",
+    Vredor(V) => "vredor",
+    Vredxor(V) => "vredxor",
+    Vwredsumu(V) => "vwredsumu",
+    Vwredsum(V) => "vwredsum",
+    Vfredosum(V) => "vfredosum",
+    Vfredusum(V) => "vfredusum",
+    Vfredmax(V) => "vfredmax",
+    Vfredmin(V) => "vfredmin",
+    Vfwredosum(V) => "vfwredosum",
+    Vfwredusum(V) => "vfwredusum",
+    // Mask
+    Vmand(V) => "vmand",
+    Vmnand(V) => "vmnand",
+    Vmandn(V) => "vmandn",
+    Vmxor(V) => "vmxor",
+    Vmor(V) => "vmor",
+    Vmnor(V) => "vmnor",
+    Vmorn(V) => "vmorn",
+    Vmxnor(V) => "vmxnor",
+    Vcpop(V) => "vcpop",
+    Vfirst(V) => "vfirst",
+    Vmsbf(V) => "vmsbf",
+    Vmsif(V) => "vmsif",
+    Vmsof(V) => "vmsof",
+    Viota(V) => "viota",
+    Vid(V) => "vid",
+    // Permutation
+    VmvXS(V) => "vmv.x.s",
+    VmvSX(V) => "vmv.s.x",
+    VfmvFS(V) => "vfmv.f.s",
+    VfmvSF(V) => "vfmv.s.f",
+    Vslideup(V) => "vslideup",
+    Vslidedown(V) => "vslidedown",
+    Vslide1up(V) => "vslide1up",
+    Vslide1down(V) => "vslide1down",
+    Vfslide1up(V) => "vfslide1up",
+    Vfslide1down(V) => "vfslide1down",
+    Vrgather(V) => "vrgather",
+    Vrgatherei16(V) => "vrgatherei16",
+    Vcompress(V) => "vcompress",
+    VmvNr(V) => "vmvnr",
+    // Custom Byte Math extension
+    Badd(R) => "badd",
+    Bsub(R) => "bsub",
+    Bsll(R) => "bsll",
+    Bslt(R) => "bslt",
+    Bsltu(R) => "bsltu",
+    Bxor(R) => "bxor",
+    Bsrl(R) => "bsrl",
+    Bsra(R) => "bsra",
+    Bor(R) => "bor",
+    Band(R) => "band",
+}
+
+impl Instruction {
+    /// Whether the instruction is the canonical `nop` (`addi x0, x0, 0`).
+    #[inline(always)]
+    pub fn is_nop(&self) -> bool {
+        matches!(
+            self,
+            Self::Addi(i) if i.rd.as_u8() == 0 && i.rs1.as_u8() == 0 && i.imm.as_u16() == 0
+        )
+    }
+
+    /// Whether the instruction falls in a base ISA HINT encoding space.
+    ///
+    /// HINTs are integer computations targeting `x0` (other than the
+    /// canonical `nop`) and fences with an empty predecessor or successor
+    /// set. They execute as no-ops; loads to `x0` are not HINTs since they
+    /// still access memory.
+    pub fn is_hint(&self) -> bool {
+        match self {
+            Self::Addi(i) => i.rd.as_u8() == 0 && !self.is_nop(),
+            Self::Lui(U { rd, .. }) | Self::Auipc(U { rd, .. }) => rd.as_u8() == 0,
+            Self::Slti(I { rd, .. })
+            | Self::Sltiu(I { rd, .. })
+            | Self::Xori(I { rd, .. })
+            | Self::Ori(I { rd, .. })
+            | Self::Andi(I { rd, .. })
+            | Self::Addiw(I { rd, .. }) => rd.as_u8() == 0,
+            Self::Slli(Shift { rd, .. })
+            | Self::Srli(Shift { rd, .. })
+            | Self::Srai(Shift { rd, .. })
+            | Self::Slliw(Shift { rd, .. })
+            | Self::Srliw(Shift { rd, .. })
+            | Self::Sraiw(Shift { rd, .. }) => rd.as_u8() == 0,
+            Self::Add(R { rd, .. })
+            | Self::Sub(R { rd, .. })
+            | Self::Sll(R { rd, .. })
+            | Self::Slt(R { rd, .. })
+            | Self::Sltu(R { rd, .. })
+            | Self::Xor(R { rd, .. })
+            | Self::Srl(R { rd, .. })
+            | Self::Sra(R { rd, .. })
+            | Self::Or(R { rd, .. })
+            | Self::And(R { rd, .. })
+            | Self::Addw(R { rd, .. })
+            | Self::Subw(R { rd, .. })
+            | Self::Sllw(R { rd, .. })
+            | Self::Srlw(R { rd, .. })
+            | Self::Sraw(R { rd, .. }) => rd.as_u8() == 0,
+            Self::Fence(f) => {
+                f.fm.as_u8() == 0
+                    && f.rs1.as_u8() == 0
+                    && f.rd.as_u8() == 0
+                    && (f.pred.as_u8() == 0 || f.succ.as_u8() == 0)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the instruction reads data memory, AMOs and `cm.pop` included.
+    pub fn is_load(&self) -> bool {
+        matches!(
+            self,
+            Self::Lb(_)
+                | Self::Lh(_)
+                | Self::Lw(_)
+                | Self::Lbu(_)
+                | Self::Lhu(_)
+                | Self::Lwu(_)
+                | Self::Ld(_)
+                | Self::LrW(_)
+                | Self::LrD(_)
+                | Self::Flw(_)
+                | Self::Fld(_)
+                | Self::Flh(_)
+                | Self::CmPop(_)
+                | Self::CmPopretz(_)
+                | Self::CmPopret(_)
+                | Self::Vle(_)
+                | Self::Vleff(_)
+                | Self::Vlm(_)
+                | Self::Vlse(_)
+                | Self::Vluxei(_)
+                | Self::Vloxei(_)
+                | Self::Vlr(_)
+        ) || self.is_amo()
+    }
+
+    /// Whether the instruction writes data memory, AMOs and `cm.push` included.
+    pub fn is_store(&self) -> bool {
+        matches!(
+            self,
+            Self::Sb(_)
+                | Self::Sh(_)
+                | Self::Sw(_)
+                | Self::Sd(_)
+                | Self::ScW(_)
+                | Self::ScD(_)
+                | Self::Fsw(_)
+                | Self::Fsd(_)
+                | Self::Fsh(_)
+                | Self::CmPush(_)
+                | Self::Vse(_)
+                | Self::Vsm(_)
+                | Self::Vsse(_)
+                | Self::Vsuxei(_)
+                | Self::Vsoxei(_)
+                | Self::Vsr(_)
+        ) || self.is_amo()
+    }
+
+    /// Whether the instruction is an AMO, which both reads and writes memory.
+    fn is_amo(&self) -> bool {
+        matches!(
+            self,
+            Self::AmoswapW(_)
+                | Self::AmoaddW(_)
+                | Self::AmoxorW(_)
+                | Self::AmoandW(_)
+                | Self::AmoorW(_)
+                | Self::AmominW(_)
+                | Self::AmomaxW(_)
+                | Self::AmominuW(_)
+                | Self::AmomaxuW(_)
+                | Self::AmoswapD(_)
+                | Self::AmoaddD(_)
+                | Self::AmoxorD(_)
+                | Self::AmoandD(_)
+                | Self::AmoorD(_)
+                | Self::AmominD(_)
+                | Self::AmomaxD(_)
+                | Self::AmominuD(_)
+                | Self::AmomaxuD(_)
+        )
+    }
+
+    /// Whether the instruction is a conditional branch.
+    #[inline(always)]
+    pub fn is_branch(&self) -> bool {
+        matches!(
+            self,
+            Self::Beq(_)
+                | Self::Bne(_)
+                | Self::Blt(_)
+                | Self::Bge(_)
+                | Self::Bltu(_)
+                | Self::Bgeu(_)
+        )
+    }
+
+    /// Whether the instruction is a scalar floating point one, from F, D, Zfh
+    /// or Zfa, its loads and stores included.
+    #[inline(always)]
+    pub fn is_float(&self) -> bool {
+        matches!(
+            self,
+            Self::Flw(_)
+                | Self::Fsw(_)
+                | Self::FmaddS(_)
+                | Self::FmsubS(_)
+                | Self::FnmsubS(_)
+                | Self::FnmaddS(_)
+                | Self::FaddS(_)
+                | Self::FsubS(_)
+                | Self::FmulS(_)
+                | Self::FdivS(_)
+                | Self::FsqrtS(_)
+                | Self::FsgnjS(_)
+                | Self::FsgnjnS(_)
+                | Self::FsgnjxS(_)
+                | Self::FminS(_)
+                | Self::FmaxS(_)
+                | Self::FcvtWS(_)
+                | Self::FcvtWuS(_)
+                | Self::FmvXW(_)
+                | Self::FeqS(_)
+                | Self::FltS(_)
+                | Self::FleS(_)
+                | Self::FclassS(_)
+                | Self::FcvtSW(_)
+                | Self::FcvtSWu(_)
+                | Self::FmvWX(_)
+                | Self::FcvtLS(_)
+                | Self::FcvtLuS(_)
+                | Self::FcvtSL(_)
+                | Self::FcvtSLu(_)
+                | Self::Fld(_)
+                | Self::Fsd(_)
+                | Self::FmaddD(_)
+                | Self::FmsubD(_)
+                | Self::FnmsubD(_)
+                | Self::FnmaddD(_)
+                | Self::FaddD(_)
+                | Self::FsubD(_)
+                | Self::FmulD(_)
+                | Self::FdivD(_)
+                | Self::FsqrtD(_)
+                | Self::FsgnjD(_)
+                | Self::FsgnjnD(_)
+                | Self::FsgnjxD(_)
+                | Self::FminD(_)
+                | Self::FmaxD(_)
+                | Self::FcvtSD(_)
+                | Self::FcvtDS(_)
+                | Self::FeqD(_)
+                | Self::FltD(_)
+                | Self::FleD(_)
+                | Self::FclassD(_)
+                | Self::FcvtWD(_)
+                | Self::FcvtWuD(_)
+                | Self::FcvtDW(_)
+                | Self::FcvtDWu(_)
+                | Self::FcvtLD(_)
+                | Self::FcvtLuD(_)
+                | Self::FmvXD(_)
+                | Self::FcvtDL(_)
+                | Self::FcvtDLu(_)
+                | Self::FmvDX(_)
+                | Self::Flh(_)
+                | Self::Fsh(_)
+                | Self::FmaddH(_)
+                | Self::FmsubH(_)
+                | Self::FnmsubH(_)
+                | Self::FnmaddH(_)
+                | Self::FaddH(_)
+                | Self::FsubH(_)
+                | Self::FmulH(_)
+                | Self::FdivH(_)
+                | Self::FsqrtH(_)
+                | Self::FsgnjH(_)
+                | Self::FsgnjnH(_)
+                | Self::FsgnjxH(_)
+                | Self::FminH(_)
+                | Self::FmaxH(_)
+                | Self::FcvtSH(_)
+                | Self::FcvtHS(_)
+                | Self::FcvtDH(_)
+                | Self::FcvtHD(_)
+                | Self::FeqH(_)
+                | Self::FltH(_)
+                | Self::FleH(_)
+                | Self::FclassH(_)
+                | Self::FcvtWH(_)
+                | Self::FcvtWuH(_)
+                | Self::FmvXH(_)
+                | Self::FcvtHW(_)
+                | Self::FcvtHWu(_)
+                | Self::FmvHX(_)
+                | Self::FcvtLH(_)
+                | Self::FcvtLuH(_)
+                | Self::FcvtHL(_)
+                | Self::FcvtHLu(_)
+                | Self::FliS(_)
+                | Self::FminmS(_)
+                | Self::FmaxmS(_)
+                | Self::FroundS(_)
+                | Self::FroundnxS(_)
+                | Self::FleqS(_)
+                | Self::FltqS(_)
+                | Self::FliD(_)
+                | Self::FminmD(_)
+                | Self::FmaxmD(_)
+                | Self::FroundD(_)
+                | Self::FroundnxD(_)
+                | Self::FleqD(_)
+                | Self::FltqD(_)
+                | Self::FcvtmodWD(_)
+                | Self::FmvhXD(_)
+                | Self::FmvpDX(_)
+                | Self::FliH(_)
+                | Self::FminmH(_)
+                | Self::FmaxmH(_)
+                | Self::FroundH(_)
+                | Self::FroundnxH(_)
+                | Self::FleqH(_)
+                | Self::FltqH(_)
+        )
+    }
+
+    /// The CSR a Zicsr instruction accesses.
+    #[inline(always)]
+    pub fn csr(&self) -> Option<usize> {
+        match self {
+            Self::Csrrw(i)
+            | Self::Csrrs(i)
+            | Self::Csrrc(i)
+            | Self::Csrrwi(i)
+            | Self::Csrrsi(i)
+            | Self::Csrrci(i) => Some(i.imm.as_u16() as usize),
+            _ => None,
+        }
+    }
+
+    /// The CSR the instruction reads, `csrrw` and `csrrwi` do not read when
+    /// `rd` is `x0`.
+    #[inline(always)]
+    pub fn csr_read(&self) -> Option<usize> {
+        match self {
+            Self::Csrrw(i) | Self::Csrrwi(i) if i.rd.as_u8() == 0 => None,
+            _ => self.csr(),
+        }
+    }
+
+    /// The CSR the instruction writes. `csrrs` and `csrrc` only write when
+    /// `rs1` is not `x0`, their immediate forms when `uimm` is not zero.
+    #[inline(always)]
+    pub fn csr_write(&self) -> Option<usize> {
+        match self {
+            Self::Csrrs(i) | Self::Csrrc(i) | Self::Csrrsi(i) | Self::Csrrci(i)
+                if i.rs1.as_u8() == 0 =>
+            {
+                None
+            }
+            _ => self.csr(),
+        }
+    }
+}
+
+/// A reserved or unsupported encoding, carrying the raw instruction bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IllegalInstruction(pub u32);
+
+impl From<IllegalInstruction> for Exception {
+    #[inline(always)]
+    fn from(IllegalInstruction(encoded): IllegalInstruction) -> Self {
+        Exception::IllegalInstruction(encoded)
+    }
+}
+
+/// Decodes a 16-bit instruction of the C extension through its 32-bit expansion.
+pub fn decode_compressed(
+    encoded: u16,
+    xlen: Xlen,
+    extensions: &Extensions,
+) -> Result<Instruction, IllegalInstruction> {
+    let illegal = IllegalInstruction(encoded as u32);
+    if !extensions.c {
+        return Err(illegal);
+    }
+    if compressed::is_cm(encoded) && !extensions.zcd() {
+        return compressed::decode_cm(encoded, extensions).ok_or(illegal);
+    }
+    decode(
+        compressed::expand(encoded, xlen, extensions)?,
+        xlen,
+        extensions,
+    )
+    .map_err(|_| illegal)
+}
+
+/// Decodes a 32-bit instruction for a hart with the given XLEN and extensions.
+///
+/// Reserved encodings, instructions from disabled extensions and RV64-only
+/// instructions on RV32 are all rejected as illegal.
+pub fn decode(
+    encoded: u32,
+    xlen: Xlen,
+    extensions: &Extensions,
+) -> Result<Instruction, IllegalInstruction> {
+    let illegal = Err(IllegalInstruction(encoded));
+    let rv64 = xlen == Xlen::Rv64;
+    // Zfh includes Zfhmin, both build on F
+    let zfhmin = extensions.f && (extensions.zfh || extensions.zfhmin);
+    let zfh = extensions.f && extensions.zfh;
+    // Zfinx and Zdinx run the F and D ops on the integer registers, without
+    // the loads, stores and moves of the float register file
+    let inx = extensions.inx();
+    let f = extensions.f || inx;
+    let d = if inx { extensions.zdinx } else { extensions.d };
+    // The D loads, stores and half conversions need the float register file
+    let fregs_d = extensions.f && extensions.d;
+    let zfa = extensions.f && extensions.zfa;
+    let instruction = match U7::new_truncate(encoded as u8) {
+        OPCODE_LUI => Instruction::Lui(U::from_u32(encoded)),
+        OPCODE_AUIPC => Instruction::Auipc(U::from_u32(encoded)),
+        OPCODE_JAL => Instruction::Jal(J::from_u32(encoded)),
+        OPCODE_JALR => {
+            let i = I::from_u32(encoded);
+            match i.id() {
+                JALR => Instruction::Jalr(i),
+                _ => return illegal,
+            }
+        }
+        OPCODE_BRANCH => {
+            let b = B::from_u32(encoded);
+            match b.id() {
+                BEQ => Instruction::Beq(b),
+                BNE => Instruction::Bne(b),
+                BLT => Instruction::Blt(b),
+                BGE => Instruction::Bge(b),
+                BLTU => Instruction::Bltu(b),
+                BGEU => Instruction::Bgeu(b),
+                _ => return illegal,
+            }
+        }
+        OPCODE_LOAD => {
+            let i = I::from_u32(encoded);
+            match i.id() {
+                LB => Instruction::Lb(i),
+                LH => Instruction::Lh(i),
+                LW => Instruction::Lw(i),
+                LBU => Instruction::Lbu(i),
+                LHU => Instruction::Lhu(i),
+                LWU if rv64 => Instruction::Lwu(i),
+                LD if rv64 => Instruction::Ld(i),
+                _ => return illegal,
+            }
+        }
+        OPCODE_STORE => {
+            let s = S::from_u32(encoded);
+            match s.id() {
+                SB => Instruction::Sb(s),
+                SH => Instruction::Sh(s),
+                SW => Instruction::Sw(s),
+                SD if rv64 => Instruction::Sd(s),
+                _ => return illegal,
+            }
+        }
+        OPCODE_OP_IMM => {
+            let i = I::from_u32(encoded);
+            let zbb = extensions.zbb || extensions.zbkb;
+            match i.id() {
+                ADDI => Instruction::Addi(i),
+                SLTI => Instruction::Slti(i),
+                SLTIU => Instruction::Sltiu(i),
+                XORI => Instruction::Xori(i),
+                ORI => Instruction::Ori(i),
+                ANDI => Instruction::Andi(i),
+                _ => {
+                    let shift = Shift::from_i(i);
+                    // shamt[5] is reserved on RV32
+                    if !rv64 && shift.shamt.as_u8() >= 32 {
+                        return illegal;
+                    }
+                    match shift.id() {
+                        SLLI => Instruction::Slli(shift),
+                        SRLI => Instruction::Srli(shift),
+                        SRAI => Instruction::Srai(shift),
+                        RORI if zbb => Instruction::Rori(shift),
+                        BCLRI if extensions.zbs => Instruction::Bclri(shift),
+                        BEXTI if extensions.zbs => Instruction::Bexti(shift),
+                        BINVI if extensions.zbs => Instruction::Binvi(shift),
+                        BSETI if extensions.zbs => Instruction::Bseti(shift),
+                        _ => match (i.funct3, i.imm) {
+                            (UNARY_SLLI, CLZ) if extensions.zbb => Instruction::Clz(i),
+                            (UNARY_SLLI, CTZ) if extensions.zbb => Instruction::Ctz(i),
+                            (UNARY_SLLI, CPOP) if extensions.zbb => Instruction::Cpop(i),
+                            (UNARY_SLLI, SEXT_B) if extensions.zbb => Instruction::SextB(i),
+                            (UNARY_SLLI, SEXT_H) if extensions.zbb => Instruction::SextH(i),
+                            (UNARY_SRLI, ORC_B) if extensions.zbb => Instruction::OrcB(i),
+                            (UNARY_SRLI, REV8_RV32) if zbb && !rv64 => Instruction::Rev8(i),
+                            (UNARY_SRLI, REV8_RV64) if zbb && rv64 => Instruction::Rev8(i),
+                            (UNARY_SRLI, BREV8) if extensions.zbkb => Instruction::Brev8(i),
+                            (UNARY_SLLI, ZIP) if extensions.zbkb && !rv64 => Instruction::Zip(i),
+                            (UNARY_SRLI, UNZIP) if extensions.zbkb && !rv64 => {
+                                Instruction::Unzip(i)
+                            }
+                            (UNARY_SLLI, AES64IM) if extensions.zknd && rv64 => {
+                                Instruction::Aes64im(i)
+                            }
+                            // rnum above 0xa is reserved
+                            (UNARY_SLLI, imm)
+                                if (extensions.zknd || extensions.zkne)
+                                    && rv64
+                                    && imm.as_u16() & !0xf == AES64KS1I.as_u16()
+                                    && imm.as_u16() & 0xf <= 0xa =>
+                            {
+                                Instruction::Aes64ks1i(i)
+                            }
+                            (UNARY_SLLI, SHA256SIG0) if extensions.zknh => {
+                                Instruction::Sha256sig0(i)
+                            }
+                            (UNARY_SLLI, SHA256SIG1) if extensions.zknh => {
+                                Instruction::Sha256sig1(i)
+                            }
+                            (UNARY_SLLI, SHA256SUM0) if extensions.zknh => {
+                                Instruction::Sha256sum0(i)
+                            }
+                            (UNARY_SLLI, SHA256SUM1) if extensions.zknh => {
+                                Instruction::Sha256sum1(i)
+                            }
+                            (UNARY_SLLI, SHA512SIG0) if extensions.zknh && rv64 => {
+                                Instruction::Sha512sig0(i)
+                            }
+                            (UNARY_SLLI, SHA512SIG1) if extensions.zknh && rv64 => {
+                                Instruction::Sha512sig1(i)
+                            }
+                            (UNARY_SLLI, SHA512SUM0) if extensions.zknh && rv64 => {
+                                Instruction::Sha512sum0(i)
+                            }
+                            (UNARY_SLLI, SHA512SUM1) if extensions.zknh && rv64 => {
+                                Instruction::Sha512sum1(i)
+                            }
+                            (UNARY_SLLI, SM3P0) if extensions.zksh => Instruction::Sm3p0(i),
+                            (UNARY_SLLI, SM3P1) if extensions.zksh => Instruction::Sm3p1(i),
+                            _ => return illegal,
+                        },
+                    }
+                }
+            }
+        }
+        OPCODE_OP_IMM_32 if rv64 => {
+            let i = I::from_u32(encoded);
+            let zbb = extensions.zbb || extensions.zbkb;
+            match i.id() {
+                ADDIW => Instruction::Addiw(i),
+                _ => {
+                    let shift = Shift::from_i(i);
+                    let shamt5 = shift.shamt.as_u8() < 32;
+                    match shift.id() {
+                        SLLIW if shamt5 => Instruction::Slliw(shift),
+                        SRLIW if shamt5 => Instruction::Srliw(shift),
+                        SRAIW if shamt5 => Instruction::Sraiw(shift),
+                        SLLI_UW if extensions.zba => Instruction::SlliUw(shift),
+                        RORIW if zbb && shamt5 => Instruction::Roriw(shift),
+                        _ if extensions.zbb => match (i.funct3, i.imm) {
+                            (UNARY_SLLI, CLZW) => Instruction::Clzw(i),
+                            (UNARY_SLLI, CTZW) => Instruction::Ctzw(i),
+                            (UNARY_SLLI, CPOPW) => Instruction::Cpopw(i),
+                            _ => return illegal,
+                        },
+                        _ => return illegal,
+                    }
+                }
+            }
+        }
+        OPCODE_OP => {
+            let r = R::from_u32(encoded);
+            let zbb = extensions.zbb || extensions.zbkb;
+            match r.id() {
+                ADD => Instruction::Add(r),
+                SUB => Instruction::Sub(r),
+                SLL => Instruction::Sll(r),
+                SLT => Instruction::Slt(r),
+                SLTU => Instruction::Sltu(r),
+                XOR => Instruction::Xor(r),
+                SRL => Instruction::Srl(r),
+                SRA => Instruction::Sra(r),
+                OR => Instruction::Or(r),
+                AND => Instruction::And(r),
+                MUL if extensions.m => Instruction::Mul(r),
+                MULH if extensions.m => Instruction::Mulh(r),
+                MULHSU if extensions.m => Instruction::Mulhsu(r),
+                MULHU if extensions.m => Instruction::Mulhu(r),
+                DIV if extensions.m => Instruction::Div(r),
+                DIVU if extensions.m => Instruction::Divu(r),
+                REM if extensions.m => Instruction::Rem(r),
+                REMU if extensions.m => Instruction::Remu(r),
+                SH1ADD if extensions.zba => Instruction::Sh1add(r),
+                SH2ADD if extensions.zba => Instruction::Sh2add(r),
+                SH3ADD if extensions.zba => Instruction::Sh3add(r),
+                ANDN if zbb => Instruction::Andn(r),
+                ORN if zbb => Instruction::Orn(r),
+                XNOR if zbb => Instruction::Xnor(r),
+                MAX if extensions.zbb => Instruction::Max(r),
+                MAXU if extensions.zbb => Instruction::Maxu(r),
+                MIN if extensions.zbb => Instruction::Min(r),
+                MINU if extensions.zbb => Instruction::Minu(r),
+                ROL if zbb => Instruction::Rol(r),
+                ROR if zbb => Instruction::Ror(r),
+                // zext.h is a unary op encoded as R-type with rs2 cleared
+                ZEXT_H if extensions.zbb && !rv64 && r.rs2.as_u8() == 0 => {
+                    Instruction::ZextH(I::from_u32(encoded))
+                }
+                CLMUL if extensions.zbc || extensions.zbkc => Instruction::Clmul(r),
+                CLMULR if extensions.zbc => Instruction::Clmulr(r),
+                CLMULH if extensions.zbc || extensions.zbkc => Instruction::Clmulh(r),
+                BCLR if extensions.zbs => Instruction::Bclr(r),
+                BEXT if extensions.zbs => Instruction::Bext(r),
+                BINV if extensions.zbs => Instruction::Binv(r),
+                BSET if extensions.zbs => Instruction::Bset(r),
+                PACK if extensions.zbkb => Instruction::Pack(r),
+                PACKH if extensions.zbkb => Instruction::Packh(r),
+                XPERM4 if extensions.zbkx => Instruction::Xperm4(r),
+                XPERM8 if extensions.zbkx => Instruction::Xperm8(r),
+                CZERO_EQZ if extensions.zicond => Instruction::CzeroEqz(r),
+                CZERO_NEZ if extensions.zicond => Instruction::CzeroNez(r),
+                AES64ES if extensions.zkne && rv64 => Instruction::Aes64es(r),
+                AES64ESM if extensions.zkne && rv64 => Instruction::Aes64esm(r),
+                AES64DS if extensions.zknd && rv64 => Instruction::Aes64ds(r),
+                AES64DSM if extensions.zknd && rv64 => Instruction::Aes64dsm(r),
+                AES64KS2 if (extensions.zknd || extensions.zkne) && rv64 => {
+                    Instruction::Aes64ks2(r)
+                }
+                SHA512SUM0R if extensions.zknh && !rv64 => Instruction::Sha512sum0r(r),
+                SHA512SUM1R if extensions.zknh && !rv64 => Instruction::Sha512sum1r(r),
+                SHA512SIG0L if extensions.zknh && !rv64 => Instruction::Sha512sig0l(r),
+                SHA512SIG1L if extensions.zknh && !rv64 => Instruction::Sha512sig1l(r),
+                SHA512SIG0H if extensions.zknh && !rv64 => Instruction::Sha512sig0h(r),
+                SHA512SIG1H if extensions.zknh && !rv64 => Instruction::Sha512sig1h(r),
+                BADD if extensions.xbyte => Instruction::Badd(r),
+                BSUB if extensions.xbyte => Instruction::Bsub(r),
+                BSLL if extensions.xbyte => Instruction::Bsll(r),
+                BSLT if extensions.xbyte => Instruction::Bslt(r),
+                BSLTU if extensions.xbyte => Instruction::Bsltu(r),
+                BXOR if extensions.xbyte => Instruction::Bxor(r),
+                BSRL if extensions.xbyte => Instruction::Bsrl(r),
+                BSRA if extensions.xbyte => Instruction::Bsra(r),
+                BOR if extensions.xbyte => Instruction::Bor(r),
+                BAND if extensions.xbyte => Instruction::Band(r),
+                // aes32* and sm4* carry a byte select in funct7[6:5]
+                _ => match (r.funct3, U5::new_truncate(r.funct7.as_u8())) {
+                    (BS_OP, AES32ESI) if extensions.zkne && !rv64 => Instruction::Aes32esi(r),
+                    (BS_OP, AES32ESMI) if extensions.zkne && !rv64 => Instruction::Aes32esmi(r),
+                    (BS_OP, AES32DSI) if extensions.zknd && !rv64 => Instruction::Aes32dsi(r),
+                    (BS_OP, AES32DSMI) if extensions.zknd && !rv64 => Instruction::Aes32dsmi(r),
+                    (BS_OP, SM4ED) if extensions.zksed => Instruction::Sm4ed(r),
+                    (BS_OP, SM4KS) if extensions.zksed => Instruction::Sm4ks(r),
+                    _ => return illegal,
+                },
+            }
+        }
+        OPCODE_OP_32 if rv64 => {
+            let r = R::from_u32(encoded);
+            let zbb = extensions.zbb || extensions.zbkb;
+            match r.id() {
+                ADDW => Instruction::Addw(r),
+                SUBW => Instruction::Subw(r),
+                SLLW => Instruction::Sllw(r),
+                SRLW => Instruction::Srlw(r),
+                SRAW => Instruction::Sraw(r),
+                MULW if extensions.m => Instruction::Mulw(r),
+                DIVW if extensions.m => Instruction::Divw(r),
+                DIVUW if extensions.m => Instruction::Divuw(r),
+                REMW if extensions.m => Instruction::Remw(r),
+                REMUW if extensions.m => Instruction::Remuw(r),
+                ADD_UW if extensions.zba => Instruction::AddUw(r),
+                SH1ADD_UW if extensions.zba => Instruction::Sh1addUw(r),
+                SH2ADD_UW if extensions.zba => Instruction::Sh2addUw(r),
+                SH3ADD_UW if extensions.zba => Instruction::Sh3addUw(r),
+                ROLW if zbb => Instruction::Rolw(r),
+                RORW if zbb => Instruction::Rorw(r),
+                ZEXT_H if extensions.zbb && r.rs2.as_u8() == 0 => {
+                    Instruction::ZextH(I::from_u32(encoded))
+                }
+                PACKW if extensions.zbkb => Instruction::Packw(r),
+                _ => return illegal,
+            }
+        }
+        OPCODE_AMO if extensions.a => {
+            let amo = Amo::from_u32(encoded);
+            let lr = amo.rs2.as_u8() == 0;
+            match amo.id() {
+                LR_W if lr => Instruction::LrW(amo),
+                SC_W => Instruction::ScW(amo),
+                AMOSWAP_W => Instruction::AmoswapW(amo),
+                AMOADD_W => Instruction::AmoaddW(amo),
+                AMOXOR_W => Instruction::AmoxorW(amo),
+                AMOAND_W => Instruction::AmoandW(amo),
+                AMOOR_W => Instruction::AmoorW(amo),
+                AMOMIN_W => Instruction::AmominW(amo),
+                AMOMAX_W => Instruction::AmomaxW(amo),
+                AMOMINU_W => Instruction::AmominuW(amo),
+                AMOMAXU_W => Instruction::AmomaxuW(amo),
+                LR_D if rv64 && lr => Instruction::LrD(amo),
+                SC_D if rv64 => Instruction::ScD(amo),
+                AMOSWAP_D if rv64 => Instruction::AmoswapD(amo),
+                AMOADD_D if rv64 => Instruction::AmoaddD(amo),
+                AMOXOR_D if rv64 => Instruction::AmoxorD(amo),
+                AMOAND_D if rv64 => Instruction::AmoandD(amo),
+                AMOOR_D if rv64 => Instruction::AmoorD(amo),
+                AMOMIN_D if rv64 => Instruction::AmominD(amo),
+                AMOMAX_D if rv64 => Instruction::AmomaxD(amo),
+                AMOMINU_D if rv64 => Instruction::AmominuD(amo),
+                AMOMAXU_D if rv64 => Instruction::AmomaxuD(amo),
+                _ => return illegal,
+            }
+        }
+        OPCODE_MISC_MEM => {
+            let fence = Fence::from_u32(encoded);
+            match fence.funct3 {
+                // Reserved fm values and non-zero rs1/rd are treated as a
+                // plain fence, as the spec mandates.
+                FENCE => Instruction::Fence(fence),
+                FENCE_I if extensions.zifencei => Instruction::FenceI(I::from_u32(encoded)),
+                _ => return illegal,
+            }
+        }
+        OPCODE_SYSTEM => {
+            let i = I::from_u32(encoded);
+            let r = R::from_u32(encoded);
+            match i.id() {
+                PRIV if r.id() == SFENCE_VMA && extensions.s => match r.rd.as_u8() {
+                    0 => Instruction::SfenceVma(r),
+                    _ => return illegal,
+                },
+                PRIV if i.rs1.as_u8() != 0 || i.rd.as_u8() != 0 => return illegal,
+                PRIV => match i.imm {
+                    ECALL => Instruction::Ecall(i),
+                    EBREAK => Instruction::Ebreak(i),
+                    MRET => Instruction::Mret(i),
+                    SRET if extensions.s => Instruction::Sret(i),
+                    WFI => Instruction::Wfi(i),
+                    _ => return illegal,
+                },
+                CSRRW if extensions.zicsr => Instruction::Csrrw(i),
+                CSRRS if extensions.zicsr => Instruction::Csrrs(i),
+                CSRRC if extensions.zicsr => Instruction::Csrrc(i),
+                CSRRWI if extensions.zicsr => Instruction::Csrrwi(i),
+                CSRRSI if extensions.zicsr => Instruction::Csrrsi(i),
+                CSRRCI if extensions.zicsr => Instruction::Csrrci(i),
+                _ => return illegal,
+            }
+        }
+        OPCODE_LOAD_FP if extensions.f || extensions.v => {
+            let i = I::from_u32(encoded);
+            match i.id() {
+                FLW if extensions.f => Instruction::Flw(i),
+                FLD if fregs_d => Instruction::Fld(i),
+                FLH if zfhmin => Instruction::Flh(i),
+                VE8 | VE16 | VE32 | VE64 if extensions.v => {
+                    match decode_vector_load(VMem::from_u32(encoded)) {
+                        Some(instruction) => instruction,
+                        None => return illegal,
+                    }
+                }
+                _ => return illegal,
+            }
+        }
+        OPCODE_STORE_FP if extensions.f || extensions.v => {
+            let s = S::from_u32(encoded);
+            match s.id() {
+                FSW if extensions.f => Instruction::Fsw(s),
+                FSD if fregs_d => Instruction::Fsd(s),
+                FSH if zfhmin => Instruction::Fsh(s),
+                VE8 | VE16 | VE32 | VE64 if extensions.v => {
+                    match decode_vector_store(VMem::from_u32(encoded)) {
+                        Some(instruction) => instruction,
+                        None => return illegal,
+                    }
+                }
+                _ => return illegal,
+            }
+        }
+        OPCODE_OP_V if extensions.v => match decode_op_v(encoded) {
+            Some(instruction) => instruction,
+            None => return illegal,
+        },
+        opcode @ (OPCODE_MADD | OPCODE_MSUB | OPCODE_NMSUB | OPCODE_NMADD) if f => {
+            let r4 = R4::from_u32(encoded);
+            if !valid_rm(r4.funct3) {
+                return illegal;
+            }
+            match (opcode, r4.funct2) {
+                (OPCODE_MADD, FMADD_S) => Instruction::FmaddS(r4),
+                (OPCODE_MSUB, FMSUB_S) => Instruction::FmsubS(r4),
+                (OPCODE_NMSUB, FNMSUB_S) => Instruction::FnmsubS(r4),
+                (OPCODE_NMADD, FNMADD_S) => Instruction::FnmaddS(r4),
+                (OPCODE_MADD, FMADD_D) if d => Instruction::FmaddD(r4),
+                (OPCODE_MSUB, FMSUB_D) if d => Instruction::FmsubD(r4),
+                (OPCODE_NMSUB, FNMSUB_D) if d => Instruction::FnmsubD(r4),
+                (OPCODE_NMADD, FNMADD_D) if d => Instruction::FnmaddD(r4),
+                (OPCODE_MADD, FMADD_H) if zfh => Instruction::FmaddH(r4),
+                (OPCODE_MSUB, FMSUB_H) if zfh => Instruction::FmsubH(r4),
+                (OPCODE_NMSUB, FNMSUB_H) if zfh => Instruction::FnmsubH(r4),
+                (OPCODE_NMADD, FNMADD_H) if zfh => Instruction::FnmaddH(r4),
+                _ => return illegal,
+            }
+        }
+        OPCODE_OP_FP if f => {
+            let r = R::from_u32(encoded);
+            let rm = valid_rm(r.funct3);
+            let unary = r.rs2.as_u8() == 0;
+            match r.fid() {
+                FADD_S if rm => Instruction::FaddS(r),
+                FSUB_S if rm => Instruction::FsubS(r),
+                FMUL_S if rm => Instruction::FmulS(r),
+                FDIV_S if rm => Instruction::FdivS(r),
+                FSQRT_S if rm && unary => Instruction::FsqrtS(r),
+                FSGNJ_S => Instruction::FsgnjS(r),
+                FSGNJN_S => Instruction::FsgnjnS(r),
+                FSGNJNX_S => Instruction::FsgnjxS(r),
+                FMIN_S => Instruction::FminS(r),
+                FMAX_S => Instruction::FmaxS(r),
+                FCVT_W_S if rm => Instruction::FcvtWS(r),
+                FCVT_WU_S if rm => Instruction::FcvtWuS(r),
+                FMV_X_W if unary && !inx => Instruction::FmvXW(r),
+                FEQ_S => Instruction::FeqS(r),
+                FLT_S => Instruction::FltS(r),
+                FLE_S => Instruction::FleS(r),
+                FCLASS_S if unary => Instruction::FclassS(r),
+                FCVT_S_W if rm => Instruction::FcvtSW(r),
+                FCVT_S_WU if rm => Instruction::FcvtSWu(r),
+                FMV_W_X if unary && !inx => Instruction::FmvWX(r),
+                FCVT_L_S if rm && rv64 => Instruction::FcvtLS(r),
+                FCVT_LU_S if rm && rv64 => Instruction::FcvtLuS(r),
+                FCVT_S_L if rm && rv64 => Instruction::FcvtSL(r),
+                FCVT_S_LU if rm && rv64 => Instruction::FcvtSLu(r),
+                FLI_S if zfa && r.rs2.as_u8() == 1 => Instruction::FliS(r),
+                FMINM_S if zfa => Instruction::FminmS(r),
+                FMAXM_S if zfa => Instruction::FmaxmS(r),
+                FROUND_S if rm && zfa => Instruction::FroundS(r),
+                FROUNDNX_S if rm && zfa => Instruction::FroundnxS(r),
+                FLEQ_S if zfa => Instruction::FleqS(r),
+                FLTQ_S if zfa => Instruction::FltqS(r),
+                FCVT_S_H if rm && zfhmin => Instruction::FcvtSH(r),
+                FCVT_H_S if rm && zfhmin => Instruction::FcvtHS(r),
+                FCVT_D_H if rm && zfhmin && fregs_d => Instruction::FcvtDH(r),
+                FCVT_H_D if rm && zfhmin && fregs_d => Instruction::FcvtHD(r),
+                FMV_X_H if unary && zfhmin => Instruction::FmvXH(r),
+                FMV_H_X if unary && zfhmin => Instruction::FmvHX(r),
+                FADD_H if rm && zfh => Instruction::FaddH(r),
+                FSUB_H if rm && zfh => Instruction::FsubH(r),
+                FMUL_H if rm && zfh => Instruction::FmulH(r),
+                FDIV_H if rm && zfh => Instruction::FdivH(r),
+                FSQRT_H if rm && unary && zfh => Instruction::FsqrtH(r),
+                FSGNJ_H if zfh => Instruction::FsgnjH(r),
+                FSGNJN_H if zfh => Instruction::FsgnjnH(r),
+                FSGNJX_H if zfh => Instruction::FsgnjxH(r),
+                FMIN_H if zfh => Instruction::FminH(r),
+                FMAX_H if zfh => Instruction::FmaxH(r),
+                FEQ_H if zfh => Instruction::FeqH(r),
+                FLT_H if zfh => Instruction::FltH(r),
+                FLE_H if zfh => Instruction::FleH(r),
+                FCLASS_H if unary && zfh => Instruction::FclassH(r),
+                FCVT_W_H if rm && zfh => Instruction::FcvtWH(r),
+                FCVT_WU_H if rm && zfh => Instruction::FcvtWuH(r),
+                FCVT_H_W if rm && zfh => Instruction::FcvtHW(r),
+                FCVT_H_WU if rm && zfh => Instruction::FcvtHWu(r),
+                FCVT_L_H if rm && zfh && rv64 => Instruction::FcvtLH(r),
+                FCVT_LU_H if rm && zfh && rv64 => Instruction::FcvtLuH(r),
+                FCVT_H_L if rm && zfh && rv64 => Instruction::FcvtHL(r),
+                FCVT_H_LU if rm && zfh && rv64 => Instruction::FcvtHLu(r),
+                FLI_H if zfa && zfh && r.rs2.as_u8() == 1 => Instruction::FliH(r),
+                FMINM_H if zfa && zfh => Instruction::FminmH(r),
+                FMAXM_H if zfa && zfh => Instruction::FmaxmH(r),
+                FROUND_H if rm && zfa && zfh => Instruction::FroundH(r),
+                FROUNDNX_H if rm && zfa && zfh => Instruction::FroundnxH(r),
+                FLEQ_H if zfa && zfh => Instruction::FleqH(r),
+                FLTQ_H if zfa && zfh => Instruction::FltqH(r),
+                _ if !d => return illegal,
+                FADD_D if rm => Instruction::FaddD(r),
+                FSUB_D if rm => Instruction::FsubD(r),
+                FMUL_D if rm => Instruction::FmulD(r),
+                FDIV_D if rm => Instruction::FdivD(r),
+                FSQRT_D if rm && unary => Instruction::FsqrtD(r),
+                FSGNJ_D => Instruction::FsgnjD(r),
+                FSGNJN_D => Instruction::FsgnjnD(r),
+                FSGNJX_D => Instruction::FsgnjxD(r),
+                FMIN_D => Instruction::FminD(r),
+                FMAX_D => Instruction::FmaxD(r),
+                FCVT_S_D if rm => Instruction::FcvtSD(r),
+                FCVT_D_S if rm => Instruction::FcvtDS(r),
+                FEQ_D => Instruction::FeqD(r),
+                FLT_D => Instruction::FltD(r),
+                FLE_D => Instruction::FleD(r),
+                FCLASS_D if unary => Instruction::FclassD(r),
+                FCVT_W_D if rm => Instruction::FcvtWD(r),
+                FCVT_WU_D if rm => Instruction::FcvtWuD(r),
+                FCVT_D_W if rm => Instruction::FcvtDW(r),
+                FCVT_D_WU if rm => Instruction::FcvtDWu(r),
+                FCVT_L_D if rm && rv64 => Instruction::FcvtLD(r),
+                FCVT_LU_D if rm && rv64 => Instruction::FcvtLuD(r),
+                FMV_X_D if unary && rv64 && !inx => Instruction::FmvXD(r),
+                FCVT_D_L if rm && rv64 => Instruction::FcvtDL(r),
+                FCVT_D_LU if rm && rv64 => Instruction::FcvtDLu(r),
+                FMV_D_X if unary && rv64 && !inx => Instruction::FmvDX(r),
+                FLI_D if zfa && r.rs2.as_u8() == 1 => Instruction::FliD(r),
+                FMINM_D if zfa => Instruction::FminmD(r),
+                FMAXM_D if zfa => Instruction::FmaxmD(r),
+                FROUND_D if rm && zfa => Instruction::FroundD(r),
+                FROUNDNX_D if rm && zfa => Instruction::FroundnxD(r),
+                FLEQ_D if zfa => Instruction::FleqD(r),
+                FLTQ_D if zfa => Instruction::FltqD(r),
+                // fcvtmod.w.d only encodes rtz
+                FCVTMOD_W_D if zfa && r.funct3.as_u8() == 0b001 => Instruction::FcvtmodWD(r),
+                FMVH_X_D if zfa && !rv64 && r.rs2.as_u8() == 1 => Instruction::FmvhXD(r),
+                FMVP_D_X if zfa && !rv64 => Instruction::FmvpDX(r),
+                _ => return illegal,
+            }
+        }
+        _ => return illegal,
+    };
+    if inx && !rv64 && !even_pairs(&instruction) {
+        return illegal;
+    }
+    Ok(instruction)
+}
+
+/// Zdinx on RV32 holds doubles in even/odd register pairs, odd register
+/// numbers for double operands are reserved.
+fn even_pairs(instruction: &Instruction) -> bool {
+    let even = |reg: U5| reg.as_u8() & 1 == 0;
+    match *instruction {
+        Instruction::FmaddD(r4)
+        | Instruction::FmsubD(r4)
+        | Instruction::FnmsubD(r4)
+        | Instruction::FnmaddD(r4) => even(r4.rd) && even(r4.rs1) && even(r4.rs2) && even(r4.rs3),
+        Instruction::FaddD(r)
+        | Instruction::FsubD(r)
+        | Instruction::FmulD(r)
+        | Instruction::FdivD(r)
+        | Instruction::FsgnjD(r)
+        | Instruction::FsgnjnD(r)
+        | Instruction::FsgnjxD(r)
+        | Instruction::FminD(r)
+        | Instruction::FmaxD(r) => even(r.rd) && even(r.rs1) && even(r.rs2),
+        Instruction::FsqrtD(r) => even(r.rd) && even(r.rs1),
+        Instruction::FeqD(r) | Instruction::FltD(r) | Instruction::FleD(r) => {
+            even(r.rs1) && even(r.rs2)
+        }
+        Instruction::FcvtSD(r)
+        | Instruction::FclassD(r)
+        | Instruction::FcvtWD(r)
+        | Instruction::FcvtWuD(r) => even(r.rs1),
+        Instruction::FcvtDS(r) | Instruction::FcvtDW(r) | Instruction::FcvtDWu(r) => even(r.rd),
+        _ => true,
+    }
+}
+
+/// Whole register moves, loads and stores take 1, 2, 4 or 8 registers.
+#[inline(always)]
+const fn valid_nreg(nf: u8) -> bool {
+    matches!(nf, 0 | 1 | 3 | 7)
+}
+
+fn decode_vector_load(m: VMem) -> Option<Instruction> {
+    if m.mew {
+        return None;
+    }
+    Some(match (m.mop, m.rs2) {
+        (MOP_UNIT, LUMOP_UNIT) => Instruction::Vle(m),
+        (MOP_UNIT, LUMOP_FF) => Instruction::Vleff(m),
+        (MOP_UNIT, LUMOP_WHOLE) if m.vm && valid_nreg(m.nf.as_u8()) => Instruction::Vlr(m),
+        (MOP_UNIT, LUMOP_MASK) if m.vm && m.nf.as_u8() == 0 && m.width == VE8 => {
+            Instruction::Vlm(m)
+        }
+        (MOP_UNIT, _) => return None,
+        (MOP_STRIDED, _) => Instruction::Vlse(m),
+        (MOP_INDEXED_UNORDERED, _) => Instruction::Vluxei(m),
+        (MOP_INDEXED_ORDERED, _) => Instruction::Vloxei(m),
+        _ => return None,
+    })
+}
+
+fn decode_vector_store(m: VMem) -> Option<Instruction> {
+    if m.mew {
+        return None;
+    }
+    // the whole register and mask stores only come with EEW 8
+    let bytes = m.vm && m.width == VE8;
+    Some(match (m.mop, m.rs2) {
+        (MOP_UNIT, LUMOP_UNIT) => Instruction::Vse(m),
+        (MOP_UNIT, LUMOP_WHOLE) if bytes && valid_nreg(m.nf.as_u8()) => Instruction::Vsr(m),
+        (MOP_UNIT, LUMOP_MASK) if bytes && m.nf.as_u8() == 0 => Instruction::Vsm(m),
+        (MOP_UNIT, _) => return None,
+        (MOP_STRIDED, _) => Instruction::Vsse(m),
+        (MOP_INDEXED_UNORDERED, _) => Instruction::Vsuxei(m),
+        (MOP_INDEXED_ORDERED, _) => Instruction::Vsoxei(m),
+        _ => return None,
+    })
+}
+
+/// Decodes OP-V, each operation only exists in some of the operand
+/// categories of its group.
+fn decode_op_v(encoded: u32) -> Option<Instruction> {
+    let v = V::from_u32(encoded);
+    let vv = matches!(v.funct3, OPIVV | OPMVV | OPFVV);
+    let vi = v.funct3 == OPIVI;
+    let unmasked = v.vm;
+    let vs2_zero = v.vs2.as_u8() == 0;
+    Some(match v.funct3 {
+        OPCFG => match encoded >> 30 {
+            0b00 | 0b01 => Instruction::Vsetvli(I::from_u32(encoded)),
+            0b11 => Instruction::Vsetivli(I::from_u32(encoded)),
+            _ if encoded >> 25 == 0b1000000 => Instruction::Vsetvl(R::from_u32(encoded)),
+            _ => return None,
+        },
+        OPIVV | OPIVX | OPIVI => match v.funct6 {
+            VADD => Instruction::Vadd(v),
+            VSUB if !vi => Instruction::Vsub(v),
+            VRSUB if !vv => Instruction::Vrsub(v),
+            VMINU if !vi => Instruction::Vminu(v),
+            VMIN if !vi => Instruction::Vmin(v),
+            VMAXU if !vi => Instruction::Vmaxu(v),
+            VMAX if !vi => Instruction::Vmax(v),
+            VAND => Instruction::Vand(v),
+            VOR => Instruction::Vor(v),
+            VXOR => Instruction::Vxor(v),
+            VRGATHER => Instruction::Vrgather(v),
+            VRGATHEREI16 if vv => Instruction::Vrgatherei16(v),
+            VSLIDEUP => Instruction::Vslideup(v),
+            VSLIDEDOWN if !vv => Instruction::Vslidedown(v),
+            VADC if !unmasked => Instruction::Vadc(v),
+            VMADC => Instruction::Vmadc(v),
+            VSBC if !unmasked && !vi => Instruction::Vsbc(v),
+            VMSBC if !vi => Instruction::Vmsbc(v),
+            VMERGE if !unmasked => Instruction::Vmerge(v),
+            VMERGE if vs2_zero => Instruction::VmvV(v),
+            VMSEQ => Instruction::Vmseq(v),
+            VMSNE => Instruction::Vmsne(v),
+            VMSLTU if !vi => Instruction::Vmsltu(v),
+            VMSLT if !vi => Instruction::Vmslt(v),
+            VMSLEU => Instruction::Vmsleu(v),
+            VMSLE => Instruction::Vmsle(v),
+            VMSGTU if !vv => Instruction::Vmsgtu(v),
+            VMSGT if !vv => Instruction::Vmsgt(v),
+            VSADDU => Instruction::Vsaddu(v),
+            VSADD => Instruction::Vsadd(v),
+            VSSUBU if !vi => Instruction::Vssubu(v),
+            VSSUB if !vi => Instruction::Vssub(v),
+            VSLL => Instruction::Vsll(v),
+            VSMUL if !vi => Instruction::Vsmul(v),
+            VMVNR if unmasked && valid_nreg(v.vs1.as_u8()) => Instruction::VmvNr(v),
+            VSRL => Instruction::Vsrl(v),
+            VSRA => Instruction::Vsra(v),
+            VSSRL => Instruction::Vssrl(v),
+            VSSRA => Instruction::Vssra(v),
+            VNSRL => Instruction::Vnsrl(v),
+            VNSRA => Instruction::Vnsra(v),
+            VNCLIPU => Instruction::Vnclipu(v),
+            VNCLIP => Instruction::Vnclip(v),
+            VWREDSUMU if vv => Instruction::Vwredsumu(v),
+            VWREDSUM if vv => Instruction::Vwredsum(v),
+            _ => return None,
+        },
+        OPMVV | OPMVX => match v.funct6 {
+            VREDSUM if vv => Instruction::Vredsum(v),
+            VREDAND if vv => Instruction::Vredand(v),
+            VREDOR if vv => Instruction::Vredor(v),
+            VREDXOR if vv => Instruction::Vredxor(v),
+            VREDMINU if vv => Instruction::Vredminu(v),
+            VREDMIN if vv => Instruction::Vredmin(v),
+            VREDMAXU if vv => Instruction::Vredmaxu(v),
+            VREDMAX if vv => Instruction::Vredmax(v),
+            VAADDU => Instruction::Vaaddu(v),
+            VAADD => Instruction::Vaadd(v),
+            VASUBU => Instruction::Vasubu(v),
+            VASUB => Instruction::Vasub(v),
+            VSLIDE1UP if !vv => Instruction::Vslide1up(v),
+            VSLIDE1DOWN if !vv => Instruction::Vslide1down(v),
+            VWXUNARY0 if vv => match v.vs1 {
+                VMV_X_S if unmasked => Instruction::VmvXS(v),
+                VCPOP => Instruction::Vcpop(v),
+                VFIRST => Instruction::Vfirst(v),
+                _ => return None,
+            },
+            VRXUNARY0 if unmasked && v.vs2 == VMV_S_X => Instruction::VmvSX(v),
+            VXUNARY0 if vv => match v.vs1 {
+                VZEXT_VF8 => Instruction::VzextVf8(v),
+                VSEXT_VF8 => Instruction::VsextVf8(v),
+                VZEXT_VF4 => Instruction::VzextVf4(v),
+                VSEXT_VF4 => Instruction::VsextVf4(v),
+                VZEXT_VF2 => Instruction::VzextVf2(v),
+                VSEXT_VF2 => Instruction::VsextVf2(v),
+                _ => return None,
+            },
+            VMUNARY0 if vv => match v.vs1 {
+                VMSBF => Instruction::Vmsbf(v),
+                VMSOF => Instruction::Vmsof(v),
+                VMSIF => Instruction::Vmsif(v),
+                VIOTA => Instruction::Viota(v),
+                VID if vs2_zero => Instruction::Vid(v),
+                _ => return None,
+            },
+            VCOMPRESS if vv && unmasked => Instruction::Vcompress(v),
+            VMANDN if vv && unmasked => Instruction::Vmandn(v),
+            VMAND if vv && unmasked => Instruction::Vmand(v),
+            VMOR if vv && unmasked => Instruction::Vmor(v),
+            VMXOR if vv && unmasked => Instruction::Vmxor(v),
+            VMORN if vv && unmasked => Instruction::Vmorn(v),
+            VMNAND if vv && unmasked => Instruction::Vmnand(v),
+            VMNOR if vv && unmasked => Instruction::Vmnor(v),
+            VMXNOR if vv && unmasked => Instruction::Vmxnor(v),
+            VDIVU => Instruction::Vdivu(v),
+            VDIV => Instruction::Vdiv(v),
+            VREMU => Instruction::Vremu(v),
+            VREM => Instruction::Vrem(v),
+            VMULHU => Instruction::Vmulhu(v),
+            VMUL => Instruction::Vmul(v),
+            VMULHSU => Instruction::Vmulhsu(v),
+            VMULH => Instruction::Vmulh(v),
+            VMADD => Instruction::Vmadd(v),
+            VNMSUB => Instruction::Vnmsub(v),
+            VMACC => Instruction::Vmacc(v),
+            VNMSAC => Instruction::Vnmsac(v),
+            VWADDU => Instruction::Vwaddu(v),
+            VWADD => Instruction::Vwadd(v),
+            VWSUBU => Instruction::Vwsubu(v),
+            VWSUB => Instruction::Vwsub(v),
+            VWADDU_W => Instruction::VwadduW(v),
+            VWADD_W => Instruction::VwaddW(v),
+            VWSUBU_W => Instruction::VwsubuW(v),
+            VWSUB_W => Instruction::VwsubW(v),
+            VWMULU => Instruction::Vwmulu(v),
+            VWMULSU => Instruction::Vwmulsu(v),
+            VWMUL => Instruction::Vwmul(v),
+            VWMACCU => Instruction::Vwmaccu(v),
+            VWMACC => Instruction::Vwmacc(v),
+            VWMACCUS if !vv => Instruction::Vwmaccus(v),
+            VWMACCSU => Instruction::Vwmaccsu(v),
+            _ => return None,
+        },
+        OPFVV | OPFVF => match v.funct6 {
+            VFADD => Instruction::Vfadd(v),
+            VFREDUSUM if vv => Instruction::Vfredusum(v),
+            VFSUB => Instruction::Vfsub(v),
+            VFREDOSUM if vv => Instruction::Vfredosum(v),
+            VFMIN => Instruction::Vfmin(v),
+            VFREDMIN if vv => Instruction::Vfredmin(v),
+            VFMAX => Instruction::Vfmax(v),
+            VFREDMAX if vv => Instruction::Vfredmax(v),
+            VFSGNJ => Instruction::Vfsgnj(v),
+            VFSGNJN => Instruction::Vfsgnjn(v),
+            VFSGNJX => Instruction::Vfsgnjx(v),
+            VFSLIDE1UP if !vv => Instruction::Vfslide1up(v),
+            VFSLIDE1DOWN if !vv => Instruction::Vfslide1down(v),
+            VWFUNARY0 if vv && unmasked && v.vs1 == VFMV_F_S => Instruction::VfmvFS(v),
+            VRFUNARY0 if !vv && unmasked && v.vs2 == VFMV_S_F => Instruction::VfmvSF(v),
+            VFUNARY0 if vv => match v.vs1 {
+                VFCVT_XU_F_V => Instruction::VfcvtXuF(v),
+                VFCVT_X_F_V => Instruction::VfcvtXF(v),
+                VFCVT_F_XU_V => Instruction::VfcvtFXu(v),
+                VFCVT_F_X_V => Instruction::VfcvtFX(v),
+                VFCVT_RTZ_XU_F_V => Instruction::VfcvtRtzXuF(v),
+                VFCVT_RTZ_X_F_V => Instruction::VfcvtRtzXF(v),
+                VFWCVT_XU_F_V => Instruction::VfwcvtXuF(v),
+                VFWCVT_X_F_V => Instruction::VfwcvtXF(v),
+                VFWCVT_F_XU_V => Instruction::VfwcvtFXu(v),
+                VFWCVT_F_X_V => Instruction::VfwcvtFX(v),
+                VFWCVT_F_F_V => Instruction::VfwcvtFF(v),
+                VFWCVT_RTZ_XU_F_V => Instruction::VfwcvtRtzXuF(v),
+                VFWCVT_RTZ_X_F_V => Instruction::VfwcvtRtzXF(v),
+                VFNCVT_XU_F_W => Instruction::VfncvtXuF(v),
+                VFNCVT_X_F_W => Instruction::VfncvtXF(v),
+                VFNCVT_F_XU_W => Instruction::VfncvtFXu(v),
+                VFNCVT_F_X_W => Instruction::VfncvtFX(v),
+                VFNCVT_F_F_W => Instruction::VfncvtFF(v),
+                VFNCVT_ROD_F_F_W => Instruction::VfncvtRodFF(v),
+                VFNCVT_RTZ_XU_F_W => Instruction::VfncvtRtzXuF(v),
+                VFNCVT_RTZ_X_F_W => Instruction::VfncvtRtzXF(v),
+                _ => return None,
+            },
+            VFUNARY1 if vv => match v.vs1 {
+                VFSQRT => Instruction::Vfsqrt(v),
+                VFRSQRT7 => Instruction::Vfrsqrt7(v),
+                VFREC7 => Instruction::Vfrec7(v),
+                VFCLASS => Instruction::Vfclass(v),
+                _ => return None,
+            },
+            VFMERGE if !vv && !unmasked => Instruction::Vfmerge(v),
+            VFMERGE if !vv && vs2_zero => Instruction::VfmvVF(v),
+            VMFEQ => Instruction::Vmfeq(v),
+            VMFLE => Instruction::Vmfle(v),
+            VMFLT => Instruction::Vmflt(v),
+            VMFNE => Instruction::Vmfne(v),
+            VMFGT if !vv => Instruction::Vmfgt(v),
+            VMFGE if !vv => Instruction::Vmfge(v),
+            VFDIV => Instruction::Vfdiv(v),
+            VFRDIV if !vv => Instruction::Vfrdiv(v),
+            VFMUL => Instruction::Vfmul(v),
+            VFRSUB if !vv => Instruction::Vfrsub(v),
+            VFMADD => Instruction::Vfmadd(v),
+            VFNMADD => Instruction::Vfnmadd(v),
+            VFMSUB => Instruction::Vfmsub(v),
+            VFNMSUB => Instruction::Vfnmsub(v),
+            VFMACC => Instruction::Vfmacc(v),
+            VFNMACC => Instruction::Vfnmacc(v),
+            VFMSAC => Instruction::Vfmsac(v),
+            VFNMSAC => Instruction::Vfnmsac(v),
+            VFWADD => Instruction::Vfwadd(v),
+            VFWREDUSUM if vv => Instruction::Vfwredusum(v),
+            VFWSUB => Instruction::Vfwsub(v),
+            VFWREDOSUM if vv => Instruction::Vfwredosum(v),
+            VFWADD_W => Instruction::VfwaddW(v),
+            VFWSUB_W => Instruction::VfwsubW(v),
+            VFWMUL => Instruction::Vfwmul(v),
+            VFWMACC => Instruction::Vfwmacc(v),
+            VFWNMACC => Instruction::Vfwnmacc(v),
+            VFWMSAC => Instruction::Vfwmsac(v),
+            VFWNMSAC => Instruction::Vfwnmsac(v),
+            _ => return None,
+        },
+        _ => return None,
+    })
+}
+
+/// Rounding modes 0b101 and 0b110 are reserved.
+#[inline(always)]
+const fn valid_rm(rm: U3) -> bool {
+    !matches!(rm.as_u8(), 0b101 | 0b110)
+}
+
 mod __sealed {
     pub trait Unsigned {
         type Signed;
@@ -380,6 +2361,9 @@ macro_rules! impl_base {
             }
         }
 
+        /// # Safety
+        ///
+        /// `value` must fit in `Self::BITS` bits.
         #[inline(always)]
         pub const unsafe fn new_unchecked(value: $base) -> Self {
             debug_assert!(value <= Self::MAX.0, concat!(stringify!($base), " too large"));
@@ -396,7 +2380,7 @@ macro_rules! impl_base {
         #[inline(always)]
         pub const fn sign_extend(&self) -> <$base as __sealed::Unsigned>::Signed {
             const OTHER_BITS: u32 = <$base as __sealed::Unsigned>::Signed::BITS - <$t>::BITS;
-            unsafe { core::mem::transmute::<$base, <$base as __sealed::Unsigned>::Signed>(self.0) }
+            (self.0 as <$base as __sealed::Unsigned>::Signed)
                 .wrapping_shl(OTHER_BITS).wrapping_shr(OTHER_BITS)
         }
     };
@@ -592,11 +2576,23 @@ impl_u8!( U5, 5,
           U2  > as_u8  => from_u2,
           U3  > as_u8  => from_u3,
           U4  > as_u8  => from_u4);
-impl_u8!( U7, 7,
+impl_u8!( U6, 6,
           U2  > as_u8  => from_u2,
           U3  > as_u8  => from_u3,
           U4  > as_u8  => from_u4,
           U5  > as_u8  => from_u5);
+impl_u8!( U7, 7,
+          U2  > as_u8  => from_u2,
+          U3  > as_u8  => from_u3,
+          U4  > as_u8  => from_u4,
+          U5  > as_u8  => from_u5,
+          U6  > as_u8  => from_u6);
+impl_u16!(U9, 9,
+          U2  > as_u8  => from_u2,
+          U3  > as_u8  => from_u3,
+          U4  > as_u8  => from_u4,
+          U5  > as_u8  => from_u5,
+          U6  > as_u8  => from_u6);
 impl_u16!(U10, 10,
           U2  > as_u8  => from_u2,
           U3  > as_u8  => from_u3,
@@ -642,11 +2638,11 @@ mod tests {
     fn sign_extend() {
         assert_eq!(
             U13::new_truncate(0b1111111111110u16).sign_extend(),
-            unsafe { core::mem::transmute::<u16, i16>(0b1111111111111110u16) }
+            0b1111111111111110u16 as i16
         );
         assert_eq!(
             U13::new_truncate(0b0111111111110u16).sign_extend(),
-            unsafe { core::mem::transmute::<u16, i16>(0b0000111111111110u16) }
+            0b0000111111111110u16 as i16
         );
     }
 
@@ -685,8 +2681,8 @@ mod tests {
                 rd: U5::new_truncate(2),
                 funct3: U3::new_truncate(0),
                 rs1: U5::new_truncate(1),
-                prefix: U7::new_truncate(0),
-                shamt: U5::new_truncate(3),
+                prefix: U6::new_truncate(0),
+                shamt: U6::new_truncate(3),
             }
         );
     }
@@ -785,4 +2781,973 @@ mod tests {
             },
         );
     }
+
+    const RV32: Xlen = Xlen::Rv32;
+    const RV64: Xlen = Xlen::Rv64;
+
+    #[test]
+    fn decode_instruction() {
+        // addi x12, x0, 42
+        let encoded = 0b000000101010_00000_000_01100_0010011;
+        assert_eq!(
+            decode(encoded, RV32, &Extensions::ALL),
+            Ok(Instruction::Addi(I::from_u32(encoded)))
+        );
+        assert_eq!(
+            decode(encoded, RV32, &Extensions::ALL).unwrap().mnemonic(),
+            "addi"
+        );
+    }
+
+    #[test]
+    fn decode_reserved_funct() {
+        let reserved = [
+            // jalr with funct3 != 0
+            0b000000000000_01101_001_01100_1100111,
+            // branch funct3 010 and 011
+            0b0000000_00001_00010_010_00000_1100011,
+            0b0000000_00001_00010_011_00000_1100011,
+            // load funct3 111
+            0b000000000000_01101_111_01100_0000011,
+            // store funct3 100
+            0b0000000_01100_01101_100_00000_0100011,
+            // add with funct7 0000100
+            0b0000100_01110_01101_000_01100_0110011,
+            // sub with funct3 001
+            0b0100000_01110_01101_001_01100_0110011,
+            // slli with funct7 0100000
+            0b0100000_00011_01101_001_01100_0010011,
+            // srli with funct7 0000010
+            0b0000010_00011_01101_101_01100_0010011,
+            // fence funct3 010
+            0b0000_0000_0000_00000_010_00000_0001111,
+            // ecall with rd != 0, ecall with imm 2
+            0b000000000000_00000_000_00001_1110011,
+            0b000000000010_00000_000_00000_1110011,
+            // system funct3 100
+            0b000000000000_00000_100_00000_1110011,
+        ];
+        for encoded in reserved {
+            assert_eq!(
+                decode(encoded, RV64, &Extensions::ALL),
+                Err(IllegalInstruction(encoded)),
+                "{encoded:#034b}"
+            );
+        }
+    }
+
+    #[test]
+    fn decode_reserved_float() {
+        let reserved = [
+            // fadd.s with rm 101 and 110
+            0b0000000_01110_01101_101_01100_1010011,
+            0b0000000_01110_01101_110_01100_1010011,
+            // fsqrt.s with rs2 != 0
+            0b0101100_00001_01101_000_01100_1010011,
+            // fcvt.w.s with rs2 00010 on RV32
+            0b1100000_00010_01101_000_01100_1010011,
+            // fsgnj.s with funct3 011
+            0b0010000_01110_01101_011_01100_1010011,
+            // fclass.s with rs2 != 0
+            0b1110000_00001_01101_001_01100_1010011,
+            // fmadd with fmt 11
+            0b01111_11_01110_01101_000_01100_1000011,
+            // flq, Q is not implemented
+            0b000000000000_01101_100_01100_0000111,
+        ];
+        for encoded in reserved {
+            assert_eq!(
+                decode(encoded, RV32, &Extensions::ALL),
+                Err(IllegalInstruction(encoded)),
+                "{encoded:#034b}"
+            );
+        }
+    }
+
+    #[test]
+    fn decode_rv64_only() {
+        let rv64_only = [
+            // ld, lwu, sd
+            0b000000000000_01101_011_01100_0000011,
+            0b000000000000_01101_110_01100_0000011,
+            0b0000000_01100_01101_011_00000_0100011,
+            // addiw, addw, mulw
+            0b000000000001_01101_000_01100_0011011,
+            0b0000000_01110_01101_000_01100_0111011,
+            0b0000001_01110_01101_000_01100_0111011,
+        ];
+        for encoded in rv64_only {
+            assert_eq!(
+                decode(encoded, RV32, &Extensions::ALL),
+                Err(IllegalInstruction(encoded))
+            );
+            assert!(decode(encoded, RV64, &Extensions::ALL).is_ok());
+        }
+    }
+
+    #[test]
+    fn decode_disabled_extension() {
+        let encodings = [
+            // mul
+            0b0000001_01110_01101_000_01100_0110011,
+            // fadd.s
+            0b0000000_01110_01101_000_01100_1010011,
+            // csrrw
+            0b000000000001_01101_001_01100_1110011,
+            // fence.i
+            0b000000000000_00000_001_00000_0001111,
+            // badd
+            0b0000010_01110_01101_000_01100_0110011,
+            // czero.eqz
+            0b0000111_01110_01101_101_01100_0110011,
+        ];
+        for encoded in encodings {
+            assert!(decode(encoded, RV64, &Extensions::ALL).is_ok());
+            assert_eq!(
+                decode(encoded, RV64, &Extensions::NONE),
+                Err(IllegalInstruction(encoded))
+            );
+        }
+    }
+
+    #[test]
+    fn decode_fence() {
+        // fence.tso and a fence with non-zero rs1/rd are plain fences
+        for encoded in [
+            0b1000_0011_0011_00000_000_00000_0001111,
+            0b0000_1111_1111_00001_000_00010_0001111,
+        ] {
+            assert_eq!(
+                decode(encoded, RV32, &Extensions::ALL),
+                Ok(Instruction::Fence(Fence::from_u32(encoded)))
+            );
+        }
+        assert_eq!(
+            Fence::from_u32(0b0000_1111_1111_00001_000_00010_0001111).rs1,
+            U5::new_truncate(1)
+        );
+    }
+    #[test]
+    fn decode_privileged() {
+        for (encoded, mnemonic) in [
+            (0x00000073, "ecall"),
+            (0x00100073, "ebreak"),
+            (0x30200073, "mret"),
+            (0x10500073, "wfi"),
+        ] {
+            let instruction = decode(encoded, RV32, &Extensions::NONE).unwrap();
+            assert_eq!(instruction.mnemonic(), mnemonic);
+        }
+        // sret needs S-mode
+        let s = Extensions {
+            s: true,
+            ..Extensions::NONE
+        };
+        assert_eq!(decode(0x10200073, RV64, &s).unwrap().mnemonic(), "sret");
+        // sfence.vma x13, x14
+        assert_eq!(
+            decode(0x12e68073, RV64, &s).unwrap().mnemonic(),
+            "sfence.vma"
+        );
+        assert_eq!(
+            decode(0x12e68873, RV64, &s),
+            Err(IllegalInstruction(0x12e68873))
+        );
+        assert_eq!(
+            decode(0x10200073, RV64, &Extensions::NONE),
+            Err(IllegalInstruction(0x10200073))
+        );
+        // mret with rs1 != 0 and rd != 0
+        for encoded in [0x30208073, 0x302000f3] {
+            assert_eq!(
+                decode(encoded, RV64, &Extensions::ALL),
+                Err(IllegalInstruction(encoded))
+            );
+        }
+    }
+
+    #[test]
+    fn decode_hint() {
+        let hint = |encoded| decode(encoded, RV64, &Extensions::ALL).unwrap().is_hint();
+        // nop is not a hint
+        assert!(!hint(0b000000000000_00000_000_00000_0010011));
+        assert!(decode(
+            0b000000000000_00000_000_00000_0010011,
+            RV64,
+            &Extensions::ALL
+        )
+        .unwrap()
+        .is_nop());
+        // addi x0, x1, 0 / addi x0, x0, 1 / lui x0 / add x0 / slli x0 / addiw x0
+        assert!(hint(0b000000000000_00001_000_00000_0010011));
+        assert!(hint(0b000000000001_00000_000_00000_0010011));
+        assert!(hint(0b00000000000000000001_00000_0110111));
+        assert!(hint(0b0000000_00010_00001_000_00000_0110011));
+        assert!(hint(0b0000000_00011_00001_001_00000_0010011));
+        assert!(hint(0b000000000001_00001_000_00000_0011011));
+        // fence with an empty predecessor or successor set
+        assert!(hint(0b0000_0000_1111_00000_000_00000_0001111));
+        assert!(hint(0b0000_1111_0000_00000_000_00000_0001111));
+        assert!(!hint(0b0000_1111_1111_00000_000_00000_0001111));
+        // loads, jumps and M instructions to x0 are not hints
+        assert!(!hint(0b000000000000_00001_010_00000_0000011));
+        assert!(!hint(0b0_0000000100_0_00000000_00000_1101111));
+        assert!(!hint(0b0000001_00010_00001_000_00000_0110011));
+        assert!(!hint(0b000000000000_00001_000_00001_0010011));
+    }
+    #[test]
+    fn decode_shift_wide() {
+        // srai x2, x1, 63
+        let encoded = 0b010000_111111_00001_101_00010_0010011;
+        let shift = Shift::from(encoded);
+        assert_eq!(shift.shamt, U6::new_truncate(63));
+        assert_eq!(shift.prefix, U6::new_truncate(0b010000));
+        assert_eq!(
+            decode(encoded, RV64, &Extensions::ALL),
+            Ok(Instruction::Srai(shift))
+        );
+        assert_eq!(
+            decode(encoded, RV32, &Extensions::ALL),
+            Err(IllegalInstruction(encoded))
+        );
+        // sraiw with shamt[5] set
+        let encoded = 0b010000_100001_00001_101_00010_0011011;
+        assert_eq!(
+            decode(encoded, RV64, &Extensions::ALL),
+            Err(IllegalInstruction(encoded))
+        );
+    }
+    #[test]
+    fn decode_compressed_instruction() {
+        // c.lw a5, 124(a0)
+        assert_eq!(
+            decode_compressed(0x5d7c, RV32, &Extensions::ALL),
+            Ok(Instruction::Lw(I::from_u32(0x07c52783)))
+        );
+        assert_eq!(
+            decode_compressed(0x5d7c, RV32, &Extensions::NONE),
+            Err(IllegalInstruction(0x5d7c))
+        );
+        // c.flw fa0, 64(a1) needs F on top of C
+        let extensions = Extensions {
+            f: false,
+            ..Extensions::ALL
+        };
+        assert_eq!(
+            decode_compressed(0x61a8, RV32, &extensions),
+            Err(IllegalInstruction(0x61a8))
+        );
+    }
+
+    #[test]
+    fn decode_zcmp_zcmt() {
+        let zc = Extensions {
+            zcmp: true,
+            zcmt: true,
+            ..Extensions::ALL
+        };
+        for (encoded, mnemonic) in [
+            (0xb866, "cm.push"),
+            (0xbafe, "cm.pop"),
+            (0xbc42, "cm.popretz"),
+            (0xbe52, "cm.popret"),
+            (0xac2a, "cm.mvsa01"),
+            (0xacfe, "cm.mva01s"),
+            (0xa006, "cm.jt"),
+            (0xa0a2, "cm.jalt"),
+        ] {
+            assert_eq!(
+                decode_compressed(encoded, RV32, &zc).map(|i| i.mnemonic()),
+                Ok(mnemonic),
+                "{encoded:#06x}"
+            );
+        }
+        // cm.push {ra, s0-s1}, -32 and cm.pop {ra, s0-s11}, 112
+        let Ok(Instruction::CmPush(push)) = decode_compressed(0xb866, RV32, &zc) else {
+            panic!("not a push");
+        };
+        assert_eq!((push.count(), push.stack_adj(Xlen::Rv32)), (3, 32));
+        let Ok(Instruction::CmPop(pop)) = decode_compressed(0xbafe, RV32, &zc) else {
+            panic!("not a pop");
+        };
+        assert_eq!((pop.count(), pop.stack_adj(Xlen::Rv32)), (13, 112));
+        assert_eq!(pop.stack_adj(Xlen::Rv64), 160);
+        // the slot stays c.fsdsp without either extension
+        assert_eq!(
+            decode_compressed(0xb866, RV32, &Extensions::ALL).map(|i| i.mnemonic()),
+            Ok("fsd")
+        );
+        let zcmt = Extensions { zcmp: false, ..zc };
+        let zcmp = Extensions { zcmt: false, ..zc };
+        for (encoded, extensions) in [
+            (0xb866, zcmt),
+            (0xa006, zcmp),
+            // rlist without ra
+            (0xb832, zc),
+            // cm.mvsa01 to the same register twice
+            (0xaca6, zc),
+            (0xa402, zc),
+        ] {
+            assert_eq!(
+                decode_compressed(encoded, RV32, &extensions),
+                Err(IllegalInstruction(encoded as u32)),
+                "{encoded:#06x}"
+            );
+        }
+    }
+
+    #[test]
+    fn decode_amo() {
+        // lr.d.aqrl a0, (a1)
+        let amo = Amo::from_u32(0x1605b52f);
+        assert!(amo.aq && amo.rl);
+        assert_eq!(amo.width(), 8);
+        assert_eq!(
+            decode(0x1605b52f, RV64, &Extensions::ALL),
+            Ok(Instruction::LrD(amo))
+        );
+        // sc.d.rl a2, a3, (a1)
+        let amo = Amo::from_u32(0x1ad5b62f);
+        assert!(!amo.aq && amo.rl);
+        assert_eq!(
+            decode(0x1ad5b62f, RV64, &Extensions::ALL),
+            Ok(Instruction::ScD(amo))
+        );
+        // amomin.w a0, a3, (a1)
+        assert_eq!(
+            decode(0x80d5a52f, RV32, &Extensions::ALL),
+            Ok(Instruction::AmominW(Amo::from_u32(0x80d5a52f)))
+        );
+        for (encoded, xlen, extensions) in [
+            // lr.w with a non-zero rs2
+            (0x1015a52f, RV32, Extensions::ALL),
+            // doubleword AMOs are RV64 only
+            (0xe0d5b52f, RV32, Extensions::ALL),
+            // amoadd.w without A
+            (0x00d5a52f, RV32, Extensions::NONE),
+            // unassigned funct5
+            (0x28d5a52f, RV64, Extensions::ALL),
+            // byte width
+            (0x00d5852f, RV64, Extensions::ALL),
+        ] {
+            assert_eq!(
+                decode(encoded, xlen, &extensions),
+                Err(IllegalInstruction(encoded))
+            );
+        }
+    }
+
+    #[test]
+    fn decode_double() {
+        for (encoded, mnemonic) in [
+            (0x02e68653, "fadd.d"),
+            (0x0ae68653, "fsub.d"),
+            (0x12e68653, "fmul.d"),
+            (0x1ae68653, "fdiv.d"),
+            (0x5a068653, "fsqrt.d"),
+            (0x22e68653, "fsgnj.d"),
+            (0x22e69653, "fsgnjn.d"),
+            (0x22e6a653, "fsgnjx.d"),
+            (0x2ae68653, "fmin.d"),
+            (0x2ae69653, "fmax.d"),
+            (0x40168653, "fcvt.s.d"),
+            (0x42068653, "fcvt.d.s"),
+            (0xa2e6a653, "feq.d"),
+            (0xa2e69653, "flt.d"),
+            (0xa2e68653, "fle.d"),
+            (0xe2069653, "fclass.d"),
+            (0xc2068653, "fcvt.w.d"),
+            (0xc2168653, "fcvt.wu.d"),
+            (0xd2068653, "fcvt.d.w"),
+            (0xd2168653, "fcvt.d.wu"),
+            (0xc2268653, "fcvt.l.d"),
+            (0xc2368653, "fcvt.lu.d"),
+            (0xe2068653, "fmv.x.d"),
+            (0xd2268653, "fcvt.d.l"),
+            (0xd2368653, "fcvt.d.lu"),
+            (0xf2068653, "fmv.d.x"),
+            (0x7ae68643, "fmadd.d"),
+            (0x7ae68647, "fmsub.d"),
+            (0x7ae6864b, "fnmsub.d"),
+            (0x7ae6864f, "fnmadd.d"),
+            (0x0086b607, "fld"),
+            (0x00c6b427, "fsd"),
+        ] {
+            assert_eq!(
+                decode(encoded, RV64, &Extensions::ALL).map(|i| i.mnemonic()),
+                Ok(mnemonic),
+                "{encoded:#010x}"
+            );
+        }
+        let no_d = Extensions {
+            d: false,
+            ..Extensions::ALL
+        };
+        for (encoded, xlen, extensions) in [
+            // 64-bit integer conversions and moves are RV64 only
+            (0xc2268653, RV32, Extensions::ALL),
+            (0xd2368653, RV32, Extensions::ALL),
+            (0xe2068653, RV32, Extensions::ALL),
+            (0xf2068653, RV32, Extensions::ALL),
+            // fsqrt.d with rs2 != 0
+            (0x5a168653, RV64, Extensions::ALL),
+            // fadd.d with rm 101
+            (0x02e6d653, RV64, Extensions::ALL),
+            // F without D
+            (0x02e68653, RV64, no_d),
+            (0x7ae68643, RV64, no_d),
+            (0x0086b607, RV64, no_d),
+            (0x00c6b427, RV64, no_d),
+        ] {
+            assert_eq!(
+                decode(encoded, xlen, &extensions),
+                Err(IllegalInstruction(encoded)),
+                "{encoded:#010x}"
+            );
+        }
+    }
+
+    #[test]
+    fn decode_half() {
+        for (encoded, mnemonic) in [
+            (0x04e6f653, "fadd.h"),
+            (0x0ce6f653, "fsub.h"),
+            (0x14e6f653, "fmul.h"),
+            (0x1ce6f653, "fdiv.h"),
+            (0x5c06f653, "fsqrt.h"),
+            (0x24e68653, "fsgnj.h"),
+            (0x24e69653, "fsgnjn.h"),
+            (0x24e6a653, "fsgnjx.h"),
+            (0x2ce68653, "fmin.h"),
+            (0x2ce69653, "fmax.h"),
+            (0x40268653, "fcvt.s.h"),
+            (0x4406f653, "fcvt.h.s"),
+            (0x42268653, "fcvt.d.h"),
+            (0x4416f653, "fcvt.h.d"),
+            (0xa4e6a653, "feq.h"),
+            (0xa4e69653, "flt.h"),
+            (0xa4e68653, "fle.h"),
+            (0xe4069653, "fclass.h"),
+            (0xc406f653, "fcvt.w.h"),
+            (0xc416f653, "fcvt.wu.h"),
+            (0xe4068653, "fmv.x.h"),
+            (0xd406f653, "fcvt.h.w"),
+            (0xd416f653, "fcvt.h.wu"),
+            (0xf4068653, "fmv.h.x"),
+            (0xc426f653, "fcvt.l.h"),
+            (0xc436f653, "fcvt.lu.h"),
+            (0xd426f653, "fcvt.h.l"),
+            (0xd436f653, "fcvt.h.lu"),
+            (0x7ce6f643, "fmadd.h"),
+            (0x7ce6f647, "fmsub.h"),
+            (0x7ce6f64b, "fnmsub.h"),
+            (0x7ce6f64f, "fnmadd.h"),
+            (0x00869607, "flh"),
+            (0x00c69827, "fsh"),
+        ] {
+            assert_eq!(
+                decode(encoded, RV64, &Extensions::ALL).map(|i| i.mnemonic()),
+                Ok(mnemonic),
+                "{encoded:#010x}"
+            );
+        }
+        // Zfhmin keeps the loads, stores, moves and conversions
+        let zfhmin = Extensions {
+            zfh: false,
+            ..Extensions::ALL
+        };
+        for (encoded, mnemonic) in [
+            (0x40268653, "fcvt.s.h"),
+            (0x4406f653, "fcvt.h.s"),
+            (0x42268653, "fcvt.d.h"),
+            (0x4416f653, "fcvt.h.d"),
+            (0xe4068653, "fmv.x.h"),
+            (0xf4068653, "fmv.h.x"),
+            (0x00869607, "flh"),
+            (0x00c69827, "fsh"),
+        ] {
+            assert_eq!(
+                decode(encoded, RV64, &zfhmin).map(|i| i.mnemonic()),
+                Ok(mnemonic),
+                "{encoded:#010x}"
+            );
+        }
+        let no_d = Extensions {
+            d: false,
+            ..Extensions::ALL
+        };
+        let no_f = Extensions {
+            f: false,
+            d: false,
+            ..Extensions::ALL
+        };
+        for (encoded, xlen, extensions) in [
+            // 64-bit integer conversions are RV64 only
+            (0xc426f653, RV32, Extensions::ALL),
+            (0xd436f653, RV32, Extensions::ALL),
+            // fsqrt.h with rs2 != 0
+            (0x5c16f653, RV64, Extensions::ALL),
+            // fadd.h with rm 101
+            (0x04e6d653, RV64, Extensions::ALL),
+            // arithmetic needs the full Zfh
+            (0x04e6f653, RV64, zfhmin),
+            (0xa4e6a653, RV64, zfhmin),
+            (0xc406f653, RV64, zfhmin),
+            (0x7ce6f643, RV64, zfhmin),
+            // conversions with doubles need D
+            (0x42268653, RV64, no_d),
+            (0x4416f653, RV64, no_d),
+            // everything builds on F
+            (0x00869607, RV64, no_f),
+            (0x04e6f653, RV64, no_f),
+        ] {
+            assert_eq!(
+                decode(encoded, xlen, &extensions),
+                Err(IllegalInstruction(encoded)),
+                "{encoded:#010x}"
+            );
+        }
+    }
+
+    #[test]
+    fn decode_zfinx() {
+        let zdinx = Extensions {
+            f: false,
+            d: false,
+            zfinx: true,
+            zdinx: true,
+            ..Extensions::ALL
+        };
+        for (encoded, xlen, mnemonic) in [
+            (0x00e6f653, RV32, "fadd.s"),
+            (0x03077653, RV32, "fadd.d"),
+            (0x53077643, RV32, "fmadd.d"),
+            (0x5a077653, RV32, "fsqrt.d"),
+            // the integer sides of conversions and compares take any register
+            (0xc20676d3, RV32, "fcvt.w.d"),
+            (0x401676d3, RV32, "fcvt.s.d"),
+            (0xa2e626d3, RV32, "feq.d"),
+            // RV64 keeps doubles in single registers
+            (0x02e68653, RV64, "fadd.d"),
+        ] {
+            assert_eq!(
+                decode(encoded, xlen, &zdinx).map(|i| i.mnemonic()),
+                Ok(mnemonic),
+                "{encoded:#010x}"
+            );
+        }
+        let zfinx = Extensions {
+            zdinx: false,
+            ..zdinx
+        };
+        // D left set does not bring the float register file back
+        let with_d = Extensions { d: true, ..zfinx };
+        for (encoded, xlen, extensions) in [
+            // no float register file to load, store or move through
+            (0x0086a607, RV64, zdinx),
+            (0x00c6a427, RV64, zdinx),
+            (0x0086b607, RV64, zdinx),
+            (0x00c6b427, RV64, zdinx),
+            (0xe0068653, RV64, zdinx),
+            (0xf0068653, RV64, zdinx),
+            (0xe2068653, RV64, zdinx),
+            (0xf2068653, RV64, zdinx),
+            // half precision needs F
+            (0x04e6f653, RV64, zdinx),
+            // odd registers for RV32 double operands
+            (0x02e68653, RV32, zdinx),
+            (0xd20606d3, RV32, zdinx),
+            (0x5a06f653, RV32, zdinx),
+            // Zfinx without Zdinx
+            (0x02e68653, RV64, zfinx),
+            (0x53077643, RV64, zfinx),
+            (0x00003607, RV64, with_d),
+            (0x00c03027, RV64, with_d),
+            (0x00003607, RV32, with_d),
+        ] {
+            assert_eq!(
+                decode(encoded, xlen, &extensions),
+                Err(IllegalInstruction(encoded)),
+                "{encoded:#010x}"
+            );
+        }
+    }
+
+    #[test]
+    fn decode_zfa() {
+        for (encoded, xlen, mnemonic) in [
+            (0xf0180653, RV64, "fli.s"),
+            (0xf2168653, RV64, "fli.d"),
+            (0xf41f0653, RV64, "fli.h"),
+            (0x28e6a653, RV64, "fminm.s"),
+            (0x2ae6b653, RV64, "fmaxm.d"),
+            (0x2ce6b653, RV64, "fmaxm.h"),
+            (0x4046f653, RV64, "fround.s"),
+            (0x4056f653, RV64, "froundnx.s"),
+            (0x42468653, RV64, "fround.d"),
+            (0xa0e6c653, RV64, "fleq.s"),
+            (0xa2e6d653, RV64, "fltq.d"),
+            (0xa4e6c653, RV64, "fleq.h"),
+            (0xc2869653, RV64, "fcvtmod.w.d"),
+            (0xe2168653, RV32, "fmvh.x.d"),
+            (0xb2e68653, RV32, "fmvp.d.x"),
+            // the shared encodings still decode as moves
+            (0xf0068653, RV64, "fmv.w.x"),
+            (0xe2068653, RV64, "fmv.x.d"),
+        ] {
+            assert_eq!(
+                decode(encoded, xlen, &Extensions::ALL).map(|i| i.mnemonic()),
+                Ok(mnemonic),
+                "{encoded:#010x}"
+            );
+        }
+        let no_zfa = Extensions {
+            zfa: false,
+            ..Extensions::ALL
+        };
+        let no_zfh = Extensions {
+            zfh: false,
+            zfhmin: false,
+            ..Extensions::ALL
+        };
+        for (encoded, xlen, extensions) in [
+            (0xf0180653, RV64, no_zfa),
+            (0x28e6a653, RV64, no_zfa),
+            (0xc2869653, RV64, no_zfa),
+            // fli needs rs2 = 1
+            (0xf0280653, RV64, Extensions::ALL),
+            // reserved rounding mode
+            (0x4046d653, RV64, Extensions::ALL),
+            // fcvtmod.w.d only encodes rtz
+            (0xc2868653, RV64, Extensions::ALL),
+            // the register pair moves are RV32 only
+            (0xe2168653, RV64, Extensions::ALL),
+            (0xb2e68653, RV64, Extensions::ALL),
+            (0xf41f0653, RV64, no_zfh),
+            (0xa4e6c653, RV64, no_zfh),
+        ] {
+            assert_eq!(
+                decode(encoded, xlen, &extensions),
+                Err(IllegalInstruction(encoded)),
+                "{encoded:#010x}"
+            );
+        }
+    }
+
+    #[test]
+    fn decode_bitmanip() {
+        for (encoded, mnemonic) in [
+            (0x20e6a633, "sh1add"),
+            (0x20e6c633, "sh2add"),
+            (0x20e6e633, "sh3add"),
+            (0x08e6863b, "add.uw"),
+            (0x20e6a63b, "sh1add.uw"),
+            (0x20e6c63b, "sh2add.uw"),
+            (0x20e6e63b, "sh3add.uw"),
+            (0x0a86961b, "slli.uw"),
+            (0x40e6f633, "andn"),
+            (0x40e6e633, "orn"),
+            (0x40e6c633, "xnor"),
+            (0x60069613, "clz"),
+            (0x60169613, "ctz"),
+            (0x60269613, "cpop"),
+            (0x0ae6e633, "max"),
+            (0x0ae6f633, "maxu"),
+            (0x0ae6c633, "min"),
+            (0x0ae6d633, "minu"),
+            (0x60469613, "sext.b"),
+            (0x60569613, "sext.h"),
+            (0x0806c63b, "zext.h"),
+            (0x60e69633, "rol"),
+            (0x60e6d633, "ror"),
+            (0x6286d613, "rori"),
+            (0x2876d613, "orc.b"),
+            (0x6b86d613, "rev8"),
+            (0x6006961b, "clzw"),
+            (0x6016961b, "ctzw"),
+            (0x6026961b, "cpopw"),
+            (0x60e6963b, "rolw"),
+            (0x60e6d63b, "rorw"),
+            (0x6146d61b, "roriw"),
+            (0x0ae69633, "clmul"),
+            (0x0ae6b633, "clmulh"),
+            (0x0ae6a633, "clmulr"),
+            (0x48e69633, "bclr"),
+            (0x4a869613, "bclri"),
+            (0x48e6d633, "bext"),
+            (0x4a86d613, "bexti"),
+            (0x68e69633, "binv"),
+            (0x6a869613, "binvi"),
+            (0x28e69633, "bset"),
+            (0x2a869613, "bseti"),
+        ] {
+            assert_eq!(
+                decode(encoded, RV64, &Extensions::ALL).map(|i| i.mnemonic()),
+                Ok(mnemonic),
+                "{encoded:#010x}"
+            );
+        }
+        // zext.h and rev8 have their own RV32 encodings
+        for (encoded, mnemonic) in [(0x0806c633, "zext.h"), (0x6986d613, "rev8")] {
+            assert_eq!(
+                decode(encoded, RV32, &Extensions::ALL).map(|i| i.mnemonic()),
+                Ok(mnemonic),
+                "{encoded:#010x}"
+            );
+        }
+        // Zbkb and Zbkc share rotations, logic with negate, rev8 and clmul
+        let no_zb = Extensions {
+            zba: false,
+            zbb: false,
+            zbc: false,
+            zbs: false,
+            zbkb: false,
+            zbkc: false,
+            ..Extensions::ALL
+        };
+        // zext.h is the rs2 = x0 form of pack and packw
+        let no_zbkb = Extensions {
+            zbkb: false,
+            ..Extensions::ALL
+        };
+        for (encoded, xlen, extensions) in [
+            // the .uw and W forms are RV64 only
+            (0x08e6863b, RV32, Extensions::ALL),
+            (0x6006961b, RV32, Extensions::ALL),
+            // rev8 and zext.h of the other XLEN
+            (0x6b86d613, RV32, Extensions::ALL),
+            (0x6986d613, RV64, Extensions::ALL),
+            (0x0806c633, RV64, no_zbkb),
+            // zext.h with rs2 != 0
+            (0x08e6c63b, RV64, no_zbkb),
+            // rori with shamt[5] set on RV32, roriw with shamt 32
+            (0x6286d613, RV32, Extensions::ALL),
+            (0x6206d61b, RV64, Extensions::ALL),
+            // unassigned unary funct12
+            (0x60369613, RV64, Extensions::ALL),
+            (0x2866d613, RV64, Extensions::ALL),
+            // disabled extensions
+            (0x20e6a633, RV64, no_zb),
+            (0x0a86961b, RV64, no_zb),
+            (0x40e6f633, RV64, no_zb),
+            (0x60069613, RV64, no_zb),
+            (0x6b86d613, RV64, no_zb),
+            (0x0ae69633, RV64, no_zb),
+            (0x2a869613, RV64, no_zb),
+        ] {
+            assert_eq!(
+                decode(encoded, xlen, &extensions),
+                Err(IllegalInstruction(encoded)),
+                "{encoded:#010x}"
+            );
+        }
+    }
+
+    #[test]
+    fn decode_vector() {
+        for (encoded, mnemonic) in [
+            (0x0d05f557, "vsetvli"),
+            (0xc0127557, "vsetivli"),
+            (0x80c5f557, "vsetvl"),
+            (0x02056407, "vle"),
+            (0x03050407, "vleff"),
+            (0x02b50087, "vlm"),
+            (0x0ab57407, "vlse"),
+            (0x06455407, "vluxei"),
+            (0x0c456407, "vloxei"),
+            (0x42055407, "vle"),
+            (0x22856407, "vlr"),
+            (0x02056427, "vse"),
+            (0x02b500a7, "vsm"),
+            (0x0ab50427, "vsse"),
+            (0x06457427, "vsuxei"),
+            (0x0e450427, "vsoxei"),
+            (0x62850427, "vsr"),
+            (0x022180d7, "vadd"),
+            (0x022540d7, "vadd"),
+            (0x022eb0d7, "vadd"),
+            (0x0e22b0d7, "vrsub"),
+            (0xc2432157, "vwaddu"),
+            (0xd6456157, "vwadd.w"),
+            (0x4a432157, "vzext.vf2"),
+            (0x4a41a157, "vsext.vf8"),
+            (0x402180d7, "vadc"),
+            (0x4620b0d7, "vmadc"),
+            (0x4e2180d7, "vmsbc"),
+            (0xb621b0d7, "vnsra"),
+            (0x7a2540d7, "vmsgtu"),
+            (0x9821a0d7, "vmulhsu"),
+            (0x8e2560d7, "vrem"),
+            (0xfa456157, "vwmaccus"),
+            (0x5c23b0d7, "vmerge"),
+            (0x5e0540d7, "vmv.v"),
+            (0x9e2180d7, "vsmul"),
+            (0xba20b0d7, "vnclipu"),
+            (0x262560d7, "vaadd"),
+            (0x022550d7, "vfadd"),
+            (0xe2431157, "vfwmul"),
+            (0xda455157, "vfwsub.w"),
+            (0xae3110d7, "vfnmsub"),
+            (0xf2455157, "vfwmacc"),
+            (0x4e2010d7, "vfsqrt"),
+            (0x4e2290d7, "vfrec7"),
+            (0x4e2810d7, "vfclass"),
+            (0x7e2550d7, "vmfge"),
+            (0x5c2550d7, "vfmerge"),
+            (0x5e0550d7, "vfmv.v.f"),
+            (0x4a2390d7, "vfcvt.rtz.x.f"),
+            (0x4a451157, "vfwcvt.f.xu"),
+            (0x4a2a90d7, "vfncvt.rod.f.f"),
+            (0x0221a0d7, "vredsum"),
+            (0xc22180d7, "vwredsumu"),
+            (0x0e2190d7, "vfredosum"),
+            (0xc62190d7, "vfwredusum"),
+            (0x6221a0d7, "vmandn"),
+            (0x7e21a0d7, "vmxnor"),
+            (0x42282557, "vcpop"),
+            (0x4028a557, "vfirst"),
+            (0x5220a0d7, "vmsbf"),
+            (0x522120d7, "vmsof"),
+            (0x522820d7, "viota"),
+            (0x5208a0d7, "vid"),
+            (0x42202557, "vmv.x.s"),
+            (0x420560d7, "vmv.s.x"),
+            (0x42201557, "vfmv.f.s"),
+            (0x420550d7, "vfmv.s.f"),
+            (0x3a21b0d7, "vslideup"),
+            (0x3e2540d7, "vslidedown"),
+            (0x3a2560d7, "vslide1up"),
+            (0x3e2550d7, "vfslide1down"),
+            (0x322180d7, "vrgather"),
+            (0x3a2180d7, "vrgatherei16"),
+            (0x5e21a0d7, "vcompress"),
+            (0x9e40b157, "vmvnr"),
+        ] {
+            assert_eq!(
+                decode(encoded, RV64, &Extensions::ALL).map(|i| i.mnemonic()),
+                Ok(mnemonic),
+                "{encoded:#010x}"
+            );
+        }
+        let encodings = [
+            // vmv3r.v v2, v4
+            0x9e413157, // vl3re32.v v8, (a0)
+            0x42856407, // vlseg3e16.v with mew set
+            0x52055407, // funct6 0b000001 of OPIVV
+            0x062180d7,
+        ];
+        for encoded in encodings {
+            assert_eq!(
+                decode(encoded, RV64, &Extensions::ALL),
+                Err(IllegalInstruction(encoded)),
+                "{encoded:#010x}"
+            );
+        }
+        let no_v = Extensions {
+            v: false,
+            ..Extensions::ALL
+        };
+        for encoded in [0x0d05f557, 0x02056407, 0x022180d7] {
+            assert_eq!(
+                decode(encoded, RV64, &no_v),
+                Err(IllegalInstruction(encoded))
+            );
+        }
+    }
+
+    #[test]
+    fn decode_crypto() {
+        for (encoded, mnemonic) in [
+            (0x08e6c633, "pack"),
+            (0x08e6f633, "packh"),
+            (0x08e6c63b, "packw"),
+            (0x6876d613, "brev8"),
+            (0x28e6a633, "xperm4"),
+            (0x28e6c633, "xperm8"),
+            (0x32e68633, "aes64es"),
+            (0x36e68633, "aes64esm"),
+            (0x3ae68633, "aes64ds"),
+            (0x3ee68633, "aes64dsm"),
+            (0x30069613, "aes64im"),
+            (0x31569613, "aes64ks1i"),
+            (0x7ee68633, "aes64ks2"),
+            (0x10269613, "sha256sig0"),
+            (0x10369613, "sha256sig1"),
+            (0x10069613, "sha256sum0"),
+            (0x10169613, "sha256sum1"),
+            (0x10669613, "sha512sig0"),
+            (0x10769613, "sha512sig1"),
+            (0x10469613, "sha512sum0"),
+            (0x10569613, "sha512sum1"),
+            (0xb0e68633, "sm4ed"),
+            (0xf4e68633, "sm4ks"),
+            (0x10869613, "sm3p0"),
+            (0x10969613, "sm3p1"),
+        ] {
+            assert_eq!(
+                decode(encoded, RV64, &Extensions::ALL).map(|i| i.mnemonic()),
+                Ok(mnemonic),
+                "{encoded:#010x}"
+            );
+        }
+        for (encoded, mnemonic) in [
+            (0x08f69613, "zip"),
+            (0x08f6d613, "unzip"),
+            (0x6ae68633, "aes32dsi"),
+            (0xaee68633, "aes32dsmi"),
+            (0xe2e68633, "aes32esi"),
+            (0x26e68633, "aes32esmi"),
+            (0x5ce68633, "sha512sig0h"),
+            (0x54e68633, "sha512sig0l"),
+            (0x5ee68633, "sha512sig1h"),
+            (0x56e68633, "sha512sig1l"),
+            (0x50e68633, "sha512sum0r"),
+            (0x52e68633, "sha512sum1r"),
+            (0xb0e68633, "sm4ed"),
+        ] {
+            assert_eq!(
+                decode(encoded, RV32, &Extensions::ALL).map(|i| i.mnemonic()),
+                Ok(mnemonic),
+                "{encoded:#010x}"
+            );
+        }
+        let no_zk = Extensions {
+            zbkb: false,
+            zbkc: false,
+            zbkx: false,
+            zknd: false,
+            zkne: false,
+            zknh: false,
+            zksed: false,
+            zksh: false,
+            ..Extensions::ALL
+        };
+        for (encoded, xlen, extensions) in [
+            // the aes32, zip and sha512 halves are RV32 only
+            (0x08f69613, RV64, Extensions::ALL),
+            (0x6ae68633, RV64, Extensions::ALL),
+            (0x5ce68633, RV64, Extensions::ALL),
+            // and the aes64, packw and full sha512 ops RV64 only
+            (0x32e68633, RV32, Extensions::ALL),
+            (0x30069613, RV32, Extensions::ALL),
+            (0x10669613, RV32, Extensions::ALL),
+            // aes64ks1i with the reserved rnum 0xb
+            (0x31b69613, RV64, Extensions::ALL),
+            // disabled extensions
+            (0x08e6c633, RV64, no_zk),
+            (0x6876d613, RV64, no_zk),
+            (0x28e6a633, RV64, no_zk),
+            (0x32e68633, RV64, no_zk),
+            (0x3ae68633, RV64, no_zk),
+            (0x10269613, RV64, no_zk),
+            (0xb0e68633, RV64, no_zk),
+            (0x10869613, RV64, no_zk),
+        ] {
+            assert_eq!(
+                decode(encoded, xlen, &extensions),
+                Err(IllegalInstruction(encoded)),
+                "{encoded:#010x}"
+            );
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use elf::{endian::LittleEndian, ElfBytes, ParseError};
 
-pub(crate) fn load_elf_le(data: &[u8]) -> Result<ElfBytes<LittleEndian>, ParseError> {
+pub fn load_elf_le(data: &[u8]) -> Result<ElfBytes<'_, LittleEndian>, ParseError> {
     ElfBytes::<LittleEndian>::minimal_parse(data)
 }
@@ -1,4 +0,0 @@
-#[derive(Debug)]
-pub(crate) enum Error {
-    InvalidOpCode,
-}
@@ -0,0 +1,6169 @@
+use crate::compressed;
+use crate::csr::{CsrRead, CsrSpec};
+use crate::decode::{self, Instruction};
+use crate::entropy::Entropy;
+use crate::instructions::*;
+use crate::isa::{Extensions, Privilege, Xlen};
+use crate::mem::Reservation;
+use crate::mmu::{Bus, Context, Tlb};
+use crate::num::As;
+use crate::ops::*;
+use crate::registers::{
+    RegFile, EVENT_BRANCH_TAKEN, EVENT_FLOAT, EVENT_LOAD, EVENT_STORE, MCAUSE, MEDELEG, MEPC,
+    MIDELEG, MIE, MIP, MSTATUS, MSTATUS_MIE, MSTATUS_MPIE, MSTATUS_MPP, MSTATUS_SIE, MSTATUS_SPIE,
+    MSTATUS_SPP, MTVAL, MTVEC, MTVEC_VECTORED, SATP, SCAUSE, SEPC, STVAL, STVEC,
+};
+use crate::trap::{Exception, Interrupt};
+use crate::vector::{Addressing, Conversion, SetFirst, Shape};
+
+/// A single RISC-V hardware thread: its register file and program counter.
+#[derive(Debug)]
+pub struct Hart<T> {
+    pub regfile: RegFile<T>,
+    pub pc: T,
+    pub extensions: Extensions,
+    /// Bytes reserved by the last LR, if no SC or conflicting store followed.
+    pub reservation: Option<Reservation>,
+    /// Source behind the `seed` CSR.
+    pub entropy: Entropy,
+    /// Privilege mode the hart executes in.
+    pub privilege: Privilege,
+    /// Translations of recently accessed virtual pages.
+    pub tlb: Tlb,
+}
+
+impl<T: Copy + Default + As<u64>> Hart<T>
+where
+    u16: As<T>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    pub fn new(pc: T) -> Self {
+        Self {
+            regfile: RegFile::default(),
+            pc,
+            extensions: Extensions::default(),
+            reservation: None,
+            entropy: Entropy::new(),
+            privilege: Privilege::default(),
+            tlb: Tlb::default(),
+        }
+    }
+}
+
+impl<T: Copy + Default + As<u64>> Default for Hart<T>
+where
+    u16: As<T>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: BaseInstruction> Hart<T>
+where
+    u64: As<T>,
+{
+    /// Fetches the instruction at `pc` and executes it, or takes the pending
+    /// interrupt instead if there is one.
+    ///
+    /// On failure `pc` still points at the instruction and the raised
+    /// exception is returned, `trap` then enters its handler. The instruction
+    /// is restartable rather than without effect: a vector load or store
+    /// keeps the elements before `vstart` written, and `cm.push` or `cm.pop`
+    /// the stack slots or registers it already transferred, which a restart
+    /// transfers again.
+    #[inline(always)]
+    pub fn step(&mut self, memory: &mut [u8]) -> Result<(), Exception> {
+        if let Some(interrupt) = self.pending_interrupt() {
+            self.interrupt(interrupt);
+            return Ok(());
+        }
+        let pc: usize = self.pc.r#as();
+        if !pc.is_multiple_of(self.extensions.ialign() as usize) {
+            return Err(Exception::InstructionAddressMisaligned(pc as u64));
+        }
+        let context = self.translation();
+        let mut bus = Bus::new(memory, &mut self.tlb, context);
+        // Instructions are fetched in 16-bit parcels, the low one giving the length
+        let low = bus.fetch(pc)?;
+        let encoded = if compressed::is_compressed(low) {
+            low as u32
+        } else {
+            low as u32 | (bus.fetch(pc.wrapping_add(2))? as u32) << 16
+        };
+        self.execute(encoded, memory)
+    }
+
+    /// Enters the trap handler for `exception`, raised by the instruction at
+    /// `pc`.
+    ///
+    /// Traps from below M-mode go to S-mode if their `medeleg` bit is set,
+    /// and to M-mode otherwise. `xepc`, `xcause` and `xtval` record the trap
+    /// and `mstatus` stacks the interrupt enable and privilege mode, for
+    /// `xret` to restore. Vectored `xtvec` only offsets interrupts,
+    /// exceptions always enter at its base.
+    pub fn trap(&mut self, exception: Exception) {
+        let medeleg: u64 = self.regfile.csrs.get(MEDELEG).r#as();
+        let delegated =
+            self.privilege != Privilege::Machine && medeleg >> exception.code() & 1 != 0;
+        self.enter(exception.code(), exception.tval(), false, delegated);
+    }
+
+    /// Enters the trap handler for `interrupt` before the instruction at `pc`,
+    /// which `xepc` records.
+    ///
+    /// It goes to S-mode if its `mideleg` bit is set, and to M-mode
+    /// otherwise. `xcause` has its interrupt bit set and a vectored `xtvec`
+    /// enters at `BASE + 4 * cause`.
+    pub fn interrupt(&mut self, interrupt: Interrupt) {
+        let mideleg: u64 = self.regfile.csrs.get(MIDELEG).r#as();
+        let delegated =
+            self.privilege != Privilege::Machine && mideleg >> interrupt.code() & 1 != 0;
+        self.enter(interrupt.code(), 0, true, delegated);
+    }
+
+    /// The interrupt to take before the next instruction, if any.
+    ///
+    /// An interrupt is pending when it is set in both `mip` and `mie`. Those
+    /// for M-mode are enabled below M-mode and by `mstatus.MIE` in M-mode,
+    /// the ones `mideleg` delegates are enabled below S-mode and by
+    /// `mstatus.SIE` in S-mode. Interrupts for M-mode come first, then the
+    /// order of `Interrupt::PRIORITY`.
+    pub fn pending_interrupt(&self) -> Option<Interrupt> {
+        let csrs = &self.regfile.csrs;
+        let mip: u64 = csrs.get(MIP).r#as();
+        let mie: u64 = csrs.get(MIE).r#as();
+        let mideleg: u64 = csrs.get(MIDELEG).r#as();
+        let mstatus: u64 = csrs.get(MSTATUS).r#as();
+        let pending = mip & mie;
+        let machine = if self.privilege < Privilege::Machine || mstatus & MSTATUS_MIE != 0 {
+            pending & !mideleg
+        } else {
+            0
+        };
+        let supervisor = if self.privilege < Privilege::Supervisor
+            || self.privilege == Privilege::Supervisor && mstatus & MSTATUS_SIE != 0
+        {
+            pending & mideleg
+        } else {
+            0
+        };
+        let pending = if machine != 0 { machine } else { supervisor };
+        Interrupt::PRIORITY
+            .into_iter()
+            .find(|interrupt| pending >> interrupt.code() & 1 != 0)
+    }
+
+    /// Records a trap with cause `code` in the CSRs of S-mode if `delegated`
+    /// and of M-mode otherwise, and jumps to its handler.
+    fn enter(&mut self, code: u64, tval: u64, interrupt: bool, delegated: bool) {
+        let csrs = &mut self.regfile.csrs;
+        let cause = if interrupt {
+            let msb = if T::XLEN == Xlen::Rv64 { 63 } else { 31 };
+            1 << msb | code
+        } else {
+            code
+        };
+        let mstatus: u64 = csrs.get(MSTATUS).r#as();
+        let (tvec, mstatus) = if delegated {
+            csrs.set(SEPC, self.pc);
+            csrs.set(SCAUSE, cause.r#as());
+            csrs.set(STVAL, tval.r#as());
+            let spie = if mstatus & MSTATUS_SIE != 0 {
+                MSTATUS_SPIE
+            } else {
+                0
+            };
+            let spp = if self.privilege == Privilege::Supervisor {
+                MSTATUS_SPP
+            } else {
+                0
+            };
+            self.privilege = Privilege::Supervisor;
+            let mstatus = mstatus & !(MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP) | spie | spp;
+            (csrs.get(STVEC), mstatus)
+        } else {
+            csrs.set(MEPC, self.pc);
+            csrs.set(MCAUSE, cause.r#as());
+            csrs.set(MTVAL, tval.r#as());
+            let mpie = if mstatus & MSTATUS_MIE != 0 {
+                MSTATUS_MPIE
+            } else {
+                0
+            };
+            let mpp = (self.privilege as u64) << 11;
+            self.privilege = Privilege::Machine;
+            let mstatus = mstatus & !(MSTATUS_MIE | MSTATUS_MPIE | MSTATUS_MPP) | mpie | mpp;
+            (csrs.get(MTVEC), mstatus)
+        };
+        csrs.set(MSTATUS, mstatus.r#as());
+        let tvec: u64 = tvec.r#as();
+        let offset = if interrupt && tvec & 0b11 == MTVEC_VECTORED {
+            4 * code
+        } else {
+            0
+        };
+        self.pc = ((tvec & !0b11) + offset).r#as();
+    }
+
+    /// Decodes and executes `encoded` as if it had been fetched at `pc`.
+    ///
+    /// Compressed instructions are given in the low 16 bits of `encoded`.
+    #[inline(always)]
+    pub fn execute(&mut self, encoded: u32, memory: &mut [u8]) -> Result<(), Exception> {
+        let (instruction, len) = if compressed::is_compressed(encoded as u16) {
+            let encoded = encoded as u16;
+            (
+                decode::decode_compressed(encoded, T::XLEN, &self.extensions)?,
+                2,
+            )
+        } else {
+            (decode::decode(encoded, T::XLEN, &self.extensions)?, 4)
+        };
+        self.execute_decoded(instruction, len, memory)
+            .map_err(|err| match err {
+                Exception::IllegalInstruction(_) => Exception::IllegalInstruction(encoded),
+                err => err,
+            })
+    }
+
+    /// Executes an already decoded instruction as if it had been fetched at `pc`.
+    ///
+    /// `len` is the length in bytes of its encoding, 2 for compressed
+    /// instructions and 4 otherwise. The counters advance once it retires.
+    pub fn execute_decoded(
+        &mut self,
+        instruction: Instruction,
+        len: u8,
+        memory: &mut [u8],
+    ) -> Result<(), Exception> {
+        let taken = self.dispatch(instruction, len, memory)?;
+        let events = [
+            (instruction.is_load(), EVENT_LOAD),
+            (instruction.is_store(), EVENT_STORE),
+            (taken, EVENT_BRANCH_TAKEN),
+            (instruction.is_float(), EVENT_FLOAT),
+        ]
+        .into_iter()
+        .filter(|&(raised, _)| raised)
+        .fold(0, |events, (_, event)| events | 1 << event);
+        self.regfile.csrs.retire(events, instruction.csr_write());
+        Ok(())
+    }
+
+    /// What address translation depends on, as the CSRs and privilege mode
+    /// stand.
+    fn translation(&self) -> Context {
+        let csrs = &self.regfile.csrs;
+        Context::new(
+            csrs.get(SATP).r#as(),
+            T::XLEN,
+            self.privilege,
+            csrs.data_privilege(self.privilege),
+            csrs.get(MSTATUS).r#as(),
+            &self.extensions,
+        )
+    }
+
+    /// Raises an illegal instruction if a CSR instruction may not access its
+    /// CSR.
+    fn csr_access(&mut self, instruction: Instruction) -> Result<(), Exception> {
+        let Some(reg) = instruction.csr() else {
+            return Ok(());
+        };
+        let write = instruction.csr_write().is_some();
+        if !self
+            .regfile
+            .csrs
+            .accessible(reg, write, self.privilege, &self.extensions)
+        {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        Ok(())
+    }
+
+    /// Executes `instruction`, returning whether it is a branch that was
+    /// taken.
+    fn dispatch(
+        &mut self,
+        instruction: Instruction,
+        len: u8,
+        memory: &mut [u8],
+    ) -> Result<bool, Exception> {
+        self.csr_access(instruction)?;
+        let ialign = self.extensions.ialign();
+        let context = self.translation();
+        let memory = &mut Bus::new(memory, &mut self.tlb, context);
+        let RegFile {
+            xregs, fregs, csrs, ..
+        } = &mut self.regfile;
+        let pc = &mut self.pc;
+        let reservation = &mut self.reservation;
+        let entropy = &mut self.entropy;
+        let privilege = &mut self.privilege;
+        let extensions = &self.extensions;
+        // Zicsr instructions skip the read or the write depending on rd and rs1
+        let read = instruction.csr_read().is_some();
+        let write = instruction.csr_write().is_some();
+        let is_seed = |i: decode::I| {
+            CsrSpec::of(i.imm.as_u16() as usize).is_some_and(|spec| spec.read == CsrRead::Entropy)
+        };
+        // Zcmp and Zcmt access XLEN-sized stack slots and table entries
+        let rv64 = T::XLEN == Xlen::Rv64;
+        match instruction {
+            // Control transfer instructions update pc themselves
+            Instruction::Jal(j) => return T::jal(j, xregs, pc, len, ialign).map(|()| false),
+            Instruction::Jalr(i) => return T::jalr(i, xregs, pc, len, ialign).map(|()| false),
+            Instruction::Beq(b) => return T::branch(b, xregs, pc, len, ialign, Beq::beq),
+            Instruction::Bne(b) => return T::branch(b, xregs, pc, len, ialign, Bne::bne),
+            Instruction::Blt(b) => return T::branch(b, xregs, pc, len, ialign, Blt::blt),
+            Instruction::Bge(b) => return T::branch(b, xregs, pc, len, ialign, Bge::bge),
+            Instruction::Bltu(b) => return T::branch(b, xregs, pc, len, ialign, Bltu::bltu),
+            Instruction::Bgeu(b) => return T::branch(b, xregs, pc, len, ialign, Bgeu::bgeu),
+            Instruction::CmPopret(c) | Instruction::CmPopretz(c) => {
+                let zero = matches!(instruction, Instruction::CmPopretz(_));
+                let load = if rv64 { Ld::ld } else { Lw::lw };
+                return T::popret(c, xregs, memory, pc, ialign, zero, load).map(|()| false);
+            }
+            Instruction::CmJt(c) | Instruction::CmJalt(c) => {
+                return T::table_jump(c, xregs, csrs, memory, pc, ialign).map(|()| false);
+            }
+            Instruction::Mret(_) => {
+                return T::mret(csrs, extensions, pc, privilege, ialign).map(|()| false)
+            }
+            Instruction::Sret(_) => return T::sret(csrs, pc, privilege, ialign).map(|()| false),
+            Instruction::Wfi(_) => T::wfi(csrs, extensions, *privilege)?,
+            Instruction::SfenceVma(r) => T::sfence_vma(r, xregs, csrs, memory.tlb(), *privilege)?,
+            Instruction::Ecall(_) => {
+                return Err(match *privilege {
+                    Privilege::User => Exception::EnvironmentCallFromUMode,
+                    Privilege::Supervisor => Exception::EnvironmentCallFromSMode,
+                    Privilege::Machine => Exception::EnvironmentCallFromMMode,
+                });
+            }
+            Instruction::Ebreak(_) => {
+                return Err(Exception::Breakpoint(<T as As<u64>>::r#as(*pc)));
+            }
+            Instruction::Lui(u) => T::lui(u, xregs)?,
+            Instruction::Auipc(u) => T::auipc(u, xregs, *pc)?,
+            Instruction::Lb(i) => T::load(i, xregs, memory, Lb::lb)?,
+            Instruction::Lh(i) => T::load(i, xregs, memory, Lh::lh)?,
+            Instruction::Lw(i) => T::load(i, xregs, memory, Lw::lw)?,
+            Instruction::Lbu(i) => T::load(i, xregs, memory, Lbu::lbu)?,
+            Instruction::Lhu(i) => T::load(i, xregs, memory, Lhu::lhu)?,
+            Instruction::Lwu(i) => T::load(i, xregs, memory, Lwu::lwu)?,
+            Instruction::Ld(i) => T::load(i, xregs, memory, Ld::ld)?,
+            Instruction::Sb(s) => T::store(s, xregs, memory, reservation, Sb::sb)?,
+            Instruction::Sh(s) => T::store(s, xregs, memory, reservation, Sh::sh)?,
+            Instruction::Sw(s) => T::store(s, xregs, memory, reservation, Sw::sw)?,
+            Instruction::Sd(s) => T::store(s, xregs, memory, reservation, Sd::sd)?,
+            Instruction::CmPush(c) if rv64 => T::push(c, xregs, memory, reservation, Sd::sd)?,
+            Instruction::CmPush(c) => T::push(c, xregs, memory, reservation, Sw::sw)?,
+            Instruction::CmPop(c) if rv64 => T::pop(c, xregs, memory, Ld::ld)?,
+            Instruction::CmPop(c) => T::pop(c, xregs, memory, Lw::lw)?,
+            Instruction::CmMvsa01(c) => T::mvsa01(c, xregs)?,
+            Instruction::CmMva01s(c) => T::mva01s(c, xregs)?,
+            Instruction::Addi(i) => T::mathi(i, xregs, Addi::addi)?,
+            Instruction::Slti(i) => T::mathi(i, xregs, Slti::slti)?,
+            Instruction::Sltiu(i) => T::mathi(i, xregs, Sltiu::sltiu)?,
+            Instruction::Xori(i) => T::mathi(i, xregs, Xori::xori)?,
+            Instruction::Ori(i) => T::mathi(i, xregs, Ori::ori)?,
+            Instruction::Andi(i) => T::mathi(i, xregs, Andi::andi)?,
+            Instruction::Addiw(i) => T::mathi(i, xregs, Addiw::addiw)?,
+            Instruction::Slli(s) => T::shifti(s, xregs, Slli::slli)?,
+            Instruction::Srli(s) => T::shifti(s, xregs, Srli::srli)?,
+            Instruction::Srai(s) => T::shifti(s, xregs, Srai::srai)?,
+            Instruction::Slliw(s) => T::shiftiw(s, xregs, Slliw::slliw)?,
+            Instruction::Srliw(s) => T::shiftiw(s, xregs, Srliw::srliw)?,
+            Instruction::Sraiw(s) => T::shiftiw(s, xregs, Sraiw::sraiw)?,
+            Instruction::Add(r) => T::math(r, xregs, Add::add)?,
+            Instruction::Sub(r) => T::math(r, xregs, Sub::sub)?,
+            Instruction::Sll(r) => T::math(r, xregs, Sll::sll)?,
+            Instruction::Slt(r) => T::math(r, xregs, Slt::slt)?,
+            Instruction::Sltu(r) => T::math(r, xregs, Sltu::sltu)?,
+            Instruction::Xor(r) => T::math(r, xregs, Xor::xor)?,
+            Instruction::Srl(r) => T::math(r, xregs, Srl::srl)?,
+            Instruction::Sra(r) => T::math(r, xregs, Sra::sra)?,
+            Instruction::Or(r) => T::math(r, xregs, Or::or)?,
+            Instruction::And(r) => T::math(r, xregs, And::and)?,
+            Instruction::Addw(r) => T::math(r, xregs, Addw::addw)?,
+            Instruction::Subw(r) => T::math(r, xregs, Subw::subw)?,
+            Instruction::Sllw(r) => T::math(r, xregs, Sllw::sllw)?,
+            Instruction::Srlw(r) => T::math(r, xregs, Srlw::srlw)?,
+            Instruction::Sraw(r) => T::math(r, xregs, Sraw::sraw)?,
+            Instruction::LrW(a) => T::lr(a, xregs, memory, reservation, Lw::lw)?,
+            Instruction::ScW(a) => T::sc(a, xregs, memory, reservation, Sw::sw)?,
+            Instruction::AmoswapW(a) => T::amo(
+                a,
+                xregs,
+                memory,
+                reservation,
+                Lw::lw,
+                Sw::sw,
+                Amoswap::amoswap,
+            )?,
+            Instruction::AmoaddW(a) => {
+                T::amo(a, xregs, memory, reservation, Lw::lw, Sw::sw, Add::add)?
+            }
+            Instruction::AmoxorW(a) => {
+                T::amo(a, xregs, memory, reservation, Lw::lw, Sw::sw, Xor::xor)?
+            }
+            Instruction::AmoandW(a) => {
+                T::amo(a, xregs, memory, reservation, Lw::lw, Sw::sw, And::and)?
+            }
+            Instruction::AmoorW(a) => {
+                T::amo(a, xregs, memory, reservation, Lw::lw, Sw::sw, Or::or)?
+            }
+            Instruction::AmominW(a) => T::amo(
+                a,
+                xregs,
+                memory,
+                reservation,
+                Lw::lw,
+                Sw::sw,
+                Amomin::amomin,
+            )?,
+            Instruction::AmomaxW(a) => T::amo(
+                a,
+                xregs,
+                memory,
+                reservation,
+                Lw::lw,
+                Sw::sw,
+                Amomax::amomax,
+            )?,
+            Instruction::AmominuW(a) => T::amo(
+                a,
+                xregs,
+                memory,
+                reservation,
+                Lw::lw,
+                Sw::sw,
+                Amominu::amominu,
+            )?,
+            Instruction::AmomaxuW(a) => T::amo(
+                a,
+                xregs,
+                memory,
+                reservation,
+                Lw::lw,
+                Sw::sw,
+                Amomaxu::amomaxu,
+            )?,
+            Instruction::LrD(a) => T::lr(a, xregs, memory, reservation, Ld::ld)?,
+            Instruction::ScD(a) => T::sc(a, xregs, memory, reservation, Sd::sd)?,
+            Instruction::AmoswapD(a) => T::amo(
+                a,
+                xregs,
+                memory,
+                reservation,
+                Ld::ld,
+                Sd::sd,
+                Amoswap::amoswap,
+            )?,
+            Instruction::AmoaddD(a) => {
+                T::amo(a, xregs, memory, reservation, Ld::ld, Sd::sd, Add::add)?
+            }
+            Instruction::AmoxorD(a) => {
+                T::amo(a, xregs, memory, reservation, Ld::ld, Sd::sd, Xor::xor)?
+            }
+            Instruction::AmoandD(a) => {
+                T::amo(a, xregs, memory, reservation, Ld::ld, Sd::sd, And::and)?
+            }
+            Instruction::AmoorD(a) => {
+                T::amo(a, xregs, memory, reservation, Ld::ld, Sd::sd, Or::or)?
+            }
+            Instruction::AmominD(a) => T::amo(
+                a,
+                xregs,
+                memory,
+                reservation,
+                Ld::ld,
+                Sd::sd,
+                Amomin::amomin,
+            )?,
+            Instruction::AmomaxD(a) => T::amo(
+                a,
+                xregs,
+                memory,
+                reservation,
+                Ld::ld,
+                Sd::sd,
+                Amomax::amomax,
+            )?,
+            Instruction::AmominuD(a) => T::amo(
+                a,
+                xregs,
+                memory,
+                reservation,
+                Ld::ld,
+                Sd::sd,
+                Amominu::amominu,
+            )?,
+            Instruction::AmomaxuD(a) => T::amo(
+                a,
+                xregs,
+                memory,
+                reservation,
+                Ld::ld,
+                Sd::sd,
+                Amomaxu::amomaxu,
+            )?,
+            // No caches or reordering to synchronize with
+            Instruction::Fence(_) | Instruction::FenceI(_) => {}
+            Instruction::Csrrw(i)
+            | Instruction::Csrrs(i)
+            | Instruction::Csrrc(i)
+            | Instruction::Csrrwi(i)
+            | Instruction::Csrrsi(i)
+            | Instruction::Csrrci(i)
+                if is_seed(i) =>
+            {
+                T::seed(i, xregs, entropy, read, write)?
+            }
+            Instruction::Csrrw(i) => T::csr(i, xregs, csrs, extensions, read, write, Csrrw::csrrw)?,
+            Instruction::Csrrs(i) => T::csr(i, xregs, csrs, extensions, read, write, Csrrs::csrrs)?,
+            Instruction::Csrrc(i) => T::csr(i, xregs, csrs, extensions, read, write, Csrrc::csrrc)?,
+            Instruction::Csrrwi(i) => {
+                T::csri(i, xregs, csrs, extensions, read, write, Csrrwi::csrrwi)?
+            }
+            Instruction::Csrrsi(i) => {
+                T::csri(i, xregs, csrs, extensions, read, write, Csrrsi::csrrsi)?
+            }
+            Instruction::Csrrci(i) => {
+                T::csri(i, xregs, csrs, extensions, read, write, Csrrci::csrrci)?
+            }
+            Instruction::Mul(r) => T::math(r, xregs, Mul::mul)?,
+            Instruction::Mulh(r) => T::math(r, xregs, Mulh::mulh)?,
+            Instruction::Mulhsu(r) => T::math(r, xregs, Mulhsu::mulhsu)?,
+            Instruction::Mulhu(r) => T::math(r, xregs, Mulhu::mulhu)?,
+            Instruction::Div(r) => T::math(r, xregs, Div::div)?,
+            Instruction::Divu(r) => T::math(r, xregs, Divu::divu)?,
+            Instruction::Rem(r) => T::math(r, xregs, Rem::rem)?,
+            Instruction::Remu(r) => T::math(r, xregs, Remu::remu)?,
+            Instruction::Mulw(r) => T::math(r, xregs, Mulw::mulw)?,
+            Instruction::Divw(r) => T::math(r, xregs, Divw::divw)?,
+            Instruction::Divuw(r) => T::math(r, xregs, Divuw::divuw)?,
+            Instruction::Remw(r) => T::math(r, xregs, Remw::remw)?,
+            Instruction::Remuw(r) => T::math(r, xregs, Remuw::remuw)?,
+            Instruction::Flw(i) => T::fload(i, xregs, fregs, memory, Flw::flw)?,
+            Instruction::Fsw(s) => T::fstore(s, xregs, fregs, memory, reservation, Fsw::fsw)?,
+            Instruction::FmaddS(r4) => {
+                T::fmas(r4, &mut self.regfile, &self.extensions, Fmadd::fmadd)?
+            }
+            Instruction::FmsubS(r4) => {
+                T::fmas(r4, &mut self.regfile, &self.extensions, Fmsub::fmsub)?
+            }
+            Instruction::FnmsubS(r4) => {
+                T::fmas(r4, &mut self.regfile, &self.extensions, Fnmsub::fnmsub)?
+            }
+            Instruction::FnmaddS(r4) => {
+                T::fmas(r4, &mut self.regfile, &self.extensions, Fnmadd::fnmadd)?
+            }
+            Instruction::FaddS(r) => T::floats(r, &mut self.regfile, &self.extensions, Fadd::fadd)?,
+            Instruction::FsubS(r) => T::floats(r, &mut self.regfile, &self.extensions, Fsub::fsub)?,
+            Instruction::FmulS(r) => T::floats(r, &mut self.regfile, &self.extensions, Fmul::fmul)?,
+            Instruction::FdivS(r) => T::floats(r, &mut self.regfile, &self.extensions, Fdiv::fdiv)?,
+            Instruction::FsqrtS(r) => {
+                T::floats(r, &mut self.regfile, &self.extensions, Fsqrt::fsqrt)?
+            }
+            Instruction::FsgnjS(r) => {
+                T::floats(r, &mut self.regfile, &self.extensions, Fsgnj::fsgnj)?
+            }
+            Instruction::FsgnjnS(r) => {
+                T::floats(r, &mut self.regfile, &self.extensions, Fsgnjn::fsgnjn)?
+            }
+            Instruction::FsgnjxS(r) => {
+                T::floats(r, &mut self.regfile, &self.extensions, Fsgnjx::fsgnjx)?
+            }
+            Instruction::FminS(r) => T::floats(r, &mut self.regfile, &self.extensions, Fmin::fmin)?,
+            Instruction::FmaxS(r) => T::floats(r, &mut self.regfile, &self.extensions, Fmax::fmax)?,
+            Instruction::FcvtWS(r) => {
+                T::floats_to_x(r, &mut self.regfile, &self.extensions, Fcvtws::fcvtws)?
+            }
+            Instruction::FcvtWuS(r) => {
+                T::floats_to_x(r, &mut self.regfile, &self.extensions, Fcvtwus::fcvtwus)?
+            }
+            Instruction::FmvXW(r) => {
+                T::floats_raw_to_x(r, &mut self.regfile, &self.extensions, Fmvxw::fmvxw)?
+            }
+            Instruction::FeqS(r) => {
+                T::floats_to_x(r, &mut self.regfile, &self.extensions, Feq::feq)?
+            }
+            Instruction::FltS(r) => {
+                T::floats_to_x(r, &mut self.regfile, &self.extensions, Flt::flt)?
+            }
+            Instruction::FleS(r) => {
+                T::floats_to_x(r, &mut self.regfile, &self.extensions, Fle::fle)?
+            }
+            Instruction::FclassS(r) => {
+                T::floats_to_x(r, &mut self.regfile, &self.extensions, Fclass::fclass)?
+            }
+            Instruction::FcvtSW(r) => {
+                T::floats_from_x(r, &mut self.regfile, &self.extensions, Fcvtsw::fcvtsw)?
+            }
+            Instruction::FcvtSWu(r) => {
+                T::floats_from_x(r, &mut self.regfile, &self.extensions, Fcvtswu::fcvtswu)?
+            }
+            Instruction::FmvWX(r) => {
+                T::floats_from_x(r, &mut self.regfile, &self.extensions, Fmvwx::fmvwx)?
+            }
+            Instruction::FcvtLS(r) => {
+                T::floats_to_x64(r, &mut self.regfile, &self.extensions, Fcvtls::fcvtls)?
+            }
+            Instruction::FcvtLuS(r) => {
+                T::floats_to_x64(r, &mut self.regfile, &self.extensions, Fcvtlus::fcvtlus)?
+            }
+            Instruction::FcvtSL(r) => {
+                T::floats_from_x64(r, &mut self.regfile, &self.extensions, Fcvtsl::fcvtsl)?
+            }
+            Instruction::FcvtSLu(r) => {
+                T::floats_from_x64(r, &mut self.regfile, &self.extensions, Fcvtslu::fcvtslu)?
+            }
+            Instruction::Fld(i) => T::fload_d(i, xregs, fregs, memory, Fld::fld)?,
+            Instruction::Fsd(s) => T::fstore_d(s, xregs, fregs, memory, reservation, Fsd::fsd)?,
+            Instruction::FmaddD(r4) => {
+                T::fmad(r4, &mut self.regfile, &self.extensions, Fmadd::fmadd)?
+            }
+            Instruction::FmsubD(r4) => {
+                T::fmad(r4, &mut self.regfile, &self.extensions, Fmsub::fmsub)?
+            }
+            Instruction::FnmsubD(r4) => {
+                T::fmad(r4, &mut self.regfile, &self.extensions, Fnmsub::fnmsub)?
+            }
+            Instruction::FnmaddD(r4) => {
+                T::fmad(r4, &mut self.regfile, &self.extensions, Fnmadd::fnmadd)?
+            }
+            Instruction::FaddD(r) => T::floatd(r, &mut self.regfile, &self.extensions, Fadd::fadd)?,
+            Instruction::FsubD(r) => T::floatd(r, &mut self.regfile, &self.extensions, Fsub::fsub)?,
+            Instruction::FmulD(r) => T::floatd(r, &mut self.regfile, &self.extensions, Fmul::fmul)?,
+            Instruction::FdivD(r) => T::floatd(r, &mut self.regfile, &self.extensions, Fdiv::fdiv)?,
+            Instruction::FsqrtD(r) => {
+                T::floatd(r, &mut self.regfile, &self.extensions, Fsqrt::fsqrt)?
+            }
+            Instruction::FsgnjD(r) => {
+                T::floatd(r, &mut self.regfile, &self.extensions, Fsgnj::fsgnj)?
+            }
+            Instruction::FsgnjnD(r) => {
+                T::floatd(r, &mut self.regfile, &self.extensions, Fsgnjn::fsgnjn)?
+            }
+            Instruction::FsgnjxD(r) => {
+                T::floatd(r, &mut self.regfile, &self.extensions, Fsgnjx::fsgnjx)?
+            }
+            Instruction::FminD(r) => T::floatd(r, &mut self.regfile, &self.extensions, Fmin::fmin)?,
+            Instruction::FmaxD(r) => T::floatd(r, &mut self.regfile, &self.extensions, Fmax::fmax)?,
+            Instruction::FcvtSD(r) => {
+                T::floatd_to_s(r, &mut self.regfile, &self.extensions, Fcvtsd::fcvtsd)?
+            }
+            Instruction::FcvtDS(r) => {
+                T::floats_to_d(r, &mut self.regfile, &self.extensions, Fcvtds::fcvtds)?
+            }
+            Instruction::FeqD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Feq::feq)?
+            }
+            Instruction::FltD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Flt::flt)?
+            }
+            Instruction::FleD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Fle::fle)?
+            }
+            Instruction::FclassD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Fclass::fclass)?
+            }
+            Instruction::FcvtWD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Fcvtwd::fcvtwd)?
+            }
+            Instruction::FcvtWuD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Fcvtwud::fcvtwud)?
+            }
+            Instruction::FcvtDW(r) => {
+                T::floatd_from_x(r, &mut self.regfile, &self.extensions, Fcvtdw::fcvtdw)?
+            }
+            Instruction::FcvtDWu(r) => {
+                T::floatd_from_x(r, &mut self.regfile, &self.extensions, Fcvtdwu::fcvtdwu)?
+            }
+            Instruction::FcvtLD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Fcvtld::fcvtld)?
+            }
+            Instruction::FcvtLuD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Fcvtlud::fcvtlud)?
+            }
+            Instruction::FmvXD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Fmvxd::fmvxd)?
+            }
+            Instruction::FcvtDL(r) => {
+                T::floatd_from_x(r, &mut self.regfile, &self.extensions, Fcvtdl::fcvtdl)?
+            }
+            Instruction::FcvtDLu(r) => {
+                T::floatd_from_x(r, &mut self.regfile, &self.extensions, Fcvtdlu::fcvtdlu)?
+            }
+            Instruction::FmvDX(r) => {
+                T::floatd_from_x(r, &mut self.regfile, &self.extensions, Fmvdx::fmvdx)?
+            }
+            Instruction::Flh(i) => T::fload_h(i, xregs, fregs, memory, Flh::flh)?,
+            Instruction::Fsh(s) => T::fstore_h(s, xregs, fregs, memory, reservation, Fsh::fsh)?,
+            Instruction::FmaddH(r4) => T::fmah(r4, fregs, csrs, Fmadd::fmadd)?,
+            Instruction::FmsubH(r4) => T::fmah(r4, fregs, csrs, Fmsub::fmsub)?,
+            Instruction::FnmsubH(r4) => T::fmah(r4, fregs, csrs, Fnmsub::fnmsub)?,
+            Instruction::FnmaddH(r4) => T::fmah(r4, fregs, csrs, Fnmadd::fnmadd)?,
+            Instruction::FaddH(r) => T::floath(r, fregs, csrs, Fadd::fadd)?,
+            Instruction::FsubH(r) => T::floath(r, fregs, csrs, Fsub::fsub)?,
+            Instruction::FmulH(r) => T::floath(r, fregs, csrs, Fmul::fmul)?,
+            Instruction::FdivH(r) => T::floath(r, fregs, csrs, Fdiv::fdiv)?,
+            Instruction::FsqrtH(r) => T::floath(r, fregs, csrs, Fsqrt::fsqrt)?,
+            Instruction::FsgnjH(r) => T::floath(r, fregs, csrs, Fsgnj::fsgnj)?,
+            Instruction::FsgnjnH(r) => T::floath(r, fregs, csrs, Fsgnjn::fsgnjn)?,
+            Instruction::FsgnjxH(r) => T::floath(r, fregs, csrs, Fsgnjx::fsgnjx)?,
+            Instruction::FminH(r) => T::floath(r, fregs, csrs, Fmin::fmin)?,
+            Instruction::FmaxH(r) => T::floath(r, fregs, csrs, Fmax::fmax)?,
+            Instruction::FcvtSH(r) => T::floath_to_s(r, fregs, csrs, Fcvtsh::fcvtsh)?,
+            Instruction::FcvtHS(r) => T::floats_to_h(r, fregs, csrs, Fcvths::fcvths)?,
+            Instruction::FcvtDH(r) => T::floath_to_d(r, fregs, csrs, Fcvtdh::fcvtdh)?,
+            Instruction::FcvtHD(r) => T::floatd_to_h(r, fregs, csrs, Fcvthd::fcvthd)?,
+            Instruction::FeqH(r) => T::floath_to_x(r, fregs, xregs, csrs, Feq::feq)?,
+            Instruction::FltH(r) => T::floath_to_x(r, fregs, xregs, csrs, Flt::flt)?,
+            Instruction::FleH(r) => T::floath_to_x(r, fregs, xregs, csrs, Fle::fle)?,
+            Instruction::FclassH(r) => T::floath_to_x(r, fregs, xregs, csrs, Fclass::fclass)?,
+            Instruction::FcvtWH(r) => T::floath_to_x32(r, fregs, xregs, csrs, Fcvtwh::fcvtwh)?,
+            Instruction::FcvtWuH(r) => T::floath_to_x32(r, fregs, xregs, csrs, Fcvtwuh::fcvtwuh)?,
+            Instruction::FmvXH(r) => T::floath_raw_to_x(r, fregs, xregs, csrs, Fmvxh::fmvxh)?,
+            Instruction::FcvtHW(r) => T::floath_from_x(r, xregs, fregs, csrs, Fcvthw::fcvthw)?,
+            Instruction::FcvtHWu(r) => T::floath_from_x(r, xregs, fregs, csrs, Fcvthwu::fcvthwu)?,
+            Instruction::FmvHX(r) => T::floath_from_x(r, xregs, fregs, csrs, Fmvhx::fmvhx)?,
+            Instruction::FcvtLH(r) => T::floath_to_x64(r, fregs, xregs, csrs, Fcvtlh::fcvtlh)?,
+            Instruction::FcvtLuH(r) => T::floath_to_x64(r, fregs, xregs, csrs, Fcvtluh::fcvtluh)?,
+            Instruction::FcvtHL(r) => T::floath_from_x(r, xregs, fregs, csrs, Fcvthl::fcvthl)?,
+            Instruction::FcvtHLu(r) => T::floath_from_x(r, xregs, fregs, csrs, Fcvthlu::fcvthlu)?,
+            Instruction::FliS(r) => {
+                T::floats_imm(r, &mut self.regfile, &self.extensions, Fli::fli)?
+            }
+            Instruction::FminmS(r) => {
+                T::floats(r, &mut self.regfile, &self.extensions, Fminm::fminm)?
+            }
+            Instruction::FmaxmS(r) => {
+                T::floats(r, &mut self.regfile, &self.extensions, Fmaxm::fmaxm)?
+            }
+            Instruction::FroundS(r) => {
+                T::floats(r, &mut self.regfile, &self.extensions, Fround::fround)?
+            }
+            Instruction::FroundnxS(r) => {
+                T::floats(r, &mut self.regfile, &self.extensions, Froundnx::froundnx)?
+            }
+            Instruction::FleqS(r) => {
+                T::floats_to_x(r, &mut self.regfile, &self.extensions, Fleq::fleq)?
+            }
+            Instruction::FltqS(r) => {
+                T::floats_to_x(r, &mut self.regfile, &self.extensions, Fltq::fltq)?
+            }
+            Instruction::FliD(r) => {
+                T::floatd_imm(r, &mut self.regfile, &self.extensions, Fli::fli)?
+            }
+            Instruction::FminmD(r) => {
+                T::floatd(r, &mut self.regfile, &self.extensions, Fminm::fminm)?
+            }
+            Instruction::FmaxmD(r) => {
+                T::floatd(r, &mut self.regfile, &self.extensions, Fmaxm::fmaxm)?
+            }
+            Instruction::FroundD(r) => {
+                T::floatd(r, &mut self.regfile, &self.extensions, Fround::fround)?
+            }
+            Instruction::FroundnxD(r) => {
+                T::floatd(r, &mut self.regfile, &self.extensions, Froundnx::froundnx)?
+            }
+            Instruction::FleqD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Fleq::fleq)?
+            }
+            Instruction::FltqD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Fltq::fltq)?
+            }
+            Instruction::FcvtmodWD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Fcvtmodwd::fcvtmodwd)?
+            }
+            Instruction::FmvhXD(r) => {
+                T::floatd_to_x(r, &mut self.regfile, &self.extensions, Fmvhxd::fmvhxd)?
+            }
+            Instruction::FmvpDX(r) => {
+                T::floatd_from_x2(r, &mut self.regfile, &self.extensions, Fmvpdx::fmvpdx)?
+            }
+            Instruction::FliH(r) => T::floath_imm(r, fregs, Fli::fli)?,
+            Instruction::FminmH(r) => T::floath(r, fregs, csrs, Fminm::fminm)?,
+            Instruction::FmaxmH(r) => T::floath(r, fregs, csrs, Fmaxm::fmaxm)?,
+            Instruction::FroundH(r) => T::floath(r, fregs, csrs, Fround::fround)?,
+            Instruction::FroundnxH(r) => T::floath(r, fregs, csrs, Froundnx::froundnx)?,
+            Instruction::FleqH(r) => T::floath_to_x(r, fregs, xregs, csrs, Fleq::fleq)?,
+            Instruction::FltqH(r) => T::floath_to_x(r, fregs, xregs, csrs, Fltq::fltq)?,
+            Instruction::Sh1add(r) => T::math(r, xregs, Sh1add::sh1add)?,
+            Instruction::Sh2add(r) => T::math(r, xregs, Sh2add::sh2add)?,
+            Instruction::Sh3add(r) => T::math(r, xregs, Sh3add::sh3add)?,
+            Instruction::AddUw(r) => T::math(r, xregs, AddUw::add_uw)?,
+            Instruction::Sh1addUw(r) => T::math(r, xregs, Sh1addUw::sh1add_uw)?,
+            Instruction::Sh2addUw(r) => T::math(r, xregs, Sh2addUw::sh2add_uw)?,
+            Instruction::Sh3addUw(r) => T::math(r, xregs, Sh3addUw::sh3add_uw)?,
+            Instruction::SlliUw(s) => T::shifti(s, xregs, SlliUw::slli_uw)?,
+            Instruction::Andn(r) => T::math(r, xregs, Andn::andn)?,
+            Instruction::Orn(r) => T::math(r, xregs, Orn::orn)?,
+            Instruction::Xnor(r) => T::math(r, xregs, Xnor::xnor)?,
+            Instruction::Clz(i) => T::unary(i, xregs, Clz::clz)?,
+            Instruction::Ctz(i) => T::unary(i, xregs, Ctz::ctz)?,
+            Instruction::Cpop(i) => T::unary(i, xregs, Cpop::cpop)?,
+            Instruction::Max(r) => T::math(r, xregs, Max::max)?,
+            Instruction::Maxu(r) => T::math(r, xregs, Maxu::maxu)?,
+            Instruction::Min(r) => T::math(r, xregs, Min::min)?,
+            Instruction::Minu(r) => T::math(r, xregs, Minu::minu)?,
+            Instruction::SextB(i) => T::unary(i, xregs, SextB::sext_b)?,
+            Instruction::SextH(i) => T::unary(i, xregs, SextH::sext_h)?,
+            Instruction::ZextH(i) => T::unary(i, xregs, ZextH::zext_h)?,
+            Instruction::Rol(r) => T::math(r, xregs, Rol::rol)?,
+            Instruction::Ror(r) => T::math(r, xregs, Ror::ror)?,
+            Instruction::Rori(s) => T::shifti(s, xregs, Rori::rori)?,
+            Instruction::OrcB(i) => T::unary(i, xregs, OrcB::orc_b)?,
+            Instruction::Rev8(i) => T::unary(i, xregs, Rev8::rev8)?,
+            Instruction::Clzw(i) => T::unary(i, xregs, Clzw::clzw)?,
+            Instruction::Ctzw(i) => T::unary(i, xregs, Ctzw::ctzw)?,
+            Instruction::Cpopw(i) => T::unary(i, xregs, Cpopw::cpopw)?,
+            Instruction::Rolw(r) => T::math(r, xregs, Rolw::rolw)?,
+            Instruction::Rorw(r) => T::math(r, xregs, Rorw::rorw)?,
+            Instruction::Roriw(s) => T::shiftiw(s, xregs, Roriw::roriw)?,
+            Instruction::Clmul(r) => T::math(r, xregs, Clmul::clmul)?,
+            Instruction::Clmulh(r) => T::math(r, xregs, Clmulh::clmulh)?,
+            Instruction::Clmulr(r) => T::math(r, xregs, Clmulr::clmulr)?,
+            Instruction::Bclr(r) => T::math(r, xregs, Bclr::bclr)?,
+            Instruction::Bclri(s) => T::shifti(s, xregs, Bclri::bclri)?,
+            Instruction::Bext(r) => T::math(r, xregs, Bext::bext)?,
+            Instruction::Bexti(s) => T::shifti(s, xregs, Bexti::bexti)?,
+            Instruction::Binv(r) => T::math(r, xregs, Binv::binv)?,
+            Instruction::Binvi(s) => T::shifti(s, xregs, Binvi::binvi)?,
+            Instruction::Bset(r) => T::math(r, xregs, Bset::bset)?,
+            Instruction::Bseti(s) => T::shifti(s, xregs, Bseti::bseti)?,
+            Instruction::Pack(r) => T::math(r, xregs, Pack::pack)?,
+            Instruction::Packh(r) => T::math(r, xregs, Packh::packh)?,
+            Instruction::Packw(r) => T::math(r, xregs, Packw::packw)?,
+            Instruction::Brev8(i) => T::unary(i, xregs, Brev8::brev8)?,
+            Instruction::Zip(i) => T::unary(i, xregs, Zip::zip)?,
+            Instruction::Unzip(i) => T::unary(i, xregs, Unzip::unzip)?,
+            Instruction::Xperm4(r) => T::math(r, xregs, Xperm4::xperm4)?,
+            Instruction::Xperm8(r) => T::math(r, xregs, Xperm8::xperm8)?,
+            Instruction::CzeroEqz(r) => T::math(r, xregs, CzeroEqz::czero_eqz)?,
+            Instruction::CzeroNez(r) => T::math(r, xregs, CzeroNez::czero_nez)?,
+            Instruction::Aes32esi(r) => T::math_bs(r, xregs, Aes32esi::aes32esi)?,
+            Instruction::Aes32esmi(r) => T::math_bs(r, xregs, Aes32esmi::aes32esmi)?,
+            Instruction::Aes32dsi(r) => T::math_bs(r, xregs, Aes32dsi::aes32dsi)?,
+            Instruction::Aes32dsmi(r) => T::math_bs(r, xregs, Aes32dsmi::aes32dsmi)?,
+            Instruction::Aes64es(r) => T::math(r, xregs, Aes64es::aes64es)?,
+            Instruction::Aes64esm(r) => T::math(r, xregs, Aes64esm::aes64esm)?,
+            Instruction::Aes64ds(r) => T::math(r, xregs, Aes64ds::aes64ds)?,
+            Instruction::Aes64dsm(r) => T::math(r, xregs, Aes64dsm::aes64dsm)?,
+            Instruction::Aes64im(i) => T::unary(i, xregs, Aes64im::aes64im)?,
+            Instruction::Aes64ks1i(i) => T::mathi(i, xregs, Aes64ks1i::aes64ks1i)?,
+            Instruction::Aes64ks2(r) => T::math(r, xregs, Aes64ks2::aes64ks2)?,
+            Instruction::Sha256sig0(i) => T::unary(i, xregs, Sha256sig0::sha256sig0)?,
+            Instruction::Sha256sig1(i) => T::unary(i, xregs, Sha256sig1::sha256sig1)?,
+            Instruction::Sha256sum0(i) => T::unary(i, xregs, Sha256sum0::sha256sum0)?,
+            Instruction::Sha256sum1(i) => T::unary(i, xregs, Sha256sum1::sha256sum1)?,
+            Instruction::Sha512sig0h(r) => T::math(r, xregs, Sha512sig0h::sha512sig0h)?,
+            Instruction::Sha512sig0l(r) => T::math(r, xregs, Sha512sig0l::sha512sig0l)?,
+            Instruction::Sha512sig1h(r) => T::math(r, xregs, Sha512sig1h::sha512sig1h)?,
+            Instruction::Sha512sig1l(r) => T::math(r, xregs, Sha512sig1l::sha512sig1l)?,
+            Instruction::Sha512sum0r(r) => T::math(r, xregs, Sha512sum0r::sha512sum0r)?,
+            Instruction::Sha512sum1r(r) => T::math(r, xregs, Sha512sum1r::sha512sum1r)?,
+            Instruction::Sha512sig0(i) => T::unary(i, xregs, Sha512sig0::sha512sig0)?,
+            Instruction::Sha512sig1(i) => T::unary(i, xregs, Sha512sig1::sha512sig1)?,
+            Instruction::Sha512sum0(i) => T::unary(i, xregs, Sha512sum0::sha512sum0)?,
+            Instruction::Sha512sum1(i) => T::unary(i, xregs, Sha512sum1::sha512sum1)?,
+            Instruction::Sm4ed(r) => T::math_bs(r, xregs, Sm4ed::sm4ed)?,
+            Instruction::Sm4ks(r) => T::math_bs(r, xregs, Sm4ks::sm4ks)?,
+            Instruction::Sm3p0(i) => T::unary(i, xregs, Sm3p0::sm3p0)?,
+            Instruction::Sm3p1(i) => T::unary(i, xregs, Sm3p1::sm3p1)?,
+            Instruction::Badd(r) => T::math(r, xregs, Badd::badd)?,
+            Instruction::Bsub(r) => T::math(r, xregs, Bsub::bsub)?,
+            Instruction::Bsll(r) => T::math(r, xregs, Bsll::bsll)?,
+            Instruction::Bslt(r) => T::math(r, xregs, Bslt::bslt)?,
+            Instruction::Bsltu(r) => T::math(r, xregs, Bsltu::bsltu)?,
+            Instruction::Bxor(r) => T::math(r, xregs, Bxor::bxor)?,
+            Instruction::Bsrl(r) => T::math(r, xregs, Bsrl::bsrl)?,
+            Instruction::Bsra(r) => T::math(r, xregs, Bsra::bsra)?,
+            Instruction::Bor(r) => T::math(r, xregs, Bor::bor)?,
+            Instruction::Band(r) => T::math(r, xregs, Band::band)?,
+            Instruction::Vsetvli(i) => T::vsetvli(i, &mut self.regfile)?,
+            Instruction::Vsetivli(i) => T::vsetivli(i, &mut self.regfile)?,
+            Instruction::Vsetvl(r) => T::vsetvl(r, &mut self.regfile)?,
+            Instruction::Vle(m) => T::vload(m, &mut self.regfile, memory, Addressing::Unit)?,
+            Instruction::Vleff(m) => {
+                T::vload(m, &mut self.regfile, memory, Addressing::FaultOnlyFirst)?
+            }
+            Instruction::Vlm(m) => T::vload(m, &mut self.regfile, memory, Addressing::Mask)?,
+            Instruction::Vlse(m) => T::vload(m, &mut self.regfile, memory, Addressing::Strided)?,
+            Instruction::Vluxei(m) => T::vload(m, &mut self.regfile, memory, Addressing::Indexed)?,
+            Instruction::Vloxei(m) => T::vload(m, &mut self.regfile, memory, Addressing::Indexed)?,
+            Instruction::Vlr(m) => T::vload(m, &mut self.regfile, memory, Addressing::Whole)?,
+            Instruction::Vse(m) => T::vstore(
+                m,
+                &mut self.regfile,
+                memory,
+                &mut self.reservation,
+                Addressing::Unit,
+            )?,
+            Instruction::Vsm(m) => T::vstore(
+                m,
+                &mut self.regfile,
+                memory,
+                &mut self.reservation,
+                Addressing::Mask,
+            )?,
+            Instruction::Vsse(m) => T::vstore(
+                m,
+                &mut self.regfile,
+                memory,
+                &mut self.reservation,
+                Addressing::Strided,
+            )?,
+            Instruction::Vsuxei(m) => T::vstore(
+                m,
+                &mut self.regfile,
+                memory,
+                &mut self.reservation,
+                Addressing::Indexed,
+            )?,
+            Instruction::Vsoxei(m) => T::vstore(
+                m,
+                &mut self.regfile,
+                memory,
+                &mut self.reservation,
+                Addressing::Indexed,
+            )?,
+            Instruction::Vsr(m) => T::vstore(
+                m,
+                &mut self.regfile,
+                memory,
+                &mut self.reservation,
+                Addressing::Whole,
+            )?,
+            Instruction::Vadd(v) => T::varith(v, &mut self.regfile, Vadd::vadd, Shape::Single)?,
+            Instruction::Vsub(v) => T::varith(v, &mut self.regfile, Vsub::vsub, Shape::Single)?,
+            Instruction::Vrsub(v) => T::varith(v, &mut self.regfile, Vrsub::vrsub, Shape::Single)?,
+            Instruction::Vand(v) => T::varith(v, &mut self.regfile, Vand::vand, Shape::Single)?,
+            Instruction::Vor(v) => T::varith(v, &mut self.regfile, Vor::vor, Shape::Single)?,
+            Instruction::Vxor(v) => T::varith(v, &mut self.regfile, Vxor::vxor, Shape::Single)?,
+            Instruction::Vwaddu(v) => {
+                T::varith(v, &mut self.regfile, Vwaddu::vwaddu, Shape::Widen)?
+            }
+            Instruction::Vwadd(v) => T::varith(v, &mut self.regfile, Vwadd::vwadd, Shape::Widen)?,
+            Instruction::Vwsubu(v) => {
+                T::varith(v, &mut self.regfile, Vwsubu::vwsubu, Shape::Widen)?
+            }
+            Instruction::Vwsub(v) => T::varith(v, &mut self.regfile, Vwsub::vwsub, Shape::Widen)?,
+            Instruction::VwadduW(v) => {
+                T::varith(v, &mut self.regfile, VwadduW::vwaddu_w, Shape::WidenW)?
+            }
+            Instruction::VwaddW(v) => {
+                T::varith(v, &mut self.regfile, VwaddW::vwadd_w, Shape::WidenW)?
+            }
+            Instruction::VwsubuW(v) => {
+                T::varith(v, &mut self.regfile, VwsubuW::vwsubu_w, Shape::WidenW)?
+            }
+            Instruction::VwsubW(v) => {
+                T::varith(v, &mut self.regfile, VwsubW::vwsub_w, Shape::WidenW)?
+            }
+            Instruction::VzextVf2(v) => T::vextend(v, &mut self.regfile, VzextVf2::vzext_vf2, 2)?,
+            Instruction::VsextVf2(v) => T::vextend(v, &mut self.regfile, VsextVf2::vsext_vf2, 2)?,
+            Instruction::VzextVf4(v) => T::vextend(v, &mut self.regfile, VzextVf4::vzext_vf4, 4)?,
+            Instruction::VsextVf4(v) => T::vextend(v, &mut self.regfile, VsextVf4::vsext_vf4, 4)?,
+            Instruction::VzextVf8(v) => T::vextend(v, &mut self.regfile, VzextVf8::vzext_vf8, 8)?,
+            Instruction::VsextVf8(v) => T::vextend(v, &mut self.regfile, VsextVf8::vsext_vf8, 8)?,
+            Instruction::Vadc(v) => T::vcarry(v, &mut self.regfile, Vadc::vadc)?,
+            Instruction::Vsbc(v) => T::vcarry(v, &mut self.regfile, Vsbc::vsbc)?,
+            Instruction::Vmadc(v) => T::vcarry_out(v, &mut self.regfile, Vmadc::vmadc)?,
+            Instruction::Vmsbc(v) => T::vcarry_out(v, &mut self.regfile, Vmsbc::vmsbc)?,
+            Instruction::Vsll(v) => T::varith(v, &mut self.regfile, Vsll::vsll, Shape::Shift)?,
+            Instruction::Vsrl(v) => T::varith(v, &mut self.regfile, Vsrl::vsrl, Shape::Shift)?,
+            Instruction::Vsra(v) => T::varith(v, &mut self.regfile, Vsra::vsra, Shape::Shift)?,
+            Instruction::Vnsrl(v) => T::varith(v, &mut self.regfile, Vnsrl::vnsrl, Shape::Narrow)?,
+            Instruction::Vnsra(v) => T::varith(v, &mut self.regfile, Vnsra::vnsra, Shape::Narrow)?,
+            Instruction::Vmseq(v) => T::vcompare(v, &mut self.regfile, Vmseq::vmseq)?,
+            Instruction::Vmsne(v) => T::vcompare(v, &mut self.regfile, Vmsne::vmsne)?,
+            Instruction::Vmsltu(v) => T::vcompare(v, &mut self.regfile, Vmsltu::vmsltu)?,
+            Instruction::Vmslt(v) => T::vcompare(v, &mut self.regfile, Vmslt::vmslt)?,
+            Instruction::Vmsleu(v) => T::vcompare(v, &mut self.regfile, Vmsleu::vmsleu)?,
+            Instruction::Vmsle(v) => T::vcompare(v, &mut self.regfile, Vmsle::vmsle)?,
+            Instruction::Vmsgtu(v) => T::vcompare(v, &mut self.regfile, Vmsgtu::vmsgtu)?,
+            Instruction::Vmsgt(v) => T::vcompare(v, &mut self.regfile, Vmsgt::vmsgt)?,
+            Instruction::Vminu(v) => T::varith(v, &mut self.regfile, Vminu::vminu, Shape::Single)?,
+            Instruction::Vmin(v) => T::varith(v, &mut self.regfile, Vmin::vmin, Shape::Single)?,
+            Instruction::Vmaxu(v) => T::varith(v, &mut self.regfile, Vmaxu::vmaxu, Shape::Single)?,
+            Instruction::Vmax(v) => T::varith(v, &mut self.regfile, Vmax::vmax, Shape::Single)?,
+            Instruction::Vmul(v) => T::varith(v, &mut self.regfile, Vmul::vmul, Shape::Single)?,
+            Instruction::Vmulh(v) => T::varith(v, &mut self.regfile, Vmulh::vmulh, Shape::Single)?,
+            Instruction::Vmulhu(v) => {
+                T::varith(v, &mut self.regfile, Vmulhu::vmulhu, Shape::Single)?
+            }
+            Instruction::Vmulhsu(v) => {
+                T::varith(v, &mut self.regfile, Vmulhsu::vmulhsu, Shape::Single)?
+            }
+            Instruction::Vdivu(v) => T::varith(v, &mut self.regfile, Vdivu::vdivu, Shape::Single)?,
+            Instruction::Vdiv(v) => T::varith(v, &mut self.regfile, Vdiv::vdiv, Shape::Single)?,
+            Instruction::Vremu(v) => T::varith(v, &mut self.regfile, Vremu::vremu, Shape::Single)?,
+            Instruction::Vrem(v) => T::varith(v, &mut self.regfile, Vrem::vrem, Shape::Single)?,
+            Instruction::Vwmul(v) => T::varith(v, &mut self.regfile, Vwmul::vwmul, Shape::Widen)?,
+            Instruction::Vwmulu(v) => {
+                T::varith(v, &mut self.regfile, Vwmulu::vwmulu, Shape::Widen)?
+            }
+            Instruction::Vwmulsu(v) => {
+                T::varith(v, &mut self.regfile, Vwmulsu::vwmulsu, Shape::Widen)?
+            }
+            Instruction::Vmacc(v) => T::vmuladd(v, &mut self.regfile, Vmacc::vmacc, Shape::Single)?,
+            Instruction::Vnmsac(v) => {
+                T::vmuladd(v, &mut self.regfile, Vnmsac::vnmsac, Shape::Single)?
+            }
+            Instruction::Vmadd(v) => T::vmuladd(v, &mut self.regfile, Vmadd::vmadd, Shape::Single)?,
+            Instruction::Vnmsub(v) => {
+                T::vmuladd(v, &mut self.regfile, Vnmsub::vnmsub, Shape::Single)?
+            }
+            Instruction::Vwmaccu(v) => {
+                T::vmuladd(v, &mut self.regfile, Vwmaccu::vwmaccu, Shape::Widen)?
+            }
+            Instruction::Vwmacc(v) => {
+                T::vmuladd(v, &mut self.regfile, Vwmacc::vwmacc, Shape::Widen)?
+            }
+            Instruction::Vwmaccsu(v) => {
+                T::vmuladd(v, &mut self.regfile, Vwmaccsu::vwmaccsu, Shape::Widen)?
+            }
+            Instruction::Vwmaccus(v) => {
+                T::vmuladd(v, &mut self.regfile, Vwmaccus::vwmaccus, Shape::Widen)?
+            }
+            Instruction::Vmerge(v) => T::vmerge(v, &mut self.regfile)?,
+            Instruction::VmvV(v) => T::vmerge(v, &mut self.regfile)?,
+            Instruction::Vsaddu(v) => {
+                T::varith(v, &mut self.regfile, Vsaddu::vsaddu, Shape::Single)?
+            }
+            Instruction::Vsadd(v) => T::varith(v, &mut self.regfile, Vsadd::vsadd, Shape::Single)?,
+            Instruction::Vssubu(v) => {
+                T::varith(v, &mut self.regfile, Vssubu::vssubu, Shape::Single)?
+            }
+            Instruction::Vssub(v) => T::varith(v, &mut self.regfile, Vssub::vssub, Shape::Single)?,
+            Instruction::Vaaddu(v) => {
+                T::varith(v, &mut self.regfile, Vaaddu::vaaddu, Shape::Single)?
+            }
+            Instruction::Vaadd(v) => T::varith(v, &mut self.regfile, Vaadd::vaadd, Shape::Single)?,
+            Instruction::Vasubu(v) => {
+                T::varith(v, &mut self.regfile, Vasubu::vasubu, Shape::Single)?
+            }
+            Instruction::Vasub(v) => T::varith(v, &mut self.regfile, Vasub::vasub, Shape::Single)?,
+            Instruction::Vsmul(v) => T::varith(v, &mut self.regfile, Vsmul::vsmul, Shape::Single)?,
+            Instruction::Vssrl(v) => T::varith(v, &mut self.regfile, Vssrl::vssrl, Shape::Shift)?,
+            Instruction::Vssra(v) => T::varith(v, &mut self.regfile, Vssra::vssra, Shape::Shift)?,
+            Instruction::Vnclipu(v) => {
+                T::varith(v, &mut self.regfile, Vnclipu::vnclipu, Shape::Narrow)?
+            }
+            Instruction::Vnclip(v) => {
+                T::varith(v, &mut self.regfile, Vnclip::vnclip, Shape::Narrow)?
+            }
+            Instruction::Vfadd(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfadd::vfadd,
+                Shape::Single,
+            )?,
+            Instruction::Vfsub(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfsub::vfsub,
+                Shape::Single,
+            )?,
+            Instruction::Vfrsub(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfrsub::vfrsub,
+                Shape::Single,
+            )?,
+            Instruction::Vfmul(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfmul::vfmul,
+                Shape::Single,
+            )?,
+            Instruction::Vfdiv(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfdiv::vfdiv,
+                Shape::Single,
+            )?,
+            Instruction::Vfrdiv(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfrdiv::vfrdiv,
+                Shape::Single,
+            )?,
+            Instruction::Vfmin(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfmin::vfmin,
+                Shape::Single,
+            )?,
+            Instruction::Vfmax(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfmax::vfmax,
+                Shape::Single,
+            )?,
+            Instruction::Vfsgnj(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfsgnj::vfsgnj,
+                Shape::Single,
+            )?,
+            Instruction::Vfsgnjn(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfsgnjn::vfsgnjn,
+                Shape::Single,
+            )?,
+            Instruction::Vfsgnjx(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfsgnjx::vfsgnjx,
+                Shape::Single,
+            )?,
+            Instruction::Vfwadd(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfwadd::vfwadd,
+                Shape::Widen,
+            )?,
+            Instruction::Vfwsub(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfwsub::vfwsub,
+                Shape::Widen,
+            )?,
+            Instruction::Vfwmul(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfwmul::vfwmul,
+                Shape::Widen,
+            )?,
+            Instruction::VfwaddW(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfwaddW::vfwadd_w,
+                Shape::WidenW,
+            )?,
+            Instruction::VfwsubW(v) => T::vfarith(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfwsubW::vfwsub_w,
+                Shape::WidenW,
+            )?,
+            Instruction::Vfmacc(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfmacc::vfmacc,
+                Shape::Single,
+            )?,
+            Instruction::Vfnmacc(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfnmacc::vfnmacc,
+                Shape::Single,
+            )?,
+            Instruction::Vfmsac(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfmsac::vfmsac,
+                Shape::Single,
+            )?,
+            Instruction::Vfnmsac(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfnmsac::vfnmsac,
+                Shape::Single,
+            )?,
+            Instruction::Vfmadd(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfmadd::vfmadd,
+                Shape::Single,
+            )?,
+            Instruction::Vfnmadd(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfnmadd::vfnmadd,
+                Shape::Single,
+            )?,
+            Instruction::Vfmsub(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfmsub::vfmsub,
+                Shape::Single,
+            )?,
+            Instruction::Vfnmsub(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfnmsub::vfnmsub,
+                Shape::Single,
+            )?,
+            Instruction::Vfwmacc(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfwmacc::vfwmacc,
+                Shape::Widen,
+            )?,
+            Instruction::Vfwnmacc(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfwnmacc::vfwnmacc,
+                Shape::Widen,
+            )?,
+            Instruction::Vfwmsac(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfwmsac::vfwmsac,
+                Shape::Widen,
+            )?,
+            Instruction::Vfwnmsac(v) => T::vfmuladd(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfwnmsac::vfwnmsac,
+                Shape::Widen,
+            )?,
+            Instruction::Vfsqrt(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfsqrt::vfsqrt,
+                Shape::Single,
+                Conversion::None,
+            )?,
+            Instruction::Vfrsqrt7(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfrsqrt7::vfrsqrt7,
+                Shape::Single,
+                Conversion::None,
+            )?,
+            Instruction::Vfrec7(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfrec7::vfrec7,
+                Shape::Single,
+                Conversion::None,
+            )?,
+            Instruction::Vfclass(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                Vfclass::vfclass,
+                Shape::Single,
+                Conversion::ToInt,
+            )?,
+            Instruction::Vmfeq(v) => {
+                T::vfcompare(v, &mut self.regfile, &self.extensions, Vmfeq::vmfeq)?
+            }
+            Instruction::Vmfne(v) => {
+                T::vfcompare(v, &mut self.regfile, &self.extensions, Vmfne::vmfne)?
+            }
+            Instruction::Vmflt(v) => {
+                T::vfcompare(v, &mut self.regfile, &self.extensions, Vmflt::vmflt)?
+            }
+            Instruction::Vmfle(v) => {
+                T::vfcompare(v, &mut self.regfile, &self.extensions, Vmfle::vmfle)?
+            }
+            Instruction::Vmfgt(v) => {
+                T::vfcompare(v, &mut self.regfile, &self.extensions, Vmfgt::vmfgt)?
+            }
+            Instruction::Vmfge(v) => {
+                T::vfcompare(v, &mut self.regfile, &self.extensions, Vmfge::vmfge)?
+            }
+            Instruction::Vfmerge(v) => T::vfmerge(v, &mut self.regfile, &self.extensions)?,
+            Instruction::VfmvVF(v) => T::vfmerge(v, &mut self.regfile, &self.extensions)?,
+            Instruction::VfcvtXuF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfcvtXuF::vfcvt_xu_f,
+                Shape::Single,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfcvtXF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfcvtXF::vfcvt_x_f,
+                Shape::Single,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfcvtRtzXuF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfcvtRtzXuF::vfcvt_rtz_xu_f,
+                Shape::Single,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfcvtRtzXF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfcvtRtzXF::vfcvt_rtz_x_f,
+                Shape::Single,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfcvtFXu(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfcvtFXu::vfcvt_f_xu,
+                Shape::Single,
+                Conversion::FromInt,
+            )?,
+            Instruction::VfcvtFX(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfcvtFX::vfcvt_f_x,
+                Shape::Single,
+                Conversion::FromInt,
+            )?,
+            Instruction::VfwcvtXuF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfwcvtXuF::vfwcvt_xu_f,
+                Shape::Widen,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfwcvtXF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfwcvtXF::vfwcvt_x_f,
+                Shape::Widen,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfwcvtRtzXuF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfwcvtRtzXuF::vfwcvt_rtz_xu_f,
+                Shape::Widen,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfwcvtRtzXF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfwcvtRtzXF::vfwcvt_rtz_x_f,
+                Shape::Widen,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfwcvtFXu(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfwcvtFXu::vfwcvt_f_xu,
+                Shape::Widen,
+                Conversion::FromInt,
+            )?,
+            Instruction::VfwcvtFX(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfwcvtFX::vfwcvt_f_x,
+                Shape::Widen,
+                Conversion::FromInt,
+            )?,
+            Instruction::VfwcvtFF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfwcvtFF::vfwcvt_f_f,
+                Shape::Widen,
+                Conversion::None,
+            )?,
+            Instruction::VfncvtXuF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfncvtXuF::vfncvt_xu_f,
+                Shape::Narrow,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfncvtXF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfncvtXF::vfncvt_x_f,
+                Shape::Narrow,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfncvtRtzXuF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfncvtRtzXuF::vfncvt_rtz_xu_f,
+                Shape::Narrow,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfncvtRtzXF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfncvtRtzXF::vfncvt_rtz_x_f,
+                Shape::Narrow,
+                Conversion::ToInt,
+            )?,
+            Instruction::VfncvtFXu(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfncvtFXu::vfncvt_f_xu,
+                Shape::Narrow,
+                Conversion::FromInt,
+            )?,
+            Instruction::VfncvtFX(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfncvtFX::vfncvt_f_x,
+                Shape::Narrow,
+                Conversion::FromInt,
+            )?,
+            Instruction::VfncvtFF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfncvtFF::vfncvt_f_f,
+                Shape::Narrow,
+                Conversion::None,
+            )?,
+            Instruction::VfncvtRodFF(v) => T::vfunary(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfncvtRodFF::vfncvt_rod_f_f,
+                Shape::Narrow,
+                Conversion::None,
+            )?,
+            Instruction::Vredsum(v) => T::vreduce(v, &mut self.regfile, Vadd::vadd, false)?,
+            Instruction::Vredmaxu(v) => T::vreduce(v, &mut self.regfile, Vmaxu::vmaxu, false)?,
+            Instruction::Vredmax(v) => T::vreduce(v, &mut self.regfile, Vmax::vmax, false)?,
+            Instruction::Vredminu(v) => T::vreduce(v, &mut self.regfile, Vminu::vminu, false)?,
+            Instruction::Vredmin(v) => T::vreduce(v, &mut self.regfile, Vmin::vmin, false)?,
+            Instruction::Vredand(v) => T::vreduce(v, &mut self.regfile, Vand::vand, false)?,
+            Instruction::Vredor(v) => T::vreduce(v, &mut self.regfile, Vor::vor, false)?,
+            Instruction::Vredxor(v) => T::vreduce(v, &mut self.regfile, Vxor::vxor, false)?,
+            Instruction::Vwredsumu(v) => T::vreduce(v, &mut self.regfile, VwadduW::vwaddu_w, true)?,
+            Instruction::Vwredsum(v) => T::vreduce(v, &mut self.regfile, VwaddW::vwadd_w, true)?,
+            Instruction::Vfredosum(v) => {
+                T::vfreduce(v, &mut self.regfile, &self.extensions, Vfadd::vfadd, false)?
+            }
+            Instruction::Vfredusum(v) => {
+                T::vfreduce(v, &mut self.regfile, &self.extensions, Vfadd::vfadd, false)?
+            }
+            Instruction::Vfredmax(v) => {
+                T::vfreduce(v, &mut self.regfile, &self.extensions, Vfmax::vfmax, false)?
+            }
+            Instruction::Vfredmin(v) => {
+                T::vfreduce(v, &mut self.regfile, &self.extensions, Vfmin::vfmin, false)?
+            }
+            Instruction::Vfwredosum(v) => T::vfreduce(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfwaddW::vfwadd_w,
+                true,
+            )?,
+            Instruction::Vfwredusum(v) => T::vfreduce(
+                v,
+                &mut self.regfile,
+                &self.extensions,
+                VfwaddW::vfwadd_w,
+                true,
+            )?,
+            Instruction::Vmand(v) => T::vmask(v, &mut self.regfile, Vand::vand)?,
+            Instruction::Vmnand(v) => T::vmask(v, &mut self.regfile, Vmnand::vmnand)?,
+            Instruction::Vmandn(v) => T::vmask(v, &mut self.regfile, Vmandn::vmandn)?,
+            Instruction::Vmxor(v) => T::vmask(v, &mut self.regfile, Vxor::vxor)?,
+            Instruction::Vmor(v) => T::vmask(v, &mut self.regfile, Vor::vor)?,
+            Instruction::Vmnor(v) => T::vmask(v, &mut self.regfile, Vmnor::vmnor)?,
+            Instruction::Vmorn(v) => T::vmask(v, &mut self.regfile, Vmorn::vmorn)?,
+            Instruction::Vmxnor(v) => T::vmask(v, &mut self.regfile, Vmxnor::vmxnor)?,
+            Instruction::Vcpop(v) => T::vcpop(v, &mut self.regfile)?,
+            Instruction::Vfirst(v) => T::vfirst(v, &mut self.regfile)?,
+            Instruction::Vmsbf(v) => T::vmset(v, &mut self.regfile, SetFirst::Before)?,
+            Instruction::Vmsif(v) => T::vmset(v, &mut self.regfile, SetFirst::Including)?,
+            Instruction::Vmsof(v) => T::vmset(v, &mut self.regfile, SetFirst::Only)?,
+            Instruction::Viota(v) => T::viota(v, &mut self.regfile)?,
+            Instruction::Vid(v) => T::vid(v, &mut self.regfile)?,
+            Instruction::VmvXS(v) => T::vmv_x_s(v, &mut self.regfile)?,
+            Instruction::VmvSX(v) => T::vmv_s_x(v, &mut self.regfile)?,
+            Instruction::VfmvFS(v) => T::vfmv_f_s(v, &mut self.regfile, &self.extensions)?,
+            Instruction::VfmvSF(v) => T::vfmv_s_f(v, &mut self.regfile, &self.extensions)?,
+            Instruction::Vslideup(v) => T::vslideup(v, &mut self.regfile)?,
+            Instruction::Vslidedown(v) => T::vslidedown(v, &mut self.regfile)?,
+            Instruction::Vslide1up(v) => T::vslide1up(v, &mut self.regfile, &self.extensions)?,
+            Instruction::Vslide1down(v) => T::vslide1down(v, &mut self.regfile, &self.extensions)?,
+            Instruction::Vfslide1up(v) => T::vslide1up(v, &mut self.regfile, &self.extensions)?,
+            Instruction::Vfslide1down(v) => T::vslide1down(v, &mut self.regfile, &self.extensions)?,
+            Instruction::Vrgather(v) => T::vrgather(v, &mut self.regfile, false)?,
+            Instruction::Vrgatherei16(v) => T::vrgather(v, &mut self.regfile, true)?,
+            Instruction::Vcompress(v) => T::vcompress(v, &mut self.regfile)?,
+            Instruction::VmvNr(v) => T::vmvnr(v, &mut self.regfile)?,
+        }
+        pc.increment(len);
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
+mod tests {
+    use super::*;
+    use crate::decode::{U12, U2, U6};
+    use crate::{mem, mmu, registers, softfloat};
+
+    const ECALL: u32 = 0x00000073;
+    const EBREAK: u32 = 0x00100073;
+
+    #[test]
+    fn test_lui() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        let instruction = 0b00000000000000000001_01100_0110111;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0b1000000000000);
+    }
+
+    #[test]
+    fn test_auipc() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(4u32);
+        let instruction = 0b00000000000000000001_01100_0010111;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0b1000000000000 + 4);
+    }
+
+    #[test]
+    fn test_jal() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(4u32);
+        let instruction = 0b0_0000000000_0_00000000_01100_1101111;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 8);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_jalr() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 12;
+        let instruction = 0b000000000000_01101_000_01100_1100111;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 8);
+        assert_eq!(hart.pc, 12 & !0b1);
+    }
+
+    #[test]
+    fn test_load_byte() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        memory[32] = 255;
+        let instruction = 0b000000000000_01101_000_01100_0000011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12 as u8, 255);
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_load_half() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        memory[32..34].copy_from_slice(&[255, 255]);
+        let instruction = 0b000000000000_01101_001_01100_0000011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12 as u16, u16::MAX);
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_load_word() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        memory[32..36].copy_from_slice(&[255, 255, 0, 0]);
+        let instruction = 0b000000000000_01101_010_01100_0000011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, u32::from_le_bytes([255, 255, 0, 0]));
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_load_dword() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        memory[32..40].copy_from_slice(&[255, 255, 0, 0, 0, 0, 0, 0]);
+        let instruction = 0b000000000000_01101_011_01100_0000011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, u64::from_le_bytes([255, 255, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_store_byte() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) = 255;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b0000000_01100_01101_000_00000_0100011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let data = mem::read::<u8>(&memory, 32).unwrap();
+        assert_eq!(data, 255);
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_store_half() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) =
+            u32::from_le_bytes([255, 255, 0, 0]);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b0000000_01100_01101_001_00000_0100011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let data = mem::read::<mem::U16>(&memory, 32).unwrap().as_u16();
+        assert_eq!(data, u16::MAX);
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_store_word() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) =
+            u32::from_le_bytes([255, 255, 0, 0]);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b0000000_01100_01101_010_00000_0100011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let data = mem::read::<mem::U32>(&memory, 32).unwrap().as_u32();
+        assert_eq!(data, u16::MAX as u32);
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_store_dword() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) = u16::MAX as u64;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b0000000_01100_01101_011_00000_0100011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let data = mem::read::<mem::U64>(&memory, 32).unwrap().as_u64();
+        assert_eq!(data, u16::MAX as u64);
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_math_add() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 100;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 10;
+        let instruction = 0b0000000_01110_01101_000_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 110);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_math_sub() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 100;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 10;
+        let instruction = 0b0100000_01110_01101_000_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 90);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_math_sll() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 4;
+        let instruction = 0b0000000_01110_01101_001_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1 << 4);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_math_srl() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 4;
+        let instruction = 0b0000000_01110_01101_101_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 >> 4);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_math_sra() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -1i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 4;
+        let instruction = 0b0100000_01110_01101_101_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, ((-1i32).wrapping_shr(4)) as u32);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_math_slt() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -1i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 4;
+        let instruction = 0b0000000_01110_01101_010_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_math_sltu() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 2;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 4;
+        let instruction = 0b0000000_01110_01101_011_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_math_xor() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 4;
+        let instruction = 0b0000000_01110_01101_100_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 ^ 4);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_math_or() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 4;
+        let instruction = 0b0000000_01110_01101_110_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 | 4);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_math_and() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 4;
+        let instruction = 0b0000000_01110_01101_111_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 & 4);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_math_mul() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -12i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 24;
+        let instruction = 0b0000001_01110_01101_000_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, (-12i32 * 24) as u32);
+    }
+
+    #[test]
+    fn test_math_mulh() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -12i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 24;
+        let instruction = 0b0000001_01110_01101_001_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, (((-12i64 * 24i64) >> 32) as u64) as u32);
+    }
+
+    #[test]
+    fn test_math_mulhu() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 12;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 6;
+        let instruction = 0b0000001_01110_01101_011_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0);
+    }
+
+    #[test]
+    fn test_math_div() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -12i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 3;
+        let instruction = 0b0000001_01110_01101_100_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -4i32 as u32);
+    }
+
+    #[test]
+    fn test_math_divu() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -12i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 3;
+        let instruction = 0b0000001_01110_01101_101_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -12i32 as u32 / 3);
+    }
+
+    #[test]
+    fn test_math_rem() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -13i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 3;
+        let instruction = 0b0000001_01110_01101_110_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -1i32 as u32);
+    }
+
+    #[test]
+    fn test_math_remu() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -13i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 3;
+        let instruction = 0b0000001_01110_01101_111_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -13i32 as u32 % 3);
+    }
+
+    #[test]
+    fn test_math_mulhsu() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -12i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = u32::MAX;
+        let instruction = 0b0000001_01110_01101_010_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, ((-12i64 * u32::MAX as i64) >> 32) as u32);
+    }
+
+    #[test]
+    fn test_math_div_by_zero() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 24;
+        // div x12, x13, x0; remu x15, x13, x0
+        hart.execute(0b0000001_00000_01101_100_01100_0110011, &mut memory)
+            .unwrap();
+        hart.execute(0b0000001_00000_01101_111_01111_0110011, &mut memory)
+            .unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), u64::MAX);
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X15), 24);
+    }
+
+    #[test]
+    fn test_math_div_overflow() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = i32::MIN as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = -1i32 as u32;
+        // div x12, x13, x14; rem x15, x13, x14
+        hart.execute(0b0000001_01110_01101_100_01100_0110011, &mut memory)
+            .unwrap();
+        hart.execute(0b0000001_01110_01101_110_01111_0110011, &mut memory)
+            .unwrap();
+        assert_eq!(
+            hart.regfile.xregs.get(registers::Register::X12),
+            i32::MIN as u32
+        );
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X15), 0);
+    }
+
+    #[test]
+    fn test_mathi_addi() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 100;
+        let instruction = 0b000000000001_01101_000_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 101);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathi_slti() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -1i32 as u32;
+        let instruction = 0b000000000001_01101_010_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathi_sltiu() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -1i32 as u32;
+        let instruction = 0b000000000001_01101_011_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathi_xori() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b000000000001_01101_100_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 ^ 1);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathi_ori() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b000000000001_01101_110_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 | 1);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathi_andi() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b000000000001_01101_111_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 & 1);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_shifti_slli() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1;
+        let instruction = 0b0000000_00011_01101_001_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1 << 3);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_shifti_srli() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b0000000_00011_01101_101_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 >> 3);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_shifti_srai() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -1i32 as u32;
+        let instruction = 0b0100000_00011_01101_101_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        println!("{:?}", hart.regfile.xregs);
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, ((-1i32).wrapping_shr(3)) as u32);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_shifti_slli_64() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1;
+        let instruction = 0b0000000_00011_01101_001_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1 << 3);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_shifti_wide_64() {
+        let mut memory = [0u8; 0];
+        let value = 0x8123_4567_89ab_cdefu64;
+        for shamt in 32..64u32 {
+            for (funct, op) in [
+                (
+                    0b000000_000000_01101_001_01100_0010011,
+                    Slli::slli as fn(u64, U6) -> u64,
+                ),
+                (0b000000_000000_01101_101_01100_0010011, Srli::srli),
+                (0b010000_000000_01101_101_01100_0010011, Srai::srai),
+            ] {
+                let mut hart = Hart::new(0u64);
+                *hart.regfile.xregs.get_mut(registers::Register::X13) = value;
+                hart.execute(funct | (shamt << 20), &mut memory).unwrap();
+                let r12 = hart.regfile.xregs.get(registers::Register::X12);
+                assert_eq!(r12, op(value, U6::new_truncate(shamt as u8)));
+                assert_eq!(hart.pc, 4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shifti_wide_ops() {
+        let value = 0x8000_0000_0000_0001u64;
+        assert_eq!(value.slli(U6::new_truncate(32)), 1 << 32);
+        assert_eq!(value.slli(U6::new_truncate(63)), 1 << 63);
+        assert_eq!(value.srli(U6::new_truncate(32)), 0x8000_0000);
+        assert_eq!(value.srli(U6::new_truncate(63)), 1);
+        assert_eq!(value.srai(U6::new_truncate(32)), 0xffff_ffff_8000_0000);
+        assert_eq!(value.srai(U6::new_truncate(63)), u64::MAX);
+    }
+
+    #[test]
+    fn test_shifti_wide_rv32() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        // slli x12, x13, 32 has shamt[5] set, which is reserved on RV32
+        let instruction = 0b000000_100000_01101_001_01100_0010011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::IllegalInstruction(instruction))
+        );
+        assert_eq!(hart.pc, 0);
+    }
+
+    #[test]
+    fn test_shiftiw_wide() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        // slliw x12, x13, 32 is reserved
+        let instruction = 0b000000_100000_01101_001_01100_0011011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::IllegalInstruction(instruction))
+        );
+    }
+
+    #[test]
+    fn test_shifti_srli_64() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b0000000_00011_01101_101_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 >> 3);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_shifti_srai_64() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -1i32 as u64;
+        let instruction = 0b0100000_00011_01101_101_01100_0010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        println!("{:?}", hart.regfile.xregs);
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, ((-1i64).wrapping_shr(3)) as u64);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_bitmanip_rv32() {
+        let mut memory = [0u8; 0];
+        // x12 = op(x13, x14)
+        for (instruction, src1, src2, expected) in [
+            (0x20e6c633u32, 3u32, 100u32, 112u32),               // sh2add
+            (0x40e6f633, 0xff00_ff00, 0x0ff0_0ff0, 0xf000_f000), // andn
+            (0x40e6e633, 0, 0xffff_0000, 0x0000_ffff),           // orn
+            (0x40e6c633, 0xf0f0_f0f0, 0xff00_ff00, 0xf00f_f00f), // xnor
+            (0x60069613, 1, 0, 31),                              // clz
+            (0x60069613, 0, 0, 32),                              // clz
+            (0x60169613, 0, 0, 32),                              // ctz
+            (0x60169613, 0x8000_0000, 0, 31),                    // ctz
+            (0x60269613, 0xf0f0, 0, 8),                          // cpop
+            (0x0ae6e633, -1i32 as u32, 1, 1),                    // max
+            (0x0ae6f633, -1i32 as u32, 1, -1i32 as u32),         // maxu
+            (0x0ae6c633, -1i32 as u32, 1, -1i32 as u32),         // min
+            (0x0ae6d633, -1i32 as u32, 1, 1),                    // minu
+            (0x60469613, 0x1234_5680, 0, 0xffff_ff80),           // sext.b
+            (0x60569613, 0x1234_8000, 0, 0xffff_8000),           // sext.h
+            (0x0806c633, 0xffff_8000, 0, 0x0000_8000),           // zext.h
+            (0x60e69633, 0x8000_00f1, 36, 0x0000_0f18),          // rol
+            (0x60e6d633, 0x0000_00f1, 4, 0x1000_000f),           // ror
+            (0x60e6d633, 0x0000_00f1, 0, 0x0000_00f1),           // ror
+            (0x6086d613, 0x0000_00f1, 0, 0xf100_0000),           // rori 8
+            (0x2876d613, 0x0001_3000, 0, 0x00ff_ff00),           // orc.b
+            (0x6986d613, 0x1122_3344, 0, 0x4433_2211),           // rev8
+            (0x0ae69633, 0b101, 0b11, 0b1111),                   // clmul
+            (0x0ae6b633, 0x8000_0000, 0x8000_0000, 0x4000_0000), // clmulh
+            (0x0ae6a633, 0x8000_0000, 0x8000_0000, 0x8000_0000), // clmulr
+            (0x48e69633, u32::MAX, 33, 0xffff_fffd),             // bclr
+            (0x48e6d633, 0x10, 4, 1),                            // bext
+            (0x68e69633, 0x10, 4, 0),                            // binv
+            (0x28e69633, 0, 31, 0x8000_0000),                    // bset
+            (0x28069613, 0, 0, 1),                               // bseti 0
+        ] {
+            let mut hart = Hart::new(0u32);
+            *hart.regfile.xregs.get_mut(registers::Register::X13) = src1;
+            *hart.regfile.xregs.get_mut(registers::Register::X14) = src2;
+            hart.execute(instruction, &mut memory).unwrap();
+            let r12 = hart.regfile.xregs.get(registers::Register::X12);
+            assert_eq!(r12, expected, "{instruction:#010x}");
+            assert_eq!(hart.pc, 4);
+        }
+    }
+
+    #[test]
+    fn test_bitmanip_rv64() {
+        let mut memory = [0u8; 0];
+        // x12 = op(x13, x14)
+        for (instruction, src1, src2, expected) in [
+            (0x20e6e633u32, 1u64, 1u64 << 63, (1 << 63) + 8), // sh3add
+            (0x08e6863b, 0xffff_ffff_0000_0001, 1, 2),        // add.uw
+            (0x20e6a63b, 0xffff_ffff_8000_0000, 1, 0x1_0000_0001), // sh1add.uw
+            (0x0a86961b, 0xffff_ffff_0000_0003, 0, 3 << 40),  // slli.uw 40
+            (0x60069613, 1, 0, 63),                           // clz
+            (0x60169613, 0, 0, 64),                           // ctz
+            (0x6006961b, 0xffff_ffff_0000_0001, 0, 31),       // clzw
+            (0x6016961b, 0x1_0000_0000, 0, 32),               // ctzw
+            (0x6026961b, u64::MAX, 0, 32),                    // cpopw
+            (0x60469613, 0x7f, 0, 0x7f),                      // sext.b
+            (0x0806c63b, u64::MAX, 0, 0xffff),                // zext.h
+            (0x6286d613, 0x0000_00f1, 0, 0xf1 << 24),         // rori 40
+            (0x60e6963b, 0x4000_0000, 1, 0xffff_ffff_8000_0000), // rolw
+            (0x60e6d63b, 0xffff_ffff_0000_0001, 33, 0xffff_ffff_8000_0000), // rorw
+            (0x6146d61b, 0x0000_00f1, 0, 0x000f_1000),        // roriw 20
+            (0x2876d613, 0x0001_0200_0000_3000, 0, 0x00ff_ff00_0000_ff00), // orc.b
+            (0x6b86d613, 0x1122_3344_5566_7788, 0, 0x8877_6655_4433_2211), // rev8
+            (0x0ae6b633, 1 << 63, 1 << 63, 1 << 62),          // clmulh
+            (0x0ae6a633, 1 << 63, 1 << 63, 1 << 63),          // clmulr
+            (0x28e69633, 0, 127, 1 << 63),                    // bset
+            (0x4a869613, u64::MAX, 0, !(1 << 40)),            // bclri 40
+            (0x4a86d613, 1 << 40, 0, 1),                      // bexti 40
+            (0x6a869613, 1 << 40, 0, 0),                      // binvi 40
+        ] {
+            let mut hart = Hart::new(0u64);
+            *hart.regfile.xregs.get_mut(registers::Register::X13) = src1;
+            *hart.regfile.xregs.get_mut(registers::Register::X14) = src2;
+            hart.execute(instruction, &mut memory).unwrap();
+            let r12 = hart.regfile.xregs.get(registers::Register::X12);
+            assert_eq!(r12, expected, "{instruction:#010x}");
+            assert_eq!(hart.pc, 4);
+        }
+    }
+
+    #[test]
+    fn test_zicond() {
+        let mut memory = [0u8; 0];
+        // x12 = op(x13, x14), RV32 only sees the low word of the condition
+        for (instruction, src2, rv64, rv32) in [
+            (0x0ee6d633u32, 0u64, 0u64, 0u32), // czero.eqz
+            (0x0ee6d633, 1, 7, 7),             // czero.eqz
+            (0x0ee6d633, 1 << 40, 7, 0),       // czero.eqz
+            (0x0ee6f633, 0, 7, 7),             // czero.nez
+            (0x0ee6f633, 1, 0, 0),             // czero.nez
+            (0x0ee6f633, 1 << 40, 0, 7),       // czero.nez
+        ] {
+            let mut hart = Hart::new(0u64);
+            *hart.regfile.xregs.get_mut(registers::Register::X13) = 7;
+            *hart.regfile.xregs.get_mut(registers::Register::X14) = src2;
+            hart.execute(instruction, &mut memory).unwrap();
+            let r12 = hart.regfile.xregs.get(registers::Register::X12);
+            assert_eq!(r12, rv64, "{instruction:#010x}");
+            let mut hart = Hart::new(0u32);
+            *hart.regfile.xregs.get_mut(registers::Register::X13) = 7;
+            *hart.regfile.xregs.get_mut(registers::Register::X14) = src2 as u32;
+            hart.execute(instruction, &mut memory).unwrap();
+            let r12 = hart.regfile.xregs.get(registers::Register::X12);
+            assert_eq!(r12, rv32, "{instruction:#010x}");
+        }
+        let mut hart = Hart::new(0u64);
+        hart.extensions.zicond = false;
+        assert_eq!(
+            hart.execute(0x0ee6d633, &mut memory),
+            Err(Exception::IllegalInstruction(0x0ee6d633))
+        );
+    }
+
+    #[test]
+    fn test_zicntr_counters() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        for _ in 0..3 {
+            hart.execute(0x00000013, &mut memory).unwrap(); // nop
+        }
+        hart.execute(0xc0002673, &mut memory).unwrap(); // rdcycle x12
+        hart.execute(0xc02026f3, &mut memory).unwrap(); // rdinstret x13
+        hart.execute(0xc0102773, &mut memory).unwrap(); // rdtime x14
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 3);
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X13), 4);
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X14), 5);
+        // Instructions that trap do not retire
+        assert!(hart.execute(ECALL, &mut memory).is_err());
+        assert_eq!(hart.regfile.csrs.counter(registers::MINSTRET), 6);
+        // The user-level views are read-only
+        assert_eq!(
+            hart.execute(0xc0051073, &mut memory), // csrw cycle, x10
+            Err(Exception::IllegalInstruction(0xc0051073))
+        );
+        assert_eq!(
+            hart.execute(0xc8002673, &mut memory), // rdcycleh x12
+            Err(Exception::IllegalInstruction(0xc8002673))
+        );
+        hart.extensions.zicntr = false;
+        assert_eq!(
+            hart.execute(0xc0002673, &mut memory),
+            Err(Exception::IllegalInstruction(0xc0002673))
+        );
+    }
+
+    #[test]
+    fn test_zicntr_rv32_high_halves() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .csrs
+            .set_counter(registers::MCYCLE, 0x1_ffff_ffff);
+        hart.execute(0xc8002673, &mut memory).unwrap(); // rdcycleh x12
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 1);
+        hart.execute(0xc0002673, &mut memory).unwrap(); // rdcycle x12
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0);
+        hart.execute(0xc8002673, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 2);
+        assert_eq!(hart.regfile.csrs.get(registers::MCYCLEH), 2);
+        assert_eq!(hart.regfile.csrs.get(registers::TIMEH), 0);
+    }
+
+    #[test]
+    fn test_zicntr_writes_and_inhibit() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        // A write replaces the increment of the instruction doing it
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 100;
+        hart.execute(0xb0251073, &mut memory).unwrap(); // csrw minstret, x10
+        assert_eq!(hart.regfile.csrs.counter(registers::MINSTRET), 100);
+        assert_eq!(hart.regfile.csrs.counter(registers::MCYCLE), 1);
+        hart.execute(0x00000013, &mut memory).unwrap();
+        assert_eq!(hart.regfile.csrs.counter(registers::MINSTRET), 101);
+        // Bit 1 of mcountinhibit is read-only zero, time keeps ticking
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 0b111;
+        hart.execute(0x32051073, &mut memory).unwrap(); // csrw mcountinhibit, x10
+        assert_eq!(
+            hart.regfile
+                .csrs
+                .read(registers::MCOUNTINHIBIT, &hart.extensions),
+            0b101
+        );
+        hart.execute(0x00000013, &mut memory).unwrap();
+        assert_eq!(hart.regfile.csrs.counter(registers::MINSTRET), 101);
+        assert_eq!(hart.regfile.csrs.counter(registers::MCYCLE), 2);
+        assert_eq!(hart.regfile.csrs.counter(registers::TIME), 4);
+    }
+
+    #[test]
+    fn test_zihpm_events() {
+        let mut memory = [0u8; 8];
+        let mut hart = Hart::new(0u64);
+        let events = [
+            registers::EVENT_LOAD,
+            registers::EVENT_STORE,
+            registers::EVENT_BRANCH_TAKEN,
+            registers::EVENT_FLOAT,
+        ];
+        for (n, event) in events.into_iter().enumerate() {
+            hart.regfile
+                .csrs
+                .write(registers::MHPMEVENT3 + n, event, &hart.extensions);
+        }
+        for instruction in [
+            0x00002603u32, // lw x12, 0(x0)
+            0x00c02023,    // sw x12, 0(x0)
+            0x00b0262f,    // amoadd.w x12, x11, (x0)
+            0x00000463,    // beq x0, x0, 8
+            0x00000263,    // beq x0, x0, 4, taken to the next instruction
+            0x00001463,    // bne x0, x0, 8
+            0x00e68653,    // fadd.s f12, f13, f14
+        ] {
+            hart.execute(instruction, &mut memory).unwrap();
+        }
+        let count = |n| hart.regfile.csrs.counter(registers::MHPMCOUNTER3 + n);
+        assert_eq!([count(0), count(1), count(2), count(3)], [2, 2, 2, 1]);
+        assert_eq!(count(4), 0);
+        hart.execute(0xc0302673, &mut memory).unwrap(); // rdhpmcounter3 x12
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 2);
+        // Unsupported events read back as 0
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 99;
+        hart.execute(0x32351073, &mut memory).unwrap(); // csrw mhpmevent3, x10
+        assert_eq!(
+            hart.regfile
+                .csrs
+                .read(registers::MHPMEVENT3, &hart.extensions),
+            0
+        );
+    }
+
+    #[test]
+    fn test_counter_enables() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        let rdcycle = 0xc0002673;
+        let rdinstret = 0xc02026f3;
+        hart.privilege = Privilege::Supervisor;
+        assert_eq!(
+            hart.execute(rdcycle, &mut memory),
+            Err(Exception::IllegalInstruction(rdcycle))
+        );
+        hart.regfile
+            .csrs
+            .write(registers::MCOUNTEREN, 0b001, &hart.extensions);
+        hart.execute(rdcycle, &mut memory).unwrap();
+        hart.privilege = Privilege::User;
+        assert_eq!(
+            hart.execute(rdcycle, &mut memory),
+            Err(Exception::IllegalInstruction(rdcycle))
+        );
+        hart.regfile
+            .csrs
+            .write(registers::SCOUNTEREN, 0b101, &hart.extensions);
+        hart.execute(rdcycle, &mut memory).unwrap();
+        assert_eq!(
+            hart.execute(rdinstret, &mut memory),
+            Err(Exception::IllegalInstruction(rdinstret))
+        );
+    }
+
+    #[test]
+    fn test_counter_enables_without_supervisor() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.extensions.s = false;
+        let rdcycle = 0xc0002673;
+        let rdinstret = 0xc02026f3;
+        hart.regfile
+            .csrs
+            .write(registers::MCOUNTEREN, 0b001, &hart.extensions);
+        hart.privilege = Privilege::User;
+        // U-mode only answers to mcounteren
+        hart.execute(rdcycle, &mut memory).unwrap();
+        assert_eq!(
+            hart.execute(rdinstret, &mut memory),
+            Err(Exception::IllegalInstruction(rdinstret))
+        );
+    }
+
+    #[test]
+    fn test_bitmanip_ops() {
+        for shamt in 0..64u64 {
+            let value = 0x8123_4567_89ab_cdefu64;
+            assert_eq!(value.rol(shamt), value.rotate_left(shamt as u32));
+            assert_eq!(value.ror(shamt), value.rotate_right(shamt as u32));
+            let value = value as u32;
+            assert_eq!(value.rol(shamt as u32), value.rotate_left(shamt as u32));
+            assert_eq!(value.ror(shamt as u32), value.rotate_right(shamt as u32));
+        }
+        assert_eq!(u64::MAX.clmul(u64::MAX), 0x5555_5555_5555_5555);
+        assert_eq!(u64::MAX.clmulh(u64::MAX), 0x5555_5555_5555_5555);
+        assert_eq!(u32::MAX.clmulr(u32::MAX), 0xaaaa_aaaa);
+    }
+
+    #[test]
+    fn test_crypto_rv64() {
+        let mut memory = [0u8; 0];
+        // x12 = op(x13, x14)
+        for (instruction, src1, src2, expected) in [
+            (
+                0x08e6c633u32,
+                0x1111_2222_3333_4444u64,
+                0x5555_6666_7777_8888u64,
+                0x7777_8888_3333_4444,
+            ), // pack
+            (0x08e6f633, 0x1234, 0x5678, 0x7834), // packh
+            (
+                0x08e6c63b,
+                0x1111_2222_3333_4444,
+                0x5555_8888,
+                0xffff_ffff_8888_4444,
+            ), // packw
+            (0x6876d613, 0x0102_0408_1020_4080, 0, 0x8040_2010_0804_0201), // brev8
+            (
+                0x28e6a633,
+                0xfedc_ba98_7654_3210,
+                0x0000_0000_0000_0f1a,
+                0x0000_0000_0000_0f1a,
+            ), // xperm4
+            (
+                0x28e6c633,
+                0x8877_6655_4433_2211,
+                0x0000_0000_0008_0107,
+                0x1111_1111_1100_2288,
+            ), // xperm8
+            (0x10069613, 0x6a09_e667, 0, 0xffff_ffff_ce20_b47e), // sha256sum0
+        ] {
+            let mut hart = Hart::new(0u64);
+            *hart.regfile.xregs.get_mut(registers::Register::X13) = src1;
+            *hart.regfile.xregs.get_mut(registers::Register::X14) = src2;
+            hart.execute(instruction, &mut memory).unwrap();
+            let r12 = hart.regfile.xregs.get(registers::Register::X12);
+            assert_eq!(r12, expected, "{instruction:#010x}");
+            assert_eq!(hart.pc, 4);
+        }
+    }
+
+    #[test]
+    fn test_crypto_rv32() {
+        let mut memory = [0u8; 0];
+        // x12 = op(x13, x14)
+        for (instruction, src1, src2, expected) in [
+            (0x08f69613u32, 0x0000_ffffu32, 0u32, 0x5555_5555), // zip
+            (0x08f6d613, 0x5555_5555, 0, 0x0000_ffff),          // unzip
+            (0x08e6c633, 0x1111_2222, 0x3333_4444, 0x4444_2222), // pack
+            (0xe2e68633, 0, 0x0100_0000, 0x7c00_0000),          // aes32esi bs3
+            (0xf4e68633, 0, 0x0100_0000, 0x9048_1200),          // sm4ks bs3
+        ] {
+            let mut hart = Hart::new(0u32);
+            *hart.regfile.xregs.get_mut(registers::Register::X13) = src1;
+            *hart.regfile.xregs.get_mut(registers::Register::X14) = src2;
+            hart.execute(instruction, &mut memory).unwrap();
+            let r12 = hart.regfile.xregs.get(registers::Register::X12);
+            assert_eq!(r12, expected, "{instruction:#010x}");
+            assert_eq!(hart.pc, 4);
+        }
+    }
+
+    #[test]
+    fn test_crypto_aes() {
+        // FIPS-197 appendix C.1, the state is held column by column in two
+        // little endian doublewords
+        let key = [0x0706_0504_0302_0100u64, 0x0f0e_0d0c_0b0a_0908];
+        let plaintext = [0x7766_5544_3322_1100u64, 0xffee_ddcc_bbaa_9988];
+        let ciphertext = [0x3004_7b6a_d8e0_c469u64, 0x5ac5_b470_80b7_cdd8];
+        let mut round_keys = [key; 11];
+        for rnum in 0..10 {
+            let [k0, k1] = round_keys[rnum];
+            let t = k1.aes64ks1i(U12::new_truncate(rnum as u16));
+            let k0 = t.aes64ks2(k0);
+            round_keys[rnum + 1] = [k0, k0.aes64ks2(k1)];
+        }
+        let mut state = [plaintext[0] ^ key[0], plaintext[1] ^ key[1]];
+        for (round, [k0, k1]) in round_keys.into_iter().enumerate().skip(1) {
+            let [s0, s1] = state;
+            state = if round < 10 {
+                [s0.aes64esm(s1) ^ k0, s1.aes64esm(s0) ^ k1]
+            } else {
+                [s0.aes64es(s1) ^ k0, s1.aes64es(s0) ^ k1]
+            };
+        }
+        assert_eq!(state, ciphertext);
+        // the equivalent inverse cipher takes the middle round keys through
+        // InvMixColumns
+        let [k0, k1] = round_keys[10];
+        let mut state = [ciphertext[0] ^ k0, ciphertext[1] ^ k1];
+        for round in (0..10).rev() {
+            let [k0, k1] = round_keys[round];
+            let [s0, s1] = state;
+            state = if round > 0 {
+                [
+                    s0.aes64dsm(s1) ^ k0.aes64im(),
+                    s1.aes64dsm(s0) ^ k1.aes64im(),
+                ]
+            } else {
+                [s0.aes64ds(s1) ^ k0, s1.aes64ds(s0) ^ k1]
+            };
+        }
+        assert_eq!(state, plaintext);
+        // a column of the 32-bit ops gathers one byte of each input column
+        let columns = [0x0302_0100u32, 0x0706_0504, 0x0b0a_0908, 0x0f0e_0d0c];
+        let (s0, s1) = (0x0706_0504_0302_0100u64, 0x0f0e_0d0c_0b0a_0908u64);
+        let mut esm = 0u32;
+        let mut dsm = 0u32;
+        for bs in 0..4u8 {
+            let bs = U2::new_truncate(bs);
+            esm = esm.aes32esmi(columns[bs.as_u8() as usize], bs);
+            dsm = dsm.aes32dsmi(columns[(4 - bs.as_u8() as usize) % 4], bs);
+        }
+        assert_eq!(esm, s0.aes64esm(s1) as u32);
+        assert_eq!(dsm, s0.aes64dsm(s1) as u32);
+    }
+
+    #[test]
+    fn test_crypto_sm4() {
+        // GB/T 32907-2016 appendix A.1
+        let key = [0x0123_4567u32, 0x89ab_cdef, 0xfedc_ba98, 0x7654_3210];
+        let ciphertext = [0x681e_df34u32, 0xd206_965e, 0x86b3_e94f, 0x536e_4246];
+        let fk = [0xa3b1_bac6u32, 0x56aa_3350, 0x677d_9197, 0xb270_22dc];
+        let t = |mut acc: u32, x: u32, op: fn(u32, u32, U2) -> u32| {
+            for bs in 0..4 {
+                acc = op(acc, x, U2::new_truncate(bs));
+            }
+            acc
+        };
+        let mut k: Vec<u32> = key.iter().zip(fk).map(|(k, fk)| k ^ fk).collect();
+        let mut x = key.to_vec();
+        for i in 0..32 {
+            let ck = u32::from_be_bytes(core::array::from_fn(|j| ((4 * i + j) * 7) as u8));
+            let rk = t(k[i], k[i + 1] ^ k[i + 2] ^ k[i + 3] ^ ck, Sm4ks::sm4ks);
+            k.push(rk);
+            x.push(t(x[i], x[i + 1] ^ x[i + 2] ^ x[i + 3] ^ rk, Sm4ed::sm4ed));
+        }
+        assert_eq!([x[35], x[34], x[33], x[32]], ciphertext);
+    }
+
+    #[test]
+    fn test_crypto_sha512_rv32() {
+        let x = 0x0123_4567_89ab_cdefu64;
+        let (hi, lo) = ((x >> 32) as u32, x as u32);
+        let join = |hi: u32, lo: u32| (hi as u64) << 32 | lo as u64;
+        assert_eq!(join(hi.sha512sig0h(lo), lo.sha512sig0l(hi)), x.sha512sig0());
+        assert_eq!(join(hi.sha512sig1h(lo), lo.sha512sig1l(hi)), x.sha512sig1());
+        assert_eq!(join(hi.sha512sum0r(lo), lo.sha512sum0r(hi)), x.sha512sum0());
+        assert_eq!(join(hi.sha512sum1r(lo), lo.sha512sum1r(hi)), x.sha512sum1());
+        assert_eq!(
+            x.sha512sum0(),
+            x.rotate_right(28) ^ x.rotate_right(34) ^ x.rotate_right(39)
+        );
+        assert_eq!(
+            x.sha512sig0(),
+            x.rotate_right(1) ^ x.rotate_right(8) ^ x >> 7
+        );
+    }
+
+    #[test]
+    fn test_csr_seed() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.entropy = Entropy::from_seed(7);
+        let mut expected = Entropy::from_seed(7);
+        // csrrw a2, seed, x0
+        hart.execute(0x01501673, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0x8000_0000 | expected.next_u16() as u64);
+        // csrrs a2, seed, x0 only reads and is illegal
+        assert_eq!(
+            hart.execute(0x01502673, &mut memory),
+            Err(Exception::IllegalInstruction(0x01502673))
+        );
+        // without Zkr there is no seed CSR
+        hart.extensions.zkr = false;
+        assert_eq!(
+            hart.execute(0x01501673, &mut memory),
+            Err(Exception::IllegalInstruction(0x01501673))
+        );
+    }
+
+    #[test]
+    fn test_branch_beq() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b0000000_01101_01100_000_00100_1100011;
+        hart.execute(instruction, &mut memory).unwrap();
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_branch_bne() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 64;
+        let instruction = 0b0000000_01101_01100_001_00100_1100011;
+        hart.execute(instruction, &mut memory).unwrap();
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_branch_blt() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 64;
+        let instruction = 0b0000000_01101_01100_100_00100_1100011;
+        hart.execute(instruction, &mut memory).unwrap();
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_branch_bltu() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 64;
+        let instruction = 0b0000000_01101_01100_110_00100_1100011;
+        hart.execute(instruction, &mut memory).unwrap();
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_branch_bge() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) = 64;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 64;
+        let instruction = 0b0000000_01101_01100_101_00100_1100011;
+        hart.execute(instruction, &mut memory).unwrap();
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_branch_bgeu() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) = 65;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 64;
+        let instruction = 0b0000000_01101_01100_111_00100_1100011;
+        hart.execute(instruction, &mut memory).unwrap();
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathw_addw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 100;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 10;
+        let instruction = 0b0000000_01110_01101_000_01100_0111011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 110);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathw_subw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 100;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 10;
+        let instruction = 0b0100000_01110_01101_000_01100_0111011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 90);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathw_sllw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 4;
+        let instruction = 0b0000000_01110_01101_001_01100_0111011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1 << 4);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathw_srlw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 3;
+        let instruction = 0b0000000_01110_01101_101_01100_0111011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 >> 3);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathw_sraw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -1i64 as u64;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 3;
+        let instruction = 0b0100000_01110_01101_101_01100_0111011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12 as u32, ((-1i64).wrapping_shr(3) as u64 as u32));
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathiw_addiw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 100;
+        let instruction = 0b000000000011_01101_000_01100_0011011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 103);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_shiftiw_slliw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1;
+        let instruction = 0b0000000_00011_01101_001_01100_0011011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1 << 3);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_shiftiw_srliw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b0000000_00011_01101_101_01100_0011011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 >> 3);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_shiftiw_sraiw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -1i64 as u64;
+        let instruction = 0b0100000_00011_01101_101_01100_0011011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12 as u32, (-1i64).wrapping_shr(3) as u64 as u32);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_mathw_mulw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0x1_0000_0003;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 0x7fff_ffff;
+        let instruction = 0b0000001_01110_01101_000_01100_0111011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 3u32.wrapping_mul(0x7fff_ffff) as i32 as i64 as u64);
+    }
+
+    #[test]
+    fn test_mathw_divuw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = u64::MAX;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 1;
+        let instruction = 0b0000001_01110_01101_101_01100_0111011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, u64::MAX);
+    }
+
+    #[test]
+    fn test_mathw_remw_by_zero() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0x1_8000_0000;
+        let instruction = 0b0000001_00000_01101_110_01100_0111011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, i32::MIN as i64 as u64);
+    }
+
+    #[test]
+    fn test_fence() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(4u32);
+        // fence rw, rw; fence.i
+        hart.execute(0b0000_0011_0011_00000_000_00000_0001111, &mut memory)
+            .unwrap();
+        hart.execute(0b000000000000_00000_001_00000_0001111, &mut memory)
+            .unwrap();
+        assert_eq!(hart.pc, 12);
+    }
+
+    #[test]
+    fn test_csr_csrrw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 12;
+        *hart.regfile.csrs.get_mut(1).unwrap() = 24;
+        let instruction = 0b000000000001_01101_001_01100_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        let csr1 = hart.regfile.csrs.get(1);
+        assert_eq!(r12, 24);
+        assert_eq!(csr1, 12);
+    }
+
+    #[test]
+    fn test_csr_csrrs() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 12;
+        *hart.regfile.csrs.get_mut(1).unwrap() = 24;
+        let instruction = 0b000000000001_01101_010_01100_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        let csr1 = hart.regfile.csrs.get(1);
+        assert_eq!(r12, 24);
+        assert_eq!(csr1, 24 | 12);
+    }
+
+    #[test]
+    fn test_csr_csrrc() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 12;
+        *hart.regfile.csrs.get_mut(1).unwrap() = 24;
+        let instruction = 0b000000000001_01101_011_01100_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        let csr1 = hart.regfile.csrs.get(1);
+        assert_eq!(r12, 24);
+        assert_eq!(csr1, 24 & !12);
+    }
+
+    #[test]
+    fn test_csr_csrrwi() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.csrs.get_mut(1).unwrap() = 24;
+        let instruction = 0b000000000001_01101_101_01100_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        let csr1 = hart.regfile.csrs.get(1);
+        assert_eq!(r12, 24);
+        assert_eq!(csr1, 13);
+    }
+
+    #[test]
+    fn test_csr_csrrsi() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.csrs.get_mut(1).unwrap() = 24;
+        let instruction = 0b000000000001_01101_110_01100_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        let csr1 = hart.regfile.csrs.get(1);
+        assert_eq!(r12, 24);
+        assert_eq!(csr1, 24 | 13);
+    }
+
+    #[test]
+    fn test_csr_csrrci() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.csrs.get_mut(1).unwrap() = 24;
+        let instruction = 0b000000000001_01101_111_01100_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        let csr1 = hart.regfile.csrs.get(1);
+        assert_eq!(r12, 24);
+        assert_eq!(csr1, 24 & !13);
+    }
+
+    #[test]
+    fn test_csr_readonly_write() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 12;
+        let instruction = 0b110000000001_01101_001_01100_1110011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::IllegalInstruction(instruction))
+        );
+        assert_eq!(hart.pc, 0);
+    }
+
+    #[test]
+    fn test_csr_readonly_write_rs_zero() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) = 24;
+        // csrrw writes even from x0, only csrrs and csrrc read alone
+        let instruction = 0b110000000001_00000_001_01100_1110011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::IllegalInstruction(instruction))
+        );
+        let instruction = 0b110000000001_00000_010_01100_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        let csr1 = hart.regfile.csrs.get(3073);
+        assert_eq!(r12, 0);
+        // `time` only ticks past the instruction that read it
+        assert_eq!(csr1, 1);
+    }
+
+    #[test]
+    fn test_csr_unimplemented() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        for instruction in [
+            0x7c002673u32, // csrr x12, 0x7c0
+            0x7c069073,    // csrw 0x7c0, x13
+        ] {
+            assert_eq!(
+                hart.execute(instruction, &mut memory),
+                Err(Exception::IllegalInstruction(instruction))
+            );
+        }
+        // fflags only exists with F or Zfinx
+        hart.execute(0x00102673, &mut memory).unwrap(); // csrr x12, fflags
+        hart.extensions.f = false;
+        assert_eq!(
+            hart.execute(0x00102673, &mut memory),
+            Err(Exception::IllegalInstruction(0x00102673))
+        );
+        hart.extensions.zfinx = true;
+        hart.execute(0x00102673, &mut memory).unwrap();
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_csr_privilege() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.privilege = Privilege::Supervisor;
+        hart.execute(0x10602673, &mut memory).unwrap(); // csrr x12, scounteren
+        assert_eq!(
+            hart.execute(0x30602673, &mut memory), // csrr x12, mcounteren
+            Err(Exception::IllegalInstruction(0x30602673))
+        );
+        hart.privilege = Privilege::User;
+        assert_eq!(
+            hart.execute(0x10602673, &mut memory),
+            Err(Exception::IllegalInstruction(0x10602673))
+        );
+        hart.execute(0x00102673, &mut memory).unwrap(); // csrr x12, fflags
+    }
+
+    #[test]
+    fn test_csr_machine_information() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        let misa = 2 << 62 | Extensions::ALL.misa() as u64;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = u64::MAX;
+        hart.execute(0x30169073, &mut memory).unwrap(); // csrw misa, x13
+        hart.execute(0x30102673, &mut memory).unwrap(); // csrr x12, misa
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), misa);
+        hart.extensions.m = false;
+        hart.execute(0x30102673, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, misa & !(1 << (b'M' - b'A')));
+        hart.regfile.csrs.set(registers::MHARTID, 3);
+        hart.execute(0xf1402673, &mut memory).unwrap(); // csrr x12, mhartid
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 3);
+        for instruction in [
+            0xf1469073u32, // csrw mhartid, x13
+            0xf1169073,    // csrw mvendorid, x13
+            0xf1269073,    // csrw marchid, x13
+        ] {
+            assert_eq!(
+                hart.execute(instruction, &mut memory),
+                Err(Exception::IllegalInstruction(instruction))
+            );
+        }
+        // Counter enables are 32 bits wide even on RV64
+        hart.execute(0x30669073, &mut memory).unwrap(); // csrw mcounteren, x13
+        assert_eq!(
+            hart.regfile
+                .csrs
+                .read(registers::MCOUNTEREN, &hart.extensions),
+            0xffff_ffff
+        );
+        let mut hart = Hart::new(0u32);
+        hart.execute(0x30102673, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1 << 30 | Extensions::ALL.misa());
+    }
+
+    // One test per row of the Zicsr table of which accesses an instruction
+    // performs. Reads are observed through `seed`, which draws entropy, and
+    // writes through `time`, which is read-only.
+
+    #[test]
+    fn test_zicsr_csrrw_rd_x0() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.entropy = Entropy::from_seed(7);
+        let mut expected = Entropy::from_seed(7);
+        hart.execute(0x01569073, &mut memory).unwrap(); // csrrw x0, seed, x13
+        assert_eq!(hart.entropy.next_u16(), expected.next_u16());
+    }
+
+    #[test]
+    fn test_zicsr_csrrw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.entropy = Entropy::from_seed(7);
+        let mut expected = Entropy::from_seed(7);
+        hart.execute(0x01569673, &mut memory).unwrap(); // csrrw x12, seed, x13
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0x8000_0000 | expected.next_u16() as u64);
+        // Writing x0 writes zero
+        hart.regfile.csrs.set(registers::FFLAGS, 0b10101);
+        hart.execute(0x00101673, &mut memory).unwrap(); // csrrw x12, fflags, x0
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0b10101);
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0);
+    }
+
+    #[test]
+    fn test_zicsr_csrrs_csrrc_rs1_x0() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile.csrs.set(registers::TIME, 5);
+        hart.execute(0xc0102673, &mut memory).unwrap(); // csrrs x12, time, x0
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 5);
+        hart.execute(0xc0103673, &mut memory).unwrap(); // csrrc x12, time, x0
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 6);
+        // Even a zero source register is a write
+        assert_eq!(
+            hart.execute(0xc016a673, &mut memory), // csrrs x12, time, x13
+            Err(Exception::IllegalInstruction(0xc016a673))
+        );
+    }
+
+    #[test]
+    fn test_zicsr_csrrs_csrrc() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile.csrs.set(registers::FFLAGS, 0b00101);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0b10010;
+        hart.execute(0x0016a673, &mut memory).unwrap(); // csrrs x12, fflags, x13
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0b00101);
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0b10111);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0b00110;
+        hart.execute(0x0016b673, &mut memory).unwrap(); // csrrc x12, fflags, x13
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0b10111);
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0b10001);
+    }
+
+    #[test]
+    fn test_zicsr_csrrwi_rd_x0() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.entropy = Entropy::from_seed(7);
+        let mut expected = Entropy::from_seed(7);
+        hart.execute(0x01505073, &mut memory).unwrap(); // csrrwi x0, seed, 0
+        assert_eq!(hart.entropy.next_u16(), expected.next_u16());
+    }
+
+    #[test]
+    fn test_zicsr_csrrwi() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.entropy = Entropy::from_seed(7);
+        let mut expected = Entropy::from_seed(7);
+        hart.execute(0x01505673, &mut memory).unwrap(); // csrrwi x12, seed, 0
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0x8000_0000 | expected.next_u16() as u64);
+        // A zero immediate writes zero
+        hart.regfile.csrs.set(registers::FFLAGS, 0b10101);
+        hart.execute(0x00105673, &mut memory).unwrap(); // csrrwi x12, fflags, 0
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0b10101);
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0);
+    }
+
+    #[test]
+    fn test_zicsr_csrrsi_csrrci_uimm_zero() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile.csrs.set(registers::TIME, 5);
+        hart.execute(0xc0106673, &mut memory).unwrap(); // csrrsi x12, time, 0
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 5);
+        hart.execute(0xc0107673, &mut memory).unwrap(); // csrrci x12, time, 0
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 6);
+        assert_eq!(
+            hart.execute(0xc010f673, &mut memory), // csrrci x12, time, 1
+            Err(Exception::IllegalInstruction(0xc010f673))
+        );
+    }
+
+    #[test]
+    fn test_zicsr_csrrsi_csrrci() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile.csrs.set(registers::FFLAGS, 0b00101);
+        hart.execute(0x00116673, &mut memory).unwrap(); // csrrsi x12, fflags, 2
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0b00101);
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0b00111);
+        hart.execute(0x0010f673, &mut memory).unwrap(); // csrrci x12, fflags, 1
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0b00111);
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0b00110);
+    }
+
+    #[test]
+    fn test_trap_direct() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0x40u64);
+        hart.regfile.csrs.set(registers::MTVEC, 0x100);
+        hart.regfile
+            .csrs
+            .set(registers::MSTATUS, registers::MSTATUS_MIE);
+        let err = hart.execute(ECALL, &mut memory).unwrap_err();
+        hart.trap(err);
+        assert_eq!(hart.pc, 0x100);
+        assert_eq!(hart.privilege, Privilege::Machine);
+        assert_eq!(hart.regfile.csrs.get(registers::MEPC), 0x40);
+        assert_eq!(hart.regfile.csrs.get(registers::MCAUSE), 11);
+        assert_eq!(hart.regfile.csrs.get(registers::MTVAL), 0);
+        let mstatus = hart.regfile.csrs.get(registers::MSTATUS);
+        assert_eq!(mstatus, registers::MSTATUS_MPIE | registers::MSTATUS_MPP);
+    }
+
+    #[test]
+    fn test_trap_vectored() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0x40u32);
+        // Exceptions enter at the base of a vectored mtvec
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0x203;
+        hart.execute(0x30569073, &mut memory).unwrap(); // csrw mtvec, x13
+        let mtvec = hart.regfile.csrs.get(registers::MTVEC);
+        assert_eq!(mtvec as u64, 0x200 | registers::MTVEC_VECTORED);
+        let err = hart.execute(0xffffffff, &mut memory).unwrap_err();
+        hart.trap(err);
+        assert_eq!(hart.pc, 0x200);
+        assert_eq!(hart.regfile.csrs.get(registers::MEPC), 0x44);
+        assert_eq!(hart.regfile.csrs.get(registers::MCAUSE), 2);
+        assert_eq!(hart.regfile.csrs.get(registers::MTVAL), 0xffffffff);
+        let err = hart.execute(EBREAK, &mut memory).unwrap_err();
+        hart.trap(err);
+        assert_eq!(hart.regfile.csrs.get(registers::MCAUSE), 3);
+        assert_eq!(hart.regfile.csrs.get(registers::MTVAL), 0x200);
+    }
+
+    #[test]
+    fn test_mret() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0x100u64);
+        hart.regfile.csrs.set(registers::MEPC, 0x82);
+        let mstatus = registers::MSTATUS_MPIE | registers::MSTATUS_MPP;
+        hart.regfile.csrs.set(registers::MSTATUS, mstatus);
+        hart.execute(0x30200073, &mut memory).unwrap(); // mret
+        assert_eq!(hart.pc, 0x82);
+        assert_eq!(hart.privilege, Privilege::Machine);
+        // MPP is left at U-mode, the least privileged mode
+        let mstatus = hart.regfile.csrs.get(registers::MSTATUS);
+        assert_eq!(mstatus, registers::MSTATUS_MIE | registers::MSTATUS_MPIE);
+        hart.execute(0x34102673, &mut memory).unwrap(); // csrr x12, mepc
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0x82);
+        // mepc[1] reads as zero without C, for csrr and mret alike
+        hart.extensions.c = false;
+        hart.execute(0x34102673, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0x80);
+        hart.execute(0x30200073, &mut memory).unwrap();
+        assert_eq!(hart.pc, 0x80);
+        assert_eq!(hart.privilege, Privilege::User);
+        assert_eq!(
+            hart.execute(0x30200073, &mut memory),
+            Err(Exception::IllegalInstruction(0x30200073))
+        );
+    }
+
+    #[test]
+    fn test_trap_delegation() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0x40u64);
+        // Environment calls from M-mode cannot be delegated
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1 << 2 | 1 << 11;
+        hart.execute(0x30269073, &mut memory).unwrap(); // csrw medeleg, x13
+        assert_eq!(hart.regfile.csrs.get(registers::MEDELEG), 1 << 2);
+        hart.regfile.csrs.set(registers::MTVEC, 0x100);
+        hart.regfile.csrs.set(registers::STVEC, 0x201);
+        hart.regfile
+            .csrs
+            .set(registers::MSTATUS, registers::MSTATUS_SIE);
+        hart.privilege = Privilege::Supervisor;
+        let err = hart.execute(0xffffffff, &mut memory).unwrap_err();
+        hart.trap(err);
+        assert_eq!(hart.pc, 0x200);
+        assert_eq!(hart.privilege, Privilege::Supervisor);
+        assert_eq!(hart.regfile.csrs.get(registers::SEPC), 0x44);
+        assert_eq!(hart.regfile.csrs.get(registers::SCAUSE), 2);
+        assert_eq!(hart.regfile.csrs.get(registers::STVAL), 0xffffffff);
+        let mstatus = hart.regfile.csrs.get(registers::MSTATUS);
+        let stacked = registers::MSTATUS_SPIE | registers::MSTATUS_SPP;
+        assert_eq!(mstatus & !registers::MSTATUS_MPP, stacked);
+        // Exceptions that are not delegated go to M-mode
+        let err = hart.execute(EBREAK, &mut memory).unwrap_err();
+        hart.trap(err);
+        assert_eq!(hart.pc, 0x100);
+        assert_eq!(hart.privilege, Privilege::Machine);
+        assert_eq!(hart.regfile.csrs.get(registers::MCAUSE), 3);
+        // Nor are those raised in M-mode
+        let err = hart.execute(0xffffffff, &mut memory).unwrap_err();
+        hart.trap(err);
+        assert_eq!(hart.pc, 0x100);
+        assert_eq!(hart.regfile.csrs.get(registers::MCAUSE), 2);
+        assert_eq!(hart.regfile.csrs.get(registers::MEPC), 0x100);
+    }
+
+    #[test]
+    fn test_interrupt_csrs() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = u64::MAX;
+        hart.execute(0x30469073, &mut memory).unwrap(); // csrw mie, x13
+        hart.execute(0x34469073, &mut memory).unwrap(); // csrw mip, x13
+        assert_eq!(hart.regfile.csrs.get(registers::MIE), 0xaaa);
+        assert_eq!(hart.regfile.csrs.get(registers::MIP), 0xaaa);
+        // S-mode sees the S-mode interrupts and may only raise or clear SSIP
+        hart.privilege = Privilege::Supervisor;
+        hart.execute(0x10402673, &mut memory).unwrap(); // csrr x12, sie
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0x222);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0;
+        hart.execute(0x10469073, &mut memory).unwrap(); // csrw sie, x13
+        hart.execute(0x14469073, &mut memory).unwrap(); // csrw sip, x13
+        assert_eq!(hart.regfile.csrs.get(registers::MIE), 0x888);
+        assert_eq!(hart.regfile.csrs.get(registers::MIP), 0xaa8);
+        hart.execute(0x14402673, &mut memory).unwrap(); // csrr x12, sip
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0x220);
+    }
+
+    #[test]
+    fn test_interrupt_vectored() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0x40u64);
+        hart.regfile.csrs.set(registers::MTVEC, 0x201);
+        hart.regfile.csrs.set(registers::MIP, 1 << 7);
+        hart.step(&mut memory).unwrap_err();
+        // Taken once enabled in both mie and mstatus
+        hart.regfile.csrs.set(registers::MIE, 1 << 7);
+        hart.step(&mut memory).unwrap_err();
+        hart.regfile
+            .csrs
+            .set(registers::MSTATUS, registers::MSTATUS_MIE);
+        assert_eq!(hart.pending_interrupt(), Some(Interrupt::MachineTimer));
+        hart.step(&mut memory).unwrap();
+        assert_eq!(hart.pc, 0x200 + 4 * 7);
+        assert_eq!(hart.regfile.csrs.get(registers::MEPC), 0x40);
+        assert_eq!(hart.regfile.csrs.get(registers::MCAUSE), 1 << 63 | 7);
+        assert_eq!(hart.regfile.csrs.get(registers::MTVAL), 0);
+        let mstatus = hart.regfile.csrs.get(registers::MSTATUS);
+        assert_eq!(mstatus, registers::MSTATUS_MPIE | registers::MSTATUS_MPP);
+        assert_eq!(hart.pending_interrupt(), None);
+        // The interrupt bit is the top bit of XLEN
+        let mut hart = Hart::new(0u32);
+        hart.regfile.csrs.set(registers::MIE, 1 << 11);
+        hart.regfile.csrs.set(registers::MIP, 1 << 11);
+        hart.privilege = Privilege::User;
+        hart.interrupt(hart.pending_interrupt().unwrap());
+        assert_eq!(hart.pc, 0);
+        assert_eq!(hart.regfile.csrs.get(registers::MCAUSE), 1 << 31 | 11);
+    }
+
+    #[test]
+    fn test_interrupt_delegation() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0x40u64);
+        hart.regfile.csrs.set(registers::MIDELEG, 0x222);
+        hart.regfile.csrs.set(registers::MTVEC, 0x100);
+        hart.regfile.csrs.set(registers::STVEC, 0x201);
+        hart.regfile.csrs.set(registers::MIE, 0xaaa);
+        hart.regfile.csrs.set(registers::MIP, 1 << 1 | 1 << 5);
+        // Delegated interrupts are masked in M-mode even with MIE set
+        hart.regfile
+            .csrs
+            .set(registers::MSTATUS, registers::MSTATUS_MIE);
+        assert_eq!(hart.pending_interrupt(), None);
+        // And in S-mode until SIE is set
+        hart.privilege = Privilege::Supervisor;
+        assert_eq!(hart.pending_interrupt(), None);
+        hart.regfile.csrs.set(
+            registers::MSTATUS,
+            registers::MSTATUS_MIE | registers::MSTATUS_SIE,
+        );
+        // Software interrupts come before timer ones
+        hart.step(&mut memory).unwrap();
+        assert_eq!(hart.pc, 0x204);
+        assert_eq!(hart.privilege, Privilege::Supervisor);
+        assert_eq!(hart.regfile.csrs.get(registers::SEPC), 0x40);
+        assert_eq!(hart.regfile.csrs.get(registers::SCAUSE), 1 << 63 | 1);
+        let mstatus = hart.regfile.csrs.get(registers::MSTATUS);
+        let stacked = registers::MSTATUS_MIE | registers::MSTATUS_SPIE | registers::MSTATUS_SPP;
+        assert_eq!(mstatus, stacked);
+        // Interrupts for M-mode are always enabled below it, and come first
+        hart.regfile.csrs.set(registers::MIP, 1 << 1 | 1 << 3);
+        hart.privilege = Privilege::User;
+        hart.step(&mut memory).unwrap();
+        assert_eq!(hart.pc, 0x100);
+        assert_eq!(hart.privilege, Privilege::Machine);
+        assert_eq!(hart.regfile.csrs.get(registers::MCAUSE), 1 << 63 | 3);
+        assert_eq!(hart.regfile.csrs.get(registers::MEPC), 0x204);
+    }
+
+    #[test]
+    fn test_sret() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0x100u64);
+        hart.regfile.csrs.set(registers::SEPC, 0x82);
+        let mstatus = registers::MSTATUS_SPIE | registers::MSTATUS_SPP;
+        hart.regfile.csrs.set(registers::MSTATUS, mstatus);
+        hart.privilege = Privilege::Supervisor;
+        // sepc[1] reads as zero without C
+        hart.extensions.c = false;
+        hart.execute(0x14102673, &mut memory).unwrap(); // csrr x12, sepc
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0x80);
+        hart.extensions.c = true;
+        hart.execute(0x10200073, &mut memory).unwrap(); // sret
+        assert_eq!(hart.pc, 0x82);
+        assert_eq!(hart.privilege, Privilege::Supervisor);
+        let mstatus = hart.regfile.csrs.get(registers::MSTATUS);
+        assert_eq!(mstatus, registers::MSTATUS_SIE | registers::MSTATUS_SPIE);
+        // SPP was cleared, so this one goes to U-mode, where sret is illegal
+        hart.execute(0x10200073, &mut memory).unwrap();
+        assert_eq!(hart.privilege, Privilege::User);
+        assert_eq!(
+            hart.execute(0x10200073, &mut memory),
+            Err(Exception::IllegalInstruction(0x10200073))
+        );
+        // TSR traps sret in S-mode only
+        hart.regfile
+            .csrs
+            .set(registers::MSTATUS, registers::MSTATUS_TSR);
+        hart.privilege = Privilege::Supervisor;
+        assert_eq!(
+            hart.execute(0x10200073, &mut memory),
+            Err(Exception::IllegalInstruction(0x10200073))
+        );
+        hart.privilege = Privilege::Machine;
+        hart.execute(0x10200073, &mut memory).unwrap();
+        assert_eq!(hart.privilege, Privilege::User);
+    }
+
+    #[test]
+    fn test_sstatus() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        let fields = registers::MSTATUS_MIE | registers::MSTATUS_SIE | registers::MSTATUS_TSR;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = fields;
+        hart.execute(0x30069073, &mut memory).unwrap(); // csrw mstatus, x13
+        hart.privilege = Privilege::Supervisor;
+        hart.execute(0x10002673, &mut memory).unwrap(); // csrr x12, sstatus
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, registers::MSTATUS_SIE);
+        // Writes only reach the supervisor fields of mstatus
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = !registers::MSTATUS_SIE;
+        hart.execute(0x10069073, &mut memory).unwrap(); // csrw sstatus, x13
+        let mstatus = hart.regfile.csrs.get(registers::MSTATUS);
+        let expected = registers::MSTATUS_MIE
+            | registers::MSTATUS_SPIE
+            | registers::MSTATUS_SPP
+            | registers::MSTATUS_SUM
+            | registers::MSTATUS_MXR
+            | registers::MSTATUS_TSR;
+        assert_eq!(mstatus, expected);
+        // mstatus itself is M-mode only
+        assert_eq!(
+            hart.execute(0x30002673, &mut memory),
+            Err(Exception::IllegalInstruction(0x30002673))
+        );
+    }
+
+    #[test]
+    fn test_mstatus_legalization() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        // MPP keeps its value on writes of the reserved mode 2
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 2 << 11;
+        hart.execute(0x30069073, &mut memory).unwrap(); // csrw mstatus, x13
+        let mstatus = hart.regfile.csrs.get(registers::MSTATUS);
+        assert_eq!(mstatus, registers::MSTATUS_MPP);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1 << 11;
+        hart.execute(0x30069073, &mut memory).unwrap();
+        assert_eq!(hart.regfile.csrs.get(registers::MSTATUS), 1 << 11);
+        // Without S-mode its fields and MPP = S cannot be written
+        hart.extensions.s = false;
+        hart.execute(0x30069073, &mut memory).unwrap();
+        let fields = registers::MSTATUS_SIE
+            | registers::MSTATUS_SUM
+            | registers::MSTATUS_TVM
+            | registers::MSTATUS_TSR
+            | registers::MSTATUS_MPP;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = fields;
+        hart.execute(0x30069073, &mut memory).unwrap();
+        assert_eq!(
+            hart.regfile.csrs.get(registers::MSTATUS),
+            registers::MSTATUS_MPP
+        );
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1 << 11;
+        hart.execute(0x30069073, &mut memory).unwrap();
+        assert_eq!(
+            hart.regfile.csrs.get(registers::MSTATUS),
+            registers::MSTATUS_MPP
+        );
+        // Nor do the supervisor CSRs exist
+        assert_eq!(
+            hart.execute(0x10002673, &mut memory),
+            Err(Exception::IllegalInstruction(0x10002673))
+        );
+        // TW and MPRV need U-mode
+        let fields = registers::MSTATUS_TW | registers::MSTATUS_MPRV;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = fields;
+        hart.execute(0x30069073, &mut memory).unwrap();
+        assert_eq!(hart.regfile.csrs.get(registers::MSTATUS), fields);
+        hart.extensions.u = false;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = fields | registers::MSTATUS_MPP;
+        hart.execute(0x30069073, &mut memory).unwrap();
+        assert_eq!(
+            hart.regfile.csrs.get(registers::MSTATUS),
+            registers::MSTATUS_MPP
+        );
+    }
+
+    #[test]
+    fn test_tvm_tw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        // Writes of a reserved mode are ignored
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1 << 60 | 0x1234;
+        hart.execute(0x18069073, &mut memory).unwrap(); // csrw satp, x13
+        assert_eq!(hart.regfile.csrs.get(registers::SATP), 0);
+        hart.privilege = Privilege::Supervisor;
+        hart.execute(0x18002673, &mut memory).unwrap(); // csrr x12, satp
+        hart.execute(0x10500073, &mut memory).unwrap(); // wfi
+        hart.regfile.csrs.set(
+            registers::MSTATUS,
+            registers::MSTATUS_TVM | registers::MSTATUS_TW,
+        );
+        for encoded in [0x18002673, 0x10500073] {
+            assert_eq!(
+                hart.execute(encoded, &mut memory),
+                Err(Exception::IllegalInstruction(encoded))
+            );
+        }
+        hart.privilege = Privilege::Machine;
+        hart.execute(0x18002673, &mut memory).unwrap();
+        hart.execute(0x10500073, &mut memory).unwrap();
+        // U-mode may not wait when S-mode exists
+        hart.regfile.csrs.set(registers::MSTATUS, 0);
+        hart.privilege = Privilege::User;
+        assert_eq!(
+            hart.execute(0x10500073, &mut memory),
+            Err(Exception::IllegalInstruction(0x10500073))
+        );
+        // Dropping S-mode lets it, with no CSR access in between
+        hart.extensions.s = false;
+        hart.execute(0x10500073, &mut memory).unwrap();
+    }
+
+    #[test]
+    fn test_ecall_causes() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        for (privilege, exception) in [
+            (Privilege::User, Exception::EnvironmentCallFromUMode),
+            (Privilege::Supervisor, Exception::EnvironmentCallFromSMode),
+            (Privilege::Machine, Exception::EnvironmentCallFromMMode),
+        ] {
+            hart.privilege = privilege;
+            assert_eq!(hart.execute(ECALL, &mut memory), Err(exception));
+        }
+    }
+
+    #[test]
+    fn test_mprv() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        let mstatus = registers::MSTATUS_MPRV | 1 << 11;
+        hart.regfile.csrs.set(registers::MSTATUS, mstatus);
+        // Only M-mode loads and stores take their privilege from MPP
+        let csrs = &hart.regfile.csrs;
+        assert_eq!(
+            csrs.data_privilege(Privilege::Machine),
+            Privilege::Supervisor
+        );
+        assert_eq!(csrs.data_privilege(Privilege::User), Privilege::User);
+        // Returns below M-mode clear MPRV
+        hart.execute(0x30200073, &mut memory).unwrap(); // mret
+        assert_eq!(hart.privilege, Privilege::Supervisor);
+        let mstatus = hart.regfile.csrs.get(registers::MSTATUS);
+        assert_eq!(mstatus & registers::MSTATUS_MPRV, 0);
+        // but returns to M-mode keep it
+        let mstatus = registers::MSTATUS_MPRV | registers::MSTATUS_MPP;
+        hart.regfile.csrs.set(registers::MSTATUS, mstatus);
+        hart.privilege = Privilege::Machine;
+        hart.execute(0x30200073, &mut memory).unwrap();
+        let mstatus = hart.regfile.csrs.get(registers::MSTATUS);
+        assert_ne!(mstatus & registers::MSTATUS_MPRV, 0);
+        hart.execute(0x10200073, &mut memory).unwrap(); // sret
+        assert_eq!(hart.privilege, Privilege::User);
+        let mstatus = hart.regfile.csrs.get(registers::MSTATUS);
+        assert_eq!(mstatus & registers::MSTATUS_MPRV, 0);
+    }
+
+    /// Maps the virtual page at 0x5000 to the physical page `ppn` with the
+    /// permissions in `flags`, through Sv39 tables at 0x1000, 0x2000 and
+    /// 0x3000.
+    fn map_sv39(hart: &mut Hart<u64>, memory: &mut [u8], ppn: u64, flags: u64) {
+        let leaf = ppn << 10 | mmu::PTE_V | mmu::PTE_A | mmu::PTE_D | flags;
+        for (addr, pte) in [
+            (0x1000, 2 << 10 | mmu::PTE_V),
+            (0x2000, 3 << 10 | mmu::PTE_V),
+            (0x3000 + 5 * 8, leaf),
+        ] {
+            mem::write(&mem::U64::new(pte), memory, addr).unwrap();
+        }
+        hart.regfile.csrs.set(registers::SATP, 8 << 60 | 1);
+    }
+
+    const RWX: u64 = mmu::PTE_R | mmu::PTE_W | mmu::PTE_X;
+
+    #[test]
+    fn test_virtual_memory() {
+        let mut memory = [0u8; 0x10000];
+        let mut hart = Hart::new(0x5000u64);
+        map_sv39(&mut hart, &mut memory, 0xa, RWX);
+        mem::write(&mem::U32::new(0x0006b603), &mut memory, 0xa000).unwrap(); // ld x12, 0(x13)
+        mem::write(&mem::U32::new(0x00073603), &mut memory, 0xa004).unwrap(); // ld x12, 0(x14)
+        mem::write(&mem::U64::new(42), &mut memory, 0xa100).unwrap();
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0x5100;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 0x9000;
+        hart.privilege = Privilege::Supervisor;
+        hart.step(&mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 42);
+        assert_eq!(hart.pc, 0x5004);
+        // Page faults record the faulting virtual address
+        let err = hart.step(&mut memory).unwrap_err();
+        assert_eq!(err, Exception::LoadPageFault(0x9000));
+        hart.trap(err);
+        assert_eq!(hart.regfile.csrs.get(registers::MCAUSE), 13);
+        assert_eq!(hart.regfile.csrs.get(registers::MTVAL), 0x9000);
+        // M-mode addresses are physical, unless MPRV has loads translated
+        hart.pc = 0x5000;
+        assert_eq!(
+            hart.step(&mut memory),
+            Err(Exception::IllegalInstruction(0))
+        );
+        *hart.regfile.xregs.get_mut(registers::Register::X12) = 0;
+        let mstatus = registers::MSTATUS_MPRV | 1 << 11;
+        hart.regfile.csrs.set(registers::MSTATUS, mstatus);
+        hart.execute(0x0006b603, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 42);
+    }
+
+    #[test]
+    fn test_sfence_vma() {
+        let mut memory = [0u8; 0x10000];
+        let mut hart = Hart::new(0u64);
+        map_sv39(&mut hart, &mut memory, 0xa, RWX);
+        mem::write(&mem::U64::new(42), &mut memory, 0xa100).unwrap();
+        mem::write(&mem::U64::new(43), &mut memory, 0xb100).unwrap();
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0x5100;
+        hart.privilege = Privilege::Supervisor;
+        hart.execute(0x0006b603, &mut memory).unwrap(); // ld x12, 0(x13)
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 42);
+        // The old translation stays cached until sfence.vma
+        map_sv39(&mut hart, &mut memory, 0xb, RWX);
+        hart.execute(0x0006b603, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 42);
+        hart.execute(0x12e68073, &mut memory).unwrap(); // sfence.vma x13, x14
+        hart.execute(0x0006b603, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 43);
+        // Illegal in U-mode, and in S-mode under TVM
+        hart.privilege = Privilege::User;
+        assert_eq!(
+            hart.execute(0x12000073, &mut memory),
+            Err(Exception::IllegalInstruction(0x12000073))
+        );
+        hart.privilege = Privilege::Supervisor;
+        hart.regfile
+            .csrs
+            .set(registers::MSTATUS, registers::MSTATUS_TVM);
+        assert_eq!(
+            hart.execute(0x12000073, &mut memory),
+            Err(Exception::IllegalInstruction(0x12000073))
+        );
+    }
+
+    #[test]
+    fn test_float_s_fadd() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 1.3f32.to_bits());
+        let instruction = 0b0000000_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 2.5f32.to_bits());
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_float_s_fsub() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 3.0f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 0.9f32.to_bits());
+        let instruction = 0b0000100_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 2.1f32.to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fmul() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 3.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 2.0f32.to_bits());
+        let instruction = 0b0001000_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 6.4f32.to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fdiv() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 6.4f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 2.0f32.to_bits());
+        let instruction = 0b0001100_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 3.2f32.to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fsqrt() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 16.0f32.to_bits());
+        let instruction = 0b0101100_00000_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 4.0f32.to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fmin() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 6.4f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 2.0f32.to_bits());
+        let instruction = 0b0010100_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 2.0f32.to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fmax() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 6.4f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 2.0f32.to_bits());
+        let instruction = 0b0010100_01110_01101_001_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 6.4f32.to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fsgnj() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 6.0f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (-1.0f32).to_bits());
+        let instruction = 0b0010000_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, (-6.0f32).to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fsgnjn() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 6.0f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (-1.0f32).to_bits());
+        let instruction = 0b0010000_01110_01101_001_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 6.0f32.to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fsgnjx() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 6.0f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (-1.0f32).to_bits());
+        let instruction = 0b0010000_01110_01101_010_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, (-6.0f32).to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fsgnjx_02() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-6.0f32).to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (-1.0f32).to_bits());
+        let instruction = 0b0010000_01110_01101_010_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 6.0f32.to_bits());
+    }
+
+    #[test]
+    fn test_float_s_feq() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-6.0f32).to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (-6.0f32).to_bits());
+        let instruction = 0b1010000_01110_01101_010_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1);
+    }
+
+    #[test]
+    fn test_float_s_fne() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-6.0f32).to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (6.0f32).to_bits());
+        let instruction = 0b1010000_01110_01101_010_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0);
+    }
+
+    #[test]
+    fn test_float_s_flt() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-6.0f32).to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (6.0f32).to_bits());
+        let instruction = 0b1010000_01110_01101_001_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1);
+    }
+
+    #[test]
+    fn test_float_s_fgt() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (6.0f32).to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (1.0f32).to_bits());
+        let instruction = 0b1010000_01110_01101_001_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0);
+    }
+
+    #[test]
+    fn test_float_s_fle() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (1.0f32).to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (1.0f32).to_bits());
+        let instruction = 0b1010000_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1);
+    }
+
+    #[test]
+    fn test_float_s_fle_02() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (1.0f32).to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (2.0f32).to_bits());
+        let instruction = 0b1010000_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1);
+    }
+
+    #[test]
+    fn test_float_s_fge() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (3.0f32).to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (1.0f32).to_bits());
+        let instruction = 0b1010000_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0);
+    }
+
+    #[test]
+    fn test_float_s_fcvtws() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-3.1f32).to_bits());
+        let instruction = 0b1100000_00000_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -3i32 as u32);
+    }
+
+    #[test]
+    fn test_float_s_fcvtwus() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-3.1f32).to_bits());
+        let instruction = 0b1100000_00001_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0);
+    }
+
+    #[test]
+    fn test_float_s_fcvtws_rounding() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-2.5f32).to_bits());
+        // fcvt.w.s x12, f13 with rm set to rne, rtz, rdn, rup and rmm
+        for (rm, expected) in [(0, -2), (1, -2), (2, -3), (3, -2), (4, -3)] {
+            let instruction = 0b1100000_00000_01101_000_01100_1010011 | rm << 12;
+            hart.execute(instruction, &mut memory).unwrap();
+            let r12 = hart.regfile.xregs.get(registers::Register::X12);
+            assert_eq!(r12, expected as u32, "rm {rm}");
+        }
+        assert_eq!(
+            hart.regfile.csrs.get(registers::FFLAGS),
+            softfloat::NX as u32
+        );
+    }
+
+    #[test]
+    fn test_float_s_dynamic_rounding() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-2.5f32).to_bits());
+        // fcvt.w.s x12, f13, dyn
+        let instruction = 0b1100000_00000_01101_111_01100_1010011;
+        *hart.regfile.csrs.get_mut(registers::FRM).unwrap() = 2;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -3i32 as u32);
+        *hart.regfile.csrs.get_mut(registers::FRM).unwrap() = 1;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -2i32 as u32);
+        // frm values 5 to 7 are reserved
+        *hart.regfile.csrs.get_mut(registers::FRM).unwrap() = 5;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::IllegalInstruction(instruction))
+        );
+        // so are static rm values 5 and 6
+        let instruction = 0b1100000_00000_01101_101_01100_1010011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::IllegalInstruction(instruction))
+        );
+    }
+
+    #[test]
+    fn test_float_s_fflags() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 1.0f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 0.0f32.to_bits());
+        // fdiv.s f12, f13, f14
+        let instruction = 0b0001100_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, f32::INFINITY.to_bits());
+        // fsqrt.s f12, f14 with f14 = -1
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, (-1.0f32).to_bits());
+        let instruction = 0b0101100_00000_01110_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, registers::FRegisters::CANONICAL_NAN_S);
+        // csrr x12, fflags
+        let instruction = 0b000000000001_00000_010_01100_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, (softfloat::NV | softfloat::DZ) as u32);
+    }
+
+    #[test]
+    fn test_csr_fcsr() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.csrs.get_mut(registers::FFLAGS).unwrap() = 0b00001;
+        *hart.regfile.csrs.get_mut(registers::FRM).unwrap() = 0b100;
+        // csrrw x12, fcsr, x13
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0xfff_ff_a3;
+        let instruction = 0b000000000011_01101_001_01100_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0b100_00001);
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0b00011);
+        assert_eq!(hart.regfile.csrs.get(registers::FRM), 0b101);
+        // fsrmi x12, 2
+        let instruction = 0b000000000010_00010_101_01100_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0b101);
+        // frcsr x12
+        let instruction = 0b000000000011_00000_010_01100_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0b010_00011);
+    }
+
+    #[test]
+    fn test_float_s_fmin_nan() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 0x7fc0_0001);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 1.0f32.to_bits());
+        // fmin.s f12, f13, f14
+        let instruction = 0b0010100_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 1.0f32.to_bits());
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 0x7f80_0001);
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 1.0f32.to_bits());
+        assert_eq!(
+            hart.regfile.csrs.get(registers::FFLAGS),
+            softfloat::NV as u32
+        );
+        // fmax.s f12, f13, f13 with both operands signalling
+        let instruction = 0b0010100_01101_01101_001_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, registers::FRegisters::CANONICAL_NAN_S);
+    }
+
+    #[test]
+    fn test_float_s_fmvxw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-3.1f32).to_bits());
+        let instruction = 0b1110000_00000_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, (-3.1f32).to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fclass() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (3.1f32).to_bits());
+        let instruction = 0b1110000_00000_01101_001_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1 << 6);
+    }
+
+    #[test]
+    fn test_float_s_fclass_02() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-3.1f32).to_bits());
+        let instruction = 0b1110000_00000_01101_001_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1 << 1);
+    }
+
+    #[test]
+    fn test_float_s_fclass_03() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (f32::INFINITY).to_bits());
+        let instruction = 0b1110000_00000_01101_001_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1 << 7);
+    }
+
+    #[test]
+    fn test_float_s_fclass_04() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile.fregs.set_s(
+            registers::FRegister::F13,
+            (f32::INFINITY).to_bits() | 1 << 31,
+        );
+        let instruction = 0b1110000_00000_01101_001_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1 << 0);
+    }
+
+    #[test]
+    fn test_float_s_fcvtsw() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -12i32 as u32;
+        let instruction = 0b1101000_00000_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, (-12.0f32).to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fcvtswu() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 12;
+        let instruction = 0b1101000_00001_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 12.0f32.to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fmvwx() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 37;
+        let instruction = 0b1111000_00000_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 37);
+    }
+
+    #[test]
+    fn test_fload_word() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        memory[32..36].copy_from_slice(&[255, 255, 0, 0]);
+        let instruction = 0b000000000000_01101_010_01100_0000111;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, f32::from_le_bytes([255, 255, 0, 0]).to_bits());
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_fstore_word() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        hart.regfile.fregs.set_s(
+            registers::FRegister::F12,
+            u32::from_le_bytes([255, 255, 0, 0]),
+        );
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        let instruction = 0b0000000_01100_01101_010_00000_0100111;
+        hart.execute(instruction, &mut memory).unwrap();
+        let data = mem::read::<mem::U32>(&memory, 32).unwrap().as_u32();
+        assert_eq!(data, f32::from_le_bytes([255, 255, 0, 0]).to_bits());
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_fmadd() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F15, 1.3f32.to_bits());
+        let instruction = 0b01111_00_01110_01101_000_01100_1000011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 2.74f32.to_bits());
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_fmsub() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F15, 0.2f32.to_bits());
+        let instruction = 0b01111_00_01110_01101_000_01100_1000111;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        // The product is not rounded before the subtraction.
+        assert_eq!(r12, 1.2f32.mul_add(1.2, -0.2).to_bits());
+        assert_ne!(r12, 1.24f32.to_bits());
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_fnmsub() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F15, 0.0f32.to_bits());
+        let instruction = 0b01111_00_01110_01101_000_01100_1001011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, (-1.44f32).to_bits());
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_fnmadd() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F15, 1.3f32.to_bits());
+        let instruction = 0b01111_00_01110_01101_000_01100_1001111;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, (-2.74f32).to_bits());
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_float_s_fadd_64() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 1.3f32.to_bits());
+        let instruction = 0b0000000_01110_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 2.5f32.to_bits());
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_float_s_fcvtws_64() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-3.1f32).to_bits());
+        let instruction = 0b1100000_00000_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -3i64 as u64);
+    }
+
+    #[test]
+    fn test_float_s_fmvxw_64() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-3.1f32).to_bits());
+        let instruction = 0b1110000_00000_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, (-3.1f32).to_bits() as i32 as i64 as u64);
+    }
+
+    #[test]
+    fn test_float_s_fcvtls() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-1e12f32).to_bits());
+        let instruction = 0b1100000_00010_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -1e12f32 as i64 as u64);
+    }
+
+    #[test]
+    fn test_float_s_fcvtls_nan() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, f32::NAN.to_bits());
+        let instruction = 0b1100000_00010_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, i64::MAX as u64);
+    }
+
+    #[test]
+    fn test_float_s_fcvtlus() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 1e19f32.to_bits());
+        let instruction = 0b1100000_00011_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1e19f32 as u64);
+    }
+
+    #[test]
+    fn test_float_s_fcvtsl() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -(1i64 << 40) as u64;
+        let instruction = 0b1101000_00010_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, (-((1u64 << 40) as f32)).to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fcvtslu() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = u64::MAX;
+        let instruction = 0b1101000_00011_01101_000_01100_1010011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, (u64::MAX as f32).to_bits());
+    }
+
+    #[test]
+    fn test_float_s_fcvtls_rv32() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        let instruction = 0b1100000_00010_01101_000_01100_1010011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::IllegalInstruction(instruction))
+        );
+    }
+
+    #[test]
+    fn test_fload_fstore_word_64() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        memory[32..36].copy_from_slice(&1.5f32.to_le_bytes());
+        // flw f12, 0(x13); fsw f12, 4(x13)
+        hart.execute(0b000000000000_01101_010_01100_0000111, &mut memory)
+            .unwrap();
+        hart.execute(0b0000000_01100_01101_010_00100_0100111, &mut memory)
+            .unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 1.5f32.to_bits());
+        assert_eq!(memory[36..40], 1.5f32.to_le_bytes());
+        assert_eq!(hart.pc, 12);
+    }
+
+    #[test]
+    fn test_fmadd_64() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 1.2f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F15, 1.3f32.to_bits());
+        let instruction = 0b01111_00_01110_01101_000_01100_1000011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.fregs.get_s(registers::FRegister::F12);
+        assert_eq!(r12, 2.74f32.to_bits());
+    }
+
+    #[test]
+    fn test_float_d_fadd_rv32() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F13, 1.25f64.to_bits());
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F14, 2.5f64.to_bits());
+        // fadd.d fa2, fa3, fa4
+        hart.execute(0x02e68653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, 3.75f64.to_bits());
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_float_f0() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F1, 1.0f64.to_bits());
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F2, 2.0f64.to_bits());
+        // fadd.d ft0, ft1, ft2
+        hart.execute(0x02208053, &mut memory).unwrap();
+        let f0 = hart.regfile.fregs.get(registers::FRegister::F0);
+        assert_eq!(f0, 3.0f64.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F0, 1.5f32.to_bits());
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F1, 2.0f32.to_bits());
+        // fadd.s ft0, ft0, ft1
+        hart.execute(0x00100053, &mut memory).unwrap();
+        let f0 = hart.regfile.fregs.get_s(registers::FRegister::F0);
+        assert_eq!(f0, 3.5f32.to_bits());
+    }
+
+    #[test]
+    fn test_float_s_nan_boxing() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F1, 1.0f64.to_bits());
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F2, 2.0f64.to_bits());
+        // fadd.d fa3, ft1, ft2
+        hart.execute(0x022086d3, &mut memory).unwrap();
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 1.0f32.to_bits());
+        // fadd.s fa2, fa3, fa4
+        hart.execute(0x00e68653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(
+            f12,
+            0xffffffff_00000000 | registers::FRegisters::CANONICAL_NAN_S as u64
+        );
+    }
+
+    #[test]
+    fn test_float_s_fmvxw_unboxed() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F13, 0x12345678_9abcdef0);
+        // fmv.x.w a2, fa3
+        hart.execute(0xe0068653, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0xffffffff_9abcdef0);
+    }
+
+    #[test]
+    fn test_float_d_fcvtsd() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F13, 1.5f64.to_bits());
+        // fcvt.s.d fa2, fa3
+        hart.execute(0x40168653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, 0xffffffff_00000000 | 1.5f32.to_bits() as u64);
+    }
+
+    #[test]
+    fn test_float_d_fcvtds() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, (-0.75f32).to_bits());
+        // fcvt.d.s fa2, fa3
+        hart.execute(0x42068653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, (-0.75f64).to_bits());
+        // an unboxed source converts from the canonical NaN
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F13, 1.0f64.to_bits());
+        hart.execute(0x42068653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, 0x7ff80000_00000000);
+    }
+
+    #[test]
+    fn test_fload_fstore_double_rv32() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        memory[40..48].copy_from_slice(&2.75f64.to_le_bytes());
+        // fld fa2, 8(a3); fsd fa2, 16(a3)
+        hart.execute(0x0086b607, &mut memory).unwrap();
+        hart.execute(0x00c6b827, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, 2.75f64.to_bits());
+        assert_eq!(memory[48..56], 2.75f64.to_le_bytes());
+        assert_eq!(hart.pc, 12);
+    }
+
+    #[test]
+    fn test_float_d_fcvtwd_64() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F13, (-3.0f64).to_bits());
+        // fcvt.w.d a2, fa3
+        hart.execute(0xc2068653, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -3i64 as u64);
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F13, 3e9f64.to_bits());
+        // fcvt.wu.d a2, fa3, the 32-bit result is sign extended
+        hart.execute(0xc2168653, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 3_000_000_000u32 as i32 as i64 as u64);
+    }
+
+    #[test]
+    fn test_float_d_fcvtdw_rv32() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -7i32 as u32;
+        // fcvt.d.w fa2, a3
+        hart.execute(0xd2068653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, (-7.0f64).to_bits());
+        // fcvt.d.wu fa2, a3
+        hart.execute(0xd2168653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, ((-7i32 as u32) as f64).to_bits());
+    }
+
+    #[test]
+    fn test_float_d_fmv_64() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0x12345678_9abcdef0;
+        // fmv.d.x fa2, a3; fmv.x.d a2, fa2
+        hart.execute(0xf2068653, &mut memory).unwrap();
+        hart.execute(0xe2060653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(f12, 0x12345678_9abcdef0);
+        assert_eq!(r12, 0x12345678_9abcdef0);
+    }
+
+    #[test]
+    fn test_float_d_fclass() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        for (value, class) in [
+            (f64::NEG_INFINITY.to_bits(), 1 << 0),
+            ((-1.0f64).to_bits(), 1 << 1),
+            (0x80000000_00000001, 1 << 2),
+            ((-0.0f64).to_bits(), 1 << 3),
+            (0, 1 << 4),
+            (1, 1 << 5),
+            (1.0f64.to_bits(), 1 << 6),
+            (f64::INFINITY.to_bits(), 1 << 7),
+            (0x7ff00000_00000001, 1 << 8),
+            (0x7ff80000_00000000, 1 << 9),
+        ] {
+            hart.regfile.fregs.set(registers::FRegister::F13, value);
+            // fclass.d a2, fa3
+            hart.execute(0xe2069653, &mut memory).unwrap();
+            let r12 = hart.regfile.xregs.get(registers::Register::X12);
+            assert_eq!(r12, class, "{value:#x}");
+        }
+    }
+
+    #[test]
+    fn test_float_d_feq_rv32() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F13, 0.0f64.to_bits());
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F14, (-0.0f64).to_bits());
+        // feq.d a2, fa3, fa4
+        hart.execute(0xa2e6a653, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1);
+    }
+
+    #[test]
+    fn test_fmadd_d() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F13, 1.5f64.to_bits());
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F14, 2.0f64.to_bits());
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F15, 0.25f64.to_bits());
+        // fnmsub.d fa2, fa3, fa4, fa5
+        hart.execute(0x7ae6864b, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, (-2.75f64).to_bits());
+    }
+
+    #[test]
+    fn test_float_d_fcvtld_rv32() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        // fcvt.l.d a2, fa3
+        assert_eq!(
+            hart.execute(0xc2268653, &mut memory),
+            Err(Exception::IllegalInstruction(0xc2268653))
+        );
+    }
+
+    #[test]
+    fn test_fload_fstore_half() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        memory[40..42].copy_from_slice(&0x3e00u16.to_le_bytes());
+        // flh fa2, 8(a3); fsh fa2, 16(a3)
+        hart.execute(0x00869607, &mut memory).unwrap();
+        hart.execute(0x00c69827, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, 0xffffffff_ffff3e00);
+        assert_eq!(memory[48..50], 0x3e00u16.to_le_bytes());
+        assert_eq!(hart.pc, 12);
+    }
+
+    #[test]
+    fn test_float_h_fadd() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        // 1.5 + 2.0
+        hart.regfile.fregs.set_h(registers::FRegister::F13, 0x3e00);
+        hart.regfile.fregs.set_h(registers::FRegister::F14, 0x4000);
+        // fadd.h fa2, fa3, fa4
+        hart.execute(0x04e6f653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, 0xffffffff_ffff4300);
+        // a single precision value is not a valid half NaN-box
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F14, 1.0f32.to_bits());
+        hart.execute(0x04e6f653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(
+            f12,
+            0xffffffff_ffff0000 | registers::FRegisters::CANONICAL_NAN_H as u64
+        );
+    }
+
+    #[test]
+    fn test_fmadd_h() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.regfile.fregs.set_h(registers::FRegister::F13, 0x3e00);
+        hart.regfile.fregs.set_h(registers::FRegister::F14, 0x4000);
+        hart.regfile.fregs.set_h(registers::FRegister::F15, 0x3400);
+        // fmadd.h fa2, fa3, fa4, fa5
+        hart.execute(0x7ce6f643, &mut memory).unwrap();
+        assert_eq!(hart.regfile.fregs.get_h(registers::FRegister::F12), 0x4280);
+    }
+
+    #[test]
+    fn test_float_h_fcvths() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        // 1 + 2^-11 is halfway between two halves and rounds to even
+        hart.regfile.fregs.set_s(
+            registers::FRegister::F13,
+            (1.0f32 + 2.0f32.powi(-11)).to_bits(),
+        );
+        // fcvt.h.s fa2, fa3
+        hart.execute(0x4406f653, &mut memory).unwrap();
+        assert_eq!(hart.regfile.fregs.get_h(registers::FRegister::F12), 0x3c00);
+        assert_eq!(
+            hart.regfile.csrs.get(registers::FFLAGS),
+            softfloat::NX as u32
+        );
+        // past the largest half overflows to infinity
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F13, 65520.0f32.to_bits());
+        hart.execute(0x4406f653, &mut memory).unwrap();
+        assert_eq!(hart.regfile.fregs.get_h(registers::FRegister::F12), 0x7c00);
+        assert_eq!(
+            hart.regfile.csrs.get(registers::FFLAGS),
+            (softfloat::OF | softfloat::NX) as u32
+        );
+        // fcvt.s.h fa2, fa3 is exact
+        hart.regfile.fregs.set_h(registers::FRegister::F13, 0xc200);
+        hart.execute(0x40268653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, 0xffffffff_00000000 | (-3.0f32).to_bits() as u64);
+        // fcvt.d.h fa2, fa3
+        hart.execute(0x42268653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, (-3.0f64).to_bits());
+    }
+
+    #[test]
+    fn test_float_h_fcvtwh() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.regfile.fregs.set_h(registers::FRegister::F13, 0xc200);
+        // fcvt.w.h a2, fa3
+        hart.execute(0xc406f653, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -3i64 as u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 7;
+        // fcvt.h.w fa2, a3
+        hart.execute(0xd406f653, &mut memory).unwrap();
+        assert_eq!(hart.regfile.fregs.get_h(registers::FRegister::F12), 0x4700);
+        // The 32-bit result fills XLEN on RV32 too
+        let mut hart = Hart::new(0u32);
+        hart.regfile.fregs.set_h(registers::FRegister::F13, 0xc200);
+        hart.execute(0xc406f653, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, -3i32 as u32);
+    }
+
+    #[test]
+    fn test_float_h_fmv() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0x12345678_9abc8001;
+        // fmv.h.x fa2, a3; fmv.x.h a2, fa2
+        hart.execute(0xf4068653, &mut memory).unwrap();
+        hart.execute(0xe4060653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(f12, 0xffffffff_ffff8001);
+        assert_eq!(r12, 0xffffffff_ffff8001);
+        // fmv.x.h reads the low bits without checking the NaN-boxing
+        hart.regfile
+            .fregs
+            .set(registers::FRegister::F12, 0x12345678_9abc4000);
+        hart.execute(0xe4060653, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0x4000);
+    }
+
+    #[test]
+    fn test_zfinx() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.extensions.f = false;
+        hart.extensions.d = false;
+        hart.extensions.zfinx = true;
+        // the upper bits of single operands are ignored
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = (-1.5f32).to_bits() as u64;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) =
+            0x12345678_00000000 | (-2.0f32).to_bits() as u64;
+        // fadd.s a2, a3, a4
+        hart.execute(0x00e6f653, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0xffffffff_00000000 | (-3.5f32).to_bits() as u64);
+        assert_eq!(hart.regfile.fregs.get(registers::FRegister::F12), 0);
+        // fmv.w.x fa2, a3
+        assert_eq!(
+            hart.execute(0xf0068653, &mut memory),
+            Err(Exception::IllegalInstruction(0xf0068653))
+        );
+        assert_eq!(hart.extensions.misa() & (1 << 5 | 1 << 3), 0);
+    }
+
+    #[test]
+    fn test_zdinx_pairs_rv32() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        hart.extensions.f = false;
+        hart.extensions.d = false;
+        hart.extensions.zfinx = true;
+        hart.extensions.zdinx = true;
+        let xregs = &mut hart.regfile.xregs;
+        *xregs.get_mut(registers::Register::X14) = 1.5f64.to_bits() as u32;
+        *xregs.get_mut(registers::Register::X15) = (1.5f64.to_bits() >> 32) as u32;
+        *xregs.get_mut(registers::Register::X16) = 2.25f64.to_bits() as u32;
+        *xregs.get_mut(registers::Register::X17) = (2.25f64.to_bits() >> 32) as u32;
+        // fadd.d a2, a4, a6
+        hart.execute(0x03077653, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        let r13 = hart.regfile.xregs.get(registers::Register::X13);
+        assert_eq!((r13 as u64) << 32 | r12 as u64, 3.75f64.to_bits());
+        // fcvt.w.d a3, a2
+        hart.execute(0xc20676d3, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X13), 4);
+        // fadd.d zero, a4, a6 leaves x1 alone
+        *hart.regfile.xregs.get_mut(registers::Register::X1) = 7;
+        hart.execute(0x03077053, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X1), 7);
+    }
+
+    #[test]
+    fn test_zfa_fli_fminm() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        // fli.s fa2, 1.0
+        hart.execute(0xf0180653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, 0xffffffff_00000000 | 1.0f32.to_bits() as u64);
+        // fli.d fa2, 0.625
+        hart.execute(0xf2168653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, 0.625f64.to_bits());
+        // fminm.s fa2, fa3, fa4 returns the canonical NaN for a quiet NaN
+        // operand where fmin.s would return the other one
+        let fregs = &mut hart.regfile.fregs;
+        fregs.set(registers::FRegister::F13, 0xffffffff_7fc00001);
+        fregs.set(registers::FRegister::F14, 0xffffffff_3f800000);
+        hart.execute(0x28e6a653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, 0xffffffff_7fc00000);
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0);
+    }
+
+    #[test]
+    fn test_zfa_fround_fleq() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        let fregs = &mut hart.regfile.fregs;
+        fregs.set(
+            registers::FRegister::F13,
+            0xffffffff_00000000 | 2.5f32.to_bits() as u64,
+        );
+        // fround.s fa2, fa3 rounds to even without raising NX
+        hart.execute(0x4046f653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12 as u32, 2.0f32.to_bits());
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0);
+        // froundnx.s fa2, fa3 does
+        hart.execute(0x4056f653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12 as u32, 2.0f32.to_bits());
+        assert_eq!(
+            hart.regfile.csrs.get(registers::FFLAGS),
+            softfloat::NX as u64
+        );
+        // fleq.s a2, fa3, fa4 with a quiet NaN is false without NV
+        *hart.regfile.csrs.get_mut(registers::FFLAGS).unwrap() = 0;
+        let fregs = &mut hart.regfile.fregs;
+        fregs.set(registers::FRegister::F14, 0xffffffff_7fc00000);
+        *hart.regfile.xregs.get_mut(registers::Register::X12) = 7;
+        hart.execute(0xa0e6c653, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0);
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0);
+        // but a signaling one raises it
+        let fregs = &mut hart.regfile.fregs;
+        fregs.set(registers::FRegister::F14, 0xffffffff_7f800001);
+        hart.execute(0xa0e6c653, &mut memory).unwrap();
+        assert_eq!(
+            hart.regfile.csrs.get(registers::FFLAGS),
+            softfloat::NV as u64
+        );
+    }
+
+    #[test]
+    fn test_zfa_fcvtmod_rv32_moves() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        // fcvtmod.w.d a2, fa3, rtz wraps modulo 2^32
+        let fregs = &mut hart.regfile.fregs;
+        fregs.set(registers::FRegister::F13, 4294967301.75f64.to_bits());
+        hart.execute(0xc2869653, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 5);
+        let flags = hart.regfile.csrs.get(registers::FFLAGS);
+        assert_eq!(flags, softfloat::NV as u32);
+        // fmvh.x.d a2, fa3
+        let fregs = &mut hart.regfile.fregs;
+        fregs.set(registers::FRegister::F13, 0x12345678_9abcdef0);
+        hart.execute(0xe2168653, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0x12345678);
+        // fmvp.d.x fa2, a3, a4
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0x9abcdef0;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 0x12345678;
+        hart.execute(0xb2e68653, &mut memory).unwrap();
+        let f12 = hart.regfile.fregs.get(registers::FRegister::F12);
+        assert_eq!(f12, 0x12345678_9abcdef0);
+    }
+
+    #[test]
+    fn test_zcmp_push_popretz() {
+        let mut memory = [0u8; 256];
+        let mut hart = Hart::new(0u32);
+        hart.extensions.zcmp = true;
+        let xregs = &mut hart.regfile.xregs;
+        *xregs.get_mut(registers::Register::X2) = 128;
+        *xregs.get_mut(registers::Register::X1) = 0x100;
+        *xregs.get_mut(registers::Register::X8) = 0x11;
+        *xregs.get_mut(registers::Register::X9) = 0x22;
+        // cm.push {ra, s0-s1}, -32
+        hart.execute(0xb866, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X2), 96);
+        assert_eq!(memory[116..128], [0, 1, 0, 0, 0x11, 0, 0, 0, 0x22, 0, 0, 0]);
+        assert_eq!(hart.pc, 2);
+        let xregs = &mut hart.regfile.xregs;
+        *xregs.get_mut(registers::Register::X1) = 0;
+        *xregs.get_mut(registers::Register::X8) = 0;
+        *xregs.get_mut(registers::Register::X9) = 0;
+        *xregs.get_mut(registers::Register::X10) = 5;
+        // cm.popretz {ra, s0-s1}, 32
+        hart.execute(0xbc66, &mut memory).unwrap();
+        let xregs = &hart.regfile.xregs;
+        assert_eq!(xregs.get(registers::Register::X2), 128);
+        assert_eq!(xregs.get(registers::Register::X8), 0x11);
+        assert_eq!(xregs.get(registers::Register::X9), 0x22);
+        assert_eq!(xregs.get(registers::Register::X10), 0);
+        assert_eq!(hart.pc, 0x100);
+        // a push off the end of memory faults on its first store and
+        // leaves sp alone
+        *hart.regfile.xregs.get_mut(registers::Register::X2) = 0x1000;
+        assert_eq!(
+            hart.execute(0xb866, &mut memory),
+            Err(Exception::StoreAccessFault(0xffc))
+        );
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X2), 0x1000);
+    }
+
+    #[test]
+    fn test_zcmp_moves() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.extensions.zcmp = true;
+        let xregs = &mut hart.regfile.xregs;
+        *xregs.get_mut(registers::Register::X10) = 1;
+        *xregs.get_mut(registers::Register::X11) = 2;
+        // cm.mvsa01 s0, s2
+        hart.execute(0xac2a, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X8), 1);
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X18), 2);
+        let xregs = &mut hart.regfile.xregs;
+        *xregs.get_mut(registers::Register::X9) = 3;
+        *xregs.get_mut(registers::Register::X23) = 4;
+        // cm.mva01s s1, s7
+        hart.execute(0xacfe, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X10), 3);
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X11), 4);
+    }
+
+    #[test]
+    fn test_zcmt_table_jump() {
+        let mut memory = [0u8; 256];
+        let mut hart = Hart::new(0u32);
+        hart.extensions.zcmt = true;
+        *hart.regfile.csrs.get_mut(registers::JVT).unwrap() = 64;
+        memory[68..72].copy_from_slice(&0x40u32.to_le_bytes());
+        memory[224..228].copy_from_slice(&0x81u32.to_le_bytes());
+        // cm.jt 1
+        hart.execute(0xa006, &mut memory).unwrap();
+        assert_eq!(hart.pc, 0x40);
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X1), 0);
+        // cm.jalt 40 links and clears bit 0 of the entry
+        hart.execute(0xa0a2, &mut memory).unwrap();
+        assert_eq!(hart.pc, 0x80);
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X1), 0x42);
+        // reserved jvt mode
+        *hart.regfile.csrs.get_mut(registers::JVT).unwrap() = 65;
+        assert_eq!(
+            hart.execute(0xa006, &mut memory),
+            Err(Exception::IllegalInstruction(0xa006))
+        );
+        // table entries outside memory are instruction access faults
+        *hart.regfile.csrs.get_mut(registers::JVT).unwrap() = 0x1000;
+        assert_eq!(
+            hart.execute(0xa006, &mut memory),
+            Err(Exception::InstructionAccessFault(0x1004))
+        );
+    }
+
+    #[test]
+    fn test_zcmt_table_jump_paged() {
+        let mut memory = [0u8; 0x10000];
+        let mut hart = Hart::new(0u64);
+        hart.extensions.zcmt = true;
+        // Table reads are fetches, so an execute-only table is enough
+        map_sv39(&mut hart, &mut memory, 0xa, mmu::PTE_X);
+        mem::write(&mem::U64::new(0x40), &mut memory, 0xa008).unwrap();
+        hart.privilege = Privilege::Supervisor;
+        *hart.regfile.csrs.get_mut(registers::JVT).unwrap() = 0x5000;
+        hart.execute(0xa006, &mut memory).unwrap(); // cm.jt 1
+        assert_eq!(hart.pc, 0x40);
+        // and unmapped tables raise instruction page faults
+        *hart.regfile.csrs.get_mut(registers::JVT).unwrap() = 0x9000;
+        assert_eq!(
+            hart.execute(0xa006, &mut memory),
+            Err(Exception::InstructionPageFault(0x9008))
+        );
+    }
+
+    #[test]
+    fn test_compressed_fld() {
+        let mut memory = [0u8; 512];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 8;
+        memory[256..264].copy_from_slice(&0.5f64.to_le_bytes());
+        // c.fld fa0, 248(a1)
+        hart.execute(0x3de8, &mut memory).unwrap();
+        let f10 = hart.regfile.fregs.get(registers::FRegister::F10);
+        assert_eq!(f10, 0.5f64.to_bits());
+        assert_eq!(hart.pc, 2);
+    }
+
+    #[test]
+    fn test_bmath_badd() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 100;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 10;
+        let instruction = 0b0000010_01110_01101_000_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 110);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_bmath_bsub() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 100;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 10;
+        let instruction = 0b0100010_01110_01101_000_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 90);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_bmath_bsll() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 5;
+        let instruction = 0b0000010_01110_01101_001_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1 << 5);
+    }
+
+    #[test]
+    fn test_bmath_bslt() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -100i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 10;
+        let instruction = 0b0000010_01110_01101_010_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1);
+    }
+
+    #[test]
+    fn test_bmath_bslt_02() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 100;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = -10i32 as u32;
+        let instruction = 0b0000010_01110_01101_010_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0);
+    }
+
+    #[test]
+    fn test_bmath_bsltu() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -100i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 10;
+        let instruction = 0b0000010_01110_01101_011_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 0);
+    }
+
+    #[test]
+    fn test_bmath_bsltu_02() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -254i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 10;
+        let instruction = 0b0000010_01110_01101_011_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 1);
+    }
+
+    #[test]
+    fn test_bmath_bxor() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 10;
+        let instruction = 0b0000010_01110_01101_100_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 ^ 10);
+    }
+
+    #[test]
+    fn test_bmath_bsrl() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 255;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 6;
+        let instruction = 0b0000010_01110_01101_101_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 255 >> 6);
+    }
+
+    #[test]
+    fn test_bmath_bsra() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = -128i32 as u32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 6;
+        let instruction = 0b0100010_01110_01101_101_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, (-128i8 >> 6) as u8 as u32);
+    }
+
+    #[test]
+    fn test_bmath_bor() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 6;
+        let instruction = 0b0000010_01110_01101_110_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 | 6);
+    }
+
+    #[test]
+    fn test_bmath_band() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X14) = 6;
+        let instruction = 0b0000010_01110_01101_111_01100_0110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        let r12 = hart.regfile.xregs.get(registers::Register::X12);
+        assert_eq!(r12, 32 & 6);
+    }
+
+    #[test]
+    fn test_illegal_opcode() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        let instruction = 0b0000000_00000_00000_000_00000_1111111;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::IllegalInstruction(instruction))
+        );
+        assert_eq!(hart.pc, 0);
+    }
+
+    #[test]
+    fn test_illegal_funct() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        let instruction = 0b1111111_01110_01101_000_01100_0110011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::IllegalInstruction(instruction))
+        );
+    }
+
+    #[test]
+    fn test_illegal_rv64_only() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(0u32);
+        // ld x12, 0(x13)
+        let instruction = 0b000000000000_01101_011_01100_0000011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::IllegalInstruction(instruction))
+        );
+    }
+
+    #[test]
+    fn test_illegal_disabled_extension() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        hart.extensions.m = false;
+        let instruction = 0b0000001_01110_01101_000_01100_0110011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::IllegalInstruction(instruction))
+        );
+    }
+
+    #[test]
+    fn test_load_access_fault() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 62;
+        let instruction = 0b000000000000_01101_010_01100_0000011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::LoadAccessFault(62))
+        );
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_store_access_fault() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 60;
+        let instruction = 0b0000000_01100_01101_011_00000_0100011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::StoreAccessFault(60))
+        );
+        assert_eq!(memory, [0u8; 64]);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_fetch_access_fault() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(64u32);
+        assert_eq!(
+            hart.step(&mut memory),
+            Err(Exception::InstructionAccessFault(64))
+        );
+    }
+
+    #[test]
+    fn test_jal_misaligned() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(4u32);
+        hart.extensions.c = false;
+        // jal x12, 6
+        let instruction = 0b0_0000000011_0_00000000_01100_1101111;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::InstructionAddressMisaligned(10))
+        );
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_ecall() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(8u64);
+        assert_eq!(
+            hart.execute(ECALL, &mut memory),
+            Err(Exception::EnvironmentCallFromMMode)
+        );
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_ebreak() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(8u32);
+        assert_eq!(
+            hart.execute(EBREAK, &mut memory),
+            Err(Exception::Breakpoint(8))
+        );
+        assert_eq!(hart.pc, 8);
+    }
+    #[test]
+    fn test_nop() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(4u32);
+        hart.execute(0b000000000000_00000_000_00000_0010011, &mut memory)
+            .unwrap();
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_hint_discards_x0_writes() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(4u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 12;
+        // addi x0, x13, 5 / lui x0, 1 / add x0, x13, x13 / slli x0, x13, 3
+        for instruction in [
+            0b000000000101_01101_000_00000_0010011,
+            0b00000000000000000001_00000_0110111,
+            0b0000000_01101_01101_000_00000_0110011,
+            0b0000000_00011_01101_001_00000_0010011,
+        ] {
+            hart.execute(instruction, &mut memory).unwrap();
+        }
+        assert_eq!(hart.pc, 20);
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X13), 12);
+    }
+
+    #[test]
+    fn test_jal_x0() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(4u32);
+        let instruction = 0b0_0000000100_0_00000000_00000_1101111;
+        hart.execute(instruction, &mut memory).unwrap();
+        assert_eq!(hart.pc, 12);
+    }
+
+    #[test]
+    fn test_load_x0() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 32;
+        memory[32..36].copy_from_slice(&[1, 2, 3, 4]);
+        let instruction = 0b000000000000_01101_010_00000_0000011;
+        hart.execute(instruction, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X13), 32);
+        assert_eq!(hart.pc, 8);
+    }
+
+    #[test]
+    fn test_load_x0_faults() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(4u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 62;
+        let instruction = 0b000000000000_01101_010_00000_0000011;
+        assert_eq!(
+            hart.execute(instruction, &mut memory),
+            Err(Exception::LoadAccessFault(62))
+        );
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_csr_csrrw_x0() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 12;
+        *hart.regfile.csrs.get_mut(1).unwrap() = 24;
+        let instruction = 0b000000000001_01101_001_00000_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        assert_eq!(hart.regfile.csrs.get(1), 12);
+        assert_eq!(hart.pc, 4);
+    }
+
+    #[test]
+    fn test_csr_csrrwi_x0() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        let instruction = 0b000000000001_00111_101_00000_1110011;
+        hart.execute(instruction, &mut memory).unwrap();
+        assert_eq!(hart.regfile.csrs.get(1), 7);
+    }
+    #[test]
+    fn test_jal_halfword_aligned() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(4u32);
+        // jal x12, 6
+        let instruction = 0b0_0000000011_0_00000000_01100_1101111;
+        hart.execute(instruction, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 8);
+        assert_eq!(hart.pc, 10);
+    }
+
+    #[test]
+    fn test_compressed_addi() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(4u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 40;
+        // c.addi a0, -32
+        hart.execute(0x1501, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X10), 8);
+        assert_eq!(hart.pc, 6);
+    }
+
+    #[test]
+    fn test_compressed_jal() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(8u32);
+        // c.jal 2046
+        hart.execute(0x2ffd, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X1), 10);
+        assert_eq!(hart.pc, 8 + 2046);
+    }
+
+    #[test]
+    fn test_compressed_jalr() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(8u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X5) = 64;
+        // c.jalr t0
+        hart.execute(0x9282, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X1), 10);
+        assert_eq!(hart.pc, 64);
+    }
+
+    #[test]
+    fn test_compressed_branch_not_taken() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(8u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 1;
+        // c.beqz a0, -256
+        hart.execute(0xd101, &mut memory).unwrap();
+        assert_eq!(hart.pc, 10);
+    }
+
+    #[test]
+    fn test_compressed_auipc_after_halfword() {
+        let mut memory = [0u8; 8];
+        // c.nop, then auipc x12, 1 at a halfword aligned address
+        memory[..6].copy_from_slice(&[0x01, 0x00, 0x17, 0x16, 0x00, 0x00]);
+        let mut hart = Hart::new(0u32);
+        hart.step(&mut memory).unwrap();
+        assert_eq!(hart.pc, 2);
+        hart.step(&mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0x1002);
+        assert_eq!(hart.pc, 6);
+    }
+
+    #[test]
+    fn test_compressed_fetch_at_end() {
+        // a compressed instruction in the last halfword of memory
+        let mut memory = [0x13, 0x00, 0x00, 0x00, 0x01, 0x00];
+        let mut hart = Hart::new(4u64);
+        hart.step(&mut memory).unwrap();
+        assert_eq!(hart.pc, 6);
+        // the upper parcel of a 32-bit instruction is missing
+        let mut memory = [0x00, 0x00, 0x13, 0x00];
+        let mut hart = Hart::new(2u64);
+        assert_eq!(
+            hart.step(&mut memory),
+            Err(Exception::InstructionAccessFault(4))
+        );
+    }
+
+    #[test]
+    fn test_compressed_disabled() {
+        let mut memory = [0x01, 0x15, 0x00, 0x00];
+        let mut hart = Hart::new(0u32);
+        hart.extensions.c = false;
+        assert_eq!(
+            hart.step(&mut memory),
+            Err(Exception::IllegalInstruction(0x1501))
+        );
+        hart.pc = 2;
+        assert_eq!(
+            hart.step(&mut memory),
+            Err(Exception::InstructionAddressMisaligned(2))
+        );
+    }
+
+    #[test]
+    fn test_compressed_reserved() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        assert_eq!(
+            hart.execute(0x0000, &mut memory),
+            Err(Exception::IllegalInstruction(0))
+        );
+        // c.fld needs the D extension
+        hart.extensions.d = false;
+        assert_eq!(
+            hart.execute(0x3de8, &mut memory),
+            Err(Exception::IllegalInstruction(0x3de8))
+        );
+    }
+    // a0 = x10, a1 = x11, a2 = x12, a3 = x13
+    const LR_W: u32 = 0x1005a52f; // lr.w a0, (a1)
+    const SC_W: u32 = 0x18d5a62f; // sc.w a2, a3, (a1)
+    const LR_D: u32 = 0x1605b52f; // lr.d.aqrl a0, (a1)
+    const SC_D: u32 = 0x1ad5b62f; // sc.d.rl a2, a3, (a1)
+
+    #[test]
+    fn test_lr_sc() {
+        let mut memory = [0u8; 64];
+        memory[32..36].copy_from_slice(&(-2i32).to_le_bytes());
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 7;
+        hart.execute(LR_W, &mut memory).unwrap();
+        assert_eq!(
+            hart.regfile.xregs.get(registers::Register::X10),
+            -2i64 as u64
+        );
+        assert_eq!(hart.reservation, Some(Reservation { addr: 32, size: 4 }));
+        hart.execute(SC_W, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0);
+        assert_eq!(memory[32..36], 7u32.to_le_bytes());
+        assert_eq!(hart.reservation, None);
+        // the reservation was consumed by the first SC
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 9;
+        hart.execute(SC_W, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 1);
+        assert_eq!(memory[32..36], 7u32.to_le_bytes());
+        assert_eq!(hart.pc, 12);
+    }
+
+    #[test]
+    fn test_lr_sc_double() {
+        let mut memory = [0u8; 64];
+        memory[40..48].copy_from_slice(&u64::MAX.to_le_bytes());
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 40;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1 << 40;
+        hart.execute(LR_D, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X10), u64::MAX);
+        hart.execute(SC_D, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 0);
+        assert_eq!(memory[40..48], (1u64 << 40).to_le_bytes());
+    }
+
+    #[test]
+    fn test_sc_other_address() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 7;
+        hart.execute(LR_W, &mut memory).unwrap();
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 36;
+        hart.execute(SC_W, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 1);
+        assert_eq!(memory, [0u8; 64]);
+        assert_eq!(hart.reservation, None);
+    }
+
+    #[test]
+    fn test_store_breaks_reservation() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 32;
+        hart.execute(LR_W, &mut memory).unwrap();
+        // sb x0, 8(a1) does not touch the reserved word
+        hart.execute(0b0000000_00000_01011_000_01000_0100011, &mut memory)
+            .unwrap();
+        assert!(hart.reservation.is_some());
+        // sb x0, 3(a1) does
+        hart.execute(0b0000000_00000_01011_000_00011_0100011, &mut memory)
+            .unwrap();
+        assert_eq!(hart.reservation, None);
+        hart.execute(SC_W, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X12), 1);
+    }
+
+    #[test]
+    fn test_amo_breaks_reservation() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 32;
+        hart.execute(LR_D, &mut memory).unwrap();
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 36;
+        // amoadd.w a0, a3, (a1)
+        hart.execute(0x00d5a52f, &mut memory).unwrap();
+        assert_eq!(hart.reservation, None);
+    }
+
+    #[test]
+    fn test_amoadd_w_64() {
+        let mut memory = [0u8; 64];
+        memory[32..36].copy_from_slice(&0x7fff_ffffu32.to_le_bytes());
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 1;
+        // amoadd.w a0, a3, (a1)
+        hart.execute(0x00d5a52f, &mut memory).unwrap();
+        assert_eq!(
+            hart.regfile.xregs.get(registers::Register::X10),
+            0x7fff_ffff
+        );
+        assert_eq!(memory[32..36], 0x8000_0000u32.to_le_bytes());
+        assert_eq!(memory[36..40], [0; 4]);
+    }
+
+    #[test]
+    fn test_amomin_w_64() {
+        let mut memory = [0u8; 64];
+        memory[32..36].copy_from_slice(&(-5i32).to_le_bytes());
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 32;
+        // only the low word of rs2 takes part, -6 in 32 bits
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0x1234_5678_ffff_fffa;
+        // amomin.w a0, a3, (a1)
+        hart.execute(0x80d5a52f, &mut memory).unwrap();
+        assert_eq!(
+            hart.regfile.xregs.get(registers::Register::X10),
+            -5i64 as u64
+        );
+        assert_eq!(memory[32..36], (-6i32).to_le_bytes());
+    }
+
+    #[test]
+    fn test_amominu_w() {
+        let mut memory = [0u8; 64];
+        memory[32..36].copy_from_slice(&0x8000_0000u32.to_le_bytes());
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 0x7fff_ffff;
+        // amominu.w a0, a3, (a1)
+        hart.execute(0xc0d5a52f, &mut memory).unwrap();
+        assert_eq!(
+            hart.regfile.xregs.get(registers::Register::X10),
+            0x8000_0000
+        );
+        assert_eq!(memory[32..36], 0x7fff_ffffu32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_amomaxu_d() {
+        let mut memory = [0u8; 64];
+        memory[32..40].copy_from_slice(&3u64.to_le_bytes());
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = u64::MAX;
+        // amomaxu.d a0, a3, (a1)
+        hart.execute(0xe0d5b52f, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X10), 3);
+        assert_eq!(memory[32..40], u64::MAX.to_le_bytes());
+    }
+
+    #[test]
+    fn test_amoswap_same_register() {
+        let mut memory = [0u8; 64];
+        memory[32..36].copy_from_slice(&11u32.to_le_bytes());
+        let mut hart = Hart::new(0u32);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 32;
+        *hart.regfile.xregs.get_mut(registers::Register::X13) = 22;
+        // amoswap.w.aq a3, a3, (a1)
+        hart.execute(0x0cd5a6af, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X13), 11);
+        assert_eq!(memory[32..36], 22u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_amo_misaligned() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 34;
+        assert_eq!(
+            hart.execute(LR_W, &mut memory),
+            Err(Exception::LoadAddressMisaligned(34))
+        );
+        assert_eq!(
+            hart.execute(0x00d5a52f, &mut memory),
+            Err(Exception::StoreAddressMisaligned(34))
+        );
+        assert_eq!(hart.reservation, None);
+        assert_eq!(hart.pc, 0);
+    }
+
+    #[test]
+    fn test_amo_access_fault() {
+        let mut memory = [0u8; 64];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 64;
+        // amoor.d a0, a3, (a1)
+        assert_eq!(
+            hart.execute(0x40d5b52f, &mut memory),
+            Err(Exception::StoreAccessFault(64))
+        );
+    }
+
+    /// `vsetivli zero, avl, vtype`
+    fn vsetivli(hart: &mut Hart<u64>, avl: u32, vtype: u32) {
+        let instruction = 0xc000_7057 | vtype << 20 | avl << 15;
+        hart.execute(instruction, &mut []).unwrap();
+    }
+
+    fn vreg(hart: &Hart<u64>, reg: usize, eew: u32, len: usize) -> Vec<u64> {
+        (0..len)
+            .map(|i| hart.regfile.vregs.get(reg, i, eew))
+            .collect()
+    }
+
+    fn set_vreg(hart: &mut Hart<u64>, reg: usize, eew: u32, values: &[u64]) {
+        for (i, &value) in values.iter().enumerate() {
+            hart.regfile.vregs.set(reg, i, eew, value);
+        }
+    }
+
+    #[test]
+    fn test_vsetvli() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 10;
+        // vsetvli a0, a1, e32, m1, ta, ma
+        hart.execute(0x0d05f557, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X10), 4);
+        assert_eq!(hart.regfile.csrs.get(registers::VL), 4);
+        assert_eq!(hart.regfile.csrs.get(registers::VTYPE), 0xd0);
+        // vsetvli a0, zero, e16, m2, ta, ma sets vl to VLMAX
+        hart.execute(0x0c907557, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X10), 16);
+        // vsetvli zero, zero, e8, m1, ta, ma keeps vl
+        hart.execute(0x0c007057, &mut memory).unwrap();
+        assert_eq!(hart.regfile.csrs.get(registers::VL), 16);
+        assert_eq!(hart.regfile.csrs.get(registers::VLENB), 16);
+        // vsetvli a0, a1, e64, mf8, ta, ma needs ELEN to be 512
+        hart.execute(0x0dd5f557, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X10), 0);
+        assert_eq!(hart.regfile.csrs.get(registers::VTYPE), 1 << 63);
+        // vadd.vv v1, v2, v3
+        assert_eq!(
+            hart.execute(0x022180d7, &mut memory),
+            Err(Exception::IllegalInstruction(0x022180d7))
+        );
+        assert_eq!(hart.pc, 16);
+    }
+
+    #[test]
+    fn test_vle_vse() {
+        let mut memory = [0u8; 64];
+        for (i, value) in [1u32, 2, 3, 4].iter().enumerate() {
+            memory[i * 4..i * 4 + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 32;
+        vsetivli(&mut hart, 4, 0x10);
+        // vle32.v v8, (a0)
+        hart.execute(0x02056407, &mut memory).unwrap();
+        // vadd.vi v8, v8, -3
+        hart.execute(0x028eb457, &mut memory).unwrap();
+        // vse32.v v8, (a1)
+        hart.execute(0x0205e427, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 8, 32, 4), [0xffff_fffe, 0xffff_ffff, 0, 1]);
+        assert_eq!(memory[32..36], (-2i32).to_le_bytes());
+        assert_eq!(memory[44..48], 1u32.to_le_bytes());
+        assert_eq!(memory[48..52], [0; 4]);
+    }
+
+    #[test]
+    fn test_vector_strided_indexed() {
+        let mut memory = [0u8; 32];
+        for (i, byte) in memory.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 16;
+        vsetivli(&mut hart, 2, 0x18);
+        // vlse64.v v8, (a0), a1
+        hart.execute(0x0ab57407, &mut memory).unwrap();
+        assert_eq!(
+            vreg(&hart, 8, 64, 2),
+            [0x0706050403020100, 0x1716151413121110]
+        );
+        vsetivli(&mut hart, 3, 0x10);
+        set_vreg(&mut hart, 4, 16, &[8, 0, 5]);
+        // vluxei16.v v8, (a0), v4
+        hart.execute(0x06455407, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 8, 32, 3), [0x0b0a0908, 0x03020100, 0x08070605]);
+    }
+
+    #[test]
+    fn test_vector_segment() {
+        let mut memory = [0u8; 32];
+        for i in 0..6u16 {
+            memory[i as usize * 2..i as usize * 2 + 2].copy_from_slice(&i.to_le_bytes());
+        }
+        let mut hart = Hart::new(0u64);
+        vsetivli(&mut hart, 2, 0x08);
+        // vlseg3e16.v v8, (a0)
+        hart.execute(0x42055407, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 8, 16, 2), [0, 3]);
+        assert_eq!(vreg(&hart, 9, 16, 2), [1, 4]);
+        assert_eq!(vreg(&hart, 10, 16, 2), [2, 5]);
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 16;
+        vsetivli(&mut hart, 3, 0x00);
+        // vsseg2e8.v v8, (a0)
+        hart.execute(0x22050427, &mut memory).unwrap();
+        assert_eq!(memory[16..23], [0, 1, 0, 0, 3, 4, 0]);
+    }
+
+    #[test]
+    fn test_vector_load_fault() {
+        let mut memory = [0u8; 16];
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 8;
+        vsetivli(&mut hart, 4, 0x10);
+        memory[8] = 1;
+        memory[12] = 2;
+        // vle32.v v8, (a0)
+        assert_eq!(
+            hart.execute(0x02056407, &mut memory),
+            Err(Exception::LoadAccessFault(16))
+        );
+        assert_eq!(hart.regfile.csrs.get(registers::VSTART), 2);
+        assert_eq!(vreg(&hart, 8, 32, 2), [1, 2]);
+        assert_eq!(hart.pc, 4);
+        // vle32ff.v v8, (a0) trims vl instead
+        hart.regfile.csrs.set(registers::VSTART, 0);
+        hart.execute(0x03056407, &mut memory).unwrap();
+        assert_eq!(hart.regfile.csrs.get(registers::VL), 2);
+        assert_eq!(hart.regfile.csrs.get(registers::VSTART), 0);
+        // but still traps on the first element
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 16;
+        assert_eq!(
+            hart.execute(0x03056407, &mut memory),
+            Err(Exception::LoadAccessFault(16))
+        );
+    }
+
+    #[test]
+    fn test_vector_masked() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        vsetivli(&mut hart, 4, 0x10);
+        hart.regfile.vregs.set(0, 0, 8, 0b0101);
+        set_vreg(&mut hart, 1, 32, &[7; 4]);
+        set_vreg(&mut hart, 2, 32, &[0xffff_fffe, 2, 0x8000_0000, 4]);
+        set_vreg(&mut hart, 3, 32, &[0x8000_0000, 3, 0xffff_ffff, 5]);
+        // vmulhsu.vv v1, v2, v3, v0.t
+        hart.execute(0x9821a0d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 32, 4), [0xffff_ffff, 7, 0x8000_0000, 7]);
+        // vadd.vv v0, v2, v3, v0.t
+        assert_eq!(
+            hart.execute(0x00218057, &mut memory),
+            Err(Exception::IllegalInstruction(0x00218057))
+        );
+        // vwaddu.vv v1, v4, v6 with a misaligned destination group
+        assert_eq!(
+            hart.execute(0xc24320d7, &mut memory),
+            Err(Exception::IllegalInstruction(0xc24320d7))
+        );
+        // vwadd.vv v2, v2, v4 with the source at the bottom of the destination
+        assert_eq!(
+            hart.execute(0xc6222157, &mut memory),
+            Err(Exception::IllegalInstruction(0xc6222157))
+        );
+    }
+
+    #[test]
+    fn test_vector_carry() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        vsetivli(&mut hart, 3, 0x00);
+        hart.regfile.vregs.set(0, 0, 8, 0b110);
+        set_vreg(&mut hart, 2, 8, &[0xff, 0xff, 0x10]);
+        set_vreg(&mut hart, 3, 8, &[1, 0, 0x20]);
+        // vadc.vvm v1, v2, v3, v0
+        hart.execute(0x402180d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 8, 3), [0, 0, 0x31]);
+        // vmadc.vvm v1, v2, v3, v0
+        hart.execute(0x442180d7, &mut memory).unwrap();
+        assert_eq!(hart.regfile.vregs.get(1, 0, 8) & 0b111, 0b011);
+        // vmacc.vx v1, a0, v2
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 2;
+        set_vreg(&mut hart, 1, 8, &[1, 2, 3]);
+        hart.execute(0xb62560d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 8, 3), [0xff, 0, 0x23]);
+    }
+
+    #[test]
+    fn test_vector_fixed_point() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        vsetivli(&mut hart, 2, 0x00);
+        set_vreg(&mut hart, 2, 8, &[100, 3]);
+        set_vreg(&mut hart, 3, 8, &[100, 4]);
+        // vsadd.vv v1, v2, v3
+        hart.execute(0x862180d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 8, 2), [0x7f, 7]);
+        assert_eq!(hart.regfile.csrs.get(registers::VXSAT), 1);
+        // vaadd.vv v1, v2, v3 rounding to nearest up, then down
+        hart.execute(0x2621a0d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 8, 2), [100, 4]);
+        hart.regfile
+            .csrs
+            .write(registers::VXRM, 2, &hart.extensions);
+        hart.execute(0x2621a0d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 8, 2), [100, 3]);
+        assert_eq!(
+            hart.regfile.csrs.read(registers::VCSR, &hart.extensions),
+            0b101
+        );
+        // vnclipu.wi v1, v2, 4 with round to odd
+        hart.regfile
+            .csrs
+            .write(registers::VCSR, 0b110, &hart.extensions);
+        set_vreg(&mut hart, 2, 16, &[0x1fff, 0x0028]);
+        hart.execute(0xba2230d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 8, 2), [0xff, 3]);
+        assert_eq!(hart.regfile.csrs.get(registers::VXSAT), 1);
+    }
+
+    #[test]
+    fn test_vector_reduction() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        vsetivli(&mut hart, 3, 0x00);
+        set_vreg(&mut hart, 2, 8, &[200, 200, 200]);
+        set_vreg(&mut hart, 3, 16, &[1000]);
+        set_vreg(&mut hart, 1, 16, &[0, 0x1234]);
+        // vredsum.vs v1, v2, v3
+        hart.execute(0x0221a0d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 8, 2), [(1000 + 600) as u8 as u64, 0]);
+        // vwredsumu.vs v1, v2, v3
+        hart.execute(0xc22180d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 16, 2), [1600, 0x1234]);
+        // no element written with vl = 0
+        vsetivli(&mut hart, 0, 0x00);
+        hart.execute(0xc22180d7, &mut memory).unwrap();
+        hart.execute(0x0221a0d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 16, 1), [1600]);
+    }
+
+    #[test]
+    fn test_vector_float() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        vsetivli(&mut hart, 2, 0x10);
+        let f = |x: f32| x.to_bits() as u64;
+        hart.regfile
+            .fregs
+            .set_s(registers::FRegister::F10, 1.5f32.to_bits());
+        set_vreg(&mut hart, 2, 32, &[f(1.0), f(2.0)]);
+        // vfadd.vf v1, v2, fa0
+        hart.execute(0x022550d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 32, 2), [f(2.5), f(3.5)]);
+        // vfredosum.vs v1, v2, v3
+        set_vreg(&mut hart, 3, 32, &[f(0.5)]);
+        hart.execute(0x0e2190d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 32, 1), [f(3.5)]);
+        // vfwmul.vv v2, v4, v6
+        set_vreg(&mut hart, 4, 32, &[f(1.5), f(-2.0)]);
+        set_vreg(&mut hart, 6, 32, &[f(2.0), f(3.0)]);
+        hart.execute(0xe2431157, &mut memory).unwrap();
+        assert_eq!(
+            vreg(&hart, 2, 64, 2),
+            [3.0f64.to_bits(), (-6.0f64).to_bits()]
+        );
+        // vfncvt.f.f.w v1, v2
+        hart.execute(0x4a2a10d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 32, 2), [f(3.0), f(-6.0)]);
+        assert_eq!(hart.regfile.csrs.get(registers::FFLAGS), 0);
+        // vfcvt.x.f.v v1, v2
+        set_vreg(&mut hart, 2, 32, &[f(2.5), f(-1.5)]);
+        hart.execute(0x4a2090d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 32, 2), [2, 0xffff_fffe]);
+        assert_eq!(
+            hart.regfile.csrs.get(registers::FFLAGS),
+            softfloat::NX as u64
+        );
+        // vfmv.f.s fa0, v2
+        hart.execute(0x42201557, &mut memory).unwrap();
+        assert_eq!(
+            hart.regfile.fregs.get(registers::FRegister::F10),
+            0xffff_ffff_4020_0000
+        );
+        // there is no 8-bit float format
+        vsetivli(&mut hart, 2, 0x00);
+        assert_eq!(
+            hart.execute(0x022550d7, &mut memory),
+            Err(Exception::IllegalInstruction(0x022550d7))
+        );
+    }
+
+    #[test]
+    fn test_vector_mask() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        vsetivli(&mut hart, 8, 0x00);
+        set_vreg(&mut hart, 8, 8, &[1, 1, 0, 1, 0, 0, 1, 1]);
+        // vmseq.vi v0, v8, 0
+        hart.execute(0x62803057, &mut memory).unwrap();
+        assert_eq!(hart.regfile.vregs.get(0, 0, 8), 0b0011_0100);
+        // vcpop.m a0, v0
+        hart.execute(0x42082557, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X10), 3);
+        // vfirst.m a0, v0
+        hart.execute(0x4208a557, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X10), 2);
+        // viota.m v1, v0
+        hart.execute(0x520820d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 8, 8), [0, 0, 0, 1, 1, 2, 3, 3]);
+        // vmsbf.m v1, v0
+        hart.execute(0x5200a0d7, &mut memory).unwrap();
+        assert_eq!(hart.regfile.vregs.get(1, 0, 8), 0b0000_0011);
+        // vid.v v2, v0.t
+        set_vreg(&mut hart, 2, 8, &[0xff; 8]);
+        hart.execute(0x5008a157, &mut memory).unwrap();
+        assert_eq!(
+            vreg(&hart, 2, 8, 8),
+            [0xff, 0xff, 2, 0xff, 4, 5, 0xff, 0xff]
+        );
+        // vfirst.m a0, v1 with no bit set
+        hart.regfile.vregs.set(1, 0, 8, 0);
+        hart.execute(0x4218a557, &mut memory).unwrap();
+        assert_eq!(hart.regfile.xregs.get(registers::Register::X10), u64::MAX);
+    }
+
+    #[test]
+    fn test_vector_permute() {
+        let mut memory = [0u8; 0];
+        let mut hart = Hart::new(0u64);
+        vsetivli(&mut hart, 4, 0x10);
+        set_vreg(&mut hart, 2, 32, &[10, 11, 12, 13]);
+        set_vreg(&mut hart, 1, 32, &[1, 2, 3, 4]);
+        // vslideup.vi v1, v2, 2
+        hart.execute(0x3a2130d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 32, 4), [1, 2, 10, 11]);
+        // vslidedown.vx v1, v2, a0
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 1;
+        hart.execute(0x3e2540d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 32, 4), [11, 12, 13, 0]);
+        // vslide1down.vx v1, v2, a0
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 99;
+        hart.execute(0x3e2560d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 32, 4), [11, 12, 13, 99]);
+        // vrgather.vv v1, v2, v3
+        set_vreg(&mut hart, 3, 32, &[3, 0, 9, 1]);
+        hart.execute(0x322180d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 32, 4), [13, 10, 0, 11]);
+        // vcompress.vm v1, v2, v0
+        hart.regfile.vregs.set(0, 0, 8, 0b1010);
+        hart.execute(0x5e2020d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 32, 4), [11, 13, 0, 11]);
+        // vmv.x.s a0, v2
+        hart.regfile.vregs.set(2, 0, 32, 0xffff_fff0);
+        hart.execute(0x42202557, &mut memory).unwrap();
+        assert_eq!(
+            hart.regfile.xregs.get(registers::Register::X10),
+            0xffff_ffff_ffff_fff0
+        );
+        // vmv.s.x v1, a0
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 5;
+        hart.execute(0x420560d7, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 1, 32, 2), [5, 13]);
+    }
+
+    #[test]
+    fn test_vector_whole_register() {
+        let mut memory = [0u8; 64];
+        for (i, byte) in memory.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let mut hart = Hart::new(0u64);
+        *hart.regfile.xregs.get_mut(registers::Register::X11) = 1;
+        // vsetvli a0, a1, e64, mf8, ta, ma leaves vtype invalid
+        hart.execute(0x0dd5f557, &mut memory).unwrap();
+        // vl2re32.v v2, (a0)
+        hart.execute(0x22856107, &mut memory).unwrap();
+        assert_eq!(hart.regfile.vregs.get(3, 3, 32), 0x1f1e1d1c);
+        // vmv2r.v v4, v2
+        hart.execute(0x9e20b257, &mut memory).unwrap();
+        assert_eq!(vreg(&hart, 4, 64, 4), vreg(&hart, 2, 64, 4));
+        // vs2r.v v2, (a0) after moving a0
+        *hart.regfile.xregs.get_mut(registers::Register::X10) = 32;
+        hart.execute(0x22850127, &mut memory).unwrap();
+        assert_eq!(memory[32..64], memory[0..32].to_vec());
+    }
+}
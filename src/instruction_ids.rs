@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use crate::decode::{U10, U12, U2, U3};
+use crate::decode::{U10, U12, U2, U3, U5, U6, U7, U9};
 
 macro_rules! def_uconst {
     ($($v:vis const $name:ident: $t:ty = $n:expr;)*) => {
@@ -15,6 +15,30 @@ macro_rules! def_uconst {
 }
 
 def_uconst! {
+    // Major opcodes
+    pub const OPCODE_LOAD: U7 = 0b0000011;
+    pub const OPCODE_LOAD_FP: U7 = 0b0000111;
+    pub const OPCODE_MISC_MEM: U7 = 0b0001111;
+    pub const OPCODE_OP_IMM: U7 = 0b0010011;
+    pub const OPCODE_AUIPC: U7 = 0b0010111;
+    pub const OPCODE_OP_IMM_32: U7 = 0b0011011;
+    pub const OPCODE_STORE: U7 = 0b0100011;
+    pub const OPCODE_STORE_FP: U7 = 0b0100111;
+    pub const OPCODE_AMO: U7 = 0b0101111;
+    pub const OPCODE_OP: U7 = 0b0110011;
+    pub const OPCODE_LUI: U7 = 0b0110111;
+    pub const OPCODE_OP_32: U7 = 0b0111011;
+    pub const OPCODE_MADD: U7 = 0b1000011;
+    pub const OPCODE_MSUB: U7 = 0b1000111;
+    pub const OPCODE_NMSUB: U7 = 0b1001011;
+    pub const OPCODE_NMADD: U7 = 0b1001111;
+    pub const OPCODE_OP_FP: U7 = 0b1010011;
+    pub const OPCODE_OP_V: U7 = 0b1010111;
+    pub const OPCODE_BRANCH: U7 = 0b1100011;
+    pub const OPCODE_JALR: U7 = 0b1100111;
+    pub const OPCODE_JAL: U7 = 0b1101111;
+    pub const OPCODE_SYSTEM: U7 = 0b1110011;
+    // Base
     pub const ADD: U10 = 0b0000000_000;
     pub const SUB: U10 = 0b0100000_000;
     pub const SLL: U10 = 0b0000000_001;
@@ -37,9 +61,9 @@ def_uconst! {
     pub const ORI: U3 = 0b110;
     pub const ANDI: U3 = 0b111;
     pub const ADDIW:U3 = 0b000;
-    pub const SLLIW: U10 = 0b0000000_001;
-    pub const SRLIW: U10 = 0b0000000_101;
-    pub const SRAIW: U10 = 0b0100000_101;
+    pub const SLLIW: U9 = 0b000000_001;
+    pub const SRLIW: U9 = 0b000000_101;
+    pub const SRAIW: U9 = 0b010000_101;
     pub const LB: U3 = 0b000;
     pub const LBU: U3 = 0b100;
     pub const LH: U3 = 0b001;
@@ -47,9 +71,9 @@ def_uconst! {
     pub const LW: U3 = 0b010;
     pub const LWU: U3 = 0b110;
     pub const LD: U3 = 0b011;
-    pub const SLLI: U10 = 0b0000000_001;
-    pub const SRLI: U10 = 0b0000000_101;
-    pub const SRAI: U10 = 0b0100000_101;
+    pub const SLLI: U9 = 0b000000_001;
+    pub const SRLI: U9 = 0b000000_101;
+    pub const SRAI: U9 = 0b010000_101;
     pub const SB: U3 = 0b000;
     pub const SH: U3 = 0b001;
     pub const SW: U3 = 0b010;
@@ -60,6 +84,18 @@ def_uconst! {
     pub const BGE: U3 = 0b101;
     pub const BLTU: U3 = 0b110;
     pub const BGEU: U3 = 0b111;
+    pub const JALR: U3 = 0b000;
+    pub const FENCE: U3 = 0b000;
+    pub const PRIV: U3 = 0b000;
+    pub const ECALL: U12 = 0b000000000000;
+    pub const EBREAK: U12 = 0b000000000001;
+    // Privileged architecture
+    pub const MRET: U12 = 0b001100000010;
+    pub const SRET: U12 = 0b000100000010;
+    pub const WFI: U12 = 0b000100000101;
+    pub const SFENCE_VMA: U10 = 0b0001001_000;
+    // Zifencei extension
+    pub const FENCE_I: U3 = 0b001;
     // M extension
     pub const MUL: U10 = 0b0000001_000;
     pub const MULH: U10 = 0b0000001_001;
@@ -69,6 +105,34 @@ def_uconst! {
     pub const DIVU: U10 = 0b0000001_101;
     pub const REM: U10 = 0b0000001_110;
     pub const REMU: U10 = 0b0000001_111;
+    pub const MULW: U10 = 0b0000001_000;
+    pub const DIVW: U10 = 0b0000001_100;
+    pub const DIVUW: U10 = 0b0000001_101;
+    pub const REMW: U10 = 0b0000001_110;
+    pub const REMUW: U10 = 0b0000001_111;
+    // A extension, funct5 and funct3 with aq/rl cleared
+    pub const LR_W: U10 = 0b00010_00_010;
+    pub const SC_W: U10 = 0b00011_00_010;
+    pub const AMOSWAP_W: U10 = 0b00001_00_010;
+    pub const AMOADD_W: U10 = 0b00000_00_010;
+    pub const AMOXOR_W: U10 = 0b00100_00_010;
+    pub const AMOAND_W: U10 = 0b01100_00_010;
+    pub const AMOOR_W: U10 = 0b01000_00_010;
+    pub const AMOMIN_W: U10 = 0b10000_00_010;
+    pub const AMOMAX_W: U10 = 0b10100_00_010;
+    pub const AMOMINU_W: U10 = 0b11000_00_010;
+    pub const AMOMAXU_W: U10 = 0b11100_00_010;
+    pub const LR_D: U10 = 0b00010_00_011;
+    pub const SC_D: U10 = 0b00011_00_011;
+    pub const AMOSWAP_D: U10 = 0b00001_00_011;
+    pub const AMOADD_D: U10 = 0b00000_00_011;
+    pub const AMOXOR_D: U10 = 0b00100_00_011;
+    pub const AMOAND_D: U10 = 0b01100_00_011;
+    pub const AMOOR_D: U10 = 0b01000_00_011;
+    pub const AMOMIN_D: U10 = 0b10000_00_011;
+    pub const AMOMAX_D: U10 = 0b10100_00_011;
+    pub const AMOMINU_D: U10 = 0b11000_00_011;
+    pub const AMOMAXU_D: U10 = 0b11100_00_011;
     // Zicsr Extension
     pub const CSRRW: U3 = 0b001;
     pub const CSRRS: U3 = 0b010;
@@ -85,7 +149,7 @@ def_uconst! {
     pub const FMADD_S: U2 = 0b00;
     pub const FMSUB_S: U2 = 0b00;
     pub const FNMSUB_S: U2 = 0b00;
-    pub const FNADD_S: U2 = 0b00;
+    pub const FNMADD_S: U2 = 0b00;
         // Base Math
     pub const FADD_S: U12 = 0b00000_0000000;
     pub const FSUB_S: U12 = 0b00000_0000100;
@@ -107,6 +171,513 @@ def_uconst! {
     pub const FCVT_S_W: U12 = 0b1101000_00000;
     pub const FCVT_S_WU: U12 = 0b1101000_00001;
     pub const FMV_W_X: U12 = 0b00_1111000_000;
+        // RV64 only
+    pub const FCVT_L_S: U12 = 0b1100000_00010;
+    pub const FCVT_LU_S: U12 = 0b1100000_00011;
+    pub const FCVT_S_L: U12 = 0b1101000_00010;
+    pub const FCVT_S_LU: U12 = 0b1101000_00011;
+    // D Extension
+        // Load
+    pub const FLD: U3 = 0b011;
+        // Store
+    pub const FSD: U3 = 0b011;
+        // Mixed Math
+    pub const FMADD_D: U2 = 0b01;
+    pub const FMSUB_D: U2 = 0b01;
+    pub const FNMSUB_D: U2 = 0b01;
+    pub const FNMADD_D: U2 = 0b01;
+        // Base Math
+    pub const FADD_D: U12 = 0b00000_0000001;
+    pub const FSUB_D: U12 = 0b00000_0000101;
+    pub const FMUL_D: U12 = 0b00000_0001001;
+    pub const FDIV_D: U12 = 0b00000_0001101;
+    pub const FSQRT_D: U12 = 0b00000_0101101;
+    pub const FSGNJ_D: U12 = 0b00_0010001_000;
+    pub const FSGNJN_D: U12 = 0b00_0010001_001;
+    pub const FSGNJX_D: U12 = 0b00_0010001_010;
+    pub const FMIN_D: U12 = 0b00_0010101_000;
+    pub const FMAX_D: U12 = 0b00_0010101_001;
+    pub const FCVT_S_D: U12 = 0b0100000_00001;
+    pub const FCVT_D_S: U12 = 0b0100001_00000;
+    pub const FEQ_D: U12 = 0b00_1010001_010;
+    pub const FLT_D: U12 = 0b00_1010001_001;
+    pub const FLE_D: U12 = 0b00_1010001_000;
+    pub const FCLASS_D: U12 = 0b00_1110001_001;
+    pub const FCVT_W_D: U12 = 0b1100001_00000;
+    pub const FCVT_WU_D: U12 = 0b1100001_00001;
+    pub const FCVT_D_W: U12 = 0b1101001_00000;
+    pub const FCVT_D_WU: U12 = 0b1101001_00001;
+        // RV64 only
+    pub const FCVT_L_D: U12 = 0b1100001_00010;
+    pub const FCVT_LU_D: U12 = 0b1100001_00011;
+    pub const FMV_X_D: U12 = 0b00_1110001_000;
+    pub const FCVT_D_L: U12 = 0b1101001_00010;
+    pub const FCVT_D_LU: U12 = 0b1101001_00011;
+    pub const FMV_D_X: U12 = 0b00_1111001_000;
+    // Zfh Extension, Zfhmin only has the loads, stores, moves and the
+    // conversions between floating-point formats
+        // Load
+    pub const FLH: U3 = 0b001;
+        // Store
+    pub const FSH: U3 = 0b001;
+        // Mixed Math
+    pub const FMADD_H: U2 = 0b10;
+    pub const FMSUB_H: U2 = 0b10;
+    pub const FNMSUB_H: U2 = 0b10;
+    pub const FNMADD_H: U2 = 0b10;
+        // Base Math
+    pub const FADD_H: U12 = 0b00000_0000010;
+    pub const FSUB_H: U12 = 0b00000_0000110;
+    pub const FMUL_H: U12 = 0b00000_0001010;
+    pub const FDIV_H: U12 = 0b00000_0001110;
+    pub const FSQRT_H: U12 = 0b00000_0101110;
+    pub const FSGNJ_H: U12 = 0b00_0010010_000;
+    pub const FSGNJN_H: U12 = 0b00_0010010_001;
+    pub const FSGNJX_H: U12 = 0b00_0010010_010;
+    pub const FMIN_H: U12 = 0b00_0010110_000;
+    pub const FMAX_H: U12 = 0b00_0010110_001;
+    pub const FCVT_S_H: U12 = 0b0100000_00010;
+    pub const FCVT_H_S: U12 = 0b0100010_00000;
+    pub const FCVT_D_H: U12 = 0b0100001_00010;
+    pub const FCVT_H_D: U12 = 0b0100010_00001;
+    pub const FEQ_H: U12 = 0b00_1010010_010;
+    pub const FLT_H: U12 = 0b00_1010010_001;
+    pub const FLE_H: U12 = 0b00_1010010_000;
+    pub const FCLASS_H: U12 = 0b00_1110010_001;
+    pub const FMV_X_H: U12 = 0b00_1110010_000;
+    pub const FCVT_W_H: U12 = 0b1100010_00000;
+    pub const FCVT_WU_H: U12 = 0b1100010_00001;
+    pub const FCVT_H_W: U12 = 0b1101010_00000;
+    pub const FCVT_H_WU: U12 = 0b1101010_00001;
+    pub const FMV_H_X: U12 = 0b00_1111010_000;
+        // RV64 only
+    pub const FCVT_L_H: U12 = 0b1100010_00010;
+    pub const FCVT_LU_H: U12 = 0b1100010_00011;
+    pub const FCVT_H_L: U12 = 0b1101010_00010;
+    pub const FCVT_H_LU: U12 = 0b1101010_00011;
+    // Zfa Extension, the H forms need Zfh
+    pub const FLI_S: U12 = 0b00_1111000_000;
+    pub const FMINM_S: U12 = 0b00_0010100_010;
+    pub const FMAXM_S: U12 = 0b00_0010100_011;
+    pub const FROUND_S: U12 = 0b0100000_00100;
+    pub const FROUNDNX_S: U12 = 0b0100000_00101;
+    pub const FLEQ_S: U12 = 0b00_1010000_100;
+    pub const FLTQ_S: U12 = 0b00_1010000_101;
+    pub const FLI_D: U12 = 0b00_1111001_000;
+    pub const FMINM_D: U12 = 0b00_0010101_010;
+    pub const FMAXM_D: U12 = 0b00_0010101_011;
+    pub const FROUND_D: U12 = 0b0100001_00100;
+    pub const FROUNDNX_D: U12 = 0b0100001_00101;
+    pub const FLEQ_D: U12 = 0b00_1010001_100;
+    pub const FLTQ_D: U12 = 0b00_1010001_101;
+    pub const FCVTMOD_W_D: U12 = 0b1100001_01000;
+    pub const FLI_H: U12 = 0b00_1111010_000;
+    pub const FMINM_H: U12 = 0b00_0010110_010;
+    pub const FMAXM_H: U12 = 0b00_0010110_011;
+    pub const FROUND_H: U12 = 0b0100010_00100;
+    pub const FROUNDNX_H: U12 = 0b0100010_00101;
+    pub const FLEQ_H: U12 = 0b00_1010010_100;
+    pub const FLTQ_H: U12 = 0b00_1010010_101;
+        // RV32 only
+    pub const FMVH_X_D: U12 = 0b00_1110001_000;
+    pub const FMVP_D_X: U12 = 0b00_1011001_000;
+    // C Extension
+        // Quadrants
+    pub const C_Q0: U2 = 0b00;
+    pub const C_Q1: U2 = 0b01;
+    pub const C_Q2: U2 = 0b10;
+        // Quadrant 0
+    pub const C_ADDI4SPN: U3 = 0b000;
+    pub const C_FLD: U3 = 0b001;
+    pub const C_LW: U3 = 0b010;
+    pub const C_FLW_LD: U3 = 0b011;
+    pub const C_FSD: U3 = 0b101;
+    pub const C_SW: U3 = 0b110;
+    pub const C_FSW_SD: U3 = 0b111;
+        // Quadrant 1
+    pub const C_ADDI: U3 = 0b000;
+    pub const C_JAL_ADDIW: U3 = 0b001;
+    pub const C_LI: U3 = 0b010;
+    pub const C_LUI_ADDI16SP: U3 = 0b011;
+    pub const C_MISC_ALU: U3 = 0b100;
+    pub const C_J: U3 = 0b101;
+    pub const C_BEQZ: U3 = 0b110;
+    pub const C_BNEZ: U3 = 0b111;
+            // MISC-ALU funct2
+    pub const C_SRLI: U2 = 0b00;
+    pub const C_SRAI: U2 = 0b01;
+    pub const C_ANDI: U2 = 0b10;
+    pub const C_ARITH: U2 = 0b11;
+            // Register arithmetic, bit 12 and funct2
+    pub const C_SUB: U3 = 0b0_00;
+    pub const C_XOR: U3 = 0b0_01;
+    pub const C_OR: U3 = 0b0_10;
+    pub const C_AND: U3 = 0b0_11;
+    pub const C_SUBW: U3 = 0b1_00;
+    pub const C_ADDW: U3 = 0b1_01;
+        // Quadrant 2
+    pub const C_SLLI: U3 = 0b000;
+    pub const C_FLDSP: U3 = 0b001;
+    pub const C_LWSP: U3 = 0b010;
+    pub const C_FLWSP_LDSP: U3 = 0b011;
+    pub const C_JR_MV_ADD: U3 = 0b100;
+    pub const C_FSDSP: U3 = 0b101;
+    pub const C_SWSP: U3 = 0b110;
+    pub const C_FSWSP_SDSP: U3 = 0b111;
+    // Zcb extension
+        // Quadrant 0, in the C_FSD gap, bits 12:10
+    pub const C_ZCB_MEM: U3 = 0b100;
+    pub const C_LBU: U3 = 0b000;
+    pub const C_LHU_LH: U3 = 0b001;
+    pub const C_SB: U3 = 0b010;
+    pub const C_SH: U3 = 0b011;
+        // Quadrant 1 register arithmetic, bit 12 and funct2
+    pub const C_MUL: U3 = 0b1_10;
+    pub const C_UNARY: U3 = 0b1_11;
+            // Unary, bits 4:2
+    pub const C_ZEXT_B: U3 = 0b000;
+    pub const C_SEXT_B: U3 = 0b001;
+    pub const C_ZEXT_H: U3 = 0b010;
+    pub const C_SEXT_H: U3 = 0b011;
+    pub const C_ZEXT_W: U3 = 0b100;
+    pub const C_NOT: U3 = 0b101;
+    // Zcmp extension, quadrant 2 in the C_FSDSP slot
+        // Bits 12:8
+    pub const CM_PUSH: U5 = 0b11000;
+    pub const CM_POP: U5 = 0b11010;
+    pub const CM_POPRETZ: U5 = 0b11100;
+    pub const CM_POPRET: U5 = 0b11110;
+        // Bits 12:10, then bits 6:5
+    pub const CM_MV: U3 = 0b011;
+    pub const CM_MVSA01: U2 = 0b01;
+    pub const CM_MVA01S: U2 = 0b11;
+    // Zcmt extension, quadrant 2 in the C_FSDSP slot, bits 12:10
+    pub const CM_JT: U3 = 0b000;
+    // Zba extension
+    pub const SH1ADD: U10 = 0b0010000_010;
+    pub const SH2ADD: U10 = 0b0010000_100;
+    pub const SH3ADD: U10 = 0b0010000_110;
+        // RV64 only
+    pub const ADD_UW: U10 = 0b0000100_000;
+    pub const SH1ADD_UW: U10 = 0b0010000_010;
+    pub const SH2ADD_UW: U10 = 0b0010000_100;
+    pub const SH3ADD_UW: U10 = 0b0010000_110;
+    pub const SLLI_UW: U9 = 0b000010_001;
+    // Zbb extension
+    pub const ANDN: U10 = 0b0100000_111;
+    pub const ORN: U10 = 0b0100000_110;
+    pub const XNOR: U10 = 0b0100000_100;
+    pub const MAX: U10 = 0b0000101_110;
+    pub const MAXU: U10 = 0b0000101_111;
+    pub const MIN: U10 = 0b0000101_100;
+    pub const MINU: U10 = 0b0000101_101;
+    pub const ROL: U10 = 0b0110000_001;
+    pub const ROR: U10 = 0b0110000_101;
+    pub const RORI: U9 = 0b011000_101;
+    pub const ZEXT_H: U10 = 0b0000100_100;
+        // Unary, funct12 of OP-IMM in the SLLI and SRLI funct3 spaces
+    pub const UNARY_SLLI: U3 = 0b001;
+    pub const UNARY_SRLI: U3 = 0b101;
+    pub const CLZ: U12 = 0b0110000_00000;
+    pub const CTZ: U12 = 0b0110000_00001;
+    pub const CPOP: U12 = 0b0110000_00010;
+    pub const SEXT_B: U12 = 0b0110000_00100;
+    pub const SEXT_H: U12 = 0b0110000_00101;
+    pub const ORC_B: U12 = 0b0010100_00111;
+    pub const REV8_RV32: U12 = 0b0110100_11000;
+    pub const REV8_RV64: U12 = 0b0110101_11000;
+        // RV64 only, ZEXT_H moves to OP-32 and the counts to OP-IMM-32
+    pub const ROLW: U10 = 0b0110000_001;
+    pub const RORW: U10 = 0b0110000_101;
+    pub const RORIW: U9 = 0b011000_101;
+    pub const CLZW: U12 = 0b0110000_00000;
+    pub const CTZW: U12 = 0b0110000_00001;
+    pub const CPOPW: U12 = 0b0110000_00010;
+    // Zbc extension
+    pub const CLMUL: U10 = 0b0000101_001;
+    pub const CLMULR: U10 = 0b0000101_010;
+    pub const CLMULH: U10 = 0b0000101_011;
+    // Zbs extension
+    pub const BCLR: U10 = 0b0100100_001;
+    pub const BCLRI: U9 = 0b010010_001;
+    pub const BEXT: U10 = 0b0100100_101;
+    pub const BEXTI: U9 = 0b010010_101;
+    pub const BINV: U10 = 0b0110100_001;
+    pub const BINVI: U9 = 0b011010_001;
+    pub const BSET: U10 = 0b0010100_001;
+    pub const BSETI: U9 = 0b001010_001;
+    // Zbkb extension, the rotations, ANDN, ORN, XNOR and REV8 are shared with Zbb
+    pub const PACK: U10 = 0b0000100_100;
+    pub const PACKH: U10 = 0b0000100_111;
+    pub const BREV8: U12 = 0b0110100_00111;
+        // RV32 only
+    pub const ZIP: U12 = 0b0000100_01111;
+    pub const UNZIP: U12 = 0b0000100_01111;
+        // RV64 only
+    pub const PACKW: U10 = 0b0000100_100;
+    // Zbkx extension
+    pub const XPERM4: U10 = 0b0010100_010;
+    pub const XPERM8: U10 = 0b0010100_100;
+    // Zicond extension
+    pub const CZERO_EQZ: U10 = 0b0000111_101;
+    pub const CZERO_NEZ: U10 = 0b0000111_111;
+    // Zkne and Zknd extensions
+        // Byte select, funct7[4:0] under funct3 000 with bs in funct7[6:5]
+    pub const BS_OP: U3 = 0b000;
+    pub const AES32ESI: U5 = 0b10001;
+    pub const AES32ESMI: U5 = 0b10011;
+    pub const AES32DSI: U5 = 0b10101;
+    pub const AES32DSMI: U5 = 0b10111;
+        // RV64 only, AES64KS1I has rnum in the low four bits
+    pub const AES64ES: U10 = 0b0011001_000;
+    pub const AES64ESM: U10 = 0b0011011_000;
+    pub const AES64DS: U10 = 0b0011101_000;
+    pub const AES64DSM: U10 = 0b0011111_000;
+    pub const AES64KS2: U10 = 0b0111111_000;
+    pub const AES64IM: U12 = 0b0011000_00000;
+    pub const AES64KS1I: U12 = 0b0011000_10000;
+    // Zknh extension
+    pub const SHA256SUM0: U12 = 0b0001000_00000;
+    pub const SHA256SUM1: U12 = 0b0001000_00001;
+    pub const SHA256SIG0: U12 = 0b0001000_00010;
+    pub const SHA256SIG1: U12 = 0b0001000_00011;
+        // RV32 only
+    pub const SHA512SUM0R: U10 = 0b0101000_000;
+    pub const SHA512SUM1R: U10 = 0b0101001_000;
+    pub const SHA512SIG0L: U10 = 0b0101010_000;
+    pub const SHA512SIG1L: U10 = 0b0101011_000;
+    pub const SHA512SIG0H: U10 = 0b0101110_000;
+    pub const SHA512SIG1H: U10 = 0b0101111_000;
+        // RV64 only
+    pub const SHA512SUM0: U12 = 0b0001000_00100;
+    pub const SHA512SUM1: U12 = 0b0001000_00101;
+    pub const SHA512SIG0: U12 = 0b0001000_00110;
+    pub const SHA512SIG1: U12 = 0b0001000_00111;
+    // Zksed extension, byte select like the AES32 ones
+    pub const SM4ED: U5 = 0b11000;
+    pub const SM4KS: U5 = 0b11010;
+    // Zksh extension
+    pub const SM3P0: U12 = 0b0001000_01000;
+    pub const SM3P1: U12 = 0b0001000_01001;
+    // V extension
+        // Operand categories, funct3 of OP-V
+    pub const OPIVV: U3 = 0b000;
+    pub const OPFVV: U3 = 0b001;
+    pub const OPMVV: U3 = 0b010;
+    pub const OPIVI: U3 = 0b011;
+    pub const OPIVX: U3 = 0b100;
+    pub const OPFVF: U3 = 0b101;
+    pub const OPMVX: U3 = 0b110;
+    pub const OPCFG: U3 = 0b111;
+        // Loads and stores, width of LOAD-FP and STORE-FP
+    pub const VE8: U3 = 0b000;
+    pub const VE16: U3 = 0b101;
+    pub const VE32: U3 = 0b110;
+    pub const VE64: U3 = 0b111;
+    pub const MOP_UNIT: U2 = 0b00;
+    pub const MOP_INDEXED_UNORDERED: U2 = 0b01;
+    pub const MOP_STRIDED: U2 = 0b10;
+    pub const MOP_INDEXED_ORDERED: U2 = 0b11;
+    pub const LUMOP_UNIT: U5 = 0b00000;
+    pub const LUMOP_WHOLE: U5 = 0b01000;
+    pub const LUMOP_MASK: U5 = 0b01011;
+    pub const LUMOP_FF: U5 = 0b10000;
+        // Integer, funct6 of OPIVV, OPIVX and OPIVI
+    pub const VADD: U6 = 0b000000;
+    pub const VSUB: U6 = 0b000010;
+    pub const VRSUB: U6 = 0b000011;
+    pub const VMINU: U6 = 0b000100;
+    pub const VMIN: U6 = 0b000101;
+    pub const VMAXU: U6 = 0b000110;
+    pub const VMAX: U6 = 0b000111;
+    pub const VAND: U6 = 0b001001;
+    pub const VOR: U6 = 0b001010;
+    pub const VXOR: U6 = 0b001011;
+    pub const VRGATHER: U6 = 0b001100;
+    pub const VSLIDEUP: U6 = 0b001110;
+    pub const VRGATHEREI16: U6 = 0b001110;
+    pub const VSLIDEDOWN: U6 = 0b001111;
+    pub const VADC: U6 = 0b010000;
+    pub const VMADC: U6 = 0b010001;
+    pub const VSBC: U6 = 0b010010;
+    pub const VMSBC: U6 = 0b010011;
+    pub const VMERGE: U6 = 0b010111;
+    pub const VMSEQ: U6 = 0b011000;
+    pub const VMSNE: U6 = 0b011001;
+    pub const VMSLTU: U6 = 0b011010;
+    pub const VMSLT: U6 = 0b011011;
+    pub const VMSLEU: U6 = 0b011100;
+    pub const VMSLE: U6 = 0b011101;
+    pub const VMSGTU: U6 = 0b011110;
+    pub const VMSGT: U6 = 0b011111;
+    pub const VSADDU: U6 = 0b100000;
+    pub const VSADD: U6 = 0b100001;
+    pub const VSSUBU: U6 = 0b100010;
+    pub const VSSUB: U6 = 0b100011;
+    pub const VSLL: U6 = 0b100101;
+    pub const VSMUL: U6 = 0b100111;
+    pub const VMVNR: U6 = 0b100111;
+    pub const VSRL: U6 = 0b101000;
+    pub const VSRA: U6 = 0b101001;
+    pub const VSSRL: U6 = 0b101010;
+    pub const VSSRA: U6 = 0b101011;
+    pub const VNSRL: U6 = 0b101100;
+    pub const VNSRA: U6 = 0b101101;
+    pub const VNCLIPU: U6 = 0b101110;
+    pub const VNCLIP: U6 = 0b101111;
+    pub const VWREDSUMU: U6 = 0b110000;
+    pub const VWREDSUM: U6 = 0b110001;
+        // Integer, funct6 of OPMVV and OPMVX
+    pub const VREDSUM: U6 = 0b000000;
+    pub const VREDAND: U6 = 0b000001;
+    pub const VREDOR: U6 = 0b000010;
+    pub const VREDXOR: U6 = 0b000011;
+    pub const VREDMINU: U6 = 0b000100;
+    pub const VREDMIN: U6 = 0b000101;
+    pub const VREDMAXU: U6 = 0b000110;
+    pub const VREDMAX: U6 = 0b000111;
+    pub const VAADDU: U6 = 0b001000;
+    pub const VAADD: U6 = 0b001001;
+    pub const VASUBU: U6 = 0b001010;
+    pub const VASUB: U6 = 0b001011;
+    pub const VSLIDE1UP: U6 = 0b001110;
+    pub const VSLIDE1DOWN: U6 = 0b001111;
+    pub const VWXUNARY0: U6 = 0b010000;
+    pub const VRXUNARY0: U6 = 0b010000;
+    pub const VXUNARY0: U6 = 0b010010;
+    pub const VMUNARY0: U6 = 0b010100;
+    pub const VCOMPRESS: U6 = 0b010111;
+    pub const VMANDN: U6 = 0b011000;
+    pub const VMAND: U6 = 0b011001;
+    pub const VMOR: U6 = 0b011010;
+    pub const VMXOR: U6 = 0b011011;
+    pub const VMORN: U6 = 0b011100;
+    pub const VMNAND: U6 = 0b011101;
+    pub const VMNOR: U6 = 0b011110;
+    pub const VMXNOR: U6 = 0b011111;
+    pub const VDIVU: U6 = 0b100000;
+    pub const VDIV: U6 = 0b100001;
+    pub const VREMU: U6 = 0b100010;
+    pub const VREM: U6 = 0b100011;
+    pub const VMULHU: U6 = 0b100100;
+    pub const VMUL: U6 = 0b100101;
+    pub const VMULHSU: U6 = 0b100110;
+    pub const VMULH: U6 = 0b100111;
+    pub const VMADD: U6 = 0b101001;
+    pub const VNMSUB: U6 = 0b101011;
+    pub const VMACC: U6 = 0b101101;
+    pub const VNMSAC: U6 = 0b101111;
+    pub const VWADDU: U6 = 0b110000;
+    pub const VWADD: U6 = 0b110001;
+    pub const VWSUBU: U6 = 0b110010;
+    pub const VWSUB: U6 = 0b110011;
+    pub const VWADDU_W: U6 = 0b110100;
+    pub const VWADD_W: U6 = 0b110101;
+    pub const VWSUBU_W: U6 = 0b110110;
+    pub const VWSUB_W: U6 = 0b110111;
+    pub const VWMULU: U6 = 0b111000;
+    pub const VWMULSU: U6 = 0b111010;
+    pub const VWMUL: U6 = 0b111011;
+    pub const VWMACCU: U6 = 0b111100;
+    pub const VWMACC: U6 = 0b111101;
+    pub const VWMACCUS: U6 = 0b111110;
+    pub const VWMACCSU: U6 = 0b111111;
+        // VWXUNARY0 and VRXUNARY0, vs1 and vs2 fields
+    pub const VMV_X_S: U5 = 0b00000;
+    pub const VCPOP: U5 = 0b10000;
+    pub const VFIRST: U5 = 0b10001;
+    pub const VMV_S_X: U5 = 0b00000;
+        // VXUNARY0, vs1 field
+    pub const VZEXT_VF8: U5 = 0b00010;
+    pub const VSEXT_VF8: U5 = 0b00011;
+    pub const VZEXT_VF4: U5 = 0b00100;
+    pub const VSEXT_VF4: U5 = 0b00101;
+    pub const VZEXT_VF2: U5 = 0b00110;
+    pub const VSEXT_VF2: U5 = 0b00111;
+        // VMUNARY0, vs1 field
+    pub const VMSBF: U5 = 0b00001;
+    pub const VMSOF: U5 = 0b00010;
+    pub const VMSIF: U5 = 0b00011;
+    pub const VIOTA: U5 = 0b10000;
+    pub const VID: U5 = 0b10001;
+        // Floating point, funct6 of OPFVV and OPFVF
+    pub const VFADD: U6 = 0b000000;
+    pub const VFREDUSUM: U6 = 0b000001;
+    pub const VFSUB: U6 = 0b000010;
+    pub const VFREDOSUM: U6 = 0b000011;
+    pub const VFMIN: U6 = 0b000100;
+    pub const VFREDMIN: U6 = 0b000101;
+    pub const VFMAX: U6 = 0b000110;
+    pub const VFREDMAX: U6 = 0b000111;
+    pub const VFSGNJ: U6 = 0b001000;
+    pub const VFSGNJN: U6 = 0b001001;
+    pub const VFSGNJX: U6 = 0b001010;
+    pub const VFSLIDE1UP: U6 = 0b001110;
+    pub const VFSLIDE1DOWN: U6 = 0b001111;
+    pub const VWFUNARY0: U6 = 0b010000;
+    pub const VRFUNARY0: U6 = 0b010000;
+    pub const VFUNARY0: U6 = 0b010010;
+    pub const VFUNARY1: U6 = 0b010011;
+    pub const VFMERGE: U6 = 0b010111;
+    pub const VMFEQ: U6 = 0b011000;
+    pub const VMFLE: U6 = 0b011001;
+    pub const VMFLT: U6 = 0b011011;
+    pub const VMFNE: U6 = 0b011100;
+    pub const VMFGT: U6 = 0b011101;
+    pub const VMFGE: U6 = 0b011111;
+    pub const VFDIV: U6 = 0b100000;
+    pub const VFRDIV: U6 = 0b100001;
+    pub const VFMUL: U6 = 0b100100;
+    pub const VFRSUB: U6 = 0b100111;
+    pub const VFMADD: U6 = 0b101000;
+    pub const VFNMADD: U6 = 0b101001;
+    pub const VFMSUB: U6 = 0b101010;
+    pub const VFNMSUB: U6 = 0b101011;
+    pub const VFMACC: U6 = 0b101100;
+    pub const VFNMACC: U6 = 0b101101;
+    pub const VFMSAC: U6 = 0b101110;
+    pub const VFNMSAC: U6 = 0b101111;
+    pub const VFWADD: U6 = 0b110000;
+    pub const VFWREDUSUM: U6 = 0b110001;
+    pub const VFWSUB: U6 = 0b110010;
+    pub const VFWREDOSUM: U6 = 0b110011;
+    pub const VFWADD_W: U6 = 0b110100;
+    pub const VFWSUB_W: U6 = 0b110110;
+    pub const VFWMUL: U6 = 0b111000;
+    pub const VFWMACC: U6 = 0b111100;
+    pub const VFWNMACC: U6 = 0b111101;
+    pub const VFWMSAC: U6 = 0b111110;
+    pub const VFWNMSAC: U6 = 0b111111;
+        // VWFUNARY0 and VRFUNARY0, vs1 and vs2 fields
+    pub const VFMV_F_S: U5 = 0b00000;
+    pub const VFMV_S_F: U5 = 0b00000;
+        // VFUNARY0, vs1 field
+    pub const VFCVT_XU_F_V: U5 = 0b00000;
+    pub const VFCVT_X_F_V: U5 = 0b00001;
+    pub const VFCVT_F_XU_V: U5 = 0b00010;
+    pub const VFCVT_F_X_V: U5 = 0b00011;
+    pub const VFCVT_RTZ_XU_F_V: U5 = 0b00110;
+    pub const VFCVT_RTZ_X_F_V: U5 = 0b00111;
+    pub const VFWCVT_XU_F_V: U5 = 0b01000;
+    pub const VFWCVT_X_F_V: U5 = 0b01001;
+    pub const VFWCVT_F_XU_V: U5 = 0b01010;
+    pub const VFWCVT_F_X_V: U5 = 0b01011;
+    pub const VFWCVT_F_F_V: U5 = 0b01100;
+    pub const VFWCVT_RTZ_XU_F_V: U5 = 0b01110;
+    pub const VFWCVT_RTZ_X_F_V: U5 = 0b01111;
+    pub const VFNCVT_XU_F_W: U5 = 0b10000;
+    pub const VFNCVT_X_F_W: U5 = 0b10001;
+    pub const VFNCVT_F_XU_W: U5 = 0b10010;
+    pub const VFNCVT_F_X_W: U5 = 0b10011;
+    pub const VFNCVT_F_F_W: U5 = 0b10100;
+    pub const VFNCVT_ROD_F_F_W: U5 = 0b10101;
+    pub const VFNCVT_RTZ_XU_F_W: U5 = 0b10110;
+    pub const VFNCVT_RTZ_X_F_W: U5 = 0b10111;
+        // VFUNARY1, vs1 field
+    pub const VFSQRT: U5 = 0b00000;
+    pub const VFRSQRT7: U5 = 0b00100;
+    pub const VFREC7: U5 = 0b00101;
+    pub const VFCLASS: U5 = 0b10000;
     // Custom Extension Byte Math
     pub const BADD: U10 = 0b0000010_000;
     pub const BSUB: U10 = 0b0100010_000;
@@ -1,213 +1,813 @@
-use crate::decode::{Shift, B, I, J, R, R4, S, U, U12, U5};
-use crate::error::Error;
-use crate::instruction_ids::*;
-use crate::num::{As, Unsigned};
+use crate::decode::{Amo, Cmjt, Cmmv, Cmpp, Shift, B, I, J, R, R4, S, U, U12, U2, U3, U5, U6};
+use crate::entropy::Entropy;
+use crate::isa::{Extensions, Privilege, Xlen};
+use crate::mem::Reservation;
+use crate::mmu::{Access, Bus, Tlb};
+use crate::num::{As, Unsigned, UnsignedWrapping};
 use crate::ops::*;
-use crate::registers::{CsrRegisters, Registers, Zero, ZeroOrRegister};
+use crate::registers::{
+    CsrRegisters, FRegisters, ProgramCounter, RegFile, Register, Registers, Zero, ZeroOrRegister,
+    FRM, JVT, MEPC, MSTATUS, MSTATUS_MIE, MSTATUS_MPIE, MSTATUS_MPP, MSTATUS_MPRV, MSTATUS_SIE,
+    MSTATUS_SPIE, MSTATUS_SPP, MSTATUS_TSR, MSTATUS_TVM, MSTATUS_TW, SEED_ES16, SEPC,
+};
+use crate::softfloat::{FloatEnv, RoundingMode};
+use crate::trap::Exception;
+use crate::vector::Vector;
 
-const OPCODE_SIZE: u32 = 4;
+// The handlers only see the decoded instruction fields, `Hart::execute` fills
+// in the raw encoding before the exception leaves the hart.
+pub(crate) const ILLEGAL_INSTRUCTION: Exception = Exception::IllegalInstruction(0);
+
+// Each handler implements the register and memory plumbing shared by an
+// instruction format, the operation itself is picked by the caller from the
+// decoded `Instruction`.
 
 pub trait Math: Sized {
-    fn math(instruction: R, regs: &mut Registers<Self>) -> Result<(), Error>;
-}
+    fn math(
+        instruction: R,
+        regs: &mut Registers<Self>,
+        op: fn(Self, Self) -> Self,
+    ) -> Result<(), Exception>;
 
-pub trait MathW: Sized {
-    fn mathw(instruction: R, regs: &mut Registers<Self>) -> Result<(), Error>;
+    /// The `aes32*` and `sm4*` ops, which select a byte of `rs2` with `bs`
+    /// in the top bits of `funct7`.
+    fn math_bs(
+        instruction: R,
+        regs: &mut Registers<Self>,
+        op: fn(Self, Self, U2) -> Self,
+    ) -> Result<(), Exception>;
 }
 
 pub trait MathI: Sized {
-    fn mathi(instruction: I, regs: &mut Registers<Self>) -> Result<(), Error>;
-}
+    fn mathi(
+        instruction: I,
+        regs: &mut Registers<Self>,
+        op: fn(Self, U12) -> Self,
+    ) -> Result<(), Exception>;
 
-pub trait MathIW: Sized {
-    fn mathiw(instruction: I, regs: &mut Registers<Self>) -> Result<(), Error>;
+    /// Unary ops of Zbb, whose immediate selects the operation.
+    fn unary(
+        instruction: I,
+        regs: &mut Registers<Self>,
+        op: fn(Self) -> Self,
+    ) -> Result<(), Exception>;
 }
 
 pub trait ShiftI: Sized {
-    fn shifti(instruction: Shift, regs: &mut Registers<Self>) -> Result<(), Error>;
-}
+    fn shifti(
+        instruction: Shift,
+        regs: &mut Registers<Self>,
+        op: fn(Self, U6) -> Self,
+    ) -> Result<(), Exception>;
 
-pub trait ShiftIW: Sized {
-    fn shiftiw(instruction: Shift, regs: &mut Registers<Self>) -> Result<(), Error>;
+    /// 32-bit shifts of RV64, which only take the low five bits of `shamt`.
+    fn shiftiw(
+        instruction: Shift,
+        regs: &mut Registers<Self>,
+        op: fn(Self, U5) -> Self,
+    ) -> Result<(), Exception>;
 }
 
 pub trait Load: Sized {
-    fn load(instruction: I, regs: &mut Registers<Self>, memory: &[u8]) -> Result<(), Error>;
+    fn load(
+        instruction: I,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        op: fn(&mut Bus, usize) -> Result<Self, Exception>,
+    ) -> Result<(), Exception>;
 }
 
+/// Stores break a reservation on the bytes they write.
 pub trait Store: Sized {
-    fn store(instruction: S, regs: &mut Registers<Self>, memory: &mut [u8]) -> Result<(), Error>;
+    fn store(
+        instruction: S,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(Self, &mut Bus, usize) -> Result<(), Exception>,
+    ) -> Result<(), Exception>;
+}
+
+/// Atomic memory operations address memory through `rs1` alone, which must be
+/// aligned to the access width.
+pub trait Atomic: Sized {
+    fn lr(
+        instruction: Amo,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(&mut Bus, usize) -> Result<Self, Exception>,
+    ) -> Result<(), Exception>;
+
+    fn sc(
+        instruction: Amo,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(Self, &mut Bus, usize) -> Result<(), Exception>,
+    ) -> Result<(), Exception>;
+
+    #[allow(clippy::too_many_arguments)]
+    fn amo(
+        instruction: Amo,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        load: fn(&mut Bus, usize) -> Result<Self, Exception>,
+        store: fn(Self, &mut Bus, usize) -> Result<(), Exception>,
+        op: fn(Self, Self) -> Self,
+    ) -> Result<(), Exception>;
 }
 
+/// Control transfers take the length `len` of the executing instruction, to
+/// find the fall-through address, and the `ialign` targets must respect.
 pub trait Branch: Sized {
-    fn branch(instruction: B, regs: &mut Registers<Self>, pc: &mut Self) -> Result<(), Error>;
+    /// Returns whether the condition held and the branch was taken.
+    fn branch(
+        instruction: B,
+        regs: &mut Registers<Self>,
+        pc: &mut Self,
+        len: u8,
+        ialign: u8,
+        op: fn(Self, Self) -> bool,
+    ) -> Result<bool, Exception>;
 }
 
 pub trait Jal: Sized {
-    fn jal(instruction: J, regs: &mut Registers<Self>, pc: &mut Self) -> Result<(), Error>;
+    fn jal(
+        instruction: J,
+        regs: &mut Registers<Self>,
+        pc: &mut Self,
+        len: u8,
+        ialign: u8,
+    ) -> Result<(), Exception>;
 }
 
 pub trait Jalr: Sized {
-    fn jalr(instruction: I, regs: &mut Registers<Self>, pc: &mut Self) -> Result<(), Error>;
+    fn jalr(
+        instruction: I,
+        regs: &mut Registers<Self>,
+        pc: &mut Self,
+        len: u8,
+        ialign: u8,
+    ) -> Result<(), Exception>;
+}
+
+/// Zcmp stack frames. Each register of the list goes through its own `op`
+/// access of the stack, so a fault reports the failing address and leaves
+/// `sp` as it was for the instruction to be restarted.
+pub trait PushPop: Sized {
+    /// Stores the list below `sp`, then moves `sp` down past the frame.
+    fn push(
+        instruction: Cmpp,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(Self, &mut Bus, usize) -> Result<(), Exception>,
+    ) -> Result<(), Exception>;
+
+    /// Loads the list from the top of the frame, then moves `sp` past it.
+    fn pop(
+        instruction: Cmpp,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        op: fn(&mut Bus, usize) -> Result<Self, Exception>,
+    ) -> Result<(), Exception>;
+
+    /// `pop` followed by `ret`, clearing `a0` in between when `zero` is set.
+    fn popret(
+        instruction: Cmpp,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        pc: &mut Self,
+        ialign: u8,
+        zero: bool,
+        op: fn(&mut Bus, usize) -> Result<Self, Exception>,
+    ) -> Result<(), Exception>;
+
+    /// `cm.mvsa01`, moves `a0` and `a1` to the saved registers.
+    fn mvsa01(instruction: Cmmv, regs: &mut Registers<Self>) -> Result<(), Exception>;
+
+    /// `cm.mva01s`, moves the saved registers to `a0` and `a1`.
+    fn mva01s(instruction: Cmmv, regs: &mut Registers<Self>) -> Result<(), Exception>;
+}
+
+/// Zcmt table jumps to the entry `index` of the table at `jvt`, which is read
+/// as an instruction fetch. Indices from 32 link through `ra`.
+pub trait TableJump: Sized {
+    fn table_jump(
+        instruction: Cmjt,
+        regs: &mut Registers<Self>,
+        csrs: &CsrRegisters<Self>,
+        memory: &mut Bus,
+        pc: &mut Self,
+        ialign: u8,
+    ) -> Result<(), Exception>;
 }
 
 pub trait Lui: Sized {
-    fn lui(instruction: U, regs: &mut Registers<Self>) -> Result<(), Error>;
+    fn lui(instruction: U, regs: &mut Registers<Self>) -> Result<(), Exception>;
 }
 
 pub trait Auipc: Sized {
-    fn auipc(instruction: U, regs: &mut Registers<Self>, pc: Self) -> Result<(), Error>;
+    fn auipc(instruction: U, regs: &mut Registers<Self>, pc: Self) -> Result<(), Exception>;
+}
+
+pub trait Privileged: Sized {
+    /// Returns from an M-mode trap handler to `mepc`, restoring the privilege
+    /// mode and interrupt enable that `mstatus` stacked.
+    fn mret(
+        csrs: &mut CsrRegisters<Self>,
+        extensions: &Extensions,
+        pc: &mut Self,
+        privilege: &mut Privilege,
+        ialign: u8,
+    ) -> Result<(), Exception>;
+
+    /// Returns from an S-mode trap handler to `sepc`, restoring the privilege
+    /// mode and interrupt enable that `sstatus` stacked.
+    fn sret(
+        csrs: &mut CsrRegisters<Self>,
+        pc: &mut Self,
+        privilege: &mut Privilege,
+        ialign: u8,
+    ) -> Result<(), Exception>;
+
+    /// Waits for an interrupt. `Hart::step` takes pending interrupts before
+    /// every instruction, so that it only checks that `privilege` may wait.
+    fn wfi(
+        csrs: &CsrRegisters<Self>,
+        extensions: &Extensions,
+        privilege: Privilege,
+    ) -> Result<(), Exception>;
+
+    /// Orders earlier page table updates before later translations, by
+    /// dropping the TLB entries of the page `rs1` holds and the address space
+    /// `rs2` holds, all of them for `x0`.
+    fn sfence_vma(
+        instruction: R,
+        regs: &Registers<Self>,
+        csrs: &CsrRegisters<Self>,
+        tlb: &mut Tlb,
+        privilege: Privilege,
+    ) -> Result<(), Exception>;
 }
 
+/// The Zicsr instructions. `read` and `write` say whether the instruction
+/// reads and writes its CSR, as `Instruction::csr_read` and
+/// `Instruction::csr_write` tell.
 pub trait Csr: Sized {
     fn csr(
         instruction: I,
         regs: &mut Registers<Self>,
         csrs: &mut CsrRegisters<Self>,
-    ) -> Result<(), Error>;
+        extensions: &Extensions,
+        read: bool,
+        write: bool,
+        op: fn(Self, &mut Self, &mut Self) -> Self,
+    ) -> Result<(), Exception>;
+
+    fn csri(
+        instruction: I,
+        regs: &mut Registers<Self>,
+        csrs: &mut CsrRegisters<Self>,
+        extensions: &Extensions,
+        read: bool,
+        write: bool,
+        op: fn(U5, &mut Self, &mut Self) -> Self,
+    ) -> Result<(), Exception>;
+
+    /// Accesses the `seed` CSR of Zkr, which must be written: every read
+    /// polls a fresh sample and the written value is ignored.
+    fn seed(
+        instruction: I,
+        regs: &mut Registers<Self>,
+        entropy: &mut Entropy,
+        read: bool,
+        write: bool,
+    ) -> Result<(), Exception>;
 }
 
+// The float registers are FLEN bits wide independently of XLEN, single
+// precision sources must be NaN-boxed or they read as the canonical NaN.
+// The ops round as `funct3` says and their flags accrue in `fflags`. With
+// Zfinx and Zdinx the F and D operands live in the integer registers instead.
+
 pub trait FloatS: Sized {
+    /// `fd = op(fs1, fs2)`
     fn floats(
         instruction: R,
-        fregs: &mut Registers<Self>,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, u32, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception>;
+
+    /// `xd = op(fs1, fs2)`
+    fn floats_to_x(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, u32, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception>;
+
+    /// `xd = op(fs1, 0)` on the low 32 bits of `fs1`, without checking the
+    /// NaN-boxing
+    fn floats_raw_to_x(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, u32, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(xs1, 0)`
+    fn floats_from_x(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, u32, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception>;
+
+    /// `xd = op(fs1)`, with a 64-bit result
+    fn floats_to_x64(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(xs1)`, with a 64-bit source
+    fn floats_from_x64(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(rs1)`, the `rs1` field being an immediate
+    fn floats_imm(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u8) -> u32,
+    ) -> Result<(), Exception>;
+}
+
+pub trait FloatD: Sized {
+    /// `fd = op(fs1, fs2)`
+    fn floatd(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `xd = op(fs1, fs2)`, truncated to XLEN bits
+    fn floatd_to_x(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(xs1, 0)`, with `xs1` zero extended to 64 bits
+    fn floatd_from_x(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(fs1)`, narrowing a double to a NaN-boxed single
+    fn floatd_to_s(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(fs1)`, widening a single to a double
+    fn floats_to_d(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(xs1, xs2)`, with both zero extended to 64 bits
+    fn floatd_from_x2(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(rs1)`, the `rs1` field being an immediate
+    fn floatd_imm(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u8) -> u64,
+    ) -> Result<(), Exception>;
+}
+
+pub trait FloatH: Sized {
+    /// `fd = op(fs1, fs2)`
+    fn floath(
+        instruction: R,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, u16, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception>;
+
+    /// `xd = op(fs1, fs2)`, zero extended
+    fn floath_to_x(
+        instruction: R,
+        fregs: &FRegisters,
+        xregs: &mut Registers<Self>,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, u16, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception>;
+
+    /// `xd = op(fs1)`, with a 32-bit result sign extended to XLEN bits
+    fn floath_to_x32(
+        instruction: R,
+        fregs: &FRegisters,
+        xregs: &mut Registers<Self>,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception>;
+
+    /// `xd = op(fs1)`, truncated to XLEN bits
+    fn floath_to_x64(
+        instruction: R,
+        fregs: &FRegisters,
+        xregs: &mut Registers<Self>,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `xd = op(fs1)` on the low 16 bits of `fs1`, without checking the
+    /// NaN-boxing
+    fn floath_raw_to_x(
+        instruction: R,
+        fregs: &FRegisters,
         xregs: &mut Registers<Self>,
-    ) -> Result<(), Error>;
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(xs1)`, with `xs1` zero extended to 64 bits
+    fn floath_from_x(
+        instruction: R,
+        xregs: &Registers<Self>,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u64, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(fs1)`, widening a half to a single
+    fn floath_to_s(
+        instruction: R,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(fs1)`, narrowing a single to a half
+    fn floats_to_h(
+        instruction: R,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u32, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(fs1)`, widening a half to a double
+    fn floath_to_d(
+        instruction: R,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(fs1)`, narrowing a double to a half
+    fn floatd_to_h(
+        instruction: R,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u64, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception>;
+
+    /// `fd = op(rs1)`, the `rs1` field being an immediate
+    fn floath_imm(
+        instruction: R,
+        fregs: &mut FRegisters,
+        op: fn(u8) -> u16,
+    ) -> Result<(), Exception>;
 }
 
 pub trait Fload: Sized {
     fn fload(
         instruction: I,
-        xregs: &mut Registers<Self>,
-        fregs: &mut Registers<Self>,
-        memory: &mut [u8],
-    ) -> Result<(), Error>;
+        xregs: &Registers<Self>,
+        fregs: &mut FRegisters,
+        memory: &mut Bus,
+        op: fn(&mut Bus, usize) -> Result<u32, Exception>,
+    ) -> Result<(), Exception>;
+
+    fn fload_d(
+        instruction: I,
+        xregs: &Registers<Self>,
+        fregs: &mut FRegisters,
+        memory: &mut Bus,
+        op: fn(&mut Bus, usize) -> Result<u64, Exception>,
+    ) -> Result<(), Exception>;
+
+    fn fload_h(
+        instruction: I,
+        xregs: &Registers<Self>,
+        fregs: &mut FRegisters,
+        memory: &mut Bus,
+        op: fn(&mut Bus, usize) -> Result<u16, Exception>,
+    ) -> Result<(), Exception>;
 }
 
+/// Single and half precision stores write the low bits of `fs2` unchecked.
 pub trait Fstore: Sized {
     fn fstore(
         instruction: S,
-        xregs: &mut Registers<Self>,
-        fregs: &mut Registers<Self>,
-        memory: &mut [u8],
-    ) -> Result<(), Error>;
-}
+        xregs: &Registers<Self>,
+        fregs: &FRegisters,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(u32, &mut Bus, usize) -> Result<(), Exception>,
+    ) -> Result<(), Exception>;
+
+    fn fstore_d(
+        instruction: S,
+        xregs: &Registers<Self>,
+        fregs: &FRegisters,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(u64, &mut Bus, usize) -> Result<(), Exception>,
+    ) -> Result<(), Exception>;
 
-pub trait FmaddS: Sized {
-    fn fmadd(instruction: R4, regs: &mut Registers<Self>) -> Result<(), Error>;
+    fn fstore_h(
+        instruction: S,
+        xregs: &Registers<Self>,
+        fregs: &FRegisters,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(u16, &mut Bus, usize) -> Result<(), Exception>,
+    ) -> Result<(), Exception>;
 }
 
-pub trait FmsubS: Sized {
-    fn fmsub(instruction: R4, regs: &mut Registers<Self>) -> Result<(), Error>;
+pub trait FmaS: Sized {
+    fn fmas(
+        instruction: R4,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, u32, u32, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception>;
 }
 
-pub trait FnmsubS: Sized {
-    fn fnmsub(instruction: R4, regs: &mut Registers<Self>) -> Result<(), Error>;
+pub trait FmaH: Sized {
+    fn fmah(
+        instruction: R4,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, u16, u16, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception>;
 }
 
-pub trait FnmaddS: Sized {
-    fn fnmadd(instruction: R4, regs: &mut Registers<Self>) -> Result<(), Error>;
+pub trait FmaD: Sized {
+    fn fmad(
+        instruction: R4,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, u64, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception>;
 }
 
+/// Everything an XLEN type must provide for `Hart` to execute any decoded
+/// instruction on it.
 pub trait BaseInstruction:
-    Math + MathI + ShiftI + Load + Store + Branch + Jal + Jalr + Lui + Auipc
+    Copy
+    + Zero
+    + ProgramCounter
+    + As<u64>
+    + As<usize>
+    + Math
+    + MathI
+    + ShiftI
+    + Load
+    + Store
+    + Atomic
+    + Branch
+    + Jal
+    + Jalr
+    + PushPop
+    + TableJump
+    + Lui
+    + Auipc
+    + Csr
+    + Privileged
+    + FloatS
+    + FloatD
+    + FloatH
+    + Fload
+    + Fstore
+    + FmaS
+    + FmaD
+    + FmaH
+    + BaseMathW
+    + BaseCrypto
+    + BaseLoad
+    + BaseStore
+    + BaseBranch
+    + BaseCsr
+    + Flw
+    + Fsw
+    + Flh
+    + Fsh
+    + Vector
 {
+    const XLEN: Xlen;
 }
 
-impl BaseInstruction for u32 {}
-impl BaseInstruction for u64 {}
-
-impl<T: Copy + BaseMath + Zero> Math for T {
-    #[inline(always)]
-    fn math(instruction: R, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let f: fn(Self, Self) -> Self = match instruction.id() {
-            ADD => Add::add,
-            SUB => Sub::sub,
-            SLL => Sll::sll,
-            SLT => Slt::slt,
-            SLTU => Sltu::sltu,
-            XOR => Xor::xor,
-            SRL => Srl::srl,
-            SRA => Sra::sra,
-            OR => Or::or,
-            AND => And::and,
-            // M extension
-            MUL => Mul::mul,
-            MULH => Mulh::mulh,
-            MULHSU => Mulhsu::mulhsu,
-            MULHU => Mulhu::mulhu,
-            DIV => Div::div,
-            DIVU => Divu::divu,
-            REM => Rem::rem,
-            REMU => Remu::remu,
-            // Byte Math extension
-            BADD => Badd::badd,
-            BSUB => Bsub::bsub,
-            BSLL => Bsll::bsll,
-            BSLT => Bslt::bslt,
-            BSLTU => Bsltu::bsltu,
-            BXOR => Bxor::bxor,
-            BSRL => Bsrl::bsrl,
-            BSRA => Bsra::bsra,
-            BOR => Bor::bor,
-            BAND => Band::band,
-            _ => return Err(Error::InvalidOpCode),
-        };
+impl BaseInstruction for u32 {
+    const XLEN: Xlen = Xlen::Rv32;
+}
 
-        match instruction.rd.into() {
-            ZeroOrRegister::Zero => Err(Error::InvalidOpCode),
-            ZeroOrRegister::Register(reg) => {
-                let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-                let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
-                *regs.get_mut(reg) = f(src1, src2);
-                Ok(())
-            }
-        }
+impl BaseInstruction for u64 {
+    const XLEN: Xlen = Xlen::Rv64;
+}
+
+#[inline(always)]
+pub(crate) fn break_reservation(reservation: &mut Option<Reservation>, addr: usize, size: usize) {
+    if reservation.is_some_and(|r| r.overlaps(addr, size)) {
+        *reservation = None;
+    }
+}
+
+/// Resolves the rounding mode of a float instruction, `DYN` takes `frm`. The
+/// instructions without a rounding mode never encode `DYN` in `funct3`.
+#[inline(always)]
+fn float_env<T>(rm: U3, csrs: &CsrRegisters<T>) -> Result<FloatEnv, Exception>
+where
+    T: Copy + As<u8>,
+{
+    let rm = match rm.as_u8() {
+        0b111 => csrs.get(FRM).r#as(),
+        rm => rm,
+    };
+    RoundingMode::from_u8(rm)
+        .map(FloatEnv::new)
+        .ok_or(ILLEGAL_INSTRUCTION)
+}
+
+// With Zfinx and Zdinx singles read the low 32 bits of `x[reg]` and are
+// written sign extended, doubles on RV32 take the even/odd pair starting at
+// `x[reg]`, where the x0 pair reads as zero and ignores writes.
+
+#[inline(always)]
+fn get_s<T>(regfile: &RegFile<T>, reg: U5, inx: bool) -> u32
+where
+    T: Copy + Zero + As<u32>,
+{
+    if inx {
+        ZeroOrRegister::from_u5(reg).fetch(&regfile.xregs).r#as()
+    } else {
+        regfile.fregs.get_s(reg.into())
+    }
+}
+
+#[inline(always)]
+fn set_s<T>(regfile: &mut RegFile<T>, reg: U5, value: u32, inx: bool)
+where
+    i32: As<T>,
+{
+    if inx {
+        ZeroOrRegister::from_u5(reg).store(&mut regfile.xregs, (value as i32).r#as());
+    } else {
+        regfile.fregs.set_s(reg.into(), value);
+    }
+}
+
+#[inline(always)]
+fn get_d<T>(regfile: &RegFile<T>, reg: U5, inx: bool) -> u64
+where
+    T: Copy + Zero + As<u64>,
+{
+    if !inx {
+        return regfile.fregs.get(reg.into());
+    }
+    let low: u64 = ZeroOrRegister::from_u5(reg).fetch(&regfile.xregs).r#as();
+    if core::mem::size_of::<T>() == 8 || reg.as_u8() == 0 {
+        return low;
+    }
+    let high: u64 = ZeroOrRegister::from_u5(U5::new_truncate(reg.as_u8() + 1))
+        .fetch(&regfile.xregs)
+        .r#as();
+    high << 32 | low
+}
+
+#[inline(always)]
+fn set_d<T>(regfile: &mut RegFile<T>, reg: U5, value: u64, inx: bool)
+where
+    u64: As<T>,
+{
+    if !inx {
+        regfile.fregs.set(reg.into(), value);
+    } else if core::mem::size_of::<T>() == 8 {
+        ZeroOrRegister::from_u5(reg).store(&mut regfile.xregs, value.r#as());
+    } else if reg.as_u8() != 0 {
+        let high = U5::new_truncate(reg.as_u8() + 1);
+        ZeroOrRegister::from_u5(reg).store(&mut regfile.xregs, value.r#as());
+        ZeroOrRegister::from_u5(high).store(&mut regfile.xregs, (value >> 32).r#as());
     }
 }
 
-impl<T: Copy + BaseMath + Zero> MathI for T {
+#[inline(always)]
+fn effective_address<T>(base: T, offset: U12) -> usize
+where
+    T: UnsignedWrapping + As<usize>,
+    i16: As<<T as Unsigned>::Signed>,
+{
+    <T as As<usize>>::r#as(base.wrapping_add_signed(offset.sign_extend().r#as()))
+}
+
+impl<T: Copy + Zero> Math for T {
     #[inline(always)]
-    fn mathi(instruction: I, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let f: fn(Self, U12) -> Self = match instruction.id() {
-            ADDI => Addi::addi,
-            SLTI => Slti::slti,
-            SLTIU => Sltiu::sltiu,
-            XORI => Xori::xori,
-            ORI => Ori::ori,
-            ANDI => Andi::andi,
-            _ => return Err(Error::InvalidOpCode),
-        };
+    fn math(
+        instruction: R,
+        regs: &mut Registers<Self>,
+        op: fn(Self, Self) -> Self,
+    ) -> Result<(), Exception> {
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
+        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, op(src1, src2));
+        Ok(())
+    }
 
-        match instruction.rd.into() {
-            ZeroOrRegister::Zero => Err(Error::InvalidOpCode),
-            ZeroOrRegister::Register(reg) => {
-                let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-                *regs.get_mut(reg) = f(src1, instruction.imm);
-                Ok(())
-            }
-        }
+    #[inline(always)]
+    fn math_bs(
+        instruction: R,
+        regs: &mut Registers<Self>,
+        op: fn(Self, Self, U2) -> Self,
+    ) -> Result<(), Exception> {
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
+        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
+        let bs = U2::new_truncate(instruction.funct7.as_u8() >> 5);
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, op(src1, src2, bs));
+        Ok(())
     }
 }
 
-impl<T: Copy + BaseMath + Zero> ShiftI for T {
+impl<T: Copy + Zero> MathI for T {
     #[inline(always)]
-    fn shifti(instruction: Shift, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let f: fn(Self, U5) -> Self = match instruction.id() {
-            SLLI => Slli::slli,
-            SRLI => Srli::srli,
-            SRAI => Srai::srai,
-            _ => return Err(Error::InvalidOpCode),
-        };
+    fn mathi(
+        instruction: I,
+        regs: &mut Registers<Self>,
+        op: fn(Self, U12) -> Self,
+    ) -> Result<(), Exception> {
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, op(src1, instruction.imm));
+        Ok(())
+    }
 
-        let dest_reg = if let ZeroOrRegister::Register(reg) = instruction.rd.into() {
-            reg
-        } else {
-            return Err(Error::InvalidOpCode);
-        };
+    #[inline(always)]
+    fn unary(
+        instruction: I,
+        regs: &mut Registers<Self>,
+        op: fn(Self) -> Self,
+    ) -> Result<(), Exception> {
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, op(src1));
+        Ok(())
+    }
+}
+
+impl<T: Copy + Zero> ShiftI for T {
+    #[inline(always)]
+    fn shifti(
+        instruction: Shift,
+        regs: &mut Registers<Self>,
+        op: fn(Self, U6) -> Self,
+    ) -> Result<(), Exception> {
         let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-        *regs.get_mut(dest_reg) = f(src1, instruction.shamt);
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, op(src1, instruction.shamt));
+        Ok(())
+    }
 
+    #[inline(always)]
+    fn shiftiw(
+        instruction: Shift,
+        regs: &mut Registers<Self>,
+        op: fn(Self, U5) -> Self,
+    ) -> Result<(), Exception> {
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
+        let shamt = U5::new_truncate(instruction.shamt.as_u8());
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, op(src1, shamt));
         Ok(())
     }
 }
@@ -220,26 +820,25 @@ macro_rules! impl_branch {
                 instruction: B,
                 regs: &mut Registers<Self>,
                 pc: &mut Self,
-            ) -> Result<(), Error> {
-                let f: fn($t, $t) -> bool = match instruction.id() {
-                    BEQ => Beq::beq,
-                    BNE => Bne::bne,
-                    BLT => Blt::blt,
-                    BGE => Bge::bge,
-                    BLTU => Bltu::bltu,
-                    BGEU => Bgeu::bgeu,
-                    _ => return Err(Error::InvalidOpCode),
-                };
+                len: u8,
+                ialign: u8,
+                op: fn(Self, Self) -> bool,
+            ) -> Result<bool, Exception> {
                 let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
                 let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
-                if f(src1, src2) {
-                    *pc = pc.wrapping_add_signed(
+                let taken = op(src1, src2);
+                if taken {
+                    let next = pc.wrapping_add_signed(
                         instruction.imm.sign_extend() as <$t as Unsigned>::Signed
                     );
+                    if next % ialign as $t != 0 {
+                        return Err(Exception::InstructionAddressMisaligned(next as u64));
+                    }
+                    *pc = next;
                 } else {
-                    *pc = pc.wrapping_add(OPCODE_SIZE as $t);
+                    *pc = pc.wrapping_add(len as $t);
                 }
-                Ok(())
+                Ok(taken)
             }
         }
     };
@@ -249,14 +848,22 @@ macro_rules! impl_jal {
     ($t:ty) => {
         impl Jal for $t {
             #[inline(always)]
-            fn jal(instruction: J, regs: &mut Registers<Self>, pc: &mut Self) -> Result<(), Error> {
-                // TODO: The JAL and JALR instructions will generate an instruction-address-misaligned exception if the target
-                //       address is not aligned to a four-byte boundary. (???)
+            fn jal(
+                instruction: J,
+                regs: &mut Registers<Self>,
+                pc: &mut Self,
+                len: u8,
+                ialign: u8,
+            ) -> Result<(), Exception> {
+                let next = (*pc)
+                    .wrapping_add_signed(instruction.imm.sign_extend() as <$t as Unsigned>::Signed);
+                if next % ialign as $t != 0 {
+                    return Err(Exception::InstructionAddressMisaligned(next as u64));
+                }
                 if let ZeroOrRegister::Register(reg) = instruction.rd.into() {
-                    *regs.get_mut(reg) = pc.wrapping_add(OPCODE_SIZE as $t);
+                    *regs.get_mut(reg) = pc.wrapping_add(len as $t);
                 }
-                *pc = (*pc)
-                    .wrapping_add_signed(instruction.imm.sign_extend() as <$t as Unsigned>::Signed);
+                *pc = next;
 
                 Ok(())
             }
@@ -272,15 +879,18 @@ macro_rules! impl_jalr {
                 instruction: I,
                 regs: &mut Registers<Self>,
                 pc: &mut Self,
-            ) -> Result<(), Error> {
-                // TODO: The JAL and JALR instructions will generate an instruction-address-misaligned exception if the target
-                //       address is not aligned to a four-byte boundary. (???)
+                len: u8,
+                ialign: u8,
+            ) -> Result<(), Exception> {
                 let next = ZeroOrRegister::from_u5(instruction.rs1)
                     .fetch(regs)
                     .wrapping_add_signed(instruction.imm.sign_extend() as <$t as Unsigned>::Signed)
                     & !1;
+                if next % ialign as $t != 0 {
+                    return Err(Exception::InstructionAddressMisaligned(next as u64));
+                }
                 if let ZeroOrRegister::Register(reg) = ZeroOrRegister::from_u5(instruction.rd) {
-                    *regs.get_mut(reg) = pc.wrapping_add(OPCODE_SIZE as $t);
+                    *regs.get_mut(reg) = pc.wrapping_add(len as $t);
                 }
                 *pc = next;
                 Ok(())
@@ -296,469 +906,1102 @@ impl_jal!(u64);
 impl_jalr!(u32);
 impl_jalr!(u64);
 
-impl MathW for u64 {
-    #[inline(always)]
-    fn mathw(instruction: R, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let f: fn(Self, Self) -> Self = match instruction.id() {
-            ADDW => Addw::addw,
-            SUBW => Subw::subw,
-            SLLW => Sllw::sllw,
-            SRLW => Srlw::srlw,
-            SRAW => Sraw::sraw,
-            _ => return Err(Error::InvalidOpCode),
-        };
-        match instruction.rd.into() {
-            ZeroOrRegister::Zero => Err(Error::InvalidOpCode),
-            ZeroOrRegister::Register(reg) => {
-                let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-                let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
-                *regs.get_mut(reg) = f(src1, src2);
+/// `ra` then `s0`-`s11`, in the order of the Zcmp register lists.
+const CM_REGS: [u8; 13] = [1, 8, 9, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27];
+const CM_RA: U5 = U5::new_truncate(1);
+const CM_SP: U5 = U5::new_truncate(2);
+
+macro_rules! impl_push_pop {
+    ($t:ty, $xlen:expr) => {
+        impl PushPop for $t {
+            #[inline(always)]
+            fn push(
+                instruction: Cmpp,
+                regs: &mut Registers<Self>,
+                memory: &mut Bus,
+                reservation: &mut Option<Reservation>,
+                op: fn(Self, &mut Bus, usize) -> Result<(), Exception>,
+            ) -> Result<(), Exception> {
+                const BYTES: usize = core::mem::size_of::<$t>();
+                let count = instruction.count();
+                // from the last register of the list right below sp down to ra
+                for (i, &reg) in CM_REGS[..count].iter().enumerate().rev() {
+                    let offset = -(((count - i) * BYTES) as i16);
+                    let store = S {
+                        imm: U12::new_truncate(offset as u16),
+                        rs2: U5::new_truncate(reg),
+                        rs1: CM_SP,
+                        funct3: U3::new_truncate(BYTES.trailing_zeros() as u8),
+                    };
+                    Self::store(store, regs, memory, reservation, op)?;
+                }
+                let sp = regs.get_mut(Register::X2);
+                *sp = sp.wrapping_sub(instruction.stack_adj($xlen) as $t);
                 Ok(())
             }
-        }
-    }
-}
 
-impl MathIW for u64 {
-    #[inline(always)]
-    fn mathiw(instruction: I, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let f: fn(Self, U12) -> Self = match instruction.id() {
-            ADDIW => Addiw::addiw,
-            _ => return Err(Error::InvalidOpCode),
-        };
-        match instruction.rd.into() {
-            ZeroOrRegister::Zero => Err(Error::InvalidOpCode),
-            ZeroOrRegister::Register(reg) => {
-                let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-                *regs.get_mut(reg) = f(src1, instruction.imm);
+            #[inline(always)]
+            fn pop(
+                instruction: Cmpp,
+                regs: &mut Registers<Self>,
+                memory: &mut Bus,
+                op: fn(&mut Bus, usize) -> Result<Self, Exception>,
+            ) -> Result<(), Exception> {
+                const BYTES: usize = core::mem::size_of::<$t>();
+                let count = instruction.count();
+                let adj = instruction.stack_adj($xlen);
+                for (i, &reg) in CM_REGS[..count].iter().enumerate().rev() {
+                    let load = I {
+                        imm: U12::new_truncate((adj - (count - i) * BYTES) as u16),
+                        rs1: CM_SP,
+                        funct3: U3::new_truncate(BYTES.trailing_zeros() as u8),
+                        rd: U5::new_truncate(reg),
+                    };
+                    Self::load(load, regs, memory, op)?;
+                }
+                let sp = regs.get_mut(Register::X2);
+                *sp = sp.wrapping_add(adj as $t);
                 Ok(())
             }
-        }
-    }
-}
-
-impl ShiftIW for u64 {
-    #[inline(always)]
-    fn shiftiw(instruction: Shift, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let f: fn(Self, Self) -> Self = match instruction.id() {
-            SLLIW => Slliw::slliw,
-            SRLIW => Srliw::srliw,
-            SRAIW => Sraiw::sraiw,
-            _ => return Err(Error::InvalidOpCode),
-        };
-        let dest_reg = if let ZeroOrRegister::Register(reg) = instruction.rd.into() {
-            reg
-        } else {
-            return Err(Error::InvalidOpCode);
-        };
-        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-        *regs.get_mut(dest_reg) = f(src1, instruction.shamt.as_u64());
 
-        Ok(())
-    }
-}
+            #[inline(always)]
+            fn popret(
+                instruction: Cmpp,
+                regs: &mut Registers<Self>,
+                memory: &mut Bus,
+                pc: &mut Self,
+                ialign: u8,
+                zero: bool,
+                op: fn(&mut Bus, usize) -> Result<Self, Exception>,
+            ) -> Result<(), Exception> {
+                Self::pop(instruction, regs, memory, op)?;
+                if zero {
+                    *regs.get_mut(Register::X10) = 0;
+                }
+                let ret = I {
+                    imm: U12::new_truncate(0),
+                    rs1: CM_RA,
+                    funct3: U3::new_truncate(0),
+                    rd: U5::new_truncate(0),
+                };
+                Self::jalr(ret, regs, pc, 2, ialign)
+            }
 
-impl Load for u32 {
+            #[inline(always)]
+            fn mvsa01(instruction: Cmmv, regs: &mut Registers<Self>) -> Result<(), Exception> {
+                let a0 = regs.get(Register::X10);
+                let a1 = regs.get(Register::X11);
+                ZeroOrRegister::from_u5(Cmmv::sreg(instruction.r1s)).store(regs, a0);
+                ZeroOrRegister::from_u5(Cmmv::sreg(instruction.r2s)).store(regs, a1);
+                Ok(())
+            }
+
+            #[inline(always)]
+            fn mva01s(instruction: Cmmv, regs: &mut Registers<Self>) -> Result<(), Exception> {
+                let r1 = ZeroOrRegister::from_u5(Cmmv::sreg(instruction.r1s)).fetch(regs);
+                let r2 = ZeroOrRegister::from_u5(Cmmv::sreg(instruction.r2s)).fetch(regs);
+                *regs.get_mut(Register::X10) = r1;
+                *regs.get_mut(Register::X11) = r2;
+                Ok(())
+            }
+        }
+
+        impl TableJump for $t {
+            #[inline(always)]
+            fn table_jump(
+                instruction: Cmjt,
+                regs: &mut Registers<Self>,
+                csrs: &CsrRegisters<Self>,
+                memory: &mut Bus,
+                pc: &mut Self,
+                ialign: u8,
+            ) -> Result<(), Exception> {
+                let jvt = csrs.get(JVT);
+                // only the jump table mode 0 is defined
+                if jvt & 0x3f != 0 {
+                    return Err(ILLEGAL_INSTRUCTION);
+                }
+                let addr = (jvt as usize)
+                    .wrapping_add(instruction.index as usize * core::mem::size_of::<$t>());
+                let entry = memory.fetch_word::<[u8; core::mem::size_of::<$t>()]>(addr)?;
+                let next = <$t>::from_le_bytes(entry) & !1;
+                if next % ialign as $t != 0 {
+                    return Err(Exception::InstructionAddressMisaligned(next as u64));
+                }
+                if instruction.index >= 32 {
+                    *regs.get_mut(Register::X1) = pc.wrapping_add(2);
+                }
+                *pc = next;
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_push_pop!(u32, Xlen::Rv32);
+impl_push_pop!(u64, Xlen::Rv64);
+
+impl<T> Load for T
+where
+    T: Copy + Zero + UnsignedWrapping + As<usize>,
+    i16: As<<T as Unsigned>::Signed>,
+{
     #[inline(always)]
-    fn load(instruction: I, regs: &mut Registers<Self>, memory: &[u8]) -> Result<(), Error> {
-        let dest_reg =
-            if let ZeroOrRegister::Register(reg) = ZeroOrRegister::from_u5(instruction.rd) {
-                reg
-            } else {
-                return Err(Error::InvalidOpCode);
-            };
-        let offset = ZeroOrRegister::from_u5(instruction.rs1)
-            .fetch(regs)
-            .wrapping_add_signed(instruction.imm.sign_extend() as i32)
-            as usize;
-        *regs.get_mut(dest_reg) = match instruction.id() {
-            LB => Lb::lb(memory, offset)?,
-            LBU => Lbu::lbu(memory, offset)?,
-            LH => Lh::lh(memory, offset)?,
-            LHU => Lhu::lhu(memory, offset)?,
-            LW => Lw::lw(memory, offset)?,
-            _ => return Err(Error::InvalidOpCode),
-        };
+    fn load(
+        instruction: I,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        op: fn(&mut Bus, usize) -> Result<Self, Exception>,
+    ) -> Result<(), Exception> {
+        let base = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
+        // loads to x0 still perform the access, and may fault
+        let value = op(memory, effective_address(base, instruction.imm))?;
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, value);
         Ok(())
     }
 }
 
-impl Load for u64 {
-    #[inline(always)]
-    fn load(instruction: I, regs: &mut Registers<Self>, memory: &[u8]) -> Result<(), Error> {
-        let dest_reg =
-            if let ZeroOrRegister::Register(reg) = ZeroOrRegister::from_u5(instruction.rd) {
-                reg
-            } else {
-                return Err(Error::InvalidOpCode);
-            };
-        let offset = ZeroOrRegister::from_u5(instruction.rs1)
-            .fetch(regs)
-            .wrapping_add_signed(instruction.imm.sign_extend() as i64)
-            as usize;
-        *regs.get_mut(dest_reg) = match instruction.id() {
-            LB => Lb::lb(memory, offset)?,
-            LBU => Lbu::lbu(memory, offset)?,
-            LH => Lh::lh(memory, offset)?,
-            LHU => Lhu::lhu(memory, offset)?,
-            LW => Lw::lw(memory, offset)?,
-            LWU => Lwu::lwu(memory, offset)?,
-            LD => Ld::ld(memory, offset)?,
-            _ => return Err(Error::InvalidOpCode),
-        };
+impl<T> Store for T
+where
+    T: Copy + Zero + UnsignedWrapping + As<usize>,
+    i16: As<<T as Unsigned>::Signed>,
+{
+    #[inline(always)]
+    fn store(
+        instruction: S,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(Self, &mut Bus, usize) -> Result<(), Exception>,
+    ) -> Result<(), Exception> {
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
+        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
+        let addr = effective_address(src1, instruction.imm);
+        op(src2, memory, addr)?;
+        break_reservation(reservation, addr, instruction.width());
         Ok(())
     }
 }
 
-impl Store for u32 {
+impl<T> Atomic for T
+where
+    T: Copy + Zero + As<usize> + As<u32>,
+    i32: As<T>,
+    bool: As<T>,
+{
     #[inline(always)]
-    fn store(instruction: S, regs: &mut Registers<Self>, memory: &mut [u8]) -> Result<(), Error> {
-        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
-        let offset = src1.wrapping_add_signed(instruction.imm.sign_extend() as i32) as usize;
-        match instruction.id() {
-            SB => Sb::sb(src2, memory, offset),
-            SH => Sh::sh(src2, memory, offset),
-            SW => Sw::sw(src2, memory, offset),
-            _ => Err(Error::InvalidOpCode),
+    fn lr(
+        instruction: Amo,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(&mut Bus, usize) -> Result<Self, Exception>,
+    ) -> Result<(), Exception> {
+        let addr: usize = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs).r#as();
+        let size = instruction.width();
+        if !addr.is_multiple_of(size) {
+            return Err(Exception::LoadAddressMisaligned(addr as u64));
         }
+        let value = op(memory, addr)?;
+        *reservation = Some(Reservation { addr, size });
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, value);
+        Ok(())
     }
-}
 
-impl Store for u64 {
     #[inline(always)]
-    fn store(instruction: S, regs: &mut Registers<Self>, memory: &mut [u8]) -> Result<(), Error> {
-        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
-        let offset = src1.wrapping_add_signed(instruction.imm.sign_extend() as i64) as usize;
-        match instruction.id() {
-            SB => Sb::sb(src2, memory, offset),
-            SH => Sh::sh(src2, memory, offset),
-            SW => Sw::sw(src2, memory, offset),
-            SD => Sd::sd(src2, memory, offset),
-            _ => Err(Error::InvalidOpCode),
+    fn sc(
+        instruction: Amo,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(Self, &mut Bus, usize) -> Result<(), Exception>,
+    ) -> Result<(), Exception> {
+        let addr: usize = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs).r#as();
+        let size = instruction.width();
+        if !addr.is_multiple_of(size) {
+            return Err(Exception::StoreAddressMisaligned(addr as u64));
         }
+        // Only a reservation of the same bytes lets the store happen
+        let success = *reservation == Some(Reservation { addr, size });
+        if success {
+            op(
+                ZeroOrRegister::from_u5(instruction.rs2).fetch(regs),
+                memory,
+                addr,
+            )?;
+        }
+        *reservation = None;
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, (!success).r#as());
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn amo(
+        instruction: Amo,
+        regs: &mut Registers<Self>,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        load: fn(&mut Bus, usize) -> Result<Self, Exception>,
+        store: fn(Self, &mut Bus, usize) -> Result<(), Exception>,
+        op: fn(Self, Self) -> Self,
+    ) -> Result<(), Exception> {
+        let addr: usize = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs).r#as();
+        let size = instruction.width();
+        if !addr.is_multiple_of(size) {
+            return Err(Exception::StoreAddressMisaligned(addr as u64));
+        }
+        // The loaded word is sign extended, so rs2 is compared sign extended too
+        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
+        let src2 = if size == 4 {
+            (<T as As<u32>>::r#as(src2) as i32).r#as()
+        } else {
+            src2
+        };
+        // AMOs report faults as stores, even on the read
+        memory.translate(addr, size, Access::Store)?;
+        let old = load(memory, addr).map_err(|_| Exception::StoreAccessFault(addr as u64))?;
+        store(op(old, src2), memory, addr)?;
+        break_reservation(reservation, addr, size);
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, old);
+        Ok(())
     }
 }
 
 impl Lui for u32 {
     #[inline(always)]
-    fn lui(instruction: U, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let dest = ZeroOrRegister::from_u5(instruction.rd)
-            .fetch_mut(regs)
-            .ok_or(Error::InvalidOpCode)?;
-        *dest = instruction.imm;
+    fn lui(instruction: U, regs: &mut Registers<Self>) -> Result<(), Exception> {
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, instruction.imm);
         Ok(())
     }
 }
 
 impl Lui for u64 {
     #[inline(always)]
-    fn lui(instruction: U, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let dest = ZeroOrRegister::from_u5(instruction.rd)
-            .fetch_mut(regs)
-            .ok_or(Error::InvalidOpCode)?;
-        *dest = unsafe {
-            core::mem::transmute::<i64, u64>(
-                core::mem::transmute::<u32, i32>(instruction.imm) as i64
-            )
-        };
+    fn lui(instruction: U, regs: &mut Registers<Self>) -> Result<(), Exception> {
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, instruction.imm as i32 as i64 as u64);
         Ok(())
     }
 }
 
 impl Auipc for u32 {
     #[inline(always)]
-    fn auipc(instruction: U, regs: &mut Registers<Self>, pc: Self) -> Result<(), Error> {
-        let dest = ZeroOrRegister::from_u5(instruction.rd)
-            .fetch_mut(regs)
-            .ok_or(Error::InvalidOpCode)?;
-        *dest = pc.wrapping_add(instruction.imm);
+    fn auipc(instruction: U, regs: &mut Registers<Self>, pc: Self) -> Result<(), Exception> {
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, pc.wrapping_add(instruction.imm));
         Ok(())
     }
 }
 
 impl Auipc for u64 {
     #[inline(always)]
-    fn auipc(instruction: U, regs: &mut Registers<Self>, pc: Self) -> Result<(), Error> {
-        let dest = ZeroOrRegister::from_u5(instruction.rd)
-            .fetch_mut(regs)
-            .ok_or(Error::InvalidOpCode)?;
-        *dest = pc.wrapping_add(unsafe {
-            core::mem::transmute::<i64, u64>(
-                core::mem::transmute::<u32, i32>(instruction.imm) as i64
-            )
-        });
+    fn auipc(instruction: U, regs: &mut Registers<Self>, pc: Self) -> Result<(), Exception> {
+        ZeroOrRegister::from_u5(instruction.rd)
+            .store(regs, pc.wrapping_add(instruction.imm as i32 as i64 as u64));
         Ok(())
     }
 }
 
-impl<T: Copy + Zero + BaseCsr> Csr for T {
+impl<T> Csr for T
+where
+    T: Copy + Zero + As<u8> + As<u64>,
+    u8: As<T>,
+    u32: As<T>,
+    u64: As<T>,
+{
     #[inline(always)]
     fn csr(
         instruction: I,
         regs: &mut Registers<Self>,
         csrs: &mut CsrRegisters<Self>,
-    ) -> Result<(), Error> {
-        if let ZeroOrRegister::Register(reg) = instruction.rs1.into() {
-            let csr = csrs
-                .get_mut(instruction.imm.as_u16() as usize)
-                .ok_or(Error::InvalidOpCode)?;
-            let src = reg.fetch(regs);
-            let dest = ZeroOrRegister::from_u5(instruction.rd)
-                .fetch_mut(regs)
-                .ok_or(Error::InvalidOpCode)?;
-            *csr = match instruction.id() {
-                CSRRW => Csrrw::csrrw(src, dest, csr),
-                CSRRS => Csrrs::csrrs(src, dest, csr),
-                CSRRC => Csrrc::csrrc(src, dest, csr),
-                CSRRWI => Csrrwi::csrrwi(instruction.rs1, dest, csr),
-                CSRRSI => Csrrsi::csrrsi(instruction.rs1, dest, csr),
-                CSRRCI => Csrrci::csrrci(instruction.rs1, dest, csr),
-                _ => return Err(Error::InvalidOpCode),
-            };
+        extensions: &Extensions,
+        read: bool,
+        write: bool,
+        op: fn(Self, &mut Self, &mut Self) -> Self,
+    ) -> Result<(), Exception> {
+        let src = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
+        access(
+            instruction,
+            regs,
+            csrs,
+            extensions,
+            read,
+            write,
+            |old, csr| op(src, old, csr),
+        )
+    }
+
+    #[inline(always)]
+    fn csri(
+        instruction: I,
+        regs: &mut Registers<Self>,
+        csrs: &mut CsrRegisters<Self>,
+        extensions: &Extensions,
+        read: bool,
+        write: bool,
+        op: fn(U5, &mut Self, &mut Self) -> Self,
+    ) -> Result<(), Exception> {
+        access(
+            instruction,
+            regs,
+            csrs,
+            extensions,
+            read,
+            write,
+            |old, csr| op(instruction.rs1, old, csr),
+        )
+    }
+
+    #[inline(always)]
+    fn seed(
+        instruction: I,
+        regs: &mut Registers<Self>,
+        entropy: &mut Entropy,
+        read: bool,
+        write: bool,
+    ) -> Result<(), Exception> {
+        if !write {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        if read {
+            // OPST is always ES16, the source never stalls or fails
+            let value = SEED_ES16 | entropy.next_u16() as u32;
+            ZeroOrRegister::from_u5(instruction.rd).store(regs, value.r#as());
+        }
+        Ok(())
+    }
+}
+
+/// Reads the CSR into `rd` if `read`, and writes back what `op` computes from
+/// its old value if `write`. Only `csrrw` and `csrrwi` skip the read, and
+/// they ignore the zero `op` sees instead.
+#[inline(always)]
+fn access<T>(
+    instruction: I,
+    regs: &mut Registers<T>,
+    csrs: &mut CsrRegisters<T>,
+    extensions: &Extensions,
+    read: bool,
+    write: bool,
+    op: impl FnOnce(&mut T, &mut T) -> T,
+) -> Result<(), Exception>
+where
+    T: Copy + Zero + As<u8> + As<u64>,
+    u8: As<T>,
+    u64: As<T>,
+{
+    let addr = instruction.imm.as_u16() as usize;
+    let mut csr = if read {
+        csrs.read(addr, extensions)
+    } else {
+        Zero::zero()
+    };
+    let mut old = Zero::zero();
+    let value = op(&mut old, &mut csr);
+    if write {
+        csrs.write(addr, value, extensions)
+            .ok_or(ILLEGAL_INSTRUCTION)?;
+    }
+    if read {
+        ZeroOrRegister::from_u5(instruction.rd).store(regs, old);
+    }
+    Ok(())
+}
+
+impl<T> Privileged for T
+where
+    T: Copy + Zero + As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn mret(
+        csrs: &mut CsrRegisters<Self>,
+        extensions: &Extensions,
+        pc: &mut Self,
+        privilege: &mut Privilege,
+        ialign: u8,
+    ) -> Result<(), Exception> {
+        if *privilege != Privilege::Machine {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        let mstatus: u64 = csrs.get(MSTATUS).r#as();
+        let mpp = ((mstatus & MSTATUS_MPP) >> 11) as u8;
+        *privilege = Privilege::decode(mpp).unwrap_or(Privilege::Machine);
+        let mie = if mstatus & MSTATUS_MPIE != 0 {
+            MSTATUS_MIE
         } else {
-            let dest = ZeroOrRegister::from_u5(instruction.rd)
-                .fetch_mut(regs)
-                .ok_or(Error::InvalidOpCode)?;
-            *dest = csrs.get(instruction.imm.as_u16() as usize);
+            0
+        };
+        // MPP is left at the least privileged mode, returns below M-mode
+        // also clear MPRV
+        let mpp = (extensions.lowest_privilege() as u64) << 11;
+        let mprv = if *privilege == Privilege::Machine {
+            mstatus & MSTATUS_MPRV
+        } else {
+            0
+        };
+        let mstatus =
+            mstatus & !(MSTATUS_MIE | MSTATUS_MPP | MSTATUS_MPRV) | mie | MSTATUS_MPIE | mpp | mprv;
+        csrs.set(MSTATUS, mstatus.r#as());
+        // mepc[1] reads as zero when IALIGN is 32
+        let mepc: u64 = csrs.get(MEPC).r#as();
+        *pc = (mepc & !(ialign as u64 - 1)).r#as();
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn sret(
+        csrs: &mut CsrRegisters<Self>,
+        pc: &mut Self,
+        privilege: &mut Privilege,
+        ialign: u8,
+    ) -> Result<(), Exception> {
+        let mstatus: u64 = csrs.get(MSTATUS).r#as();
+        if *privilege == Privilege::User
+            || *privilege == Privilege::Supervisor && mstatus & MSTATUS_TSR != 0
+        {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        *privilege = if mstatus & MSTATUS_SPP != 0 {
+            Privilege::Supervisor
+        } else {
+            Privilege::User
+        };
+        let sie = if mstatus & MSTATUS_SPIE != 0 {
+            MSTATUS_SIE
+        } else {
+            0
+        };
+        // sret always returns below M-mode, so it clears MPRV
+        let mstatus = mstatus & !(MSTATUS_SIE | MSTATUS_SPP | MSTATUS_MPRV) | sie | MSTATUS_SPIE;
+        csrs.set(MSTATUS, mstatus.r#as());
+        let sepc: u64 = csrs.get(SEPC).r#as();
+        *pc = (sepc & !(ialign as u64 - 1)).r#as();
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn wfi(
+        csrs: &CsrRegisters<Self>,
+        extensions: &Extensions,
+        privilege: Privilege,
+    ) -> Result<(), Exception> {
+        let mstatus: u64 = csrs.get(MSTATUS).r#as();
+        // U-mode waits time out at once when S-mode exists
+        let timeout = privilege < Privilege::Machine && mstatus & MSTATUS_TW != 0
+            || privilege == Privilege::User && extensions.supports(Privilege::Supervisor);
+        if timeout {
+            return Err(ILLEGAL_INSTRUCTION);
         }
         Ok(())
     }
+
+    #[inline(always)]
+    fn sfence_vma(
+        instruction: R,
+        regs: &Registers<Self>,
+        csrs: &CsrRegisters<Self>,
+        tlb: &mut Tlb,
+        privilege: Privilege,
+    ) -> Result<(), Exception> {
+        let mstatus: u64 = csrs.get(MSTATUS).r#as();
+        if privilege == Privilege::User
+            || privilege == Privilege::Supervisor && mstatus & MSTATUS_TVM != 0
+        {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        let operand = |reg: U5| match ZeroOrRegister::from_u5(reg) {
+            ZeroOrRegister::Zero => None,
+            reg => Some(<Self as As<u64>>::r#as(reg.fetch(regs))),
+        };
+        // ASIDs are 9 bits wide on RV32
+        let asid_mask = if size_of::<Self>() == 4 {
+            0x1ff
+        } else {
+            0xffff
+        };
+        let asid = operand(instruction.rs2).map(|asid| (asid & asid_mask) as u16);
+        tlb.flush(operand(instruction.rs1), asid);
+        Ok(())
+    }
 }
 
-// it's ok since casting between integers of the
-// same size is a noop
+// Results moved to integer registers are sign extended to XLEN bits, the
+// 64-bit conversions are only decoded on RV64.
 impl<T> FloatS for T
 where
     T: Copy,
     T: Zero,
+    T: As<u8>,
     T: As<u32>,
-    u32: As<T>,
+    T: As<u64>,
+    u8: As<T>,
+    i32: As<T>,
+    u64: As<T>,
 {
     #[inline(always)]
     fn floats(
         instruction: R,
-        fregs: &mut Registers<Self>,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, u32, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception> {
+        let inx = extensions.inx();
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = get_s(regfile, instruction.rs1, inx);
+        let src2 = get_s(regfile, instruction.rs2, inx);
+        set_s(regfile, instruction.rd, op(src1, src2, &mut env), inx);
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floats_to_x(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, u32, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception> {
+        let inx = extensions.inx();
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = get_s(regfile, instruction.rs1, inx);
+        let src2 = get_s(regfile, instruction.rs2, inx);
+        let value = (op(src1, src2, &mut env) as i32).r#as();
+        ZeroOrRegister::from_u5(instruction.rd).store(&mut regfile.xregs, value);
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floats_raw_to_x(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        _: &Extensions,
+        op: fn(u32, u32, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = regfile.fregs.get(instruction.rs1.into()) as u32;
+        let value = (op(src1, 0, &mut env) as i32).r#as();
+        ZeroOrRegister::from_u5(instruction.rd).store(&mut regfile.xregs, value);
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floats_from_x(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, u32, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(&regfile.xregs);
+        let value = op(src1.r#as(), 0, &mut env);
+        set_s(regfile, instruction.rd, value, extensions.inx());
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floats_to_x64(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = get_s(regfile, instruction.rs1, extensions.inx());
+        let value = op(src1, &mut env).r#as();
+        ZeroOrRegister::from_u5(instruction.rd).store(&mut regfile.xregs, value);
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floats_from_x64(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(&regfile.xregs);
+        let value = op(src1.r#as(), &mut env);
+        set_s(regfile, instruction.rd, value, extensions.inx());
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floats_imm(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u8) -> u32,
+    ) -> Result<(), Exception> {
+        let value = op(instruction.rs1.as_u8());
+        set_s(regfile, instruction.rd, value, extensions.inx());
+        Ok(())
+    }
+}
+
+impl<T> FloatD for T
+where
+    T: Copy,
+    T: Zero,
+    T: As<u8>,
+    T: As<u32>,
+    T: As<u64>,
+    u8: As<T>,
+    i32: As<T>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn floatd(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let inx = extensions.inx();
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = get_d(regfile, instruction.rs1, inx);
+        let src2 = get_d(regfile, instruction.rs2, inx);
+        set_d(regfile, instruction.rd, op(src1, src2, &mut env), inx);
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floatd_to_x(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let inx = extensions.inx();
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = get_d(regfile, instruction.rs1, inx);
+        let src2 = get_d(regfile, instruction.rs2, inx);
+        let value = op(src1, src2, &mut env).r#as();
+        ZeroOrRegister::from_u5(instruction.rd).store(&mut regfile.xregs, value);
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floatd_from_x(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(&regfile.xregs);
+        let value = op(src1.r#as(), 0, &mut env);
+        set_d(regfile, instruction.rd, value, extensions.inx());
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floatd_to_s(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception> {
+        let inx = extensions.inx();
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = get_d(regfile, instruction.rs1, inx);
+        set_s(regfile, instruction.rd, op(src1, &mut env), inx);
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floats_to_d(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let inx = extensions.inx();
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = get_s(regfile, instruction.rs1, inx);
+        set_d(regfile, instruction.rd, op(src1, &mut env), inx);
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floatd_from_x2(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(&regfile.xregs);
+        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(&regfile.xregs);
+        let value = op(src1.r#as(), src2.r#as(), &mut env);
+        set_d(regfile, instruction.rd, value, extensions.inx());
+        regfile.csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floatd_imm(
+        instruction: R,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u8) -> u64,
+    ) -> Result<(), Exception> {
+        let value = op(instruction.rs1.as_u8());
+        set_d(regfile, instruction.rd, value, extensions.inx());
+        Ok(())
+    }
+}
+
+impl<T> FloatH for T
+where
+    T: Copy,
+    T: Zero,
+    T: As<u8>,
+    T: As<u64>,
+    u8: As<T>,
+    i32: As<T>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn floath(
+        instruction: R,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, u16, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, csrs)?;
+        let src1 = fregs.get_h(instruction.rs1.into());
+        let src2 = fregs.get_h(instruction.rs2.into());
+        fregs.set_h(instruction.rd.into(), op(src1, src2, &mut env));
+        csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floath_to_x(
+        instruction: R,
+        fregs: &FRegisters,
         xregs: &mut Registers<Self>,
-    ) -> Result<(), Error> {
-        match instruction.rd.into() {
-            ZeroOrRegister::Zero => Err(Error::InvalidOpCode),
-            ZeroOrRegister::Register(reg) => {
-                match instruction.fid() {
-                    FADD_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *fregs.get_mut(reg) = Fadd::fadd(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FSUB_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *fregs.get_mut(reg) = Fsub::fsub(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FMUL_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *fregs.get_mut(reg) = Fmul::fmul(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FDIV_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *fregs.get_mut(reg) = Fdiv::fdiv(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FSQRT_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        *fregs.get_mut(reg) = Fsqrt::fsqrt(src1.r#as(), 0).r#as();
-                    }
-                    FSGNJ_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *fregs.get_mut(reg) = Fsgnj::fsgnj(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FSGNJN_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *fregs.get_mut(reg) = Fsgnjn::fsgnjn(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FSGNJNX_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *fregs.get_mut(reg) = Fsgnjx::fsgnjx(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FMIN_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *fregs.get_mut(reg) = Fmin::fmin(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FMAX_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *fregs.get_mut(reg) = Fmax::fmax(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FCVT_W_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        *xregs.get_mut(reg) = Fcvtws::fcvtws(src1.r#as(), 0).r#as();
-                    }
-                    FCVT_WU_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        *xregs.get_mut(reg) = Fcvtwus::fcvtwus(src1.r#as(), 0).r#as();
-                    }
-                    FMV_X_W => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        *xregs.get_mut(reg) = Fmvxw::fmvxw(src1.r#as(), 0).r#as();
-                    }
-                    FEQ_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *xregs.get_mut(reg) = Feq::feq(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FLT_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *xregs.get_mut(reg) = Flt::flt(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FLE_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-                        *xregs.get_mut(reg) = Fle::fle(src1.r#as(), src2.r#as()).r#as();
-                    }
-                    FCLASS_S => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(fregs);
-                        *xregs.get_mut(reg) = Fclass::fclass(src1.r#as(), 0).r#as();
-                    }
-                    FCVT_S_W => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(xregs);
-                        *fregs.get_mut(reg) = Fcvtsw::fcvtsw(src1.r#as(), 0).r#as();
-                    }
-                    FCVT_S_WU => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(xregs);
-                        *fregs.get_mut(reg) = Fcvtswu::fcvtswu(src1.r#as(), 0).r#as();
-                    }
-                    FMV_W_X => {
-                        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(xregs);
-                        *fregs.get_mut(reg) = Fmvwx::fmvwx(src1.r#as(), 0).r#as();
-                    }
-                    _ => return Err(Error::InvalidOpCode),
-                }
-                Ok(())
-            }
-        }
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, u16, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, csrs)?;
+        let src1 = fregs.get_h(instruction.rs1.into());
+        let src2 = fregs.get_h(instruction.rs2.into());
+        let value = (op(src1, src2, &mut env) as u64).r#as();
+        ZeroOrRegister::from_u5(instruction.rd).store(xregs, value);
+        csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floath_to_x32(
+        instruction: R,
+        fregs: &FRegisters,
+        xregs: &mut Registers<Self>,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, csrs)?;
+        let src1 = fregs.get_h(instruction.rs1.into());
+        let value = (op(src1, &mut env) as i32).r#as();
+        ZeroOrRegister::from_u5(instruction.rd).store(xregs, value);
+        csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floath_to_x64(
+        instruction: R,
+        fregs: &FRegisters,
+        xregs: &mut Registers<Self>,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, csrs)?;
+        let src1 = fregs.get_h(instruction.rs1.into());
+        ZeroOrRegister::from_u5(instruction.rd).store(xregs, op(src1, &mut env).r#as());
+        csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floath_raw_to_x(
+        instruction: R,
+        fregs: &FRegisters,
+        xregs: &mut Registers<Self>,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, csrs)?;
+        let src1 = fregs.get(instruction.rs1.into()) as u16;
+        ZeroOrRegister::from_u5(instruction.rd).store(xregs, op(src1, &mut env).r#as());
+        csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floath_from_x(
+        instruction: R,
+        xregs: &Registers<Self>,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u64, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, csrs)?;
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(xregs);
+        fregs.set_h(instruction.rd.into(), op(src1.r#as(), &mut env));
+        csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floath_to_s(
+        instruction: R,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, csrs)?;
+        let src1 = fregs.get_h(instruction.rs1.into());
+        fregs.set_s(instruction.rd.into(), op(src1, &mut env));
+        csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floats_to_h(
+        instruction: R,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u32, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, csrs)?;
+        let src1 = fregs.get_s(instruction.rs1.into());
+        fregs.set_h(instruction.rd.into(), op(src1, &mut env));
+        csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floath_to_d(
+        instruction: R,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, csrs)?;
+        let src1 = fregs.get_h(instruction.rs1.into());
+        fregs.set(instruction.rd.into(), op(src1, &mut env));
+        csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floatd_to_h(
+        instruction: R,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u64, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, csrs)?;
+        let src1 = fregs.get(instruction.rs1.into());
+        fregs.set_h(instruction.rd.into(), op(src1, &mut env));
+        csrs.accrue_fflags(env.flags);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn floath_imm(
+        instruction: R,
+        fregs: &mut FRegisters,
+        op: fn(u8) -> u16,
+    ) -> Result<(), Exception> {
+        fregs.set_h(instruction.rd.into(), op(instruction.rs1.as_u8()));
+        Ok(())
     }
 }
 
-impl Fload for u32 {
+impl<T> Fload for T
+where
+    T: Copy + Zero + UnsignedWrapping + As<usize>,
+    i16: As<<T as Unsigned>::Signed>,
+{
     #[inline(always)]
     fn fload(
         instruction: I,
-        xregs: &mut Registers<Self>,
-        fregs: &mut Registers<Self>,
-        memory: &mut [u8],
-    ) -> Result<(), Error> {
-        let dest_reg =
-            if let ZeroOrRegister::Register(reg) = ZeroOrRegister::from_u5(instruction.rd) {
-                reg
-            } else {
-                return Err(Error::InvalidOpCode);
-            };
-        let offset = ZeroOrRegister::from_u5(instruction.rs1)
-            .fetch(xregs)
-            .wrapping_add_signed(instruction.imm.sign_extend() as i32)
-            as usize;
-        *fregs.get_mut(dest_reg) = match instruction.id() {
-            FLW => Flw::flw(memory, offset)?,
-            _ => return Err(Error::InvalidOpCode),
-        };
+        xregs: &Registers<Self>,
+        fregs: &mut FRegisters,
+        memory: &mut Bus,
+        op: fn(&mut Bus, usize) -> Result<u32, Exception>,
+    ) -> Result<(), Exception> {
+        let base = ZeroOrRegister::from_u5(instruction.rs1).fetch(xregs);
+        let value = op(memory, effective_address(base, instruction.imm))?;
+        fregs.set_s(instruction.rd.into(), value);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn fload_d(
+        instruction: I,
+        xregs: &Registers<Self>,
+        fregs: &mut FRegisters,
+        memory: &mut Bus,
+        op: fn(&mut Bus, usize) -> Result<u64, Exception>,
+    ) -> Result<(), Exception> {
+        let base = ZeroOrRegister::from_u5(instruction.rs1).fetch(xregs);
+        let value = op(memory, effective_address(base, instruction.imm))?;
+        fregs.set(instruction.rd.into(), value);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn fload_h(
+        instruction: I,
+        xregs: &Registers<Self>,
+        fregs: &mut FRegisters,
+        memory: &mut Bus,
+        op: fn(&mut Bus, usize) -> Result<u16, Exception>,
+    ) -> Result<(), Exception> {
+        let base = ZeroOrRegister::from_u5(instruction.rs1).fetch(xregs);
+        let value = op(memory, effective_address(base, instruction.imm))?;
+        fregs.set_h(instruction.rd.into(), value);
         Ok(())
     }
 }
 
-impl Fstore for u32 {
+impl<T> Fstore for T
+where
+    T: Copy + Zero + UnsignedWrapping + As<usize>,
+    i16: As<<T as Unsigned>::Signed>,
+{
     #[inline(always)]
     fn fstore(
         instruction: S,
-        xregs: &mut Registers<Self>,
-        fregs: &mut Registers<Self>,
-        memory: &mut [u8],
-    ) -> Result<(), Error> {
+        xregs: &Registers<Self>,
+        fregs: &FRegisters,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(u32, &mut Bus, usize) -> Result<(), Exception>,
+    ) -> Result<(), Exception> {
         let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(xregs);
-        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(fregs);
-        let offset = src1.wrapping_add_signed(instruction.imm.sign_extend() as i32) as usize;
-        match instruction.id() {
-            FSW => Fsw::fsw(src2, memory, offset),
-            _ => Err(Error::InvalidOpCode),
-        }
+        let src2 = fregs.get(instruction.rs2.into()) as u32;
+        let addr = effective_address(src1, instruction.imm);
+        op(src2, memory, addr)?;
+        break_reservation(reservation, addr, instruction.width());
+        Ok(())
     }
-}
 
-// TODO: switch to macro when implementing for u64
+    #[inline(always)]
+    fn fstore_d(
+        instruction: S,
+        xregs: &Registers<Self>,
+        fregs: &FRegisters,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(u64, &mut Bus, usize) -> Result<(), Exception>,
+    ) -> Result<(), Exception> {
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(xregs);
+        let src2 = fregs.get(instruction.rs2.into());
+        let addr = effective_address(src1, instruction.imm);
+        op(src2, memory, addr)?;
+        break_reservation(reservation, addr, instruction.width());
+        Ok(())
+    }
 
-impl FmaddS for u32 {
     #[inline(always)]
-    fn fmadd(instruction: R4, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
-        let src3 = ZeroOrRegister::from_u5(instruction.rs3).fetch(regs);
-        match instruction.rd.into() {
-            ZeroOrRegister::Register(reg) => {
-                *regs.get_mut(reg) = f32::from_bits(src1)
-                    .mul_add(f32::from_bits(src2), f32::from_bits(src3))
-                    .to_bits()
-            }
-            _ => return Err(Error::InvalidOpCode),
-        };
+    fn fstore_h(
+        instruction: S,
+        xregs: &Registers<Self>,
+        fregs: &FRegisters,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        op: fn(u16, &mut Bus, usize) -> Result<(), Exception>,
+    ) -> Result<(), Exception> {
+        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(xregs);
+        let src2 = fregs.get(instruction.rs2.into()) as u16;
+        let addr = effective_address(src1, instruction.imm);
+        op(src2, memory, addr)?;
+        break_reservation(reservation, addr, instruction.width());
         Ok(())
     }
 }
 
-impl FmsubS for u32 {
+impl<T> FmaS for T
+where
+    T: Copy + Zero + As<u8> + As<u32>,
+    u8: As<T>,
+    i32: As<T>,
+{
     #[inline(always)]
-    fn fmsub(instruction: R4, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
-        let src3 = ZeroOrRegister::from_u5(instruction.rs3).fetch(regs);
-        match instruction.rd.into() {
-            ZeroOrRegister::Register(reg) => {
-                *regs.get_mut(reg) =
-                    (f32::from_bits(src1) * f32::from_bits(src2) - f32::from_bits(src3)).to_bits()
-            }
-            _ => return Err(Error::InvalidOpCode),
-        };
+    fn fmas(
+        instruction: R4,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u32, u32, u32, &mut FloatEnv) -> u32,
+    ) -> Result<(), Exception> {
+        let inx = extensions.inx();
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = get_s(regfile, instruction.rs1, inx);
+        let src2 = get_s(regfile, instruction.rs2, inx);
+        let src3 = get_s(regfile, instruction.rs3, inx);
+        set_s(regfile, instruction.rd, op(src1, src2, src3, &mut env), inx);
+        regfile.csrs.accrue_fflags(env.flags);
         Ok(())
     }
 }
 
-impl FnmsubS for u32 {
+impl<T> FmaD for T
+where
+    T: Copy + Zero + As<u8> + As<u64>,
+    u8: As<T>,
+    u64: As<T>,
+{
     #[inline(always)]
-    fn fnmsub(instruction: R4, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
-        let src3 = ZeroOrRegister::from_u5(instruction.rs3).fetch(regs);
-        match instruction.rd.into() {
-            ZeroOrRegister::Register(reg) => {
-                *regs.get_mut(reg) = (-(f32::from_bits(src1) * f32::from_bits(src2))
-                    + f32::from_bits(src3))
-                .to_bits()
-            }
-            _ => return Err(Error::InvalidOpCode),
-        };
+    fn fmad(
+        instruction: R4,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, u64, &mut FloatEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let inx = extensions.inx();
+        let mut env = float_env(instruction.funct3, &regfile.csrs)?;
+        let src1 = get_d(regfile, instruction.rs1, inx);
+        let src2 = get_d(regfile, instruction.rs2, inx);
+        let src3 = get_d(regfile, instruction.rs3, inx);
+        set_d(regfile, instruction.rd, op(src1, src2, src3, &mut env), inx);
+        regfile.csrs.accrue_fflags(env.flags);
         Ok(())
     }
 }
 
-impl FnmaddS for u32 {
+impl<T> FmaH for T
+where
+    T: Copy + As<u8>,
+    u8: As<T>,
+{
     #[inline(always)]
-    fn fnmadd(instruction: R4, regs: &mut Registers<Self>) -> Result<(), Error> {
-        let src1 = ZeroOrRegister::from_u5(instruction.rs1).fetch(regs);
-        let src2 = ZeroOrRegister::from_u5(instruction.rs2).fetch(regs);
-        let src3 = ZeroOrRegister::from_u5(instruction.rs3).fetch(regs);
-        match instruction.rd.into() {
-            ZeroOrRegister::Register(reg) => {
-                *regs.get_mut(reg) = (-(f32::from_bits(src1) * f32::from_bits(src2))
-                    - f32::from_bits(src3))
-                .to_bits()
-            }
-            _ => return Err(Error::InvalidOpCode),
-        };
+    fn fmah(
+        instruction: R4,
+        fregs: &mut FRegisters,
+        csrs: &mut CsrRegisters<Self>,
+        op: fn(u16, u16, u16, &mut FloatEnv) -> u16,
+    ) -> Result<(), Exception> {
+        let mut env = float_env(instruction.funct3, csrs)?;
+        let src1 = fregs.get_h(instruction.rs1.into());
+        let src2 = fregs.get_h(instruction.rs2.into());
+        let src3 = fregs.get_h(instruction.rs3.into());
+        fregs.set_h(instruction.rd.into(), op(src1, src2, src3, &mut env));
+        csrs.accrue_fflags(env.flags);
         Ok(())
     }
 }
@@ -0,0 +1,277 @@
+/// Width of the integer registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Xlen {
+    Rv32,
+    Rv64,
+}
+
+/// Privilege mode a hart executes in, ordered from least to most privileged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Privilege {
+    User = 0,
+    Supervisor = 1,
+    #[default]
+    Machine = 3,
+}
+
+impl Privilege {
+    /// The mode with the given encoding, as in `mstatus.MPP`. 2 is reserved.
+    #[inline(always)]
+    pub const fn decode(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(Self::User),
+            1 => Some(Self::Supervisor),
+            3 => Some(Self::Machine),
+            _ => None,
+        }
+    }
+}
+
+/// ISA extensions implemented by a hart, on top of the RV32I/RV64I base.
+///
+/// Instructions belonging to a disabled extension decode as illegal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Extensions {
+    /// Integer multiplication and division.
+    pub m: bool,
+    /// Atomic memory operations.
+    pub a: bool,
+    /// Single-precision floating point.
+    pub f: bool,
+    /// Double-precision floating point, requires `f`.
+    pub d: bool,
+    /// Half-precision floating point, implies `zfhmin`.
+    pub zfh: bool,
+    /// Half-precision loads, stores and conversions, requires `f`.
+    pub zfhmin: bool,
+    /// Single-precision floating point on the integer registers, in place of
+    /// `f`.
+    pub zfinx: bool,
+    /// Double-precision floating point on the integer registers, in register
+    /// pairs on RV32, requires `zfinx`.
+    pub zdinx: bool,
+    /// Additional floating point instructions, requires `f`.
+    pub zfa: bool,
+    /// Compressed 16-bit instructions.
+    pub c: bool,
+    /// Additional compressed byte and halfword accesses, extensions and
+    /// multiplication, requires `c`.
+    pub zcb: bool,
+    /// Compressed stack push and pop and the `a0`/`a1` moves, requires `c`.
+    /// Takes over the encodings of the compressed double-precision accesses.
+    pub zcmp: bool,
+    /// Compressed table jumps through the `jvt` CSR, requires `c`. Takes over
+    /// the encodings of the compressed double-precision accesses.
+    pub zcmt: bool,
+    /// Vector operations, the register file shape is configured on
+    /// `VRegisters`.
+    pub v: bool,
+    /// Supervisor mode, with trap delegation and `sret`.
+    pub s: bool,
+    /// User mode, the least privileged.
+    pub u: bool,
+    /// 64 KiB naturally aligned pages in Sv39 and up.
+    pub svnapot: bool,
+    /// Hardware updating of the page table A and D bits, which raise page
+    /// faults without it.
+    pub svadu: bool,
+    /// Control and status register instructions.
+    pub zicsr: bool,
+    /// Base counters `cycle`, `time` and `instret`.
+    pub zicntr: bool,
+    /// Hardware performance counters `hpmcounter3` to `hpmcounter31`.
+    pub zihpm: bool,
+    /// Instruction-fetch fence.
+    pub zifencei: bool,
+    /// Address generation, shifted adds and `.uw` forms.
+    pub zba: bool,
+    /// Basic bit manipulation.
+    pub zbb: bool,
+    /// Carry-less multiplication.
+    pub zbc: bool,
+    /// Single-bit instructions.
+    pub zbs: bool,
+    /// Bit manipulation for cryptography.
+    pub zbkb: bool,
+    /// Carry-less multiplication for cryptography.
+    pub zbkc: bool,
+    /// Crossbar permutations.
+    pub zbkx: bool,
+    /// AES decryption.
+    pub zknd: bool,
+    /// AES encryption.
+    pub zkne: bool,
+    /// SHA-2 hash functions.
+    pub zknh: bool,
+    /// SM4 block cipher.
+    pub zksed: bool,
+    /// SM3 hash function.
+    pub zksh: bool,
+    /// Entropy source, the `seed` CSR.
+    pub zkr: bool,
+    /// Conditional zeroing, used by compilers for branchless selects. Clear
+    /// it to model cores that predate it.
+    pub zicond: bool,
+    /// Custom byte math extension.
+    pub xbyte: bool,
+}
+
+impl Extensions {
+    /// Every extension supported by the emulator, except Zfinx and Zdinx
+    /// which replace F and D, and Zcmp and Zcmt which replace the compressed
+    /// double-precision accesses.
+    pub const ALL: Self = Self {
+        m: true,
+        a: true,
+        f: true,
+        d: true,
+        zfh: true,
+        zfhmin: true,
+        zfinx: false,
+        zdinx: false,
+        zfa: true,
+        c: true,
+        zcb: true,
+        zcmp: false,
+        zcmt: false,
+        v: true,
+        s: true,
+        u: true,
+        svnapot: true,
+        svadu: true,
+        zicsr: true,
+        zicntr: true,
+        zihpm: true,
+        zifencei: true,
+        zba: true,
+        zbb: true,
+        zbc: true,
+        zbs: true,
+        zbkb: true,
+        zbkc: true,
+        zbkx: true,
+        zknd: true,
+        zkne: true,
+        zknh: true,
+        zksed: true,
+        zksh: true,
+        zkr: true,
+        zicond: true,
+        xbyte: true,
+    };
+
+    /// The bare base integer ISA.
+    pub const NONE: Self = Self {
+        m: false,
+        a: false,
+        f: false,
+        d: false,
+        zfh: false,
+        zfhmin: false,
+        zfinx: false,
+        zdinx: false,
+        zfa: false,
+        c: false,
+        zcb: false,
+        zcmp: false,
+        zcmt: false,
+        v: false,
+        s: false,
+        u: false,
+        svnapot: false,
+        svadu: false,
+        zicsr: false,
+        zicntr: false,
+        zihpm: false,
+        zifencei: false,
+        zba: false,
+        zbb: false,
+        zbc: false,
+        zbs: false,
+        zbkb: false,
+        zbkc: false,
+        zbkx: false,
+        zknd: false,
+        zkne: false,
+        zknh: false,
+        zksed: false,
+        zksh: false,
+        zkr: false,
+        zicond: false,
+        xbyte: false,
+    };
+}
+
+impl Extensions {
+    /// Whether F and D operands live in the integer registers, `f` wins when
+    /// both it and `zfinx` are set.
+    #[inline(always)]
+    pub const fn inx(&self) -> bool {
+        self.zfinx && !self.f
+    }
+
+    /// The single-letter extension bits of `misa`, F and D stay clear under
+    /// Zfinx and Zdinx.
+    pub const fn misa(&self) -> u32 {
+        letter(self.a, b'A')
+            | letter(self.c, b'C')
+            | letter(self.f && self.d, b'D')
+            | letter(self.f, b'F')
+            | letter(true, b'I')
+            | letter(self.m, b'M')
+            | letter(self.s, b'S')
+            | letter(self.u, b'U')
+            | letter(self.v, b'V')
+    }
+
+    /// Whether the hart has the `privilege` mode.
+    #[inline(always)]
+    pub const fn supports(&self, privilege: Privilege) -> bool {
+        match privilege {
+            Privilege::User => self.u,
+            Privilege::Supervisor => self.s,
+            Privilege::Machine => true,
+        }
+    }
+
+    /// The least privileged mode the hart has, which xRET leave in xPP.
+    #[inline(always)]
+    pub const fn lowest_privilege(&self) -> Privilege {
+        if self.u {
+            Privilege::User
+        } else if self.s {
+            Privilege::Supervisor
+        } else {
+            Privilege::Machine
+        }
+    }
+
+    /// Whether the compressed double-precision loads and stores are encoded,
+    /// Zcmp and Zcmt reuse their opcode space.
+    #[inline(always)]
+    pub const fn zcd(&self) -> bool {
+        !self.zcmp && !self.zcmt
+    }
+
+    /// Alignment in bytes required of instruction addresses.
+    #[inline(always)]
+    pub const fn ialign(&self) -> u8 {
+        if self.c {
+            2
+        } else {
+            4
+        }
+    }
+}
+
+impl Default for Extensions {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+#[inline(always)]
+const fn letter(set: bool, letter: u8) -> u32 {
+    (set as u32) << (letter - b'A')
+}
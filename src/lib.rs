@@ -0,0 +1,22 @@
+pub mod compressed;
+pub mod csr;
+pub mod decode;
+pub mod elf;
+pub mod entropy;
+pub mod hart;
+pub mod instruction_ids;
+pub mod instructions;
+pub mod isa;
+pub mod machine;
+pub mod mem;
+pub mod mmu;
+pub mod num;
+pub mod ops;
+pub mod registers;
+pub mod softfloat;
+pub mod trap;
+pub mod vector;
+
+pub use hart::Hart;
+pub use machine::Machine;
+pub use trap::{Exception, Interrupt};
@@ -0,0 +1,281 @@
+use crate::hart::Hart;
+use crate::instructions::{break_reservation, BaseInstruction};
+use crate::mem::{self, Pod};
+use crate::num::As;
+use crate::registers::{FRegister, Register};
+use crate::trap::Exception;
+
+/// A hart attached to a flat, zero-based physical memory.
+#[derive(Debug)]
+pub struct Machine<T> {
+    hart: Hart<T>,
+    memory: Vec<u8>,
+}
+
+impl<T: Copy + Default + As<u64>> Machine<T>
+where
+    u16: As<T>,
+    u64: As<T>,
+{
+    /// Creates a machine with `memory_size` bytes of zeroed memory and the
+    /// program counter at address zero.
+    #[inline]
+    pub fn new(memory_size: usize) -> Self {
+        Self {
+            hart: Hart::default(),
+            memory: vec![0; memory_size],
+        }
+    }
+}
+
+impl<T> Machine<T> {
+    #[inline(always)]
+    pub fn hart(&self) -> &Hart<T> {
+        &self.hart
+    }
+
+    #[inline(always)]
+    pub fn hart_mut(&mut self) -> &mut Hart<T> {
+        &mut self.hart
+    }
+
+    #[inline(always)]
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    #[inline(always)]
+    pub fn memory_mut(&mut self) -> &mut [u8] {
+        &mut self.memory
+    }
+
+    /// Copies `data` into memory starting at `addr`. Like a store, it breaks
+    /// an LR reservation on the bytes it overwrites.
+    #[inline(always)]
+    pub fn load(&mut self, data: &[u8], addr: usize) -> Result<(), Exception> {
+        mem::memw(data, &mut self.memory, addr)?;
+        break_reservation(&mut self.hart.reservation, addr, data.len());
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn read<V: Pod>(&self, addr: usize) -> Result<V, Exception> {
+        mem::read(&self.memory, addr)
+    }
+
+    /// Writes `value` at `addr`, breaking an LR reservation on its bytes as a
+    /// store does.
+    #[inline(always)]
+    pub fn write<V: Pod>(&mut self, value: V, addr: usize) -> Result<(), Exception> {
+        mem::write(&value, &mut self.memory, addr)?;
+        break_reservation(&mut self.hart.reservation, addr, size_of::<V>());
+        Ok(())
+    }
+}
+
+impl<T: Copy> Machine<T> {
+    #[inline(always)]
+    pub fn pc(&self) -> T {
+        self.hart.pc
+    }
+
+    #[inline(always)]
+    pub fn set_pc(&mut self, pc: T) {
+        self.hart.pc = pc;
+    }
+
+    #[inline(always)]
+    pub fn xreg(&self, reg: Register) -> T {
+        self.hart.regfile.xregs.get(reg)
+    }
+
+    #[inline(always)]
+    pub fn set_xreg(&mut self, reg: Register, value: T) {
+        *self.hart.regfile.xregs.get_mut(reg) = value;
+    }
+
+    #[inline(always)]
+    pub fn freg(&self, reg: FRegister) -> u64 {
+        self.hart.regfile.fregs.get(reg)
+    }
+
+    #[inline(always)]
+    pub fn set_freg(&mut self, reg: FRegister, value: u64) {
+        self.hart.regfile.fregs.set(reg, value);
+    }
+
+    #[inline(always)]
+    pub fn csr(&self, csr: usize) -> T {
+        self.hart.regfile.csrs.get(csr)
+    }
+}
+
+impl<T: BaseInstruction> Machine<T>
+where
+    u64: As<T>,
+{
+    /// Fetches, decodes and executes a single instruction.
+    #[inline(always)]
+    pub fn step(&mut self) -> Result<(), Exception> {
+        self.hart.step(&mut self.memory)
+    }
+
+    /// Enters the trap handler for an exception `step` returned.
+    #[inline(always)]
+    pub fn trap(&mut self, exception: Exception) {
+        self.hart.trap(exception)
+    }
+
+    /// Steps the machine until an instruction raises an exception, returning it.
+    pub fn run(&mut self) -> Exception {
+        loop {
+            if let Err(err) = self.step() {
+                return err;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
+mod tests {
+    use super::*;
+    use crate::isa::Privilege;
+    use crate::registers;
+
+    #[test]
+    fn test_step() {
+        let mut machine = Machine::<u32>::new(64);
+        // addi x12, x0, 42
+        machine
+            .write(mem::U32::new(0b000000101010_00000_000_01100_0010011), 0)
+            .unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.xreg(Register::X12), 42);
+        assert_eq!(machine.pc(), 4);
+    }
+
+    #[test]
+    fn test_run() {
+        let mut machine = Machine::<u64>::new(64);
+        // addi x12, x0, 42; addi x13, x12, 1
+        machine
+            .write(mem::U32::new(0b000000101010_00000_000_01100_0010011), 0)
+            .unwrap();
+        machine
+            .write(mem::U32::new(0b000000000001_01100_000_01101_0010011), 4)
+            .unwrap();
+        assert_eq!(machine.run(), Exception::IllegalInstruction(0));
+        assert_eq!(machine.xreg(Register::X12), 42);
+        assert_eq!(machine.xreg(Register::X13), 43);
+        assert_eq!(machine.pc(), 8);
+    }
+
+    #[test]
+    fn test_write_breaks_reservation() {
+        let mut machine = Machine::<u64>::new(0x100);
+        // lr.w x11, (x10); sc.w x12, x13, (x10)
+        machine.load(&0x1005_25afu32.to_le_bytes(), 0).unwrap();
+        machine.load(&0x18d5_262fu32.to_le_bytes(), 4).unwrap();
+        machine.set_xreg(Register::X10, 0x80);
+        machine.set_xreg(Register::X13, 7);
+        machine.step().unwrap();
+        machine.write(mem::U16::new(0xffff), 0x82).unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.xreg(Register::X12), 1);
+        assert_eq!(
+            machine.read::<mem::U32>(0x80).unwrap().as_u32(),
+            0xffff_0000
+        );
+    }
+
+    #[test]
+    fn test_trap_handler() {
+        let mut machine = Machine::<u32>::new(0x200);
+        let program = [
+            0x10000293, // li t0, 0x100
+            0x30529073, // csrw mtvec, t0
+            0x00000073, // ecall
+            0x02a00613, // li a2, 42
+        ];
+        let handler = [
+            0x341022f3, // csrr t0, mepc
+            0x00428293, // addi t0, t0, 4
+            0x34129073, // csrw mepc, t0
+            0x30200073, // mret
+        ];
+        for (i, instruction) in program.into_iter().enumerate() {
+            machine.write(mem::U32::new(instruction), i * 4).unwrap();
+        }
+        for (i, instruction) in handler.into_iter().enumerate() {
+            machine
+                .write(mem::U32::new(instruction), 0x100 + i * 4)
+                .unwrap();
+        }
+        let exception = loop {
+            match machine.step() {
+                Ok(()) => {}
+                Err(Exception::EnvironmentCallFromMMode) => {
+                    machine.trap(Exception::EnvironmentCallFromMMode)
+                }
+                Err(err) => break err,
+            }
+        };
+        assert_eq!(exception, Exception::IllegalInstruction(0));
+        assert_eq!(machine.xreg(Register::X12), 42);
+        assert_eq!(machine.csr(registers::MEPC), 0xc);
+        assert_eq!(machine.csr(registers::MCAUSE), 11);
+        assert_eq!(machine.pc(), 0x10);
+    }
+
+    #[test]
+    fn test_user_mode() {
+        let mut machine = Machine::<u32>::new(0x200);
+        let program = [
+            0x10000293, // li t0, 0x100
+            0x30529073, // csrw mtvec, t0
+            0x02000293, // li t0, 0x20
+            0x34129073, // csrw mepc, t0
+            0x00002337, // lui t1, 2
+            0x80030313, // addi t1, t1, -2048
+            0x30033073, // csrc mstatus, t1
+            0x30200073, // mret
+            0x00000073, // ecall
+            0x02a00613, // li a2, 42
+            0x300026f3, // csrr a3, mstatus
+        ];
+        let handler = [
+            0x341022f3, // csrr t0, mepc
+            0x00428293, // addi t0, t0, 4
+            0x34129073, // csrw mepc, t0
+            0x30200073, // mret
+        ];
+        for (i, instruction) in program.into_iter().enumerate() {
+            machine.write(mem::U32::new(instruction), i * 4).unwrap();
+        }
+        for (i, instruction) in handler.into_iter().enumerate() {
+            machine
+                .write(mem::U32::new(instruction), 0x100 + i * 4)
+                .unwrap();
+        }
+        let mut causes = vec![];
+        let exception = loop {
+            match machine.step() {
+                Ok(()) => {}
+                Err(Exception::IllegalInstruction(0)) => break Exception::IllegalInstruction(0),
+                Err(err) => {
+                    causes.push(err.code());
+                    machine.trap(err);
+                }
+            }
+        };
+        // The handler returns to U-mode, where mstatus cannot be read
+        assert_eq!(exception, Exception::IllegalInstruction(0));
+        assert_eq!(causes, [8, 2]);
+        assert_eq!(machine.hart().privilege, Privilege::User);
+        assert_eq!(machine.xreg(Register::X12), 42);
+        assert_eq!(machine.xreg(Register::X13), 0);
+        assert_eq!(machine.csr(registers::MTVAL), 0x300026f3);
+        assert_eq!(machine.pc(), 0x2c);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::error::Error;
+use crate::trap::Exception;
 
 #[allow(clippy::missing_safety_doc)]
 pub unsafe trait Pod: Copy {}
@@ -53,24 +53,40 @@ impl_pod! {
 }
 unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
 
-pub fn read<T: Pod>(src: &[u8], addr: usize) -> Result<T, Error> {
+/// Bytes reserved by a load-reserved instruction, until a store-conditional
+/// consumes the reservation or a store to them breaks it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Reservation {
+    pub addr: usize,
+    pub size: usize,
+}
+
+impl Reservation {
+    /// Whether an access of `size` bytes at `addr` touches the reserved bytes.
+    #[inline(always)]
+    pub const fn overlaps(&self, addr: usize, size: usize) -> bool {
+        addr < self.addr.saturating_add(self.size) && self.addr < addr.saturating_add(size)
+    }
+}
+
+pub fn read<T: Pod>(src: &[u8], addr: usize) -> Result<T, Exception> {
     Ok(unsafe {
         core::ptr::read_unaligned(
             src.get(addr..)
                 .and_then(|src| src.get(..core::mem::size_of::<T>()))
-                .ok_or(Error::InvalidOpCode)?
+                .ok_or(Exception::LoadAccessFault(addr as u64))?
                 .as_ptr()
                 .cast(),
         )
     })
 }
 
-pub fn write<T: Pod>(src: &T, dest: &mut [u8], addr: usize) -> Result<(), Error> {
+pub fn write<T: Pod>(src: &T, dest: &mut [u8], addr: usize) -> Result<(), Exception> {
     unsafe {
         core::ptr::write_unaligned(
             dest.get_mut(addr..)
                 .and_then(|dst| dst.get_mut(..core::mem::size_of::<T>()))
-                .ok_or(Error::InvalidOpCode)?
+                .ok_or(Exception::StoreAccessFault(addr as u64))?
                 .as_mut_ptr()
                 .cast(),
             *src,
@@ -79,42 +95,41 @@ pub fn write<T: Pod>(src: &T, dest: &mut [u8], addr: usize) -> Result<(), Error>
     Ok(())
 }
 
-pub fn memw(src: &[u8], dest: &mut [u8], addr: usize) -> Result<(), Error> {
+pub fn memw(src: &[u8], dest: &mut [u8], addr: usize) -> Result<(), Exception> {
     let dest = dest
         .get_mut(addr..)
-        .and_then(|src| src.get_mut(..src.len()))
-        .ok_or(Error::InvalidOpCode)?
+        .and_then(|dest| dest.get_mut(..src.len()))
+        .ok_or(Exception::StoreAccessFault(addr as u64))?
         .as_mut_ptr();
     unsafe { core::ptr::copy_nonoverlapping(src.as_ptr(), dest, src.len()) };
     Ok(())
 }
 
 #[allow(dead_code)]
-pub fn memr<const N: usize>(dest: &mut [u8], src: &[u8], addr: usize) -> Result<(), Error> {
+pub fn memr<const N: usize>(dest: &mut [u8], src: &[u8], addr: usize) -> Result<(), Exception> {
     let src = src
         .get(addr..)
         .and_then(|src| src.get(..dest.len()))
-        .ok_or(Error::InvalidOpCode)?
+        .ok_or(Exception::LoadAccessFault(addr as u64))?
         .as_ptr();
     unsafe { core::ptr::copy_nonoverlapping(src, dest.as_mut_ptr(), dest.len()) };
     Ok(())
 }
 
 #[inline(always)]
-pub fn memr32(src: &[u8], addr: usize) -> Result<[u8; 4], Error> {
+pub fn memr32(src: &[u8], addr: usize) -> Result<[u8; 4], Exception> {
     read::<[u8; 4]>(src, addr)
 }
 
-#[allow(dead_code)]
 #[inline(always)]
-pub fn memr16(src: &[u8], addr: usize) -> Result<[u8; 2], Error> {
+pub fn memr16(src: &[u8], addr: usize) -> Result<[u8; 2], Exception> {
     read::<[u8; 2]>(src, addr)
 }
 
 #[allow(dead_code)]
 #[inline(always)]
-pub fn memr8(src: &[u8], addr: usize) -> Result<u8, Error> {
-    read::<[u8; 1]>(src, addr).map(|n| unsafe { core::mem::transmute(n) })
+pub fn memr8(src: &[u8], addr: usize) -> Result<u8, Exception> {
+    read::<u8>(src, addr)
 }
 
 #[cfg(test)]
@@ -129,6 +144,17 @@ mod tests {
         assert_eq!(data.as_bytes(), &memory[0..data.len()])
     }
 
+    #[test]
+    fn test_memw_out_of_bounds() {
+        let mut memory = [0u8; 8];
+        let data = "hello world!";
+        assert_eq!(
+            memw(data.as_bytes(), &mut memory, 0x0),
+            Err(Exception::StoreAccessFault(0x0))
+        );
+        assert_eq!(memory, [0u8; 8]);
+    }
+
     #[test]
     fn test_memr32() {
         let mut memory = [0u8; 1024];
@@ -155,4 +181,14 @@ mod tests {
         let read = memr8(&memory, 0x0).unwrap();
         assert_eq!(read, b'h');
     }
+
+    #[test]
+    fn test_reservation_overlaps() {
+        let reservation = Reservation { addr: 8, size: 4 };
+        assert!(reservation.overlaps(8, 4));
+        assert!(reservation.overlaps(11, 1));
+        assert!(reservation.overlaps(4, 8));
+        assert!(!reservation.overlaps(12, 8));
+        assert!(!reservation.overlaps(4, 4));
+    }
 }
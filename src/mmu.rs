@@ -0,0 +1,827 @@
+//! Virtual memory: the Sv32, Sv39, Sv48 and Sv57 page-based translation
+//! schemes `satp` selects, and the TLB caching their translations.
+
+use crate::isa::{Extensions, Privilege, Xlen};
+use crate::mem::{self, Pod, U32, U64};
+use crate::registers::{MSTATUS_MXR, MSTATUS_SUM};
+use crate::trap::Exception;
+
+const PAGE_SHIFT: u32 = 12;
+const PAGE_MASK: u64 = (1 << PAGE_SHIFT) - 1;
+
+/// PTE valid bit.
+pub const PTE_V: u64 = 1 << 0;
+/// PTE readable bit.
+pub const PTE_R: u64 = 1 << 1;
+/// PTE writable bit.
+pub const PTE_W: u64 = 1 << 2;
+/// PTE executable bit.
+pub const PTE_X: u64 = 1 << 3;
+/// PTE bit of pages accessible to U-mode.
+pub const PTE_U: u64 = 1 << 4;
+/// PTE bit of mappings present in all address spaces.
+pub const PTE_G: u64 = 1 << 5;
+/// PTE accessed bit.
+pub const PTE_A: u64 = 1 << 6;
+/// PTE dirty bit.
+pub const PTE_D: u64 = 1 << 7;
+/// PTE bit of Svnapot 64 KiB pages, on Sv39 and up.
+pub const PTE_N: u64 = 1 << 63;
+/// PTE bits of Svpbmt and those reserved for future use, on Sv39 and up.
+const PTE_RESERVED: u64 = 0x1ff << 54;
+/// The `ppn[3:0]` of Svnapot 64 KiB pages.
+const NAPOT_64K: u64 = 0b1000;
+
+/// The kind of memory access being translated, which decides the
+/// permission checked and the exception raised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Access {
+    Fetch,
+    Load,
+    Store,
+}
+
+impl Access {
+    #[inline(always)]
+    const fn page_fault(self, addr: u64) -> Exception {
+        match self {
+            Self::Fetch => Exception::InstructionPageFault(addr),
+            Self::Load => Exception::LoadPageFault(addr),
+            Self::Store => Exception::StorePageFault(addr),
+        }
+    }
+
+    #[inline(always)]
+    const fn access_fault(self, addr: u64) -> Exception {
+        match self {
+            Self::Fetch => Exception::InstructionAccessFault(addr),
+            Self::Load => Exception::LoadAccessFault(addr),
+            Self::Store => Exception::StoreAccessFault(addr),
+        }
+    }
+
+    #[inline(always)]
+    const fn misaligned(self, addr: u64) -> Exception {
+        match self {
+            Self::Fetch => Exception::InstructionAddressMisaligned(addr),
+            Self::Load => Exception::LoadAddressMisaligned(addr),
+            Self::Store => Exception::StoreAddressMisaligned(addr),
+        }
+    }
+}
+
+/// Translation scheme, as selected by `satp.MODE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Bare,
+    Sv32,
+    Sv39,
+    Sv48,
+    Sv57,
+}
+
+impl Mode {
+    /// The scheme `satp` selects, `None` for the reserved and custom ones.
+    #[inline(always)]
+    pub const fn of(satp: u64, xlen: Xlen) -> Option<Self> {
+        match xlen {
+            Xlen::Rv32 if satp >> 31 & 1 == 0 => Some(Self::Bare),
+            Xlen::Rv32 => Some(Self::Sv32),
+            Xlen::Rv64 => match satp >> 60 {
+                0 => Some(Self::Bare),
+                8 => Some(Self::Sv39),
+                9 => Some(Self::Sv48),
+                10 => Some(Self::Sv57),
+                _ => None,
+            },
+        }
+    }
+
+    #[inline(always)]
+    const fn levels(self) -> u32 {
+        match self {
+            Self::Bare => 0,
+            Self::Sv32 => 2,
+            Self::Sv39 => 3,
+            Self::Sv48 => 4,
+            Self::Sv57 => 5,
+        }
+    }
+
+    /// Bits of the virtual page number each level translates.
+    #[inline(always)]
+    const fn vpn_bits(self) -> u32 {
+        match self {
+            Self::Sv32 => 10,
+            _ => 9,
+        }
+    }
+
+    #[inline(always)]
+    const fn pte_size(self) -> u64 {
+        match self {
+            Self::Sv32 => 4,
+            _ => 8,
+        }
+    }
+
+    #[inline(always)]
+    const fn ppn(self, pte: u64) -> u64 {
+        match self {
+            Self::Sv32 => pte >> 10 & 0x3f_ffff,
+            _ => pte >> 10 & 0xfff_ffff_ffff,
+        }
+    }
+}
+
+/// The hart state translation depends on: `satp`, the privilege modes of
+/// fetches and of loads and stores, and the `mstatus` fields.
+#[derive(Debug, Clone, Copy)]
+pub struct Context {
+    pub mode: Mode,
+    /// Physical page number of the root page table.
+    pub root: u64,
+    pub asid: u16,
+    /// Privilege mode instructions are fetched with.
+    pub privilege: Privilege,
+    /// Privilege mode loads and stores access memory with, as `mstatus.MPRV`
+    /// may change it.
+    pub data_privilege: Privilege,
+    /// `mstatus.SUM`, S-mode loads and stores may access user pages.
+    pub sum: bool,
+    /// `mstatus.MXR`, loads may read executable pages.
+    pub mxr: bool,
+    pub svnapot: bool,
+    /// Svadu, the A and D bits are updated rather than raising page faults.
+    pub svadu: bool,
+}
+
+impl Context {
+    #[inline(always)]
+    pub fn new(
+        satp: u64,
+        xlen: Xlen,
+        privilege: Privilege,
+        data_privilege: Privilege,
+        mstatus: u64,
+        extensions: &Extensions,
+    ) -> Self {
+        let (root, asid) = match xlen {
+            Xlen::Rv32 => (satp & 0x3f_ffff, satp >> 22 & 0x1ff),
+            Xlen::Rv64 => (satp & 0xfff_ffff_ffff, satp >> 44 & 0xffff),
+        };
+        Self {
+            mode: Mode::of(satp, xlen).unwrap_or(Mode::Bare),
+            root,
+            asid: asid as u16,
+            privilege,
+            data_privilege,
+            sum: mstatus & MSTATUS_SUM != 0,
+            mxr: mstatus & MSTATUS_MXR != 0,
+            svnapot: extensions.svnapot,
+            svadu: extensions.svadu,
+        }
+    }
+
+    /// Whether a leaf `pte` allows `access` in this context.
+    #[inline(always)]
+    fn permits(&self, pte: u64, access: Access) -> bool {
+        let allowed = match access {
+            Access::Fetch => pte & PTE_X != 0,
+            Access::Load => pte & PTE_R != 0 || self.mxr && pte & PTE_X != 0,
+            Access::Store => pte & PTE_W != 0,
+        };
+        let privilege = match access {
+            Access::Fetch => self.privilege,
+            _ => self.data_privilege,
+        };
+        // S-mode never executes user pages, and loads and stores them only
+        // under SUM
+        let user = pte & PTE_U != 0;
+        allowed
+            && match privilege {
+                Privilege::User => user,
+                Privilege::Supervisor => !user || self.sum && access != Access::Fetch,
+                Privilege::Machine => true,
+            }
+    }
+}
+
+const TLB_ENTRIES: usize = 64;
+
+/// A cached translation of a 4 KiB virtual page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Entry {
+    vpn: u64,
+    asid: u16,
+    ppn: u64,
+    /// The leaf PTE, whose permissions are checked on every hit.
+    pte: u64,
+    /// Log2 of the pages in the superpage or Svnapot page the PTE maps, for
+    /// `sfence.vma` to drop all of it.
+    shift: u32,
+}
+
+/// Direct-mapped cache of the translations of recently accessed pages,
+/// indexed by virtual page number.
+#[derive(Debug, Clone)]
+pub struct Tlb {
+    entries: [Option<Entry>; TLB_ENTRIES],
+}
+
+impl Default for Tlb {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            entries: [None; TLB_ENTRIES],
+        }
+    }
+}
+
+impl Tlb {
+    #[inline(always)]
+    fn lookup(&self, vpn: u64, asid: u16) -> Option<Entry> {
+        self.entries[vpn as usize % TLB_ENTRIES]
+            .filter(|entry| entry.vpn == vpn && (entry.asid == asid || entry.pte & PTE_G != 0))
+    }
+
+    #[inline(always)]
+    fn insert(&mut self, entry: Entry) {
+        self.entries[entry.vpn as usize % TLB_ENTRIES] = Some(entry);
+    }
+
+    /// Drops the translations `sfence.vma` orders: those of the page holding
+    /// `vaddr` if given, and those of the `asid` address space if given,
+    /// global mappings excepted.
+    pub fn flush(&mut self, vaddr: Option<u64>, asid: Option<u16>) {
+        for slot in &mut self.entries {
+            let Some(entry) = slot else {
+                continue;
+            };
+            let page = vaddr
+                .is_none_or(|vaddr| vaddr >> PAGE_SHIFT >> entry.shift == entry.vpn >> entry.shift);
+            let space = asid.is_none_or(|asid| entry.asid == asid && entry.pte & PTE_G == 0);
+            if page && space {
+                *slot = None;
+            }
+        }
+    }
+}
+
+/// Memory as instructions see it, their addresses translated through the
+/// TLB and the page tables of `satp`.
+#[derive(Debug)]
+pub struct Bus<'a> {
+    memory: &'a mut [u8],
+    tlb: &'a mut Tlb,
+    context: Context,
+}
+
+impl<'a> Bus<'a> {
+    #[inline(always)]
+    pub fn new(memory: &'a mut [u8], tlb: &'a mut Tlb, context: Context) -> Self {
+        Self {
+            memory,
+            tlb,
+            context,
+        }
+    }
+
+    /// The TLB, for `sfence.vma` to flush.
+    #[inline(always)]
+    pub fn tlb(&mut self) -> &mut Tlb {
+        self.tlb
+    }
+
+    /// Loads a `T` from the virtual address `addr`.
+    #[inline(always)]
+    pub fn read<T: Pod>(&mut self, addr: usize) -> Result<T, Exception> {
+        let paddr = self.translate(addr, size_of::<T>(), Access::Load)?;
+        mem::read(self.memory, paddr).map_err(|_| Exception::LoadAccessFault(addr as u64))
+    }
+
+    /// Stores `value` to the virtual address `addr`.
+    #[inline(always)]
+    pub fn write<T: Pod>(&mut self, value: &T, addr: usize) -> Result<(), Exception> {
+        let paddr = self.translate(addr, size_of::<T>(), Access::Store)?;
+        mem::write(value, self.memory, paddr).map_err(|_| Exception::StoreAccessFault(addr as u64))
+    }
+
+    /// Fetches the 16-bit instruction parcel at the virtual address `addr`.
+    #[inline(always)]
+    pub fn fetch(&mut self, addr: usize) -> Result<u16, Exception> {
+        self.fetch_word::<[u8; 2]>(addr).map(u16::from_le_bytes)
+    }
+
+    /// Reads a `T` from the virtual address `addr` as an instruction fetch,
+    /// as Zcmt reads its jump table entries.
+    #[inline(always)]
+    pub fn fetch_word<T: Pod>(&mut self, addr: usize) -> Result<T, Exception> {
+        let paddr = self.translate(addr, size_of::<T>(), Access::Fetch)?;
+        mem::read(self.memory, paddr).map_err(|_| Exception::InstructionAccessFault(addr as u64))
+    }
+
+    /// The physical address of the `size` bytes at `addr`.
+    ///
+    /// Misaligned accesses spanning two pages must find them physically
+    /// contiguous, and raise address-misaligned exceptions otherwise.
+    pub fn translate(
+        &mut self,
+        addr: usize,
+        size: usize,
+        access: Access,
+    ) -> Result<usize, Exception> {
+        let privilege = match access {
+            Access::Fetch => self.context.privilege,
+            _ => self.context.data_privilege,
+        };
+        if self.context.mode == Mode::Bare || privilege == Privilege::Machine {
+            return Ok(addr);
+        }
+        let paddr = self.translate_page(addr as u64, access)?;
+        let last = (addr as u64).wrapping_add(size.max(1) as u64 - 1);
+        if (addr as u64 ^ last) >> PAGE_SHIFT != 0 {
+            let boundary = last & !PAGE_MASK;
+            let next = self.translate_page(boundary, access)?;
+            if next != paddr.wrapping_add(boundary.wrapping_sub(addr as u64)) {
+                return Err(access.misaligned(addr as u64));
+            }
+        }
+        Ok(paddr as usize)
+    }
+
+    fn translate_page(&mut self, vaddr: u64, access: Access) -> Result<u64, Exception> {
+        let vpn = vaddr >> PAGE_SHIFT;
+        let hit = self
+            .tlb
+            .lookup(vpn, self.context.asid)
+            .filter(|entry| self.context.permits(entry.pte, access))
+            .filter(|entry| access != Access::Store || entry.pte & PTE_D != 0);
+        let entry = match hit {
+            Some(entry) => entry,
+            None => {
+                let entry = self.walk(vaddr, access)?;
+                self.tlb.insert(entry);
+                entry
+            }
+        };
+        Ok(entry.ppn << PAGE_SHIFT | vaddr & PAGE_MASK)
+    }
+
+    /// Walks the page tables for `vaddr`, setting the A and D bits the
+    /// access needs under Svadu.
+    fn walk(&mut self, vaddr: u64, access: Access) -> Result<Entry, Exception> {
+        let Context { mode, .. } = self.context;
+        let fault = access.page_fault(vaddr);
+        let bits = mode.vpn_bits();
+        let levels = mode.levels();
+        // The bits above the virtual address must copy its top bit
+        if mode != Mode::Sv32 {
+            let unused = 64 - (PAGE_SHIFT + levels * bits);
+            if ((vaddr << unused) as i64 >> unused) as u64 != vaddr {
+                return Err(fault);
+            }
+        }
+        let vpn = vaddr >> PAGE_SHIFT;
+        let mut table = self.context.root << PAGE_SHIFT;
+        for level in (0..levels).rev() {
+            let index = vpn >> (level * bits) & ((1 << bits) - 1);
+            let pte_addr = table.wrapping_add(index * mode.pte_size()) as usize;
+            let pte = self
+                .read_pte(pte_addr, mode)
+                .ok_or(access.access_fault(vaddr))?;
+            let napot = pte & PTE_N != 0;
+            if pte & PTE_V == 0
+                || pte & (PTE_R | PTE_W) == PTE_W
+                || pte & PTE_RESERVED != 0
+                || napot && !self.context.svnapot
+            {
+                return Err(fault);
+            }
+            let ppn = mode.ppn(pte);
+            if pte & (PTE_R | PTE_X) == 0 {
+                if napot {
+                    return Err(fault);
+                }
+                table = ppn << PAGE_SHIFT;
+                continue;
+            }
+            let shift = level * bits;
+            let low = (1 << shift) - 1;
+            // Superpages must be aligned, Svnapot only has 64 KiB pages
+            if ppn & low != 0 || napot && (level != 0 || ppn & 0xf != NAPOT_64K) {
+                return Err(fault);
+            }
+            if !self.context.permits(pte, access) {
+                return Err(fault);
+            }
+            let mut pte = pte;
+            let needed = match access {
+                Access::Store => PTE_A | PTE_D,
+                _ => PTE_A,
+            };
+            if pte & needed != needed {
+                if !self.context.svadu {
+                    return Err(fault);
+                }
+                pte |= needed;
+                self.write_pte(pte_addr, mode, pte)
+                    .ok_or(access.access_fault(vaddr))?;
+            }
+            let (shift, low) = if napot { (4, 0xf) } else { (shift, low) };
+            return Ok(Entry {
+                vpn,
+                asid: self.context.asid,
+                ppn: ppn & !low | vpn & low,
+                pte,
+                shift,
+            });
+        }
+        Err(fault)
+    }
+
+    #[inline(always)]
+    fn read_pte(&self, addr: usize, mode: Mode) -> Option<u64> {
+        match mode {
+            Mode::Sv32 => mem::read::<U32>(self.memory, addr)
+                .map(|pte| pte.as_u32() as u64)
+                .ok(),
+            _ => mem::read::<U64>(self.memory, addr)
+                .map(|pte| pte.as_u64())
+                .ok(),
+        }
+    }
+
+    #[inline(always)]
+    fn write_pte(&mut self, addr: usize, mode: Mode, pte: u64) -> Option<()> {
+        match mode {
+            Mode::Sv32 => mem::write(&U32::new(pte as u32), self.memory, addr).ok(),
+            _ => mem::write(&U64::new(pte), self.memory, addr).ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: u64 = PTE_V | PTE_A | PTE_D;
+
+    fn context(mode: Mode, privilege: Privilege) -> Context {
+        Context {
+            mode,
+            root: 1,
+            asid: 0,
+            privilege,
+            data_privilege: privilege,
+            sum: false,
+            mxr: false,
+            svnapot: true,
+            svadu: false,
+        }
+    }
+
+    /// Writes `pte` at `level` of the tables translating `va`, taking the
+    /// missing tables from `next` on.
+    fn map(memory: &mut [u8], mode: Mode, va: u64, level: u32, pte: u64, next: &mut u64) {
+        let bits = mode.vpn_bits();
+        let mut table = 1;
+        for i in (level..mode.levels()).rev() {
+            let index = va >> (PAGE_SHIFT + i * bits) & ((1 << bits) - 1);
+            let addr = ((table << PAGE_SHIFT) + index * mode.pte_size()) as usize;
+            let pte = if i == level {
+                pte
+            } else {
+                let pte = Bus::new(
+                    memory,
+                    &mut Tlb::default(),
+                    context(mode, Privilege::Machine),
+                )
+                .read_pte(addr, mode)
+                .unwrap();
+                if pte & PTE_V != 0 {
+                    table = mode.ppn(pte);
+                    continue;
+                }
+                table = *next;
+                *next += 1;
+                table << 10 | PTE_V
+            };
+            Bus::new(
+                memory,
+                &mut Tlb::default(),
+                context(mode, Privilege::Machine),
+            )
+            .write_pte(addr, mode, pte)
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_sv39_permissions() {
+        let mut memory = vec![0u8; 0x10000];
+        let mut next = 2;
+        let mode = Mode::Sv39;
+        map(
+            &mut memory,
+            mode,
+            0x5000,
+            0,
+            0xa << 10 | VALID | PTE_R | PTE_W,
+            &mut next,
+        );
+        map(
+            &mut memory,
+            mode,
+            0x6000,
+            0,
+            0xb << 10 | VALID | PTE_R,
+            &mut next,
+        );
+        map(
+            &mut memory,
+            mode,
+            0x7000,
+            0,
+            0xc << 10 | VALID | PTE_X,
+            &mut next,
+        );
+        let user = 0xd << 10 | VALID | PTE_R | PTE_X | PTE_U;
+        map(&mut memory, mode, 0x8000, 0, user, &mut next);
+        let mut tlb = Tlb::default();
+        let mut bus = Bus::new(&mut memory, &mut tlb, context(mode, Privilege::Supervisor));
+        bus.write(&U32::new(0xdeadbeef), 0x5004).unwrap();
+        assert_eq!(bus.read::<U32>(0x5004).unwrap().as_u32(), 0xdeadbeef);
+        assert_eq!(bus.translate(0x5004, 4, Access::Load), Ok(0xa004));
+        assert_eq!(
+            bus.read::<u8>(0x9000),
+            Err(Exception::LoadPageFault(0x9000))
+        );
+        assert_eq!(
+            bus.write(&0u8, 0x6000),
+            Err(Exception::StorePageFault(0x6000))
+        );
+        assert_eq!(
+            bus.fetch(0x6000),
+            Err(Exception::InstructionPageFault(0x6000))
+        );
+        assert_eq!(bus.fetch(0x7000), Ok(0));
+        // Execute-only pages are readable under MXR
+        assert_eq!(
+            bus.read::<u8>(0x7000),
+            Err(Exception::LoadPageFault(0x7000))
+        );
+        bus.context.mxr = true;
+        assert_eq!(bus.read::<u8>(0x7000), Ok(0));
+        // S-mode reads user pages under SUM, but never executes them
+        assert_eq!(
+            bus.read::<u8>(0x8000),
+            Err(Exception::LoadPageFault(0x8000))
+        );
+        bus.context.sum = true;
+        assert_eq!(bus.read::<u8>(0x8000), Ok(0));
+        assert_eq!(
+            bus.fetch(0x8000),
+            Err(Exception::InstructionPageFault(0x8000))
+        );
+        bus.context.privilege = Privilege::User;
+        bus.context.data_privilege = Privilege::User;
+        assert_eq!(bus.fetch(0x8000), Ok(0));
+        assert_eq!(
+            bus.read::<u8>(0x6000),
+            Err(Exception::LoadPageFault(0x6000))
+        );
+        // Addresses whose upper bits do not copy bit 38 fault
+        assert_eq!(
+            bus.read::<u8>(0x40_0000_0000),
+            Err(Exception::LoadPageFault(0x40_0000_0000))
+        );
+        // M-mode does not translate
+        bus.context.data_privilege = Privilege::Machine;
+        assert_eq!(bus.translate(0x9000, 1, Access::Load), Ok(0x9000));
+    }
+
+    #[test]
+    fn test_levels() {
+        for mode in [Mode::Sv32, Mode::Sv39, Mode::Sv48, Mode::Sv57] {
+            let mut memory = vec![0u8; 0x10000];
+            let mut next = 2;
+            let va = 0x1234_5000 & ((1 << (PAGE_SHIFT + mode.levels() * mode.vpn_bits())) - 1);
+            map(
+                &mut memory,
+                mode,
+                va,
+                0,
+                0xf << 10 | VALID | PTE_R,
+                &mut next,
+            );
+            let mut tlb = Tlb::default();
+            let mut bus = Bus::new(&mut memory, &mut tlb, context(mode, Privilege::Supervisor));
+            assert_eq!(bus.translate(va as usize + 8, 1, Access::Load), Ok(0xf008));
+            assert_eq!(next as u32, 1 + mode.levels());
+        }
+        assert_eq!(Mode::of(1 << 31, Xlen::Rv32), Some(Mode::Sv32));
+        assert_eq!(Mode::of(9 << 60, Xlen::Rv64), Some(Mode::Sv48));
+        assert_eq!(Mode::of(1 << 60, Xlen::Rv64), None);
+    }
+
+    #[test]
+    fn test_superpages() {
+        let mut memory = vec![0u8; 0x10000];
+        let mut next = 2;
+        let mode = Mode::Sv39;
+        // A 2 MiB page at 0x200000, and one whose PPN is misaligned
+        map(&mut memory, mode, 0x20_0000, 1, VALID | PTE_R, &mut next);
+        map(
+            &mut memory,
+            mode,
+            0x40_0000,
+            1,
+            1 << 10 | VALID | PTE_R,
+            &mut next,
+        );
+        let mut tlb = Tlb::default();
+        let mut bus = Bus::new(&mut memory, &mut tlb, context(mode, Privilege::Supervisor));
+        assert_eq!(bus.translate(0x20_4321, 1, Access::Load), Ok(0x4321));
+        assert_eq!(
+            bus.translate(0x40_0000, 1, Access::Load),
+            Err(Exception::LoadPageFault(0x40_0000))
+        );
+        // A 4 MiB page on Sv32
+        let mut memory = vec![0u8; 0x10000];
+        let mut next = 2;
+        let mode = Mode::Sv32;
+        map(&mut memory, mode, 0x40_0000, 1, VALID | PTE_R, &mut next);
+        let mut bus = Bus::new(&mut memory, &mut tlb, context(mode, Privilege::Supervisor));
+        assert_eq!(bus.translate(0x40_4321, 1, Access::Load), Ok(0x4321));
+    }
+
+    #[test]
+    fn test_svnapot() {
+        let mut memory = vec![0u8; 0x10000];
+        let mut next = 2;
+        let mode = Mode::Sv39;
+        // A 64 KiB page at 0x10000 mapping the first 64 KiB
+        let napot = PTE_N | NAPOT_64K << 10 | VALID | PTE_R;
+        map(&mut memory, mode, 0x13000, 0, napot, &mut next);
+        map(
+            &mut memory,
+            mode,
+            0x24000,
+            0,
+            PTE_N | VALID | PTE_R,
+            &mut next,
+        );
+        let mut tlb = Tlb::default();
+        let mut bus = Bus::new(&mut memory, &mut tlb, context(mode, Privilege::Supervisor));
+        assert_eq!(bus.translate(0x13008, 1, Access::Load), Ok(0x3008));
+        // Other encodings of ppn[3:0] are reserved
+        assert_eq!(
+            bus.translate(0x24000, 1, Access::Load),
+            Err(Exception::LoadPageFault(0x24000))
+        );
+        bus.context.svnapot = false;
+        assert_eq!(
+            bus.translate(0x13000, 1, Access::Store),
+            Err(Exception::StorePageFault(0x13000))
+        );
+    }
+
+    #[test]
+    fn test_svadu() {
+        let mut memory = vec![0u8; 0x10000];
+        let mut next = 2;
+        let mode = Mode::Sv39;
+        map(
+            &mut memory,
+            mode,
+            0x5000,
+            0,
+            0xa << 10 | PTE_V | PTE_R | PTE_W,
+            &mut next,
+        );
+        let leaf = 0x3000 + 5 * 8;
+        let mut tlb = Tlb::default();
+        let mut context = context(mode, Privilege::Supervisor);
+        let mut bus = Bus::new(&mut memory, &mut tlb, context);
+        assert_eq!(
+            bus.read::<u8>(0x5000),
+            Err(Exception::LoadPageFault(0x5000))
+        );
+        context.svadu = true;
+        let mut bus = Bus::new(&mut memory, &mut tlb, context);
+        assert_eq!(bus.read::<u8>(0x5000), Ok(0));
+        let pte = bus.read_pte(leaf, mode).unwrap();
+        assert_eq!(pte & (PTE_A | PTE_D), PTE_A);
+        // The cached translation is walked again to set D
+        bus.write(&0u8, 0x5000).unwrap();
+        let pte = bus.read_pte(leaf, mode).unwrap();
+        assert_eq!(pte & (PTE_A | PTE_D), PTE_A | PTE_D);
+    }
+
+    #[test]
+    fn test_tlb_flush() {
+        let mut memory = vec![0u8; 0x10000];
+        let mut next = 2;
+        let mode = Mode::Sv39;
+        map(
+            &mut memory,
+            mode,
+            0x5000,
+            0,
+            0xa << 10 | VALID | PTE_R,
+            &mut next,
+        );
+        map(
+            &mut memory,
+            mode,
+            0x6000,
+            0,
+            0xb << 10 | VALID | PTE_R | PTE_G,
+            &mut next,
+        );
+        let mut tlb = Tlb::default();
+        let context = context(mode, Privilege::Supervisor);
+        let mut bus = Bus::new(&mut memory, &mut tlb, context);
+        assert_eq!(bus.translate(0x5000, 1, Access::Load), Ok(0xa000));
+        assert_eq!(bus.translate(0x6000, 1, Access::Load), Ok(0xb000));
+        // Remapping both pages goes unnoticed until the TLB is flushed
+        map(
+            &mut memory,
+            mode,
+            0x5000,
+            0,
+            0xc << 10 | VALID | PTE_R,
+            &mut next,
+        );
+        map(
+            &mut memory,
+            mode,
+            0x6000,
+            0,
+            0xd << 10 | VALID | PTE_R | PTE_G,
+            &mut next,
+        );
+        let mut bus = Bus::new(&mut memory, &mut tlb, context);
+        assert_eq!(bus.translate(0x5000, 1, Access::Load), Ok(0xa000));
+        // Flushing an address space keeps the global mappings
+        bus.tlb().flush(None, Some(0));
+        assert_eq!(bus.translate(0x5000, 1, Access::Load), Ok(0xc000));
+        assert_eq!(bus.translate(0x6000, 1, Access::Load), Ok(0xb000));
+        bus.tlb().flush(Some(0x6abc), None);
+        assert_eq!(bus.translate(0x6000, 1, Access::Load), Ok(0xd000));
+        // Other address spaces miss
+        let mut bus = Bus::new(&mut memory, &mut tlb, Context { asid: 1, ..context });
+        map(
+            bus.memory,
+            mode,
+            0x5000,
+            0,
+            0xe << 10 | VALID | PTE_R,
+            &mut next,
+        );
+        assert_eq!(bus.translate(0x5000, 1, Access::Load), Ok(0xe000));
+    }
+
+    #[test]
+    fn test_page_crossing() {
+        let mut memory = vec![0u8; 0x10000];
+        let mut next = 2;
+        let mode = Mode::Sv39;
+        map(
+            &mut memory,
+            mode,
+            0x5000,
+            0,
+            0xa << 10 | VALID | PTE_R,
+            &mut next,
+        );
+        map(
+            &mut memory,
+            mode,
+            0x6000,
+            0,
+            0xb << 10 | VALID | PTE_R,
+            &mut next,
+        );
+        map(
+            &mut memory,
+            mode,
+            0x7000,
+            0,
+            0xd << 10 | VALID | PTE_R,
+            &mut next,
+        );
+        let mut tlb = Tlb::default();
+        let mut bus = Bus::new(&mut memory, &mut tlb, context(mode, Privilege::Supervisor));
+        assert_eq!(bus.translate(0x5ffe, 4, Access::Load), Ok(0xaffe));
+        assert_eq!(
+            bus.translate(0x6ffe, 4, Access::Load),
+            Err(Exception::LoadAddressMisaligned(0x6ffe))
+        );
+        assert_eq!(
+            bus.translate(0x7ffe, 4, Access::Load),
+            Err(Exception::LoadPageFault(0x8000))
+        );
+    }
+}
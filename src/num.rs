@@ -153,7 +153,7 @@ macro_rules! impl_uint {
         impl Bitcast<$st> for $ut {
             #[inline(always)]
             fn bitcast(self) -> $st {
-                unsafe { core::mem::transmute(self) }
+                self as $st
             }
         }
     };
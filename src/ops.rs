@@ -1,9 +1,13 @@
 mod branch;
+mod crypto;
 mod csr;
 mod math;
 mod mem;
+mod vector;
 
 pub use branch::*;
+pub use crypto::*;
 pub use csr::*;
 pub use math::*;
 pub use mem::*;
+pub use vector::*;
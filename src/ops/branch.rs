@@ -24,7 +24,6 @@ pub trait Bgeu {
     fn bgeu(self, other: Self) -> bool;
 }
 
-#[allow(unused)]
 pub trait BaseBranch: Beq + Bne + Blt + Bge + Bltu + Bgeu {}
 
 impl<T> Beq for T
@@ -90,3 +89,5 @@ where
         self >= other
     }
 }
+
+impl<T: Beq + Bne + Blt + Bge + Bltu + Bgeu> BaseBranch for T {}
@@ -0,0 +1,671 @@
+use crate::decode::{U12, U2};
+use crate::num::As;
+
+// Zkne and Zknd, AES encryption and decryption rounds
+
+pub trait Aes32esi {
+    fn aes32esi(self, other: Self, bs: U2) -> Self;
+}
+
+pub trait Aes32esmi {
+    fn aes32esmi(self, other: Self, bs: U2) -> Self;
+}
+
+pub trait Aes32dsi {
+    fn aes32dsi(self, other: Self, bs: U2) -> Self;
+}
+
+pub trait Aes32dsmi {
+    fn aes32dsmi(self, other: Self, bs: U2) -> Self;
+}
+
+pub trait Aes64es {
+    fn aes64es(self, other: Self) -> Self;
+}
+
+pub trait Aes64esm {
+    fn aes64esm(self, other: Self) -> Self;
+}
+
+pub trait Aes64ds {
+    fn aes64ds(self, other: Self) -> Self;
+}
+
+pub trait Aes64dsm {
+    fn aes64dsm(self, other: Self) -> Self;
+}
+
+pub trait Aes64im {
+    fn aes64im(self) -> Self;
+}
+
+pub trait Aes64ks1i {
+    fn aes64ks1i(self, other: U12) -> Self;
+}
+
+pub trait Aes64ks2 {
+    fn aes64ks2(self, other: Self) -> Self;
+}
+
+// Zknh, the SHA-256 and SHA-512 sigma and sum functions. RV32 computes the
+// SHA-512 ones a 32-bit half at a time, from the matching halves in `rs1` and
+// the other halves in `rs2`.
+
+pub trait Sha256sig0 {
+    fn sha256sig0(self) -> Self;
+}
+
+pub trait Sha256sig1 {
+    fn sha256sig1(self) -> Self;
+}
+
+pub trait Sha256sum0 {
+    fn sha256sum0(self) -> Self;
+}
+
+pub trait Sha256sum1 {
+    fn sha256sum1(self) -> Self;
+}
+
+pub trait Sha512sig0h {
+    fn sha512sig0h(self, other: Self) -> Self;
+}
+
+pub trait Sha512sig0l {
+    fn sha512sig0l(self, other: Self) -> Self;
+}
+
+pub trait Sha512sig1h {
+    fn sha512sig1h(self, other: Self) -> Self;
+}
+
+pub trait Sha512sig1l {
+    fn sha512sig1l(self, other: Self) -> Self;
+}
+
+pub trait Sha512sum0r {
+    fn sha512sum0r(self, other: Self) -> Self;
+}
+
+pub trait Sha512sum1r {
+    fn sha512sum1r(self, other: Self) -> Self;
+}
+
+pub trait Sha512sig0 {
+    fn sha512sig0(self) -> Self;
+}
+
+pub trait Sha512sig1 {
+    fn sha512sig1(self) -> Self;
+}
+
+pub trait Sha512sum0 {
+    fn sha512sum0(self) -> Self;
+}
+
+pub trait Sha512sum1 {
+    fn sha512sum1(self) -> Self;
+}
+
+// Zksed and Zksh, SM4 rounds and the SM3 permutations
+
+pub trait Sm4ed {
+    fn sm4ed(self, other: Self, bs: U2) -> Self;
+}
+
+pub trait Sm4ks {
+    fn sm4ks(self, other: Self, bs: U2) -> Self;
+}
+
+pub trait Sm3p0 {
+    fn sm3p0(self) -> Self;
+}
+
+pub trait Sm3p1 {
+    fn sm3p1(self) -> Self;
+}
+
+pub trait BaseCrypto:
+    Aes32esi
+    + Aes32esmi
+    + Aes32dsi
+    + Aes32dsmi
+    + Aes64es
+    + Aes64esm
+    + Aes64ds
+    + Aes64dsm
+    + Aes64im
+    + Aes64ks1i
+    + Aes64ks2
+    + Sha256sig0
+    + Sha256sig1
+    + Sha256sum0
+    + Sha256sum1
+    + Sha512sig0h
+    + Sha512sig0l
+    + Sha512sig1h
+    + Sha512sig1l
+    + Sha512sum0r
+    + Sha512sum1r
+    + Sha512sig0
+    + Sha512sig1
+    + Sha512sum0
+    + Sha512sum1
+    + Sm4ed
+    + Sm4ks
+    + Sm3p0
+    + Sm3p1
+{
+}
+
+const AES_SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const AES_INV_SBOX: [u8; 256] = {
+    let mut inv = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        inv[AES_SBOX[i] as usize] = i as u8;
+        i += 1;
+    }
+    inv
+};
+
+const SM4_SBOX: [u8; 256] = [
+    0xd6, 0x90, 0xe9, 0xfe, 0xcc, 0xe1, 0x3d, 0xb7, 0x16, 0xb6, 0x14, 0xc2, 0x28, 0xfb, 0x2c, 0x05,
+    0x2b, 0x67, 0x9a, 0x76, 0x2a, 0xbe, 0x04, 0xc3, 0xaa, 0x44, 0x13, 0x26, 0x49, 0x86, 0x06, 0x99,
+    0x9c, 0x42, 0x50, 0xf4, 0x91, 0xef, 0x98, 0x7a, 0x33, 0x54, 0x0b, 0x43, 0xed, 0xcf, 0xac, 0x62,
+    0xe4, 0xb3, 0x1c, 0xa9, 0xc9, 0x08, 0xe8, 0x95, 0x80, 0xdf, 0x94, 0xfa, 0x75, 0x8f, 0x3f, 0xa6,
+    0x47, 0x07, 0xa7, 0xfc, 0xf3, 0x73, 0x17, 0xba, 0x83, 0x59, 0x3c, 0x19, 0xe6, 0x85, 0x4f, 0xa8,
+    0x68, 0x6b, 0x81, 0xb2, 0x71, 0x64, 0xda, 0x8b, 0xf8, 0xeb, 0x0f, 0x4b, 0x70, 0x56, 0x9d, 0x35,
+    0x1e, 0x24, 0x0e, 0x5e, 0x63, 0x58, 0xd1, 0xa2, 0x25, 0x22, 0x7c, 0x3b, 0x01, 0x21, 0x78, 0x87,
+    0xd4, 0x00, 0x46, 0x57, 0x9f, 0xd3, 0x27, 0x52, 0x4c, 0x36, 0x02, 0xe7, 0xa0, 0xc4, 0xc8, 0x9e,
+    0xea, 0xbf, 0x8a, 0xd2, 0x40, 0xc7, 0x38, 0xb5, 0xa3, 0xf7, 0xf2, 0xce, 0xf9, 0x61, 0x15, 0xa1,
+    0xe0, 0xae, 0x5d, 0xa4, 0x9b, 0x34, 0x1a, 0x55, 0xad, 0x93, 0x32, 0x30, 0xf5, 0x8c, 0xb1, 0xe3,
+    0x1d, 0xf6, 0xe2, 0x2e, 0x82, 0x66, 0xca, 0x60, 0xc0, 0x29, 0x23, 0xab, 0x0d, 0x53, 0x4e, 0x6f,
+    0xd5, 0xdb, 0x37, 0x45, 0xde, 0xfd, 0x8e, 0x2f, 0x03, 0xff, 0x6a, 0x72, 0x6d, 0x6c, 0x5b, 0x51,
+    0x8d, 0x1b, 0xaf, 0x92, 0xbb, 0xdd, 0xbc, 0x7f, 0x11, 0xd9, 0x5c, 0x41, 0x1f, 0x10, 0x5a, 0xd8,
+    0x0a, 0xc1, 0x31, 0x88, 0xa5, 0xcd, 0x7b, 0xbd, 0x2d, 0x74, 0xd0, 0x12, 0xb8, 0xe5, 0xb4, 0xb0,
+    0x89, 0x69, 0x97, 0x4a, 0x0c, 0x96, 0x77, 0x7e, 0x65, 0xb9, 0xf1, 0x09, 0xc5, 0x6e, 0xc6, 0x84,
+    0x18, 0xf0, 0x7d, 0xec, 0x3a, 0xdc, 0x4d, 0x20, 0x79, 0xee, 0x5f, 0x3e, 0xd7, 0xcb, 0x39, 0x48,
+];
+
+/// Round constants of the AES key schedule, indexed by `rnum`.
+const AES_RCON: [u8; 11] = [
+    0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36, 0x00,
+];
+
+/// Multiplication in GF(2^8) modulo the AES polynomial.
+#[inline(always)]
+const fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        a = (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 };
+        b >>= 1;
+    }
+    product
+}
+
+/// The MixColumns matrix applied to one column, `matrix` gives its first row.
+#[inline(always)]
+fn mix_column(column: u32, matrix: [u8; 4]) -> u32 {
+    let bytes = column.to_le_bytes();
+    let mut mixed = [0u8; 4];
+    for (row, byte) in mixed.iter_mut().enumerate() {
+        for (col, &value) in bytes.iter().enumerate() {
+            *byte ^= gf_mul(value, matrix[(col + 4 - row) % 4]);
+        }
+    }
+    u32::from_le_bytes(mixed)
+}
+
+const MIX: [u8; 4] = [2, 3, 1, 1];
+const INV_MIX: [u8; 4] = [14, 11, 13, 9];
+
+#[inline(always)]
+fn sub_bytes(value: u64, sbox: &[u8; 256]) -> u64 {
+    u64::from_le_bytes(value.to_le_bytes().map(|byte| sbox[byte as usize]))
+}
+
+#[inline(always)]
+fn mix_columns(value: u64, matrix: [u8; 4]) -> u64 {
+    mix_column(value as u32, matrix) as u64
+        | (mix_column((value >> 32) as u32, matrix) as u64) << 32
+}
+
+/// The low two columns of ShiftRows applied to the state `rs2:rs1`, whose
+/// bytes are in column-major order.
+#[inline(always)]
+fn shift_rows(rs1: u64, rs2: u64, inverse: bool) -> u64 {
+    let state = (rs2 as u128) << 64 | rs1 as u128;
+    let mut out = 0;
+    for col in 0..2 {
+        for row in 0..4 {
+            let from = if inverse { col + 4 - row } else { col + row } % 4;
+            let byte = (state >> ((from * 4 + row) * 8)) as u8;
+            out |= (byte as u64) << ((col * 4 + row) * 8);
+        }
+    }
+    out
+}
+
+/// One byte of `rs2` picked by `bs` and substituted.
+#[inline(always)]
+fn select_byte<T: As<u32>>(value: T, bs: U2, sbox: &[u8; 256]) -> u32 {
+    sbox[(value.r#as() >> (bs.as_u32() * 8)) as u8 as usize] as u32
+}
+
+// The 32-bit ops produce a word that RV64 sign extends.
+
+impl<T> Aes32esi for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn aes32esi(self, other: Self, bs: U2) -> Self {
+        let so = select_byte(other, bs, &AES_SBOX);
+        ((self.r#as() ^ so.rotate_left(bs.as_u32() * 8)) as i32).r#as()
+    }
+}
+
+impl<T> Aes32esmi for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn aes32esmi(self, other: Self, bs: U2) -> Self {
+        let so = select_byte(other, bs, &AES_SBOX);
+        let mixed = mix_column(so, MIX);
+        ((self.r#as() ^ mixed.rotate_left(bs.as_u32() * 8)) as i32).r#as()
+    }
+}
+
+impl<T> Aes32dsi for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn aes32dsi(self, other: Self, bs: U2) -> Self {
+        let so = select_byte(other, bs, &AES_INV_SBOX);
+        ((self.r#as() ^ so.rotate_left(bs.as_u32() * 8)) as i32).r#as()
+    }
+}
+
+impl<T> Aes32dsmi for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn aes32dsmi(self, other: Self, bs: U2) -> Self {
+        let so = select_byte(other, bs, &AES_INV_SBOX);
+        let mixed = mix_column(so, INV_MIX);
+        ((self.r#as() ^ mixed.rotate_left(bs.as_u32() * 8)) as i32).r#as()
+    }
+}
+
+impl<T> Aes64es for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn aes64es(self, other: Self) -> Self {
+        sub_bytes(shift_rows(self.r#as(), other.r#as(), false), &AES_SBOX).r#as()
+    }
+}
+
+impl<T> Aes64esm for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn aes64esm(self, other: Self) -> Self {
+        let sb = sub_bytes(shift_rows(self.r#as(), other.r#as(), false), &AES_SBOX);
+        mix_columns(sb, MIX).r#as()
+    }
+}
+
+impl<T> Aes64ds for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn aes64ds(self, other: Self) -> Self {
+        sub_bytes(shift_rows(self.r#as(), other.r#as(), true), &AES_INV_SBOX).r#as()
+    }
+}
+
+impl<T> Aes64dsm for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn aes64dsm(self, other: Self) -> Self {
+        let sb = sub_bytes(shift_rows(self.r#as(), other.r#as(), true), &AES_INV_SBOX);
+        mix_columns(sb, INV_MIX).r#as()
+    }
+}
+
+impl<T> Aes64im for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn aes64im(self) -> Self {
+        mix_columns(self.r#as(), INV_MIX).r#as()
+    }
+}
+
+impl<T> Aes64ks1i for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    /// `rnum` above 0xa is reserved and rejected by the decoder.
+    #[inline(always)]
+    fn aes64ks1i(self, other: U12) -> Self {
+        let rnum = (other.as_u16() & 0xf) as usize;
+        let word = (<T as As<u64>>::r#as(self) >> 32) as u32;
+        let word = if rnum == 0xa {
+            word
+        } else {
+            word.rotate_right(8)
+        };
+        let word = sub_bytes(word as u64, &AES_SBOX) as u32 ^ AES_RCON[rnum] as u32;
+        (word as u64 | (word as u64) << 32).r#as()
+    }
+}
+
+impl<T> Aes64ks2 for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn aes64ks2(self, other: Self) -> Self {
+        let (rs1, rs2): (u64, u64) = (self.r#as(), other.r#as());
+        let w0 = (rs1 >> 32) as u32 ^ rs2 as u32;
+        let w1 = w0 ^ (rs2 >> 32) as u32;
+        (w0 as u64 | (w1 as u64) << 32).r#as()
+    }
+}
+
+impl<T> Sha256sig0 for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sha256sig0(self) -> Self {
+        let x: u32 = self.r#as();
+        ((x.rotate_right(7) ^ x.rotate_right(18) ^ x >> 3) as i32).r#as()
+    }
+}
+
+impl<T> Sha256sig1 for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sha256sig1(self) -> Self {
+        let x: u32 = self.r#as();
+        ((x.rotate_right(17) ^ x.rotate_right(19) ^ x >> 10) as i32).r#as()
+    }
+}
+
+impl<T> Sha256sum0 for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sha256sum0(self) -> Self {
+        let x: u32 = self.r#as();
+        ((x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)) as i32).r#as()
+    }
+}
+
+impl<T> Sha256sum1 for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sha256sum1(self) -> Self {
+        let x: u32 = self.r#as();
+        ((x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)) as i32).r#as()
+    }
+}
+
+impl<T> Sha512sig0h for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sha512sig0h(self, other: Self) -> Self {
+        let (hi, lo): (u32, u32) = (self.r#as(), other.r#as());
+        ((hi >> 1 ^ hi >> 7 ^ hi >> 8 ^ lo << 31 ^ lo << 24) as i32).r#as()
+    }
+}
+
+impl<T> Sha512sig0l for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sha512sig0l(self, other: Self) -> Self {
+        let (lo, hi): (u32, u32) = (self.r#as(), other.r#as());
+        ((lo >> 1 ^ lo >> 7 ^ lo >> 8 ^ hi << 31 ^ hi << 25 ^ hi << 24) as i32).r#as()
+    }
+}
+
+impl<T> Sha512sig1h for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sha512sig1h(self, other: Self) -> Self {
+        let (hi, lo): (u32, u32) = (self.r#as(), other.r#as());
+        ((hi << 3 ^ hi >> 6 ^ hi >> 19 ^ lo >> 29 ^ lo << 13) as i32).r#as()
+    }
+}
+
+impl<T> Sha512sig1l for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sha512sig1l(self, other: Self) -> Self {
+        let (lo, hi): (u32, u32) = (self.r#as(), other.r#as());
+        ((lo << 3 ^ lo >> 6 ^ lo >> 19 ^ hi >> 29 ^ hi << 26 ^ hi << 13) as i32).r#as()
+    }
+}
+
+impl<T> Sha512sum0r for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sha512sum0r(self, other: Self) -> Self {
+        let (a, b): (u32, u32) = (self.r#as(), other.r#as());
+        ((a << 25 ^ a << 30 ^ a >> 28 ^ b >> 7 ^ b >> 2 ^ b << 4) as i32).r#as()
+    }
+}
+
+impl<T> Sha512sum1r for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sha512sum1r(self, other: Self) -> Self {
+        let (a, b): (u32, u32) = (self.r#as(), other.r#as());
+        ((a << 23 ^ a >> 14 ^ a >> 18 ^ b >> 9 ^ b << 18 ^ b << 14) as i32).r#as()
+    }
+}
+
+impl<T> Sha512sig0 for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn sha512sig0(self) -> Self {
+        let x: u64 = self.r#as();
+        (x.rotate_right(1) ^ x.rotate_right(8) ^ x >> 7).r#as()
+    }
+}
+
+impl<T> Sha512sig1 for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn sha512sig1(self) -> Self {
+        let x: u64 = self.r#as();
+        (x.rotate_right(19) ^ x.rotate_right(61) ^ x >> 6).r#as()
+    }
+}
+
+impl<T> Sha512sum0 for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn sha512sum0(self) -> Self {
+        let x: u64 = self.r#as();
+        (x.rotate_right(28) ^ x.rotate_right(34) ^ x.rotate_right(39)).r#as()
+    }
+}
+
+impl<T> Sha512sum1 for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn sha512sum1(self) -> Self {
+        let x: u64 = self.r#as();
+        (x.rotate_right(14) ^ x.rotate_right(18) ^ x.rotate_right(41)).r#as()
+    }
+}
+
+impl<T> Sm4ed for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sm4ed(self, other: Self, bs: U2) -> Self {
+        let x = select_byte(other, bs, &SM4_SBOX);
+        // L of a lone byte, none of the rotations wrap
+        let y = x ^ x << 2 ^ x << 10 ^ x << 18 ^ x << 24;
+        ((self.r#as() ^ y.rotate_left(bs.as_u32() * 8)) as i32).r#as()
+    }
+}
+
+impl<T> Sm4ks for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sm4ks(self, other: Self, bs: U2) -> Self {
+        let x = select_byte(other, bs, &SM4_SBOX);
+        // L' of a lone byte
+        let y = x ^ x << 13 ^ x << 23;
+        ((self.r#as() ^ y.rotate_left(bs.as_u32() * 8)) as i32).r#as()
+    }
+}
+
+impl<T> Sm3p0 for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sm3p0(self) -> Self {
+        let x: u32 = self.r#as();
+        ((x ^ x.rotate_left(9) ^ x.rotate_left(17)) as i32).r#as()
+    }
+}
+
+impl<T> Sm3p1 for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn sm3p1(self) -> Self {
+        let x: u32 = self.r#as();
+        ((x ^ x.rotate_left(15) ^ x.rotate_left(23)) as i32).r#as()
+    }
+}
+
+impl<T> BaseCrypto for T where
+    T: Aes32esi
+        + Aes32esmi
+        + Aes32dsi
+        + Aes32dsmi
+        + Aes64es
+        + Aes64esm
+        + Aes64ds
+        + Aes64dsm
+        + Aes64im
+        + Aes64ks1i
+        + Aes64ks2
+        + Sha256sig0
+        + Sha256sig1
+        + Sha256sum0
+        + Sha256sum1
+        + Sha512sig0h
+        + Sha512sig0l
+        + Sha512sig1h
+        + Sha512sig1l
+        + Sha512sum0r
+        + Sha512sum1r
+        + Sha512sig0
+        + Sha512sig1
+        + Sha512sum0
+        + Sha512sum1
+        + Sm4ed
+        + Sm4ks
+        + Sm3p0
+        + Sm3p1
+{
+}
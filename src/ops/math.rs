@@ -1,8 +1,6 @@
-use std::cmp::Ordering;
-use std::num::FpCategory;
-
-use crate::decode::{U12, U5};
+use crate::decode::{U12, U5, U6};
 use crate::num::{As, Bitcast, Shiftable, Unsigned, UnsignedWrapping, Wrapping};
+use crate::softfloat::{self, FloatEnv, Format, F16, F32, F64};
 
 pub trait Add {
     fn add(self, other: Self) -> Self;
@@ -69,15 +67,15 @@ pub trait Andi {
 }
 
 pub trait Slli {
-    fn slli(self, other: U5) -> Self;
+    fn slli(self, other: U6) -> Self;
 }
 
 pub trait Srli {
-    fn srli(self, other: U5) -> Self;
+    fn srli(self, other: U6) -> Self;
 }
 
 pub trait Srai {
-    fn srai(self, other: U5) -> Self;
+    fn srai(self, other: U6) -> Self;
 }
 
 pub trait Addw {
@@ -105,15 +103,15 @@ pub trait Addiw {
 }
 
 pub trait Slliw {
-    fn slliw(self, other: Self) -> Self;
+    fn slliw(self, other: U5) -> Self;
 }
 
 pub trait Srliw {
-    fn srliw(self, other: Self) -> Self;
+    fn srliw(self, other: U5) -> Self;
 }
 
 pub trait Sraiw {
-    fn sraiw(self, other: Self) -> Self;
+    fn sraiw(self, other: U5) -> Self;
 }
 
 // M extension
@@ -150,6 +148,269 @@ pub trait Remu {
     fn remu(self, other: Self) -> Self;
 }
 
+pub trait Mulw {
+    fn mulw(self, other: Self) -> Self;
+}
+
+pub trait Divw {
+    fn divw(self, other: Self) -> Self;
+}
+
+pub trait Divuw {
+    fn divuw(self, other: Self) -> Self;
+}
+
+pub trait Remw {
+    fn remw(self, other: Self) -> Self;
+}
+
+pub trait Remuw {
+    fn remuw(self, other: Self) -> Self;
+}
+
+// A extension, the values an AMO writes back given the loaded one and rs2
+
+pub trait Amoswap {
+    fn amoswap(self, other: Self) -> Self;
+}
+
+pub trait Amomin {
+    fn amomin(self, other: Self) -> Self;
+}
+
+pub trait Amomax {
+    fn amomax(self, other: Self) -> Self;
+}
+
+pub trait Amominu {
+    fn amominu(self, other: Self) -> Self;
+}
+
+pub trait Amomaxu {
+    fn amomaxu(self, other: Self) -> Self;
+}
+
+// Zba, Zbb, Zbc and Zbs extensions
+
+pub trait Sh1add {
+    fn sh1add(self, other: Self) -> Self;
+}
+
+pub trait Sh2add {
+    fn sh2add(self, other: Self) -> Self;
+}
+
+pub trait Sh3add {
+    fn sh3add(self, other: Self) -> Self;
+}
+
+pub trait Andn {
+    fn andn(self, other: Self) -> Self;
+}
+
+pub trait Orn {
+    fn orn(self, other: Self) -> Self;
+}
+
+pub trait Xnor {
+    fn xnor(self, other: Self) -> Self;
+}
+
+pub trait Max {
+    fn max(self, other: Self) -> Self;
+}
+
+pub trait Maxu {
+    fn maxu(self, other: Self) -> Self;
+}
+
+pub trait Min {
+    fn min(self, other: Self) -> Self;
+}
+
+pub trait Minu {
+    fn minu(self, other: Self) -> Self;
+}
+
+pub trait Rol {
+    fn rol(self, other: Self) -> Self;
+}
+
+pub trait Ror {
+    fn ror(self, other: Self) -> Self;
+}
+
+pub trait Rori {
+    fn rori(self, other: U6) -> Self;
+}
+
+pub trait Clz {
+    fn clz(self) -> Self;
+}
+
+pub trait Ctz {
+    fn ctz(self) -> Self;
+}
+
+pub trait Cpop {
+    fn cpop(self) -> Self;
+}
+
+pub trait SextB {
+    fn sext_b(self) -> Self;
+}
+
+pub trait SextH {
+    fn sext_h(self) -> Self;
+}
+
+pub trait ZextH {
+    fn zext_h(self) -> Self;
+}
+
+pub trait OrcB {
+    fn orc_b(self) -> Self;
+}
+
+pub trait Rev8 {
+    fn rev8(self) -> Self;
+}
+
+pub trait Clmul {
+    fn clmul(self, other: Self) -> Self;
+}
+
+pub trait Clmulh {
+    fn clmulh(self, other: Self) -> Self;
+}
+
+pub trait Clmulr {
+    fn clmulr(self, other: Self) -> Self;
+}
+
+pub trait Bclr {
+    fn bclr(self, other: Self) -> Self;
+}
+
+pub trait Bext {
+    fn bext(self, other: Self) -> Self;
+}
+
+pub trait Binv {
+    fn binv(self, other: Self) -> Self;
+}
+
+pub trait Bset {
+    fn bset(self, other: Self) -> Self;
+}
+
+pub trait Bclri {
+    fn bclri(self, other: U6) -> Self;
+}
+
+pub trait Bexti {
+    fn bexti(self, other: U6) -> Self;
+}
+
+pub trait Binvi {
+    fn binvi(self, other: U6) -> Self;
+}
+
+pub trait Bseti {
+    fn bseti(self, other: U6) -> Self;
+}
+
+// RV64 Zba and Zbb, the `.uw` forms take the low 32 bits of `rs1` zero
+// extended
+
+pub trait AddUw {
+    fn add_uw(self, other: Self) -> Self;
+}
+
+pub trait Sh1addUw {
+    fn sh1add_uw(self, other: Self) -> Self;
+}
+
+pub trait Sh2addUw {
+    fn sh2add_uw(self, other: Self) -> Self;
+}
+
+pub trait Sh3addUw {
+    fn sh3add_uw(self, other: Self) -> Self;
+}
+
+pub trait SlliUw {
+    fn slli_uw(self, other: U6) -> Self;
+}
+
+pub trait Rolw {
+    fn rolw(self, other: Self) -> Self;
+}
+
+pub trait Rorw {
+    fn rorw(self, other: Self) -> Self;
+}
+
+pub trait Roriw {
+    fn roriw(self, other: U5) -> Self;
+}
+
+pub trait Clzw {
+    fn clzw(self) -> Self;
+}
+
+pub trait Ctzw {
+    fn ctzw(self) -> Self;
+}
+
+pub trait Cpopw {
+    fn cpopw(self) -> Self;
+}
+
+// Zbkb and Zbkx, the bit-manipulation ops for cryptography that Zbb lacks
+
+pub trait Pack {
+    fn pack(self, other: Self) -> Self;
+}
+
+pub trait Packh {
+    fn packh(self, other: Self) -> Self;
+}
+
+pub trait Packw {
+    fn packw(self, other: Self) -> Self;
+}
+
+pub trait Brev8 {
+    fn brev8(self) -> Self;
+}
+
+pub trait Zip {
+    fn zip(self) -> Self;
+}
+
+pub trait Unzip {
+    fn unzip(self) -> Self;
+}
+
+pub trait Xperm4 {
+    fn xperm4(self, other: Self) -> Self;
+}
+
+pub trait Xperm8 {
+    fn xperm8(self, other: Self) -> Self;
+}
+
+// Zicond extension
+
+pub trait CzeroEqz {
+    fn czero_eqz(self, other: Self) -> Self;
+}
+
+pub trait CzeroNez {
+    fn czero_nez(self, other: Self) -> Self;
+}
+
 // Custom Byte Math extension
 
 pub trait Badd {
@@ -192,86 +453,276 @@ pub trait Band {
     fn band(self, other: Self) -> Self;
 }
 
-// F extension
+// F extension, the ops take the rounding mode and accrue exception flags in
+// `env`. Sign injection and moves ignore it.
 
 pub trait Fadd {
-    fn fadd(self, other: Self) -> Self;
+    fn fadd(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fsub {
-    fn fsub(self, other: Self) -> Self;
+    fn fsub(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fmul {
-    fn fmul(self, other: Self) -> Self;
+    fn fmul(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fdiv {
-    fn fdiv(self, other: Self) -> Self;
+    fn fdiv(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fsqrt {
-    fn fsqrt(self, other: Self) -> Self;
+    fn fsqrt(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fsgnj {
-    fn fsgnj(self, other: Self) -> Self;
+    fn fsgnj(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fsgnjn {
-    fn fsgnjn(self, other: Self) -> Self;
+    fn fsgnjn(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fsgnjx {
-    fn fsgnjx(self, other: Self) -> Self;
+    fn fsgnjx(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fmin {
-    fn fmin(self, other: Self) -> Self;
+    fn fmin(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fmax {
-    fn fmax(self, other: Self) -> Self;
+    fn fmax(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fcvtws {
-    fn fcvtws(self, other: Self) -> Self;
+    fn fcvtws(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fcvtwus {
-    fn fcvtwus(self, other: Self) -> Self;
+    fn fcvtwus(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fmvxw {
-    fn fmvxw(self, other: Self) -> Self;
+    fn fmvxw(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Feq {
-    fn feq(self, other: Self) -> Self;
+    fn feq(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Flt {
-    fn flt(self, other: Self) -> Self;
+    fn flt(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fle {
-    fn fle(self, other: Self) -> Self;
+    fn fle(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fclass {
-    fn fclass(self, other: Self) -> Self;
+    fn fclass(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fcvtsw {
-    fn fcvtsw(self, other: Self) -> Self;
+    fn fcvtsw(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fcvtswu {
-    fn fcvtswu(self, other: Self) -> Self;
+    fn fcvtswu(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait Fmvwx {
-    fn fmvwx(self, other: Self) -> Self;
+    fn fmvwx(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+// RV64 F extension, converting between single precision and 64-bit integers
+
+pub trait Fcvtls {
+    fn fcvtls(self, env: &mut FloatEnv) -> u64;
+}
+
+pub trait Fcvtlus {
+    fn fcvtlus(self, env: &mut FloatEnv) -> u64;
+}
+
+pub trait Fcvtsl {
+    fn fcvtsl(self, env: &mut FloatEnv) -> u32;
+}
+
+pub trait Fcvtslu {
+    fn fcvtslu(self, env: &mut FloatEnv) -> u32;
+}
+
+pub trait Fmadd {
+    fn fmadd(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fmsub {
+    fn fmsub(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fnmsub {
+    fn fnmsub(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fnmadd {
+    fn fnmadd(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self;
+}
+
+// D extension, double precision values and conversions to and from single
+// precision. The arithmetic, sign injection, min/max, compare, classify and
+// fused multiply-add ops are the F traits implemented for u64.
+
+pub trait Fcvtwd {
+    fn fcvtwd(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fcvtwud {
+    fn fcvtwud(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fmvxd {
+    fn fmvxd(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fcvtdw {
+    fn fcvtdw(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fcvtdwu {
+    fn fcvtdwu(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fmvdx {
+    fn fmvdx(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+// RV64 D extension, converting between double precision and 64-bit integers
+
+pub trait Fcvtld {
+    fn fcvtld(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fcvtlud {
+    fn fcvtlud(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fcvtdl {
+    fn fcvtdl(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fcvtdlu {
+    fn fcvtdlu(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fcvtsd {
+    fn fcvtsd(self, env: &mut FloatEnv) -> u32;
+}
+
+pub trait Fcvtds {
+    fn fcvtds(self, env: &mut FloatEnv) -> u64;
+}
+
+// Zfh extension, half precision values convert to and from XLEN integers and
+// the other float formats
+
+pub trait Fcvtwh {
+    fn fcvtwh(self, env: &mut FloatEnv) -> u32;
+}
+
+pub trait Fcvtwuh {
+    fn fcvtwuh(self, env: &mut FloatEnv) -> u32;
+}
+
+pub trait Fcvtlh {
+    fn fcvtlh(self, env: &mut FloatEnv) -> u64;
+}
+
+pub trait Fcvtluh {
+    fn fcvtluh(self, env: &mut FloatEnv) -> u64;
+}
+
+pub trait Fcvthw {
+    fn fcvthw(self, env: &mut FloatEnv) -> u16;
+}
+
+pub trait Fcvthwu {
+    fn fcvthwu(self, env: &mut FloatEnv) -> u16;
+}
+
+pub trait Fcvthl {
+    fn fcvthl(self, env: &mut FloatEnv) -> u16;
+}
+
+pub trait Fcvthlu {
+    fn fcvthlu(self, env: &mut FloatEnv) -> u16;
+}
+
+pub trait Fmvxh {
+    fn fmvxh(self, env: &mut FloatEnv) -> u64;
+}
+
+pub trait Fmvhx {
+    fn fmvhx(self, env: &mut FloatEnv) -> u16;
+}
+
+pub trait Fcvtsh {
+    fn fcvtsh(self, env: &mut FloatEnv) -> u32;
+}
+
+pub trait Fcvths {
+    fn fcvths(self, env: &mut FloatEnv) -> u16;
+}
+
+pub trait Fcvtdh {
+    fn fcvtdh(self, env: &mut FloatEnv) -> u64;
+}
+
+pub trait Fcvthd {
+    fn fcvthd(self, env: &mut FloatEnv) -> u16;
+}
+
+// Zfa extension
+
+pub trait Fli {
+    fn fli(index: u8) -> Self;
+}
+
+pub trait Fminm {
+    fn fminm(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fmaxm {
+    fn fmaxm(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fround {
+    fn fround(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Froundnx {
+    fn froundnx(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fleq {
+    fn fleq(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fltq {
+    fn fltq(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fcvtmodwd {
+    fn fcvtmodwd(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fmvhxd {
+    fn fmvhxd(self, other: Self, env: &mut FloatEnv) -> Self;
+}
+
+pub trait Fmvpdx {
+    fn fmvpdx(self, other: Self, env: &mut FloatEnv) -> Self;
 }
 
 pub trait BaseMath:
@@ -303,6 +754,59 @@ pub trait BaseMath:
     + Divu
     + Rem
     + Remu
+    // A ratified extension
+    + Amoswap
+    + Amomin
+    + Amomax
+    + Amominu
+    + Amomaxu
+    // Zba ratified extension
+    + Sh1add
+    + Sh2add
+    + Sh3add
+    // Zbb ratified extension
+    + Andn
+    + Orn
+    + Xnor
+    + Max
+    + Maxu
+    + Min
+    + Minu
+    + Rol
+    + Ror
+    + Rori
+    + Clz
+    + Ctz
+    + Cpop
+    + SextB
+    + SextH
+    + ZextH
+    + OrcB
+    + Rev8
+    // Zbc ratified extension
+    + Clmul
+    + Clmulh
+    + Clmulr
+    // Zbs ratified extension
+    + Bclr
+    + Bclri
+    + Bext
+    + Bexti
+    + Binv
+    + Binvi
+    + Bset
+    + Bseti
+    // Zbkb and Zbkx ratified extensions
+    + Pack
+    + Packh
+    + Brev8
+    + Zip
+    + Unzip
+    + Xperm4
+    + Xperm8
+    // Zicond ratified extension
+    + CzeroEqz
+    + CzeroNez
     // Byte Math custom extension
     + Badd
     + Bsub
@@ -317,9 +821,38 @@ pub trait BaseMath:
 {
 }
 
-#[allow(unused)]
 pub trait BaseMathW:
-    BaseMath + Addw + Subw + Sllw + Srlw + Sraw + Addiw + Slliw + Srliw + Sraiw
+    BaseMath
+    + Addw
+    + Subw
+    + Sllw
+    + Srlw
+    + Sraw
+    + Addiw
+    + Slliw
+    + Srliw
+    + Sraiw
+    // M ratified extension
+    + Mulw
+    + Divw
+    + Divuw
+    + Remw
+    + Remuw
+    // Zba ratified extension
+    + AddUw
+    + Sh1addUw
+    + Sh2addUw
+    + Sh3addUw
+    + SlliUw
+    // Zbb ratified extension
+    + Rolw
+    + Rorw
+    + Roriw
+    + Clzw
+    + Ctzw
+    + Cpopw
+    // Zbkb ratified extension
+    + Packw
 {
 }
 
@@ -346,6 +879,10 @@ pub trait BaseFloat:
     + Fcvtsw
     + Fcvtswu
     + Fmvwx
+    + Fmadd
+    + Fmsub
+    + Fnmsub
+    + Fnmadd
 {
 }
 
@@ -519,14 +1056,14 @@ where
 
 impl<T: Wrapping> Slli for T {
     #[inline(always)]
-    fn slli(self, other: U5) -> Self {
+    fn slli(self, other: U6) -> Self {
         self.wrapping_shl(other.as_u32())
     }
 }
 
 impl<T: Wrapping> Srli for T {
     #[inline(always)]
-    fn srli(self, other: U5) -> Self {
+    fn srli(self, other: U6) -> Self {
         self.wrapping_shr(other.as_u32())
     }
 }
@@ -538,7 +1075,7 @@ where
     <T as Unsigned>::Signed: Wrapping,
 {
     #[inline(always)]
-    fn srai(self, other: U5) -> Self {
+    fn srai(self, other: U6) -> Self {
         (<T as Bitcast<T::Signed>>::bitcast(self))
             .wrapping_shr(other.as_u32())
             .bitcast()
@@ -587,50 +1124,144 @@ where
     }
 }
 
-impl<T: Wrapping> Mulhsu for T {
-    #[inline(always)]
-    fn mulhsu(self, other: Self) -> Self {
-        // TODO: implement it
-        T::wrapping_add(self, other)
-    }
-}
-
-impl<T> Div for T
+impl<T> Mulhsu for T
 where
     T: Unsigned,
-    T: Wrapping,
-    <T as Unsigned>::Signed: Wrapping,
-    <T as Unsigned>::Signed: As<T>,
+    T: Shiftable,
+    <T as Shiftable>::To: Wrapping,
+    <T as Shiftable>::To: As<T>,
+    <T as Unsigned>::Signed: As<<T as Shiftable>::To>,
 {
     #[inline(always)]
-    fn div(self, other: Self) -> Self {
-        self.r#as().wrapping_div(other.r#as()).r#as()
+    fn mulhsu(self, other: Self) -> Self {
+        // the sign extended operand wraps around the double width type, the
+        // high half of the wrapped product is still the exact one
+        <T as Bitcast<T::Signed>>::bitcast(self)
+            .r#as()
+            .wrapping_mul(other.r#as())
+            .wrapping_shr(<T as Shiftable>::SHIFT_BITS)
+            .r#as()
     }
 }
 
-impl<T: Wrapping> Divu for T {
+impl<T> Amoswap for T {
     #[inline(always)]
-    fn divu(self, other: Self) -> Self {
-        T::wrapping_div(self, other)
+    fn amoswap(self, other: Self) -> Self {
+        other
     }
 }
 
-impl<T> Rem for T
+impl<T> Amomin for T
 where
     T: Unsigned,
-    T: Wrapping,
-    <T as Unsigned>::Signed: Wrapping,
-    <T as Unsigned>::Signed: As<T>,
+    <T as Unsigned>::Signed: core::cmp::Ord,
 {
     #[inline(always)]
-    fn rem(self, other: Self) -> Self {
-        self.r#as().wrapping_rem(other.r#as()).r#as()
+    fn amomin(self, other: Self) -> Self {
+        core::cmp::min(
+            Bitcast::<<T as Unsigned>::Signed>::bitcast(self),
+            Bitcast::<<T as Unsigned>::Signed>::bitcast(other),
+        )
+        .bitcast()
     }
 }
 
-impl<T: Wrapping> Remu for T {
+impl<T> Amomax for T
+where
+    T: Unsigned,
+    <T as Unsigned>::Signed: core::cmp::Ord,
+{
+    #[inline(always)]
+    fn amomax(self, other: Self) -> Self {
+        core::cmp::max(
+            Bitcast::<<T as Unsigned>::Signed>::bitcast(self),
+            Bitcast::<<T as Unsigned>::Signed>::bitcast(other),
+        )
+        .bitcast()
+    }
+}
+
+impl<T: core::cmp::Ord> Amominu for T {
+    #[inline(always)]
+    fn amominu(self, other: Self) -> Self {
+        core::cmp::min(self, other)
+    }
+}
+
+impl<T: core::cmp::Ord> Amomaxu for T {
+    #[inline(always)]
+    fn amomaxu(self, other: Self) -> Self {
+        core::cmp::max(self, other)
+    }
+}
+
+// Division by zero yields all ones for the quotient and the dividend for the
+// remainder, signed overflow wraps. Neither traps.
+
+impl<T> Div for T
+where
+    T: Unsigned,
+    T: Wrapping,
+    T: PartialEq,
+    <T as Unsigned>::Signed: Wrapping,
+    <T as Unsigned>::Signed: As<T>,
+    u8: As<T>,
+    i8: As<T>,
+{
+    #[inline(always)]
+    fn div(self, other: Self) -> Self {
+        if other == 0u8.r#as() {
+            return (-1i8).r#as();
+        }
+        self.r#as().wrapping_div(other.r#as()).r#as()
+    }
+}
+
+impl<T> Divu for T
+where
+    T: Wrapping,
+    T: PartialEq,
+    u8: As<T>,
+    i8: As<T>,
+{
+    #[inline(always)]
+    fn divu(self, other: Self) -> Self {
+        if other == 0u8.r#as() {
+            return (-1i8).r#as();
+        }
+        T::wrapping_div(self, other)
+    }
+}
+
+impl<T> Rem for T
+where
+    T: Unsigned,
+    T: Wrapping,
+    T: PartialEq,
+    <T as Unsigned>::Signed: Wrapping,
+    <T as Unsigned>::Signed: As<T>,
+    u8: As<T>,
+{
+    #[inline(always)]
+    fn rem(self, other: Self) -> Self {
+        if other == 0u8.r#as() {
+            return self;
+        }
+        self.r#as().wrapping_rem(other.r#as()).r#as()
+    }
+}
+
+impl<T> Remu for T
+where
+    T: Wrapping,
+    T: PartialEq,
+    u8: As<T>,
+{
     #[inline(always)]
     fn remu(self, other: Self) -> Self {
+        if other == 0u8.r#as() {
+            return self;
+        }
         T::wrapping_rem(self, other)
     }
 }
@@ -664,6 +1295,52 @@ impl<
             + Divu
             + Rem
             + Remu
+            + Amoswap
+            + Amomin
+            + Amomax
+            + Amominu
+            + Amomaxu
+            + Sh1add
+            + Sh2add
+            + Sh3add
+            + Andn
+            + Orn
+            + Xnor
+            + Max
+            + Maxu
+            + Min
+            + Minu
+            + Rol
+            + Ror
+            + Rori
+            + Clz
+            + Ctz
+            + Cpop
+            + SextB
+            + SextH
+            + ZextH
+            + OrcB
+            + Rev8
+            + Clmul
+            + Clmulh
+            + Clmulr
+            + Bclr
+            + Bclri
+            + Bext
+            + Bexti
+            + Binv
+            + Binvi
+            + Bset
+            + Bseti
+            + Pack
+            + Packh
+            + Brev8
+            + Zip
+            + Unzip
+            + Xperm4
+            + Xperm8
+            + CzeroEqz
+            + CzeroNez
             + Badd
             + Bsub
             + Bsll
@@ -678,399 +1355,1708 @@ impl<
 {
 }
 
-impl Addw for u64 {
+// The W variants operate on the low 32 bits and sign extend the result to
+// XLEN bits. They are only decoded on RV64.
+
+impl<T> Addw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
     #[inline(always)]
     fn addw(self, other: Self) -> Self {
-        unsafe {
-            core::mem::transmute(core::mem::transmute::<u32, i32>(
-                (self as u32).wrapping_add(other as u32),
-            ) as i64)
-        }
+        (self.r#as().wrapping_add(other.r#as()) as i32).r#as()
     }
 }
 
-impl Subw for u64 {
+impl<T> Subw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
     #[inline(always)]
     fn subw(self, other: Self) -> Self {
-        unsafe {
-            core::mem::transmute(core::mem::transmute::<u32, i32>(
-                (self as u32).wrapping_sub(other as u32),
-            ) as i64)
-        }
+        (self.r#as().wrapping_sub(other.r#as()) as i32).r#as()
     }
 }
 
-impl Sllw for u64 {
+impl<T> Sllw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
     #[inline(always)]
     fn sllw(self, other: Self) -> Self {
-        unsafe {
-            core::mem::transmute(core::mem::transmute::<u32, i32>(
-                (self as u32).wrapping_shl(other as u32),
-            ) as i64)
-        }
+        (self.r#as().wrapping_shl(other.r#as()) as i32).r#as()
     }
 }
 
-impl Srlw for u64 {
+impl<T> Srlw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
     #[inline(always)]
     fn srlw(self, other: Self) -> Self {
-        unsafe {
-            core::mem::transmute(core::mem::transmute::<u32, i32>(
-                (self as u32).wrapping_shr(other as u32),
-            ) as i64)
-        }
+        (self.r#as().wrapping_shr(other.r#as()) as i32).r#as()
     }
 }
 
-impl Sraw for u64 {
+impl<T> Sraw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
     #[inline(always)]
     fn sraw(self, other: Self) -> Self {
-        unsafe {
-            core::mem::transmute(
-                core::mem::transmute::<u32, i32>(self as u32).wrapping_shr(other as u32) as i64,
-            )
-        }
+        (self.r#as() as i32).wrapping_shr(other.r#as()).r#as()
     }
 }
 
-impl Addiw for u64 {
+impl<T> Addiw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
     #[inline(always)]
     fn addiw(self, other: U12) -> Self {
-        unsafe {
-            core::mem::transmute(core::mem::transmute::<u32, i32>(
-                (self as u32).wrapping_add_signed(other.sign_extend() as i32),
-            ) as i64)
-        }
+        (self.r#as().wrapping_add_signed(other.sign_extend() as i32) as i32).r#as()
     }
 }
 
-impl Slliw for u64 {
+impl<T> Slliw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
     #[inline(always)]
-    fn slliw(self, other: Self) -> Self {
-        unsafe {
-            core::mem::transmute(core::mem::transmute::<u32, i32>(
-                (self as u32).wrapping_shl(other as u32),
-            ) as i64)
-        }
+    fn slliw(self, other: U5) -> Self {
+        (self.r#as().wrapping_shl(other.as_u32()) as i32).r#as()
     }
 }
 
-impl Srliw for u64 {
+impl<T> Srliw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
     #[inline(always)]
-    fn srliw(self, other: Self) -> Self {
-        unsafe {
-            core::mem::transmute(core::mem::transmute::<u32, i32>(
-                (self as u32).wrapping_shr(other as u32),
-            ) as i64)
-        }
+    fn srliw(self, other: U5) -> Self {
+        (self.r#as().wrapping_shr(other.as_u32()) as i32).r#as()
     }
 }
 
-impl Sraiw for u64 {
+impl<T> Sraiw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
     #[inline(always)]
-    fn sraiw(self, other: Self) -> Self {
-        unsafe {
-            core::mem::transmute(
-                core::mem::transmute::<u32, i32>(self as u32).wrapping_shr(other as u32) as i64,
-            )
-        }
+    fn sraiw(self, other: U5) -> Self {
+        (self.r#as() as i32).wrapping_shr(other.as_u32()).r#as()
     }
 }
 
-impl BaseMathW for u64 {}
-
-impl<T> Badd for T
+impl<T> Mulw for T
 where
-    T: As<u8>,
-    u8: As<T>,
+    T: As<u32>,
+    i32: As<T>,
 {
     #[inline(always)]
-    fn badd(self, other: Self) -> Self {
-        self.r#as().wrapping_add(other.r#as()).r#as()
+    fn mulw(self, other: Self) -> Self {
+        (self.r#as().wrapping_mul(other.r#as()) as i32).r#as()
     }
 }
 
-impl<T> Bsub for T
+impl<T> Divw for T
 where
-    T: As<u8>,
-    u8: As<T>,
+    T: As<u32>,
+    i32: As<T>,
 {
     #[inline(always)]
-    fn bsub(self, other: Self) -> Self {
-        self.r#as().wrapping_sub(other.r#as()).r#as()
+    fn divw(self, other: Self) -> Self {
+        let (dividend, divisor) = (self.r#as() as i32, other.r#as() as i32);
+        if divisor == 0 {
+            return (-1i32).r#as();
+        }
+        dividend.wrapping_div(divisor).r#as()
     }
 }
 
-impl<T> Bsll for T
+impl<T> Divuw for T
 where
-    T: As<u8>,
-    u8: As<T>,
+    T: As<u32>,
+    i32: As<T>,
 {
     #[inline(always)]
-    fn bsll(self, other: Self) -> Self {
-        self.r#as().wrapping_shl(other.r#as() as u32).r#as()
+    fn divuw(self, other: Self) -> Self {
+        let (dividend, divisor): (u32, u32) = (self.r#as(), other.r#as());
+        if divisor == 0 {
+            return (-1i32).r#as();
+        }
+        ((dividend / divisor) as i32).r#as()
     }
 }
 
-impl<T> Bslt for T
+impl<T> Remw for T
 where
-    T: As<u8>,
-    bool: As<T>,
+    T: As<u32>,
+    i32: As<T>,
 {
     #[inline(always)]
-    fn bslt(self, other: Self) -> Self {
-        ((self.r#as() as i8) < (other.r#as() as i8)).r#as()
+    fn remw(self, other: Self) -> Self {
+        let (dividend, divisor) = (self.r#as() as i32, other.r#as() as i32);
+        if divisor == 0 {
+            return dividend.r#as();
+        }
+        dividend.wrapping_rem(divisor).r#as()
     }
 }
 
-impl<T> Bsltu for T
+impl<T> Remuw for T
 where
-    T: As<u8>,
-    bool: As<T>,
+    T: As<u32>,
+    i32: As<T>,
 {
     #[inline(always)]
-    fn bsltu(self, other: Self) -> Self {
-        (self.r#as() < other.r#as()).r#as()
+    fn remuw(self, other: Self) -> Self {
+        let (dividend, divisor): (u32, u32) = (self.r#as(), other.r#as());
+        if divisor == 0 {
+            return (dividend as i32).r#as();
+        }
+        ((dividend % divisor) as i32).r#as()
     }
 }
 
-impl<T> Bxor for T
-where
-    T: As<u8>,
-    u8: As<T>,
+impl<
+        T: BaseMath
+            + Addw
+            + Subw
+            + Sllw
+            + Srlw
+            + Sraw
+            + Addiw
+            + Slliw
+            + Srliw
+            + Sraiw
+            + Mulw
+            + Divw
+            + Divuw
+            + Remw
+            + Remuw
+            + AddUw
+            + Sh1addUw
+            + Sh2addUw
+            + Sh3addUw
+            + SlliUw
+            + Rolw
+            + Rorw
+            + Roriw
+            + Clzw
+            + Ctzw
+            + Cpopw
+            + Packw,
+    > BaseMathW for T
 {
+}
+
+// Zba, the shifted adds compute `rs2 + (rs1 << n)`
+
+impl<T: Wrapping> Sh1add for T {
     #[inline(always)]
-    fn bxor(self, other: Self) -> Self {
-        (self.r#as() ^ other.r#as()).r#as()
+    fn sh1add(self, other: Self) -> Self {
+        other.wrapping_add(self.wrapping_shl(1))
     }
 }
 
-impl<T> Bsrl for T
-where
-    T: As<u8>,
-    u8: As<T>,
-{
+impl<T: Wrapping> Sh2add for T {
     #[inline(always)]
-    fn bsrl(self, other: Self) -> Self {
-        self.r#as().wrapping_shr(other.r#as() as u32).r#as()
+    fn sh2add(self, other: Self) -> Self {
+        other.wrapping_add(self.wrapping_shl(2))
     }
 }
 
-impl<T> Bsra for T
-where
-    T: Unsigned,
-    T: As<u8>,
-    u8: As<T>,
-{
+impl<T: Wrapping> Sh3add for T {
     #[inline(always)]
-    fn bsra(self, other: Self) -> Self {
-        ((<T as As<u8>>::r#as(self) as i8).wrapping_shr(<T as As<u8>>::r#as(other) as u32) as u8)
-            .r#as()
+    fn sh3add(self, other: Self) -> Self {
+        other.wrapping_add(self.wrapping_shl(3))
     }
 }
 
-impl<T> Bor for T
+impl<T> Andn for T
 where
-    T: As<u8>,
-    u8: As<T>,
+    T: core::ops::BitAnd<Output = T>,
+    T: core::ops::Not<Output = T>,
 {
     #[inline(always)]
-    fn bor(self, other: Self) -> Self {
-        (self.r#as() | other.r#as()).r#as()
+    fn andn(self, other: Self) -> Self {
+        self & !other
     }
 }
 
-impl<T> Band for T
+impl<T> Orn for T
 where
-    T: As<u8>,
-    u8: As<T>,
+    T: core::ops::BitOr<Output = T>,
+    T: core::ops::Not<Output = T>,
 {
     #[inline(always)]
-    fn band(self, other: Self) -> Self {
-        (self.r#as() & other.r#as()).r#as()
+    fn orn(self, other: Self) -> Self {
+        self | !other
     }
 }
 
-impl Fadd for u32 {
+impl<T> Xnor for T
+where
+    T: core::ops::BitXor<Output = T>,
+    T: core::ops::Not<Output = T>,
+{
     #[inline(always)]
-    fn fadd(self, other: Self) -> Self {
-        (f32::from_bits(self) + f32::from_bits(other)).to_bits()
+    fn xnor(self, other: Self) -> Self {
+        !(self ^ other)
     }
 }
 
-impl Fsub for u32 {
+impl<T: Amomax> Max for T {
     #[inline(always)]
-    fn fsub(self, other: Self) -> Self {
-        (f32::from_bits(self) - f32::from_bits(other)).to_bits()
+    fn max(self, other: Self) -> Self {
+        self.amomax(other)
     }
 }
 
-impl Fmul for u32 {
+impl<T: Amomaxu> Maxu for T {
     #[inline(always)]
-    fn fmul(self, other: Self) -> Self {
-        (f32::from_bits(self) * f32::from_bits(other)).to_bits()
+    fn maxu(self, other: Self) -> Self {
+        self.amomaxu(other)
     }
 }
 
-impl Fdiv for u32 {
+impl<T: Amomin> Min for T {
     #[inline(always)]
-    fn fdiv(self, other: Self) -> Self {
-        (f32::from_bits(self) / f32::from_bits(other)).to_bits()
+    fn min(self, other: Self) -> Self {
+        self.amomin(other)
     }
 }
 
-impl Fsqrt for u32 {
+impl<T: Amominu> Minu for T {
     #[inline(always)]
-    fn fsqrt(self, _: Self) -> Self {
-        f32::from_bits(self).sqrt().to_bits()
+    fn minu(self, other: Self) -> Self {
+        self.amominu(other)
     }
 }
 
-impl Fsgnj for u32 {
-    #[inline(always)]
-    fn fsgnj(self, other: Self) -> Self {
-        ((self << 1) >> 1) | ((other >> 31) << 31)
-    }
-}
+// Rotates by `n` and `XLEN - n`, both shifts take the amount modulo XLEN so a
+// rotation by zero ors the operand with itself.
 
-impl Fsgnjn for u32 {
+impl<T> Rol for T
+where
+    T: Copy + Wrapping + Shiftable,
+    T: core::ops::BitOr<Output = T>,
+    T: As<u32>,
+{
     #[inline(always)]
-    fn fsgnjn(self, other: Self) -> Self {
-        ((self << 1) >> 1) | (!(other >> 31) << 31)
+    fn rol(self, other: Self) -> Self {
+        let shamt = <T as As<u32>>::r#as(other) & (T::SHIFT_BITS - 1);
+        self.wrapping_shl(shamt) | self.wrapping_shr(T::SHIFT_BITS - shamt)
     }
 }
 
-impl Fsgnjx for u32 {
+impl<T> Ror for T
+where
+    T: Copy + Wrapping + Shiftable,
+    T: core::ops::BitOr<Output = T>,
+    T: As<u32>,
+{
     #[inline(always)]
-    fn fsgnjx(self, other: Self) -> Self {
-        self ^ ((other >> 31) << 31)
+    fn ror(self, other: Self) -> Self {
+        let shamt = <T as As<u32>>::r#as(other) & (T::SHIFT_BITS - 1);
+        self.wrapping_shr(shamt) | self.wrapping_shl(T::SHIFT_BITS - shamt)
     }
 }
 
-impl Fmin for u32 {
+impl<T> Rori for T
+where
+    T: Copy + Wrapping + Shiftable,
+    T: core::ops::BitOr<Output = T>,
+{
     #[inline(always)]
-    fn fmin(self, other: Self) -> Self {
-        match f32::from_bits(self).total_cmp(&f32::from_bits(other)) {
-            Ordering::Less => self,
-            _ => other,
-        }
+    fn rori(self, other: U6) -> Self {
+        let shamt = other.as_u32();
+        self.wrapping_shr(shamt) | self.wrapping_shl(T::SHIFT_BITS - shamt)
     }
 }
 
-impl Fmax for u32 {
-    #[inline(always)]
-    fn fmax(self, other: Self) -> Self {
-        match f32::from_bits(self).total_cmp(&f32::from_bits(other)) {
-            Ordering::Greater => self,
-            _ => other,
-        }
-    }
-}
+// The counts and byte ops work on the zero extended value in a u64, then
+// account for the bits above XLEN.
 
-impl Feq for u32 {
+impl<T> Clz for T
+where
+    T: Shiftable + As<u64>,
+    u32: As<T>,
+{
     #[inline(always)]
-    fn feq(self, other: Self) -> Self {
-        (self == other) as Self
+    fn clz(self) -> Self {
+        (<T as As<u64>>::r#as(self).leading_zeros() - (64 - T::SHIFT_BITS)).r#as()
     }
 }
 
-impl Flt for u32 {
+impl<T> Ctz for T
+where
+    T: Shiftable + As<u64>,
+    u32: As<T>,
+{
     #[inline(always)]
-    fn flt(self, other: Self) -> Self {
-        println!("{} < {}", self, other);
-        (f32::from_bits(self) < f32::from_bits(other)) as Self
+    fn ctz(self) -> Self {
+        core::cmp::min(<T as As<u64>>::r#as(self).trailing_zeros(), T::SHIFT_BITS).r#as()
     }
 }
 
-impl Fle for u32 {
+impl<T> Cpop for T
+where
+    T: As<u64>,
+    u32: As<T>,
+{
     #[inline(always)]
-    fn fle(self, other: Self) -> Self {
-        (f32::from_bits(self) <= f32::from_bits(other)) as Self
+    fn cpop(self) -> Self {
+        <T as As<u64>>::r#as(self).count_ones().r#as()
     }
 }
 
-impl Fcvtws for u32 {
+impl<T> SextB for T
+where
+    T: As<i8>,
+    i8: As<T>,
+{
     #[inline(always)]
-    fn fcvtws(self, _: Self) -> Self {
-        f32::from_bits(self) as i32 as u32
+    fn sext_b(self) -> Self {
+        <T as As<i8>>::r#as(self).r#as()
     }
 }
 
-impl Fcvtwus for u32 {
+impl<T> SextH for T
+where
+    T: As<i16>,
+    i16: As<T>,
+{
     #[inline(always)]
-    fn fcvtwus(self, _: Self) -> Self {
-        f32::from_bits(self) as u32
+    fn sext_h(self) -> Self {
+        <T as As<i16>>::r#as(self).r#as()
     }
 }
 
-impl Fmvxw for u32 {
+impl<T> ZextH for T
+where
+    T: As<u16>,
+    u16: As<T>,
+{
     #[inline(always)]
-    fn fmvxw(self, _: Self) -> Self {
-        self
+    fn zext_h(self) -> Self {
+        <T as As<u16>>::r#as(self).r#as()
     }
 }
 
-impl Fclass for u32 {
+impl<T> OrcB for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
     #[inline(always)]
-    fn fclass(self, _: Self) -> Self {
-        let f = f32::from_bits(self);
-        match f.classify() {
-            FpCategory::Infinite => {
-                if f.is_sign_negative() {
-                    1 << 0
-                } else {
-                    1 << 7
-                }
-            }
-            FpCategory::Normal => {
-                if f.is_sign_negative() {
-                    1 << 1
-                } else {
-                    1 << 6
-                }
-            }
-            FpCategory::Subnormal => {
-                if f.is_sign_negative() {
-                    1 << 2
-                } else {
-                    1 << 5
-                }
-            }
-            FpCategory::Zero => {
-                if f.is_sign_negative() {
-                    1 << 3
-                } else {
-                    1 << 4
-                }
-            }
-            FpCategory::Nan => {
-                if f.to_bits() == 0x7fc00000 {
-                    1 << 8
-                } else {
-                    1 << 9
-                }
+    fn orc_b(self) -> Self {
+        let value: u64 = self.r#as();
+        let mut result = 0;
+        for byte in 0..8 {
+            if value >> (byte * 8) & 0xff != 0 {
+                result |= 0xff << (byte * 8);
             }
         }
+        result.r#as()
     }
 }
 
-impl Fcvtsw for u32 {
+impl<T> Rev8 for T
+where
+    T: Shiftable + As<u64>,
+    u64: As<T>,
+{
     #[inline(always)]
-    fn fcvtsw(self, _: Self) -> Self {
-        (self as i32 as f32).to_bits()
+    fn rev8(self) -> Self {
+        (<T as As<u64>>::r#as(self).swap_bytes() >> (64 - T::SHIFT_BITS)).r#as()
     }
 }
 
-impl Fcvtswu for u32 {
+// Zbc, the operands are at most 64 bits wide so their full carry-less product
+// fits in a u128.
+
+#[inline(always)]
+fn clmul_wide<T: As<u128>>(a: T, b: T) -> u128 {
+    let (a, b): (u128, u128) = (a.r#as(), b.r#as());
+    (0..64)
+        .filter(|i| b >> i & 1 != 0)
+        .fold(0, |product, i| product ^ a << i)
+}
+
+impl<T> Clmul for T
+where
+    T: As<u128>,
+    u128: As<T>,
+{
     #[inline(always)]
-    fn fcvtswu(self, _: Self) -> Self {
-        (self as f32).to_bits()
+    fn clmul(self, other: Self) -> Self {
+        clmul_wide(self, other).r#as()
     }
 }
 
-impl Fmvwx for u32 {
+impl<T> Clmulh for T
+where
+    T: Shiftable + As<u128>,
+    u128: As<T>,
+{
+    #[inline(always)]
+    fn clmulh(self, other: Self) -> Self {
+        (clmul_wide(self, other) >> T::SHIFT_BITS).r#as()
+    }
+}
+
+impl<T> Clmulr for T
+where
+    T: Shiftable + As<u128>,
+    u128: As<T>,
+{
+    #[inline(always)]
+    fn clmulr(self, other: Self) -> Self {
+        (clmul_wide(self, other) >> (T::SHIFT_BITS - 1)).r#as()
+    }
+}
+
+// Zbs, the bit index is taken modulo XLEN like a shift amount
+
+impl<T> Bclr for T
+where
+    T: Wrapping + As<u32>,
+    T: core::ops::BitAnd<Output = T>,
+    T: core::ops::Not<Output = T>,
+    bool: As<T>,
+{
+    #[inline(always)]
+    fn bclr(self, other: Self) -> Self {
+        self & !T::wrapping_shl(true.r#as(), other.r#as())
+    }
+}
+
+impl<T> Bclri for T
+where
+    T: Wrapping,
+    T: core::ops::BitAnd<Output = T>,
+    T: core::ops::Not<Output = T>,
+    bool: As<T>,
+{
+    #[inline(always)]
+    fn bclri(self, other: U6) -> Self {
+        self & !T::wrapping_shl(true.r#as(), other.as_u32())
+    }
+}
+
+impl<T> Bext for T
+where
+    T: Wrapping + As<u32>,
+    T: core::ops::BitAnd<Output = T>,
+    bool: As<T>,
+{
+    #[inline(always)]
+    fn bext(self, other: Self) -> Self {
+        self.wrapping_shr(other.r#as()) & true.r#as()
+    }
+}
+
+impl<T> Bexti for T
+where
+    T: Wrapping,
+    T: core::ops::BitAnd<Output = T>,
+    bool: As<T>,
+{
+    #[inline(always)]
+    fn bexti(self, other: U6) -> Self {
+        self.wrapping_shr(other.as_u32()) & true.r#as()
+    }
+}
+
+impl<T> Binv for T
+where
+    T: Wrapping + As<u32>,
+    T: core::ops::BitXor<Output = T>,
+    bool: As<T>,
+{
+    #[inline(always)]
+    fn binv(self, other: Self) -> Self {
+        self ^ T::wrapping_shl(true.r#as(), other.r#as())
+    }
+}
+
+impl<T> Binvi for T
+where
+    T: Wrapping,
+    T: core::ops::BitXor<Output = T>,
+    bool: As<T>,
+{
+    #[inline(always)]
+    fn binvi(self, other: U6) -> Self {
+        self ^ T::wrapping_shl(true.r#as(), other.as_u32())
+    }
+}
+
+impl<T> Bset for T
+where
+    T: Wrapping + As<u32>,
+    T: core::ops::BitOr<Output = T>,
+    bool: As<T>,
+{
+    #[inline(always)]
+    fn bset(self, other: Self) -> Self {
+        self | T::wrapping_shl(true.r#as(), other.r#as())
+    }
+}
+
+impl<T> Bseti for T
+where
+    T: Wrapping,
+    T: core::ops::BitOr<Output = T>,
+    bool: As<T>,
+{
+    #[inline(always)]
+    fn bseti(self, other: U6) -> Self {
+        self | T::wrapping_shl(true.r#as(), other.as_u32())
+    }
+}
+
+impl<T> AddUw for T
+where
+    T: Wrapping + As<u32>,
+    u32: As<T>,
+{
+    #[inline(always)]
+    fn add_uw(self, other: Self) -> Self {
+        other.wrapping_add(<T as As<u32>>::r#as(self).r#as())
+    }
+}
+
+impl<T> Sh1addUw for T
+where
+    T: Wrapping + As<u32>,
+    u32: As<T>,
+{
+    #[inline(always)]
+    fn sh1add_uw(self, other: Self) -> Self {
+        other.wrapping_add(T::wrapping_shl(<T as As<u32>>::r#as(self).r#as(), 1))
+    }
+}
+
+impl<T> Sh2addUw for T
+where
+    T: Wrapping + As<u32>,
+    u32: As<T>,
+{
+    #[inline(always)]
+    fn sh2add_uw(self, other: Self) -> Self {
+        other.wrapping_add(T::wrapping_shl(<T as As<u32>>::r#as(self).r#as(), 2))
+    }
+}
+
+impl<T> Sh3addUw for T
+where
+    T: Wrapping + As<u32>,
+    u32: As<T>,
+{
+    #[inline(always)]
+    fn sh3add_uw(self, other: Self) -> Self {
+        other.wrapping_add(T::wrapping_shl(<T as As<u32>>::r#as(self).r#as(), 3))
+    }
+}
+
+impl<T> SlliUw for T
+where
+    T: Wrapping + As<u32>,
+    u32: As<T>,
+{
+    #[inline(always)]
+    fn slli_uw(self, other: U6) -> Self {
+        T::wrapping_shl(<T as As<u32>>::r#as(self).r#as(), other.as_u32())
+    }
+}
+
+impl<T> Rolw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn rolw(self, other: Self) -> Self {
+        (<T as As<u32>>::r#as(self).rotate_left(other.r#as()) as i32).r#as()
+    }
+}
+
+impl<T> Rorw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn rorw(self, other: Self) -> Self {
+        (<T as As<u32>>::r#as(self).rotate_right(other.r#as()) as i32).r#as()
+    }
+}
+
+impl<T> Roriw for T
+where
+    T: As<u32>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn roriw(self, other: U5) -> Self {
+        (<T as As<u32>>::r#as(self).rotate_right(other.as_u32()) as i32).r#as()
+    }
+}
+
+impl<T> Clzw for T
+where
+    T: As<u32>,
+    u32: As<T>,
+{
+    #[inline(always)]
+    fn clzw(self) -> Self {
+        <T as As<u32>>::r#as(self).leading_zeros().r#as()
+    }
+}
+
+impl<T> Ctzw for T
+where
+    T: As<u32>,
+    u32: As<T>,
+{
+    #[inline(always)]
+    fn ctzw(self) -> Self {
+        <T as As<u32>>::r#as(self).trailing_zeros().r#as()
+    }
+}
+
+impl<T> Cpopw for T
+where
+    T: As<u32>,
+    u32: As<T>,
+{
+    #[inline(always)]
+    fn cpopw(self) -> Self {
+        <T as As<u32>>::r#as(self).count_ones().r#as()
+    }
+}
+
+impl<T> Pack for T
+where
+    T: Shiftable + As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn pack(self, other: Self) -> Self {
+        let half = T::SHIFT_BITS / 2;
+        let mask = (1u64 << half) - 1;
+        let (low, high): (u64, u64) = (self.r#as(), other.r#as());
+        (low & mask | (high & mask) << half).r#as()
+    }
+}
+
+impl<T> Packh for T
+where
+    T: As<u8>,
+    u16: As<T>,
+{
+    #[inline(always)]
+    fn packh(self, other: Self) -> Self {
+        let (low, high): (u8, u8) = (self.r#as(), other.r#as());
+        u16::from_le_bytes([low, high]).r#as()
+    }
+}
+
+impl<T> Packw for T
+where
+    T: As<u16>,
+    i32: As<T>,
+{
+    #[inline(always)]
+    fn packw(self, other: Self) -> Self {
+        let (low, high): (u16, u16) = (self.r#as(), other.r#as());
+        ((low as u32 | (high as u32) << 16) as i32).r#as()
+    }
+}
+
+impl<T> Brev8 for T
+where
+    T: As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn brev8(self) -> Self {
+        <T as As<u64>>::r#as(self)
+            .reverse_bits()
+            .swap_bytes()
+            .r#as()
+    }
+}
+
+// zip and unzip are RV32 only, interleaving the halves of the word and
+// splitting them back.
+
+impl<T> Zip for T
+where
+    T: As<u32>,
+    u32: As<T>,
+{
+    #[inline(always)]
+    fn zip(self) -> Self {
+        let value: u32 = self.r#as();
+        (0..16)
+            .fold(0u32, |zipped, i| {
+                zipped | (value >> i & 1) << (2 * i) | (value >> (i + 16) & 1) << (2 * i + 1)
+            })
+            .r#as()
+    }
+}
+
+impl<T> Unzip for T
+where
+    T: As<u32>,
+    u32: As<T>,
+{
+    #[inline(always)]
+    fn unzip(self) -> Self {
+        let value: u32 = self.r#as();
+        (0..16)
+            .fold(0u32, |unzipped, i| {
+                unzipped | (value >> (2 * i) & 1) << i | (value >> (2 * i + 1) & 1) << (i + 16)
+            })
+            .r#as()
+    }
+}
+
+// The crossbar permutations look up each element of `rs2` in `rs1`, indices
+// past the end of the register select zero.
+
+#[inline(always)]
+fn xperm(table: u64, indices: u64, bits: u32, xlen: u32) -> u64 {
+    let mask = (1 << bits) - 1;
+    (0..xlen / bits).fold(0, |result, i| {
+        let index = indices >> (i * bits) & mask;
+        if index < (xlen / bits) as u64 {
+            result | (table >> (index as u32 * bits) & mask) << (i * bits)
+        } else {
+            result
+        }
+    })
+}
+
+impl<T> Xperm4 for T
+where
+    T: Shiftable + As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn xperm4(self, other: Self) -> Self {
+        xperm(self.r#as(), other.r#as(), 4, T::SHIFT_BITS).r#as()
+    }
+}
+
+impl<T> Xperm8 for T
+where
+    T: Shiftable + As<u64>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn xperm8(self, other: Self) -> Self {
+        xperm(self.r#as(), other.r#as(), 8, T::SHIFT_BITS).r#as()
+    }
+}
+
+// Zicond, rs1 or zero depending on whether rs2 is zero
+
+impl<T: Default + PartialEq> CzeroEqz for T {
+    #[inline(always)]
+    fn czero_eqz(self, other: Self) -> Self {
+        if other == T::default() {
+            T::default()
+        } else {
+            self
+        }
+    }
+}
+
+impl<T: Default + PartialEq> CzeroNez for T {
+    #[inline(always)]
+    fn czero_nez(self, other: Self) -> Self {
+        if other != T::default() {
+            T::default()
+        } else {
+            self
+        }
+    }
+}
+
+impl<T> Badd for T
+where
+    T: As<u8>,
+    u8: As<T>,
+{
+    #[inline(always)]
+    fn badd(self, other: Self) -> Self {
+        self.r#as().wrapping_add(other.r#as()).r#as()
+    }
+}
+
+impl<T> Bsub for T
+where
+    T: As<u8>,
+    u8: As<T>,
+{
+    #[inline(always)]
+    fn bsub(self, other: Self) -> Self {
+        self.r#as().wrapping_sub(other.r#as()).r#as()
+    }
+}
+
+impl<T> Bsll for T
+where
+    T: As<u8>,
+    u8: As<T>,
+{
+    #[inline(always)]
+    fn bsll(self, other: Self) -> Self {
+        self.r#as().wrapping_shl(other.r#as() as u32).r#as()
+    }
+}
+
+impl<T> Bslt for T
+where
+    T: As<u8>,
+    bool: As<T>,
+{
+    #[inline(always)]
+    fn bslt(self, other: Self) -> Self {
+        ((self.r#as() as i8) < (other.r#as() as i8)).r#as()
+    }
+}
+
+impl<T> Bsltu for T
+where
+    T: As<u8>,
+    bool: As<T>,
+{
+    #[inline(always)]
+    fn bsltu(self, other: Self) -> Self {
+        (self.r#as() < other.r#as()).r#as()
+    }
+}
+
+impl<T> Bxor for T
+where
+    T: As<u8>,
+    u8: As<T>,
+{
+    #[inline(always)]
+    fn bxor(self, other: Self) -> Self {
+        (self.r#as() ^ other.r#as()).r#as()
+    }
+}
+
+impl<T> Bsrl for T
+where
+    T: As<u8>,
+    u8: As<T>,
+{
+    #[inline(always)]
+    fn bsrl(self, other: Self) -> Self {
+        self.r#as().wrapping_shr(other.r#as() as u32).r#as()
+    }
+}
+
+impl<T> Bsra for T
+where
+    T: Unsigned,
+    T: As<u8>,
+    u8: As<T>,
+{
+    #[inline(always)]
+    fn bsra(self, other: Self) -> Self {
+        ((<T as As<u8>>::r#as(self) as i8).wrapping_shr(<T as As<u8>>::r#as(other) as u32) as u8)
+            .r#as()
+    }
+}
+
+impl<T> Bor for T
+where
+    T: As<u8>,
+    u8: As<T>,
+{
+    #[inline(always)]
+    fn bor(self, other: Self) -> Self {
+        (self.r#as() | other.r#as()).r#as()
+    }
+}
+
+impl<T> Band for T
+where
+    T: As<u8>,
+    u8: As<T>,
+{
+    #[inline(always)]
+    fn band(self, other: Self) -> Self {
+        (self.r#as() & other.r#as()).r#as()
+    }
+}
+
+impl Fadd for u32 {
+    #[inline(always)]
+    fn fadd(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::add::<F32>(self as u64, other as u64, env) as u32
+    }
+}
+
+impl Fsub for u32 {
+    #[inline(always)]
+    fn fsub(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::sub::<F32>(self as u64, other as u64, env) as u32
+    }
+}
+
+impl Fmul for u32 {
+    #[inline(always)]
+    fn fmul(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::mul::<F32>(self as u64, other as u64, env) as u32
+    }
+}
+
+impl Fdiv for u32 {
+    #[inline(always)]
+    fn fdiv(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::div::<F32>(self as u64, other as u64, env) as u32
+    }
+}
+
+impl Fsqrt for u32 {
+    #[inline(always)]
+    fn fsqrt(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::sqrt::<F32>(self as u64, env) as u32
+    }
+}
+
+impl Fsgnj for u32 {
+    #[inline(always)]
+    fn fsgnj(self, other: Self, _: &mut FloatEnv) -> Self {
+        ((self << 1) >> 1) | ((other >> 31) << 31)
+    }
+}
+
+impl Fsgnjn for u32 {
+    #[inline(always)]
+    fn fsgnjn(self, other: Self, _: &mut FloatEnv) -> Self {
+        ((self << 1) >> 1) | (!(other >> 31) << 31)
+    }
+}
+
+impl Fsgnjx for u32 {
+    #[inline(always)]
+    fn fsgnjx(self, other: Self, _: &mut FloatEnv) -> Self {
+        self ^ ((other >> 31) << 31)
+    }
+}
+
+impl Fmin for u32 {
+    #[inline(always)]
+    fn fmin(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::min::<F32>(self as u64, other as u64, env) as u32
+    }
+}
+
+impl Fmax for u32 {
+    #[inline(always)]
+    fn fmax(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::max::<F32>(self as u64, other as u64, env) as u32
+    }
+}
+
+impl Feq for u32 {
+    #[inline(always)]
+    fn feq(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::eq::<F32>(self as u64, other as u64, env) as Self
+    }
+}
+
+impl Flt for u32 {
+    #[inline(always)]
+    fn flt(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::lt::<F32>(self as u64, other as u64, env) as Self
+    }
+}
+
+impl Fle for u32 {
+    #[inline(always)]
+    fn fle(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::le::<F32>(self as u64, other as u64, env) as Self
+    }
+}
+
+impl Fclass for u32 {
+    #[inline(always)]
+    fn fclass(self, _: Self, _: &mut FloatEnv) -> Self {
+        softfloat::classify::<F32>(self as u64) as u32
+    }
+}
+
+impl Fcvtws for u32 {
+    #[inline(always)]
+    fn fcvtws(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::to_int::<F32>(self as u64, true, 32, env) as u32
+    }
+}
+
+impl Fcvtwus for u32 {
+    #[inline(always)]
+    fn fcvtwus(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::to_int::<F32>(self as u64, false, 32, env) as u32
+    }
+}
+
+impl Fmvxw for u32 {
+    #[inline(always)]
+    fn fmvxw(self, _: Self, _: &mut FloatEnv) -> Self {
+        self
+    }
+}
+
+impl Fcvtsw for u32 {
+    #[inline(always)]
+    fn fcvtsw(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::from_int::<F32>(self as u64, true, 32, env) as u32
+    }
+}
+
+impl Fcvtswu for u32 {
+    #[inline(always)]
+    fn fcvtswu(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::from_int::<F32>(self as u64, false, 32, env) as u32
+    }
+}
+
+impl Fmvwx for u32 {
+    #[inline(always)]
+    fn fmvwx(self, _: Self, _: &mut FloatEnv) -> Self {
+        self
+    }
+}
+
+impl Fcvtls for u32 {
+    #[inline(always)]
+    fn fcvtls(self, env: &mut FloatEnv) -> u64 {
+        softfloat::to_int::<F32>(self as u64, true, 64, env)
+    }
+}
+
+impl Fcvtlus for u32 {
+    #[inline(always)]
+    fn fcvtlus(self, env: &mut FloatEnv) -> u64 {
+        softfloat::to_int::<F32>(self as u64, false, 64, env)
+    }
+}
+
+impl Fcvtsl for u64 {
+    #[inline(always)]
+    fn fcvtsl(self, env: &mut FloatEnv) -> u32 {
+        softfloat::from_int::<F32>(self, true, 64, env) as u32
+    }
+}
+
+impl Fcvtslu for u64 {
+    #[inline(always)]
+    fn fcvtslu(self, env: &mut FloatEnv) -> u32 {
+        softfloat::from_int::<F32>(self, false, 64, env) as u32
+    }
+}
+
+impl Fmadd for u32 {
+    #[inline(always)]
+    fn fmadd(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        softfloat::fma::<F32>(self as u64, other as u64, addend as u64, env) as u32
+    }
+}
+
+impl Fmsub for u32 {
+    #[inline(always)]
+    fn fmsub(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        Fmadd::fmadd(self, other, addend ^ (F32::SIGN as u32), env)
+    }
+}
+
+impl Fnmsub for u32 {
+    #[inline(always)]
+    fn fnmsub(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        Fmadd::fmadd(self ^ (F32::SIGN as u32), other, addend, env)
+    }
+}
+
+impl Fnmadd for u32 {
+    #[inline(always)]
+    fn fnmadd(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        Fmadd::fmadd(
+            self ^ (F32::SIGN as u32),
+            other,
+            addend ^ (F32::SIGN as u32),
+            env,
+        )
+    }
+}
+
+impl Fadd for u64 {
+    #[inline(always)]
+    fn fadd(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::add::<F64>(self, other, env)
+    }
+}
+
+impl Fsub for u64 {
+    #[inline(always)]
+    fn fsub(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::sub::<F64>(self, other, env)
+    }
+}
+
+impl Fmul for u64 {
+    #[inline(always)]
+    fn fmul(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::mul::<F64>(self, other, env)
+    }
+}
+
+impl Fdiv for u64 {
+    #[inline(always)]
+    fn fdiv(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::div::<F64>(self, other, env)
+    }
+}
+
+impl Fsqrt for u64 {
+    #[inline(always)]
+    fn fsqrt(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::sqrt::<F64>(self, env)
+    }
+}
+
+impl Fsgnj for u64 {
+    #[inline(always)]
+    fn fsgnj(self, other: Self, _: &mut FloatEnv) -> Self {
+        ((self << 1) >> 1) | ((other >> 63) << 63)
+    }
+}
+
+impl Fsgnjn for u64 {
+    #[inline(always)]
+    fn fsgnjn(self, other: Self, _: &mut FloatEnv) -> Self {
+        ((self << 1) >> 1) | (!(other >> 63) << 63)
+    }
+}
+
+impl Fsgnjx for u64 {
+    #[inline(always)]
+    fn fsgnjx(self, other: Self, _: &mut FloatEnv) -> Self {
+        self ^ ((other >> 63) << 63)
+    }
+}
+
+impl Fmin for u64 {
     #[inline(always)]
-    fn fmvwx(self, _: Self) -> Self {
+    fn fmin(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::min::<F64>(self, other, env)
+    }
+}
+
+impl Fmax for u64 {
+    #[inline(always)]
+    fn fmax(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::max::<F64>(self, other, env)
+    }
+}
+
+impl Feq for u64 {
+    #[inline(always)]
+    fn feq(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::eq::<F64>(self, other, env) as Self
+    }
+}
+
+impl Flt for u64 {
+    #[inline(always)]
+    fn flt(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::lt::<F64>(self, other, env) as Self
+    }
+}
+
+impl Fle for u64 {
+    #[inline(always)]
+    fn fle(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::le::<F64>(self, other, env) as Self
+    }
+}
+
+impl Fclass for u64 {
+    #[inline(always)]
+    fn fclass(self, _: Self, _: &mut FloatEnv) -> Self {
+        softfloat::classify::<F64>(self)
+    }
+}
+
+// The 32-bit conversion results are sign extended to 64 bits, callers keep
+// the low XLEN bits.
+
+impl Fcvtwd for u64 {
+    #[inline(always)]
+    fn fcvtwd(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::to_int::<F64>(self, true, 32, env) as i32 as u64
+    }
+}
+
+impl Fcvtwud for u64 {
+    #[inline(always)]
+    fn fcvtwud(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::to_int::<F64>(self, false, 32, env) as i32 as u64
+    }
+}
+
+impl Fmvxd for u64 {
+    #[inline(always)]
+    fn fmvxd(self, _: Self, _: &mut FloatEnv) -> Self {
+        self
+    }
+}
+
+impl Fcvtdw for u64 {
+    #[inline(always)]
+    fn fcvtdw(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::from_int::<F64>(self, true, 32, env)
+    }
+}
+
+impl Fcvtdwu for u64 {
+    #[inline(always)]
+    fn fcvtdwu(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::from_int::<F64>(self, false, 32, env)
+    }
+}
+
+impl Fmvdx for u64 {
+    #[inline(always)]
+    fn fmvdx(self, _: Self, _: &mut FloatEnv) -> Self {
         self
     }
 }
+
+impl Fcvtld for u64 {
+    #[inline(always)]
+    fn fcvtld(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::to_int::<F64>(self, true, 64, env)
+    }
+}
+
+impl Fcvtlud for u64 {
+    #[inline(always)]
+    fn fcvtlud(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::to_int::<F64>(self, false, 64, env)
+    }
+}
+
+impl Fcvtdl for u64 {
+    #[inline(always)]
+    fn fcvtdl(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::from_int::<F64>(self, true, 64, env)
+    }
+}
+
+impl Fcvtdlu for u64 {
+    #[inline(always)]
+    fn fcvtdlu(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::from_int::<F64>(self, false, 64, env)
+    }
+}
+
+impl Fcvtsd for u64 {
+    #[inline(always)]
+    fn fcvtsd(self, env: &mut FloatEnv) -> u32 {
+        softfloat::convert::<F64, F32>(self, env) as u32
+    }
+}
+
+impl Fcvtds for u32 {
+    #[inline(always)]
+    fn fcvtds(self, env: &mut FloatEnv) -> u64 {
+        softfloat::convert::<F32, F64>(self as u64, env)
+    }
+}
+
+impl Fmadd for u64 {
+    #[inline(always)]
+    fn fmadd(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        softfloat::fma::<F64>(self, other, addend, env)
+    }
+}
+
+impl Fmsub for u64 {
+    #[inline(always)]
+    fn fmsub(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        Fmadd::fmadd(self, other, addend ^ F64::SIGN, env)
+    }
+}
+
+impl Fnmsub for u64 {
+    #[inline(always)]
+    fn fnmsub(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        Fmadd::fmadd(self ^ F64::SIGN, other, addend, env)
+    }
+}
+
+impl Fnmadd for u64 {
+    #[inline(always)]
+    fn fnmadd(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        Fmadd::fmadd(self ^ F64::SIGN, other, addend ^ F64::SIGN, env)
+    }
+}
+
+impl Fadd for u16 {
+    #[inline(always)]
+    fn fadd(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::add::<F16>(self as u64, other as u64, env) as u16
+    }
+}
+
+impl Fsub for u16 {
+    #[inline(always)]
+    fn fsub(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::sub::<F16>(self as u64, other as u64, env) as u16
+    }
+}
+
+impl Fmul for u16 {
+    #[inline(always)]
+    fn fmul(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::mul::<F16>(self as u64, other as u64, env) as u16
+    }
+}
+
+impl Fdiv for u16 {
+    #[inline(always)]
+    fn fdiv(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::div::<F16>(self as u64, other as u64, env) as u16
+    }
+}
+
+impl Fsqrt for u16 {
+    #[inline(always)]
+    fn fsqrt(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::sqrt::<F16>(self as u64, env) as u16
+    }
+}
+
+impl Fsgnj for u16 {
+    #[inline(always)]
+    fn fsgnj(self, other: Self, _: &mut FloatEnv) -> Self {
+        ((self << 1) >> 1) | ((other >> 15) << 15)
+    }
+}
+
+impl Fsgnjn for u16 {
+    #[inline(always)]
+    fn fsgnjn(self, other: Self, _: &mut FloatEnv) -> Self {
+        ((self << 1) >> 1) | (!(other >> 15) << 15)
+    }
+}
+
+impl Fsgnjx for u16 {
+    #[inline(always)]
+    fn fsgnjx(self, other: Self, _: &mut FloatEnv) -> Self {
+        self ^ ((other >> 15) << 15)
+    }
+}
+
+impl Fmin for u16 {
+    #[inline(always)]
+    fn fmin(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::min::<F16>(self as u64, other as u64, env) as u16
+    }
+}
+
+impl Fmax for u16 {
+    #[inline(always)]
+    fn fmax(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::max::<F16>(self as u64, other as u64, env) as u16
+    }
+}
+
+impl Feq for u16 {
+    #[inline(always)]
+    fn feq(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::eq::<F16>(self as u64, other as u64, env) as u16
+    }
+}
+
+impl Flt for u16 {
+    #[inline(always)]
+    fn flt(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::lt::<F16>(self as u64, other as u64, env) as u16
+    }
+}
+
+impl Fle for u16 {
+    #[inline(always)]
+    fn fle(self, other: Self, env: &mut FloatEnv) -> Self {
+        softfloat::le::<F16>(self as u64, other as u64, env) as u16
+    }
+}
+
+impl Fclass for u16 {
+    #[inline(always)]
+    fn fclass(self, _: Self, _: &mut FloatEnv) -> Self {
+        softfloat::classify::<F16>(self as u64) as u16
+    }
+}
+
+impl Fcvtwh for u16 {
+    #[inline(always)]
+    fn fcvtwh(self, env: &mut FloatEnv) -> u32 {
+        softfloat::to_int::<F16>(self as u64, true, 32, env) as u32
+    }
+}
+
+impl Fcvtwuh for u16 {
+    #[inline(always)]
+    fn fcvtwuh(self, env: &mut FloatEnv) -> u32 {
+        softfloat::to_int::<F16>(self as u64, false, 32, env) as u32
+    }
+}
+
+impl Fcvtlh for u16 {
+    #[inline(always)]
+    fn fcvtlh(self, env: &mut FloatEnv) -> u64 {
+        softfloat::to_int::<F16>(self as u64, true, 64, env)
+    }
+}
+
+impl Fcvtluh for u16 {
+    #[inline(always)]
+    fn fcvtluh(self, env: &mut FloatEnv) -> u64 {
+        softfloat::to_int::<F16>(self as u64, false, 64, env)
+    }
+}
+
+impl Fcvthw for u64 {
+    #[inline(always)]
+    fn fcvthw(self, env: &mut FloatEnv) -> u16 {
+        softfloat::from_int::<F16>(self, true, 32, env) as u16
+    }
+}
+
+impl Fcvthwu for u64 {
+    #[inline(always)]
+    fn fcvthwu(self, env: &mut FloatEnv) -> u16 {
+        softfloat::from_int::<F16>(self, false, 32, env) as u16
+    }
+}
+
+impl Fcvthl for u64 {
+    #[inline(always)]
+    fn fcvthl(self, env: &mut FloatEnv) -> u16 {
+        softfloat::from_int::<F16>(self, true, 64, env) as u16
+    }
+}
+
+impl Fcvthlu for u64 {
+    #[inline(always)]
+    fn fcvthlu(self, env: &mut FloatEnv) -> u16 {
+        softfloat::from_int::<F16>(self, false, 64, env) as u16
+    }
+}
+
+impl Fmvxh for u16 {
+    #[inline(always)]
+    fn fmvxh(self, _: &mut FloatEnv) -> u64 {
+        self as i16 as u64
+    }
+}
+
+impl Fmvhx for u64 {
+    #[inline(always)]
+    fn fmvhx(self, _: &mut FloatEnv) -> u16 {
+        self as u16
+    }
+}
+
+impl Fcvtsh for u16 {
+    #[inline(always)]
+    fn fcvtsh(self, env: &mut FloatEnv) -> u32 {
+        softfloat::convert::<F16, F32>(self as u64, env) as u32
+    }
+}
+
+impl Fcvths for u32 {
+    #[inline(always)]
+    fn fcvths(self, env: &mut FloatEnv) -> u16 {
+        softfloat::convert::<F32, F16>(self as u64, env) as u16
+    }
+}
+
+impl Fcvtdh for u16 {
+    #[inline(always)]
+    fn fcvtdh(self, env: &mut FloatEnv) -> u64 {
+        softfloat::convert::<F16, F64>(self as u64, env)
+    }
+}
+
+impl Fcvthd for u64 {
+    #[inline(always)]
+    fn fcvthd(self, env: &mut FloatEnv) -> u16 {
+        softfloat::convert::<F64, F16>(self, env) as u16
+    }
+}
+
+impl Fmadd for u16 {
+    #[inline(always)]
+    fn fmadd(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        softfloat::fma::<F16>(self as u64, other as u64, addend as u64, env) as u16
+    }
+}
+
+impl Fmsub for u16 {
+    #[inline(always)]
+    fn fmsub(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        Fmadd::fmadd(self, other, addend ^ (F16::SIGN as u16), env)
+    }
+}
+
+impl Fnmsub for u16 {
+    #[inline(always)]
+    fn fnmsub(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        Fmadd::fmadd(self ^ (F16::SIGN as u16), other, addend, env)
+    }
+}
+
+impl Fnmadd for u16 {
+    #[inline(always)]
+    fn fnmadd(self, other: Self, addend: Self, env: &mut FloatEnv) -> Self {
+        Fmadd::fmadd(
+            self ^ (F16::SIGN as u16),
+            other,
+            addend ^ (F16::SIGN as u16),
+            env,
+        )
+    }
+}
+
+// Zfa, fminm and fmaxm return the canonical NaN for any NaN operand and the
+// quiet compares only raise NV for signaling NaNs, otherwise both defer to
+// the IEEE ops.
+macro_rules! impl_zfa {
+    ($t:ty, $f:ty) => {
+        impl Fli for $t {
+            #[inline(always)]
+            fn fli(index: u8) -> Self {
+                softfloat::fli::<$f>(index) as $t
+            }
+        }
+
+        impl Fminm for $t {
+            #[inline(always)]
+            fn fminm(self, other: Self, env: &mut FloatEnv) -> Self {
+                let min = Fmin::fmin(self, other, env);
+                if softfloat::is_nan::<$f>(self as u64) || softfloat::is_nan::<$f>(other as u64) {
+                    <$f>::NAN as $t
+                } else {
+                    min
+                }
+            }
+        }
+
+        impl Fmaxm for $t {
+            #[inline(always)]
+            fn fmaxm(self, other: Self, env: &mut FloatEnv) -> Self {
+                let max = Fmax::fmax(self, other, env);
+                if softfloat::is_nan::<$f>(self as u64) || softfloat::is_nan::<$f>(other as u64) {
+                    <$f>::NAN as $t
+                } else {
+                    max
+                }
+            }
+        }
+
+        impl Fround for $t {
+            #[inline(always)]
+            fn fround(self, _: Self, env: &mut FloatEnv) -> Self {
+                softfloat::round_int::<$f>(self as u64, false, env) as $t
+            }
+        }
+
+        impl Froundnx for $t {
+            #[inline(always)]
+            fn froundnx(self, _: Self, env: &mut FloatEnv) -> Self {
+                softfloat::round_int::<$f>(self as u64, true, env) as $t
+            }
+        }
+
+        impl Fleq for $t {
+            #[inline(always)]
+            fn fleq(self, other: Self, env: &mut FloatEnv) -> Self {
+                if softfloat::is_nan::<$f>(self as u64) || softfloat::is_nan::<$f>(other as u64) {
+                    if softfloat::is_signaling::<$f>(self as u64)
+                        || softfloat::is_signaling::<$f>(other as u64)
+                    {
+                        env.flags |= softfloat::NV;
+                    }
+                    return 0;
+                }
+                Fle::fle(self, other, env)
+            }
+        }
+
+        impl Fltq for $t {
+            #[inline(always)]
+            fn fltq(self, other: Self, env: &mut FloatEnv) -> Self {
+                if softfloat::is_nan::<$f>(self as u64) || softfloat::is_nan::<$f>(other as u64) {
+                    if softfloat::is_signaling::<$f>(self as u64)
+                        || softfloat::is_signaling::<$f>(other as u64)
+                    {
+                        env.flags |= softfloat::NV;
+                    }
+                    return 0;
+                }
+                Flt::flt(self, other, env)
+            }
+        }
+    };
+}
+
+impl_zfa!(u16, F16);
+impl_zfa!(u32, F32);
+impl_zfa!(u64, F64);
+
+impl Fcvtmodwd for u64 {
+    #[inline(always)]
+    fn fcvtmodwd(self, _: Self, env: &mut FloatEnv) -> Self {
+        softfloat::to_int_mod::<F64>(self, 32, env) as i32 as u64
+    }
+}
+
+// The RV32 moves between a double and an integer register pair
+
+impl Fmvhxd for u64 {
+    #[inline(always)]
+    fn fmvhxd(self, _: Self, _: &mut FloatEnv) -> Self {
+        self >> 32
+    }
+}
+
+impl Fmvpdx for u64 {
+    #[inline(always)]
+    fn fmvpdx(self, other: Self, _: &mut FloatEnv) -> Self {
+        other << 32 | self as u32 as u64
+    }
+}
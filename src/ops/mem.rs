@@ -1,225 +1,261 @@
 use crate::{
-    error::Error,
-    mem::{read, write, I16, I32, I64, U16, U32, U64},
+    mem::{I16, I32, I64, U16, U32, U64},
+    mmu::Bus,
+    num::As,
+    trap::Exception,
 };
 
 pub trait Lb: Sized {
-    fn lb(memory: &[u8], addr: usize) -> Result<Self, Error>;
+    fn lb(memory: &mut Bus, addr: usize) -> Result<Self, Exception>;
 }
 
 pub trait Lbu: Sized {
-    fn lbu(memory: &[u8], addr: usize) -> Result<Self, Error>;
+    fn lbu(memory: &mut Bus, addr: usize) -> Result<Self, Exception>;
 }
 
 pub trait Lh: Sized {
-    fn lh(memory: &[u8], addr: usize) -> Result<Self, Error>;
+    fn lh(memory: &mut Bus, addr: usize) -> Result<Self, Exception>;
 }
 
 pub trait Lhu: Sized {
-    fn lhu(memory: &[u8], addr: usize) -> Result<Self, Error>;
+    fn lhu(memory: &mut Bus, addr: usize) -> Result<Self, Exception>;
 }
 
 pub trait Lw: Sized {
-    fn lw(memory: &[u8], addr: usize) -> Result<Self, Error>;
+    fn lw(memory: &mut Bus, addr: usize) -> Result<Self, Exception>;
 }
 
 pub trait Lwu: Sized {
-    fn lwu(memory: &[u8], addr: usize) -> Result<Self, Error>;
+    fn lwu(memory: &mut Bus, addr: usize) -> Result<Self, Exception>;
 }
 
 pub trait Ld: Sized {
-    fn ld(memory: &[u8], addr: usize) -> Result<Self, Error>;
+    fn ld(memory: &mut Bus, addr: usize) -> Result<Self, Exception>;
 }
 
 pub trait Sb: Sized {
-    fn sb(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error>;
+    fn sb(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception>;
 }
 
 pub trait Sh: Sized {
-    fn sh(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error>;
+    fn sh(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception>;
 }
 
 pub trait Sw: Sized {
-    fn sw(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error>;
+    fn sw(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception>;
 }
 
 pub trait Sd: Sized {
-    fn sd(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error>;
+    fn sd(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception>;
 }
 
 pub trait Flw: Sized {
-    fn flw(memory: &mut [u8], addr: usize) -> Result<Self, Error>;
+    fn flw(memory: &mut Bus, addr: usize) -> Result<Self, Exception>;
 }
 
 pub trait Fsw: Sized {
-    fn fsw(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error>;
+    fn fsw(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception>;
+}
+
+pub trait Flh: Sized {
+    fn flh(memory: &mut Bus, addr: usize) -> Result<Self, Exception>;
+}
+
+pub trait Fsh: Sized {
+    fn fsh(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception>;
+}
+
+pub trait Fld: Sized {
+    fn fld(memory: &mut Bus, addr: usize) -> Result<Self, Exception>;
+}
+
+pub trait Fsd: Sized {
+    fn fsd(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception>;
 }
 
-#[allow(unused)]
 pub trait BaseLoad: Lb + Lbu + Lh + Lhu + Lw + Lwu + Ld {}
 
-#[allow(unused)]
 pub trait BaseStore: Sb + Sh + Sw + Sd {}
 
-// when D and/or Q extensions are implemented we could have something like:
+// when the Q extension is implemented we could have something like:
 // pub trait FloatLoad: Flw + Fld + Flq {}
 // pub trait FloatStore: Fsw + Fsd + Fsq {}
 
-impl Lb for u32 {
-    #[inline(always)]
-    fn lb(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(unsafe { core::mem::transmute::<i32, u32>(read::<i8>(memory, addr)? as i32) })
-    }
-}
+// Loads sign or zero extend the value read to XLEN bits, stores keep the low
+// bits of the source register. `Ld`, `Lwu` and `Sd` are only reachable on
+// RV64, where no truncation happens.
 
-impl Lbu for u32 {
+impl<T> Lb for T
+where
+    i8: As<T>,
+{
     #[inline(always)]
-    fn lbu(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(read::<u8>(memory, addr)? as u32)
+    fn lb(memory: &mut Bus, addr: usize) -> Result<Self, Exception> {
+        Ok(memory.read::<i8>(addr)?.r#as())
     }
 }
 
-impl Lh for u32 {
+impl<T> Lbu for T
+where
+    u8: As<T>,
+{
     #[inline(always)]
-    fn lh(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(unsafe { core::mem::transmute::<i32, u32>(read::<I16>(memory, addr)?.as_i16() as i32) })
+    fn lbu(memory: &mut Bus, addr: usize) -> Result<Self, Exception> {
+        Ok(memory.read::<u8>(addr)?.r#as())
     }
 }
 
-impl Lhu for u32 {
+impl<T> Lh for T
+where
+    i16: As<T>,
+{
     #[inline(always)]
-    fn lhu(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(read::<U16>(memory, addr)?.as_u16() as u32)
+    fn lh(memory: &mut Bus, addr: usize) -> Result<Self, Exception> {
+        Ok(memory.read::<I16>(addr)?.as_i16().r#as())
     }
 }
 
-impl Lw for u32 {
+impl<T> Lhu for T
+where
+    u16: As<T>,
+{
     #[inline(always)]
-    fn lw(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(unsafe { core::mem::transmute::<i32, u32>(read::<I32>(memory, addr)?.as_i32()) })
+    fn lhu(memory: &mut Bus, addr: usize) -> Result<Self, Exception> {
+        Ok(memory.read::<U16>(addr)?.as_u16().r#as())
     }
 }
 
-impl Lb for u64 {
+impl<T> Lw for T
+where
+    i32: As<T>,
+{
     #[inline(always)]
-    fn lb(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(unsafe { core::mem::transmute::<i64, u64>(read::<i8>(memory, addr)? as i64) })
+    fn lw(memory: &mut Bus, addr: usize) -> Result<Self, Exception> {
+        Ok(memory.read::<I32>(addr)?.as_i32().r#as())
     }
 }
 
-impl Lbu for u64 {
+impl<T> Lwu for T
+where
+    u32: As<T>,
+{
     #[inline(always)]
-    fn lbu(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(read::<u8>(memory, addr)? as u64)
+    fn lwu(memory: &mut Bus, addr: usize) -> Result<Self, Exception> {
+        Ok(memory.read::<U32>(addr)?.as_u32().r#as())
     }
 }
 
-impl Lh for u64 {
+impl<T> Ld for T
+where
+    i64: As<T>,
+{
     #[inline(always)]
-    fn lh(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(unsafe { core::mem::transmute::<i64, u64>(read::<I16>(memory, addr)?.as_i16() as i64) })
+    fn ld(memory: &mut Bus, addr: usize) -> Result<Self, Exception> {
+        Ok(memory.read::<I64>(addr)?.as_i64().r#as())
     }
 }
 
-impl Lhu for u64 {
+impl<T> Sb for T
+where
+    T: As<u8>,
+{
     #[inline(always)]
-    fn lhu(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(read::<U16>(memory, addr)?.as_u16() as u64)
+    fn sb(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception> {
+        let byte: u8 = src.r#as();
+        memory.write(&byte, addr)
     }
 }
 
-impl Lw for u64 {
+impl<T> Sh for T
+where
+    T: As<u16>,
+{
     #[inline(always)]
-    fn lw(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(unsafe { core::mem::transmute::<i64, u64>(read::<I32>(memory, addr)?.as_i32() as i64) })
+    fn sh(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception> {
+        memory.write(&U16::new(src.r#as()), addr)
     }
 }
 
-impl Lwu for u64 {
+impl<T> Sw for T
+where
+    T: As<u32>,
+{
     #[inline(always)]
-    fn lwu(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(read::<U32>(memory, addr)?.as_u32() as u64)
+    fn sw(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception> {
+        memory.write(&U32::new(src.r#as()), addr)
     }
 }
 
-impl Ld for u64 {
+impl<T> Sd for T
+where
+    T: As<u64>,
+{
     #[inline(always)]
-    fn ld(memory: &[u8], addr: usize) -> Result<Self, Error> {
-        Ok(unsafe { core::mem::transmute::<i64, u64>(read::<I64>(memory, addr)?.as_i64()) })
+    fn sd(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception> {
+        memory.write(&U64::new(src.r#as()), addr)
     }
 }
 
-impl Sb for u32 {
-    #[inline(always)]
-    fn sb(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error> {
-        write(&(src as u8), memory, addr)
-    }
-}
+impl<T: Lb + Lbu + Lh + Lhu + Lw + Lwu + Ld> BaseLoad for T {}
 
-impl Sh for u32 {
-    #[inline(always)]
-    fn sh(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error> {
-        write(&U16::new(src as u16), memory, addr)
-    }
-}
-
-impl Sw for u32 {
-    #[inline(always)]
-    fn sw(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error> {
-        write(&U32::new(src), memory, addr)
-    }
-}
+impl<T: Sb + Sh + Sw + Sd> BaseStore for T {}
 
-impl Sb for u64 {
+impl<T> Flw for T
+where
+    u32: As<T>,
+{
     #[inline(always)]
-    fn sb(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error> {
-        write(&(src as u8), memory, addr)
+    fn flw(memory: &mut Bus, addr: usize) -> Result<Self, Exception> {
+        Ok(memory.read::<U32>(addr)?.as_u32().r#as())
     }
 }
 
-impl Sh for u64 {
-    #[inline(always)]
-    fn sh(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error> {
-        write(&U16::new(src as u16), memory, addr)
-    }
-}
-impl Sw for u64 {
-    #[inline(always)]
-    fn sw(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error> {
-        write(&U32::new(src as u32), memory, addr)
-    }
-}
-impl Sd for u64 {
+impl<T> Fsw for T
+where
+    T: As<u32>,
+{
     #[inline(always)]
-    fn sd(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error> {
-        write(&U64::new(src), memory, addr)
+    fn fsw(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception> {
+        memory.write(&U32::new(src.r#as()), addr)
     }
 }
 
-impl Flw for u32 {
+impl<T> Flh for T
+where
+    u16: As<T>,
+{
     #[inline(always)]
-    fn flw(memory: &mut [u8], addr: usize) -> Result<Self, Error> {
-        Ok(read::<U32>(memory, addr)?.as_u32())
+    fn flh(memory: &mut Bus, addr: usize) -> Result<Self, Exception> {
+        Ok(memory.read::<U16>(addr)?.as_u16().r#as())
     }
 }
 
-impl Fsw for u32 {
+impl<T> Fsh for T
+where
+    T: As<u16>,
+{
     #[inline(always)]
-    fn fsw(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error> {
-        write(&U32::new(src), memory, addr)
+    fn fsh(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception> {
+        memory.write(&U16::new(src.r#as()), addr)
     }
 }
 
-impl Flw for u64 {
+impl<T> Fld for T
+where
+    u64: As<T>,
+{
     #[inline(always)]
-    fn flw(memory: &mut [u8], addr: usize) -> Result<Self, Error> {
-        Ok(read::<U32>(memory, addr)?.as_u32() as u64)
+    fn fld(memory: &mut Bus, addr: usize) -> Result<Self, Exception> {
+        Ok(memory.read::<U64>(addr)?.as_u64().r#as())
     }
 }
 
-impl Fsw for u64 {
+impl<T> Fsd for T
+where
+    T: As<u64>,
+{
     #[inline(always)]
-    fn fsw(src: Self, memory: &mut [u8], addr: usize) -> Result<(), Error> {
-        write(&U32::new(src as _), memory, addr)
+    fn fsd(src: Self, memory: &mut Bus, addr: usize) -> Result<(), Exception> {
+        memory.write(&U64::new(src.r#as()), addr)
     }
 }
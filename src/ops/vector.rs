@@ -0,0 +1,692 @@
+use super::math::{
+    Fadd, Fclass, Fdiv, Feq, Fle, Flt, Fmadd, Fmax, Fmin, Fmsub, Fmul, Fnmadd, Fnmsub, Fsgnj,
+    Fsgnjn, Fsgnjx, Fsqrt, Fsub,
+};
+use crate::softfloat::{self, FloatEnv, RoundingMode, F32, F64, NX};
+
+// Element operations of the V extension. Elements are passed zero extended in
+// a `u64` and results are truncated to the destination width by the caller.
+// Binary ops take `vs2` first and the `vs1`, scalar or immediate operand
+// second, ternary ones take the destination element, that operand and `vs2`.
+// Widening ops see SEW wide sources and narrowing ops a 2*SEW wide `vs2`,
+// `sew` is always the narrow width.
+
+/// The state an element operation may depend on or update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorEnv {
+    /// Selected element width in bits.
+    pub sew: u32,
+    /// Fixed point rounding mode, as encoded in `vxrm`.
+    pub vxrm: u8,
+    /// Set by fixed point ops that saturated.
+    pub vxsat: bool,
+    pub float: FloatEnv,
+}
+
+impl VectorEnv {
+    #[inline(always)]
+    pub const fn new(sew: u32, vxrm: u8, rm: RoundingMode) -> Self {
+        Self {
+            sew,
+            vxrm,
+            vxsat: false,
+            float: FloatEnv::new(rm),
+        }
+    }
+}
+
+#[inline(always)]
+const fn ones(bits: u32) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+#[inline(always)]
+const fn sext(value: u64, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    ((value << shift) as i64) >> shift
+}
+
+/// Shifts `value` right by `shift` bits, rounding as `vxrm` says.
+#[inline(always)]
+fn roundoff(value: i128, shift: u32, vxrm: u8) -> i128 {
+    if shift == 0 {
+        return value;
+    }
+    let bits = value as u128;
+    let half = (bits >> (shift - 1)) & 1 != 0;
+    let below_half = bits & ((1 << (shift - 1)) - 1) != 0;
+    let lsb = (bits >> shift) & 1 != 0;
+    let up = match vxrm {
+        // round to nearest up
+        0b00 => half,
+        // round to nearest even
+        0b01 => half && (below_half || lsb),
+        // round down
+        0b10 => false,
+        // round to odd
+        _ => !lsb && (half || below_half),
+    };
+    (value >> shift) + up as i128
+}
+
+/// Clamps `value` to the signed range of `bits` bits, flagging saturation.
+#[inline(always)]
+fn clip_signed(value: i128, bits: u32, env: &mut VectorEnv) -> u64 {
+    let max = (1i128 << (bits - 1)) - 1;
+    let min = -(1i128 << (bits - 1));
+    if value > max || value < min {
+        env.vxsat = true;
+    }
+    value.clamp(min, max) as u64
+}
+
+/// Clamps `value` to the unsigned range of `bits` bits, flagging saturation.
+#[inline(always)]
+fn clip_unsigned(value: i128, bits: u32, env: &mut VectorEnv) -> u64 {
+    let max = ones(bits) as i128;
+    if value > max || value < 0 {
+        env.vxsat = true;
+    }
+    value.clamp(0, max) as u64
+}
+
+#[inline(always)]
+fn float_unary(
+    a: u64,
+    width: u32,
+    env: &mut VectorEnv,
+    op32: fn(u32, u32, &mut FloatEnv) -> u32,
+    op64: fn(u64, u64, &mut FloatEnv) -> u64,
+) -> u64 {
+    match width {
+        32 => op32(a as u32, 0, &mut env.float) as u64,
+        _ => op64(a, 0, &mut env.float),
+    }
+}
+
+#[inline(always)]
+fn float_binary(
+    a: u64,
+    b: u64,
+    width: u32,
+    env: &mut VectorEnv,
+    op32: fn(u32, u32, &mut FloatEnv) -> u32,
+    op64: fn(u64, u64, &mut FloatEnv) -> u64,
+) -> u64 {
+    match width {
+        32 => op32(a as u32, b as u32, &mut env.float) as u64,
+        _ => op64(a, b, &mut env.float),
+    }
+}
+
+#[inline(always)]
+fn float_ternary(
+    a: u64,
+    b: u64,
+    c: u64,
+    width: u32,
+    env: &mut VectorEnv,
+    op32: fn(u32, u32, u32, &mut FloatEnv) -> u32,
+    op64: fn(u64, u64, u64, &mut FloatEnv) -> u64,
+) -> u64 {
+    match width {
+        32 => op32(a as u32, b as u32, c as u32, &mut env.float) as u64,
+        _ => op64(a, b, c, &mut env.float),
+    }
+}
+
+/// Converts a SEW wide float to 2*SEW, exactly.
+#[inline(always)]
+fn widen(a: u64, env: &mut VectorEnv) -> u64 {
+    match env.sew {
+        32 => softfloat::convert::<F32, F64>(a, &mut env.float),
+        sew => unreachable!("no float format widens from {sew} bits"),
+    }
+}
+
+#[inline(always)]
+fn float_to_int(a: u64, width: u32, signed: bool, int_width: u32, env: &mut VectorEnv) -> u64 {
+    match width {
+        32 => softfloat::to_int::<F32>(a, signed, int_width, &mut env.float),
+        _ => softfloat::to_int::<F64>(a, signed, int_width, &mut env.float),
+    }
+}
+
+#[inline(always)]
+fn int_to_float(a: u64, width: u32, signed: bool, int_width: u32, env: &mut VectorEnv) -> u64 {
+    match width {
+        32 => softfloat::from_int::<F32>(a, signed, int_width, &mut env.float),
+        _ => softfloat::from_int::<F64>(a, signed, int_width, &mut env.float),
+    }
+}
+
+/// Runs `op` rounding towards zero whatever `frm` says.
+#[inline(always)]
+fn rtz(env: &mut VectorEnv, op: impl FnOnce(&mut VectorEnv) -> u64) -> u64 {
+    let rm = env.float.rm;
+    env.float.rm = RoundingMode::Rtz;
+    let value = op(env);
+    env.float.rm = rm;
+    value
+}
+
+macro_rules! unary {
+    ($($(#[$attr:meta])* $trait:ident::$method:ident($a:ident, $env:ident) $body:block)*) => {
+        $(
+            $(#[$attr])*
+            pub trait $trait {
+                fn $method(self, env: &mut VectorEnv) -> Self;
+            }
+
+            impl $trait for u64 {
+                #[inline(always)]
+                #[allow(unused_variables)]
+                fn $method(self, env: &mut VectorEnv) -> Self {
+                    let ($a, $env) = (self, env);
+                    $body
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! binary {
+    ($($(#[$attr:meta])* $trait:ident::$method:ident($a:ident, $b:ident, $env:ident) $body:block)*) => {
+        $(
+            $(#[$attr])*
+            pub trait $trait {
+                fn $method(self, other: Self, env: &mut VectorEnv) -> Self;
+            }
+
+            impl $trait for u64 {
+                #[inline(always)]
+                #[allow(unused_variables)]
+                fn $method(self, other: Self, env: &mut VectorEnv) -> Self {
+                    let ($a, $b, $env) = (self, other, env);
+                    $body
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! ternary {
+    ($($(#[$attr:meta])* $trait:ident::$method:ident($a:ident, $b:ident, $c:ident, $env:ident) $body:block)*) => {
+        $(
+            $(#[$attr])*
+            pub trait $trait {
+                fn $method(self, other: Self, third: Self, env: &mut VectorEnv) -> Self;
+            }
+
+            impl $trait for u64 {
+                #[inline(always)]
+                #[allow(unused_variables)]
+                fn $method(self, other: Self, third: Self, env: &mut VectorEnv) -> Self {
+                    let ($a, $b, $c, $env) = (self, other, third, env);
+                    $body
+                }
+            }
+        )*
+    };
+}
+
+// Integer arithmetic
+
+binary! {
+    Vadd::vadd(a, b, env) { a.wrapping_add(b) }
+    Vsub::vsub(a, b, env) { a.wrapping_sub(b) }
+    Vrsub::vrsub(a, b, env) { b.wrapping_sub(a) }
+    Vand::vand(a, b, env) { a & b }
+    Vor::vor(a, b, env) { a | b }
+    Vxor::vxor(a, b, env) { a ^ b }
+    Vsll::vsll(a, b, env) { a << (b & (env.sew as u64 - 1)) }
+    Vsrl::vsrl(a, b, env) { a >> (b & (env.sew as u64 - 1)) }
+    Vsra::vsra(a, b, env) { (sext(a, env.sew) >> (b & (env.sew as u64 - 1))) as u64 }
+    Vminu::vminu(a, b, env) { a.min(b) }
+    Vmin::vmin(a, b, env) { if sext(a, env.sew) < sext(b, env.sew) { a } else { b } }
+    Vmaxu::vmaxu(a, b, env) { a.max(b) }
+    Vmax::vmax(a, b, env) { if sext(a, env.sew) > sext(b, env.sew) { a } else { b } }
+    Vmul::vmul(a, b, env) { a.wrapping_mul(b) }
+    Vmulh::vmulh(a, b, env) {
+        ((sext(a, env.sew) as i128 * sext(b, env.sew) as i128) >> env.sew) as u64
+    }
+    Vmulhu::vmulhu(a, b, env) { ((a as u128 * b as u128) >> env.sew) as u64 }
+    /// Signed `vs2` times unsigned `vs1`.
+    Vmulhsu::vmulhsu(a, b, env) { ((sext(a, env.sew) as i128 * b as i128) >> env.sew) as u64 }
+    /// Division by zero gives all ones, as for `divu`.
+    Vdivu::vdivu(a, b, env) { a.checked_div(b).unwrap_or(u64::MAX) }
+    /// The overflowing division wraps to the dividend once truncated.
+    Vdiv::vdiv(a, b, env) {
+        match sext(b, env.sew) {
+            0 => u64::MAX,
+            b => sext(a, env.sew).wrapping_div(b) as u64,
+        }
+    }
+    Vremu::vremu(a, b, env) { a.checked_rem(b).unwrap_or(a) }
+    Vrem::vrem(a, b, env) {
+        match sext(b, env.sew) {
+            0 => a,
+            b => sext(a, env.sew).wrapping_rem(b) as u64,
+        }
+    }
+}
+
+// Integer compares, the result is a mask bit
+
+binary! {
+    Vmseq::vmseq(a, b, env) { (a == b) as u64 }
+    Vmsne::vmsne(a, b, env) { (a != b) as u64 }
+    Vmsltu::vmsltu(a, b, env) { (a < b) as u64 }
+    Vmslt::vmslt(a, b, env) { (sext(a, env.sew) < sext(b, env.sew)) as u64 }
+    Vmsleu::vmsleu(a, b, env) { (a <= b) as u64 }
+    Vmsle::vmsle(a, b, env) { (sext(a, env.sew) <= sext(b, env.sew)) as u64 }
+    Vmsgtu::vmsgtu(a, b, env) { (a > b) as u64 }
+    Vmsgt::vmsgt(a, b, env) { (sext(a, env.sew) > sext(b, env.sew)) as u64 }
+}
+
+// Widening integer arithmetic, the `.w` forms take a 2*SEW wide `vs2`
+
+binary! {
+    Vwaddu::vwaddu(a, b, env) { a + b }
+    Vwadd::vwadd(a, b, env) { (sext(a, env.sew) + sext(b, env.sew)) as u64 }
+    Vwsubu::vwsubu(a, b, env) { a.wrapping_sub(b) }
+    Vwsub::vwsub(a, b, env) { (sext(a, env.sew) - sext(b, env.sew)) as u64 }
+    VwadduW::vwaddu_w(a, b, env) { a.wrapping_add(b) }
+    VwaddW::vwadd_w(a, b, env) { a.wrapping_add(sext(b, env.sew) as u64) }
+    VwsubuW::vwsubu_w(a, b, env) { a.wrapping_sub(b) }
+    VwsubW::vwsub_w(a, b, env) { a.wrapping_sub(sext(b, env.sew) as u64) }
+    Vwmulu::vwmulu(a, b, env) { a * b }
+    Vwmul::vwmul(a, b, env) { (sext(a, env.sew) * sext(b, env.sew)) as u64 }
+    /// Signed `vs2` times unsigned `vs1`.
+    Vwmulsu::vwmulsu(a, b, env) { (sext(a, env.sew) * b as i64) as u64 }
+}
+
+// Narrowing shifts of a 2*SEW wide `vs2`
+
+binary! {
+    Vnsrl::vnsrl(a, b, env) { a >> (b & (2 * env.sew as u64 - 1)) }
+    Vnsra::vnsra(a, b, env) { (sext(a, 2 * env.sew) >> (b & (2 * env.sew as u64 - 1))) as u64 }
+}
+
+// Integer extension, from SEW/2, SEW/4 or SEW/8 wide sources
+
+unary! {
+    VzextVf2::vzext_vf2(a, env) { a }
+    VzextVf4::vzext_vf4(a, env) { a }
+    VzextVf8::vzext_vf8(a, env) { a }
+    VsextVf2::vsext_vf2(a, env) { sext(a, env.sew / 2) as u64 }
+    VsextVf4::vsext_vf4(a, env) { sext(a, env.sew / 4) as u64 }
+    VsextVf8::vsext_vf8(a, env) { sext(a, env.sew / 8) as u64 }
+}
+
+// Add with carry and subtract with borrow, the third operand is the carry in
+
+ternary! {
+    Vadc::vadc(a, b, carry, env) { a.wrapping_add(b).wrapping_add(carry) }
+    Vmadc::vmadc(a, b, carry, env) { ((a as u128 + b as u128 + carry as u128) >> env.sew) as u64 & 1 }
+    Vsbc::vsbc(a, b, borrow, env) { a.wrapping_sub(b).wrapping_sub(borrow) }
+    Vmsbc::vmsbc(a, b, borrow, env) { ((a as u128) < b as u128 + borrow as u128) as u64 }
+}
+
+// Multiply-add, `vd` first and the multiplier operand second
+
+ternary! {
+    Vmacc::vmacc(vd, a, vs2, env) { a.wrapping_mul(vs2).wrapping_add(vd) }
+    Vnmsac::vnmsac(vd, a, vs2, env) { vd.wrapping_sub(a.wrapping_mul(vs2)) }
+    Vmadd::vmadd(vd, a, vs2, env) { a.wrapping_mul(vd).wrapping_add(vs2) }
+    Vnmsub::vnmsub(vd, a, vs2, env) { vs2.wrapping_sub(a.wrapping_mul(vd)) }
+    Vwmaccu::vwmaccu(vd, a, vs2, env) { vd.wrapping_add(a * vs2) }
+    Vwmacc::vwmacc(vd, a, vs2, env) {
+        vd.wrapping_add((sext(a, env.sew) * sext(vs2, env.sew)) as u64)
+    }
+    /// Signed `vs1` or `rs1` times unsigned `vs2`.
+    Vwmaccsu::vwmaccsu(vd, a, vs2, env) { vd.wrapping_add((sext(a, env.sew) * vs2 as i64) as u64) }
+    /// Unsigned `rs1` times signed `vs2`.
+    Vwmaccus::vwmaccus(vd, a, vs2, env) { vd.wrapping_add((a as i64 * sext(vs2, env.sew)) as u64) }
+}
+
+// Fixed point arithmetic, saturating ops set `vxsat`
+
+binary! {
+    Vsaddu::vsaddu(a, b, env) { clip_unsigned(a as i128 + b as i128, env.sew, env) }
+    Vsadd::vsadd(a, b, env) {
+        clip_signed(sext(a, env.sew) as i128 + sext(b, env.sew) as i128, env.sew, env)
+    }
+    Vssubu::vssubu(a, b, env) { clip_unsigned(a as i128 - b as i128, env.sew, env) }
+    Vssub::vssub(a, b, env) {
+        clip_signed(sext(a, env.sew) as i128 - sext(b, env.sew) as i128, env.sew, env)
+    }
+    Vaaddu::vaaddu(a, b, env) { roundoff(a as i128 + b as i128, 1, env.vxrm) as u64 }
+    Vaadd::vaadd(a, b, env) {
+        roundoff(sext(a, env.sew) as i128 + sext(b, env.sew) as i128, 1, env.vxrm) as u64
+    }
+    Vasubu::vasubu(a, b, env) { roundoff(a as i128 - b as i128, 1, env.vxrm) as u64 }
+    Vasub::vasub(a, b, env) {
+        roundoff(sext(a, env.sew) as i128 - sext(b, env.sew) as i128, 1, env.vxrm) as u64
+    }
+    /// Fractional multiply, only `-1 * -1` saturates.
+    Vsmul::vsmul(a, b, env) {
+        let product = sext(a, env.sew) as i128 * sext(b, env.sew) as i128;
+        clip_signed(roundoff(product, env.sew - 1, env.vxrm), env.sew, env)
+    }
+    Vssrl::vssrl(a, b, env) { roundoff(a as i128, (b & (env.sew as u64 - 1)) as u32, env.vxrm) as u64 }
+    Vssra::vssra(a, b, env) {
+        roundoff(sext(a, env.sew) as i128, (b & (env.sew as u64 - 1)) as u32, env.vxrm) as u64
+    }
+    Vnclipu::vnclipu(a, b, env) {
+        let shift = (b & (2 * env.sew as u64 - 1)) as u32;
+        clip_unsigned(roundoff(a as i128, shift, env.vxrm), env.sew, env)
+    }
+    Vnclip::vnclip(a, b, env) {
+        let shift = (b & (2 * env.sew as u64 - 1)) as u32;
+        clip_signed(roundoff(sext(a, 2 * env.sew) as i128, shift, env.vxrm), env.sew, env)
+    }
+}
+
+// Mask logic, on single mask bits
+
+binary! {
+    Vmnand::vmnand(a, b, env) { !(a & b) & 1 }
+    /// `vs2 & !vs1`
+    Vmandn::vmandn(a, b, env) { a & !b & 1 }
+    Vmnor::vmnor(a, b, env) { !(a | b) & 1 }
+    /// `vs2 | !vs1`
+    Vmorn::vmorn(a, b, env) { (a | !b) & 1 }
+    Vmxnor::vmxnor(a, b, env) { !(a ^ b) & 1 }
+}
+
+// Floating point arithmetic, SEW selects the format
+
+binary! {
+    Vfadd::vfadd(a, b, env) { float_binary(a, b, env.sew, env, Fadd::fadd, Fadd::fadd) }
+    Vfsub::vfsub(a, b, env) { float_binary(a, b, env.sew, env, Fsub::fsub, Fsub::fsub) }
+    Vfrsub::vfrsub(a, b, env) { float_binary(b, a, env.sew, env, Fsub::fsub, Fsub::fsub) }
+    Vfmul::vfmul(a, b, env) { float_binary(a, b, env.sew, env, Fmul::fmul, Fmul::fmul) }
+    Vfdiv::vfdiv(a, b, env) { float_binary(a, b, env.sew, env, Fdiv::fdiv, Fdiv::fdiv) }
+    Vfrdiv::vfrdiv(a, b, env) { float_binary(b, a, env.sew, env, Fdiv::fdiv, Fdiv::fdiv) }
+    Vfmin::vfmin(a, b, env) { float_binary(a, b, env.sew, env, Fmin::fmin, Fmin::fmin) }
+    Vfmax::vfmax(a, b, env) { float_binary(a, b, env.sew, env, Fmax::fmax, Fmax::fmax) }
+    /// Magnitude of `vs2`, sign of the other operand.
+    Vfsgnj::vfsgnj(a, b, env) { float_binary(a, b, env.sew, env, Fsgnj::fsgnj, Fsgnj::fsgnj) }
+    Vfsgnjn::vfsgnjn(a, b, env) { float_binary(a, b, env.sew, env, Fsgnjn::fsgnjn, Fsgnjn::fsgnjn) }
+    Vfsgnjx::vfsgnjx(a, b, env) { float_binary(a, b, env.sew, env, Fsgnjx::fsgnjx, Fsgnjx::fsgnjx) }
+    Vmfeq::vmfeq(a, b, env) { float_binary(a, b, env.sew, env, Feq::feq, Feq::feq) }
+    Vmfne::vmfne(a, b, env) { 1 - float_binary(a, b, env.sew, env, Feq::feq, Feq::feq) }
+    Vmflt::vmflt(a, b, env) { float_binary(a, b, env.sew, env, Flt::flt, Flt::flt) }
+    Vmfle::vmfle(a, b, env) { float_binary(a, b, env.sew, env, Fle::fle, Fle::fle) }
+    Vmfgt::vmfgt(a, b, env) { float_binary(b, a, env.sew, env, Flt::flt, Flt::flt) }
+    Vmfge::vmfge(a, b, env) { float_binary(b, a, env.sew, env, Fle::fle, Fle::fle) }
+}
+
+// Widening floating point arithmetic, computed in the 2*SEW format
+
+binary! {
+    Vfwadd::vfwadd(a, b, env) {
+        let (a, b) = (widen(a, env), widen(b, env));
+        a.fadd(b, &mut env.float)
+    }
+    Vfwsub::vfwsub(a, b, env) {
+        let (a, b) = (widen(a, env), widen(b, env));
+        a.fsub(b, &mut env.float)
+    }
+    Vfwmul::vfwmul(a, b, env) {
+        let (a, b) = (widen(a, env), widen(b, env));
+        a.fmul(b, &mut env.float)
+    }
+    VfwaddW::vfwadd_w(a, b, env) {
+        let b = widen(b, env);
+        a.fadd(b, &mut env.float)
+    }
+    VfwsubW::vfwsub_w(a, b, env) {
+        let b = widen(b, env);
+        a.fsub(b, &mut env.float)
+    }
+}
+
+// Floating point multiply-add, `vd` first and the multiplier operand second
+
+ternary! {
+    Vfmacc::vfmacc(vd, a, vs2, env) { float_ternary(a, vs2, vd, env.sew, env, Fmadd::fmadd, Fmadd::fmadd) }
+    Vfnmacc::vfnmacc(vd, a, vs2, env) {
+        float_ternary(a, vs2, vd, env.sew, env, Fnmadd::fnmadd, Fnmadd::fnmadd)
+    }
+    Vfmsac::vfmsac(vd, a, vs2, env) { float_ternary(a, vs2, vd, env.sew, env, Fmsub::fmsub, Fmsub::fmsub) }
+    Vfnmsac::vfnmsac(vd, a, vs2, env) {
+        float_ternary(a, vs2, vd, env.sew, env, Fnmsub::fnmsub, Fnmsub::fnmsub)
+    }
+    Vfmadd::vfmadd(vd, a, vs2, env) { float_ternary(a, vd, vs2, env.sew, env, Fmadd::fmadd, Fmadd::fmadd) }
+    Vfnmadd::vfnmadd(vd, a, vs2, env) {
+        float_ternary(a, vd, vs2, env.sew, env, Fnmadd::fnmadd, Fnmadd::fnmadd)
+    }
+    Vfmsub::vfmsub(vd, a, vs2, env) { float_ternary(a, vd, vs2, env.sew, env, Fmsub::fmsub, Fmsub::fmsub) }
+    Vfnmsub::vfnmsub(vd, a, vs2, env) {
+        float_ternary(a, vd, vs2, env.sew, env, Fnmsub::fnmsub, Fnmsub::fnmsub)
+    }
+    Vfwmacc::vfwmacc(vd, a, vs2, env) {
+        let (a, vs2) = (widen(a, env), widen(vs2, env));
+        a.fmadd(vs2, vd, &mut env.float)
+    }
+    Vfwnmacc::vfwnmacc(vd, a, vs2, env) {
+        let (a, vs2) = (widen(a, env), widen(vs2, env));
+        a.fnmadd(vs2, vd, &mut env.float)
+    }
+    Vfwmsac::vfwmsac(vd, a, vs2, env) {
+        let (a, vs2) = (widen(a, env), widen(vs2, env));
+        a.fmsub(vs2, vd, &mut env.float)
+    }
+    Vfwnmsac::vfwnmsac(vd, a, vs2, env) {
+        let (a, vs2) = (widen(a, env), widen(vs2, env));
+        a.fnmsub(vs2, vd, &mut env.float)
+    }
+}
+
+// Floating point unary ops
+
+unary! {
+    Vfsqrt::vfsqrt(a, env) { float_unary(a, env.sew, env, Fsqrt::fsqrt, Fsqrt::fsqrt) }
+    Vfclass::vfclass(a, env) { float_unary(a, env.sew, env, Fclass::fclass, Fclass::fclass) }
+    Vfrsqrt7::vfrsqrt7(a, env) {
+        match env.sew {
+            32 => softfloat::rsqrt7::<F32>(a, &mut env.float),
+            _ => softfloat::rsqrt7::<F64>(a, &mut env.float),
+        }
+    }
+    Vfrec7::vfrec7(a, env) {
+        match env.sew {
+            32 => softfloat::rec7::<F32>(a, &mut env.float),
+            _ => softfloat::rec7::<F64>(a, &mut env.float),
+        }
+    }
+}
+
+// Conversions. The widening ones read SEW and write 2*SEW wide elements, the
+// narrowing ones read 2*SEW and write SEW wide elements.
+
+unary! {
+    VfcvtXuF::vfcvt_xu_f(a, env) { float_to_int(a, env.sew, false, env.sew, env) }
+    VfcvtXF::vfcvt_x_f(a, env) { float_to_int(a, env.sew, true, env.sew, env) }
+    VfcvtRtzXuF::vfcvt_rtz_xu_f(a, env) {
+        rtz(env, |env| float_to_int(a, env.sew, false, env.sew, env))
+    }
+    VfcvtRtzXF::vfcvt_rtz_x_f(a, env) { rtz(env, |env| float_to_int(a, env.sew, true, env.sew, env)) }
+    VfcvtFXu::vfcvt_f_xu(a, env) { int_to_float(a, env.sew, false, env.sew, env) }
+    VfcvtFX::vfcvt_f_x(a, env) { int_to_float(a, env.sew, true, env.sew, env) }
+    VfwcvtXuF::vfwcvt_xu_f(a, env) { float_to_int(a, env.sew, false, 2 * env.sew, env) }
+    VfwcvtXF::vfwcvt_x_f(a, env) { float_to_int(a, env.sew, true, 2 * env.sew, env) }
+    VfwcvtRtzXuF::vfwcvt_rtz_xu_f(a, env) {
+        rtz(env, |env| float_to_int(a, env.sew, false, 2 * env.sew, env))
+    }
+    VfwcvtRtzXF::vfwcvt_rtz_x_f(a, env) {
+        rtz(env, |env| float_to_int(a, env.sew, true, 2 * env.sew, env))
+    }
+    VfwcvtFXu::vfwcvt_f_xu(a, env) { int_to_float(a, 2 * env.sew, false, env.sew, env) }
+    VfwcvtFX::vfwcvt_f_x(a, env) { int_to_float(a, 2 * env.sew, true, env.sew, env) }
+    VfwcvtFF::vfwcvt_f_f(a, env) { widen(a, env) }
+    VfncvtXuF::vfncvt_xu_f(a, env) { float_to_int(a, 2 * env.sew, false, env.sew, env) }
+    VfncvtXF::vfncvt_x_f(a, env) { float_to_int(a, 2 * env.sew, true, env.sew, env) }
+    VfncvtRtzXuF::vfncvt_rtz_xu_f(a, env) {
+        rtz(env, |env| float_to_int(a, 2 * env.sew, false, env.sew, env))
+    }
+    VfncvtRtzXF::vfncvt_rtz_x_f(a, env) {
+        rtz(env, |env| float_to_int(a, 2 * env.sew, true, env.sew, env))
+    }
+    VfncvtFXu::vfncvt_f_xu(a, env) { int_to_float(a, env.sew, false, 2 * env.sew, env) }
+    VfncvtFX::vfncvt_f_x(a, env) { int_to_float(a, env.sew, true, 2 * env.sew, env) }
+    VfncvtFF::vfncvt_f_f(a, env) { softfloat::convert::<F64, F32>(a, &mut env.float) }
+    /// Rounds to odd: truncates, then sets the lsb of inexact results.
+    VfncvtRodFF::vfncvt_rod_f_f(a, env) {
+        let flags = env.float.flags;
+        env.float.flags = 0;
+        let value = rtz(env, |env| softfloat::convert::<F64, F32>(a, &mut env.float));
+        let odd = env.float.flags & NX != 0;
+        env.float.flags |= flags;
+        value | odd as u64
+    }
+}
+
+pub trait BaseVector:
+    Vadd
+    + Vsub
+    + Vrsub
+    + Vand
+    + Vor
+    + Vxor
+    + Vsll
+    + Vsrl
+    + Vsra
+    + Vminu
+    + Vmin
+    + Vmaxu
+    + Vmax
+    + Vmul
+    + Vmulh
+    + Vmulhu
+    + Vmulhsu
+    + Vdivu
+    + Vdiv
+    + Vremu
+    + Vrem
+    + Vmseq
+    + Vmsne
+    + Vmsltu
+    + Vmslt
+    + Vmsleu
+    + Vmsle
+    + Vmsgtu
+    + Vmsgt
+    + Vwaddu
+    + Vwadd
+    + Vwsubu
+    + Vwsub
+    + VwadduW
+    + VwaddW
+    + VwsubuW
+    + VwsubW
+    + Vwmulu
+    + Vwmul
+    + Vwmulsu
+    + Vnsrl
+    + Vnsra
+    + VzextVf2
+    + VzextVf4
+    + VzextVf8
+    + VsextVf2
+    + VsextVf4
+    + VsextVf8
+    + Vadc
+    + Vmadc
+    + Vsbc
+    + Vmsbc
+    + Vmacc
+    + Vnmsac
+    + Vmadd
+    + Vnmsub
+    + Vwmaccu
+    + Vwmacc
+    + Vwmaccsu
+    + Vwmaccus
+    + Vsaddu
+    + Vsadd
+    + Vssubu
+    + Vssub
+    + Vaaddu
+    + Vaadd
+    + Vasubu
+    + Vasub
+    + Vsmul
+    + Vssrl
+    + Vssra
+    + Vnclipu
+    + Vnclip
+    + Vmnand
+    + Vmandn
+    + Vmnor
+    + Vmorn
+    + Vmxnor
+    + Vfadd
+    + Vfsub
+    + Vfrsub
+    + Vfmul
+    + Vfdiv
+    + Vfrdiv
+    + Vfmin
+    + Vfmax
+    + Vfsgnj
+    + Vfsgnjn
+    + Vfsgnjx
+    + Vmfeq
+    + Vmfne
+    + Vmflt
+    + Vmfle
+    + Vmfgt
+    + Vmfge
+    + Vfwadd
+    + Vfwsub
+    + Vfwmul
+    + VfwaddW
+    + VfwsubW
+    + Vfmacc
+    + Vfnmacc
+    + Vfmsac
+    + Vfnmsac
+    + Vfmadd
+    + Vfnmadd
+    + Vfmsub
+    + Vfnmsub
+    + Vfwmacc
+    + Vfwnmacc
+    + Vfwmsac
+    + Vfwnmsac
+    + Vfsqrt
+    + Vfclass
+    + Vfrsqrt7
+    + Vfrec7
+    + VfcvtXuF
+    + VfcvtXF
+    + VfcvtRtzXuF
+    + VfcvtRtzXF
+    + VfcvtFXu
+    + VfcvtFX
+    + VfwcvtXuF
+    + VfwcvtXF
+    + VfwcvtRtzXuF
+    + VfwcvtRtzXF
+    + VfwcvtFXu
+    + VfwcvtFX
+    + VfwcvtFF
+    + VfncvtXuF
+    + VfncvtXF
+    + VfncvtRtzXuF
+    + VfncvtRtzXF
+    + VfncvtFXu
+    + VfncvtFX
+    + VfncvtFF
+    + VfncvtRodFF
+{
+}
+
+impl BaseVector for u64 {}
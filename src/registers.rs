@@ -1,4 +1,9 @@
+use crate::csr::{CsrRead, CsrSpec};
 use crate::decode::U5;
+use crate::isa::{Extensions, Privilege, Xlen};
+use crate::mem;
+use crate::mmu::Mode;
+use crate::num::As;
 
 pub trait Zero {
     fn zero() -> Self;
@@ -55,6 +60,44 @@ pub enum Register {
     X31,
 }
 
+#[allow(dead_code)]
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FRegister {
+    F0 = 0,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+    F25,
+    F26,
+    F27,
+    F28,
+    F29,
+    F30,
+    F31,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ZeroOrRegister {
     Zero,
@@ -65,6 +108,195 @@ pub enum ZeroOrRegister {
 #[derive(Debug)]
 pub struct Registers<T>([T; 31]);
 
+/// The floating point registers are FLEN = 64 bits wide regardless of XLEN,
+/// single precision values are NaN-boxed in the upper 32 bits.
+#[repr(transparent)]
+#[derive(Debug)]
+pub struct FRegisters([u64; 32]);
+
+/// Accrued floating point exception flags.
+pub const FFLAGS: usize = 0x001;
+/// Dynamic rounding mode.
+pub const FRM: usize = 0x002;
+/// `frm` and `fflags` together, stored in their own cells.
+pub const FCSR: usize = 0x003;
+/// Entropy source of Zkr.
+pub const SEED: usize = 0x015;
+/// Zcmt jump table base, with the table mode in the low six bits.
+pub const JVT: usize = 0x017;
+/// `seed` status reporting that the low 16 bits hold fresh entropy.
+pub const SEED_ES16: u32 = 0b10 << 30;
+/// First element a vector instruction executes.
+pub const VSTART: usize = 0x008;
+/// Fixed point saturation flag.
+pub const VXSAT: usize = 0x009;
+/// Fixed point rounding mode.
+pub const VXRM: usize = 0x00a;
+/// `vxrm` and `vxsat` together, stored in their own cells.
+pub const VCSR: usize = 0x00f;
+/// Vector length.
+pub const VL: usize = 0xc20;
+/// Vector data type.
+pub const VTYPE: usize = 0xc21;
+/// VLEN in bytes.
+pub const VLENB: usize = 0xc22;
+/// Cycle counter, followed by `time`, `instret` and `hpmcounter3` to
+/// `hpmcounter31`. All but `time` read their machine-level counterpart.
+pub const CYCLE: usize = 0xc00;
+/// Real time, ticking once per retired instruction.
+pub const TIME: usize = 0xc01;
+/// Instructions retired.
+pub const INSTRET: usize = 0xc02;
+/// First programmable counter.
+pub const HPMCOUNTER3: usize = 0xc03;
+/// Last of the user-level counters.
+pub const HPMCOUNTER31: usize = 0xc1f;
+/// Upper 32 bits of `cycle` on RV32, the other high halves follow it.
+pub const CYCLEH: usize = 0xc80;
+/// Upper 32 bits of `time` on RV32.
+pub const TIMEH: usize = 0xc81;
+/// Upper 32 bits of `instret` on RV32.
+pub const INSTRETH: usize = 0xc82;
+/// Upper 32 bits of `hpmcounter3` on RV32.
+pub const HPMCOUNTER3H: usize = 0xc83;
+/// Last of the user-level high halves.
+pub const HPMCOUNTER31H: usize = 0xc9f;
+/// Machine cycle counter, followed by `minstret` and `mhpmcounter3` to
+/// `mhpmcounter31` at the offsets of their user-level views.
+pub const MCYCLE: usize = 0xb00;
+/// Machine instructions retired.
+pub const MINSTRET: usize = 0xb02;
+/// First programmable machine counter.
+pub const MHPMCOUNTER3: usize = 0xb03;
+/// Last programmable machine counter.
+pub const MHPMCOUNTER31: usize = 0xb1f;
+/// Upper 32 bits of `mcycle` on RV32, the other high halves follow it.
+pub const MCYCLEH: usize = 0xb80;
+/// Upper 32 bits of `minstret` on RV32.
+pub const MINSTRETH: usize = 0xb82;
+/// Upper 32 bits of `mhpmcounter3` on RV32.
+pub const MHPMCOUNTER3H: usize = 0xb83;
+/// Upper 32 bits of `mhpmcounter31` on RV32.
+pub const MHPMCOUNTER31H: usize = 0xb9f;
+/// Supervisor status, a view of the S-mode fields of `mstatus`.
+pub const SSTATUS: usize = 0x100;
+/// Supervisor interrupt enables, a view of the S-mode bits of `mie`.
+pub const SIE: usize = 0x104;
+/// Supervisor trap handler base address, with the vector mode in the low two
+/// bits.
+pub const STVEC: usize = 0x105;
+/// Scratch register for S-mode trap handlers.
+pub const SSCRATCH: usize = 0x140;
+/// Address of the instruction that trapped into S-mode.
+pub const SEPC: usize = 0x141;
+/// Cause of the last trap into S-mode.
+pub const SCAUSE: usize = 0x142;
+/// Faulting address or instruction bits of the last trap into S-mode.
+pub const STVAL: usize = 0x143;
+/// Supervisor pending interrupts, a view of the S-mode bits of `mip`.
+pub const SIP: usize = 0x144;
+/// Supervisor address translation and protection: the translation mode,
+/// address space and root page table.
+pub const SATP: usize = 0x180;
+/// Machine status, the interrupt enable and privilege stacks among others.
+pub const MSTATUS: usize = 0x300;
+/// Machine XLEN and single-letter extensions, read-only.
+pub const MISA: usize = 0x301;
+/// JEDEC manufacturer ID, 0 for a non-commercial implementation.
+pub const MVENDORID: usize = 0xf11;
+/// Microarchitecture ID.
+pub const MARCHID: usize = 0xf12;
+/// Implementation version.
+pub const MIMPID: usize = 0xf13;
+/// Number of the hart.
+pub const MHARTID: usize = 0xf14;
+/// Exceptions delegated to S-mode, one bit per cause.
+pub const MEDELEG: usize = 0x302;
+/// Interrupts delegated to S-mode, one bit per cause.
+pub const MIDELEG: usize = 0x303;
+/// Machine interrupt enables, zero as no interrupt sources are modelled.
+pub const MIE: usize = 0x304;
+/// Machine trap handler base address, with the vector mode in the low two
+/// bits.
+pub const MTVEC: usize = 0x305;
+/// Upper 32 bits of `mstatus` on RV32.
+pub const MSTATUSH: usize = 0x310;
+/// Scratch register for M-mode trap handlers.
+pub const MSCRATCH: usize = 0x340;
+/// Address of the instruction that trapped into M-mode.
+pub const MEPC: usize = 0x341;
+/// Cause of the last trap into M-mode.
+pub const MCAUSE: usize = 0x342;
+/// Faulting address or instruction bits of the last trap into M-mode.
+pub const MTVAL: usize = 0x343;
+/// Machine pending interrupts. No interrupt controllers are modelled, so
+/// software raises and clears them all.
+pub const MIP: usize = 0x344;
+/// `mstatus` global S-mode interrupt enable.
+pub const MSTATUS_SIE: u64 = 1 << 1;
+/// `mstatus` global M-mode interrupt enable.
+pub const MSTATUS_MIE: u64 = 1 << 3;
+/// `mstatus` interrupt enable before the last trap into S-mode.
+pub const MSTATUS_SPIE: u64 = 1 << 5;
+/// `mstatus` interrupt enable before the last trap into M-mode.
+pub const MSTATUS_MPIE: u64 = 1 << 7;
+/// `mstatus` privilege mode before the last trap into S-mode, U or S.
+pub const MSTATUS_SPP: u64 = 1 << 8;
+/// `mstatus` privilege mode before the last trap into M-mode.
+pub const MSTATUS_MPP: u64 = 0b11 << 11;
+/// `mstatus` loads and stores from M-mode at the privilege mode in MPP.
+pub const MSTATUS_MPRV: u64 = 1 << 17;
+/// `mstatus` S-mode access to user pages.
+pub const MSTATUS_SUM: u64 = 1 << 18;
+/// `mstatus` loads from executable pages that are not readable.
+pub const MSTATUS_MXR: u64 = 1 << 19;
+/// `mstatus` trapping of `satp` accesses and `sfence.vma` in S-mode.
+pub const MSTATUS_TVM: u64 = 1 << 20;
+/// `mstatus` trapping of `wfi` below M-mode.
+pub const MSTATUS_TW: u64 = 1 << 21;
+/// `mstatus` trapping of `sret` in S-mode.
+pub const MSTATUS_TSR: u64 = 1 << 22;
+/// The `mstatus` fields `sstatus` shows.
+pub const SSTATUS_FIELDS: u64 =
+    MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP | MSTATUS_SUM | MSTATUS_MXR;
+/// The interrupts S-mode can take, as laid out in `mie`, `mip` and
+/// `mideleg`.
+pub const S_INTERRUPTS: u64 = 0x222;
+/// The interrupts the hart can take, as laid out in `mie` and `mip`.
+pub const INTERRUPTS: u64 = 0xaaa;
+/// Supervisor software interrupt pending, the `sip` bit S-mode can write.
+pub const MIP_SSIP: u64 = 1 << 1;
+/// `mtvec` mode sending interrupts to `BASE + 4 * cause`.
+pub const MTVEC_VECTORED: u64 = 0b01;
+/// Counters readable below M-mode, one bit per counter.
+pub const MCOUNTEREN: usize = 0x306;
+/// Counters readable in U-mode, on top of `mcounteren`.
+pub const SCOUNTEREN: usize = 0x106;
+/// Counters held still, one bit per counter. `time` cannot be inhibited.
+pub const MCOUNTINHIBIT: usize = 0x320;
+/// Event counted by `mhpmcounter3`, the selectors of the other programmable
+/// counters follow it.
+pub const MHPMEVENT3: usize = 0x323;
+/// Selector of `mhpmcounter31`.
+pub const MHPMEVENT31: usize = 0x33f;
+/// `mhpmevent` value counting loads, AMOs and `cm.pop` included.
+pub const EVENT_LOAD: u64 = 1;
+/// `mhpmevent` value counting stores, AMOs and `cm.push` included.
+pub const EVENT_STORE: u64 = 2;
+/// `mhpmevent` value counting taken conditional branches.
+pub const EVENT_BRANCH_TAKEN: u64 = 3;
+/// `mhpmevent` value counting scalar floating point instructions.
+pub const EVENT_FLOAT: u64 = 4;
+
+/// The 32 vector registers, VLEN bits each, kept as one byte array so that
+/// the elements of a register group are contiguous.
+#[derive(Debug, Clone)]
+pub struct VRegisters {
+    vlenb: usize,
+    elen: u32,
+    bytes: Box<[u8]>,
+}
+
 #[repr(transparent)]
 #[derive(Debug)]
 pub struct CsrRegisters<T>([T; 4096]);
@@ -72,8 +304,9 @@ pub struct CsrRegisters<T>([T; 4096]);
 #[derive(Debug)]
 pub struct RegFile<T> {
     pub xregs: Registers<T>,
-    pub fregs: Registers<T>,
+    pub fregs: FRegisters,
     pub csrs: CsrRegisters<T>,
+    pub vregs: VRegisters,
 }
 
 const _: [(); 0] = [(); ((Register::X31 as usize + 1) * core::mem::size_of::<u32>())
@@ -131,6 +364,161 @@ impl<T: Copy + Default> Default for Registers<T> {
     }
 }
 
+impl FRegisters {
+    /// The canonical NaN read by single precision operations when the source
+    /// register does not hold a properly NaN-boxed value.
+    pub const CANONICAL_NAN_S: u32 = 0x7fc00000;
+
+    /// The canonical NaN read by half precision operations.
+    pub const CANONICAL_NAN_H: u16 = 0x7e00;
+
+    const NAN_BOX: u64 = 0xffffffff_00000000;
+
+    const NAN_BOX_H: u64 = 0xffffffff_ffff0000;
+
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self([0; 32])
+    }
+
+    #[inline(always)]
+    pub fn get(&self, reg: FRegister) -> u64 {
+        self.0[reg as usize]
+    }
+
+    #[inline(always)]
+    pub fn set(&mut self, reg: FRegister, value: u64) {
+        self.0[reg as usize] = value;
+    }
+
+    /// Reads a single precision value, invalid NaN-boxes read as the
+    /// canonical NaN.
+    #[inline(always)]
+    pub fn get_s(&self, reg: FRegister) -> u32 {
+        let value = self.get(reg);
+        if value & Self::NAN_BOX == Self::NAN_BOX {
+            value as u32
+        } else {
+            Self::CANONICAL_NAN_S
+        }
+    }
+
+    /// Writes a single precision value NaN-boxed to FLEN bits.
+    #[inline(always)]
+    pub fn set_s(&mut self, reg: FRegister, value: u32) {
+        self.set(reg, Self::NAN_BOX | value as u64);
+    }
+
+    /// Reads a half precision value, invalid NaN-boxes read as the canonical
+    /// NaN.
+    #[inline(always)]
+    pub fn get_h(&self, reg: FRegister) -> u16 {
+        let value = self.get(reg);
+        if value & Self::NAN_BOX_H == Self::NAN_BOX_H {
+            value as u16
+        } else {
+            Self::CANONICAL_NAN_H
+        }
+    }
+
+    /// Writes a half precision value NaN-boxed to FLEN bits.
+    #[inline(always)]
+    pub fn set_h(&mut self, reg: FRegister, value: u16) {
+        self.set(reg, Self::NAN_BOX_H | value as u64);
+    }
+}
+
+impl Default for FRegisters {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VRegisters {
+    /// A zeroed register file, `vlen` and `elen` are in bits. ELEN is 32 or
+    /// 64 and VLEN a power of two between ELEN and 65536.
+    pub fn new(vlen: usize, elen: u32) -> Self {
+        assert!(matches!(elen, 32 | 64), "unsupported ELEN {elen}");
+        assert!(
+            vlen.is_power_of_two() && vlen >= elen as usize && vlen <= 1 << 16,
+            "unsupported VLEN {vlen}"
+        );
+        Self {
+            vlenb: vlen / 8,
+            elen,
+            bytes: vec![0; 32 * vlen / 8].into_boxed_slice(),
+        }
+    }
+
+    #[inline(always)]
+    pub const fn vlenb(&self) -> usize {
+        self.vlenb
+    }
+
+    #[inline(always)]
+    pub const fn elen(&self) -> u32 {
+        self.elen
+    }
+
+    /// Element `index` of `eew` bits of the register group starting at `reg`,
+    /// zero extended.
+    ///
+    /// # Panics
+    ///
+    /// If the element lies past `v31`, callers check the group bounds.
+    #[inline(always)]
+    pub fn get(&self, reg: usize, index: usize, eew: u32) -> u64 {
+        let addr = reg * self.vlenb + index * (eew as usize / 8);
+        match eew {
+            8 => mem::read::<u8>(&self.bytes, addr).map(u64::from),
+            16 => mem::read::<mem::U16>(&self.bytes, addr).map(|e| e.as_u16() as u64),
+            32 => mem::read::<mem::U32>(&self.bytes, addr).map(|e| e.as_u32() as u64),
+            _ => mem::read::<mem::U64>(&self.bytes, addr).map(|e| e.as_u64()),
+        }
+        .expect("vector element past v31")
+    }
+
+    /// Writes the low `eew` bits of `value` to element `index` of the
+    /// register group starting at `reg`.
+    ///
+    /// # Panics
+    ///
+    /// If the element lies past `v31`.
+    #[inline(always)]
+    pub fn set(&mut self, reg: usize, index: usize, eew: u32, value: u64) {
+        let addr = reg * self.vlenb + index * (eew as usize / 8);
+        match eew {
+            8 => mem::write(&(value as u8), &mut self.bytes, addr),
+            16 => mem::write(&mem::U16::new(value as u16), &mut self.bytes, addr),
+            32 => mem::write(&mem::U32::new(value as u32), &mut self.bytes, addr),
+            _ => mem::write(&mem::U64::new(value), &mut self.bytes, addr),
+        }
+        .expect("vector element past v31")
+    }
+
+    /// Bit `index` of the mask register `reg`.
+    #[inline(always)]
+    pub fn mask(&self, reg: usize, index: usize) -> bool {
+        self.get(reg, index / 8, 8) >> (index % 8) & 1 != 0
+    }
+
+    #[inline(always)]
+    pub fn set_mask(&mut self, reg: usize, index: usize, value: bool) {
+        let byte = self.get(reg, index / 8, 8) & !(1 << (index % 8));
+        self.set(reg, index / 8, 8, byte | (value as u64) << (index % 8));
+    }
+}
+
+impl Default for VRegisters {
+    /// VLEN 128 and ELEN 64, the minimum the V extension asks of application
+    /// processors.
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(128, 64)
+    }
+}
+
 impl<T: Copy + Default> CsrRegisters<T> {
     #[inline(always)]
     pub fn new() -> Self {
@@ -144,6 +532,12 @@ impl<T: Copy> CsrRegisters<T> {
     pub fn get(&self, reg: usize) -> T {
         self.0[reg]
     }
+
+    /// Updates a CSR on behalf of the hart, read-only ones included.
+    #[inline(always)]
+    pub fn set(&mut self, reg: usize, value: T) {
+        self.0[reg] = value;
+    }
 }
 
 impl<T> CsrRegisters<T> {
@@ -157,6 +551,243 @@ impl<T> CsrRegisters<T> {
     }
 }
 
+impl<T> CsrRegisters<T>
+where
+    T: Copy + As<u8>,
+    u8: As<T>,
+{
+    /// Sets the exception flags raised by a float instruction.
+    #[inline(always)]
+    pub fn accrue_fflags(&mut self, flags: u8) {
+        let fflags: u8 = self.0[FFLAGS].r#as();
+        self.0[FFLAGS] = (fflags | flags).r#as();
+    }
+}
+
+impl<T> CsrRegisters<T>
+where
+    T: Copy + As<u8> + As<u64>,
+    u8: As<T>,
+    u64: As<T>,
+{
+    /// Reads a CSR as an instruction sees it, once `accessible` allowed it.
+    #[inline(always)]
+    pub fn read(&self, reg: usize, extensions: &Extensions) -> T {
+        match CsrSpec::of(reg).map(|spec| spec.read) {
+            // MXL gives the XLEN
+            Some(CsrRead::Misa) => {
+                let mxl: u64 = if size_of::<T>() == 4 {
+                    1 << 30
+                } else {
+                    2 << 62
+                };
+                (mxl | extensions.misa() as u64).r#as()
+            }
+            Some(CsrRead::Epc) => {
+                let epc: u64 = self.0[reg].r#as();
+                (epc & !(extensions.ialign() as u64 - 1)).r#as()
+            }
+            Some(CsrRead::Fcsr) => {
+                let frm: u8 = self.0[FRM].r#as();
+                let fflags: u8 = self.0[FFLAGS].r#as();
+                (frm << 5 | fflags).r#as()
+            }
+            Some(CsrRead::Vcsr) => {
+                let vxrm: u8 = self.0[VXRM].r#as();
+                let vxsat: u8 = self.0[VXSAT].r#as();
+                (vxrm << 1 | vxsat).r#as()
+            }
+            Some(CsrRead::Alias(alias)) => self.0[alias],
+            Some(CsrRead::View(csr, fields)) => {
+                let value: u64 = self.0[csr].r#as();
+                (value & fields).r#as()
+            }
+            _ => self.0[reg],
+        }
+    }
+
+    /// Writes a CSR as an instruction does, only the bits in its WARL mask
+    /// change. `None` if it is unimplemented or read-only.
+    #[inline(always)]
+    pub fn write(&mut self, reg: usize, value: T, extensions: &Extensions) -> Option<()> {
+        let spec = CsrSpec::of(reg).filter(|_| !CsrSpec::read_only(reg))?;
+        let bits: u8 = value.r#as();
+        match spec.read {
+            CsrRead::Fcsr => {
+                self.0[FFLAGS] = (bits & 0x1f).r#as();
+                self.0[FRM] = (bits >> 5 & 0x7).r#as();
+            }
+            CsrRead::Vcsr => {
+                self.0[VXSAT] = (bits & 0x1).r#as();
+                self.0[VXRM] = (bits >> 1 & 0x3).r#as();
+            }
+            // Unsupported events read back as 0, counting nothing
+            _ if (MHPMEVENT3..=MHPMEVENT31).contains(&reg) => {
+                let event: u64 = value.r#as();
+                self.0[reg] = if event <= EVENT_FLOAT {
+                    value
+                } else {
+                    0u8.r#as()
+                };
+            }
+            _ => {
+                let reg = match spec.read {
+                    CsrRead::View(csr, _) => csr,
+                    _ => reg,
+                };
+                let old: u64 = self.0[reg].r#as();
+                let new: u64 = value.r#as();
+                let new = old & !spec.mask | new & spec.mask;
+                let xlen = if size_of::<T>() == 4 {
+                    Xlen::Rv32
+                } else {
+                    Xlen::Rv64
+                };
+                self.0[reg] = match reg {
+                    MSTATUS => legalize_mstatus(old, new, extensions),
+                    // Writes of an unsupported mode have no effect
+                    SATP if Mode::of(new, xlen).is_none() => old,
+                    _ => new,
+                }
+                .r#as();
+            }
+        }
+        Some(())
+    }
+}
+
+/// Keeps the `mstatus` fields of missing privilege modes read-only zero,
+/// and MPP at its `old` value rather than an unsupported mode.
+fn legalize_mstatus(old: u64, new: u64, extensions: &Extensions) -> u64 {
+    let mut new = new;
+    if !extensions.supports(Privilege::Supervisor) {
+        new &= !(SSTATUS_FIELDS | MSTATUS_TVM | MSTATUS_TSR);
+    }
+    if !extensions.supports(Privilege::User) {
+        new &= !MSTATUS_MPRV;
+    }
+    if extensions.lowest_privilege() == Privilege::Machine {
+        new &= !MSTATUS_TW;
+    }
+    let mpp = Privilege::decode(((new & MSTATUS_MPP) >> 11) as u8);
+    if !mpp.is_some_and(|mpp| extensions.supports(mpp)) {
+        new = new & !MSTATUS_MPP | old & MSTATUS_MPP;
+    }
+    new
+}
+
+impl<T> CsrRegisters<T>
+where
+    T: Copy + As<u64>,
+    u64: As<T>,
+{
+    /// The privilege mode loads and stores executing at `privilege` access
+    /// memory with, which `mstatus.MPRV` takes from MPP in M-mode.
+    #[inline(always)]
+    pub fn data_privilege(&self, privilege: Privilege) -> Privilege {
+        let mstatus: u64 = self.0[MSTATUS].r#as();
+        if privilege == Privilege::Machine && mstatus & MSTATUS_MPRV != 0 {
+            Privilege::decode(((mstatus & MSTATUS_MPP) >> 11) as u8).unwrap_or(privilege)
+        } else {
+            privilege
+        }
+    }
+
+    /// Reads a 64-bit counter, whose upper half lives `0x80` above it on RV32.
+    #[inline(always)]
+    pub fn counter(&self, reg: usize) -> u64 {
+        let low: u64 = self.0[reg].r#as();
+        if size_of::<T>() == 4 {
+            let high: u64 = self.0[reg + 0x80].r#as();
+            high << 32 | low
+        } else {
+            low
+        }
+    }
+
+    /// Sets a 64-bit counter, split in two halves on RV32.
+    #[inline(always)]
+    pub fn set_counter(&mut self, reg: usize, value: u64) {
+        self.0[reg] = value.r#as();
+        if size_of::<T>() == 4 {
+            self.0[reg + 0x80] = (value >> 32).r#as();
+        }
+    }
+
+    /// Advances the counters past a retired instruction.
+    ///
+    /// `events` has bit `n` set when the instruction raised the `mhpmevent`
+    /// event `n`. A counter `written` by the instruction keeps the written
+    /// value rather than counting it, and `time` ticks regardless of
+    /// `mcountinhibit`.
+    pub fn retire(&mut self, events: u64, written: Option<usize>) {
+        self.set_counter(TIME, self.counter(TIME).wrapping_add(1));
+        let inhibit: u64 = self.0[MCOUNTINHIBIT].r#as();
+        for n in (0..32).filter(|&n| n != 1 && inhibit >> n & 1 == 0) {
+            let reg = MCYCLE + n;
+            if written.is_some_and(|csr| csr & !0x80 == reg) {
+                continue;
+            }
+            let counts = match n {
+                0 | 2 => true,
+                _ => {
+                    let event: u64 = self.0[MHPMEVENT3 + n - 3].r#as();
+                    event != 0 && events >> event & 1 != 0
+                }
+            };
+            if counts {
+                self.set_counter(reg, self.counter(reg).wrapping_add(1));
+            }
+        }
+    }
+
+    /// Whether an instruction executing at `privilege` may access `reg`,
+    /// writing to it if `write`.
+    ///
+    /// The CSR must exist with these extensions, and be writable to be
+    /// written. `mstatus.TVM` keeps `satp` from S-mode, the user-level
+    /// counters also need their `mcounteren` bit below M-mode, and their
+    /// `scounteren` bit in U-mode.
+    pub fn accessible(
+        &self,
+        reg: usize,
+        write: bool,
+        privilege: Privilege,
+        extensions: &Extensions,
+    ) -> bool {
+        let xlen = if size_of::<T>() == 4 {
+            Xlen::Rv32
+        } else {
+            Xlen::Rv64
+        };
+        let Some(spec) = CsrSpec::of(reg) else {
+            return false;
+        };
+        if !(spec.exists)(xlen, extensions)
+            || privilege < spec.privilege
+            || write && CsrSpec::read_only(reg)
+        {
+            return false;
+        }
+        let mstatus: u64 = self.0[MSTATUS].r#as();
+        if reg == SATP && privilege == Privilege::Supervisor && mstatus & MSTATUS_TVM != 0 {
+            return false;
+        }
+        let n = match reg {
+            CYCLE..=HPMCOUNTER31 => reg - CYCLE,
+            CYCLEH..=HPMCOUNTER31H => reg - CYCLEH,
+            _ => return true,
+        };
+        let enabled = |csr: usize| <T as As<u64>>::r#as(self.0[csr]) >> n & 1 != 0;
+        match privilege {
+            Privilege::Machine => true,
+            Privilege::Supervisor => enabled(MCOUNTEREN),
+            // Without S-mode there is no `scounteren` to consult
+            Privilege::User => enabled(MCOUNTEREN) && (!extensions.s || enabled(SCOUNTEREN)),
+        }
+    }
+}
+
 impl<T: Copy + Default> Default for CsrRegisters<T> {
     #[inline(always)]
     fn default() -> Self {
@@ -164,21 +795,48 @@ impl<T: Copy + Default> Default for CsrRegisters<T> {
     }
 }
 
-impl<T> RegFile<T> {
+impl<T> RegFile<T>
+where
+    u16: As<T>,
+{
+    #[inline(always)]
+    pub fn new(
+        xregs: Registers<T>,
+        fregs: FRegisters,
+        mut csrs: CsrRegisters<T>,
+        vregs: VRegisters,
+    ) -> Self {
+        csrs.0[VLENB] = (vregs.vlenb() as u16).r#as();
+        // M-mode is the only privilege mode to return to
+        csrs.0[MSTATUS] = (MSTATUS_MPP as u16).r#as();
+        Self {
+            xregs,
+            fregs,
+            csrs,
+            vregs,
+        }
+    }
+
+    /// Swaps in a vector register file of another shape, `vlenb` follows it.
     #[inline(always)]
-    pub fn new(xregs: Registers<T>, fregs: Registers<T>, csrs: CsrRegisters<T>) -> Self {
-        Self { xregs, fregs, csrs }
+    pub fn set_vregs(&mut self, vregs: VRegisters) {
+        self.csrs.0[VLENB] = (vregs.vlenb() as u16).r#as();
+        self.vregs = vregs;
     }
 }
 
-impl<T: Copy + Default> Default for RegFile<T> {
+impl<T: Copy + Default> Default for RegFile<T>
+where
+    u16: As<T>,
+{
     #[inline(always)]
     fn default() -> Self {
-        Self {
-            xregs: Registers::default(),
-            fregs: Registers::default(),
-            csrs: CsrRegisters::default(),
-        }
+        Self::new(
+            Registers::default(),
+            FRegisters::default(),
+            CsrRegisters::default(),
+            VRegisters::default(),
+        )
     }
 }
 
@@ -211,6 +869,17 @@ impl ZeroOrRegister {
         }
     }
 
+    /// Writes `value` to the register, writes to `x0` are discarded.
+    #[inline(always)]
+    pub fn store<T>(&self, regs: &mut Registers<T>, value: T) {
+        if let Self::Register(reg) = *self {
+            *reg.fetch_mut(regs) = value;
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `raw` must be a valid register number, i.e. less than 32.
     #[inline(always)]
     pub const unsafe fn decode_unchecked(raw: u8) -> Self {
         debug_assert!(raw < 32, "invalid register number");
@@ -243,6 +912,20 @@ impl ZeroOrRegister {
     }
 }
 
+impl FRegister {
+    #[inline(always)]
+    pub const fn from_u5(value: U5) -> Self {
+        unsafe { core::mem::transmute::<u8, FRegister>(value.as_u8()) }
+    }
+}
+
+impl From<U5> for FRegister {
+    #[inline(always)]
+    fn from(value: U5) -> Self {
+        Self::from_u5(value)
+    }
+}
+
 impl From<U5> for ZeroOrRegister {
     #[inline(always)]
     fn from(value: U5) -> Self {
@@ -251,20 +934,21 @@ impl From<U5> for ZeroOrRegister {
 }
 
 pub trait ProgramCounter {
-    fn increment(&mut self);
+    /// Advances past an instruction of `len` bytes.
+    fn increment(&mut self, len: u8);
 }
 
 impl ProgramCounter for u32 {
     #[inline(always)]
-    fn increment(&mut self) {
-        *self += 4;
+    fn increment(&mut self, len: u8) {
+        *self = self.wrapping_add(len as u32);
     }
 }
 
 impl ProgramCounter for u64 {
     #[inline(always)]
-    fn increment(&mut self) {
-        *self += 4;
+    fn increment(&mut self, len: u8) {
+        *self = self.wrapping_add(len as u64);
     }
 }
 
@@ -308,4 +992,16 @@ mod tests {
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn test_fregisters_nan_boxing() {
+        let mut fregs = FRegisters::default();
+        fregs.set_s(FRegister::F0, 1.5f32.to_bits());
+        assert_eq!(fregs.get(FRegister::F0), 0xffffffff_3fc00000);
+        assert_eq!(fregs.get_s(FRegister::F0), 1.5f32.to_bits());
+        fregs.set(FRegister::F31, 1.5f64.to_bits());
+        assert_eq!(fregs.get_s(FRegister::F31), FRegisters::CANONICAL_NAN_S);
+        fregs.set(FRegister::F1, 0xfffffffe_3fc00000);
+        assert_eq!(fregs.get_s(FRegister::F1), FRegisters::CANONICAL_NAN_S);
+    }
 }
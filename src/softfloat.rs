@@ -0,0 +1,1225 @@
+//! IEEE 754 arithmetic implemented on the raw encodings, so results and
+//! exception flags do not depend on the host FPU.
+//!
+//! Every operation computes the exact result, or enough of it plus a sticky
+//! bit, and rounds once in `round_pack`. NaN results are always the canonical
+//! NaN and tininess is detected after rounding, as on RISC-V.
+
+/// Invalid operation.
+pub const NV: u8 = 1 << 4;
+/// Divide by zero.
+pub const DZ: u8 = 1 << 3;
+/// Overflow.
+pub const OF: u8 = 1 << 2;
+/// Underflow.
+pub const UF: u8 = 1 << 1;
+/// Inexact.
+pub const NX: u8 = 1 << 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoundingMode {
+    /// Round to nearest, ties to even.
+    Rne = 0,
+    /// Round towards zero.
+    Rtz = 1,
+    /// Round down, towards negative infinity.
+    Rdn = 2,
+    /// Round up, towards positive infinity.
+    Rup = 3,
+    /// Round to nearest, ties to max magnitude.
+    Rmm = 4,
+}
+
+impl RoundingMode {
+    /// Decodes the `rm` field, `None` for the reserved modes and for `DYN`.
+    #[inline(always)]
+    pub const fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Rne),
+            1 => Some(Self::Rtz),
+            2 => Some(Self::Rdn),
+            3 => Some(Self::Rup),
+            4 => Some(Self::Rmm),
+            _ => None,
+        }
+    }
+}
+
+/// The rounding mode an operation uses and the flags it raises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FloatEnv {
+    pub rm: RoundingMode,
+    pub flags: u8,
+}
+
+impl FloatEnv {
+    #[inline(always)]
+    pub const fn new(rm: RoundingMode) -> Self {
+        Self { rm, flags: 0 }
+    }
+}
+
+/// A binary interchange format, values are passed around as their encoding
+/// in the low bits of a `u64`.
+pub trait Format {
+    const EXP_BITS: u32;
+    const FRAC_BITS: u32;
+
+    const BIAS: i32 = (1 << (Self::EXP_BITS - 1)) - 1;
+    const EXP_MAX: u64 = (1 << Self::EXP_BITS) - 1;
+    const SIGN: u64 = 1 << (Self::EXP_BITS + Self::FRAC_BITS);
+    const INF: u64 = Self::EXP_MAX << Self::FRAC_BITS;
+    const QUIET: u64 = 1 << (Self::FRAC_BITS - 1);
+    const NAN: u64 = Self::INF | Self::QUIET;
+}
+
+pub struct F16;
+
+pub struct F32;
+
+pub struct F64;
+
+impl Format for F16 {
+    const EXP_BITS: u32 = 5;
+    const FRAC_BITS: u32 = 10;
+}
+
+impl Format for F32 {
+    const EXP_BITS: u32 = 8;
+    const FRAC_BITS: u32 = 23;
+}
+
+impl Format for F64 {
+    const EXP_BITS: u32 = 11;
+    const FRAC_BITS: u32 = 52;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Value {
+    Nan {
+        signaling: bool,
+    },
+    Inf,
+    Zero,
+    /// `sig * 2^exp`, with `sig != 0`.
+    Finite {
+        sig: u128,
+        exp: i32,
+    },
+}
+
+#[inline(always)]
+fn unpack<F: Format>(bits: u64) -> (bool, Value) {
+    let sign = bits & F::SIGN != 0;
+    let exp = (bits >> F::FRAC_BITS) & F::EXP_MAX;
+    let frac = bits & ((1 << F::FRAC_BITS) - 1);
+    let value = match (exp, frac) {
+        (0, 0) => Value::Zero,
+        (0, _) => Value::Finite {
+            sig: frac as u128,
+            exp: 1 - F::BIAS - F::FRAC_BITS as i32,
+        },
+        (e, 0) if e == F::EXP_MAX => Value::Inf,
+        (e, _) if e == F::EXP_MAX => Value::Nan {
+            signaling: frac & F::QUIET == 0,
+        },
+        (e, _) => Value::Finite {
+            sig: (frac | 1 << F::FRAC_BITS) as u128,
+            exp: e as i32 - F::BIAS - F::FRAC_BITS as i32,
+        },
+    };
+    (sign, value)
+}
+
+#[inline(always)]
+fn sign_bit<F: Format>(sign: bool) -> u64 {
+    if sign {
+        F::SIGN
+    } else {
+        0
+    }
+}
+
+#[inline(always)]
+pub fn is_nan<F: Format>(bits: u64) -> bool {
+    bits & !F::SIGN > F::INF
+}
+
+#[inline(always)]
+pub fn is_signaling<F: Format>(bits: u64) -> bool {
+    is_nan::<F>(bits) && bits & F::QUIET == 0
+}
+
+#[inline(always)]
+fn msb(sig: u128) -> i32 {
+    127 - sig.leading_zeros() as i32
+}
+
+/// Rounds `sig * 2^exp` (plus something below its lsb when `sticky`) to a
+/// multiple of `2^lsb`, returning the multiple and whether it is inexact.
+#[inline(always)]
+fn round(
+    sign: bool,
+    sig: u128,
+    exp: i32,
+    sticky: bool,
+    lsb: i32,
+    rm: RoundingMode,
+) -> (u128, bool) {
+    let shift = lsb - exp;
+    if shift <= 0 {
+        debug_assert!(!sticky, "not enough guard bits");
+        return (sig << -shift, false);
+    }
+    let (kept, half, rest) = if shift > 128 {
+        (0, false, sig != 0 || sticky)
+    } else if shift == 128 {
+        (0, sig >> 127 != 0, sig << 1 != 0 || sticky)
+    } else {
+        let half = (sig >> (shift - 1)) & 1 != 0;
+        let rest = sig & ((1 << (shift - 1)) - 1) != 0 || sticky;
+        (sig >> shift, half, rest)
+    };
+    let inexact = half || rest;
+    let up = match rm {
+        RoundingMode::Rne => half && (rest || kept & 1 != 0),
+        RoundingMode::Rtz => false,
+        RoundingMode::Rdn => sign && inexact,
+        RoundingMode::Rup => !sign && inexact,
+        RoundingMode::Rmm => half,
+    };
+    (kept + up as u128, inexact)
+}
+
+/// Rounds a non-zero `sig * 2^exp` to the format. When `sticky` is set `sig`
+/// must carry at least two bits more than the format's precision.
+fn round_pack<F: Format>(sign: bool, sig: u128, exp: i32, sticky: bool, env: &mut FloatEnv) -> u64 {
+    debug_assert!(sig != 0);
+    let fb = F::FRAC_BITS as i32;
+    let emin = 1 - F::BIAS;
+    let top = exp + msb(sig);
+    if top > F::BIAS {
+        return overflow::<F>(sign, env);
+    }
+    let (kept, lsb, inexact) = if top < emin {
+        let (unbounded, _) = round(sign, sig, exp, sticky, top - fb, env.rm);
+        let tiny = top < emin - 1 || unbounded >> (fb + 1) == 0;
+        let (kept, inexact) = round(sign, sig, exp, sticky, emin - fb, env.rm);
+        if tiny && inexact {
+            env.flags |= UF;
+        }
+        (kept, emin - fb, inexact)
+    } else {
+        let (kept, inexact) = round(sign, sig, exp, sticky, top - fb, env.rm);
+        (kept, top - fb, inexact)
+    };
+    if inexact {
+        env.flags |= NX;
+    }
+    // a carry out of the significand bumps the exponent field
+    let bits = ((((lsb - emin + fb) as u64) << fb) as u128 + kept) as u64;
+    if bits >= F::INF {
+        return overflow::<F>(sign, env);
+    }
+    sign_bit::<F>(sign) | bits
+}
+
+#[inline(always)]
+fn overflow<F: Format>(sign: bool, env: &mut FloatEnv) -> u64 {
+    env.flags |= OF | NX;
+    let to_inf = match env.rm {
+        RoundingMode::Rne | RoundingMode::Rmm => true,
+        RoundingMode::Rtz => false,
+        RoundingMode::Rdn => sign,
+        RoundingMode::Rup => !sign,
+    };
+    sign_bit::<F>(sign) | if to_inf { F::INF } else { F::INF - 1 }
+}
+
+/// Positions `sig * 2^exp` on the grid `2^base`, the bits shifted out are
+/// folded into the returned sticky flag.
+#[inline(always)]
+fn align(sig: u128, exp: i32, base: i32) -> (u128, bool) {
+    let shift = exp - base;
+    if shift >= 0 {
+        (sig << shift, false)
+    } else if shift <= -128 {
+        (0, sig != 0)
+    } else {
+        (sig >> -shift, sig & ((1 << -shift) - 1) != 0)
+    }
+}
+
+/// Adds two non-zero finite values of at most 106 significant bits each.
+fn add_finite<F: Format>(
+    (sign_a, sig_a, exp_a): (bool, u128, i32),
+    (sign_b, sig_b, exp_b): (bool, u128, i32),
+    env: &mut FloatEnv,
+) -> u64 {
+    // the larger operand lands with its msb on bit 125, only the other one
+    // can lose bits and it is then smaller by far
+    let base = (exp_a + msb(sig_a)).max(exp_b + msb(sig_b)) - 125;
+    let (a, sticky_a) = align(sig_a, exp_a, base);
+    let (b, sticky_b) = align(sig_b, exp_b, base);
+    let sticky = sticky_a || sticky_b;
+    if sign_a == sign_b {
+        return round_pack::<F>(sign_a, a + b, base, sticky, env);
+    }
+    let (sign, mut diff) = if a > b {
+        (sign_a, a - b)
+    } else {
+        (sign_b, b - a)
+    };
+    if sticky {
+        diff -= 1;
+    } else if diff == 0 {
+        return sign_bit::<F>(env.rm == RoundingMode::Rdn);
+    }
+    round_pack::<F>(sign, diff, base, sticky, env)
+}
+
+#[inline(always)]
+fn invalid<F: Format>(env: &mut FloatEnv) -> u64 {
+    env.flags |= NV;
+    F::NAN
+}
+
+/// Propagates NaN operands as the canonical NaN, signaling ones raise NV.
+#[inline(always)]
+fn nan<F: Format>(operands: &[u64], env: &mut FloatEnv) -> u64 {
+    if operands.iter().any(|&bits| is_signaling::<F>(bits)) {
+        env.flags |= NV;
+    }
+    F::NAN
+}
+
+pub fn add<F: Format>(a: u64, b: u64, env: &mut FloatEnv) -> u64 {
+    match (unpack::<F>(a), unpack::<F>(b)) {
+        ((_, Value::Nan { .. }), _) | (_, (_, Value::Nan { .. })) => nan::<F>(&[a, b], env),
+        ((sa, Value::Inf), (sb, Value::Inf)) if sa != sb => invalid::<F>(env),
+        ((_, Value::Inf), _) => a,
+        (_, (_, Value::Inf)) => b,
+        ((sa, Value::Zero), (sb, Value::Zero)) if sa != sb => {
+            sign_bit::<F>(env.rm == RoundingMode::Rdn)
+        }
+        ((_, Value::Zero), _) => b,
+        (_, (_, Value::Zero)) => a,
+        ((sa, Value::Finite { sig: ma, exp: ea }), (sb, Value::Finite { sig: mb, exp: eb })) => {
+            add_finite::<F>((sa, ma, ea), (sb, mb, eb), env)
+        }
+    }
+}
+
+#[inline(always)]
+pub fn sub<F: Format>(a: u64, b: u64, env: &mut FloatEnv) -> u64 {
+    add::<F>(a, b ^ F::SIGN, env)
+}
+
+pub fn mul<F: Format>(a: u64, b: u64, env: &mut FloatEnv) -> u64 {
+    let ((sa, va), (sb, vb)) = (unpack::<F>(a), unpack::<F>(b));
+    let sign = sa != sb;
+    match (va, vb) {
+        (Value::Nan { .. }, _) | (_, Value::Nan { .. }) => nan::<F>(&[a, b], env),
+        (Value::Inf, Value::Zero) | (Value::Zero, Value::Inf) => invalid::<F>(env),
+        (Value::Inf, _) | (_, Value::Inf) => sign_bit::<F>(sign) | F::INF,
+        (Value::Zero, _) | (_, Value::Zero) => sign_bit::<F>(sign),
+        (Value::Finite { sig: ma, exp: ea }, Value::Finite { sig: mb, exp: eb }) => {
+            round_pack::<F>(sign, ma * mb, ea + eb, false, env)
+        }
+    }
+}
+
+pub fn div<F: Format>(a: u64, b: u64, env: &mut FloatEnv) -> u64 {
+    let ((sa, va), (sb, vb)) = (unpack::<F>(a), unpack::<F>(b));
+    let sign = sa != sb;
+    match (va, vb) {
+        (Value::Nan { .. }, _) | (_, Value::Nan { .. }) => nan::<F>(&[a, b], env),
+        (Value::Inf, Value::Inf) | (Value::Zero, Value::Zero) => invalid::<F>(env),
+        (Value::Inf, _) => sign_bit::<F>(sign) | F::INF,
+        (_, Value::Inf) | (Value::Zero, _) => sign_bit::<F>(sign),
+        (_, Value::Zero) => {
+            env.flags |= DZ;
+            sign_bit::<F>(sign) | F::INF
+        }
+        (Value::Finite { sig: ma, exp: ea }, Value::Finite { sig: mb, exp: eb }) => {
+            let shift = 125 - msb(ma);
+            let num = ma << shift;
+            let (q, r) = (num / mb, num % mb);
+            round_pack::<F>(sign, q, ea - shift - eb, r != 0, env)
+        }
+    }
+}
+
+#[inline(always)]
+fn isqrt(n: u128) -> u128 {
+    let (mut rem, mut root) = (n, 0u128);
+    let mut bit = 1u128 << 126;
+    while bit > n {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if rem >= root + bit {
+            rem -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+pub fn sqrt<F: Format>(a: u64, env: &mut FloatEnv) -> u64 {
+    match unpack::<F>(a) {
+        (_, Value::Nan { .. }) => nan::<F>(&[a], env),
+        (_, Value::Zero) => a,
+        (true, _) => invalid::<F>(env),
+        (false, Value::Inf) => a,
+        (false, Value::Finite { sig, exp }) => {
+            // an even exponent and about 120 significant bits leave the
+            // root with plenty of guard bits
+            let mut shift = 120 - msb(sig);
+            if (exp - shift) & 1 != 0 {
+                shift += 1;
+            }
+            let n = sig << shift;
+            let root = isqrt(n);
+            round_pack::<F>(false, root, (exp - shift) / 2, root * root != n, env)
+        }
+    }
+}
+
+/// `a * b + c` with a single rounding.
+pub fn fma<F: Format>(a: u64, b: u64, c: u64, env: &mut FloatEnv) -> u64 {
+    let ((sa, va), (sb, vb), (sc, vc)) = (unpack::<F>(a), unpack::<F>(b), unpack::<F>(c));
+    let sign = sa != sb;
+    match (va, vb, vc) {
+        (Value::Nan { .. }, _, _) | (_, Value::Nan { .. }, _) => nan::<F>(&[a, b, c], env),
+        (Value::Inf, Value::Zero, _) | (Value::Zero, Value::Inf, _) => {
+            nan::<F>(&[c], env);
+            invalid::<F>(env)
+        }
+        (_, _, Value::Nan { .. }) => nan::<F>(&[c], env),
+        (Value::Inf, _, Value::Inf) | (_, Value::Inf, Value::Inf) if sign != sc => {
+            invalid::<F>(env)
+        }
+        (Value::Inf, _, _) | (_, Value::Inf, _) => sign_bit::<F>(sign) | F::INF,
+        (_, _, Value::Inf) => c,
+        (Value::Zero, _, Value::Zero) | (_, Value::Zero, Value::Zero) => {
+            if sign == sc {
+                c
+            } else {
+                sign_bit::<F>(env.rm == RoundingMode::Rdn)
+            }
+        }
+        (Value::Zero, _, _) | (_, Value::Zero, _) => c,
+        (Value::Finite { sig: ma, exp: ea }, Value::Finite { sig: mb, exp: eb }, Value::Zero) => {
+            round_pack::<F>(sign, ma * mb, ea + eb, false, env)
+        }
+        (
+            Value::Finite { sig: ma, exp: ea },
+            Value::Finite { sig: mb, exp: eb },
+            Value::Finite { sig: mc, exp: ec },
+        ) => add_finite::<F>((sign, ma * mb, ea + eb), (sc, mc, ec), env),
+    }
+}
+
+/// Quiet equality, only signaling NaNs raise NV.
+pub fn eq<F: Format>(a: u64, b: u64, env: &mut FloatEnv) -> bool {
+    if is_nan::<F>(a) || is_nan::<F>(b) {
+        nan::<F>(&[a, b], env);
+        return false;
+    }
+    a == b || (a | b) & !F::SIGN == 0
+}
+
+#[inline(always)]
+fn less<F: Format>(a: u64, b: u64, or_equal: bool) -> bool {
+    let (sa, sb) = (a & F::SIGN != 0, b & F::SIGN != 0);
+    if (a | b) & !F::SIGN == 0 {
+        or_equal
+    } else if sa != sb {
+        sa
+    } else if a == b {
+        or_equal
+    } else {
+        (a < b) != sa
+    }
+}
+
+/// Signaling less than, any NaN raises NV.
+pub fn lt<F: Format>(a: u64, b: u64, env: &mut FloatEnv) -> bool {
+    if is_nan::<F>(a) || is_nan::<F>(b) {
+        env.flags |= NV;
+        return false;
+    }
+    less::<F>(a, b, false)
+}
+
+/// Signaling less than or equal, any NaN raises NV.
+pub fn le<F: Format>(a: u64, b: u64, env: &mut FloatEnv) -> bool {
+    if is_nan::<F>(a) || is_nan::<F>(b) {
+        env.flags |= NV;
+        return false;
+    }
+    less::<F>(a, b, true)
+}
+
+/// IEEE 754-2019 minimumNumber, -0 is less than +0 and a single NaN operand
+/// is ignored.
+pub fn min<F: Format>(a: u64, b: u64, env: &mut FloatEnv) -> u64 {
+    min_max::<F>(a, b, false, env)
+}
+
+/// IEEE 754-2019 maximumNumber.
+pub fn max<F: Format>(a: u64, b: u64, env: &mut FloatEnv) -> u64 {
+    min_max::<F>(a, b, true, env)
+}
+
+#[inline(always)]
+fn min_max<F: Format>(a: u64, b: u64, max: bool, env: &mut FloatEnv) -> u64 {
+    match (is_nan::<F>(a), is_nan::<F>(b)) {
+        (true, true) => nan::<F>(&[a, b], env),
+        (true, false) => {
+            nan::<F>(&[a], env);
+            b
+        }
+        (false, true) => {
+            nan::<F>(&[b], env);
+            a
+        }
+        // -0 is less than +0 here
+        (false, false) if (a | b) & !F::SIGN == 0 => {
+            if (a & F::SIGN != 0) != max {
+                a
+            } else {
+                b
+            }
+        }
+        (false, false) if less::<F>(a, b, false) != max => a,
+        (false, false) => b,
+    }
+}
+
+/// The `fclass` mask of a value.
+pub fn classify<F: Format>(a: u64) -> u64 {
+    let (sign, value) = unpack::<F>(a);
+    let subnormal = a & (F::EXP_MAX << F::FRAC_BITS) == 0;
+    match (sign, value) {
+        (true, Value::Inf) => 1 << 0,
+        (true, Value::Finite { .. }) if !subnormal => 1 << 1,
+        (true, Value::Finite { .. }) => 1 << 2,
+        (true, Value::Zero) => 1 << 3,
+        (false, Value::Zero) => 1 << 4,
+        (false, Value::Finite { .. }) if subnormal => 1 << 5,
+        (false, Value::Finite { .. }) => 1 << 6,
+        (false, Value::Inf) => 1 << 7,
+        (_, Value::Nan { signaling: true }) => 1 << 8,
+        (_, Value::Nan { signaling: false }) => 1 << 9,
+    }
+}
+
+/// Converts to a `width`-bit integer, the result is zero extended from
+/// `width` bits. NaN and out of range values raise NV and saturate, NaN to
+/// the largest integer.
+pub fn to_int<F: Format>(a: u64, signed: bool, width: u32, env: &mut FloatEnv) -> u64 {
+    let max_pos: u128 = if signed {
+        (1 << (width - 1)) - 1
+    } else {
+        (1 << width) - 1
+    };
+    let max_neg: u128 = if signed { 1 << (width - 1) } else { 0 };
+    let mask = (1u128 << width) - 1;
+    let saturate = |sign: bool, env: &mut FloatEnv| {
+        env.flags |= NV;
+        (if sign {
+            max_neg.wrapping_neg() & mask
+        } else {
+            max_pos
+        }) as u64
+    };
+    match unpack::<F>(a) {
+        (_, Value::Nan { .. }) => saturate(false, env),
+        (sign, Value::Inf) => saturate(sign, env),
+        (_, Value::Zero) => 0,
+        (sign, Value::Finite { sig, exp }) => {
+            if exp + msb(sig) >= 64 {
+                return saturate(sign, env);
+            }
+            let (magnitude, inexact) = round(sign, sig, exp, false, 0, env.rm);
+            if magnitude > if sign { max_neg } else { max_pos } {
+                return saturate(sign, env);
+            }
+            if inexact {
+                env.flags |= NX;
+            }
+            (if sign {
+                magnitude.wrapping_neg()
+            } else {
+                magnitude
+            } & mask) as u64
+        }
+    }
+}
+
+/// Converts the `width`-bit integer in the low bits of `a`.
+pub fn from_int<F: Format>(a: u64, signed: bool, width: u32, env: &mut FloatEnv) -> u64 {
+    let value = a as u128 & ((1 << width) - 1);
+    let sign = signed && value >> (width - 1) != 0;
+    let magnitude = if sign { (1 << width) - value } else { value };
+    if magnitude == 0 {
+        return 0;
+    }
+    round_pack::<F>(sign, magnitude, 0, false, env)
+}
+
+/// Converts between formats, NaNs become the canonical NaN of `T`.
+pub fn convert<F: Format, T: Format>(a: u64, env: &mut FloatEnv) -> u64 {
+    match unpack::<F>(a) {
+        (_, Value::Nan { .. }) => {
+            nan::<F>(&[a], env);
+            T::NAN
+        }
+        (sign, Value::Inf) => sign_bit::<T>(sign) | T::INF,
+        (sign, Value::Zero) => sign_bit::<T>(sign),
+        (sign, Value::Finite { sig, exp }) => round_pack::<T>(sign, sig, exp, false, env),
+    }
+}
+
+/// Rounds to an integral value in the same format, as `fround` does. Only
+/// `exact` rounding raises NX for a value that changed.
+pub fn round_int<F: Format>(a: u64, exact: bool, env: &mut FloatEnv) -> u64 {
+    match unpack::<F>(a) {
+        (_, Value::Nan { .. }) => nan::<F>(&[a], env),
+        (_, Value::Inf | Value::Zero) => a,
+        (_, Value::Finite { exp, .. }) if exp >= 0 => a,
+        (sign, Value::Finite { sig, exp }) => {
+            let (magnitude, inexact) = round(sign, sig, exp, false, 0, env.rm);
+            if exact && inexact {
+                env.flags |= NX;
+            }
+            if magnitude == 0 {
+                sign_bit::<F>(sign)
+            } else {
+                round_pack::<F>(sign, magnitude, 0, false, env)
+            }
+        }
+    }
+}
+
+/// Truncates to an integer modulo `2^width`, as `fcvtmod.w.d` does. NaNs and
+/// infinities give zero, integers outside the signed `width`-bit range raise
+/// NV and otherwise dropped fraction bits raise NX.
+pub fn to_int_mod<F: Format>(a: u64, width: u32, env: &mut FloatEnv) -> u64 {
+    let mask = (1u128 << width) - 1;
+    match unpack::<F>(a) {
+        (_, Value::Nan { .. } | Value::Inf) => {
+            env.flags |= NV;
+            0
+        }
+        (_, Value::Zero) => 0,
+        (sign, Value::Finite { sig, exp }) => {
+            // only the low bits of huge integers are kept
+            let (magnitude, inexact) = match exp {
+                128.. => (0, false),
+                0.. => (sig << exp, false),
+                _ => round(sign, sig, exp, false, 0, RoundingMode::Rtz),
+            };
+            let top = exp + msb(sig);
+            let min = sign && magnitude == 1 << (width - 1);
+            if top >= width as i32 - 1 && !min {
+                env.flags |= NV;
+            } else if inexact {
+                env.flags |= NX;
+            }
+            (if sign {
+                magnitude.wrapping_neg()
+            } else {
+                magnitude
+            } & mask) as u64
+        }
+    }
+}
+
+/// The `fli` constants from index 2 to 29 as `(exponent, fraction)`, with the
+/// two top fraction bits.
+const FLI: [(i32, u64); 28] = [
+    (-16, 0),
+    (-15, 0),
+    (-8, 0),
+    (-7, 0),
+    (-4, 0),
+    (-3, 0),
+    (-2, 0),
+    (-2, 1),
+    (-2, 2),
+    (-2, 3),
+    (-1, 0),
+    (-1, 1),
+    (-1, 2),
+    (-1, 3),
+    (0, 0),
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (1, 0),
+    (1, 1),
+    (1, 2),
+    (2, 0),
+    (3, 0),
+    (4, 0),
+    (7, 0),
+    (8, 0),
+    (15, 0),
+    (16, 0),
+];
+
+/// The constant `fli` loads for `index`, -1.0, the smallest normal, the
+/// table values, infinity and the canonical NaN. Values past the format's
+/// range become infinity or subnormals.
+pub fn fli<F: Format>(index: u8) -> u64 {
+    match index {
+        0 => F::SIGN | (F::BIAS as u64) << F::FRAC_BITS,
+        1 => 1 << F::FRAC_BITS,
+        2..=29 => {
+            let (exp, frac) = FLI[index as usize - 2];
+            let biased = exp + F::BIAS;
+            if biased < 1 {
+                (4 | frac) << (F::FRAC_BITS - 2) >> (1 - biased)
+            } else if biased as u64 >= F::EXP_MAX {
+                F::INF
+            } else {
+                (biased as u64) << F::FRAC_BITS | frac << (F::FRAC_BITS - 2)
+            }
+        }
+        30 => F::INF,
+        _ => F::NAN,
+    }
+}
+
+/// `vfrsqrt7` estimates, indexed by the exponent parity and the six top
+/// fraction bits of the normalised operand.
+const RSQRT7: [u8; 128] = [
+    52, 51, 50, 48, 47, 46, 44, 43, 42, 41, 40, 39, 38, 36, 35, 34, 33, 32, 31, 30, 30, 29, 28, 27,
+    26, 25, 24, 23, 23, 22, 21, 20, 19, 19, 18, 17, 16, 16, 15, 14, 14, 13, 12, 12, 11, 10, 10, 9,
+    9, 8, 7, 7, 6, 6, 5, 4, 4, 3, 3, 2, 2, 1, 1, 0, 127, 125, 123, 121, 119, 118, 116, 114, 113,
+    111, 109, 108, 106, 105, 103, 102, 100, 99, 97, 96, 95, 93, 92, 91, 90, 88, 87, 86, 85, 84, 83,
+    82, 80, 79, 78, 77, 76, 75, 74, 73, 72, 71, 70, 70, 69, 68, 67, 66, 65, 64, 63, 63, 62, 61, 60,
+    59, 59, 58, 57, 56, 56, 55, 54, 53,
+];
+
+/// `vfrec7` estimates, indexed by the seven top fraction bits of the
+/// normalised operand.
+const REC7: [u8; 128] = [
+    127, 125, 123, 121, 119, 117, 116, 114, 112, 110, 109, 107, 105, 104, 102, 100, 99, 97, 96, 94,
+    93, 91, 90, 88, 87, 85, 84, 83, 81, 80, 79, 77, 76, 75, 74, 72, 71, 70, 69, 68, 66, 65, 64, 63,
+    62, 61, 60, 59, 58, 57, 56, 55, 54, 53, 52, 51, 50, 49, 48, 47, 46, 45, 44, 43, 42, 41, 40, 40,
+    39, 38, 37, 36, 35, 35, 34, 33, 32, 31, 31, 30, 29, 28, 28, 27, 26, 25, 25, 24, 23, 23, 22, 21,
+    21, 20, 19, 19, 18, 17, 17, 16, 15, 15, 14, 14, 13, 12, 12, 11, 11, 10, 9, 9, 8, 8, 7, 7, 6, 5,
+    5, 4, 4, 3, 3, 2, 2, 1, 1, 0,
+];
+
+/// Splits a non-zero finite value into its sign, exponent and fraction, with
+/// subnormals normalised so that the exponent can go below one.
+#[inline(always)]
+fn normalize<F: Format>(bits: u64) -> (bool, i32, u64) {
+    let mut exp = ((bits >> F::FRAC_BITS) & F::EXP_MAX) as i32;
+    let mut frac = bits & ((1 << F::FRAC_BITS) - 1);
+    if exp == 0 {
+        while frac >> F::FRAC_BITS == 0 {
+            frac <<= 1;
+            exp -= 1;
+        }
+        exp += 1;
+    }
+    (bits & F::SIGN != 0, exp, frac & ((1 << F::FRAC_BITS) - 1))
+}
+
+/// Reciprocal square root estimate to 7 bits, as `vfrsqrt7` computes it.
+pub fn rsqrt7<F: Format>(a: u64, env: &mut FloatEnv) -> u64 {
+    match unpack::<F>(a) {
+        (_, Value::Nan { .. }) => nan::<F>(&[a], env),
+        (sign, Value::Zero) => {
+            env.flags |= DZ;
+            sign_bit::<F>(sign) | F::INF
+        }
+        (true, _) => invalid::<F>(env),
+        (false, Value::Inf) => 0,
+        (false, Value::Finite { .. }) => {
+            let (_, exp, frac) = normalize::<F>(a);
+            let index = ((exp & 1) as usize) << 6 | (frac >> (F::FRAC_BITS - 6)) as usize;
+            let out_exp = ((3 * F::BIAS - 1 - exp) / 2) as u64;
+            out_exp << F::FRAC_BITS | (RSQRT7[index] as u64) << (F::FRAC_BITS - 7)
+        }
+    }
+}
+
+/// Reciprocal estimate to 7 bits, as `vfrec7` computes it. Results too large
+/// for the format overflow as a rounded result would.
+pub fn rec7<F: Format>(a: u64, env: &mut FloatEnv) -> u64 {
+    match unpack::<F>(a) {
+        (_, Value::Nan { .. }) => nan::<F>(&[a], env),
+        (sign, Value::Zero) => {
+            env.flags |= DZ;
+            sign_bit::<F>(sign) | F::INF
+        }
+        (sign, Value::Inf) => sign_bit::<F>(sign),
+        (sign, Value::Finite { .. }) => {
+            let (_, exp, frac) = normalize::<F>(a);
+            if exp < -1 {
+                return overflow::<F>(sign, env);
+            }
+            let out_exp = 2 * F::BIAS - 1 - exp;
+            let out_frac =
+                (REC7[(frac >> (F::FRAC_BITS - 7)) as usize] as u64) << (F::FRAC_BITS - 7);
+            let bits = if out_exp < 1 {
+                // the implicit bit shifts into a subnormal significand
+                (1 << F::FRAC_BITS | out_frac) >> (1 - out_exp)
+            } else {
+                (out_exp as u64) << F::FRAC_BITS | out_frac
+            };
+            sign_bit::<F>(sign) | bits
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [RoundingMode; 5] = [
+        RoundingMode::Rne,
+        RoundingMode::Rtz,
+        RoundingMode::Rdn,
+        RoundingMode::Rup,
+        RoundingMode::Rmm,
+    ];
+
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Biased towards special values and extreme exponents.
+        fn f32(&mut self) -> u32 {
+            let bits = self.next();
+            let exp = match bits >> 60 {
+                0 => 0,
+                1 => 0xff,
+                2 => 1,
+                3 => 0xfe,
+                _ => (bits >> 32) as u32 & 0xff,
+            };
+            (bits as u32 & 0x807fffff) | exp << 23
+        }
+
+        fn f64(&mut self) -> u64 {
+            let bits = self.next();
+            let exp = match self.next() >> 60 {
+                0 => 0,
+                1 => 0x7ff,
+                2 => 1,
+                3 => 0x7fe,
+                _ => self.next() & 0x7ff,
+            };
+            (bits & 0x800fffff_ffffffff) | exp << 52
+        }
+    }
+
+    fn host32(value: f32) -> u64 {
+        if value.is_nan() {
+            F32::NAN
+        } else {
+            value.to_bits() as u64
+        }
+    }
+
+    fn host64(value: f64) -> u64 {
+        if value.is_nan() {
+            F64::NAN
+        } else {
+            value.to_bits()
+        }
+    }
+
+    #[test]
+    fn test_matches_host_rne() {
+        let mut rng = Rng(0x9e3779b97f4a7c15);
+        for _ in 0..200_000 {
+            let (a, b, c) = (rng.f32(), rng.f32(), rng.f32());
+            let (fa, fb, fc) = (f32::from_bits(a), f32::from_bits(b), f32::from_bits(c));
+            let (a, b, c) = (a as u64, b as u64, c as u64);
+            let env = &mut FloatEnv::new(RoundingMode::Rne);
+            assert_eq!(add::<F32>(a, b, env), host32(fa + fb), "{a:#x} + {b:#x}");
+            assert_eq!(sub::<F32>(a, b, env), host32(fa - fb), "{a:#x} - {b:#x}");
+            assert_eq!(mul::<F32>(a, b, env), host32(fa * fb), "{a:#x} * {b:#x}");
+            assert_eq!(div::<F32>(a, b, env), host32(fa / fb), "{a:#x} / {b:#x}");
+            assert_eq!(sqrt::<F32>(a, env), host32(fa.sqrt()), "sqrt {a:#x}");
+            assert_eq!(
+                fma::<F32>(a, b, c, env),
+                host32(fa.mul_add(fb, fc)),
+                "{a:#x} * {b:#x} + {c:#x}"
+            );
+            assert_eq!(convert::<F32, F64>(a, env), host64(fa as f64));
+            if !fa.is_nan() {
+                let rtz = &mut FloatEnv::new(RoundingMode::Rtz);
+                assert_eq!(to_int::<F32>(a, true, 32, rtz), fa as i32 as u32 as u64);
+                assert_eq!(to_int::<F32>(a, false, 64, rtz), fa as u64);
+            }
+            let x = rng.next();
+            assert_eq!(from_int::<F32>(x, true, 32, env), host32(x as i32 as f32));
+            assert_eq!(from_int::<F32>(x, false, 64, env), host32(x as f32));
+            assert_eq!(from_int::<F64>(x, true, 64, env), host64(x as i64 as f64));
+
+            let (a, b, c) = (rng.f64(), rng.f64(), rng.f64());
+            let (fa, fb, fc) = (f64::from_bits(a), f64::from_bits(b), f64::from_bits(c));
+            assert_eq!(add::<F64>(a, b, env), host64(fa + fb), "{a:#x} + {b:#x}");
+            assert_eq!(sub::<F64>(a, b, env), host64(fa - fb), "{a:#x} - {b:#x}");
+            assert_eq!(mul::<F64>(a, b, env), host64(fa * fb), "{a:#x} * {b:#x}");
+            assert_eq!(div::<F64>(a, b, env), host64(fa / fb), "{a:#x} / {b:#x}");
+            assert_eq!(sqrt::<F64>(a, env), host64(fa.sqrt()), "sqrt {a:#x}");
+            assert_eq!(
+                fma::<F64>(a, b, c, env),
+                host64(fa.mul_add(fb, fc)),
+                "{a:#x} * {b:#x} + {c:#x}"
+            );
+            assert_eq!(convert::<F64, F32>(a, env), host32(fa as f32));
+        }
+    }
+
+    #[test]
+    fn test_zfa() {
+        let table = [
+            -1.0,
+            f64::MIN_POSITIVE,
+            2f64.powi(-16),
+            2f64.powi(-15),
+            2f64.powi(-8),
+            2f64.powi(-7),
+            0.0625,
+            0.125,
+            0.25,
+            0.3125,
+            0.375,
+            0.4375,
+            0.5,
+            0.625,
+            0.75,
+            0.875,
+            1.0,
+            1.25,
+            1.5,
+            1.75,
+            2.0,
+            2.5,
+            3.0,
+            4.0,
+            8.0,
+            16.0,
+            128.0,
+            256.0,
+            2f64.powi(15),
+            2f64.powi(16),
+            f64::INFINITY,
+            f64::NAN,
+        ];
+        for (index, value) in table.into_iter().enumerate() {
+            assert_eq!(fli::<F64>(index as u8), host64(value), "{index}");
+            if index != 1 {
+                assert_eq!(fli::<F32>(index as u8), host32(value as f32), "{index}");
+            }
+        }
+        assert_eq!(fli::<F32>(1), host32(f32::MIN_POSITIVE));
+        // the half table reaches into subnormals and past the largest half
+        assert_eq!(fli::<F16>(0), 0xbc00);
+        assert_eq!(fli::<F16>(1), 0x0400);
+        assert_eq!(fli::<F16>(2), 0x0100);
+        assert_eq!(fli::<F16>(3), 0x0200);
+        assert_eq!(fli::<F16>(28), 0x7800);
+        assert_eq!(fli::<F16>(29), 0x7c00);
+
+        let mut rng = Rng(0x853c49e6748fea9b);
+        for _ in 0..100_000 {
+            let a = rng.f64();
+            let fa = f64::from_bits(a);
+            for (rm, host) in [
+                (RoundingMode::Rne, fa.round_ties_even()),
+                (RoundingMode::Rtz, fa.trunc()),
+                (RoundingMode::Rdn, fa.floor()),
+                (RoundingMode::Rup, fa.ceil()),
+                (RoundingMode::Rmm, fa.round()),
+            ] {
+                let env = &mut FloatEnv::new(rm);
+                assert_eq!(round_int::<F64>(a, true, env), host64(host), "{a:#x}");
+                let changed = !fa.is_nan() && host != fa;
+                assert_eq!(env.flags & NX != 0, changed, "{a:#x}");
+            }
+            let env = &mut FloatEnv::new(RoundingMode::Rne);
+            round_int::<F64>(a, false, env);
+            assert_eq!(env.flags & NX, 0);
+        }
+
+        for (value, result, flags) in [
+            (3.7, 3, NX),
+            (-1.5, 0xffff_ffff, NX),
+            (-2147483648.0, 0x8000_0000, 0),
+            (-2147483648.5, 0x8000_0000, NX),
+            (2147483648.0, 0x8000_0000, NV),
+            (4294967301.0, 5, NV),
+            (-4294967301.0, 0xffff_fffb, NV),
+            (1e300, 0, NV),
+            (f64::INFINITY, 0, NV),
+            (f64::NAN, 0, NV),
+            (-0.0, 0, 0),
+            (1e-310, 0, NX),
+        ] {
+            let env = &mut FloatEnv::new(RoundingMode::Rtz);
+            let a = value.to_bits();
+            assert_eq!(to_int_mod::<F64>(a, 32, env), result, "{value}");
+            assert_eq!(env.flags, flags, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_directed_rounding() {
+        // single precision products are exact in double precision, the
+        // directed modes must bracket them
+        let mut rng = Rng(0x2545f4914f6cdd1d);
+        for _ in 0..100_000 {
+            let (a, b) = (rng.f32() as u64, rng.f32() as u64);
+            let exact = f32::from_bits(a as u32) as f64 * f32::from_bits(b as u32) as f64;
+            if !exact.is_finite() || exact.abs() > f32::MAX as f64 {
+                continue;
+            }
+            let results = MODES.map(|rm| {
+                let env = &mut FloatEnv::new(rm);
+                let bits = mul::<F32>(a, b, env);
+                (f32::from_bits(bits as u32) as f64, env.flags)
+            });
+            let [(rne, _), (rtz, _), (rdn, _), (rup, _), (rmm, _)] = results;
+            assert!(rdn <= exact && exact <= rup, "{a:#x} * {b:#x}");
+            assert_eq!(rtz, if exact < 0.0 { rup } else { rdn });
+            assert!(rne == rdn || rne == rup);
+            assert!(rmm == rdn || rmm == rup);
+            let inexact = rdn != rup;
+            for (_, flags) in results {
+                assert_eq!(flags & NX != 0, inexact || flags & OF != 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_flags() {
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(div::<F32>(0x3f800000, 0, env), F32::INF);
+        assert_eq!(env.flags, DZ);
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(div::<F32>(0, 0, env), F32::NAN);
+        assert_eq!(env.flags, NV);
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(sqrt::<F64>((-1.0f64).to_bits(), env), F64::NAN);
+        assert_eq!(env.flags, NV);
+        // quiet NaNs propagate without NV, signaling ones raise it
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(add::<F32>(0x7fc00001, 0x3f800000, env), F32::NAN);
+        assert_eq!(env.flags, 0);
+        assert_eq!(add::<F32>(0x7f800001, 0x3f800000, env), F32::NAN);
+        assert_eq!(env.flags, NV);
+        // the largest value overflows to infinity or saturates
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(mul::<F32>(0x7f7fffff, 0x40000000, env), F32::INF);
+        assert_eq!(env.flags, OF | NX);
+        let env = &mut FloatEnv::new(RoundingMode::Rtz);
+        assert_eq!(mul::<F32>(0x7f7fffff, 0x40000000, env), 0x7f7fffff);
+        let env = &mut FloatEnv::new(RoundingMode::Rdn);
+        assert_eq!(mul::<F32>(0xff7fffff, 0x40000000, env), 0xff800000);
+        // 1/3 is inexact
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(div::<F32>(0x3f800000, 0x40400000, env), 0x3eaaaaab);
+        assert_eq!(env.flags, NX);
+        // inf * 0 + qNaN is invalid
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(fma::<F32>(F32::INF, 0, F32::NAN, env), F32::NAN);
+        assert_eq!(env.flags, NV);
+    }
+
+    #[test]
+    fn test_tininess_after_rounding() {
+        // (1 - 2^-24) * 2^-126 rounds up to the smallest normal but is tiny
+        // even with an unbounded exponent
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(mul::<F32>(0x3f7fffff, 0x00800000, env), 0x00800000);
+        assert_eq!(env.flags, UF | NX);
+        // 2^-126 - 2^-151 only reaches the smallest normal once rounded to
+        // full precision, so it is not tiny
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(
+            round_pack::<F32>(false, (1 << 25) - 1, -151, false, env),
+            0x00800000
+        );
+        assert_eq!(env.flags, NX);
+        // exact subnormal results do not underflow
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(mul::<F32>(0x3f000000, 0x00000002, env), 0x00000001);
+        assert_eq!(env.flags, 0);
+    }
+
+    #[test]
+    fn test_exact_zero_sign() {
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(sub::<F64>(1.5f64.to_bits(), 1.5f64.to_bits(), env), 0);
+        let env = &mut FloatEnv::new(RoundingMode::Rdn);
+        assert_eq!(
+            sub::<F64>(1.5f64.to_bits(), 1.5f64.to_bits(), env),
+            F64::SIGN
+        );
+        assert_eq!(add::<F64>(F64::SIGN, 0, env), F64::SIGN);
+    }
+
+    #[test]
+    fn test_compare() {
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert!(eq::<F32>(0, F32::SIGN, env));
+        assert!(!lt::<F32>(F32::SIGN, 0, env));
+        assert!(le::<F32>(F32::SIGN, 0, env));
+        assert!(lt::<F32>(0xbf800000, 0x3f800000, env));
+        assert!(lt::<F32>(0xc0000000, 0xbf800000, env));
+        assert_eq!(env.flags, 0);
+        assert!(!eq::<F32>(F32::NAN, F32::NAN, env));
+        assert_eq!(env.flags, 0);
+        assert!(!le::<F32>(F32::NAN, 0, env));
+        assert_eq!(env.flags, NV);
+    }
+
+    #[test]
+    fn test_min_max() {
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(min::<F32>(0, F32::SIGN, env), F32::SIGN);
+        assert_eq!(max::<F32>(F32::SIGN, 0, env), 0);
+        assert_eq!(min::<F32>(F32::NAN, 0x3f800000, env), 0x3f800000);
+        assert_eq!(max::<F32>(0x3f800000, F32::NAN, env), 0x3f800000);
+        assert_eq!(min::<F32>(0x7fc00001, 0x7fc00002, env), F32::NAN);
+        assert_eq!(env.flags, 0);
+        assert_eq!(max::<F32>(0x7f800001, 0x3f800000, env), 0x3f800000);
+        assert_eq!(env.flags, NV);
+    }
+
+    #[test]
+    fn test_to_int() {
+        let cases: [(u64, bool, RoundingMode, u64, u8); 10] = [
+            (2.5f32.to_bits() as u64, true, RoundingMode::Rne, 2, NX),
+            (2.5f32.to_bits() as u64, true, RoundingMode::Rmm, 3, NX),
+            (
+                (-2.5f32).to_bits() as u64,
+                true,
+                RoundingMode::Rdn,
+                (-3i32) as u32 as u64,
+                NX,
+            ),
+            (
+                (-2.5f32).to_bits() as u64,
+                true,
+                RoundingMode::Rup,
+                (-2i32) as u32 as u64,
+                NX,
+            ),
+            (
+                3e9f32.to_bits() as u64,
+                true,
+                RoundingMode::Rne,
+                0x7fffffff,
+                NV,
+            ),
+            (
+                (-3e9f32).to_bits() as u64,
+                true,
+                RoundingMode::Rne,
+                0x80000000,
+                NV,
+            ),
+            (
+                F32::NAN | F32::SIGN,
+                true,
+                RoundingMode::Rne,
+                0x7fffffff,
+                NV,
+            ),
+            ((-0.25f32).to_bits() as u64, false, RoundingMode::Rtz, 0, NX),
+            ((-1.0f32).to_bits() as u64, false, RoundingMode::Rtz, 0, NV),
+            (
+                3e9f32.to_bits() as u64,
+                false,
+                RoundingMode::Rne,
+                3_000_000_000,
+                0,
+            ),
+        ];
+        for (a, signed, rm, expected, flags) in cases {
+            let env = &mut FloatEnv::new(rm);
+            assert_eq!(to_int::<F32>(a, signed, 32, env), expected, "{a:#x} {rm:?}");
+            assert_eq!(env.flags, flags, "{a:#x} {rm:?}");
+        }
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(
+            to_int::<F64>((-9223372036854775808.0f64).to_bits(), true, 64, env),
+            1 << 63
+        );
+        assert_eq!(env.flags, 0);
+        assert_eq!(
+            to_int::<F64>(18446744073709551616.0f64.to_bits(), false, 64, env),
+            u64::MAX
+        );
+        assert_eq!(env.flags, NV);
+    }
+
+    #[test]
+    fn test_from_int() {
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(from_int::<F32>(0xffffffff, true, 32, env), 0xbf800000);
+        assert_eq!(env.flags, 0);
+        assert_eq!(from_int::<F32>(0xffffffff, false, 32, env), 0x4f800000);
+        assert_eq!(env.flags, NX);
+        let env = &mut FloatEnv::new(RoundingMode::Rtz);
+        assert_eq!(from_int::<F32>(0xffffffff, false, 32, env), 0x4f7fffff);
+        assert_eq!(
+            from_int::<F64>(1 << 63, true, 64, env),
+            (-9223372036854775808.0f64).to_bits()
+        );
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify::<F32>(0x7fc00001), 1 << 9);
+        assert_eq!(classify::<F32>(0x7f800001), 1 << 8);
+        assert_eq!(classify::<F32>(0x80000001), 1 << 2);
+        assert_eq!(classify::<F64>(F64::INF | F64::SIGN), 1 << 0);
+    }
+
+    #[test]
+    fn test_estimates() {
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(rec7::<F32>(0x3f800000, env), 0x3f7f0000);
+        assert_eq!(rec7::<F32>(0xc0000000, env), 0xbeff0000);
+        // the largest finite values have subnormal reciprocals
+        assert_eq!(rec7::<F32>(0x7f7fffff, env), 0x00200000);
+        assert_eq!(rsqrt7::<F32>(0x3f800000, env), 0x3f7f0000);
+        assert_eq!(rsqrt7::<F32>(0x40000000, env), 0x3f340000);
+        assert_eq!(rsqrt7::<F32>(0x00000001, env), 0x64b40000);
+        assert_eq!(rsqrt7::<F64>(0x4010000000000000, env), 0x3fdfe00000000000);
+        assert_eq!(rsqrt7::<F32>(F32::INF, env), 0);
+        assert_eq!(env.flags, 0);
+        assert_eq!(rsqrt7::<F32>(0x80000000, env), F32::INF | F32::SIGN);
+        assert_eq!(env.flags, DZ);
+        let env = &mut FloatEnv::new(RoundingMode::Rne);
+        assert_eq!(rsqrt7::<F32>(0xbf800000, env), F32::NAN);
+        assert_eq!(env.flags, NV);
+        let env = &mut FloatEnv::new(RoundingMode::Rtz);
+        assert_eq!(rec7::<F32>(0x00000001, env), 0x7f7fffff);
+        assert_eq!(env.flags, OF | NX);
+    }
+}
@@ -0,0 +1,174 @@
+/// Synchronous exceptions, mirroring the RISC-V `mcause` exception codes.
+///
+/// Each variant carries the value the hart would write into `mtval`: the
+/// faulting address for access, misaligned and page faults, the instruction bits for
+/// illegal instructions and the PC of the `ebreak` for breakpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Exception {
+    InstructionAddressMisaligned(u64),
+    InstructionAccessFault(u64),
+    IllegalInstruction(u32),
+    Breakpoint(u64),
+    LoadAddressMisaligned(u64),
+    LoadAccessFault(u64),
+    StoreAddressMisaligned(u64),
+    StoreAccessFault(u64),
+    EnvironmentCallFromUMode,
+    EnvironmentCallFromSMode,
+    EnvironmentCallFromMMode,
+    InstructionPageFault(u64),
+    LoadPageFault(u64),
+    StorePageFault(u64),
+}
+
+impl Exception {
+    /// The exception code, as written in `mcause`.
+    #[inline(always)]
+    pub const fn code(&self) -> u64 {
+        match self {
+            Self::InstructionAddressMisaligned(_) => 0,
+            Self::InstructionAccessFault(_) => 1,
+            Self::IllegalInstruction(_) => 2,
+            Self::Breakpoint(_) => 3,
+            Self::LoadAddressMisaligned(_) => 4,
+            Self::LoadAccessFault(_) => 5,
+            Self::StoreAddressMisaligned(_) => 6,
+            Self::StoreAccessFault(_) => 7,
+            Self::EnvironmentCallFromUMode => 8,
+            Self::EnvironmentCallFromSMode => 9,
+            Self::EnvironmentCallFromMMode => 11,
+            Self::InstructionPageFault(_) => 12,
+            Self::LoadPageFault(_) => 13,
+            Self::StorePageFault(_) => 15,
+        }
+    }
+
+    /// The trap value, as written in `mtval`.
+    #[inline(always)]
+    pub const fn tval(&self) -> u64 {
+        match *self {
+            Self::InstructionAddressMisaligned(addr)
+            | Self::InstructionAccessFault(addr)
+            | Self::Breakpoint(addr)
+            | Self::LoadAddressMisaligned(addr)
+            | Self::LoadAccessFault(addr)
+            | Self::StoreAddressMisaligned(addr)
+            | Self::StoreAccessFault(addr)
+            | Self::InstructionPageFault(addr)
+            | Self::LoadPageFault(addr)
+            | Self::StorePageFault(addr) => addr,
+            Self::IllegalInstruction(bits) => bits as u64,
+            Self::EnvironmentCallFromUMode
+            | Self::EnvironmentCallFromSMode
+            | Self::EnvironmentCallFromMMode => 0,
+        }
+    }
+}
+
+impl core::fmt::Display for Exception {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            Self::InstructionAddressMisaligned(_) => "instruction address misaligned",
+            Self::InstructionAccessFault(_) => "instruction access fault",
+            Self::IllegalInstruction(_) => "illegal instruction",
+            Self::Breakpoint(_) => "breakpoint",
+            Self::LoadAddressMisaligned(_) => "load address misaligned",
+            Self::LoadAccessFault(_) => "load access fault",
+            Self::StoreAddressMisaligned(_) => "store/AMO address misaligned",
+            Self::StoreAccessFault(_) => "store/AMO access fault",
+            Self::EnvironmentCallFromUMode => "environment call from U-mode",
+            Self::EnvironmentCallFromSMode => "environment call from S-mode",
+            Self::EnvironmentCallFromMMode => "environment call from M-mode",
+            Self::InstructionPageFault(_) => "instruction page fault",
+            Self::LoadPageFault(_) => "load page fault",
+            Self::StorePageFault(_) => "store/AMO page fault",
+        };
+        write!(
+            f,
+            "{} (cause {}, tval {:#x})",
+            name,
+            self.code(),
+            self.tval()
+        )
+    }
+}
+
+impl std::error::Error for Exception {}
+
+/// Asynchronous interrupts, mirroring the RISC-V `mcause` interrupt codes.
+///
+/// Their bits in `mip`, `mie` and `mideleg` are at the same positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Interrupt {
+    SupervisorSoftware,
+    MachineSoftware,
+    SupervisorTimer,
+    MachineTimer,
+    SupervisorExternal,
+    MachineExternal,
+}
+
+impl Interrupt {
+    /// The interrupts from highest to lowest priority, as taken when several
+    /// are pending for the same privilege mode.
+    pub const PRIORITY: [Self; 6] = [
+        Self::MachineExternal,
+        Self::MachineSoftware,
+        Self::MachineTimer,
+        Self::SupervisorExternal,
+        Self::SupervisorSoftware,
+        Self::SupervisorTimer,
+    ];
+
+    /// The interrupt code, as written in `mcause` below its interrupt bit.
+    #[inline(always)]
+    pub const fn code(&self) -> u64 {
+        match self {
+            Self::SupervisorSoftware => 1,
+            Self::MachineSoftware => 3,
+            Self::SupervisorTimer => 5,
+            Self::MachineTimer => 7,
+            Self::SupervisorExternal => 9,
+            Self::MachineExternal => 11,
+        }
+    }
+}
+
+impl core::fmt::Display for Interrupt {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            Self::SupervisorSoftware => "supervisor software interrupt",
+            Self::MachineSoftware => "machine software interrupt",
+            Self::SupervisorTimer => "supervisor timer interrupt",
+            Self::MachineTimer => "machine timer interrupt",
+            Self::SupervisorExternal => "supervisor external interrupt",
+            Self::MachineExternal => "machine external interrupt",
+        };
+        write!(f, "{} (cause {})", name, self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cause_codes() {
+        assert_eq!(Exception::IllegalInstruction(0).code(), 2);
+        assert_eq!(Exception::LoadAccessFault(0).code(), 5);
+        assert_eq!(Exception::StoreAccessFault(0).code(), 7);
+        assert_eq!(Exception::EnvironmentCallFromMMode.code(), 11);
+        assert_eq!(Exception::StorePageFault(0).code(), 15);
+        assert_eq!(Interrupt::SupervisorSoftware.code(), 1);
+        assert_eq!(Interrupt::MachineTimer.code(), 7);
+        assert_eq!(Interrupt::MachineExternal.code(), 11);
+    }
+
+    #[test]
+    fn test_tval() {
+        assert_eq!(Exception::IllegalInstruction(0xdeadbeef).tval(), 0xdeadbeef);
+        assert_eq!(Exception::LoadAccessFault(0x1000).tval(), 0x1000);
+        assert_eq!(Exception::Breakpoint(0x80).tval(), 0x80);
+        assert_eq!(Exception::EnvironmentCallFromMMode.tval(), 0);
+    }
+}
@@ -0,0 +1,1633 @@
+//! The V extension. `vtype` and `vl` say how many elements of which width the
+//! instructions process, the element operations themselves live in
+//! `ops::vector`.
+//!
+//! Tail and masked-off elements are always left undisturbed, which both
+//! policies allow. A trap on an element of a load or store leaves `vstart` at
+//! that element and the elements before it written, every instruction that
+//! completes resets `vstart` to zero.
+
+use crate::decode::{VMem, I, R, U5, V};
+use crate::instruction_ids::{OPFVF, OPFVV, OPIVI, OPIVV, OPIVX, OPMVV, OPMVX};
+use crate::instructions::{break_reservation, ILLEGAL_INSTRUCTION};
+use crate::isa::Extensions;
+use crate::mem::{self, Reservation};
+use crate::mmu::Bus;
+use crate::num::As;
+use crate::ops::VectorEnv;
+use crate::registers::{
+    CsrRegisters, RegFile, VRegisters, Zero, ZeroOrRegister, FRM, VL, VSTART, VTYPE, VXRM, VXSAT,
+};
+use crate::softfloat::RoundingMode;
+use crate::trap::Exception;
+
+/// How the element widths of an arithmetic instruction relate to SEW.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shape {
+    /// SEW wide operands and destination, immediates are sign extended.
+    Single,
+    /// Like `Single` with a zero extended immediate, for shifts.
+    Shift,
+    /// SEW wide sources and 2*SEW wide destination.
+    Widen,
+    /// 2*SEW wide `vs2` and destination, SEW wide other operand.
+    WidenW,
+    /// 2*SEW wide `vs2`, SEW wide other operand and destination. Narrowing
+    /// instructions are all shifts and zero extend their immediate.
+    Narrow,
+}
+
+/// Which side of a float unary op holds integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Conversion {
+    /// Floats in and out.
+    None,
+    /// Integer sources, float results.
+    FromInt,
+    /// Float sources, integer results.
+    ToInt,
+}
+
+/// How a load or store walks memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Addressing {
+    /// Consecutive elements, or consecutive segments of `nf + 1` fields.
+    Unit,
+    /// Unit-stride, a fault past the first element trims `vl` instead.
+    FaultOnlyFirst,
+    /// The bytes of a mask, ceil(vl / 8) of them.
+    Mask,
+    /// Elements `rs2` bytes apart.
+    Strided,
+    /// Byte offsets from the index register group `vs2`.
+    Indexed,
+    /// `nf + 1` whole registers, whatever `vtype` and `vl` say.
+    Whole,
+}
+
+/// The mask `vmsbf`, `vmsif` and `vmsof` set around the first set bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SetFirst {
+    Before,
+    Including,
+    Only,
+}
+
+pub trait Vector: Sized {
+    fn vsetvli(instruction: I, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    fn vsetivli(instruction: I, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    fn vsetvl(instruction: R, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    fn vload(
+        instruction: VMem,
+        regfile: &mut RegFile<Self>,
+        memory: &mut Bus,
+        addressing: Addressing,
+    ) -> Result<(), Exception>;
+
+    fn vstore(
+        instruction: VMem,
+        regfile: &mut RegFile<Self>,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        addressing: Addressing,
+    ) -> Result<(), Exception>;
+
+    /// `vd[i] = op(vs2[i], vs1[i] or the scalar)`
+    fn varith(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+        shape: Shape,
+    ) -> Result<(), Exception>;
+
+    fn vfarith(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+        shape: Shape,
+    ) -> Result<(), Exception>;
+
+    /// `vd[i] = op(vd[i], vs1[i] or the scalar, vs2[i])`, `Single` or `Widen`
+    fn vmuladd(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, u64, &mut VectorEnv) -> u64,
+        shape: Shape,
+    ) -> Result<(), Exception>;
+
+    fn vfmuladd(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, u64, &mut VectorEnv) -> u64,
+        shape: Shape,
+    ) -> Result<(), Exception>;
+
+    /// Mask bit `vd[i] = op(vs2[i], vs1[i] or the scalar)`
+    fn vcompare(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    fn vfcompare(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `vadc` and `vsbc`, `v0` holds the carries and every body element is
+    /// written.
+    fn vcarry(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, u64, &mut VectorEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `vmadc` and `vmsbc`, which take the carries from `v0` when masked.
+    fn vcarry_out(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, u64, &mut VectorEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    /// `vmerge`, or `vmv.v` when unmasked.
+    fn vmerge(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    /// `vfmerge.vfm`, or `vfmv.v.f` when unmasked.
+    fn vfmerge(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+    ) -> Result<(), Exception>;
+
+    /// `vzext` and `vsext` of SEW / `factor` wide sources.
+    fn vextend(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, &mut VectorEnv) -> u64,
+        factor: u32,
+    ) -> Result<(), Exception>;
+
+    /// Float unary ops and conversions, `Single`, `Widen` or `Narrow`.
+    fn vfunary(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, &mut VectorEnv) -> u64,
+        shape: Shape,
+        conversion: Conversion,
+    ) -> Result<(), Exception>;
+
+    /// `vd[0] = op(...op(vs1[0], vs2[0])..., vs2[vl - 1])` over the active
+    /// elements, with a 2*SEW wide accumulator when `widen`.
+    fn vreduce(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+        widen: bool,
+    ) -> Result<(), Exception>;
+
+    /// Float reductions, always in element order.
+    fn vfreduce(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+        widen: bool,
+    ) -> Result<(), Exception>;
+
+    /// Mask bit `vd[i] = op(vs2[i], vs1[i])`
+    fn vmask(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+    ) -> Result<(), Exception>;
+
+    fn vcpop(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    fn vfirst(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    fn vmset(instruction: V, regfile: &mut RegFile<Self>, set: SetFirst) -> Result<(), Exception>;
+
+    fn viota(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    fn vid(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    fn vmv_x_s(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    fn vmv_s_x(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    fn vfmv_f_s(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+    ) -> Result<(), Exception>;
+
+    fn vfmv_s_f(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+    ) -> Result<(), Exception>;
+
+    fn vslideup(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    fn vslidedown(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    /// `vslide1up` and `vfslide1up`, the scalar comes from `funct3`'s register
+    /// file.
+    fn vslide1up(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+    ) -> Result<(), Exception>;
+
+    fn vslide1down(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+    ) -> Result<(), Exception>;
+
+    /// `vrgather`, with 16-bit indices in `vs1` for `vrgatherei16`.
+    fn vrgather(instruction: V, regfile: &mut RegFile<Self>, ei16: bool) -> Result<(), Exception>;
+
+    fn vcompress(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+
+    /// `vmv<nr>r.v`, which copies whole registers whatever `vl` says.
+    fn vmvnr(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception>;
+}
+
+/// The configuration set by the last `vset{i}vl{i}`.
+#[derive(Debug, Clone, Copy)]
+struct VConfig {
+    sew: u32,
+    /// log2 of LMUL, -3 to 3.
+    lmul: i32,
+    vl: usize,
+    vstart: usize,
+    vlmax: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operand {
+    Vector(usize),
+    Scalar(u64),
+}
+
+impl Operand {
+    #[inline(always)]
+    fn get(self, vregs: &VRegisters, index: usize, eew: u32) -> u64 {
+        match self {
+            Operand::Vector(reg) => vregs.get(reg, index, eew),
+            Operand::Scalar(value) => value,
+        }
+    }
+}
+
+#[inline(always)]
+const fn xlen<T>() -> u32 {
+    core::mem::size_of::<T>() as u32 * 8
+}
+
+#[inline(always)]
+const fn ones(bits: u32) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+#[inline(always)]
+const fn log2(value: u32) -> i32 {
+    value.trailing_zeros() as i32
+}
+
+/// Number of registers of a group of EMUL `2^emul`.
+#[inline(always)]
+const fn group_len(emul: i32) -> usize {
+    1 << if emul > 0 { emul } else { 0 }
+}
+
+/// SEW and log2(LMUL) of a `vtype` value, `None` when it sets `vill`. SEW
+/// cannot exceed ELEN, nor ELEN * LMUL with a fractional LMUL.
+#[inline(always)]
+fn vtype_fields(vtype: u64, elen: u32) -> Option<(u32, i32)> {
+    // the reserved bits and vill
+    if vtype >> 8 != 0 {
+        return None;
+    }
+    let vsew = (vtype >> 3) & 0b111;
+    let vlmul = (vtype & 0b111) as i32;
+    if vsew > 3 || vlmul == 0b100 {
+        return None;
+    }
+    let sew = 8 << vsew;
+    let lmul = (vlmul << 29) >> 29;
+    if sew > elen || (lmul < 0 && sew > elen >> -lmul) {
+        return None;
+    }
+    Some((sew, lmul))
+}
+
+#[inline(always)]
+fn vlmax(vlenb: usize, sew: u32, lmul: i32) -> usize {
+    let bits = if lmul >= 0 {
+        (vlenb * 8) << lmul
+    } else {
+        (vlenb * 8) >> -lmul
+    };
+    bits / sew as usize
+}
+
+#[inline(always)]
+fn vconfig<T>(regfile: &RegFile<T>) -> Result<VConfig, Exception>
+where
+    T: Copy + As<u64>,
+{
+    let vtype: u64 = regfile.csrs.get(VTYPE).r#as();
+    let (sew, lmul) = vtype_fields(vtype, regfile.vregs.elen()).ok_or(ILLEGAL_INSTRUCTION)?;
+    let vl: u64 = regfile.csrs.get(VL).r#as();
+    let vstart: u64 = regfile.csrs.get(VSTART).r#as();
+    Ok(VConfig {
+        sew,
+        lmul,
+        vl: vl as usize,
+        vstart: vstart as usize,
+        vlmax: vlmax(regfile.vregs.vlenb(), sew, lmul),
+    })
+}
+
+/// Checks that `reg` starts a group of EMUL `2^emul` registers.
+#[inline(always)]
+fn group(reg: U5, emul: i32) -> Result<usize, Exception> {
+    let reg = reg.as_u8() as usize;
+    if !(-3..=3).contains(&emul) || !reg.is_multiple_of(group_len(emul)) {
+        return Err(ILLEGAL_INSTRUCTION);
+    }
+    Ok(reg)
+}
+
+/// A destination group may only overlap a source group of another EEW in its
+/// lowest registers when the source is wider, or in its highest registers
+/// when the source is narrower and EMUL is at least one.
+#[inline(always)]
+fn check_overlap(
+    (dst, dst_emul, dst_eew): (usize, i32, u32),
+    (src, src_emul, src_eew): (usize, i32, u32),
+) -> Result<(), Exception> {
+    let (dst_len, src_len) = (group_len(dst_emul), group_len(src_emul));
+    let overlaps = dst < src + src_len && src < dst + dst_len;
+    let allowed = !overlaps
+        || dst_eew == src_eew
+        || (dst_eew < src_eew && dst == src)
+        || (dst_eew > src_eew && src_emul >= 0 && src + src_len == dst + dst_len);
+    if allowed {
+        Ok(())
+    } else {
+        Err(ILLEGAL_INSTRUCTION)
+    }
+}
+
+/// Groups that may not overlap at all.
+#[inline(always)]
+fn check_disjoint(
+    (dst, dst_emul): (usize, i32),
+    (src, src_emul): (usize, i32),
+) -> Result<(), Exception> {
+    if dst < src + group_len(src_emul) && src < dst + group_len(dst_emul) {
+        return Err(ILLEGAL_INSTRUCTION);
+    }
+    Ok(())
+}
+
+/// A masked instruction cannot write a vector (rather than mask) result over
+/// `v0`.
+#[inline(always)]
+fn check_mask_overlap(vm: bool, vd: usize) -> Result<(), Exception> {
+    if !vm && vd == 0 {
+        return Err(ILLEGAL_INSTRUCTION);
+    }
+    Ok(())
+}
+
+/// Float instructions need the scalar extension providing the format.
+#[inline(always)]
+fn check_float(width: u32, extensions: &Extensions) -> Result<(), Exception> {
+    match width {
+        32 if extensions.f => Ok(()),
+        64 if extensions.d => Ok(()),
+        _ => Err(ILLEGAL_INSTRUCTION),
+    }
+}
+
+/// Widening and narrowing instructions need 2*SEW to fit in ELEN.
+#[inline(always)]
+fn check_wide(cfg: &VConfig, vregs: &VRegisters) -> Result<(), Exception> {
+    if 2 * cfg.sew > vregs.elen() {
+        return Err(ILLEGAL_INSTRUCTION);
+    }
+    Ok(())
+}
+
+#[inline(always)]
+fn active(vm: bool, vregs: &VRegisters, index: usize) -> bool {
+    vm || vregs.mask(0, index)
+}
+
+/// `x[reg]` sign extended from XLEN to 64 bits.
+#[inline(always)]
+fn xreg<T>(regfile: &RegFile<T>, reg: U5) -> u64
+where
+    T: Copy + Zero + As<u64>,
+{
+    let value: u64 = ZeroOrRegister::from_u5(reg).fetch(&regfile.xregs).r#as();
+    let shift = 64 - xlen::<T>();
+    (((value << shift) as i64) >> shift) as u64
+}
+
+/// `x[reg]` zero extended from XLEN to 64 bits.
+#[inline(always)]
+fn xreg_unsigned<T>(regfile: &RegFile<T>, reg: U5) -> u64
+where
+    T: Copy + Zero + As<u64>,
+{
+    ZeroOrRegister::from_u5(reg).fetch(&regfile.xregs).r#as()
+}
+
+#[inline(always)]
+fn set_xreg<T>(regfile: &mut RegFile<T>, reg: U5, value: u64)
+where
+    u64: As<T>,
+{
+    ZeroOrRegister::from_u5(reg).store(&mut regfile.xregs, value.r#as());
+}
+
+/// `f[reg]` as a `width` bit float, single precision must be NaN-boxed.
+#[inline(always)]
+fn freg<T>(regfile: &RegFile<T>, reg: U5, width: u32) -> u64 {
+    match width {
+        32 => regfile.fregs.get_s(reg.into()) as u64,
+        _ => regfile.fregs.get(reg.into()),
+    }
+}
+
+/// The `vs1` operand of `instruction`: a register group, or the `eew` bit
+/// scalar or immediate `funct3` picks.
+#[inline(always)]
+fn operand<T>(instruction: &V, regfile: &RegFile<T>, eew: u32, signed_imm: bool) -> Operand
+where
+    T: Copy + Zero + As<u64>,
+{
+    let value = match instruction.funct3 {
+        OPIVV | OPMVV | OPFVV => return Operand::Vector(instruction.vs1.as_u8() as usize),
+        OPIVX | OPMVX => xreg(regfile, instruction.vs1),
+        OPIVI if signed_imm => instruction.vs1.sign_extend() as i64 as u64,
+        OPIVI => instruction.vs1.as_u8() as u64,
+        _ => freg(regfile, instruction.vs1, eew),
+    };
+    Operand::Scalar(value & ones(eew))
+}
+
+/// Checks a vector `vs1` operand group against the destination.
+#[inline(always)]
+fn check_operand(
+    op1: Operand,
+    instruction: &V,
+    (emul, eew): (i32, u32),
+    dst: (usize, i32, u32),
+) -> Result<(), Exception> {
+    if let Operand::Vector(_) = op1 {
+        let vs1 = group(instruction.vs1, emul)?;
+        check_overlap(dst, (vs1, emul, eew))?;
+    }
+    Ok(())
+}
+
+#[inline(always)]
+fn env<T>(csrs: &CsrRegisters<T>, sew: u32, float: bool) -> Result<VectorEnv, Exception>
+where
+    T: Copy + As<u8>,
+{
+    let vxrm: u8 = csrs.get(VXRM).r#as();
+    let rm = if float {
+        let frm: u8 = csrs.get(FRM).r#as();
+        RoundingMode::from_u8(frm).ok_or(ILLEGAL_INSTRUCTION)?
+    } else {
+        RoundingMode::Rne
+    };
+    Ok(VectorEnv::new(sew, vxrm, rm))
+}
+
+/// Completes an instruction: resets `vstart` and accrues the flags.
+#[inline(always)]
+fn finish<T>(csrs: &mut CsrRegisters<T>, env: &VectorEnv)
+where
+    T: Copy + As<u8>,
+    u8: As<T>,
+{
+    csrs.set(VSTART, 0u8.r#as());
+    if env.vxsat {
+        csrs.set(VXSAT, 1u8.r#as());
+    }
+    csrs.accrue_fflags(env.float.flags);
+}
+
+#[inline(always)]
+fn load_element(memory: &mut Bus, addr: usize, eew: u32) -> Result<u64, Exception> {
+    Ok(match eew {
+        8 => memory.read::<u8>(addr)? as u64,
+        16 => memory.read::<mem::U16>(addr)?.as_u16() as u64,
+        32 => memory.read::<mem::U32>(addr)?.as_u32() as u64,
+        _ => memory.read::<mem::U64>(addr)?.as_u64(),
+    })
+}
+
+#[inline(always)]
+fn store_element(value: u64, memory: &mut Bus, addr: usize, eew: u32) -> Result<(), Exception> {
+    match eew {
+        8 => memory.write(&(value as u8), addr),
+        16 => memory.write(&mem::U16::new(value as u16), addr),
+        32 => memory.write(&mem::U32::new(value as u32), addr),
+        _ => memory.write(&mem::U64::new(value), addr),
+    }
+}
+
+/// The registers, element width and element count a load or store accesses,
+/// and how to find the address of each field.
+struct Access {
+    vd: usize,
+    /// Registers between the fields of a segment.
+    field_len: usize,
+    fields: usize,
+    eew: u32,
+    evl: usize,
+    vstart: usize,
+    /// The index register group and its EEW, for indexed accesses.
+    index: Option<(usize, u32)>,
+}
+
+fn access<T>(
+    instruction: &VMem,
+    regfile: &RegFile<T>,
+    addressing: Addressing,
+) -> Result<Access, Exception>
+where
+    T: Copy + As<u64>,
+{
+    // the decoder only lets the vector widths through
+    let width = instruction.eew().ok_or(ILLEGAL_INSTRUCTION)?;
+    let fields = instruction.nf.as_u8() as usize + 1;
+    if addressing == Addressing::Whole {
+        let vd = group(instruction.vd, log2(fields as u32))?;
+        let vstart: u64 = regfile.csrs.get(VSTART).r#as();
+        return Ok(Access {
+            vd,
+            field_len: 0,
+            fields: 1,
+            eew: width,
+            evl: fields * regfile.vregs.vlenb() * 8 / width as usize,
+            vstart: vstart as usize,
+            index: None,
+        });
+    }
+    let cfg = vconfig(regfile)?;
+    if addressing == Addressing::Mask {
+        return Ok(Access {
+            vd: instruction.vd.as_u8() as usize,
+            field_len: 0,
+            fields: 1,
+            eew: 8,
+            evl: cfg.vl.div_ceil(8),
+            vstart: cfg.vstart,
+            index: None,
+        });
+    }
+    let (eew, emul, index) = if addressing == Addressing::Indexed {
+        let index_emul = log2(width) - log2(cfg.sew) + cfg.lmul;
+        let vs2 = group(instruction.rs2, index_emul)?;
+        (cfg.sew, cfg.lmul, Some((vs2, width, index_emul)))
+    } else {
+        (width, log2(width) - log2(cfg.sew) + cfg.lmul, None)
+    };
+    let vd = group(instruction.vd, emul)?;
+    let field_len = group_len(emul);
+    if fields * field_len > 8 || vd + fields * field_len > 32 {
+        return Err(ILLEGAL_INSTRUCTION);
+    }
+    check_mask_overlap(instruction.vm, vd)?;
+    if let Some((vs2, index_eew, index_emul)) = index {
+        for field in 0..fields {
+            let dst = (vd + field * field_len, emul, eew);
+            if fields > 1 {
+                check_disjoint((dst.0, emul), (vs2, index_emul))?;
+            } else {
+                check_overlap(dst, (vs2, index_emul, index_eew))?;
+            }
+        }
+    }
+    Ok(Access {
+        vd,
+        field_len,
+        fields,
+        eew,
+        evl: cfg.vl,
+        vstart: cfg.vstart,
+        index: index.map(|(vs2, eew, _)| (vs2, eew)),
+    })
+}
+
+impl Access {
+    /// Address of field `field` of element `index`.
+    #[inline(always)]
+    fn addr<T>(
+        &self,
+        regfile: &RegFile<T>,
+        instruction: &VMem,
+        addressing: Addressing,
+        index: usize,
+        field: usize,
+    ) -> usize
+    where
+        T: Copy + Zero + As<u64>,
+    {
+        let base = xreg_unsigned(regfile, instruction.rs1);
+        let size = self.eew as u64 / 8;
+        let offset = match addressing {
+            Addressing::Strided => {
+                let stride = xreg(regfile, instruction.rs2);
+                (index as u64).wrapping_mul(stride)
+            }
+            Addressing::Indexed => {
+                let (vs2, eew) = self.index.expect("indexed access without indices");
+                regfile.vregs.get(vs2, index, eew)
+            }
+            _ => (index * self.fields) as u64 * size,
+        };
+        let addr = base.wrapping_add(offset).wrapping_add(field as u64 * size);
+        (addr & ones(xlen::<T>())) as usize
+    }
+}
+
+/// Sets `vl` and `vtype` for an application vector length of `avl`, `None`
+/// keeping the current `vl`.
+fn set_vl<T>(regfile: &mut RegFile<T>, rd: U5, avl: Option<u64>, vtype: u64)
+where
+    T: Copy + As<u64>,
+    u8: As<T>,
+    u64: As<T>,
+{
+    let (vtype, vl) = match vtype_fields(vtype, regfile.vregs.elen()) {
+        Some((sew, lmul)) => {
+            let vlmax = vlmax(regfile.vregs.vlenb(), sew, lmul) as u64;
+            let vl = match avl {
+                Some(avl) => avl.min(vlmax),
+                None => {
+                    let vl: u64 = regfile.csrs.get(VL).r#as();
+                    vl.min(vlmax)
+                }
+            };
+            (vtype, vl)
+        }
+        None => (1 << (xlen::<T>() - 1), 0),
+    };
+    regfile.csrs.set(VTYPE, vtype.r#as());
+    regfile.csrs.set(VL, vl.r#as());
+    regfile.csrs.set(VSTART, 0u8.r#as());
+    ZeroOrRegister::from_u5(rd).store(&mut regfile.xregs, vl.r#as());
+}
+
+/// The AVL of `vsetvli` and `vsetvl`: `rs1`, or VLMAX when only `rd` is x0.
+#[inline(always)]
+fn register_avl<T>(regfile: &RegFile<T>, rd: U5, rs1: U5) -> Option<u64>
+where
+    T: Copy + Zero + As<u64>,
+{
+    match (rd.as_u8(), rs1.as_u8()) {
+        (0, 0) => None,
+        (_, 0) => Some(u64::MAX),
+        _ => Some(xreg_unsigned(regfile, rs1)),
+    }
+}
+
+/// Shared by the arithmetic handlers: checks the groups of `shape` and runs
+/// `op` on the active body elements.
+#[inline(always)]
+fn arith<T>(
+    instruction: V,
+    regfile: &mut RegFile<T>,
+    cfg: VConfig,
+    mut env: VectorEnv,
+    op: fn(u64, u64, &mut VectorEnv) -> u64,
+    shape: Shape,
+) -> Result<(), Exception>
+where
+    T: Copy + Zero + As<u8> + As<u64>,
+    u8: As<T>,
+{
+    let (sew, lmul) = (cfg.sew, cfg.lmul);
+    let ((dst_emul, dst_eew), (vs2_emul, vs2_eew)) = match shape {
+        Shape::Single | Shape::Shift => ((lmul, sew), (lmul, sew)),
+        Shape::Widen => ((lmul + 1, 2 * sew), (lmul, sew)),
+        Shape::WidenW => ((lmul + 1, 2 * sew), (lmul + 1, 2 * sew)),
+        Shape::Narrow => ((lmul, sew), (lmul + 1, 2 * sew)),
+    };
+    if shape != Shape::Single && shape != Shape::Shift {
+        check_wide(&cfg, &regfile.vregs)?;
+    }
+    let vd = group(instruction.vd, dst_emul)?;
+    let vs2 = group(instruction.vs2, vs2_emul)?;
+    let dst = (vd, dst_emul, dst_eew);
+    check_overlap(dst, (vs2, vs2_emul, vs2_eew))?;
+    check_mask_overlap(instruction.vm, vd)?;
+    let op1 = operand(&instruction, regfile, sew, shape == Shape::Single);
+    check_operand(op1, &instruction, (lmul, sew), dst)?;
+    let vregs = &mut regfile.vregs;
+    for i in cfg.vstart..cfg.vl {
+        if active(instruction.vm, vregs, i) {
+            let a = vregs.get(vs2, i, vs2_eew);
+            let b = op1.get(vregs, i, sew);
+            vregs.set(vd, i, dst_eew, op(a, b, &mut env));
+        }
+    }
+    finish(&mut regfile.csrs, &env);
+    Ok(())
+}
+
+#[inline(always)]
+fn muladd<T>(
+    instruction: V,
+    regfile: &mut RegFile<T>,
+    cfg: VConfig,
+    mut env: VectorEnv,
+    op: fn(u64, u64, u64, &mut VectorEnv) -> u64,
+    shape: Shape,
+) -> Result<(), Exception>
+where
+    T: Copy + Zero + As<u8> + As<u64>,
+    u8: As<T>,
+{
+    let (sew, lmul) = (cfg.sew, cfg.lmul);
+    let (dst_emul, dst_eew) = match shape {
+        Shape::Widen => {
+            check_wide(&cfg, &regfile.vregs)?;
+            (lmul + 1, 2 * sew)
+        }
+        _ => (lmul, sew),
+    };
+    let vd = group(instruction.vd, dst_emul)?;
+    let vs2 = group(instruction.vs2, lmul)?;
+    let dst = (vd, dst_emul, dst_eew);
+    check_overlap(dst, (vs2, lmul, sew))?;
+    check_mask_overlap(instruction.vm, vd)?;
+    let op1 = operand(&instruction, regfile, sew, true);
+    check_operand(op1, &instruction, (lmul, sew), dst)?;
+    let vregs = &mut regfile.vregs;
+    for i in cfg.vstart..cfg.vl {
+        if active(instruction.vm, vregs, i) {
+            let acc = vregs.get(vd, i, dst_eew);
+            let a = op1.get(vregs, i, sew);
+            let b = vregs.get(vs2, i, sew);
+            vregs.set(vd, i, dst_eew, op(acc, a, b, &mut env));
+        }
+    }
+    finish(&mut regfile.csrs, &env);
+    Ok(())
+}
+
+#[inline(always)]
+fn compare<T>(
+    instruction: V,
+    regfile: &mut RegFile<T>,
+    cfg: VConfig,
+    mut env: VectorEnv,
+    op: fn(u64, u64, &mut VectorEnv) -> u64,
+) -> Result<(), Exception>
+where
+    T: Copy + Zero + As<u8> + As<u64>,
+    u8: As<T>,
+{
+    let (sew, lmul) = (cfg.sew, cfg.lmul);
+    let vd = instruction.vd.as_u8() as usize;
+    let vs2 = group(instruction.vs2, lmul)?;
+    let dst = (vd, 0, 1);
+    check_overlap(dst, (vs2, lmul, sew))?;
+    let op1 = operand(&instruction, regfile, sew, true);
+    check_operand(op1, &instruction, (lmul, sew), dst)?;
+    let vregs = &mut regfile.vregs;
+    for i in cfg.vstart..cfg.vl {
+        if active(instruction.vm, vregs, i) {
+            let a = vregs.get(vs2, i, sew);
+            let b = op1.get(vregs, i, sew);
+            vregs.set_mask(vd, i, op(a, b, &mut env) != 0);
+        }
+    }
+    finish(&mut regfile.csrs, &env);
+    Ok(())
+}
+
+#[inline(always)]
+fn unary<T>(
+    instruction: V,
+    regfile: &mut RegFile<T>,
+    cfg: VConfig,
+    mut env: VectorEnv,
+    op: fn(u64, &mut VectorEnv) -> u64,
+    (dst_emul, dst_eew): (i32, u32),
+    (src_emul, src_eew): (i32, u32),
+) -> Result<(), Exception>
+where
+    T: Copy + As<u8>,
+    u8: As<T>,
+{
+    let vd = group(instruction.vd, dst_emul)?;
+    let vs2 = group(instruction.vs2, src_emul)?;
+    check_overlap((vd, dst_emul, dst_eew), (vs2, src_emul, src_eew))?;
+    check_mask_overlap(instruction.vm, vd)?;
+    let vregs = &mut regfile.vregs;
+    for i in cfg.vstart..cfg.vl {
+        if active(instruction.vm, vregs, i) {
+            let value = op(vregs.get(vs2, i, src_eew), &mut env);
+            vregs.set(vd, i, dst_eew, value);
+        }
+    }
+    finish(&mut regfile.csrs, &env);
+    Ok(())
+}
+
+#[inline(always)]
+fn reduce<T>(
+    instruction: V,
+    regfile: &mut RegFile<T>,
+    cfg: VConfig,
+    mut env: VectorEnv,
+    op: fn(u64, u64, &mut VectorEnv) -> u64,
+    widen: bool,
+) -> Result<(), Exception>
+where
+    T: Copy + As<u8>,
+    u8: As<T>,
+{
+    if cfg.vstart != 0 {
+        return Err(ILLEGAL_INSTRUCTION);
+    }
+    let acc_eew = if widen {
+        check_wide(&cfg, &regfile.vregs)?;
+        2 * cfg.sew
+    } else {
+        cfg.sew
+    };
+    let vs2 = group(instruction.vs2, cfg.lmul)?;
+    let (vd, vs1) = (
+        instruction.vd.as_u8() as usize,
+        instruction.vs1.as_u8() as usize,
+    );
+    let vregs = &mut regfile.vregs;
+    if cfg.vl > 0 {
+        let mut acc = vregs.get(vs1, 0, acc_eew);
+        for i in 0..cfg.vl {
+            if active(instruction.vm, vregs, i) {
+                acc = op(acc, vregs.get(vs2, i, cfg.sew), &mut env) & ones(acc_eew);
+            }
+        }
+        vregs.set(vd, 0, acc_eew, acc);
+    }
+    finish(&mut regfile.csrs, &env);
+    Ok(())
+}
+
+/// `vd[0] = value` unless `vstart >= vl`.
+#[inline(always)]
+fn move_to_element<T>(regfile: &mut RegFile<T>, vd: U5, cfg: VConfig, value: u64)
+where
+    T: Copy + As<u8>,
+    u8: As<T>,
+{
+    if cfg.vstart < cfg.vl {
+        regfile.vregs.set(vd.as_u8() as usize, 0, cfg.sew, value);
+    }
+    regfile.csrs.set(VSTART, 0u8.r#as());
+}
+
+/// The scalar of `vslide1up` and `vslide1down`, from `x` or `f` registers.
+#[inline(always)]
+fn slide1_scalar<T>(
+    instruction: &V,
+    regfile: &RegFile<T>,
+    extensions: &Extensions,
+    sew: u32,
+) -> Result<u64, Exception>
+where
+    T: Copy + Zero + As<u8> + As<u64>,
+{
+    if instruction.funct3 == OPFVF {
+        check_float(sew, extensions)?;
+        let frm: u8 = regfile.csrs.get(FRM).r#as();
+        if RoundingMode::from_u8(frm).is_none() {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+    }
+    match operand(instruction, regfile, sew, true) {
+        Operand::Scalar(value) => Ok(value),
+        Operand::Vector(_) => Err(ILLEGAL_INSTRUCTION),
+    }
+}
+
+impl<T> Vector for T
+where
+    T: Copy + Zero + As<u8> + As<u64>,
+    u8: As<T>,
+    u64: As<T>,
+{
+    #[inline(always)]
+    fn vsetvli(instruction: I, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let avl = register_avl(regfile, instruction.rd, instruction.rs1);
+        let vtype = instruction.imm.as_u64() & 0x7ff;
+        set_vl(regfile, instruction.rd, avl, vtype);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn vsetivli(instruction: I, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let avl = instruction.rs1.as_u8() as u64;
+        let vtype = instruction.imm.as_u64() & 0x3ff;
+        set_vl(regfile, instruction.rd, Some(avl), vtype);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn vsetvl(instruction: R, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let avl = register_avl(regfile, instruction.rd, instruction.rs1);
+        let vtype = xreg_unsigned(regfile, instruction.rs2);
+        set_vl(regfile, instruction.rd, avl, vtype);
+        Ok(())
+    }
+
+    fn vload(
+        instruction: VMem,
+        regfile: &mut RegFile<Self>,
+        memory: &mut Bus,
+        addressing: Addressing,
+    ) -> Result<(), Exception> {
+        let access = access(&instruction, regfile, addressing)?;
+        let vm = instruction.vm || matches!(addressing, Addressing::Whole | Addressing::Mask);
+        for i in access.vstart..access.evl {
+            if !active(vm, &regfile.vregs, i) {
+                continue;
+            }
+            for field in 0..access.fields {
+                let addr = access.addr(regfile, &instruction, addressing, i, field);
+                match load_element(memory, addr, access.eew) {
+                    Ok(value) => {
+                        let reg = access.vd + field * access.field_len;
+                        regfile.vregs.set(reg, i, access.eew, value);
+                    }
+                    Err(_) if addressing == Addressing::FaultOnlyFirst && i > 0 => {
+                        regfile.csrs.set(VL, (i as u64).r#as());
+                        regfile.csrs.set(VSTART, 0u8.r#as());
+                        return Ok(());
+                    }
+                    Err(err) => {
+                        regfile.csrs.set(VSTART, (i as u64).r#as());
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn vstore(
+        instruction: VMem,
+        regfile: &mut RegFile<Self>,
+        memory: &mut Bus,
+        reservation: &mut Option<Reservation>,
+        addressing: Addressing,
+    ) -> Result<(), Exception> {
+        let access = access(&instruction, regfile, addressing)?;
+        let vm = instruction.vm || matches!(addressing, Addressing::Whole | Addressing::Mask);
+        for i in access.vstart..access.evl {
+            if !active(vm, &regfile.vregs, i) {
+                continue;
+            }
+            for field in 0..access.fields {
+                let addr = access.addr(regfile, &instruction, addressing, i, field);
+                let reg = access.vd + field * access.field_len;
+                let value = regfile.vregs.get(reg, i, access.eew);
+                if let Err(err) = store_element(value, memory, addr, access.eew) {
+                    regfile.csrs.set(VSTART, (i as u64).r#as());
+                    return Err(err);
+                }
+                break_reservation(reservation, addr, access.eew as usize / 8);
+            }
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn varith(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+        shape: Shape,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let env = env(&regfile.csrs, cfg.sew, false)?;
+        arith(instruction, regfile, cfg, env, op, shape)
+    }
+
+    #[inline(always)]
+    fn vfarith(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+        shape: Shape,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        check_float(cfg.sew, extensions)?;
+        if shape != Shape::Single {
+            check_float(2 * cfg.sew, extensions)?;
+        }
+        let env = env(&regfile.csrs, cfg.sew, true)?;
+        arith(instruction, regfile, cfg, env, op, shape)
+    }
+
+    #[inline(always)]
+    fn vmuladd(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, u64, &mut VectorEnv) -> u64,
+        shape: Shape,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let env = env(&regfile.csrs, cfg.sew, false)?;
+        muladd(instruction, regfile, cfg, env, op, shape)
+    }
+
+    #[inline(always)]
+    fn vfmuladd(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, u64, &mut VectorEnv) -> u64,
+        shape: Shape,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        check_float(cfg.sew, extensions)?;
+        if shape != Shape::Single {
+            check_float(2 * cfg.sew, extensions)?;
+        }
+        let env = env(&regfile.csrs, cfg.sew, true)?;
+        muladd(instruction, regfile, cfg, env, op, shape)
+    }
+
+    #[inline(always)]
+    fn vcompare(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let env = env(&regfile.csrs, cfg.sew, false)?;
+        compare(instruction, regfile, cfg, env, op)
+    }
+
+    #[inline(always)]
+    fn vfcompare(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        check_float(cfg.sew, extensions)?;
+        let env = env(&regfile.csrs, cfg.sew, true)?;
+        compare(instruction, regfile, cfg, env, op)
+    }
+
+    fn vcarry(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, u64, &mut VectorEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let mut env = env(&regfile.csrs, cfg.sew, false)?;
+        let (sew, lmul) = (cfg.sew, cfg.lmul);
+        let vd = group(instruction.vd, lmul)?;
+        let vs2 = group(instruction.vs2, lmul)?;
+        // the carries are read from v0 like a mask
+        check_mask_overlap(false, vd)?;
+        let op1 = operand(&instruction, regfile, sew, true);
+        check_operand(op1, &instruction, (lmul, sew), (vd, lmul, sew))?;
+        let vregs = &mut regfile.vregs;
+        for i in cfg.vstart..cfg.vl {
+            let carry = vregs.mask(0, i) as u64;
+            let value = op(
+                vregs.get(vs2, i, sew),
+                op1.get(vregs, i, sew),
+                carry,
+                &mut env,
+            );
+            vregs.set(vd, i, sew, value);
+        }
+        finish(&mut regfile.csrs, &env);
+        Ok(())
+    }
+
+    fn vcarry_out(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, u64, &mut VectorEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let mut env = env(&regfile.csrs, cfg.sew, false)?;
+        let (sew, lmul) = (cfg.sew, cfg.lmul);
+        let vd = instruction.vd.as_u8() as usize;
+        let vs2 = group(instruction.vs2, lmul)?;
+        let dst = (vd, 0, 1);
+        check_overlap(dst, (vs2, lmul, sew))?;
+        let op1 = operand(&instruction, regfile, sew, true);
+        check_operand(op1, &instruction, (lmul, sew), dst)?;
+        let vregs = &mut regfile.vregs;
+        for i in cfg.vstart..cfg.vl {
+            let carry = !instruction.vm && vregs.mask(0, i);
+            let value = op(
+                vregs.get(vs2, i, sew),
+                op1.get(vregs, i, sew),
+                carry as u64,
+                &mut env,
+            );
+            vregs.set_mask(vd, i, value != 0);
+        }
+        finish(&mut regfile.csrs, &env);
+        Ok(())
+    }
+
+    fn vmerge(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let (sew, lmul) = (cfg.sew, cfg.lmul);
+        let vd = group(instruction.vd, lmul)?;
+        let vs2 = group(instruction.vs2, lmul)?;
+        check_mask_overlap(instruction.vm, vd)?;
+        let op1 = operand(&instruction, regfile, sew, true);
+        check_operand(op1, &instruction, (lmul, sew), (vd, lmul, sew))?;
+        let vregs = &mut regfile.vregs;
+        for i in cfg.vstart..cfg.vl {
+            let value = if active(instruction.vm, vregs, i) {
+                op1.get(vregs, i, sew)
+            } else {
+                vregs.get(vs2, i, sew)
+            };
+            vregs.set(vd, i, sew, value);
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn vfmerge(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        check_float(cfg.sew, extensions)?;
+        Self::vmerge(instruction, regfile)
+    }
+
+    fn vextend(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, &mut VectorEnv) -> u64,
+        factor: u32,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let env = env(&regfile.csrs, cfg.sew, false)?;
+        let src_eew = cfg.sew / factor;
+        if src_eew < 8 {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        let src_emul = cfg.lmul - log2(factor);
+        unary(
+            instruction,
+            regfile,
+            cfg,
+            env,
+            op,
+            (cfg.lmul, cfg.sew),
+            (src_emul, src_eew),
+        )
+    }
+
+    fn vfunary(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, &mut VectorEnv) -> u64,
+        shape: Shape,
+        conversion: Conversion,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let (sew, lmul) = (cfg.sew, cfg.lmul);
+        let (dst, src) = match shape {
+            Shape::Widen => ((lmul + 1, 2 * sew), (lmul, sew)),
+            Shape::Narrow => ((lmul, sew), (lmul + 1, 2 * sew)),
+            _ => ((lmul, sew), (lmul, sew)),
+        };
+        if dst.1 > regfile.vregs.elen() || src.1 > regfile.vregs.elen() {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        if conversion != Conversion::FromInt {
+            check_float(src.1, extensions)?;
+        }
+        if conversion != Conversion::ToInt {
+            check_float(dst.1, extensions)?;
+        }
+        let env = env(&regfile.csrs, sew, true)?;
+        unary(instruction, regfile, cfg, env, op, dst, src)
+    }
+
+    #[inline(always)]
+    fn vreduce(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+        widen: bool,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let env = env(&regfile.csrs, cfg.sew, false)?;
+        reduce(instruction, regfile, cfg, env, op, widen)
+    }
+
+    #[inline(always)]
+    fn vfreduce(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+        widen: bool,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        check_float(cfg.sew, extensions)?;
+        if widen {
+            check_float(2 * cfg.sew, extensions)?;
+        }
+        let env = env(&regfile.csrs, cfg.sew, true)?;
+        reduce(instruction, regfile, cfg, env, op, widen)
+    }
+
+    fn vmask(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        op: fn(u64, u64, &mut VectorEnv) -> u64,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let mut env = env(&regfile.csrs, cfg.sew, false)?;
+        let vd = instruction.vd.as_u8() as usize;
+        let (vs2, vs1) = (
+            instruction.vs2.as_u8() as usize,
+            instruction.vs1.as_u8() as usize,
+        );
+        let vregs = &mut regfile.vregs;
+        for i in cfg.vstart..cfg.vl {
+            let a = vregs.mask(vs2, i) as u64;
+            let b = vregs.mask(vs1, i) as u64;
+            vregs.set_mask(vd, i, op(a, b, &mut env) & 1 != 0);
+        }
+        finish(&mut regfile.csrs, &env);
+        Ok(())
+    }
+
+    fn vcpop(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        if cfg.vstart != 0 {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        let vs2 = instruction.vs2.as_u8() as usize;
+        let vregs = &regfile.vregs;
+        let count = (0..cfg.vl)
+            .filter(|&i| active(instruction.vm, vregs, i) && vregs.mask(vs2, i))
+            .count();
+        set_xreg(regfile, instruction.vd, count as u64);
+        Ok(())
+    }
+
+    fn vfirst(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        if cfg.vstart != 0 {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        let vs2 = instruction.vs2.as_u8() as usize;
+        let vregs = &regfile.vregs;
+        let first = (0..cfg.vl)
+            .find(|&i| active(instruction.vm, vregs, i) && vregs.mask(vs2, i))
+            .map_or(u64::MAX, |i| i as u64);
+        set_xreg(regfile, instruction.vd, first);
+        Ok(())
+    }
+
+    fn vmset(instruction: V, regfile: &mut RegFile<Self>, set: SetFirst) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        if cfg.vstart != 0 {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        let vd = instruction.vd.as_u8() as usize;
+        let vs2 = instruction.vs2.as_u8() as usize;
+        if vd == vs2 {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        check_mask_overlap(instruction.vm, vd)?;
+        let vregs = &mut regfile.vregs;
+        let mut found = false;
+        for i in 0..cfg.vl {
+            if active(instruction.vm, vregs, i) {
+                let first = !found && vregs.mask(vs2, i);
+                let value = match set {
+                    SetFirst::Before => !found && !first,
+                    SetFirst::Including => !found,
+                    SetFirst::Only => first,
+                };
+                found |= first;
+                vregs.set_mask(vd, i, value);
+            }
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn viota(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        if cfg.vstart != 0 {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        let vd = group(instruction.vd, cfg.lmul)?;
+        let vs2 = instruction.vs2.as_u8() as usize;
+        check_disjoint((vd, cfg.lmul), (vs2, 0))?;
+        check_mask_overlap(instruction.vm, vd)?;
+        let vregs = &mut regfile.vregs;
+        let mut count = 0;
+        for i in 0..cfg.vl {
+            if active(instruction.vm, vregs, i) {
+                vregs.set(vd, i, cfg.sew, count);
+                count += vregs.mask(vs2, i) as u64;
+            }
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn vid(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let vd = group(instruction.vd, cfg.lmul)?;
+        check_mask_overlap(instruction.vm, vd)?;
+        let vregs = &mut regfile.vregs;
+        for i in cfg.vstart..cfg.vl {
+            if active(instruction.vm, vregs, i) {
+                vregs.set(vd, i, cfg.sew, i as u64);
+            }
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn vmv_x_s(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let value = regfile
+            .vregs
+            .get(instruction.vs2.as_u8() as usize, 0, cfg.sew);
+        let shift = 64 - cfg.sew;
+        set_xreg(
+            regfile,
+            instruction.vd,
+            (((value << shift) as i64) >> shift) as u64,
+        );
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn vmv_s_x(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let value = xreg(regfile, instruction.vs1);
+        move_to_element(regfile, instruction.vd, cfg, value);
+        Ok(())
+    }
+
+    fn vfmv_f_s(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        check_float(cfg.sew, extensions)?;
+        let value = regfile
+            .vregs
+            .get(instruction.vs2.as_u8() as usize, 0, cfg.sew);
+        match cfg.sew {
+            32 => regfile.fregs.set_s(instruction.vd.into(), value as u32),
+            _ => regfile.fregs.set(instruction.vd.into(), value),
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn vfmv_s_f(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        check_float(cfg.sew, extensions)?;
+        let value = freg(regfile, instruction.vs1, cfg.sew);
+        move_to_element(regfile, instruction.vd, cfg, value);
+        Ok(())
+    }
+
+    fn vslideup(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let (sew, lmul) = (cfg.sew, cfg.lmul);
+        let vd = group(instruction.vd, lmul)?;
+        let vs2 = group(instruction.vs2, lmul)?;
+        check_disjoint((vd, lmul), (vs2, lmul))?;
+        check_mask_overlap(instruction.vm, vd)?;
+        let offset = match operand(&instruction, regfile, 64, false) {
+            Operand::Scalar(_) if instruction.funct3 == OPIVX => {
+                xreg_unsigned(regfile, instruction.vs1)
+            }
+            Operand::Scalar(offset) => offset,
+            Operand::Vector(_) => return Err(ILLEGAL_INSTRUCTION),
+        };
+        let vregs = &mut regfile.vregs;
+        let start = (cfg.vstart as u64).max(offset);
+        for i in start..cfg.vl as u64 {
+            let i = i as usize;
+            if active(instruction.vm, vregs, i) {
+                let value = vregs.get(vs2, i - offset as usize, sew);
+                vregs.set(vd, i, sew, value);
+            }
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn vslidedown(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let (sew, lmul) = (cfg.sew, cfg.lmul);
+        let vd = group(instruction.vd, lmul)?;
+        let vs2 = group(instruction.vs2, lmul)?;
+        check_mask_overlap(instruction.vm, vd)?;
+        let offset = match instruction.funct3 {
+            OPIVX => xreg_unsigned(regfile, instruction.vs1),
+            _ => instruction.vs1.as_u8() as u64,
+        };
+        let vregs = &mut regfile.vregs;
+        for i in cfg.vstart..cfg.vl {
+            if active(instruction.vm, vregs, i) {
+                let value = match (i as u64).checked_add(offset) {
+                    Some(src) if src < cfg.vlmax as u64 => vregs.get(vs2, src as usize, sew),
+                    _ => 0,
+                };
+                vregs.set(vd, i, sew, value);
+            }
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn vslide1up(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let (sew, lmul) = (cfg.sew, cfg.lmul);
+        let vd = group(instruction.vd, lmul)?;
+        let vs2 = group(instruction.vs2, lmul)?;
+        check_disjoint((vd, lmul), (vs2, lmul))?;
+        check_mask_overlap(instruction.vm, vd)?;
+        let scalar = slide1_scalar(&instruction, regfile, extensions, sew)?;
+        let vregs = &mut regfile.vregs;
+        for i in cfg.vstart..cfg.vl {
+            if active(instruction.vm, vregs, i) {
+                let value = match i {
+                    0 => scalar,
+                    i => vregs.get(vs2, i - 1, sew),
+                };
+                vregs.set(vd, i, sew, value);
+            }
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn vslide1down(
+        instruction: V,
+        regfile: &mut RegFile<Self>,
+        extensions: &Extensions,
+    ) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let (sew, lmul) = (cfg.sew, cfg.lmul);
+        let vd = group(instruction.vd, lmul)?;
+        let vs2 = group(instruction.vs2, lmul)?;
+        check_mask_overlap(instruction.vm, vd)?;
+        let scalar = slide1_scalar(&instruction, regfile, extensions, sew)?;
+        let vregs = &mut regfile.vregs;
+        for i in cfg.vstart..cfg.vl {
+            if active(instruction.vm, vregs, i) {
+                let value = if i + 1 == cfg.vl {
+                    scalar
+                } else {
+                    vregs.get(vs2, i + 1, sew)
+                };
+                vregs.set(vd, i, sew, value);
+            }
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn vrgather(instruction: V, regfile: &mut RegFile<Self>, ei16: bool) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        let (sew, lmul) = (cfg.sew, cfg.lmul);
+        let vd = group(instruction.vd, lmul)?;
+        let vs2 = group(instruction.vs2, lmul)?;
+        check_disjoint((vd, lmul), (vs2, lmul))?;
+        check_mask_overlap(instruction.vm, vd)?;
+        let (index_emul, index_eew) = if ei16 {
+            (log2(16) - log2(sew) + lmul, 16)
+        } else {
+            (lmul, sew)
+        };
+        let index = match instruction.funct3 {
+            OPIVV => {
+                let vs1 = group(instruction.vs1, index_emul)?;
+                check_disjoint((vd, lmul), (vs1, index_emul))?;
+                Operand::Vector(vs1)
+            }
+            OPIVX => Operand::Scalar(xreg_unsigned(regfile, instruction.vs1)),
+            _ => Operand::Scalar(instruction.vs1.as_u8() as u64),
+        };
+        let vregs = &mut regfile.vregs;
+        for i in cfg.vstart..cfg.vl {
+            if active(instruction.vm, vregs, i) {
+                let index = index.get(vregs, i, index_eew);
+                let value = if index < cfg.vlmax as u64 {
+                    vregs.get(vs2, index as usize, sew)
+                } else {
+                    0
+                };
+                vregs.set(vd, i, sew, value);
+            }
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn vcompress(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let cfg = vconfig(regfile)?;
+        if cfg.vstart != 0 {
+            return Err(ILLEGAL_INSTRUCTION);
+        }
+        let (sew, lmul) = (cfg.sew, cfg.lmul);
+        let vd = group(instruction.vd, lmul)?;
+        let vs2 = group(instruction.vs2, lmul)?;
+        let vs1 = instruction.vs1.as_u8() as usize;
+        check_disjoint((vd, lmul), (vs2, lmul))?;
+        check_disjoint((vd, lmul), (vs1, 0))?;
+        let vregs = &mut regfile.vregs;
+        let mut packed = 0;
+        for i in 0..cfg.vl {
+            if vregs.mask(vs1, i) {
+                let value = vregs.get(vs2, i, sew);
+                vregs.set(vd, packed, sew, value);
+                packed += 1;
+            }
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+
+    fn vmvnr(instruction: V, regfile: &mut RegFile<Self>) -> Result<(), Exception> {
+        let nreg = instruction.vs1.as_u8() as u32 + 1;
+        let vd = group(instruction.vd, log2(nreg))?;
+        let vs2 = group(instruction.vs2, log2(nreg))?;
+        // vstart counts SEW wide elements, bytes when vtype is not valid
+        let eew = vconfig(regfile).map_or(8, |cfg| cfg.sew);
+        let vstart: u64 = regfile.csrs.get(VSTART).r#as();
+        let evl = nreg as usize * regfile.vregs.vlenb() * 8 / eew as usize;
+        let vregs = &mut regfile.vregs;
+        for i in vstart as usize..evl {
+            let value = vregs.get(vs2, i, eew);
+            vregs.set(vd, i, eew, value);
+        }
+        regfile.csrs.set(VSTART, 0u8.r#as());
+        Ok(())
+    }
+}